@@ -0,0 +1,24 @@
+-- 库存与库存流水
+-- stock_mode: mysql = 行锁扣减（默认）；redis = 秒杀模式，Redis 计数器扣减后异步回写
+CREATE TABLE IF NOT EXISTS inventory (
+    sku_id          BIGINT UNSIGNED NOT NULL PRIMARY KEY,
+    stock           BIGINT          NOT NULL DEFAULT 0,
+    stock_mode      VARCHAR(16)     NOT NULL DEFAULT 'mysql',
+    per_user_limit  INT UNSIGNED    NULL,
+    created_at      DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at      DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+-- 只追加的库存流水；(sku_id, order_ref, kind) 唯一，保证回写幂等
+CREATE TABLE IF NOT EXISTS inventory_ledger (
+    id          BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    sku_id      BIGINT UNSIGNED NOT NULL,
+    order_ref   VARCHAR(64)     NOT NULL,
+    user_id     INT UNSIGNED    NOT NULL DEFAULT 0,
+    kind        VARCHAR(16)     NOT NULL,
+    delta       BIGINT          NOT NULL,
+    source      VARCHAR(16)     NOT NULL,
+    created_at  DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE KEY uk_sku_ref_kind (sku_id, order_ref, kind),
+    KEY idx_order_ref (order_ref)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
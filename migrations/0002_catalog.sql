@@ -0,0 +1,21 @@
+-- 商品与 SKU；金额统一以「分」为单位存储
+CREATE TABLE IF NOT EXISTS products (
+    id           BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    name         VARCHAR(128)    NOT NULL,
+    category_id  BIGINT UNSIGNED NOT NULL DEFAULT 0,
+    status       VARCHAR(16)     NOT NULL DEFAULT 'on_sale',
+    created_at   DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at   DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    KEY idx_category (category_id)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE IF NOT EXISTS skus (
+    id          BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    product_id  BIGINT UNSIGNED NOT NULL,
+    title       VARCHAR(128)    NOT NULL,
+    price       BIGINT          NOT NULL,
+    status      VARCHAR(16)     NOT NULL DEFAULT 'on_sale',
+    created_at  DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at  DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    KEY idx_product (product_id)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,11 @@
+-- 登录用户的持久化购物车；游客购物车保存在 Session 中
+CREATE TABLE IF NOT EXISTS cart_items (
+    id          BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    user_id     INT UNSIGNED    NOT NULL,
+    sku_id      BIGINT UNSIGNED NOT NULL,
+    quantity    INT UNSIGNED    NOT NULL,
+    selected    TINYINT(1)      NOT NULL DEFAULT 1,
+    created_at  DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at  DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    UNIQUE KEY uk_user_sku (user_id, sku_id)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,52 @@
+-- 收货地址
+CREATE TABLE IF NOT EXISTS user_addresses (
+    id             BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    user_id        INT UNSIGNED    NOT NULL,
+    receiver_name  VARCHAR(64)     NOT NULL,
+    phone          VARCHAR(32)     NOT NULL,
+    province       VARCHAR(32)     NOT NULL,
+    city           VARCHAR(32)     NOT NULL,
+    district       VARCHAR(32)     NOT NULL,
+    detail         VARCHAR(255)    NOT NULL,
+    created_at     DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at     DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    KEY idx_user (user_id)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+-- 订单；金额单位：分，地址为下单时快照
+CREATE TABLE IF NOT EXISTS orders (
+    id               BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    order_no         VARCHAR(32)     NOT NULL,
+    user_id          INT UNSIGNED    NOT NULL,
+    status           VARCHAR(32)     NOT NULL,
+    goods_amount     BIGINT          NOT NULL,
+    discount_amount  BIGINT          NOT NULL,
+    shipping_fee     BIGINT          NOT NULL,
+    payable_amount   BIGINT          NOT NULL,
+    receiver_name    VARCHAR(64)     NOT NULL,
+    receiver_phone   VARCHAR(32)     NOT NULL,
+    province         VARCHAR(32)     NOT NULL,
+    city             VARCHAR(32)     NOT NULL,
+    district         VARCHAR(32)     NOT NULL,
+    address_detail   VARCHAR(255)    NOT NULL,
+    created_at       DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at       DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    UNIQUE KEY uk_order_no (order_no),
+    KEY idx_user_created (user_id, created_at)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+-- 订单行；商品名称和价格为下单时快照
+CREATE TABLE IF NOT EXISTS order_items (
+    id               BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    order_id         BIGINT UNSIGNED NOT NULL,
+    sku_id           BIGINT UNSIGNED NOT NULL,
+    product_id       BIGINT UNSIGNED NOT NULL,
+    product_name     VARCHAR(128)    NOT NULL,
+    sku_title        VARCHAR(128)    NOT NULL,
+    unit_price       BIGINT          NOT NULL,
+    quantity         INT UNSIGNED    NOT NULL,
+    line_total       BIGINT          NOT NULL,
+    discount_amount  BIGINT          NOT NULL,
+    payable_amount   BIGINT          NOT NULL,
+    KEY idx_order (order_id)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
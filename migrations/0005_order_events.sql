@@ -0,0 +1,12 @@
+-- 订单状态流转审计，只追加
+CREATE TABLE IF NOT EXISTS order_events (
+    id           BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    order_id     BIGINT UNSIGNED NOT NULL,
+    from_status  VARCHAR(32)     NULL,
+    to_status    VARCHAR(32)     NOT NULL,
+    actor_type   VARCHAR(16)     NOT NULL,
+    actor_id     INT UNSIGNED    NOT NULL DEFAULT 0,
+    reason       VARCHAR(255)    NOT NULL DEFAULT '',
+    created_at   DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    KEY idx_order (order_id)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,17 @@
+-- 支付单；一个订单对应一条支付记录，商户订单号即订单号，金额单位：分
+CREATE TABLE IF NOT EXISTS payments (
+    id              BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    order_id        BIGINT UNSIGNED NOT NULL,
+    user_id         INT UNSIGNED    NOT NULL,
+    channel         VARCHAR(32)     NOT NULL,
+    out_trade_no    VARCHAR(32)     NOT NULL,
+    amount          BIGINT          NOT NULL,
+    status          VARCHAR(16)     NOT NULL,
+    prepay_id       VARCHAR(64)     NULL,
+    transaction_id  VARCHAR(32)     NULL,
+    paid_at         DATETIME        NULL,
+    created_at      DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at      DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    UNIQUE KEY uk_order (order_id),
+    UNIQUE KEY uk_out_trade_no (out_trade_no)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
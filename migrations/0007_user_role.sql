@@ -0,0 +1,2 @@
+-- 用户角色：user 普通用户，admin 后台管理员
+ALTER TABLE t_user ADD COLUMN role VARCHAR(16) NOT NULL DEFAULT 'user';
@@ -0,0 +1,24 @@
+-- 退款单；order_item_id 为空表示整单退款，金额单位：分
+CREATE TABLE IF NOT EXISTS refunds (
+    id                BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    refund_no         VARCHAR(32)     NOT NULL,
+    order_id          BIGINT UNSIGNED NOT NULL,
+    order_item_id     BIGINT UNSIGNED NULL,
+    user_id           INT UNSIGNED    NOT NULL,
+    amount            BIGINT          NOT NULL,
+    reason            VARCHAR(255)    NOT NULL DEFAULT '',
+    status            VARCHAR(16)     NOT NULL,
+    -- 申请时订单所处状态，退款结束后订单回到该状态
+    order_status      VARCHAR(32)     NOT NULL,
+    -- 已发货订单是否在退款成功后回补库存（由审核人确认退货入库）
+    restock           TINYINT(1)      NOT NULL DEFAULT 0,
+    admin_id          INT UNSIGNED    NULL,
+    reject_reason     VARCHAR(255)    NULL,
+    channel_refund_id VARCHAR(64)     NULL,
+    refunded_at       DATETIME        NULL,
+    created_at        DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at        DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    UNIQUE KEY uk_refund_no (refund_no),
+    KEY idx_order (order_id),
+    KEY idx_status_created (status, created_at)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
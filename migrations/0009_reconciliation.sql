@@ -0,0 +1,28 @@
+-- 微信支付交易账单对账结果；同一账单日重复对账时覆盖
+CREATE TABLE IF NOT EXISTS reconciliation_runs (
+    id                BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    bill_date         DATE            NOT NULL,
+    -- 账单中的支付、退款记录数
+    remote_count      INT UNSIGNED    NOT NULL DEFAULT 0,
+    -- 本地当天成功的支付、退款记录数
+    local_count       INT UNSIGNED    NOT NULL DEFAULT 0,
+    discrepancy_count INT UNSIGNED    NOT NULL DEFAULT 0,
+    created_at        DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at        DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    UNIQUE KEY uk_bill_date (bill_date)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+-- 对账差异明细；category 为 payment / refund，金额单位：分，缺失一侧为 NULL
+CREATE TABLE IF NOT EXISTS reconciliation_discrepancies (
+    id            BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    run_id        BIGINT UNSIGNED NOT NULL,
+    category      VARCHAR(16)     NOT NULL,
+    -- missing_local / missing_remote / amount_mismatch
+    kind          VARCHAR(32)     NOT NULL,
+    -- 商户订单号或商户退款单号
+    reference_no  VARCHAR(64)     NOT NULL,
+    local_amount  BIGINT          NULL,
+    remote_amount BIGINT          NULL,
+    created_at    DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    KEY idx_run (run_id)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
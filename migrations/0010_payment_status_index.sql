@@ -0,0 +1,2 @@
+-- 主动查单按状态和最后发起支付时间扫描待支付单
+ALTER TABLE payments ADD KEY idx_status_updated (status, updated_at);
@@ -0,0 +1,47 @@
+-- 优惠券模板；金额单位：分
+CREATE TABLE IF NOT EXISTS coupon_templates (
+    id             BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    name           VARCHAR(64)     NOT NULL,
+    -- fixed 立减 / percent 折扣 / threshold 满减 / free_shipping 免运费
+    kind           VARCHAR(16)     NOT NULL,
+    -- 适用商品应付满该金额才能使用，0 表示无门槛
+    threshold      BIGINT          NOT NULL DEFAULT 0,
+    -- fixed / threshold 的减免金额
+    amount         BIGINT          NOT NULL DEFAULT 0,
+    -- percent 的折扣比例，15 表示减 15%
+    percent_off    INT UNSIGNED    NOT NULL DEFAULT 0,
+    -- percent 的减免上限，0 表示不封顶
+    max_discount   BIGINT          NOT NULL DEFAULT 0,
+    -- all / category / product
+    scope          VARCHAR(16)     NOT NULL DEFAULT 'all',
+    -- 适用的分类或商品 ID 列表
+    scope_ids      JSON            NOT NULL,
+    total_quota    INT UNSIGNED    NOT NULL,
+    issued         INT UNSIGNED    NOT NULL DEFAULT 0,
+    per_user_limit INT UNSIGNED    NOT NULL DEFAULT 1,
+    -- 领取时间窗口；valid_days 为 0 时券在 valid_to 过期，否则领取后 valid_days 天过期
+    valid_from     DATETIME        NOT NULL,
+    valid_to       DATETIME        NOT NULL,
+    valid_days     INT UNSIGNED    NOT NULL DEFAULT 0,
+    status         VARCHAR(16)     NOT NULL DEFAULT 'active',
+    created_at     DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at     DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    KEY idx_status_valid (status, valid_to)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+-- 用户领取的券；下单时锁定到订单号，订单取消后退回，支付成功后核销
+CREATE TABLE IF NOT EXISTS user_coupons (
+    id           BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    template_id  BIGINT UNSIGNED NOT NULL,
+    user_id      INT UNSIGNED    NOT NULL,
+    -- unused / locked / used
+    status       VARCHAR(16)     NOT NULL,
+    order_no     VARCHAR(32)     NULL,
+    expires_at   DATETIME        NOT NULL,
+    used_at      DATETIME        NULL,
+    created_at   DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at   DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    KEY idx_template_user (template_id, user_id),
+    KEY idx_user_status (user_id, status),
+    KEY idx_order_no (order_no)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
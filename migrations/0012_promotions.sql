@@ -0,0 +1,17 @@
+-- 营销活动；rule 为声明式规则 JSON，结构见 models::promotion::PromotionRule
+CREATE TABLE IF NOT EXISTS promotions (
+    id          BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    name        VARCHAR(64)     NOT NULL,
+    -- 数值越大越先计算
+    priority    INT             NOT NULL DEFAULT 0,
+    -- 独占活动不与其他活动叠加在同一商品行上
+    exclusive   TINYINT(1)      NOT NULL DEFAULT 0,
+    rule        JSON            NOT NULL,
+    starts_at   DATETIME        NOT NULL,
+    ends_at     DATETIME        NOT NULL,
+    -- active / disabled
+    status      VARCHAR(16)     NOT NULL DEFAULT 'active',
+    created_at  DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at  DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    KEY idx_status_ends (status, ends_at)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,49 @@
+-- 拼团活动：指定 SKU 以拼团价售卖；金额单位：分
+CREATE TABLE IF NOT EXISTS group_campaigns (
+    id             BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    name           VARCHAR(64)     NOT NULL,
+    sku_id         BIGINT UNSIGNED NOT NULL,
+    group_price    BIGINT          NOT NULL,
+    -- 成团人数（含团长）
+    group_size     INT UNSIGNED    NOT NULL,
+    -- 开团后多久未成团即失败
+    expire_minutes INT UNSIGNED    NOT NULL,
+    starts_at      DATETIME        NOT NULL,
+    ends_at        DATETIME        NOT NULL,
+    -- active / disabled
+    status         VARCHAR(16)     NOT NULL DEFAULT 'active',
+    created_at     DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at     DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    KEY idx_status_ends (status, ends_at)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+-- 团：expires_at 前已支付成员达到 group_size 即成团，否则失败并退款
+CREATE TABLE IF NOT EXISTS group_buys (
+    id           BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    campaign_id  BIGINT UNSIGNED NOT NULL,
+    leader_id    INT UNSIGNED    NOT NULL,
+    group_size   INT UNSIGNED    NOT NULL,
+    paid_count   INT UNSIGNED    NOT NULL DEFAULT 0,
+    -- forming / succeeded / failed
+    status       VARCHAR(16)     NOT NULL,
+    expires_at   DATETIME        NOT NULL,
+    succeeded_at DATETIME        NULL,
+    created_at   DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at   DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    KEY idx_campaign_status (campaign_id, status)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+-- 团成员，每人对应一笔拼团订单；订单取消后名额释放
+CREATE TABLE IF NOT EXISTS group_members (
+    id         BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    group_id   BIGINT UNSIGNED NOT NULL,
+    user_id    INT UNSIGNED    NOT NULL,
+    is_leader  TINYINT(1)      NOT NULL DEFAULT 0,
+    -- 占位后写入，下单失败时连同成员记录一起删除
+    order_id   BIGINT UNSIGNED NULL,
+    paid_at    DATETIME        NULL,
+    created_at DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    KEY idx_group (group_id),
+    KEY idx_user (user_id),
+    UNIQUE KEY uk_order (order_id)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
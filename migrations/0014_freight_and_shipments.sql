@@ -0,0 +1,31 @@
+-- 运费模板；金额单位：分，重量单位：克
+CREATE TABLE IF NOT EXISTS freight_templates (
+    id             BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    name           VARCHAR(64)     NOT NULL,
+    -- piece 按件 / weight 按重量
+    charge_by      VARCHAR(16)     NOT NULL,
+    -- 计费规则列表：首件(重)、续件(重)及费用；provinces 为空的规则是默认规则
+    rules          JSON            NOT NULL,
+    -- 使用该模板的商品应付满该金额包邮，0 表示不包邮
+    free_threshold BIGINT          NOT NULL DEFAULT 0,
+    created_at     DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at     DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+-- 未设置模板的商品按 [order] 的统一运费计算
+ALTER TABLE products ADD COLUMN freight_template_id BIGINT UNSIGNED NULL AFTER category_id;
+ALTER TABLE skus ADD COLUMN weight_grams INT UNSIGNED NOT NULL DEFAULT 0 AFTER price;
+
+-- 发货记录，一个订单一条，重复发货会更新快递公司和运单号
+CREATE TABLE IF NOT EXISTS shipments (
+    id          BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    order_id    BIGINT UNSIGNED NOT NULL,
+    -- 快递公司编码，与物流查询服务商的编码一致，如 shunfeng / yuantong
+    carrier     VARCHAR(32)     NOT NULL,
+    tracking_no VARCHAR(64)     NOT NULL,
+    admin_id    INT UNSIGNED    NOT NULL,
+    shipped_at  DATETIME        NOT NULL,
+    created_at  DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at  DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    UNIQUE KEY uk_order (order_id)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,44 @@
+-- 售后单：签收后的退货退款 / 换货，每个订单行同时只能有一张进行中的售后单；金额单位：分
+CREATE TABLE IF NOT EXISTS after_sales (
+    id                  BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    after_sale_no       VARCHAR(32)     NOT NULL,
+    order_id            BIGINT UNSIGNED NOT NULL,
+    order_item_id       BIGINT UNSIGNED NOT NULL,
+    user_id             INT UNSIGNED    NOT NULL,
+    -- return / exchange
+    kind                VARCHAR(16)     NOT NULL,
+    reason              VARCHAR(32)     NOT NULL,
+    description         VARCHAR(500)    NOT NULL DEFAULT '',
+    -- 凭证图片 URL 列表
+    photos              JSON            NOT NULL,
+    quantity            INT UNSIGNED    NOT NULL,
+    -- 退货退款的金额，换货为 0
+    refund_amount       BIGINT          NOT NULL DEFAULT 0,
+    -- 换货发出的 SKU
+    exchange_sku_id     BIGINT UNSIGNED NULL,
+    -- requested / approved / buyer_shipped / received / refunded / exchanged / rejected
+    status              VARCHAR(16)     NOT NULL,
+    return_carrier      VARCHAR(32)     NULL,
+    return_tracking_no  VARCHAR(64)     NULL,
+    exchange_carrier    VARCHAR(32)     NULL,
+    exchange_tracking_no VARCHAR(64)    NULL,
+    refund_id           BIGINT UNSIGNED NULL,
+    admin_id            INT UNSIGNED    NULL,
+    reject_reason       VARCHAR(255)    NULL,
+    created_at          DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at          DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    UNIQUE KEY uk_after_sale_no (after_sale_no),
+    KEY idx_order_item (order_item_id),
+    KEY idx_user_created (user_id, created_at),
+    KEY idx_status_created (status, created_at)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+-- 客服备注，仅后台可见
+CREATE TABLE IF NOT EXISTS after_sale_notes (
+    id            BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    after_sale_id BIGINT UNSIGNED NOT NULL,
+    admin_id      INT UNSIGNED    NOT NULL,
+    note          VARCHAR(1000)   NOT NULL,
+    created_at    DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    KEY idx_after_sale (after_sale_id)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
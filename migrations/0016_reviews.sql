@@ -0,0 +1,36 @@
+-- 商品评价：每个订单行一条，可追评一次；审核通过后才对外展示并计入商品评分
+CREATE TABLE IF NOT EXISTS reviews (
+    id                BIGINT UNSIGNED  NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    order_id          BIGINT UNSIGNED  NOT NULL,
+    order_item_id     BIGINT UNSIGNED  NOT NULL,
+    user_id           INT UNSIGNED     NOT NULL,
+    product_id        BIGINT UNSIGNED  NOT NULL,
+    sku_id            BIGINT UNSIGNED  NOT NULL,
+    sku_title         VARCHAR(128)     NOT NULL,
+    -- 1 ~ 5 星
+    rating            TINYINT UNSIGNED NOT NULL,
+    content           VARCHAR(500)     NOT NULL DEFAULT '',
+    -- 图片 URL 列表
+    photos            JSON             NOT NULL,
+    has_photos        TINYINT(1)       NOT NULL DEFAULT 0,
+    -- pending / approved / hidden
+    status            VARCHAR(16)      NOT NULL,
+    follow_up         VARCHAR(500)     NULL,
+    follow_up_photos  JSON             NULL,
+    followed_up_at    DATETIME         NULL,
+    -- 商家回复
+    reply             VARCHAR(500)     NULL,
+    replied_at        DATETIME         NULL,
+    created_at        DATETIME         NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at        DATETIME         NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    UNIQUE KEY uk_order_item (order_item_id),
+    KEY idx_product_status (product_id, status, rating, has_photos),
+    KEY idx_user_created (user_id, created_at),
+    KEY idx_status_created (status, created_at)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+-- 商品上缓存的评分汇总，只统计审核通过的评价，评价状态变化时重新计算
+ALTER TABLE products
+    ADD COLUMN review_count       INT UNSIGNED NOT NULL DEFAULT 0,
+    ADD COLUMN rating_sum         INT UNSIGNED NOT NULL DEFAULT 0,
+    ADD COLUMN photo_review_count INT UNSIGNED NOT NULL DEFAULT 0;
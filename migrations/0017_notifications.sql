@@ -0,0 +1,26 @@
+-- 订阅消息授权：小程序端每次 requestSubscribeMessage 同意一次可发送一条
+CREATE TABLE IF NOT EXISTS notification_subscriptions (
+    id          BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    user_id     INT UNSIGNED    NOT NULL,
+    template_id VARCHAR(64)     NOT NULL,
+    openid      VARCHAR(64)     NOT NULL,
+    -- 剩余可发送次数
+    remaining   INT UNSIGNED    NOT NULL DEFAULT 0,
+    created_at  DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at  DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    UNIQUE KEY uk_user_template (user_id, template_id)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+-- 每次发送尝试的结果：sent / failed / skipped
+CREATE TABLE IF NOT EXISTS notification_deliveries (
+    id          BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    user_id     INT UNSIGNED    NOT NULL,
+    order_id    BIGINT UNSIGNED NOT NULL,
+    event       VARCHAR(32)     NOT NULL,
+    template_id VARCHAR(64)     NOT NULL,
+    status      VARCHAR(16)     NOT NULL,
+    error       VARCHAR(512)    NULL,
+    created_at  DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    KEY idx_order_event (order_id, event),
+    KEY idx_status_created (status, created_at)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
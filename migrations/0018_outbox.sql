@@ -0,0 +1,15 @@
+-- 事务性发件箱：与业务状态变更在同一事务中写入，由转发协程投递给进程内订阅者
+CREATE TABLE IF NOT EXISTS outbox_events (
+    id              BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    event_type      VARCHAR(64)     NOT NULL,
+    aggregate_id    VARCHAR(64)     NOT NULL,
+    payload         TEXT            NOT NULL,
+    -- 已投递次数，超过上限后不再投递，需人工处理
+    attempts        INT UNSIGNED    NOT NULL DEFAULT 0,
+    -- 下次可投递时间；投递中的事件会被推迟到租约结束
+    next_attempt_at DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    published_at    DATETIME        NULL,
+    last_error      VARCHAR(512)    NULL,
+    created_at      DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    KEY idx_pending (published_at, next_attempt_at)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
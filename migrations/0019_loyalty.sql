@@ -0,0 +1,31 @@
+-- 会员：积分余额与等级缓存，首次产生积分流水时创建
+CREATE TABLE IF NOT EXISTS members (
+    user_id        INT UNSIGNED NOT NULL PRIMARY KEY,
+    level          INT UNSIGNED NOT NULL DEFAULT 0,
+    points_balance BIGINT       NOT NULL DEFAULT 0,
+    -- 最近一次计算等级时的滚动消费（分）
+    rolling_spend  BIGINT       NOT NULL DEFAULT 0,
+    created_at     DATETIME     NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at     DATETIME     NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    KEY idx_level (level)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+-- 积分流水，只追加；同一用户同一类型同一业务单号只记一次
+CREATE TABLE IF NOT EXISTS points_ledger (
+    id            BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    user_id       INT UNSIGNED    NOT NULL,
+    -- earn / redeem / release / reverse / expire
+    kind          VARCHAR(16)     NOT NULL,
+    -- 正数为增加，负数为扣减
+    delta         BIGINT          NOT NULL,
+    balance_after BIGINT          NOT NULL,
+    -- 订单号，过期流水为过期日期
+    ref_no        VARCHAR(64)     NOT NULL,
+    -- 增加的积分在该时间后过期
+    expires_at    DATETIME        NULL,
+    note          VARCHAR(255)    NOT NULL DEFAULT '',
+    created_at    DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE KEY uk_user_kind_ref (user_id, kind, ref_no),
+    KEY idx_user (user_id, id),
+    KEY idx_expires (expires_at)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,11 @@
+-- 商品收藏；收藏数缓存在商品上
+CREATE TABLE IF NOT EXISTS favorites (
+    id         BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    user_id    INT UNSIGNED    NOT NULL,
+    product_id BIGINT UNSIGNED NOT NULL,
+    created_at DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE KEY uk_user_product (user_id, product_id),
+    KEY idx_user (user_id, id)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+ALTER TABLE products ADD COLUMN favorite_count INT UNSIGNED NOT NULL DEFAULT 0;
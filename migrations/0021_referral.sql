@@ -0,0 +1,43 @@
+-- 分销推广码，用户首次获取分享链接时生成
+CREATE TABLE IF NOT EXISTS referral_codes (
+    user_id    INT UNSIGNED NOT NULL PRIMARY KEY,
+    code       VARCHAR(16)  NOT NULL,
+    created_at DATETIME     NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE KEY uk_code (code)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+-- 客户归属：首次绑定的推广人在有效期内不会被覆盖，过期后可以重新绑定
+CREATE TABLE IF NOT EXISTS referral_bindings (
+    user_id     INT UNSIGNED NOT NULL PRIMARY KEY,
+    referrer_id INT UNSIGNED NOT NULL,
+    code        VARCHAR(16)  NOT NULL,
+    bound_at    DATETIME     NOT NULL,
+    expires_at  DATETIME     NOT NULL,
+    KEY idx_referrer (referrer_id, expires_at)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+-- 分销佣金：订单支付时按层级记入，售后期结束后可提现，退款时冲回
+CREATE TABLE IF NOT EXISTS commissions (
+    id             BIGINT UNSIGNED  NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    order_id       BIGINT UNSIGNED  NOT NULL,
+    order_no       VARCHAR(32)      NOT NULL,
+    buyer_id       INT UNSIGNED     NOT NULL,
+    beneficiary_id INT UNSIGNED     NOT NULL,
+    -- 1 为直接推广人，2 为推广人的推广人，依此类推
+    level          TINYINT UNSIGNED NOT NULL,
+    -- 计佣金额（分）：商品实付，结算时扣除已退款金额
+    base_amount    BIGINT           NOT NULL,
+    -- 佣金比例，万分比
+    rate_bp        INT UNSIGNED     NOT NULL,
+    amount         BIGINT           NOT NULL,
+    -- pending / withdrawable / reversed
+    status         VARCHAR(16)      NOT NULL,
+    -- 订单完成后确定，到期后转为可提现
+    settle_at      DATETIME         NULL,
+    settled_at     DATETIME         NULL,
+    created_at     DATETIME         NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at     DATETIME         NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    UNIQUE KEY uk_order_level (order_id, level),
+    KEY idx_beneficiary (beneficiary_id, id),
+    KEY idx_status_settle (status, settle_at)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
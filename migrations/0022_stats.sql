@@ -0,0 +1,36 @@
+-- 按日预聚合的经营数据，由每日汇总任务写入；重新汇总某天时整天覆盖
+CREATE TABLE IF NOT EXISTS stats_daily (
+    stat_date        DATE         NOT NULL PRIMARY KEY,
+    -- 当天创建的订单数
+    order_count      INT UNSIGNED NOT NULL DEFAULT 0,
+    -- 当天支付成功的订单数与金额（分）
+    paid_order_count INT UNSIGNED NOT NULL DEFAULT 0,
+    gmv              BIGINT       NOT NULL DEFAULT 0,
+    paying_users     INT UNSIGNED NOT NULL DEFAULT 0,
+    -- 当天退款成功的笔数与金额（分）
+    refund_count     INT UNSIGNED NOT NULL DEFAULT 0,
+    refund_amount    BIGINT       NOT NULL DEFAULT 0,
+    updated_at       DATETIME     NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+-- 每天的支付用户，跨天统计支付用户数时去重
+CREATE TABLE IF NOT EXISTS stats_daily_payers (
+    stat_date DATE         NOT NULL,
+    user_id   INT UNSIGNED NOT NULL,
+    PRIMARY KEY (stat_date, user_id)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+-- 每天每个商品的支付件数与实付金额
+CREATE TABLE IF NOT EXISTS stats_daily_products (
+    stat_date    DATE            NOT NULL,
+    product_id   BIGINT UNSIGNED NOT NULL,
+    product_name VARCHAR(128)    NOT NULL,
+    quantity     BIGINT          NOT NULL,
+    gmv          BIGINT          NOT NULL,
+    PRIMARY KEY (stat_date, product_id)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+-- 汇总任务按时间范围扫描当天的订单、支付与退款
+ALTER TABLE orders ADD KEY idx_created (created_at);
+ALTER TABLE payments ADD KEY idx_status_paid (status, paid_at);
+ALTER TABLE refunds ADD KEY idx_status_refunded (status, refunded_at);
@@ -0,0 +1,22 @@
+-- 后台数据导出任务；文件存放在文件存储中，过期后删除
+CREATE TABLE IF NOT EXISTS export_jobs (
+    id             BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    -- orders / users / inventory
+    kind           VARCHAR(16)     NOT NULL,
+    -- csv / xlsx
+    format         VARCHAR(8)      NOT NULL,
+    filter         JSON            NOT NULL,
+    -- pending / running / succeeded / failed / expired
+    status         VARCHAR(16)     NOT NULL,
+    total_rows     BIGINT UNSIGNED NOT NULL DEFAULT 0,
+    processed_rows BIGINT UNSIGNED NOT NULL DEFAULT 0,
+    blob_key       VARCHAR(255)    NULL,
+    file_size      BIGINT UNSIGNED NULL,
+    error          VARCHAR(512)    NULL,
+    admin_id       INT UNSIGNED    NOT NULL,
+    expires_at     DATETIME        NULL,
+    started_at     DATETIME        NULL,
+    finished_at    DATETIME        NULL,
+    created_at     DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at     DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,28 @@
+-- 商品与 SKU 的业务编码，批量导入时按编码新增或更新
+ALTER TABLE products ADD COLUMN code VARCHAR(64) NULL AFTER id, ADD UNIQUE KEY uk_code (code);
+ALTER TABLE skus ADD COLUMN code VARCHAR(64) NULL AFTER product_id, ADD UNIQUE KEY uk_code (code);
+
+-- 后台商品批量导入任务；上传的文件存放在文件存储的 imports/{id}/ 下，结果文件生成后记录 report_key
+CREATE TABLE IF NOT EXISTS import_jobs (
+    id               BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    -- csv / xlsx
+    format           VARCHAR(8)      NOT NULL,
+    -- 只校验并预览新增 / 更新，不写入
+    dry_run          TINYINT(1)      NOT NULL DEFAULT 0,
+    -- pending / running / succeeded / failed
+    status           VARCHAR(16)     NOT NULL,
+    report_key       VARCHAR(255)    NULL,
+    total_rows       BIGINT UNSIGNED NOT NULL DEFAULT 0,
+    processed_rows   BIGINT UNSIGNED NOT NULL DEFAULT 0,
+    error_rows       BIGINT UNSIGNED NOT NULL DEFAULT 0,
+    created_products BIGINT UNSIGNED NOT NULL DEFAULT 0,
+    updated_products BIGINT UNSIGNED NOT NULL DEFAULT 0,
+    created_skus     BIGINT UNSIGNED NOT NULL DEFAULT 0,
+    updated_skus     BIGINT UNSIGNED NOT NULL DEFAULT 0,
+    error            VARCHAR(512)    NULL,
+    admin_id         INT UNSIGNED    NOT NULL,
+    started_at       DATETIME        NULL,
+    finished_at      DATETIME        NULL,
+    created_at       DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at       DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,29 @@
+-- 后台操作审计日志，只追加
+CREATE TABLE IF NOT EXISTS audit_logs (
+    id            BIGINT UNSIGNED   NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    actor_id      INT UNSIGNED      NOT NULL,
+    -- 直连的对端地址与 X-Forwarded-For 原样记录，后台任务写入的记录为空
+    ip            VARCHAR(45)       NULL,
+    forwarded_for VARCHAR(255)      NULL,
+    session_id    VARCHAR(64)       NULL,
+    -- 接口为「方法 路由模板」，如 POST /admin/refunds/{id}/approve；后台任务为任务名
+    action        VARCHAR(128)      NOT NULL,
+    target_type   VARCHAR(32)       NOT NULL,
+    target_id     VARCHAR(64)       NULL,
+    status_code   SMALLINT UNSIGNED NULL,
+    -- 脱敏后的 JSON 请求体
+    request       JSON              NULL,
+    -- 敏感数据的变更前后对比
+    changes       JSON              NOT NULL,
+    created_at    DATETIME          NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    KEY idx_actor (actor_id, id),
+    KEY idx_target (target_type, target_id, id),
+    KEY idx_action (action, id),
+    KEY idx_created_at (created_at)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+CREATE TRIGGER audit_logs_no_update BEFORE UPDATE ON audit_logs
+    FOR EACH ROW SIGNAL SQLSTATE '45000' SET MESSAGE_TEXT = 'audit_logs is append-only';
+
+CREATE TRIGGER audit_logs_no_delete BEFORE DELETE ON audit_logs
+    FOR EACH ROW SIGNAL SQLSTATE '45000' SET MESSAGE_TEXT = 'audit_logs is append-only';
@@ -0,0 +1,8 @@
+use crate::domain::BoxFuture;
+use crate::models::address::{Address, NewAddress};
+
+pub trait AddressRepo: Send + Sync {
+    fn find(&self, user_id: u32, id: u64) -> BoxFuture<'_, Result<Option<Address>, sqlx::Error>>;
+    fn list(&self, user_id: u32) -> BoxFuture<'_, Result<Vec<Address>, sqlx::Error>>;
+    fn create<'a>(&'a self, user_id: u32, address: &'a NewAddress) -> BoxFuture<'a, Result<u64, sqlx::Error>>;
+}
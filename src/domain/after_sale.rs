@@ -0,0 +1,29 @@
+use crate::domain::BoxFuture;
+use crate::models::after_sale::{AfterSale, AfterSaleNote, AfterSaleStatus, NewAfterSale};
+
+/// 状态变更都是以当前状态为条件的更新，返回是否生效
+pub trait AfterSaleRepo: Send + Sync {
+    fn create<'a>(&'a self, after_sale: &'a NewAfterSale) -> BoxFuture<'a, Result<u64, sqlx::Error>>;
+    fn find(&self, id: u64) -> BoxFuture<'_, Result<Option<AfterSale>, sqlx::Error>>;
+    fn list_by_user(&self, user_id: u32, limit: u32, offset: u32) -> BoxFuture<'_, Result<Vec<AfterSale>, sqlx::Error>>;
+    fn list_by_status<'a>(&'a self, status: &'a str, limit: u32, offset: u32) -> BoxFuture<'a, Result<Vec<AfterSale>, sqlx::Error>>;
+    /// 订单行上进行中的售后单
+    fn find_open_by_item(&self, order_item_id: u64) -> BoxFuture<'_, Result<Option<AfterSale>, sqlx::Error>>;
+    /// 订单行上的全部售后单，按申请先后排序
+    fn list_by_item(&self, order_item_id: u64) -> BoxFuture<'_, Result<Vec<AfterSale>, sqlx::Error>>;
+    /// 订单上等待渠道退款结果的售后单
+    fn list_refunding(&self, order_id: u64) -> BoxFuture<'_, Result<Vec<AfterSale>, sqlx::Error>>;
+    /// `admin_id` 为 `Some` 时同时记录处理人
+    fn set_status(&self, id: u64, from: AfterSaleStatus, to: AfterSaleStatus, admin_id: Option<u32>) -> BoxFuture<'_, Result<bool, sqlx::Error>>;
+    /// approved -> buyer_shipped，记录寄回运单
+    fn ship_back<'a>(&'a self, id: u64, carrier: &'a str, tracking_no: &'a str) -> BoxFuture<'a, Result<bool, sqlx::Error>>;
+    /// requested / received -> rejected
+    fn reject<'a>(&'a self, id: u64, from: AfterSaleStatus, admin_id: u32, reason: &'a str) -> BoxFuture<'a, Result<bool, sqlx::Error>>;
+    fn set_refund(&self, id: u64, refund_id: u64) -> BoxFuture<'_, Result<(), sqlx::Error>>;
+    /// 渠道退款失败：refunding -> received 并清空退款单，之后可以重新发起退款
+    fn reset_refund(&self, id: u64) -> BoxFuture<'_, Result<bool, sqlx::Error>>;
+    /// received -> exchanged，记录换货发出的运单
+    fn exchange<'a>(&'a self, id: u64, admin_id: u32, carrier: &'a str, tracking_no: &'a str) -> BoxFuture<'a, Result<bool, sqlx::Error>>;
+    fn add_note<'a>(&'a self, id: u64, admin_id: u32, note: &'a str) -> BoxFuture<'a, Result<(), sqlx::Error>>;
+    fn list_notes(&self, id: u64) -> BoxFuture<'_, Result<Vec<AfterSaleNote>, sqlx::Error>>;
+}
@@ -0,0 +1,8 @@
+use crate::domain::BoxFuture;
+use crate::models::audit::{AuditLog, AuditQuery, NewAuditLog};
+
+/// 审计日志只追加，不提供修改和删除
+pub trait AuditRepo: Send + Sync {
+    fn insert<'a>(&'a self, log: &'a NewAuditLog) -> BoxFuture<'a, Result<u64, sqlx::Error>>;
+    fn search<'a>(&'a self, query: &'a AuditQuery, limit: u32, offset: u32) -> BoxFuture<'a, Result<Vec<AuditLog>, sqlx::Error>>;
+}
@@ -0,0 +1,24 @@
+use crate::domain::BoxFuture;
+use std::io;
+use std::path::Path;
+use std::pin::Pin;
+use tokio::io::AsyncRead;
+
+/// 打开待读取的文件，内容按块读取，不整个载入内存
+pub struct BlobFile {
+    pub reader: Pin<Box<dyn AsyncRead + Send>>,
+    /// 文件大小（字节）
+    pub size: u64,
+}
+
+/// 文件存储，按 key 存取；本地实现把 key 作为根目录下的相对路径
+pub trait BlobStore: Send + Sync {
+    fn put<'a>(&'a self, key: &'a str, bytes: &'a [u8]) -> BoxFuture<'a, Result<(), io::Error>>;
+    /// 上传本地文件，返回文件大小（字节）
+    fn put_file<'a>(&'a self, key: &'a str, path: &'a Path) -> BoxFuture<'a, Result<u64, io::Error>>;
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Vec<u8>, io::Error>>;
+    /// 打开文件用于流式下载
+    fn open<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<BlobFile, io::Error>>;
+    /// 删除文件，不存在时不报错
+    fn delete<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<(), io::Error>>;
+}
@@ -0,0 +1,14 @@
+use crate::domain::BoxFuture;
+use crate::models::cart::CartLine;
+
+pub trait CartRepo: Send + Sync {
+    fn list(&self, user_id: u32) -> BoxFuture<'_, Result<Vec<CartLine>, sqlx::Error>>;
+    /// 不存在则新增，存在则累加数量（不超过 `max_quantity`）
+    fn add(&self, user_id: u32, sku_id: u64, quantity: u32, max_quantity: u32) -> BoxFuture<'_, Result<(), sqlx::Error>>;
+    /// 返回是否找到对应行
+    fn set_quantity(&self, user_id: u32, sku_id: u64, quantity: u32) -> BoxFuture<'_, Result<bool, sqlx::Error>>;
+    fn remove<'a>(&'a self, user_id: u32, sku_ids: &'a [u64]) -> BoxFuture<'a, Result<(), sqlx::Error>>;
+    fn set_selected<'a>(&'a self, user_id: u32, sku_ids: &'a [u64], selected: bool) -> BoxFuture<'a, Result<(), sqlx::Error>>;
+    /// 在一个事务里把游客购物车并入用户购物车
+    fn merge<'a>(&'a self, user_id: u32, lines: &'a [CartLine], max_quantity: u32) -> BoxFuture<'a, Result<(), sqlx::Error>>;
+}
@@ -0,0 +1,9 @@
+use crate::domain::BoxFuture;
+use crate::models::catalog::{ProductSummary, SkuDetail};
+
+pub trait CatalogRepo: Send + Sync {
+    /// 批量读取 SKU，不存在的 ID 直接忽略
+    fn find_skus<'a>(&'a self, sku_ids: &'a [u64]) -> BoxFuture<'a, Result<Vec<SkuDetail>, sqlx::Error>>;
+    /// 批量读取商品概要，不存在的 ID 直接忽略
+    fn find_products<'a>(&'a self, product_ids: &'a [u64]) -> BoxFuture<'a, Result<Vec<ProductSummary>, sqlx::Error>>;
+}
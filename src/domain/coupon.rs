@@ -0,0 +1,23 @@
+use crate::domain::BoxFuture;
+use crate::models::coupon::{ClaimOutcome, CouponTemplate, NewCouponTemplate, UserCoupon};
+use chrono::{DateTime, Local};
+
+pub trait CouponRepo: Send + Sync {
+    fn create_template<'a>(&'a self, template: &'a NewCouponTemplate) -> BoxFuture<'a, Result<u64, sqlx::Error>>;
+    fn find_template(&self, id: u64) -> BoxFuture<'_, Result<Option<CouponTemplate>, sqlx::Error>>;
+    fn find_templates<'a>(&'a self, ids: &'a [u64]) -> BoxFuture<'a, Result<Vec<CouponTemplate>, sqlx::Error>>;
+    fn list_templates(&self, limit: u32, offset: u32) -> BoxFuture<'_, Result<Vec<CouponTemplate>, sqlx::Error>>;
+    /// 启用且处在领取时间窗口内、尚未领完的模板
+    fn list_claimable(&self, now: DateTime<Local>) -> BoxFuture<'_, Result<Vec<CouponTemplate>, sqlx::Error>>;
+    fn set_template_status<'a>(&'a self, id: u64, status: &'a str) -> BoxFuture<'a, Result<bool, sqlx::Error>>;
+    /// 在一个事务中扣减总量并校验每人限领，并发领取不会超发
+    fn claim(&self, template_id: u64, user_id: u32, now: DateTime<Local>) -> BoxFuture<'_, Result<ClaimOutcome, sqlx::Error>>;
+    fn find_user_coupon(&self, id: u64) -> BoxFuture<'_, Result<Option<UserCoupon>, sqlx::Error>>;
+    fn list_user_coupons(&self, user_id: u32) -> BoxFuture<'_, Result<Vec<UserCoupon>, sqlx::Error>>;
+    /// unused -> locked，仅当券属于该用户且未过期；返回是否更新
+    fn lock<'a>(&'a self, id: u64, user_id: u32, order_no: &'a str, now: DateTime<Local>) -> BoxFuture<'a, Result<bool, sqlx::Error>>;
+    /// 订单锁定的券 locked -> unused
+    fn release<'a>(&'a self, order_no: &'a str) -> BoxFuture<'a, Result<bool, sqlx::Error>>;
+    /// 订单锁定的券 locked -> used
+    fn consume<'a>(&'a self, order_no: &'a str, used_at: DateTime<Local>) -> BoxFuture<'a, Result<bool, sqlx::Error>>;
+}
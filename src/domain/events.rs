@@ -0,0 +1,14 @@
+use chrono::{DateTime, Local};
+use crate::domain::BoxFuture;
+use crate::models::event::OutboxEvent;
+
+/// 发件箱读写；写入由各业务仓储在自己的事务中通过 `repos::outbox::append` 完成
+pub trait OutboxRepo: Send + Sync {
+    /// 到期未投递且未超过重试上限的事件，按写入顺序返回
+    fn fetch_due(&self, now: DateTime<Local>, max_attempts: u32, limit: u32) -> BoxFuture<'_, Result<Vec<OutboxEvent>, sqlx::Error>>;
+    /// 以 `attempts` 做乐观锁领取事件并推迟到 `lease_until`；返回是否领取成功
+    fn claim(&self, id: u64, attempts: u32, lease_until: DateTime<Local>) -> BoxFuture<'_, Result<bool, sqlx::Error>>;
+    fn mark_published(&self, id: u64, at: DateTime<Local>) -> BoxFuture<'_, Result<(), sqlx::Error>>;
+    /// 记录失败原因，`next_attempt_at` 后重试
+    fn mark_failed<'a>(&'a self, id: u64, error: &'a str, next_attempt_at: DateTime<Local>) -> BoxFuture<'a, Result<(), sqlx::Error>>;
+}
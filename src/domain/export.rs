@@ -0,0 +1,32 @@
+use chrono::{DateTime, Local};
+use crate::domain::BoxFuture;
+use crate::models::export::{Export, ExportFilter, ExportKind, ExportRequest, ExportRow};
+
+pub trait ExportRepo: Send + Sync {
+    fn create<'a>(&'a self, request: &'a ExportRequest, admin_id: u32) -> BoxFuture<'a, Result<u64, sqlx::Error>>;
+    fn find(&self, id: u64) -> BoxFuture<'_, Result<Option<Export>, sqlx::Error>>;
+    fn list(&self, limit: u32, offset: u32) -> BoxFuture<'_, Result<Vec<Export>, sqlx::Error>>;
+    /// 开始（或重试时重新开始）写文件，已写入行数清零
+    fn start(&self, id: u64, total_rows: u64, now: DateTime<Local>) -> BoxFuture<'_, Result<(), sqlx::Error>>;
+    fn progress(&self, id: u64, processed_rows: u64) -> BoxFuture<'_, Result<(), sqlx::Error>>;
+    fn complete<'a>(
+        &'a self,
+        id: u64,
+        blob_key: &'a str,
+        file_size: u64,
+        processed_rows: u64,
+        expires_at: DateTime<Local>,
+    ) -> BoxFuture<'a, Result<(), sqlx::Error>>;
+    fn fail<'a>(&'a self, id: u64, error: &'a str) -> BoxFuture<'a, Result<(), sqlx::Error>>;
+    /// 成功的导出过期后标记为已过期
+    fn expire(&self, id: u64) -> BoxFuture<'_, Result<bool, sqlx::Error>>;
+    fn count_rows<'a>(&'a self, kind: ExportKind, filter: &'a ExportFilter) -> BoxFuture<'a, Result<u64, sqlx::Error>>;
+    /// 按主键顺序读取 `after_id` 之后的一批数据，返回每行的主键和各列
+    fn fetch_batch<'a>(
+        &'a self,
+        kind: ExportKind,
+        filter: &'a ExportFilter,
+        after_id: u64,
+        limit: u32,
+    ) -> BoxFuture<'a, Result<Vec<ExportRow>, sqlx::Error>>;
+}
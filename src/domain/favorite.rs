@@ -0,0 +1,20 @@
+use crate::domain::BoxFuture;
+use crate::models::favorite::{Favorite, ViewedProduct};
+
+pub trait FavoriteRepo: Send + Sync {
+    /// 收藏并在同一事务中累加商品收藏数；已收藏时返回 false
+    fn add(&self, user_id: u32, product_id: u64) -> BoxFuture<'_, Result<bool, sqlx::Error>>;
+    /// 取消收藏并扣减商品收藏数；未收藏时返回 false
+    fn remove(&self, user_id: u32, product_id: u64) -> BoxFuture<'_, Result<bool, sqlx::Error>>;
+    fn list(&self, user_id: u32, limit: u32, offset: u32) -> BoxFuture<'_, Result<Vec<Favorite>, sqlx::Error>>;
+}
+
+/// 最近浏览记录，每个用户只保留最近的若干条
+pub trait BrowsingHistory: Send + Sync {
+    /// 记录一次浏览，同一商品只保留最近一次；超出 `limit` 的旧记录被淘汰
+    fn record(&self, user_id: u32, product_id: u64, viewed_at: i64, limit: u32, ttl_secs: u64) -> BoxFuture<'_, Result<(), fred::error::Error>>;
+    /// 按浏览时间倒序
+    fn list(&self, user_id: u32, limit: u32) -> BoxFuture<'_, Result<Vec<ViewedProduct>, fred::error::Error>>;
+    fn remove(&self, user_id: u32, product_id: u64) -> BoxFuture<'_, Result<(), fred::error::Error>>;
+    fn clear(&self, user_id: u32) -> BoxFuture<'_, Result<(), fred::error::Error>>;
+}
@@ -0,0 +1,12 @@
+use crate::domain::BoxFuture;
+use crate::models::freight::{FreightTemplate, NewFreightTemplate};
+
+pub trait FreightRepo: Send + Sync {
+    fn create<'a>(&'a self, template: &'a NewFreightTemplate) -> BoxFuture<'a, Result<u64, sqlx::Error>>;
+    fn update<'a>(&'a self, id: u64, template: &'a NewFreightTemplate) -> BoxFuture<'a, Result<bool, sqlx::Error>>;
+    fn find(&self, id: u64) -> BoxFuture<'_, Result<Option<FreightTemplate>, sqlx::Error>>;
+    fn find_many<'a>(&'a self, ids: &'a [u64]) -> BoxFuture<'a, Result<Vec<FreightTemplate>, sqlx::Error>>;
+    fn list(&self) -> BoxFuture<'_, Result<Vec<FreightTemplate>, sqlx::Error>>;
+    /// 设置商品使用的运费模板，`None` 表示改回统一运费；返回商品是否存在
+    fn assign(&self, product_id: u64, template_id: Option<u64>) -> BoxFuture<'_, Result<bool, sqlx::Error>>;
+}
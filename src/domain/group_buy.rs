@@ -0,0 +1,28 @@
+use crate::domain::BoxFuture;
+use crate::models::group_buy::{GroupBuy, GroupCampaign, GroupMember, JoinOutcome, NewGroupCampaign};
+use chrono::{DateTime, Local};
+
+pub trait GroupBuyRepo: Send + Sync {
+    fn create_campaign<'a>(&'a self, campaign: &'a NewGroupCampaign) -> BoxFuture<'a, Result<u64, sqlx::Error>>;
+    fn find_campaign(&self, id: u64) -> BoxFuture<'_, Result<Option<GroupCampaign>, sqlx::Error>>;
+    fn list_campaigns(&self, limit: u32, offset: u32) -> BoxFuture<'_, Result<Vec<GroupCampaign>, sqlx::Error>>;
+    /// 启用且在活动时间内的活动
+    fn list_open_campaigns(&self, now: DateTime<Local>) -> BoxFuture<'_, Result<Vec<GroupCampaign>, sqlx::Error>>;
+    fn set_campaign_status<'a>(&'a self, id: u64, status: &'a str) -> BoxFuture<'a, Result<bool, sqlx::Error>>;
+    /// 开团并写入团长的成员记录，返回团 ID 和成员 ID
+    fn open(&self, campaign: &GroupCampaign, leader_id: u32, expires_at: DateTime<Local>) -> BoxFuture<'_, Result<(u64, u64), sqlx::Error>>;
+    /// 锁定团后校验状态、截止时间和名额并占位；订单已取消的成员不占名额
+    fn join(&self, group_id: u64, user_id: u32, now: DateTime<Local>) -> BoxFuture<'_, Result<JoinOutcome, sqlx::Error>>;
+    fn attach_order(&self, member_id: u64, order_id: u64) -> BoxFuture<'_, Result<(), sqlx::Error>>;
+    /// 下单失败时撤销占位
+    fn remove_member(&self, member_id: u64) -> BoxFuture<'_, Result<(), sqlx::Error>>;
+    fn find_group(&self, id: u64) -> BoxFuture<'_, Result<Option<GroupBuy>, sqlx::Error>>;
+    fn list_members(&self, group_id: u64) -> BoxFuture<'_, Result<Vec<GroupMember>, sqlx::Error>>;
+    /// 记录成员已支付，已支付人数达到成团人数时置为成团。
+    /// 返回订单所属的团（更新后），不是拼团订单时返回 `None`；重复调用只计数一次
+    fn mark_paid(&self, order_id: u64, paid_at: DateTime<Local>) -> BoxFuture<'_, Result<Option<GroupBuy>, sqlx::Error>>;
+    /// 拼团中且已过截止时间的团置为失败，返回是否更新
+    fn fail(&self, group_id: u64, now: DateTime<Local>) -> BoxFuture<'_, Result<bool, sqlx::Error>>;
+    /// 不等截止时间，把拼团中的团直接置为失败，返回是否更新
+    fn abort(&self, group_id: u64) -> BoxFuture<'_, Result<bool, sqlx::Error>>;
+}
@@ -0,0 +1,28 @@
+use chrono::{DateTime, Local};
+use crate::domain::BoxFuture;
+use crate::models::import::{Import, ImportCounts, ImportStatus, ProductImport, ProductUpsert};
+
+pub trait ImportRepo: Send + Sync {
+    fn create<'a>(&'a self, format: &'a str, dry_run: bool, admin_id: u32) -> BoxFuture<'a, Result<u64, sqlx::Error>>;
+    fn find(&self, id: u64) -> BoxFuture<'_, Result<Option<Import>, sqlx::Error>>;
+    fn list(&self, limit: u32, offset: u32) -> BoxFuture<'_, Result<Vec<Import>, sqlx::Error>>;
+    /// 开始（或重试时重新开始）处理，已处理行数清零
+    fn start(&self, id: u64, total_rows: u64, now: DateTime<Local>) -> BoxFuture<'_, Result<(), sqlx::Error>>;
+    fn progress(&self, id: u64, processed_rows: u64) -> BoxFuture<'_, Result<(), sqlx::Error>>;
+    /// 记录统计与结果文件并结束
+    fn finish<'a>(
+        &'a self,
+        id: u64,
+        status: ImportStatus,
+        counts: &'a ImportCounts,
+        report_key: &'a str,
+        error: Option<&'a str>,
+    ) -> BoxFuture<'a, Result<(), sqlx::Error>>;
+    fn fail<'a>(&'a self, id: u64, error: &'a str) -> BoxFuture<'a, Result<(), sqlx::Error>>;
+    /// 已存在的商品编码
+    fn existing_product_codes<'a>(&'a self, codes: &'a [&'a str]) -> BoxFuture<'a, Result<Vec<String>, sqlx::Error>>;
+    /// 已存在的 SKU 编码
+    fn existing_sku_codes<'a>(&'a self, codes: &'a [&'a str]) -> BoxFuture<'a, Result<Vec<String>, sqlx::Error>>;
+    /// 在一个事务中按编码新增或更新一批商品及其 SKU，新建的 SKU 同时建库存
+    fn upsert_products<'a>(&'a self, products: &'a [ProductImport]) -> BoxFuture<'a, Result<Vec<ProductUpsert>, sqlx::Error>>;
+}
@@ -0,0 +1,52 @@
+use crate::domain::BoxFuture;
+use crate::models::inventory::{Inventory, StockMovement};
+
+pub trait InventoryRepo: Send + Sync {
+    fn find_by_sku(&self, sku_id: u64) -> BoxFuture<'_, Result<Option<Inventory>, sqlx::Error>>;
+    fn list_redis_mode(&self) -> BoxFuture<'_, Result<Vec<Inventory>, sqlx::Error>>;
+    /// MySQL 模式预占：条件更新 + 写流水，库存不足返回 `false`；重复预占视为成功
+    fn reserve<'a>(&'a self, movement: &'a StockMovement) -> BoxFuture<'a, Result<bool, sqlx::Error>>;
+    /// MySQL 模式释放：按预占流水回补，返回回补数量；没有预占或已释放返回 `None`
+    fn release<'a>(&'a self, sku_id: u64, order_ref: &'a str) -> BoxFuture<'a, Result<Option<i64>, sqlx::Error>>;
+    /// MySQL 模式退货入库：写流水并回补库存，同一 `order_ref` 重复入库返回 `false`
+    fn restock<'a>(&'a self, movement: &'a StockMovement) -> BoxFuture<'a, Result<bool, sqlx::Error>>;
+    /// 回写 Redis 模式产生的流水，已存在则跳过；返回是否真正应用
+    fn apply_movement<'a>(&'a self, movement: &'a StockMovement) -> BoxFuture<'a, Result<bool, sqlx::Error>>;
+}
+
+/// Redis 预占失败的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CounterRejection {
+    /// 计数器未初始化（SKU 未开启秒杀或尚未预热）
+    NotInitialized,
+    /// 超出单用户限购
+    LimitExceeded,
+    /// 库存不足
+    OutOfStock,
+}
+
+/// 秒杀库存计数器，所有扣减 / 回补必须是原子的
+pub trait StockCounter: Send + Sync {
+    /// 计数器不存在时用 MySQL 库存初始化，返回是否写入
+    fn seed(&self, sku_id: u64, stock: i64) -> BoxFuture<'_, Result<bool, fred::error::Error>>;
+    fn get(&self, sku_id: u64) -> BoxFuture<'_, Result<Option<i64>, fred::error::Error>>;
+    /// 原子扣减并记录流水，成功返回剩余库存
+    fn reserve<'a>(
+        &'a self,
+        movement: &'a StockMovement,
+        per_user_limit: Option<u32>,
+    ) -> BoxFuture<'a, Result<Result<i64, CounterRejection>, fred::error::Error>>;
+    /// 按订单回补，返回回补数量；没有预占或已释放返回 `None`
+    fn release<'a>(&'a self, sku_id: u64, order_ref: &'a str) -> BoxFuture<'a, Result<Option<i64>, fred::error::Error>>;
+    /// 退货入库，回补计数器并记录流水；同一 `order_ref` 重复入库返回 `false`
+    fn restock<'a>(&'a self, movement: &'a StockMovement) -> BoxFuture<'a, Result<Result<bool, CounterRejection>, fred::error::Error>>;
+    /// 取出一条待回写流水（移入处理中队列），返回原始报文，用于 `ack_movement`
+    fn claim_movement(&self) -> BoxFuture<'_, Result<Option<String>, fred::error::Error>>;
+    fn ack_movement<'a>(&'a self, raw: &'a str) -> BoxFuture<'a, Result<(), fred::error::Error>>;
+    /// 回写失败时把流水从处理中队列放回待处理队列队首，下一轮重试
+    fn nack_movement<'a>(&'a self, raw: &'a str) -> BoxFuture<'a, Result<(), fred::error::Error>>;
+    /// 把上次崩溃遗留在处理中队列的流水放回待处理队列
+    fn requeue_unacked(&self) -> BoxFuture<'_, Result<u64, fred::error::Error>>;
+    /// 待回写 + 处理中的流水数量
+    fn pending(&self) -> BoxFuture<'_, Result<u64, fred::error::Error>>;
+}
@@ -0,0 +1,15 @@
+use crate::domain::BoxFuture;
+use crate::models::job::Job;
+
+/// 至少一次投递的延迟队列，时间均为毫秒时间戳
+pub trait JobQueue: Send + Sync {
+    /// 在 `run_at` 之后可被领取；同 ID 的任务会被覆盖
+    fn enqueue<'a>(&'a self, job: &'a Job, run_at: i64) -> BoxFuture<'a, Result<(), fred::error::Error>>;
+    /// 领取一个到期任务，`visible_at` 前未确认的任务会被重新投递
+    fn claim(&self, now: i64, visible_at: i64) -> BoxFuture<'_, Result<Option<Job>, fred::error::Error>>;
+    fn ack<'a>(&'a self, job_id: &'a str) -> BoxFuture<'a, Result<(), fred::error::Error>>;
+    /// 执行失败，`run_at` 之后重试
+    fn retry<'a>(&'a self, job_id: &'a str, run_at: i64) -> BoxFuture<'a, Result<(), fred::error::Error>>;
+    /// 超过重试次数，移入死信队列
+    fn bury<'a>(&'a self, job: &'a Job, error: &'a str) -> BoxFuture<'a, Result<(), fred::error::Error>>;
+}
@@ -0,0 +1,20 @@
+use chrono::{DateTime, Local};
+use crate::domain::BoxFuture;
+use crate::models::loyalty::{Member, NewPointsEntry, PointsEntry, PointsKind, PointsOutcome};
+
+pub trait LoyaltyRepo: Send + Sync {
+    fn find_member(&self, user_id: u32) -> BoxFuture<'_, Result<Option<Member>, sqlx::Error>>;
+    /// 锁定会员行，在同一事务中写入流水并更新余额；余额不足或重复记账时不做任何修改
+    fn append<'a>(&'a self, entry: &'a NewPointsEntry) -> BoxFuture<'a, Result<PointsOutcome, sqlx::Error>>;
+    fn find_entry<'a>(&'a self, user_id: u32, kind: PointsKind, ref_no: &'a str) -> BoxFuture<'a, Result<Option<PointsEntry>, sqlx::Error>>;
+    fn list_entries(&self, user_id: u32, limit: u32, offset: u32) -> BoxFuture<'_, Result<Vec<PointsEntry>, sqlx::Error>>;
+    /// 截至 `now` 已到期的增加积分之和，以及全部扣减积分之和（正数）
+    fn expiry_totals(&self, user_id: u32, now: DateTime<Local>) -> BoxFuture<'_, Result<(i64, i64), sqlx::Error>>;
+    /// 仍有余额且有到期积分的用户，按用户 ID 分批
+    fn users_with_expired(&self, now: DateTime<Local>, after_user_id: u32, limit: u32) -> BoxFuture<'_, Result<Vec<u32>, sqlx::Error>>;
+    /// `since` 之后完成的订单实付金额之和
+    fn rolling_spend(&self, user_id: u32, since: DateTime<Local>) -> BoxFuture<'_, Result<i64, sqlx::Error>>;
+    fn save_tier(&self, user_id: u32, level: u32, rolling_spend: i64) -> BoxFuture<'_, Result<(), sqlx::Error>>;
+    /// 当前等级高于 0 的用户，按用户 ID 分批
+    fn tiered_users(&self, after_user_id: u32, limit: u32) -> BoxFuture<'_, Result<Vec<u32>, sqlx::Error>>;
+}
@@ -1,2 +1,33 @@
 pub mod users;
+pub mod inventory;
+pub mod catalog;
+pub mod cart;
+pub mod address;
+pub mod order;
+pub mod jobs;
+pub mod payment;
+pub mod refund;
+pub mod reconciliation;
+pub mod coupon;
+pub mod promotion;
+pub mod group_buy;
+pub mod freight;
+pub mod shipment;
+pub mod after_sale;
+pub mod review;
+pub mod notification;
+pub mod loyalty;
+pub mod favorite;
+pub mod referral;
+pub mod stats;
+pub mod blob;
+pub mod export;
+pub mod import;
+pub mod audit;
+pub mod events;
 
+use std::future::Future;
+use std::pin::Pin;
+
+/// 仓储 trait 中统一使用的装箱 Future，便于 `dyn` 调用
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
@@ -0,0 +1,24 @@
+use crate::domain::BoxFuture;
+use crate::domain::payment::GatewayError;
+use crate::models::notification::{Delivery, NewDelivery, SendOutcome, SubscribeMessage, Subscription};
+
+pub trait NotificationRepo: Send + Sync {
+    /// 记录一次授权，可发送次数加一
+    fn subscribe<'a>(&'a self, user_id: u32, openid: &'a str, template_id: &'a str) -> BoxFuture<'a, Result<(), sqlx::Error>>;
+    fn list_subscriptions(&self, user_id: u32) -> BoxFuture<'_, Result<Vec<Subscription>, sqlx::Error>>;
+    /// 还有剩余次数的授权
+    fn find_usable<'a>(&'a self, user_id: u32, template_id: &'a str) -> BoxFuture<'a, Result<Option<Subscription>, sqlx::Error>>;
+    /// 发送成功后扣减一次
+    fn consume<'a>(&'a self, user_id: u32, template_id: &'a str) -> BoxFuture<'a, Result<(), sqlx::Error>>;
+    /// 用户已拒收，清空剩余次数
+    fn revoke<'a>(&'a self, user_id: u32, template_id: &'a str) -> BoxFuture<'a, Result<(), sqlx::Error>>;
+    /// 该订单事件是否已经发送成功过，用于任务重复投递时去重
+    fn has_sent<'a>(&'a self, order_id: u64, event: &'a str) -> BoxFuture<'a, Result<bool, sqlx::Error>>;
+    fn log_delivery<'a>(&'a self, delivery: &'a NewDelivery) -> BoxFuture<'a, Result<(), sqlx::Error>>;
+    fn list_deliveries<'a>(&'a self, status: &'a str, limit: u32, offset: u32) -> BoxFuture<'a, Result<Vec<Delivery>, sqlx::Error>>;
+}
+
+/// 订阅消息发送通道；测试和本地开发使用只记录不发送的实现
+pub trait SubscribeTransport: Send + Sync {
+    fn send<'a>(&'a self, message: &'a SubscribeMessage) -> BoxFuture<'a, Result<SendOutcome, GatewayError>>;
+}
@@ -0,0 +1,34 @@
+use crate::domain::BoxFuture;
+use crate::models::event::DomainEvent;
+use crate::models::order::{Actor, NewOrder, Order, OrderEvent, OrderItem, OrderStatus};
+
+/// 下单时 MySQL 模式库存不足的 SKU
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutOfStock {
+    pub sku_id: u64,
+}
+
+pub trait OrderRepo: Send + Sync {
+    /// 在一个事务中预占 MySQL 模式库存，写入订单、订单行及初始状态记录，返回订单 ID；
+    /// 任一 SKU 库存不足时整单回滚
+    fn create<'a>(&'a self, order: &'a NewOrder) -> BoxFuture<'a, Result<Result<u64, OutOfStock>, sqlx::Error>>;
+    fn find_by_id(&self, id: u64) -> BoxFuture<'_, Result<Option<Order>, sqlx::Error>>;
+    fn find_items(&self, order_id: u64) -> BoxFuture<'_, Result<Vec<OrderItem>, sqlx::Error>>;
+    fn list_by_user(&self, user_id: u32, limit: u32, offset: u32) -> BoxFuture<'_, Result<Vec<Order>, sqlx::Error>>;
+}
+
+/// 订单状态机依赖的最小仓储接口，便于脱离数据库测试
+pub trait OrderStateRepo: Send + Sync {
+    fn find_order(&self, id: u64) -> BoxFuture<'_, Result<Option<Order>, sqlx::Error>>;
+    /// 仅当订单当前状态为 `from` 时改为 `to`，并在同一事务中写入审计记录和发件箱事件；返回是否更新成功
+    fn transition<'a>(
+        &'a self,
+        order_id: u64,
+        from: OrderStatus,
+        to: OrderStatus,
+        actor: Actor,
+        reason: &'a str,
+        event: &'a DomainEvent,
+    ) -> BoxFuture<'a, Result<bool, sqlx::Error>>;
+    fn list_events(&self, order_id: u64) -> BoxFuture<'_, Result<Vec<OrderEvent>, sqlx::Error>>;
+}
@@ -0,0 +1,182 @@
+use crate::domain::BoxFuture;
+use crate::models::payment::{JsapiPayParams, NewPayment, Payment};
+use chrono::{DateTime, Local, NaiveDate};
+use serde::Deserialize;
+use std::fmt;
+
+pub trait PaymentRepo: Send + Sync {
+    /// 写入待支付记录；同一订单重复发起支付时更新金额和 prepay_id
+    fn save_prepay<'a>(&'a self, payment: &'a NewPayment) -> BoxFuture<'a, Result<(), sqlx::Error>>;
+    fn find_by_out_trade_no<'a>(&'a self, out_trade_no: &'a str) -> BoxFuture<'a, Result<Option<Payment>, sqlx::Error>>;
+    fn find_by_order(&self, order_id: u64) -> BoxFuture<'_, Result<Option<Payment>, sqlx::Error>>;
+    /// 仅当支付单仍为待支付时标记为成功，返回是否更新
+    fn mark_succeeded<'a>(
+        &'a self,
+        id: u64,
+        transaction_id: &'a str,
+        paid_at: DateTime<Local>,
+    ) -> BoxFuture<'a, Result<bool, sqlx::Error>>;
+    /// `paid_at` 落在 [start, end) 内的成功支付，用于按日对账
+    fn list_succeeded_between(
+        &self,
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+    ) -> BoxFuture<'_, Result<Vec<Payment>, sqlx::Error>>;
+    /// 最后一次发起支付在 (after, before) 之间、仍未收到结果的支付单
+    fn list_pending(
+        &self,
+        before: DateTime<Local>,
+        after: DateTime<Local>,
+        limit: u32,
+    ) -> BoxFuture<'_, Result<Vec<Payment>, sqlx::Error>>;
+    /// 仅当支付单仍为待支付时标记为已关闭，返回是否更新
+    fn mark_closed(&self, id: u64) -> BoxFuture<'_, Result<bool, sqlx::Error>>;
+}
+
+/// JSAPI 下单参数，金额单位：分
+#[derive(Debug, Clone)]
+pub struct PrepayRequest {
+    pub description: String,
+    pub out_trade_no: String,
+    pub amount: i64,
+    pub payer_openid: String,
+    /// 支付截止时间，RFC 3339 格式
+    pub time_expire: Option<String>,
+}
+
+/// 支付结果通知的签名相关请求头
+#[derive(Debug, Clone)]
+pub struct NotifyHeaders {
+    pub timestamp: String,
+    pub nonce: String,
+    pub signature: String,
+    pub serial: String,
+}
+
+/// 解密后的支付结果，只保留需要核对的字段
+#[derive(Debug, Clone, Deserialize)]
+pub struct Transaction {
+    pub appid: String,
+    pub mchid: String,
+    pub out_trade_no: String,
+    /// 未支付的订单查询结果中没有微信订单号和金额
+    #[serde(default)]
+    pub transaction_id: String,
+    /// SUCCESS / REFUND / NOTPAY / CLOSED / REVOKED / USERPAYING / PAYERROR
+    pub trade_state: String,
+    pub success_time: Option<String>,
+    #[serde(default)]
+    pub amount: TransactionAmount,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TransactionAmount {
+    pub total: i64,
+}
+
+impl Transaction {
+    pub fn is_success(&self) -> bool {
+        self.trade_state == "SUCCESS"
+    }
+
+    /// 交易已关闭或撤销，该商户订单号不能再支付
+    pub fn is_closed(&self) -> bool {
+        self.trade_state == "CLOSED" || self.trade_state == "REVOKED"
+    }
+}
+
+/// 申请退款参数，金额单位：分
+#[derive(Debug, Clone)]
+pub struct RefundRequest {
+    pub out_trade_no: String,
+    pub out_refund_no: String,
+    pub reason: String,
+    pub refund: i64,
+    /// 原支付金额
+    pub total: i64,
+}
+
+/// 退款受理结果或退款结果通知
+#[derive(Debug, Clone, Deserialize)]
+pub struct RefundResult {
+    pub out_refund_no: String,
+    pub refund_id: String,
+    /// SUCCESS / CLOSED / PROCESSING / ABNORMAL
+    #[serde(alias = "refund_status")]
+    pub status: String,
+    pub success_time: Option<String>,
+    pub amount: RefundAmount,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RefundAmount {
+    pub refund: i64,
+}
+
+impl RefundResult {
+    pub fn is_success(&self) -> bool {
+        self.status == "SUCCESS"
+    }
+
+    /// 退款关闭或异常，款项不会退回
+    pub fn is_failed(&self) -> bool {
+        self.status == "CLOSED" || self.status == "ABNORMAL"
+    }
+}
+
+/// 交易账单文件；`data` 可能是 gzip 压缩的，`sha1` 是解压后原始账单的摘要
+#[derive(Debug, Clone)]
+pub struct TradeBill {
+    pub data: Vec<u8>,
+    pub sha1: String,
+}
+
+#[derive(Debug)]
+pub enum GatewayError {
+    /// 网络或响应解析失败
+    Http(reqwest::Error),
+    /// 微信支付返回了非 2xx 状态码
+    Api { status: u16, body: String },
+    /// 商户私钥签名失败
+    Sign(String),
+    /// 通知验签或解密失败，报文不可信
+    Verify(String),
+    /// 未配置支付渠道
+    Unconfigured,
+}
+
+impl fmt::Display for GatewayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GatewayError::Http(e) => write!(f, "http error: {}", e),
+            GatewayError::Api { status, body } => write!(f, "api error {}: {}", status, body),
+            GatewayError::Sign(msg) => write!(f, "sign error: {}", msg),
+            GatewayError::Verify(msg) => write!(f, "verify error: {}", msg),
+            GatewayError::Unconfigured => write!(f, "pay gateway is not configured"),
+        }
+    }
+}
+
+impl From<reqwest::Error> for GatewayError {
+    fn from(e: reqwest::Error) -> Self {
+        GatewayError::Http(e)
+    }
+}
+
+/// 支付渠道接口；测试时可以替换成指向本地 mock 的实现
+pub trait PayGateway: Send + Sync {
+    /// 创建预支付单，返回 prepay_id
+    fn jsapi_prepay<'a>(&'a self, request: &'a PrepayRequest) -> BoxFuture<'a, Result<String, GatewayError>>;
+    /// 用商户私钥为 prepay_id 生成小程序调起支付的参数
+    fn jsapi_pay_params(&self, prepay_id: &str) -> Result<JsapiPayParams, GatewayError>;
+    /// 用平台证书验证通知签名，再用 APIv3 密钥解密出支付结果
+    fn parse_notify<'a>(&'a self, headers: &'a NotifyHeaders, body: &'a str) -> BoxFuture<'a, Result<Transaction, GatewayError>>;
+    /// 按商户订单号查询支付结果；微信侧没有该订单时返回 `None`
+    fn query_order<'a>(&'a self, out_trade_no: &'a str) -> BoxFuture<'a, Result<Option<Transaction>, GatewayError>>;
+    /// 申请退款；以 out_refund_no 幂等，重复提交返回同一笔退款
+    fn refund<'a>(&'a self, request: &'a RefundRequest) -> BoxFuture<'a, Result<RefundResult, GatewayError>>;
+    /// 验签并解密退款结果通知
+    fn parse_refund_notify<'a>(&'a self, headers: &'a NotifyHeaders, body: &'a str) -> BoxFuture<'a, Result<RefundResult, GatewayError>>;
+    /// 下载某日全部交易类型的账单；当天没有交易时返回 `None`
+    fn download_trade_bill(&self, bill_date: NaiveDate) -> BoxFuture<'_, Result<Option<TradeBill>, GatewayError>>;
+}
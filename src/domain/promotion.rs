@@ -0,0 +1,12 @@
+use crate::domain::BoxFuture;
+use crate::models::promotion::{NewPromotion, Promotion};
+use chrono::{DateTime, Local};
+
+pub trait PromotionRepo: Send + Sync {
+    fn create<'a>(&'a self, promotion: &'a NewPromotion) -> BoxFuture<'a, Result<u64, sqlx::Error>>;
+    fn find(&self, id: u64) -> BoxFuture<'_, Result<Option<Promotion>, sqlx::Error>>;
+    fn list(&self, limit: u32, offset: u32) -> BoxFuture<'_, Result<Vec<Promotion>, sqlx::Error>>;
+    /// 启用且处在活动时间内的活动
+    fn list_active(&self, now: DateTime<Local>) -> BoxFuture<'_, Result<Vec<Promotion>, sqlx::Error>>;
+    fn set_status<'a>(&'a self, id: u64, status: &'a str) -> BoxFuture<'a, Result<bool, sqlx::Error>>;
+}
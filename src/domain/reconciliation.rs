@@ -0,0 +1,18 @@
+use crate::domain::BoxFuture;
+use crate::models::reconciliation::{Discrepancy, NewDiscrepancy, ReconciliationRun};
+use chrono::NaiveDate;
+
+pub trait ReconciliationRepo: Send + Sync {
+    /// 写入某个账单日的对账结果，已存在时覆盖计数并替换全部差异，返回 run id
+    fn save<'a>(
+        &'a self,
+        bill_date: NaiveDate,
+        remote_count: u32,
+        local_count: u32,
+        discrepancies: &'a [NewDiscrepancy],
+    ) -> BoxFuture<'a, Result<u64, sqlx::Error>>;
+    fn find_by_date(&self, bill_date: NaiveDate) -> BoxFuture<'_, Result<Option<ReconciliationRun>, sqlx::Error>>;
+    /// 按账单日倒序
+    fn list_runs(&self, limit: u32, offset: u32) -> BoxFuture<'_, Result<Vec<ReconciliationRun>, sqlx::Error>>;
+    fn list_discrepancies(&self, run_id: u64) -> BoxFuture<'_, Result<Vec<Discrepancy>, sqlx::Error>>;
+}
@@ -0,0 +1,37 @@
+use chrono::{DateTime, Local};
+use crate::domain::BoxFuture;
+use crate::models::referral::{Commission, CommissionStatus, NewCommission, ReferralBinding, ReferralCode};
+
+pub trait ReferralRepo: Send + Sync {
+    fn find_code_by_user(&self, user_id: u32) -> BoxFuture<'_, Result<Option<ReferralCode>, sqlx::Error>>;
+    fn find_code<'a>(&'a self, code: &'a str) -> BoxFuture<'a, Result<Option<ReferralCode>, sqlx::Error>>;
+    /// 推广码已被占用或用户已有推广码时返回 false
+    fn create_code<'a>(&'a self, user_id: u32, code: &'a str) -> BoxFuture<'a, Result<bool, sqlx::Error>>;
+    fn find_binding(&self, user_id: u32) -> BoxFuture<'_, Result<Option<ReferralBinding>, sqlx::Error>>;
+    /// 没有归属或原归属已过期时绑定，返回是否绑定成功
+    fn bind<'a>(
+        &'a self,
+        user_id: u32,
+        referrer_id: u32,
+        code: &'a str,
+        now: DateTime<Local>,
+        expires_at: DateTime<Local>,
+    ) -> BoxFuture<'a, Result<bool, sqlx::Error>>;
+    /// 用户是否有过已支付的订单
+    fn has_paid_orders(&self, user_id: u32) -> BoxFuture<'_, Result<bool, sqlx::Error>>;
+    fn count_invited(&self, referrer_id: u32, now: DateTime<Local>) -> BoxFuture<'_, Result<i64, sqlx::Error>>;
+    /// 同一订单同一层级只记一次
+    fn add_commissions<'a>(&'a self, commissions: &'a [NewCommission]) -> BoxFuture<'a, Result<(), sqlx::Error>>;
+    /// 为订单待结算的佣金设置结算时间
+    fn schedule_settle(&self, order_id: u64, settle_at: DateTime<Local>) -> BoxFuture<'_, Result<u64, sqlx::Error>>;
+    /// 到结算时间仍待结算的订单，按订单 ID 分批
+    fn due_orders(&self, now: DateTime<Local>, after_order_id: u64, limit: u32) -> BoxFuture<'_, Result<Vec<u64>, sqlx::Error>>;
+    /// 按最终计佣金额重算并转为可提现，返回更新的条数
+    fn settle(&self, order_id: u64, base_amount: i64, now: DateTime<Local>) -> BoxFuture<'_, Result<u64, sqlx::Error>>;
+    /// 冲回订单尚未冲回的佣金，返回更新的条数
+    fn reverse(&self, order_id: u64) -> BoxFuture<'_, Result<u64, sqlx::Error>>;
+    fn list_by_beneficiary(&self, beneficiary_id: u32, limit: u32, offset: u32) -> BoxFuture<'_, Result<Vec<Commission>, sqlx::Error>>;
+    fn list_by_status(&self, status: CommissionStatus, limit: u32, offset: u32) -> BoxFuture<'_, Result<Vec<Commission>, sqlx::Error>>;
+    /// 按状态汇总的佣金金额
+    fn totals(&self, beneficiary_id: u32) -> BoxFuture<'_, Result<Vec<(String, i64)>, sqlx::Error>>;
+}
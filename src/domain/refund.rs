@@ -0,0 +1,31 @@
+use crate::domain::BoxFuture;
+use crate::models::refund::{NewRefund, Refund};
+use chrono::{DateTime, Local};
+
+/// 状态变更都是条件更新，返回是否生效，由调用方据此判断重复请求
+pub trait RefundRepo: Send + Sync {
+    fn create<'a>(&'a self, refund: &'a NewRefund) -> BoxFuture<'a, Result<u64, sqlx::Error>>;
+    fn find(&self, id: u64) -> BoxFuture<'_, Result<Option<Refund>, sqlx::Error>>;
+    fn find_by_refund_no<'a>(&'a self, refund_no: &'a str) -> BoxFuture<'a, Result<Option<Refund>, sqlx::Error>>;
+    fn list_by_order(&self, order_id: u64) -> BoxFuture<'_, Result<Vec<Refund>, sqlx::Error>>;
+    fn list_by_status<'a>(&'a self, status: &'a str, limit: u32, offset: u32) -> BoxFuture<'a, Result<Vec<Refund>, sqlx::Error>>;
+    /// pending -> processing
+    fn approve(&self, id: u64, admin_id: u32, restock: bool) -> BoxFuture<'_, Result<bool, sqlx::Error>>;
+    /// pending -> rejected
+    fn reject<'a>(&'a self, id: u64, admin_id: u32, reason: &'a str) -> BoxFuture<'a, Result<bool, sqlx::Error>>;
+    /// processing -> succeeded
+    fn mark_succeeded<'a>(
+        &'a self,
+        id: u64,
+        channel_refund_id: &'a str,
+        refunded_at: DateTime<Local>,
+    ) -> BoxFuture<'a, Result<bool, sqlx::Error>>;
+    /// processing -> failed
+    fn mark_failed(&self, id: u64) -> BoxFuture<'_, Result<bool, sqlx::Error>>;
+    /// `refunded_at` 落在 [start, end) 内的成功退款，用于按日对账
+    fn list_succeeded_between(
+        &self,
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+    ) -> BoxFuture<'_, Result<Vec<Refund>, sqlx::Error>>;
+}
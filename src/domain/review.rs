@@ -0,0 +1,27 @@
+use crate::domain::BoxFuture;
+use crate::models::review::{NewReview, Review, ReviewFilter, ReviewStats, ReviewStatus};
+
+pub trait ReviewRepo: Send + Sync {
+    fn create<'a>(&'a self, review: &'a NewReview) -> BoxFuture<'a, Result<u64, sqlx::Error>>;
+    fn find(&self, id: u64) -> BoxFuture<'_, Result<Option<Review>, sqlx::Error>>;
+    fn find_by_item(&self, order_item_id: u64) -> BoxFuture<'_, Result<Option<Review>, sqlx::Error>>;
+    fn list_by_user(&self, user_id: u32, limit: u32, offset: u32) -> BoxFuture<'_, Result<Vec<Review>, sqlx::Error>>;
+    fn list_by_status<'a>(&'a self, status: &'a str, limit: u32, offset: u32) -> BoxFuture<'a, Result<Vec<Review>, sqlx::Error>>;
+    /// 商品下审核通过的评价，按时间倒序
+    fn list_for_product<'a>(
+        &'a self,
+        product_id: u64,
+        filter: &'a ReviewFilter,
+        limit: u32,
+        offset: u32,
+    ) -> BoxFuture<'a, Result<Vec<Review>, sqlx::Error>>;
+    /// 商品下审核通过的评价按星级计数
+    fn rating_counts(&self, product_id: u64) -> BoxFuture<'_, Result<Vec<(u8, u32)>, sqlx::Error>>;
+    /// 写入追评并重新进入待审核；已追评过时返回 false
+    fn follow_up<'a>(&'a self, id: u64, content: &'a str, photos: &'a [String]) -> BoxFuture<'a, Result<bool, sqlx::Error>>;
+    fn set_status(&self, id: u64, status: ReviewStatus) -> BoxFuture<'_, Result<(), sqlx::Error>>;
+    fn reply<'a>(&'a self, id: u64, reply: &'a str) -> BoxFuture<'a, Result<(), sqlx::Error>>;
+    /// 按审核通过的评价重新计算商品上缓存的汇总
+    fn refresh_stats(&self, product_id: u64) -> BoxFuture<'_, Result<(), sqlx::Error>>;
+    fn stats(&self, product_id: u64) -> BoxFuture<'_, Result<Option<ReviewStats>, sqlx::Error>>;
+}
@@ -0,0 +1,29 @@
+use crate::domain::BoxFuture;
+use crate::domain::payment::GatewayError;
+use crate::models::shipment::{Shipment, TrackingInfo};
+use chrono::{DateTime, Local};
+
+pub trait ShipmentRepo: Send + Sync {
+    /// 写入发货记录，订单已有记录时更新快递公司和运单号
+    fn upsert<'a>(
+        &'a self,
+        order_id: u64,
+        carrier: &'a str,
+        tracking_no: &'a str,
+        admin_id: u32,
+        shipped_at: DateTime<Local>,
+    ) -> BoxFuture<'a, Result<(), sqlx::Error>>;
+    fn find_by_order(&self, order_id: u64) -> BoxFuture<'_, Result<Option<Shipment>, sqlx::Error>>;
+}
+
+/// 物流轨迹查询服务商；测试和本地开发使用返回固定轨迹的实现
+pub trait TrackingProvider: Send + Sync {
+    /// `phone` 为收件人手机号，部分快递公司（如顺丰）要求校验
+    fn query<'a>(&'a self, carrier: &'a str, tracking_no: &'a str, phone: &'a str) -> BoxFuture<'a, Result<TrackingInfo, GatewayError>>;
+}
+
+/// 物流轨迹缓存，避免频繁调用按次计费的查询接口
+pub trait TrackingCache: Send + Sync {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<TrackingInfo>, fred::error::Error>>;
+    fn set<'a>(&'a self, key: &'a str, info: &'a TrackingInfo, ttl_secs: u64) -> BoxFuture<'a, Result<(), fred::error::Error>>;
+}
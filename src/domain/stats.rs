@@ -0,0 +1,15 @@
+use chrono::{DateTime, Local, NaiveDate};
+use crate::domain::BoxFuture;
+use crate::models::stats::{DailyStats, Granularity, ProductStats};
+
+pub trait StatsRepo: Send + Sync {
+    /// 从订单、支付与退款重新汇总 [start, end) 并覆盖 `stat_date` 的全部汇总数据
+    fn rollup(&self, stat_date: NaiveDate, start: DateTime<Local>, end: DateTime<Local>) -> BoxFuture<'_, Result<DailyStats, sqlx::Error>>;
+    /// [from, to] 内已汇总的日数据，按日期升序
+    fn list_daily(&self, from: NaiveDate, to: NaiveDate) -> BoxFuture<'_, Result<Vec<DailyStats>, sqlx::Error>>;
+    /// 按周期去重的支付用户数，键为周期第一天
+    fn payers_by_period(&self, from: NaiveDate, to: NaiveDate, group_by: Granularity) -> BoxFuture<'_, Result<Vec<(NaiveDate, i64)>, sqlx::Error>>;
+    fn count_payers(&self, from: NaiveDate, to: NaiveDate) -> BoxFuture<'_, Result<i64, sqlx::Error>>;
+    /// 按实付金额倒序
+    fn top_products(&self, from: NaiveDate, to: NaiveDate, limit: u32) -> BoxFuture<'_, Result<Vec<ProductStats>, sqlx::Error>>;
+}
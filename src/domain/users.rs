@@ -1,3 +1,5 @@
+use crate::domain::BoxFuture;
+use crate::domain::payment::GatewayError;
 use crate::models;
 use std::future::Future;
 use std::pin::Pin;
@@ -15,3 +17,8 @@ impl Clone for Box<dyn UserRepo> {
         self.clone_box()
     }
 }
+
+/// 小程序登录凭证校验：用 `wx.login` 返回的 code 换取用户在本小程序下的 openid
+pub trait WechatAuth: Send + Sync {
+    fn code_to_openid<'a>(&'a self, code: &'a str) -> BoxFuture<'a, Result<String, GatewayError>>;
+}
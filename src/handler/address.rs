@@ -0,0 +1,33 @@
+use axum::extract::State;
+use axum::Json;
+use std::sync::Arc;
+use tower_sessions::Session;
+use crate::handler::require_user;
+use crate::models::address::NewAddress;
+use crate::service::ServiceError;
+use crate::service::address::AddressService;
+
+pub async fn list_addresses_handler(
+    session: Session,
+    State(address_service): State<Arc<dyn AddressService>>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let addresses = address_service.list(user.id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": addresses
+    })))
+}
+
+pub async fn create_address_handler(
+    session: Session,
+    State(address_service): State<Arc<dyn AddressService>>,
+    Json(payload): Json<NewAddress>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let address = address_service.create(user.id, payload).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": address
+    })))
+}
@@ -0,0 +1,198 @@
+use axum::extract::{Path, Query, State};
+use axum::Json;
+use serde::Deserialize;
+use std::sync::Arc;
+use tower_sessions::Session;
+use crate::handler::order::{default_page, default_page_size, PageQuery};
+use crate::handler::require_user;
+use crate::models::after_sale::{AfterSaleApply, AfterSaleStatus};
+use crate::service::ServiceError;
+use crate::service::after_sale::AfterSaleService;
+
+#[derive(Deserialize)]
+pub struct AfterSaleListQuery {
+    #[serde(default = "default_status")]
+    pub status: String,
+    #[serde(default = "default_page")]
+    pub page: u32,
+    #[serde(default = "default_page_size")]
+    pub page_size: u32,
+}
+
+fn default_status() -> String {
+    AfterSaleStatus::Requested.as_str().to_string()
+}
+
+/// 寄回或换货发出的运单
+#[derive(Deserialize)]
+pub struct WaybillReq {
+    pub carrier: String,
+    pub tracking_no: String,
+}
+
+#[derive(Deserialize)]
+pub struct RejectAfterSaleReq {
+    pub reason: String,
+}
+
+#[derive(Deserialize)]
+pub struct AfterSaleNoteReq {
+    pub note: String,
+}
+
+pub async fn request_after_sale_handler(
+    session: Session,
+    State(after_sale_service): State<Arc<dyn AfterSaleService>>,
+    Path(order_id): Path<u64>,
+    Json(payload): Json<AfterSaleApply>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let after_sale = after_sale_service.request(user.id, order_id, &payload).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": after_sale
+    })))
+}
+
+pub async fn list_my_after_sales_handler(
+    session: Session,
+    State(after_sale_service): State<Arc<dyn AfterSaleService>>,
+    Query(query): Query<PageQuery>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let after_sales = after_sale_service.list_mine(user.id, query.page, query.page_size).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": after_sales
+    })))
+}
+
+pub async fn get_after_sale_handler(
+    session: Session,
+    State(after_sale_service): State<Arc<dyn AfterSaleService>>,
+    Path(id): Path<u64>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let after_sale = after_sale_service.get(user.id, id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": after_sale
+    })))
+}
+
+pub async fn ship_back_handler(
+    session: Session,
+    State(after_sale_service): State<Arc<dyn AfterSaleService>>,
+    Path(id): Path<u64>,
+    Json(payload): Json<WaybillReq>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let after_sale = after_sale_service.ship_back(user.id, id, &payload.carrier, &payload.tracking_no).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": after_sale
+    })))
+}
+
+pub async fn admin_list_after_sales_handler(
+    State(after_sale_service): State<Arc<dyn AfterSaleService>>,
+    Query(query): Query<AfterSaleListQuery>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let after_sales = after_sale_service.list_by_status(&query.status, query.page, query.page_size).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": after_sales
+    })))
+}
+
+pub async fn admin_get_after_sale_handler(
+    State(after_sale_service): State<Arc<dyn AfterSaleService>>,
+    Path(id): Path<u64>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let detail = after_sale_service.detail(id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": detail
+    })))
+}
+
+pub async fn admin_approve_after_sale_handler(
+    session: Session,
+    State(after_sale_service): State<Arc<dyn AfterSaleService>>,
+    Path(id): Path<u64>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let admin = require_user(&session).await?;
+    let after_sale = after_sale_service.approve(admin.id, id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": after_sale
+    })))
+}
+
+pub async fn admin_reject_after_sale_handler(
+    session: Session,
+    State(after_sale_service): State<Arc<dyn AfterSaleService>>,
+    Path(id): Path<u64>,
+    Json(payload): Json<RejectAfterSaleReq>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let admin = require_user(&session).await?;
+    let after_sale = after_sale_service.reject(admin.id, id, &payload.reason).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": after_sale
+    })))
+}
+
+pub async fn admin_receive_after_sale_handler(
+    session: Session,
+    State(after_sale_service): State<Arc<dyn AfterSaleService>>,
+    Path(id): Path<u64>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let admin = require_user(&session).await?;
+    let after_sale = after_sale_service.receive(admin.id, id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": after_sale
+    })))
+}
+
+pub async fn admin_refund_after_sale_handler(
+    session: Session,
+    State(after_sale_service): State<Arc<dyn AfterSaleService>>,
+    Path(id): Path<u64>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let admin = require_user(&session).await?;
+    let after_sale = after_sale_service.refund(admin.id, id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": after_sale
+    })))
+}
+
+pub async fn admin_exchange_after_sale_handler(
+    session: Session,
+    State(after_sale_service): State<Arc<dyn AfterSaleService>>,
+    Path(id): Path<u64>,
+    Json(payload): Json<WaybillReq>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let admin = require_user(&session).await?;
+    let after_sale = after_sale_service.exchange(admin.id, id, &payload.carrier, &payload.tracking_no).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": after_sale
+    })))
+}
+
+pub async fn admin_add_after_sale_note_handler(
+    session: Session,
+    State(after_sale_service): State<Arc<dyn AfterSaleService>>,
+    Path(id): Path<u64>,
+    Json(payload): Json<AfterSaleNoteReq>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let admin = require_user(&session).await?;
+    let detail = after_sale_service.add_note(admin.id, id, &payload.note).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": detail
+    })))
+}
@@ -0,0 +1,43 @@
+use axum::extract::{Query, State};
+use axum::Json;
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::sync::Arc;
+use crate::handler::order::{default_page, default_page_size};
+use crate::models::audit::AuditQuery;
+use crate::service::ServiceError;
+use crate::service::audit::AuditService;
+
+#[derive(Deserialize)]
+pub struct AuditLogListQuery {
+    pub actor_id: Option<u32>,
+    pub action: Option<String>,
+    pub target_type: Option<String>,
+    pub target_id: Option<String>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    #[serde(default = "default_page")]
+    pub page: u32,
+    #[serde(default = "default_page_size")]
+    pub page_size: u32,
+}
+
+/// 按操作人、接口、操作对象和日期查询审计日志，按时间倒序
+pub async fn admin_list_audit_logs_handler(
+    State(audit_service): State<Arc<dyn AuditService>>,
+    Query(query): Query<AuditLogListQuery>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let filter = AuditQuery {
+        actor_id: query.actor_id,
+        action: query.action,
+        target_type: query.target_type,
+        target_id: query.target_id,
+        from: query.from,
+        to: query.to,
+    };
+    let logs = audit_service.search(filter, query.page, query.page_size).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": logs
+    })))
+}
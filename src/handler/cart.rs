@@ -0,0 +1,146 @@
+use axum::extract::{Path, State};
+use axum::Json;
+use serde::Deserialize;
+use std::sync::Arc;
+use tower_sessions::Session;
+use crate::handler::current_user;
+use crate::models::cart::CartLine;
+use crate::service::ServiceError;
+use crate::service::cart::{self, CartService};
+
+/// 游客购物车在 Session 中的 key
+const GUEST_CART_KEY: &str = "cart";
+
+#[derive(Deserialize)]
+pub struct AddItemReq {
+    pub sku_id: u64,
+    pub quantity: u32,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateQuantityReq {
+    pub quantity: u32,
+}
+
+#[derive(Deserialize)]
+pub struct SelectReq {
+    pub sku_ids: Vec<u64>,
+    pub selected: bool,
+}
+
+async fn guest_lines(session: &Session) -> Result<Vec<CartLine>, ServiceError> {
+    Ok(session.get::<Vec<CartLine>>(GUEST_CART_KEY).await?.unwrap_or_default())
+}
+
+async fn save_guest_lines(session: &Session, lines: &[CartLine]) -> Result<(), ServiceError> {
+    Ok(session.insert(GUEST_CART_KEY, lines).await?)
+}
+
+async fn render(session: &Session, cart_service: &Arc<dyn CartService>) -> Result<Json<serde_json::Value>, ServiceError> {
+    let lines = match current_user(session).await? {
+        Some(user) => cart_service.list(user.id).await?,
+        None => guest_lines(session).await?,
+    };
+    let view = cart_service.view(lines).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": view
+    })))
+}
+
+/// 登录成功后调用：把 Session 中的游客购物车并入用户购物车。
+/// 合并失败不影响登录，游客购物车保留在 Session 中等待下次登录。
+pub async fn merge_guest_cart(session: &Session, cart_service: &Arc<dyn CartService>, user_id: u32) {
+    let lines = match guest_lines(session).await {
+        Ok(lines) => lines,
+        Err(e) => {
+            tracing::error!("Failed to read guest cart: {:?}", e);
+            return;
+        }
+    };
+    if lines.is_empty() {
+        return;
+    }
+    match cart_service.merge(user_id, lines).await {
+        Ok(()) => {
+            if let Err(e) = session.remove::<Vec<CartLine>>(GUEST_CART_KEY).await {
+                tracing::error!("Failed to clear guest cart: {:?}", e);
+            }
+        }
+        Err(e) => tracing::error!("Failed to merge guest cart for user {}: {:?}", user_id, e),
+    }
+}
+
+pub async fn get_cart_handler(
+    session: Session,
+    State(cart_service): State<Arc<dyn CartService>>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    render(&session, &cart_service).await
+}
+
+pub async fn add_item_handler(
+    session: Session,
+    State(cart_service): State<Arc<dyn CartService>>,
+    Json(payload): Json<AddItemReq>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    cart_service.check_line(payload.sku_id, payload.quantity).await?;
+    match current_user(&session).await? {
+        Some(user) => cart_service.add(user.id, payload.sku_id, payload.quantity).await?,
+        None => {
+            let mut lines = guest_lines(&session).await?;
+            cart::add_line(&mut lines, payload.sku_id, payload.quantity)?;
+            save_guest_lines(&session, &lines).await?;
+        }
+    }
+    render(&session, &cart_service).await
+}
+
+pub async fn update_quantity_handler(
+    session: Session,
+    State(cart_service): State<Arc<dyn CartService>>,
+    Path(sku_id): Path<u64>,
+    Json(payload): Json<UpdateQuantityReq>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    cart_service.check_line(sku_id, payload.quantity).await?;
+    match current_user(&session).await? {
+        Some(user) => cart_service.set_quantity(user.id, sku_id, payload.quantity).await?,
+        None => {
+            let mut lines = guest_lines(&session).await?;
+            cart::set_line_quantity(&mut lines, sku_id, payload.quantity)?;
+            save_guest_lines(&session, &lines).await?;
+        }
+    }
+    render(&session, &cart_service).await
+}
+
+pub async fn remove_item_handler(
+    session: Session,
+    State(cart_service): State<Arc<dyn CartService>>,
+    Path(sku_id): Path<u64>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    match current_user(&session).await? {
+        Some(user) => cart_service.remove(user.id, &[sku_id]).await?,
+        None => {
+            let mut lines = guest_lines(&session).await?;
+            cart::remove_lines(&mut lines, &[sku_id]);
+            save_guest_lines(&session, &lines).await?;
+        }
+    }
+    render(&session, &cart_service).await
+}
+
+pub async fn select_items_handler(
+    session: Session,
+    State(cart_service): State<Arc<dyn CartService>>,
+    Json(payload): Json<SelectReq>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    match current_user(&session).await? {
+        Some(user) => cart_service.select(user.id, &payload.sku_ids, payload.selected).await?,
+        None => {
+            let mut lines = guest_lines(&session).await?;
+            cart::select_lines(&mut lines, &payload.sku_ids, payload.selected);
+            save_guest_lines(&session, &lines).await?;
+        }
+    }
+    render(&session, &cart_service).await
+}
@@ -0,0 +1,77 @@
+use axum::extract::{Path, Query, State};
+use axum::Json;
+use std::sync::Arc;
+use tower_sessions::Session;
+use crate::handler::order::PageQuery;
+use crate::handler::require_user;
+use crate::models::coupon::NewCouponTemplate;
+use crate::service::ServiceError;
+use crate::service::coupon::CouponService;
+
+pub async fn list_claimable_coupons_handler(
+    State(coupon_service): State<Arc<dyn CouponService>>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let templates = coupon_service.list_claimable().await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": templates
+    })))
+}
+
+pub async fn claim_coupon_handler(
+    session: Session,
+    State(coupon_service): State<Arc<dyn CouponService>>,
+    Path(template_id): Path<u64>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let coupon = coupon_service.claim(user.id, template_id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": coupon
+    })))
+}
+
+pub async fn list_my_coupons_handler(
+    session: Session,
+    State(coupon_service): State<Arc<dyn CouponService>>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let coupons = coupon_service.list_mine(user.id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": coupons
+    })))
+}
+
+pub async fn admin_create_coupon_template_handler(
+    State(coupon_service): State<Arc<dyn CouponService>>,
+    Json(payload): Json<NewCouponTemplate>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let template = coupon_service.create_template(&payload).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": template
+    })))
+}
+
+pub async fn admin_list_coupon_templates_handler(
+    State(coupon_service): State<Arc<dyn CouponService>>,
+    Query(query): Query<PageQuery>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let templates = coupon_service.list_templates(query.page, query.page_size).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": templates
+    })))
+}
+
+pub async fn admin_disable_coupon_template_handler(
+    State(coupon_service): State<Arc<dyn CouponService>>,
+    Path(id): Path<u64>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let template = coupon_service.disable_template(id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": template
+    })))
+}
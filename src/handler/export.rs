@@ -0,0 +1,69 @@
+use axum::body::Body;
+use axum::extract::{Path, Query, State};
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use std::sync::Arc;
+use tokio_util::io::ReaderStream;
+use tower_sessions::Session;
+use crate::handler::order::PageQuery;
+use crate::handler::require_user;
+use crate::models::export::{ExportFile, ExportRequest};
+use crate::service::ServiceError;
+use crate::service::export::ExportService;
+
+/// 提交导出，文件在后台生成
+pub async fn admin_submit_export_handler(
+    session: Session,
+    State(export_service): State<Arc<dyn ExportService>>,
+    Json(payload): Json<ExportRequest>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let admin = require_user(&session).await?;
+    let export = export_service.submit(payload, admin.id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": export
+    })))
+}
+
+pub async fn admin_list_exports_handler(
+    State(export_service): State<Arc<dyn ExportService>>,
+    Query(query): Query<PageQuery>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let exports = export_service.list(query.page, query.page_size).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": exports
+    })))
+}
+
+/// 导出进度与下载地址
+pub async fn admin_get_export_handler(
+    State(export_service): State<Arc<dyn ExportService>>,
+    Path(id): Path<u64>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let export = export_service.get(id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": export
+    })))
+}
+
+/// 下载导出文件，链接过期后返回 409
+pub async fn admin_download_export_handler(
+    State(export_service): State<Arc<dyn ExportService>>,
+    Path(id): Path<u64>,
+) -> Result<Response, ServiceError> {
+    Ok(attachment(export_service.download(id).await?))
+}
+
+/// 以附件形式按块返回文件内容，不整个读入内存
+pub fn attachment(file: ExportFile) -> Response {
+    let disposition = format!("attachment; filename=\"{}\"", file.file_name);
+    let headers = [
+        (header::CONTENT_TYPE, file.content_type.to_string()),
+        (header::CONTENT_DISPOSITION, disposition),
+        (header::CONTENT_LENGTH, file.content.size.to_string()),
+    ];
+    (headers, Body::from_stream(ReaderStream::new(file.content.reader))).into_response()
+}
@@ -0,0 +1,99 @@
+use axum::extract::{Path, Query, State};
+use axum::Json;
+use std::sync::Arc;
+use tower_sessions::Session;
+use crate::handler::order::PageQuery;
+use crate::handler::require_user;
+use crate::service::ServiceError;
+use crate::service::favorite::FavoriteService;
+
+pub async fn list_favorites_handler(
+    session: Session,
+    State(favorite_service): State<Arc<dyn FavoriteService>>,
+    Query(query): Query<PageQuery>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let favorites = favorite_service.list_favorites(user.id, query.page, query.page_size).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": favorites
+    })))
+}
+
+pub async fn add_favorite_handler(
+    session: Session,
+    State(favorite_service): State<Arc<dyn FavoriteService>>,
+    Path(product_id): Path<u64>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    favorite_service.add_favorite(user.id, product_id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "msg": "ok"
+    })))
+}
+
+pub async fn remove_favorite_handler(
+    session: Session,
+    State(favorite_service): State<Arc<dyn FavoriteService>>,
+    Path(product_id): Path<u64>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    favorite_service.remove_favorite(user.id, product_id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "msg": "ok"
+    })))
+}
+
+/// 最近浏览的商品
+pub async fn list_history_handler(
+    session: Session,
+    State(favorite_service): State<Arc<dyn FavoriteService>>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let history = favorite_service.list_history(user.id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": history
+    })))
+}
+
+/// 商品详情页打开时上报一次浏览
+pub async fn record_view_handler(
+    session: Session,
+    State(favorite_service): State<Arc<dyn FavoriteService>>,
+    Path(product_id): Path<u64>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    favorite_service.record_view(user.id, product_id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "msg": "ok"
+    })))
+}
+
+pub async fn remove_view_handler(
+    session: Session,
+    State(favorite_service): State<Arc<dyn FavoriteService>>,
+    Path(product_id): Path<u64>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    favorite_service.remove_view(user.id, product_id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "msg": "ok"
+    })))
+}
+
+pub async fn clear_history_handler(
+    session: Session,
+    State(favorite_service): State<Arc<dyn FavoriteService>>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    favorite_service.clear_history(user.id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "msg": "ok"
+    })))
+}
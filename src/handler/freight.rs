@@ -0,0 +1,58 @@
+use axum::extract::{Path, State};
+use axum::Json;
+use serde::Deserialize;
+use std::sync::Arc;
+use crate::models::freight::NewFreightTemplate;
+use crate::service::ServiceError;
+use crate::service::freight::FreightService;
+
+#[derive(Deserialize)]
+pub struct AssignFreightReq {
+    /// 为空表示改回统一运费
+    pub template_id: Option<u64>,
+}
+
+pub async fn admin_list_freight_templates_handler(
+    State(freight_service): State<Arc<dyn FreightService>>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let templates = freight_service.list().await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": templates
+    })))
+}
+
+pub async fn admin_create_freight_template_handler(
+    State(freight_service): State<Arc<dyn FreightService>>,
+    Json(payload): Json<NewFreightTemplate>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let template = freight_service.create(&payload).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": template
+    })))
+}
+
+pub async fn admin_update_freight_template_handler(
+    State(freight_service): State<Arc<dyn FreightService>>,
+    Path(id): Path<u64>,
+    Json(payload): Json<NewFreightTemplate>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let template = freight_service.update(id, &payload).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": template
+    })))
+}
+
+pub async fn admin_assign_freight_template_handler(
+    State(freight_service): State<Arc<dyn FreightService>>,
+    Path(product_id): Path<u64>,
+    Json(payload): Json<AssignFreightReq>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    freight_service.assign(product_id, payload.template_id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": null
+    })))
+}
@@ -0,0 +1,98 @@
+use axum::extract::{Path, Query, State};
+use axum::Json;
+use serde::Deserialize;
+use std::sync::Arc;
+use tower_sessions::Session;
+use crate::handler::order::PageQuery;
+use crate::handler::require_user;
+use crate::models::group_buy::NewGroupCampaign;
+use crate::service::ServiceError;
+use crate::service::group_buy::GroupBuyService;
+
+/// 开团、参团请求
+#[derive(Deserialize)]
+pub struct GroupCheckoutReq {
+    pub address_id: u64,
+}
+
+pub async fn list_group_campaigns_handler(
+    State(group_buy_service): State<Arc<dyn GroupBuyService>>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let campaigns = group_buy_service.list_campaigns().await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": campaigns
+    })))
+}
+
+pub async fn get_group_handler(
+    State(group_buy_service): State<Arc<dyn GroupBuyService>>,
+    Path(group_id): Path<u64>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let group = group_buy_service.get(group_id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": group
+    })))
+}
+
+pub async fn open_group_handler(
+    session: Session,
+    State(group_buy_service): State<Arc<dyn GroupBuyService>>,
+    Path(campaign_id): Path<u64>,
+    Json(payload): Json<GroupCheckoutReq>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let group_order = group_buy_service.open(user.id, campaign_id, payload.address_id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": group_order
+    })))
+}
+
+pub async fn join_group_handler(
+    session: Session,
+    State(group_buy_service): State<Arc<dyn GroupBuyService>>,
+    Path(group_id): Path<u64>,
+    Json(payload): Json<GroupCheckoutReq>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let group_order = group_buy_service.join(user.id, group_id, payload.address_id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": group_order
+    })))
+}
+
+pub async fn admin_create_group_campaign_handler(
+    State(group_buy_service): State<Arc<dyn GroupBuyService>>,
+    Json(payload): Json<NewGroupCampaign>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let campaign = group_buy_service.create_campaign(&payload).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": campaign
+    })))
+}
+
+pub async fn admin_list_group_campaigns_handler(
+    State(group_buy_service): State<Arc<dyn GroupBuyService>>,
+    Query(query): Query<PageQuery>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let campaigns = group_buy_service.list_all_campaigns(query.page, query.page_size).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": campaigns
+    })))
+}
+
+pub async fn admin_disable_group_campaign_handler(
+    State(group_buy_service): State<Arc<dyn GroupBuyService>>,
+    Path(id): Path<u64>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let campaign = group_buy_service.disable_campaign(id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": campaign
+    })))
+}
@@ -0,0 +1,69 @@
+use axum::body::Bytes;
+use axum::extract::{Path, Query, State};
+use axum::response::Response;
+use axum::Json;
+use serde::Deserialize;
+use std::sync::Arc;
+use tower_sessions::Session;
+use crate::handler::order::PageQuery;
+use crate::handler::{export, require_user};
+use crate::models::export::ExportFormat;
+use crate::service::ServiceError;
+use crate::service::import::ImportService;
+
+/// 上传文件的大小上限
+pub const MAX_IMPORT_BYTES: usize = 10 * 1024 * 1024;
+
+#[derive(Deserialize)]
+pub struct ImportQuery {
+    pub format: ExportFormat,
+    /// 只校验并预览，不写入
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// 请求体为 CSV 或 XLSX 文件内容，导入在后台执行
+pub async fn admin_submit_import_handler(
+    session: Session,
+    State(import_service): State<Arc<dyn ImportService>>,
+    Query(query): Query<ImportQuery>,
+    body: Bytes,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let admin = require_user(&session).await?;
+    let import = import_service.submit(query.format, query.dry_run, body.to_vec(), admin.id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": import
+    })))
+}
+
+pub async fn admin_list_imports_handler(
+    State(import_service): State<Arc<dyn ImportService>>,
+    Query(query): Query<PageQuery>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let imports = import_service.list(query.page, query.page_size).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": imports
+    })))
+}
+
+/// 导入进度与统计
+pub async fn admin_get_import_handler(
+    State(import_service): State<Arc<dyn ImportService>>,
+    Path(id): Path<u64>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let import = import_service.get(id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": import
+    })))
+}
+
+/// 下载逐行结果文件
+pub async fn admin_download_import_report_handler(
+    State(import_service): State<Arc<dyn ImportService>>,
+    Path(id): Path<u64>,
+) -> Result<Response, ServiceError> {
+    Ok(export::attachment(import_service.report(id).await?))
+}
@@ -0,0 +1,16 @@
+use axum::extract::{Path, State};
+use axum::Json;
+use std::sync::Arc;
+use crate::service::ServiceError;
+use crate::service::inventory::InventoryService;
+
+pub async fn get_stock_handler(
+    State(inventory_service): State<Arc<dyn InventoryService>>,
+    Path(sku_id): Path<u64>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let stock = inventory_service.available(sku_id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": {"sku_id": sku_id, "stock": stock}
+    })))
+}
@@ -0,0 +1,22 @@
+use axum::extract::{Query, State};
+use axum::Json;
+use std::sync::Arc;
+use tower_sessions::Session;
+use crate::handler::order::PageQuery;
+use crate::handler::require_user;
+use crate::service::ServiceError;
+use crate::service::loyalty::LoyaltyService;
+
+/// 积分余额、会员等级与积分流水
+pub async fn my_points_handler(
+    session: Session,
+    State(loyalty_service): State<Arc<dyn LoyaltyService>>,
+    Query(query): Query<PageQuery>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let points = loyalty_service.my_points(user.id, query.page, query.page_size).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": points
+    })))
+}
@@ -1,2 +1,43 @@
 pub mod users ;
-pub mod index;
\ No newline at end of file
+pub mod index;
+pub mod inventory;
+pub mod cart;
+pub mod address;
+pub mod order;
+pub mod payment;
+pub mod refund;
+pub mod reconciliation;
+pub mod coupon;
+pub mod promotion;
+pub mod group_buy;
+pub mod freight;
+pub mod shipment;
+pub mod after_sale;
+pub mod review;
+pub mod notification;
+pub mod loyalty;
+pub mod favorite;
+pub mod referral;
+pub mod stats;
+pub mod export;
+pub mod import;
+pub mod audit;
+
+use tower_sessions::Session;
+use crate::models;
+use crate::service::ServiceError;
+
+/// Session 中保存登录用户的 key，与 `require_login` 保持一致
+pub const SESSION_USER_KEY: &str = "user";
+/// Session 中保存小程序 openid 的 key，登录时用 `wx.login` 的 code 换取
+pub const SESSION_OPENID_KEY: &str = "openid";
+
+/// 读取当前登录用户，游客返回 `None`
+pub async fn current_user(session: &Session) -> Result<Option<models::User>, ServiceError> {
+    Ok(session.get::<models::User>(SESSION_USER_KEY).await?)
+}
+
+/// 读取当前登录用户，游客返回 `ServiceError::Unauthorized`
+pub async fn require_user(session: &Session) -> Result<models::User, ServiceError> {
+    current_user(session).await?.ok_or(ServiceError::Unauthorized)
+}
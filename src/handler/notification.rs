@@ -0,0 +1,58 @@
+use axum::extract::{Query, State};
+use axum::Json;
+use serde::Deserialize;
+use std::sync::Arc;
+use tower_sessions::Session;
+use crate::handler::order::{default_page, default_page_size};
+use crate::handler::require_user;
+use crate::models::notification::{DeliveryStatus, SubscribeApply};
+use crate::service::ServiceError;
+use crate::service::notification::NotificationService;
+
+#[derive(Deserialize)]
+pub struct DeliveryListQuery {
+    #[serde(default = "default_status")]
+    pub status: String,
+    #[serde(default = "default_page")]
+    pub page: u32,
+    #[serde(default = "default_page_size")]
+    pub page_size: u32,
+}
+
+fn default_status() -> String {
+    DeliveryStatus::Failed.as_str().to_string()
+}
+
+/// 订单状态到模板 ID，小程序端用于调起 `wx.requestSubscribeMessage`
+pub async fn list_templates_handler(
+    State(notification_service): State<Arc<dyn NotificationService>>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": notification_service.templates()
+    })))
+}
+
+pub async fn subscribe_handler(
+    session: Session,
+    State(notification_service): State<Arc<dyn NotificationService>>,
+    Json(payload): Json<SubscribeApply>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let subscriptions = notification_service.subscribe(user.id, &payload).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": subscriptions
+    })))
+}
+
+pub async fn admin_list_deliveries_handler(
+    State(notification_service): State<Arc<dyn NotificationService>>,
+    Query(query): Query<DeliveryListQuery>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let deliveries = notification_service.list_deliveries(&query.status, query.page, query.page_size).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": deliveries
+    })))
+}
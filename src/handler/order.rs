@@ -0,0 +1,131 @@
+use axum::extract::{Path, Query, State};
+use axum::Json;
+use serde::Deserialize;
+use std::sync::Arc;
+use tower_sessions::Session;
+use crate::handler::require_user;
+use crate::service::ServiceError;
+use crate::service::order::OrderService;
+
+#[derive(Deserialize)]
+pub struct PreviewReq {
+    pub address_id: u64,
+    /// 使用的用户优惠券 ID
+    #[serde(default)]
+    pub coupon_id: Option<u64>,
+    /// 希望抵扣的积分
+    #[serde(default)]
+    pub points: i64,
+}
+
+#[derive(Deserialize)]
+pub struct CreateOrderReq {
+    pub address_id: u64,
+    #[serde(default)]
+    pub coupon_id: Option<u64>,
+    #[serde(default)]
+    pub points: i64,
+    /// 预览时返回的应付金额（分），与下单时重新计算的结果不一致则拒绝
+    pub expected_payable: i64,
+}
+
+#[derive(Deserialize)]
+pub struct CancelOrderReq {
+    #[serde(default)]
+    pub reason: String,
+}
+
+#[derive(Deserialize)]
+pub struct PageQuery {
+    #[serde(default = "default_page")]
+    pub page: u32,
+    #[serde(default = "default_page_size")]
+    pub page_size: u32,
+}
+
+pub fn default_page() -> u32 {
+    1
+}
+
+pub fn default_page_size() -> u32 {
+    20
+}
+
+pub async fn preview_order_handler(
+    session: Session,
+    State(order_service): State<Arc<dyn OrderService>>,
+    Json(payload): Json<PreviewReq>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let quote = order_service.preview(user.id, payload.address_id, payload.coupon_id, payload.points).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": quote
+    })))
+}
+
+pub async fn create_order_handler(
+    session: Session,
+    State(order_service): State<Arc<dyn OrderService>>,
+    Json(payload): Json<CreateOrderReq>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let order = order_service.place(user.id, payload.address_id, payload.coupon_id, payload.points, payload.expected_payable).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": order
+    })))
+}
+
+pub async fn get_order_handler(
+    session: Session,
+    State(order_service): State<Arc<dyn OrderService>>,
+    Path(id): Path<u64>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let order = order_service.get(user.id, id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": order
+    })))
+}
+
+pub async fn list_orders_handler(
+    session: Session,
+    State(order_service): State<Arc<dyn OrderService>>,
+    Query(query): Query<PageQuery>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let orders = order_service.list(user.id, query.page, query.page_size).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": orders
+    })))
+}
+
+pub async fn cancel_order_handler(
+    session: Session,
+    State(order_service): State<Arc<dyn OrderService>>,
+    Path(id): Path<u64>,
+    Json(payload): Json<CancelOrderReq>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let order = order_service.cancel(user.id, id, &payload.reason).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": order
+    })))
+}
+
+pub async fn list_order_events_handler(
+    session: Session,
+    State(order_service): State<Arc<dyn OrderService>>,
+    Path(id): Path<u64>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let events = order_service.events(user.id, id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": events
+    })))
+}
@@ -0,0 +1,79 @@
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use std::sync::Arc;
+use tower_sessions::Session;
+use crate::domain::payment::{GatewayError, NotifyHeaders};
+use crate::handler::{SESSION_OPENID_KEY, require_user};
+use crate::service::ServiceError;
+use crate::service::payment::PaymentService;
+
+/// 付款人 openid 取自登录时绑定到 Session 的值，不接受客户端传入
+pub async fn jsapi_pay_handler(
+    session: Session,
+    State(payment_service): State<Arc<dyn PaymentService>>,
+    Path(order_id): Path<u64>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let openid = session
+        .get::<String>(SESSION_OPENID_KEY)
+        .await?
+        .ok_or_else(|| ServiceError::BadRequest("please log in from the mini program before paying".to_string()))?;
+    let params = payment_service.jsapi_pay(user.id, order_id, &openid).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": params
+    })))
+}
+
+/// 小程序 `requestPayment` 返回后调用，主动查单并返回最新订单详情
+pub async fn sync_payment_handler(
+    session: Session,
+    State(payment_service): State<Arc<dyn PaymentService>>,
+    Path(order_id): Path<u64>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let detail = payment_service.sync(user.id, order_id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": detail
+    })))
+}
+
+/// 微信支付结果通知。成功时返回 204 且无应答报文；失败时返回非 2xx 和
+/// `{"code": "FAIL", "message": ...}`，微信会按策略重发。
+pub async fn wechat_notify_handler(
+    State(payment_service): State<Arc<dyn PaymentService>>,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    let Some(notify_headers) = notify_headers(&headers) else {
+        return notify_fail(StatusCode::BAD_REQUEST, "missing signature headers");
+    };
+    match payment_service.handle_wechat_notify(&notify_headers, &body).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(ServiceError::Gateway(GatewayError::Verify(msg))) => {
+            tracing::warn!("Rejected wechat pay notification: {}", msg);
+            notify_fail(StatusCode::UNAUTHORIZED, "signature verification failed")
+        }
+        Err(e) => {
+            tracing::error!("Failed to handle wechat pay notification: {:?}", e);
+            notify_fail(StatusCode::INTERNAL_SERVER_ERROR, "internal error")
+        }
+    }
+}
+
+pub fn notify_headers(headers: &HeaderMap) -> Option<NotifyHeaders> {
+    let get = |name: &str| headers.get(name).and_then(|value| value.to_str().ok()).map(str::to_string);
+    Some(NotifyHeaders {
+        timestamp: get("wechatpay-timestamp")?,
+        nonce: get("wechatpay-nonce")?,
+        signature: get("wechatpay-signature")?,
+        serial: get("wechatpay-serial")?,
+    })
+}
+
+pub fn notify_fail(status: StatusCode, message: &str) -> Response {
+    (status, Json(serde_json::json!({"code": "FAIL", "message": message}))).into_response()
+}
@@ -0,0 +1,50 @@
+use axum::extract::{Path, Query, State};
+use axum::Json;
+use std::sync::Arc;
+use crate::handler::order::PageQuery;
+use crate::models::promotion::NewPromotion;
+use crate::service::ServiceError;
+use crate::service::promotion::PromotionService;
+
+pub async fn list_active_promotions_handler(
+    State(promotion_service): State<Arc<dyn PromotionService>>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let promotions = promotion_service.list_active().await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": promotions
+    })))
+}
+
+pub async fn admin_create_promotion_handler(
+    State(promotion_service): State<Arc<dyn PromotionService>>,
+    Json(payload): Json<NewPromotion>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let promotion = promotion_service.create(&payload).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": promotion
+    })))
+}
+
+pub async fn admin_list_promotions_handler(
+    State(promotion_service): State<Arc<dyn PromotionService>>,
+    Query(query): Query<PageQuery>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let promotions = promotion_service.list(query.page, query.page_size).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": promotions
+    })))
+}
+
+pub async fn admin_disable_promotion_handler(
+    State(promotion_service): State<Arc<dyn PromotionService>>,
+    Path(id): Path<u64>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let promotion = promotion_service.disable(id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": promotion
+    })))
+}
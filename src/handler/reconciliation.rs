@@ -0,0 +1,38 @@
+use axum::extract::{Path, Query, State};
+use axum::Json;
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::sync::Arc;
+use crate::handler::order::{default_page, default_page_size};
+use crate::service::ServiceError;
+use crate::service::reconciliation::ReconciliationService;
+
+#[derive(Deserialize)]
+pub struct ReconciliationListQuery {
+    #[serde(default = "default_page")]
+    pub page: u32,
+    #[serde(default = "default_page_size")]
+    pub page_size: u32,
+}
+
+pub async fn admin_list_reconciliations_handler(
+    State(reconciliation_service): State<Arc<dyn ReconciliationService>>,
+    Query(query): Query<ReconciliationListQuery>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let runs = reconciliation_service.list(query.page, query.page_size).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": runs
+    })))
+}
+
+pub async fn admin_get_reconciliation_handler(
+    State(reconciliation_service): State<Arc<dyn ReconciliationService>>,
+    Path(bill_date): Path<NaiveDate>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let report = reconciliation_service.get(bill_date).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": report
+    })))
+}
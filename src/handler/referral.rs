@@ -0,0 +1,76 @@
+use axum::extract::{Query, State};
+use axum::Json;
+use serde::Deserialize;
+use std::sync::Arc;
+use tower_sessions::Session;
+use crate::handler::order::{default_page, default_page_size, PageQuery};
+use crate::handler::require_user;
+use crate::models::referral::{BindReferral, CommissionStatus};
+use crate::service::ServiceError;
+use crate::service::referral::ReferralService;
+
+#[derive(Deserialize)]
+pub struct CommissionListQuery {
+    #[serde(default = "default_status")]
+    pub status: String,
+    #[serde(default = "default_page")]
+    pub page: u32,
+    #[serde(default = "default_page_size")]
+    pub page_size: u32,
+}
+
+fn default_status() -> String {
+    CommissionStatus::Pending.as_str().to_string()
+}
+
+/// 自己的推广码和分享路径
+pub async fn share_info_handler(
+    session: Session,
+    State(referral_service): State<Arc<dyn ReferralService>>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let share = referral_service.share_info(user.id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": share
+    })))
+}
+
+/// 通过分享链接进入后上报推广码
+pub async fn bind_referral_handler(
+    session: Session,
+    State(referral_service): State<Arc<dyn ReferralService>>,
+    Json(payload): Json<BindReferral>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let binding = referral_service.bind(user.id, &payload.code).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": binding
+    })))
+}
+
+pub async fn my_commissions_handler(
+    session: Session,
+    State(referral_service): State<Arc<dyn ReferralService>>,
+    Query(query): Query<PageQuery>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let commissions = referral_service.my_commissions(user.id, query.page, query.page_size).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": commissions
+    })))
+}
+
+pub async fn admin_list_commissions_handler(
+    State(referral_service): State<Arc<dyn ReferralService>>,
+    Query(query): Query<CommissionListQuery>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let status = query.status.parse().map_err(ServiceError::BadRequest)?;
+    let commissions = referral_service.list_by_status(status, query.page, query.page_size).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": commissions
+    })))
+}
@@ -0,0 +1,128 @@
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::Deserialize;
+use std::sync::Arc;
+use tower_sessions::Session;
+use crate::domain::payment::GatewayError;
+use crate::handler::order::{default_page, default_page_size};
+use crate::handler::payment::{notify_fail, notify_headers};
+use crate::handler::require_user;
+use crate::models::refund::{RefundApply, RefundStatus};
+use crate::service::ServiceError;
+use crate::service::refund::RefundService;
+
+#[derive(Deserialize)]
+pub struct RefundListQuery {
+    #[serde(default = "default_status")]
+    pub status: String,
+    #[serde(default = "default_page")]
+    pub page: u32,
+    #[serde(default = "default_page_size")]
+    pub page_size: u32,
+}
+
+fn default_status() -> String {
+    RefundStatus::Pending.as_str().to_string()
+}
+
+#[derive(Deserialize)]
+pub struct ApproveRefundReq {
+    /// 已发货订单确认退货入库后回补库存
+    #[serde(default)]
+    pub restock: bool,
+}
+
+#[derive(Deserialize)]
+pub struct RejectRefundReq {
+    pub reason: String,
+}
+
+pub async fn apply_refund_handler(
+    session: Session,
+    State(refund_service): State<Arc<dyn RefundService>>,
+    Path(order_id): Path<u64>,
+    Json(payload): Json<RefundApply>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let refund = refund_service.apply(user.id, order_id, &payload).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": refund
+    })))
+}
+
+pub async fn list_order_refunds_handler(
+    session: Session,
+    State(refund_service): State<Arc<dyn RefundService>>,
+    Path(order_id): Path<u64>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let refunds = refund_service.list_for_order(user.id, order_id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": refunds
+    })))
+}
+
+pub async fn admin_list_refunds_handler(
+    State(refund_service): State<Arc<dyn RefundService>>,
+    Query(query): Query<RefundListQuery>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let refunds = refund_service.list_by_status(&query.status, query.page, query.page_size).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": refunds
+    })))
+}
+
+pub async fn admin_approve_refund_handler(
+    session: Session,
+    State(refund_service): State<Arc<dyn RefundService>>,
+    Path(id): Path<u64>,
+    Json(payload): Json<ApproveRefundReq>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let admin = require_user(&session).await?;
+    let refund = refund_service.approve(admin.id, id, payload.restock).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": refund
+    })))
+}
+
+pub async fn admin_reject_refund_handler(
+    session: Session,
+    State(refund_service): State<Arc<dyn RefundService>>,
+    Path(id): Path<u64>,
+    Json(payload): Json<RejectRefundReq>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let admin = require_user(&session).await?;
+    let refund = refund_service.reject(admin.id, id, payload.reason.trim()).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": refund
+    })))
+}
+
+/// 微信退款结果通知，应答格式与支付结果通知相同
+pub async fn wechat_refund_notify_handler(
+    State(refund_service): State<Arc<dyn RefundService>>,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    let Some(notify_headers) = notify_headers(&headers) else {
+        return notify_fail(StatusCode::BAD_REQUEST, "missing signature headers");
+    };
+    match refund_service.handle_wechat_notify(&notify_headers, &body).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(ServiceError::Gateway(GatewayError::Verify(msg))) => {
+            tracing::warn!("Rejected wechat refund notification: {}", msg);
+            notify_fail(StatusCode::UNAUTHORIZED, "signature verification failed")
+        }
+        Err(e) => {
+            tracing::error!("Failed to handle wechat refund notification: {:?}", e);
+            notify_fail(StatusCode::INTERNAL_SERVER_ERROR, "internal error")
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use axum::extract::{Path, Query, State};
+use axum::Json;
+use serde::Deserialize;
+use std::sync::Arc;
+use tower_sessions::Session;
+use crate::handler::order::{default_page, default_page_size, PageQuery};
+use crate::handler::require_user;
+use crate::models::review::{FollowUpApply, ReviewApply, ReviewFilter, ReviewStatus};
+use crate::service::ServiceError;
+use crate::service::review::ReviewService;
+
+#[derive(Deserialize)]
+pub struct ReviewListQuery {
+    #[serde(default = "default_status")]
+    pub status: String,
+    #[serde(default = "default_page")]
+    pub page: u32,
+    #[serde(default = "default_page_size")]
+    pub page_size: u32,
+}
+
+fn default_status() -> String {
+    ReviewStatus::Pending.as_str().to_string()
+}
+
+#[derive(Deserialize)]
+pub struct ReplyReviewReq {
+    pub reply: String,
+}
+
+pub async fn create_review_handler(
+    session: Session,
+    State(review_service): State<Arc<dyn ReviewService>>,
+    Path(order_id): Path<u64>,
+    Json(payload): Json<ReviewApply>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let review = review_service.create(user.id, order_id, &payload).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": review
+    })))
+}
+
+pub async fn follow_up_review_handler(
+    session: Session,
+    State(review_service): State<Arc<dyn ReviewService>>,
+    Path(id): Path<u64>,
+    Json(payload): Json<FollowUpApply>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let review = review_service.follow_up(user.id, id, &payload).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": review
+    })))
+}
+
+pub async fn list_my_reviews_handler(
+    session: Session,
+    State(review_service): State<Arc<dyn ReviewService>>,
+    Query(query): Query<PageQuery>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let reviews = review_service.list_mine(user.id, query.page, query.page_size).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": reviews
+    })))
+}
+
+pub async fn list_product_reviews_handler(
+    State(review_service): State<Arc<dyn ReviewService>>,
+    Path(product_id): Path<u64>,
+    Query(filter): Query<ReviewFilter>,
+    Query(query): Query<PageQuery>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let reviews = review_service.product_reviews(product_id, &filter, query.page, query.page_size).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": reviews
+    })))
+}
+
+pub async fn admin_list_reviews_handler(
+    State(review_service): State<Arc<dyn ReviewService>>,
+    Query(query): Query<ReviewListQuery>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let reviews = review_service.list_by_status(&query.status, query.page, query.page_size).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": reviews
+    })))
+}
+
+pub async fn admin_approve_review_handler(
+    State(review_service): State<Arc<dyn ReviewService>>,
+    Path(id): Path<u64>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let review = review_service.moderate(id, ReviewStatus::Approved).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": review
+    })))
+}
+
+pub async fn admin_hide_review_handler(
+    State(review_service): State<Arc<dyn ReviewService>>,
+    Path(id): Path<u64>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let review = review_service.moderate(id, ReviewStatus::Hidden).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": review
+    })))
+}
+
+pub async fn admin_reply_review_handler(
+    State(review_service): State<Arc<dyn ReviewService>>,
+    Path(id): Path<u64>,
+    Json(payload): Json<ReplyReviewReq>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let review = review_service.reply(id, &payload.reply).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": review
+    })))
+}
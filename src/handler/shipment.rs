@@ -0,0 +1,55 @@
+use axum::extract::{Path, State};
+use axum::Json;
+use serde::Deserialize;
+use std::sync::Arc;
+use tower_sessions::Session;
+use crate::handler::require_user;
+use crate::service::ServiceError;
+use crate::service::shipment::ShipmentService;
+
+#[derive(Deserialize)]
+pub struct ShipOrderReq {
+    /// 快递公司编码，如 yuantong、shunfeng
+    pub carrier: String,
+    pub tracking_no: String,
+}
+
+pub async fn admin_ship_order_handler(
+    session: Session,
+    State(shipment_service): State<Arc<dyn ShipmentService>>,
+    Path(id): Path<u64>,
+    Json(payload): Json<ShipOrderReq>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let admin = require_user(&session).await?;
+    let shipment = shipment_service.ship(admin.id, id, &payload.carrier, &payload.tracking_no).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": shipment
+    })))
+}
+
+pub async fn order_tracking_handler(
+    session: Session,
+    State(shipment_service): State<Arc<dyn ShipmentService>>,
+    Path(id): Path<u64>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let tracking = shipment_service.tracking(user.id, id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": tracking
+    })))
+}
+
+pub async fn confirm_receipt_handler(
+    session: Session,
+    State(shipment_service): State<Arc<dyn ShipmentService>>,
+    Path(id): Path<u64>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let user = require_user(&session).await?;
+    let order = shipment_service.confirm_receipt(user.id, id).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": order
+    })))
+}
@@ -0,0 +1,69 @@
+use axum::extract::{Query, State};
+use axum::Json;
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::sync::Arc;
+use crate::models::stats::Granularity;
+use crate::service::ServiceError;
+use crate::service::stats::StatsService;
+
+#[derive(Deserialize)]
+pub struct OverviewQuery {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    #[serde(default)]
+    pub group_by: Granularity,
+}
+
+#[derive(Deserialize)]
+pub struct TopProductsQuery {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    #[serde(default = "default_limit")]
+    pub limit: u32,
+}
+
+fn default_limit() -> u32 {
+    10
+}
+
+#[derive(Deserialize)]
+pub struct RollupReq {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+/// GMV、订单数、支付用户数、客单价与退款率，按日 / 周 / 月分组
+pub async fn admin_stats_overview_handler(
+    State(stats_service): State<Arc<dyn StatsService>>,
+    Query(query): Query<OverviewQuery>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let overview = stats_service.overview(query.from, query.to, query.group_by).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": overview
+    })))
+}
+
+pub async fn admin_top_products_handler(
+    State(stats_service): State<Arc<dyn StatsService>>,
+    Query(query): Query<TopProductsQuery>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let products = stats_service.top_products(query.from, query.to, query.limit).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": products
+    })))
+}
+
+/// 手动重新汇总一段日期，用于补数或刷新当天数据
+pub async fn admin_rollup_stats_handler(
+    State(stats_service): State<Arc<dyn StatsService>>,
+    Json(payload): Json<RollupReq>,
+) -> Result<Json<serde_json::Value>, ServiceError> {
+    let days = stats_service.rollup_range(payload.from, payload.to).await?;
+    Ok(Json(serde_json::json!({
+        "code": 0,
+        "data": { "days": days }
+    })))
+}
@@ -4,8 +4,11 @@ use axum::extract::Path;
 use serde::Deserialize;
 use serde_json;
 use sha2::{Sha256, Digest};
+use std::sync::Arc;
 use crate::AppState;
+use crate::handler::{cart, SESSION_OPENID_KEY, SESSION_USER_KEY};
 use crate::service::ServiceError;
+use crate::service::users::UserService;
 
 #[derive(Deserialize)]
 pub struct HashReq {
@@ -28,6 +31,28 @@ pub async fn hash_handler(Json(payload): Json<HashReq>) -> Json<serde_json::Valu
 pub struct LoginReq {
     pub username: String,
     pub passwd: String,
+    /// 小程序内登录时带上 `wx.login` 返回的 code，换取的 openid 用于微信支付
+    #[serde(default)]
+    pub code: Option<String>,
+}
+
+/// 用登录凭证换取 openid 存入 Session，换取失败不影响登录，付款时再提示重新登录
+async fn bind_openid(session: &tower_sessions::Session, app_state: &AppState, user_id: u32, code: Option<&str>) {
+    if let Err(e) = session.remove::<String>(SESSION_OPENID_KEY).await {
+        tracing::error!("Failed to clear openid: {:?}", e);
+        return;
+    }
+    let Some(code) = code.map(str::trim).filter(|code| !code.is_empty()) else {
+        return;
+    };
+    match app_state.wechat_auth.code_to_openid(code).await {
+        Ok(openid) => {
+            if let Err(e) = session.insert(SESSION_OPENID_KEY, openid).await {
+                tracing::error!("Failed to save openid for user {}: {:?}", user_id, e);
+            }
+        }
+        Err(e) => tracing::error!("Failed to exchange wx.login code for user {}: {}", user_id, e),
+    }
 }
 
 
@@ -38,12 +63,15 @@ pub async fn login_handler(
 ) -> Result<Json<serde_json::Value>, StatusCode> {
     match app_state.user_service.login(&payload.username, &payload.passwd).await {
         Ok(user) => {
-            if let Err(e) = session.insert("user", user).await {
-                return Ok(Json(serde_json::json!({
+            let user_id = user.id;
+            if let Err(e) = session.insert(SESSION_USER_KEY, user).await {
+                Ok(Json(serde_json::json!({
                     "code": 5000,
                     "msg": format!("Session error: {}", e)
-                })));
+                })))
             } else {
+                bind_openid(&session, &app_state, user_id, payload.code.as_deref()).await;
+                cart::merge_guest_cart(&session, &app_state.cart_service, user_id).await;
                 Ok(Json(serde_json::json!({
                     "code": 0,
                     "msg": "login success"
@@ -60,10 +88,10 @@ pub async fn login_handler(
 
 
 pub async fn get_user_by_id_handler(
-    State(app_state): State<AppState>,
+    State(user_service): State<Arc<dyn UserService>>,
     Path(id): Path<u32>,
 ) -> Result<Json<serde_json::Value>, ServiceError> {
-    let user = app_state.user_service.find_user_by_id(id).await?;
+    let user = user_service.find_user_by_id(id).await?;
     Ok(Json(serde_json::json!({
         "code": 0,
         "data": user
@@ -73,8 +101,6 @@ pub async fn get_user_by_id_handler(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::sync::Arc;
-    use crate::service::users::UserService;
     use crate::models;
     use std::future::Future;
     use std::pin::Pin;
@@ -88,15 +114,15 @@ mod tests {
 
         fn find_user_by_id<'a>(&'a self, id:u32) -> Pin<Box<dyn Future<Output = Result<models::User, ServiceError>> + Send + 'a>> {
             Box::pin(async move {
-                Ok(models::User { id, username: "u".into(), passwd: "p".into(), salt: "s".into(), created_at: None, updated_at: None })
+                Ok(models::User { id, username: "u".into(), passwd: "p".into(), salt: "s".into(), role: "user".into(), created_at: None, updated_at: None })
             })
         }
     }
 
     #[tokio::test]
     async fn test_get_user_by_id_handler() {
-        let app_state = AppState { user_service: Arc::new(MockService) };
-        let resp = get_user_by_id_handler(State(app_state), Path(1)).await.unwrap();
+        let user_service: Arc<dyn UserService> = Arc::new(MockService);
+        let resp = get_user_by_id_handler(State(user_service), Path(1)).await.unwrap();
         let v = resp.0;
         assert_eq!(v["code"], 0);
         assert_eq!(v["data"]["id"], 1);
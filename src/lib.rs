@@ -1,6 +1,7 @@
 use serde::Deserialize;
 use sqlx::mysql::MySqlPoolOptions;
 use sqlx::{MySql, Pool};
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 use tower_sessions_redis_store::fred::{clients::Pool as RedisPool, interfaces::ClientLike, prelude::Config};
 
@@ -26,12 +27,443 @@ pub struct LogSettings {
     pub level: String,
 }
 
+/// 库存配置结构
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct InventorySettings {
+    /// 秒杀库存回写 MySQL 的轮询间隔（秒）
+    pub reconcile_interval_secs: u64,
+    /// 每轮最多回写的流水条数
+    pub reconcile_batch_size: usize,
+}
+
+impl Default for InventorySettings {
+    fn default() -> Self {
+        Self {
+            reconcile_interval_secs: 5,
+            reconcile_batch_size: 500,
+        }
+    }
+}
+
+/// 订单配置结构，金额单位：分
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct OrderSettings {
+    /// 未设置运费模板的商品统一收取的运费
+    pub shipping_fee: i64,
+    /// 上述商品应付达到该金额包邮，0 表示不设包邮
+    pub free_shipping_threshold: i64,
+    /// 待支付订单超过该时长自动取消（分钟）
+    pub payment_timeout_minutes: u64,
+    /// 签收后超过该天数自动确认完成，完成后才发放积分
+    pub auto_complete_days: u32,
+}
+
+impl Default for OrderSettings {
+    fn default() -> Self {
+        Self {
+            shipping_fee: 0,
+            free_shipping_threshold: 0,
+            payment_timeout_minutes: 30,
+            auto_complete_days: 7,
+        }
+    }
+}
+
+/// 会员等级：近 `tier_window_days` 天已完成订单的实付金额达到 `min_spend`（分）即可升级
+#[derive(Debug, Deserialize, Clone)]
+pub struct MemberTier {
+    pub level: u32,
+    pub name: String,
+    pub min_spend: i64,
+}
+
+/// 积分与会员等级配置结构
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct LoyaltySettings {
+    /// 订单完成后每实付 1 元获得的积分
+    pub earn_points_per_yuan: u32,
+    /// 按类目覆盖上面的倍率，键为类目 ID
+    pub category_points_per_yuan: HashMap<String, u32>,
+    /// 结算时 1 积分抵扣的金额（分）
+    pub cents_per_point: i64,
+    /// 积分最多抵扣商品应付金额的百分比
+    pub max_redeem_percent: u32,
+    /// 积分自获得起的有效期（天）
+    pub expire_after_days: u32,
+    /// 每天几点（本地时间）清理过期积分并重新计算会员等级
+    pub run_at_hour: u32,
+    /// 会员等级按最近多少天的消费计算
+    pub tier_window_days: u32,
+    /// 会员等级，未达到任何等级时为 0 级
+    pub tiers: Vec<MemberTier>,
+}
+
+impl Default for LoyaltySettings {
+    fn default() -> Self {
+        Self {
+            earn_points_per_yuan: 1,
+            category_points_per_yuan: HashMap::new(),
+            cents_per_point: 1,
+            max_redeem_percent: 50,
+            expire_after_days: 365,
+            run_at_hour: 3,
+            tier_window_days: 365,
+            tiers: Vec::new(),
+        }
+    }
+}
+
+/// 分销配置结构
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ReferralSettings {
+    /// 各层级的佣金比例（万分比），第一个为直接推广人；为空时不计佣金
+    pub level_rates_bp: Vec<u32>,
+    /// 客户归属的有效期（天），有效期内不会被其他推广人覆盖
+    pub attribution_days: u32,
+    /// 只允许没有支付过订单的新客户绑定推广人
+    pub new_customers_only: bool,
+    /// 订单完成后的售后期（天），期满后佣金才可提现
+    pub settle_after_days: u32,
+    /// 每天几点（本地时间）结算到期的佣金
+    pub run_at_hour: u32,
+    /// 分享打开的小程序页面，`{code}` 会替换为推广码
+    pub share_path: String,
+}
+
+impl Default for ReferralSettings {
+    fn default() -> Self {
+        Self {
+            level_rates_bp: Vec::new(),
+            attribution_days: 30,
+            new_customers_only: true,
+            settle_after_days: 7,
+            run_at_hour: 4,
+            share_path: "pages/index/index?ref={code}".to_string(),
+        }
+    }
+}
+
+/// 经营数据汇总配置结构
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct StatsSettings {
+    /// 每天几点（本地时间）汇总前一天的数据
+    pub run_at_hour: u32,
+    /// 汇总时顺带重新计算之前几天，覆盖迟到的支付结果与退款
+    pub lookback_days: u32,
+    /// 单次查询或重新汇总的最大天数
+    pub max_range_days: u32,
+}
+
+impl Default for StatsSettings {
+    fn default() -> Self {
+        Self { run_at_hour: 1, lookback_days: 2, max_range_days: 366 }
+    }
+}
+
+/// 文件存储配置结构
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct BlobSettings {
+    /// 本地存储的根目录
+    pub root: String,
+}
+
+impl Default for BlobSettings {
+    fn default() -> Self {
+        Self { root: "data/blobs".to_string() }
+    }
+}
+
+/// 数据导出配置结构
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ExportSettings {
+    /// 每批从数据库读取的行数
+    pub batch_size: u32,
+    /// 单个导出文件的最大行数，超过时导出失败，需缩小筛选范围
+    pub max_rows: u64,
+    /// 导出完成后下载链接的有效期（小时），过期后文件被删除
+    pub link_ttl_hours: u32,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        Self { batch_size: 1000, max_rows: 500_000, link_ttl_hours: 24 }
+    }
+}
+
+/// 商品批量导入配置结构
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ImportSettings {
+    /// 单个文件的最大数据行数
+    pub max_rows: u64,
+    /// 每个事务写入的商品数
+    pub chunk_size: usize,
+}
+
+impl Default for ImportSettings {
+    fn default() -> Self {
+        Self { max_rows: 20_000, chunk_size: 100 }
+    }
+}
+
+/// 最近浏览记录配置结构
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct HistorySettings {
+    /// 每个用户保留的浏览记录条数
+    pub limit: u32,
+    /// 用户多少天没有浏览后清空记录
+    pub ttl_days: u32,
+}
+
+impl Default for HistorySettings {
+    fn default() -> Self {
+        Self { limit: 50, ttl_days: 30 }
+    }
+}
+
+/// 延迟任务队列配置结构
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct JobSettings {
+    /// 并发执行任务的协程数
+    pub concurrency: usize,
+    /// 队列为空时的轮询间隔（毫秒）
+    pub poll_interval_ms: u64,
+    /// 领取后超过该时长未确认则重新投递（秒）
+    pub visibility_timeout_secs: u64,
+}
+
+impl Default for JobSettings {
+    fn default() -> Self {
+        Self {
+            concurrency: 2,
+            poll_interval_ms: 1000,
+            visibility_timeout_secs: 60,
+        }
+    }
+}
+
+/// 事务性发件箱转发配置结构
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct OutboxSettings {
+    /// 没有到期事件时的轮询间隔（毫秒）
+    pub poll_interval_ms: u64,
+    /// 每轮最多领取的事件数
+    pub batch_size: u32,
+    /// 领取后超过该时长未完成则允许重新投递（秒）
+    pub lease_secs: u64,
+    /// 最多投递次数，超过后停止投递，需人工处理
+    pub max_attempts: u32,
+}
+
+impl Default for OutboxSettings {
+    fn default() -> Self {
+        Self {
+            poll_interval_ms: 1000,
+            batch_size: 100,
+            lease_secs: 60,
+            max_attempts: 10,
+        }
+    }
+}
+
+/// 主动查单配置结构：补偿丢失的支付结果通知
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct PaymentSyncSettings {
+    /// 扫描间隔（秒）
+    pub interval_secs: u64,
+    /// 发起支付超过该时长仍未收到通知才去查单（秒）
+    pub pending_after_secs: u64,
+    /// 超过该时长不再查单，交由每日对账处理（分钟）
+    pub give_up_after_minutes: u64,
+    /// 每轮最多查询的支付单数
+    pub batch_size: u32,
+}
+
+impl Default for PaymentSyncSettings {
+    fn default() -> Self {
+        Self {
+            interval_secs: 60,
+            pending_after_secs: 60,
+            give_up_after_minutes: 120,
+            batch_size: 100,
+        }
+    }
+}
+
+/// 微信支付账单对账配置结构
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ReconciliationSettings {
+    /// 每天几点（本地时间）下载前一天的交易账单，微信建议 10 点以后
+    pub run_at_hour: u32,
+}
+
+impl Default for ReconciliationSettings {
+    fn default() -> Self {
+        Self { run_at_hour: 10 }
+    }
+}
+
+/// 发货与物流查询配置结构
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct LogisticsSettings {
+    /// 轨迹查询服务商：kuaidi100，或 fake（返回固定轨迹，仅用于本地开发）
+    pub provider: String,
+    /// 快递100 授权 customer
+    pub kuaidi100_customer: String,
+    /// 快递100 授权 key
+    pub kuaidi100_key: String,
+    /// 快递100 接口域名，测试时可指向本地 mock
+    pub kuaidi100_api_base: String,
+    /// 物流轨迹缓存时长（秒）
+    pub cache_ttl_secs: u64,
+}
+
+impl Default for LogisticsSettings {
+    fn default() -> Self {
+        Self {
+            provider: "kuaidi100".to_string(),
+            kuaidi100_customer: String::new(),
+            kuaidi100_key: String::new(),
+            kuaidi100_api_base: "https://poll.kuaidi100.com".to_string(),
+            cache_ttl_secs: 1800,
+        }
+    }
+}
+
+/// 一个订阅消息模板：`fields` 为模板字段名到取值来源的映射。
+/// 来源可以是 order_no、payable_amount、product_name、event_time、status、reason，其余按原文填入
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct SubscribeTemplate {
+    pub template_id: String,
+    #[serde(default)]
+    pub fields: BTreeMap<String, String>,
+}
+
+/// 小程序订阅消息配置结构
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct NotificationSettings {
+    /// 发送通道：wechat，或 fake（只记录不发送，仅用于本地开发）
+    pub transport: String,
+    /// 小程序 AppSecret，用于获取 access_token 和登录换取 openid；appid 与 `[wechat_pay] app_id` 相同
+    pub app_secret: String,
+    /// 接口域名，测试时可指向本地 mock
+    pub api_base: String,
+    /// 点击消息打开的页面，`{order_id}` 会替换为订单 ID
+    pub page: String,
+    /// 跳转的小程序版本：developer / trial / formal
+    pub miniprogram_state: String,
+    /// 订单状态（paid、shipped、refunded 等）到模板的映射，未配置的状态不发通知
+    pub templates: HashMap<String, SubscribeTemplate>,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            transport: "wechat".to_string(),
+            app_secret: String::new(),
+            api_base: "https://api.weixin.qq.com".to_string(),
+            page: "pages/order/detail?id={order_id}".to_string(),
+            miniprogram_state: "formal".to_string(),
+            templates: HashMap::new(),
+        }
+    }
+}
+
+/// 微信支付 APIv3 商户配置结构；未配置商户号时不创建支付客户端，支付相关接口和任务不可用
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct WechatPaySettings {
+    /// 小程序 appid
+    pub app_id: String,
+    /// 商户号
+    pub mch_id: String,
+    /// 商户 API 证书序列号
+    pub serial_no: String,
+    /// 商户 API 私钥（apiclient_key.pem）路径
+    pub private_key_path: String,
+    /// APIv3 密钥，用于解密回调报文
+    pub api_v3_key: String,
+    /// 支付结果回调地址
+    pub notify_url: String,
+    /// 退款结果回调地址，为空时使用商户平台上配置的地址
+    pub refund_notify_url: String,
+    /// 接口域名，测试时可指向本地 mock
+    pub api_base: String,
+}
+
+impl WechatPaySettings {
+    pub fn is_configured(&self) -> bool {
+        !self.mch_id.is_empty()
+    }
+}
+
+impl Default for WechatPaySettings {
+    fn default() -> Self {
+        Self {
+            app_id: String::new(),
+            mch_id: String::new(),
+            serial_no: String::new(),
+            private_key_path: String::new(),
+            api_v3_key: String::new(),
+            notify_url: String::new(),
+            refund_notify_url: String::new(),
+            api_base: "https://api.mch.weixin.qq.com".to_string(),
+        }
+    }
+}
+
 /// 顶级配置结构
 #[derive(Debug, Deserialize, Clone)]
 pub struct Settings {
     pub database: DatabaseSettings,
     pub redis: RedisSettings,
     pub log: LogSettings,
+    #[serde(default)]
+    pub inventory: InventorySettings,
+    #[serde(default)]
+    pub order: OrderSettings,
+    #[serde(default)]
+    pub jobs: JobSettings,
+    #[serde(default)]
+    pub outbox: OutboxSettings,
+    #[serde(default)]
+    pub wechat_pay: WechatPaySettings,
+    #[serde(default)]
+    pub payment_sync: PaymentSyncSettings,
+    #[serde(default)]
+    pub reconciliation: ReconciliationSettings,
+    #[serde(default)]
+    pub logistics: LogisticsSettings,
+    #[serde(default)]
+    pub notification: NotificationSettings,
+    #[serde(default)]
+    pub loyalty: LoyaltySettings,
+    #[serde(default)]
+    pub history: HistorySettings,
+    #[serde(default)]
+    pub referral: ReferralSettings,
+    #[serde(default)]
+    pub stats: StatsSettings,
+    #[serde(default)]
+    pub blob: BlobSettings,
+    #[serde(default)]
+    pub export: ExportSettings,
+    #[serde(default)]
+    pub import: ImportSettings,
 }
 
 
@@ -66,10 +498,10 @@ impl Settings {
         // redis_config.version = tower_sessions_redis_store::fred::types::RespVersion::RESP2;
         let pool = RedisPool::new(redis_config, None, None, None,self.redis.pool_size)
             .map_err(|e| e.to_string())?;
-        let _ = pool.connect();
+        let _connect_handle = pool.connect();
         pool.wait_for_connect().await.map_err(|e| e.to_string())?;
         let _: String = pool.ping(Some("ping".to_string())).await
-            .map_err(|e| format!("Redis pool PING failed: {}", e.to_string()))?;
+            .map_err(|e| format!("Redis pool PING failed: {}", e))?;
         Ok(pool)
     }
 }
@@ -19,6 +19,43 @@ use tracing_subscriber::fmt::writer::MakeWriterExt;
 use axum::extract::FromRef;
 use std::sync::Arc;
 use crate::service::users::{UserService, new_user_service};
+use crate::service::inventory::{InventoryService, new_inventory_service};
+use crate::service::cart::{CartService, new_cart_service};
+use crate::service::coupon::{CouponConsumeSubscriber, CouponService, new_coupon_service};
+use crate::service::promotion::{PromotionService, new_promotion_service};
+use crate::service::freight::{FreightService, new_freight_service};
+use crate::service::address::{AddressService, new_address_service};
+use crate::service::order::{
+    OrderCompleteJob, OrderCompleteScheduler, OrderDeps, OrderService, OrderTimeoutJob, new_order_service,
+};
+use crate::service::jobs::JobWorker;
+use crate::service::events::{EventBus, OutboxRelay};
+use crate::service::order_state::new_order_state_service;
+use crate::service::payment::{PaymentDeps, PaymentService, new_payment_service};
+use crate::service::group_buy::{GroupBuyPaidSubscriber, GroupBuyService, GroupExpireJob, new_group_buy_service};
+use crate::service::refund::{RefundService, new_refund_service};
+use crate::service::after_sale::{AfterSaleDeps, AfterSaleRefundSubscriber, AfterSaleService, new_after_sale_service};
+use crate::service::review::{ReviewService, new_review_service};
+use crate::service::favorite::{FavoriteService, new_favorite_service};
+use crate::service::audit::{AuditService, new_audit_service};
+use crate::service::export::{ExportPurgeJob, ExportRunJob, ExportService, new_export_service};
+use crate::service::import::{ImportRunJob, ImportService, new_import_service};
+use crate::service::stats::{StatsRollupJob, StatsService, new_stats_service, next_stats_date, schedule_stats_rollup};
+use crate::service::referral::{
+    CommissionSettleJob, ReferralService, ReferralSubscriber, new_referral_service, next_commission_settle_date, schedule_commission_settle,
+};
+use crate::service::loyalty::{
+    LoyaltyService, LoyaltySubscriber, PointsExpireJob, new_loyalty_service, next_points_expire_date, schedule_points_expire,
+};
+use crate::service::notification::{NotificationService, OrderNotificationJob, OrderNotificationSubscriber, new_notification_service};
+use crate::service::shipment::{ShipmentService, TrackingLookup, new_shipment_service};
+use crate::domain::shipment::TrackingProvider;
+use crate::domain::payment::PayGateway;
+use crate::domain::users::WechatAuth;
+use crate::domain::notification::SubscribeTransport;
+use crate::service::reconciliation::{
+    BillReconcileJob, ReconciliationService, new_reconciliation_service, next_bill_date, schedule_bill_reconcile,
+};
 
 
 #[derive(Parser, Debug)]
@@ -27,12 +64,46 @@ struct Args {
     /// 配置文件路径
     #[arg(short, long, default_value = "Settings.toml")]
     conf: String,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+    /// 下载某日微信支付交易账单并对账，输出差异报告后退出
+    Reconcile {
+        /// 账单日，格式 YYYY-MM-DD，默认昨天
+        #[arg(long)]
+        date: Option<chrono::NaiveDate>,
+    },
 }
 
 #[derive(Clone)]
 pub struct AppState {
     pub user_service: Arc<dyn UserService>,
-    //
+    pub inventory_service: Arc<dyn InventoryService>,
+    pub cart_service: Arc<dyn CartService>,
+    pub address_service: Arc<dyn AddressService>,
+    pub order_service: Arc<dyn OrderService>,
+    pub coupon_service: Arc<dyn CouponService>,
+    pub promotion_service: Arc<dyn PromotionService>,
+    pub freight_service: Arc<dyn FreightService>,
+    pub shipment_service: Arc<dyn ShipmentService>,
+    pub group_buy_service: Arc<dyn GroupBuyService>,
+    pub payment_service: Arc<dyn PaymentService>,
+    pub refund_service: Arc<dyn RefundService>,
+    pub after_sale_service: Arc<dyn AfterSaleService>,
+    pub review_service: Arc<dyn ReviewService>,
+    pub loyalty_service: Arc<dyn LoyaltyService>,
+    pub favorite_service: Arc<dyn FavoriteService>,
+    pub referral_service: Arc<dyn ReferralService>,
+    pub stats_service: Arc<dyn StatsService>,
+    pub export_service: Arc<dyn ExportService>,
+    pub import_service: Arc<dyn ImportService>,
+    pub audit_service: Arc<dyn AuditService>,
+    pub notification_service: Arc<dyn NotificationService>,
+    pub reconciliation_service: Arc<dyn ReconciliationService>,
+    pub wechat_auth: Arc<dyn WechatAuth>,
 }
 
 impl FromRef<AppState> for Arc<dyn UserService> {
@@ -41,6 +112,138 @@ impl FromRef<AppState> for Arc<dyn UserService> {
     }
 }
 
+impl FromRef<AppState> for Arc<dyn InventoryService> {
+    fn from_ref(state: &AppState) -> Self {
+        state.inventory_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn CartService> {
+    fn from_ref(state: &AppState) -> Self {
+        state.cart_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn AddressService> {
+    fn from_ref(state: &AppState) -> Self {
+        state.address_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn OrderService> {
+    fn from_ref(state: &AppState) -> Self {
+        state.order_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn CouponService> {
+    fn from_ref(state: &AppState) -> Self {
+        state.coupon_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn PromotionService> {
+    fn from_ref(state: &AppState) -> Self {
+        state.promotion_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn FreightService> {
+    fn from_ref(state: &AppState) -> Self {
+        state.freight_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn ShipmentService> {
+    fn from_ref(state: &AppState) -> Self {
+        state.shipment_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn GroupBuyService> {
+    fn from_ref(state: &AppState) -> Self {
+        state.group_buy_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn PaymentService> {
+    fn from_ref(state: &AppState) -> Self {
+        state.payment_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn RefundService> {
+    fn from_ref(state: &AppState) -> Self {
+        state.refund_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn AfterSaleService> {
+    fn from_ref(state: &AppState) -> Self {
+        state.after_sale_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn ReviewService> {
+    fn from_ref(state: &AppState) -> Self {
+        state.review_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn LoyaltyService> {
+    fn from_ref(state: &AppState) -> Self {
+        state.loyalty_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn FavoriteService> {
+    fn from_ref(state: &AppState) -> Self {
+        state.favorite_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn ReferralService> {
+    fn from_ref(state: &AppState) -> Self {
+        state.referral_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn StatsService> {
+    fn from_ref(state: &AppState) -> Self {
+        state.stats_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn ExportService> {
+    fn from_ref(state: &AppState) -> Self {
+        state.export_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn ImportService> {
+    fn from_ref(state: &AppState) -> Self {
+        state.import_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn AuditService> {
+    fn from_ref(state: &AppState) -> Self {
+        state.audit_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn NotificationService> {
+    fn from_ref(state: &AppState) -> Self {
+        state.notification_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn ReconciliationService> {
+    fn from_ref(state: &AppState) -> Self {
+        state.reconciliation_service.clone()
+    }
+}
+
 
 #[tokio::main]
 async fn main() {
@@ -94,24 +297,258 @@ async fn main() {
         }
     };
 
-    let session_store = RedisStore::new(redis_pool);
+    let session_store = RedisStore::new(redis_pool.clone());
     let session_layer = SessionManagerLayer::new(session_store)
         .with_secure(false)
         .with_expiry(Expiry::OnInactivity(Duration::seconds(3600)));
 
     // 创建 Repositories，并注入数据库连接池
     let user_repo = repos::users::UserRepository::new(pool.clone()); // 注意：使用 pool.clone()
+    let inventory_repo = repos::inventory::InventoryRepository::new(pool.clone());
+    let stock_counter = repos::stock_counter::RedisStockCounter::new(redis_pool.clone());
+    let catalog_repo = repos::catalog::CatalogRepository::new(pool.clone());
+    let cart_repo = repos::cart::CartRepository::new(pool.clone());
+    let address_repo = repos::address::AddressRepository::new(pool.clone());
+    let order_repo = repos::order::OrderRepository::new(pool.clone());
+    let job_queue = repos::job_queue::RedisJobQueue::new(redis_pool.clone());
+    let payment_repo = repos::payment::PaymentRepository::new(pool.clone());
+    let refund_repo = repos::refund::RefundRepository::new(pool.clone());
+    let coupon_repo = repos::coupon::CouponRepository::new(pool.clone());
+    let promotion_repo = repos::promotion::PromotionRepository::new(pool.clone());
+    let group_buy_repo = repos::group_buy::GroupBuyRepository::new(pool.clone());
+    let reconciliation_repo = repos::reconciliation::ReconciliationRepository::new(pool.clone());
+    let freight_repo = repos::freight::FreightRepository::new(pool.clone());
+    let shipment_repo = repos::shipment::ShipmentRepository::new(pool.clone());
+    let after_sale_repo = repos::after_sale::AfterSaleRepository::new(pool.clone());
+    let review_repo = repos::review::ReviewRepository::new(pool.clone());
+    let notification_repo = repos::notification::NotificationRepository::new(pool.clone());
+    let outbox_repo = repos::outbox::OutboxRepository::new(pool.clone());
+    let loyalty_repo = repos::loyalty::LoyaltyRepository::new(pool.clone());
+    let stats_repo = repos::stats::StatsRepository::new(pool.clone());
+    let export_repo = repos::export::ExportRepository::new(pool.clone());
+    let import_repo = repos::import::ImportRepository::new(pool.clone());
+    let audit_repo = repos::audit::AuditRepository::new(pool.clone());
+    let blob_store = repos::blob::LocalBlobStore::new(&settings.blob.root);
+    let referral_repo = repos::referral::ReferralRepository::new(pool.clone());
+    let favorite_repo = repos::favorite::FavoriteRepository::new(pool.clone());
+    let browsing_history = repos::history::RedisBrowsingHistory::new(redis_pool.clone());
+    let tracking_cache = repos::tracking::RedisTrackingCache::new(redis_pool.clone());
+    let tracking_provider: Arc<dyn TrackingProvider> = match settings.logistics.provider.as_str() {
+        "fake" => repos::tracking::FakeTrackingProvider::new(),
+        _ => match repos::kuaidi100::Kuaidi100Client::new(&settings.logistics) {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::error!("Failed to init kuaidi100 client: {}", e);
+                return;
+            }
+        },
+    };
+    let subscribe_transport: Arc<dyn SubscribeTransport> = match settings.notification.transport.as_str() {
+        "fake" => repos::wechat_subscribe::RecordingTransport::new(),
+        _ => match repos::wechat_subscribe::WechatSubscribeClient::new(&settings.wechat_pay.app_id, &settings.notification) {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::error!("Failed to init wechat subscribe client: {}", e);
+                return;
+            }
+        },
+    };
+    let wechat_auth = match repos::wechat_auth::WechatAuthClient::new(&settings.wechat_pay.app_id, &settings.notification) {
+        Ok(client) => client,
+        Err(e) => {
+            tracing::error!("Failed to init wechat auth client: {}", e);
+            return;
+        }
+    };
+    let wechat_pay: Arc<dyn PayGateway> = if settings.wechat_pay.is_configured() {
+        match repos::wechat_pay::WechatPayClient::new(&settings.wechat_pay) {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::error!("Failed to init wechat pay client: {}", e);
+                return;
+            }
+        }
+    } else {
+        tracing::warn!("WeChat Pay is not configured, payment and refund are disabled");
+        repos::wechat_pay::UnconfiguredPayGateway::new()
+    };
     // 创建 Services，并注入 Repositories
     let user_service = new_user_service(user_repo);
+    let inventory_service = new_inventory_service(inventory_repo, stock_counter);
+    let cart_service = new_cart_service(cart_repo, catalog_repo.clone(), inventory_service.clone());
+    let address_service = new_address_service(address_repo.clone());
+    let order_state_service = new_order_state_service(order_repo.clone());
+    let coupon_service = new_coupon_service(coupon_repo);
+    let promotion_service = new_promotion_service(promotion_repo);
+    let freight_service = new_freight_service(freight_repo);
+    let loyalty_service = new_loyalty_service(loyalty_repo, order_repo.clone(), catalog_repo.clone(), settings.loyalty.clone());
+    let favorite_service = new_favorite_service(favorite_repo, browsing_history, catalog_repo.clone(), settings.history.clone());
+    let order_service = new_order_service(
+        order_repo.clone(),
+        address_repo,
+        catalog_repo.clone(),
+        OrderDeps {
+            cart: cart_service.clone(),
+            inventory: inventory_service.clone(),
+            state: order_state_service.clone(),
+            jobs: job_queue.clone(),
+            coupons: coupon_service.clone(),
+            promotions: promotion_service.clone(),
+            freight: freight_service.clone(),
+            loyalty: loyalty_service.clone(),
+        },
+        settings.order.clone(),
+    );
+    let shipment_service = new_shipment_service(
+        shipment_repo,
+        order_service.clone(),
+        order_state_service.clone(),
+        TrackingLookup::new(tracking_provider, tracking_cache, settings.logistics.cache_ttl_secs),
+    );
+    let refund_service = new_refund_service(
+        refund_repo.clone(),
+        order_repo.clone(),
+        payment_repo.clone(),
+        order_state_service.clone(),
+        inventory_service.clone(),
+        wechat_pay.clone(),
+    );
+    let review_service = new_review_service(review_repo, order_repo.clone());
+    let notification_service =
+        new_notification_service(notification_repo, order_repo.clone(), subscribe_transport, settings.notification.clone());
+    let referral_service = new_referral_service(
+        referral_repo,
+        order_repo.clone(),
+        refund_repo.clone(),
+        after_sale_repo.clone(),
+        settings.referral.clone(),
+    );
+    let after_sale_service = new_after_sale_service(
+        after_sale_repo,
+        order_repo,
+        catalog_repo,
+        AfterSaleDeps { refunds: refund_service.clone(), inventory: inventory_service.clone() },
+    );
+    let group_buy_service =
+        new_group_buy_service(group_buy_repo, order_service.clone(), refund_service.clone(), job_queue.clone());
+    let payment_service = new_payment_service(
+        payment_repo.clone(),
+        PaymentDeps {
+            orders: order_service.clone(),
+            state: order_state_service.clone(),
+            gateway: wechat_pay.clone(),
+            refunds: refund_service.clone(),
+        },
+        settings.order.clone(),
+        settings.wechat_pay.clone(),
+    );
+    let stats_service = new_stats_service(stats_repo, settings.stats.clone());
+    let export_service = new_export_service(export_repo, blob_store.clone(), job_queue.clone(), settings.export.clone());
+    let audit_service = new_audit_service(audit_repo);
+    let import_service = new_import_service(import_repo, blob_store, job_queue.clone(), audit_service.clone(), settings.import.clone());
+    let reconciliation_service = new_reconciliation_service(reconciliation_repo, payment_repo, refund_repo, wechat_pay);
+
+    // 命令行子命令：执行完即退出，不启动后台任务和 HTTP 服务
+    if let Some(Command::Reconcile { date }) = args.command {
+        let bill_date = date.unwrap_or_else(|| chrono::Local::now().date_naive() - chrono::Days::new(1));
+        match reconciliation_service.run(bill_date).await {
+            Ok(report) => println!("{}", serde_json::to_string_pretty(&report).unwrap_or_default()),
+            Err(e) => tracing::error!("Failed to reconcile {}: {:?}", bill_date, e),
+        }
+        return;
+    }
+
+    // 后台任务：延迟任务队列
+    let run_at_hour = settings.reconciliation.run_at_hour;
+    if settings.wechat_pay.is_configured()
+        && let Err(e) = schedule_bill_reconcile(job_queue.as_ref(), next_bill_date(chrono::Local::now(), run_at_hour), run_at_hour).await
+    {
+        tracing::error!("Failed to schedule bill reconciliation: {:?}", e);
+    }
+    // 后台任务：发件箱转发，领域事件至少投递一次
+    let event_bus = EventBus::new()
+        .subscribe(OrderNotificationSubscriber::new(job_queue.clone(), &settings.notification))
+        .subscribe(OrderCompleteScheduler::new(job_queue.clone(), &settings.order))
+        .subscribe(LoyaltySubscriber::new(loyalty_service.clone()))
+        .subscribe(ReferralSubscriber::new(referral_service.clone()))
+        .subscribe(AfterSaleRefundSubscriber::new(after_sale_service.clone()))
+        .subscribe(CouponConsumeSubscriber::new(coupon_service.clone()))
+        .subscribe(GroupBuyPaidSubscriber::new(group_buy_service.clone()));
+    OutboxRelay::new(outbox_repo, Arc::new(event_bus), settings.outbox.clone()).spawn();
+
+    let points_hour = settings.loyalty.run_at_hour;
+    if let Err(e) = schedule_points_expire(job_queue.as_ref(), next_points_expire_date(chrono::Local::now(), points_hour), points_hour).await {
+        tracing::error!("Failed to schedule points expiry: {:?}", e);
+    }
+    let stats_hour = settings.stats.run_at_hour;
+    if let Err(e) = schedule_stats_rollup(job_queue.as_ref(), next_stats_date(chrono::Local::now(), stats_hour), stats_hour).await {
+        tracing::error!("Failed to schedule stats rollup: {:?}", e);
+    }
+    let settle_hour = settings.referral.run_at_hour;
+    if let Err(e) =
+        schedule_commission_settle(job_queue.as_ref(), next_commission_settle_date(chrono::Local::now(), settle_hour), settle_hour).await
+    {
+        tracing::error!("Failed to schedule commission settlement: {:?}", e);
+    }
+    JobWorker::new(job_queue.clone(), settings.jobs.clone())
+        .register(OrderTimeoutJob::new(order_service.clone()))
+        .register(OrderCompleteJob::new(order_service.clone()))
+        .register(PointsExpireJob::new(loyalty_service.clone(), job_queue.clone(), points_hour))
+        .register(CommissionSettleJob::new(referral_service.clone(), job_queue.clone(), settle_hour))
+        .register(StatsRollupJob::new(stats_service.clone(), job_queue.clone(), settings.stats.clone()))
+        .register(ExportRunJob::new(export_service.clone()))
+        .register(ExportPurgeJob::new(export_service.clone()))
+        .register(ImportRunJob::new(import_service.clone()))
+        .register(GroupExpireJob::new(group_buy_service.clone()))
+        .register(OrderNotificationJob::new(notification_service.clone()))
+        .register(BillReconcileJob::new(reconciliation_service.clone(), job_queue, run_at_hour))
+        .spawn();
+
+    // 后台任务：主动查单，补偿丢失的支付结果通知
+    if settings.wechat_pay.is_configured() {
+        service::payment::spawn_payment_sync(payment_service.clone(), settings.payment_sync.clone());
+    }
+
+    // 后台任务：秒杀库存回写 MySQL 与漂移检测
+    service::inventory::spawn_reconciler(
+        inventory_service.clone(),
+        std::time::Duration::from_secs(settings.inventory.reconcile_interval_secs),
+        settings.inventory.reconcile_batch_size,
+    );
 
     let app_state = AppState {
-        user_service
+        user_service,
+        inventory_service,
+        cart_service,
+        address_service,
+        order_service,
+        coupon_service,
+        promotion_service,
+        freight_service,
+        shipment_service,
+        group_buy_service,
+        payment_service,
+        refund_service,
+        after_sale_service,
+        review_service,
+        loyalty_service,
+        favorite_service,
+        referral_service,
+        stats_service,
+        export_service,
+        import_service,
+        audit_service: audit_service.clone(),
+        notification_service,
+        reconciliation_service,
+        wechat_auth,
     };
 
     // --- 4. 路由合并与依赖挂载 ---
     let app = Router::new()
         .merge(router::routes())
         .with_state(app_state)
+        // 审计中间件通过扩展取得审计服务
+        .layer(axum::Extension(audit_service))
         .layer(axum::middleware::from_fn(router::middleware::print_request_body))
         .layer(session_layer)
         .layer(
@@ -165,5 +602,6 @@ async fn main() {
     // --- 5. 启动服务 ---
     let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
     tracing::info!("Listening on http://0.0.0.0:3000");
-    axum::serve(listener, app).await.unwrap();
+    // 带上对端地址，供审计日志记录来源 IP
+    axum::serve(listener, app.into_make_service_with_connect_info::<std::net::SocketAddr>()).await.unwrap();
 }
@@ -0,0 +1,27 @@
+use sqlx::FromRow;
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Local};
+
+#[derive(FromRow, Debug, Clone, Serialize, Deserialize)]
+pub struct Address {
+    pub id: u64,
+    pub user_id: u32,
+    pub receiver_name: String,
+    pub phone: String,
+    pub province: String,
+    pub city: String,
+    pub district: String,
+    pub detail: String,
+    pub created_at: Option<DateTime<Local>>,
+    pub updated_at: Option<DateTime<Local>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewAddress {
+    pub receiver_name: String,
+    pub phone: String,
+    pub province: String,
+    pub city: String,
+    pub district: String,
+    pub detail: String,
+}
@@ -0,0 +1,242 @@
+use sqlx::FromRow;
+use sqlx::types::Json;
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Local};
+use std::fmt;
+use std::str::FromStr;
+
+/// 凭证图片最多张数
+pub const MAX_PHOTOS: usize = 9;
+
+/// 售后类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AfterSaleKind {
+    /// 退货退款
+    Return,
+    /// 换货
+    Exchange,
+}
+
+impl AfterSaleKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AfterSaleKind::Return => "return",
+            AfterSaleKind::Exchange => "exchange",
+        }
+    }
+}
+
+impl FromStr for AfterSaleKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use AfterSaleKind::*;
+        [Return, Exchange]
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| format!("unknown after-sale kind: {}", s))
+    }
+}
+
+/// 售后原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AfterSaleReason {
+    /// 质量问题
+    QualityIssue,
+    /// 发错货
+    WrongItem,
+    /// 与描述不符
+    NotAsDescribed,
+    /// 运输破损
+    Damaged,
+    /// 尺码不合适
+    SizeIssue,
+    /// 不想要了
+    NoLongerNeeded,
+    Other,
+}
+
+impl AfterSaleReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AfterSaleReason::QualityIssue => "quality_issue",
+            AfterSaleReason::WrongItem => "wrong_item",
+            AfterSaleReason::NotAsDescribed => "not_as_described",
+            AfterSaleReason::Damaged => "damaged",
+            AfterSaleReason::SizeIssue => "size_issue",
+            AfterSaleReason::NoLongerNeeded => "no_longer_needed",
+            AfterSaleReason::Other => "other",
+        }
+    }
+}
+
+/// 售后单状态：申请 -> 同意 -> 买家寄回 -> 收货 -> 退款中 -> 已退款 / 已换货；申请和收货验货时可以拒绝，
+/// 渠道退款失败时从退款中回到收货
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AfterSaleStatus {
+    Requested,
+    Approved,
+    BuyerShipped,
+    Received,
+    Refunding,
+    Refunded,
+    Exchanged,
+    Rejected,
+}
+
+impl AfterSaleStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AfterSaleStatus::Requested => "requested",
+            AfterSaleStatus::Approved => "approved",
+            AfterSaleStatus::BuyerShipped => "buyer_shipped",
+            AfterSaleStatus::Received => "received",
+            AfterSaleStatus::Refunding => "refunding",
+            AfterSaleStatus::Refunded => "refunded",
+            AfterSaleStatus::Exchanged => "exchanged",
+            AfterSaleStatus::Rejected => "rejected",
+        }
+    }
+
+    /// 状态机允许的流转；收货后按售后类型进入退款中或已换货
+    pub fn can_transition_to(&self, to: AfterSaleStatus, kind: AfterSaleKind) -> bool {
+        use AfterSaleStatus::*;
+        match (self, to) {
+            (Requested, Approved) | (Requested, Rejected) => true,
+            (Approved, BuyerShipped) => true,
+            (BuyerShipped, Received) => true,
+            (Received, Rejected) => true,
+            (Received, Refunding) => kind == AfterSaleKind::Return,
+            (Refunding, Refunded) | (Refunding, Received) => true,
+            (Received, Exchanged) => kind == AfterSaleKind::Exchange,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for AfterSaleStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AfterSaleStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use AfterSaleStatus::*;
+        [Requested, Approved, BuyerShipped, Received, Refunding, Refunded, Exchanged, Rejected]
+            .into_iter()
+            .find(|status| status.as_str() == s)
+            .ok_or_else(|| format!("unknown after-sale status: {}", s))
+    }
+}
+
+/// 售后单；金额单位：分
+#[derive(FromRow, Debug, Clone, Serialize)]
+pub struct AfterSale {
+    pub id: u64,
+    pub after_sale_no: String,
+    pub order_id: u64,
+    pub order_item_id: u64,
+    pub user_id: u32,
+    pub kind: String,
+    pub reason: String,
+    pub description: String,
+    pub photos: Json<Vec<String>>,
+    pub quantity: u32,
+    pub refund_amount: i64,
+    pub exchange_sku_id: Option<u64>,
+    pub status: String,
+    pub return_carrier: Option<String>,
+    pub return_tracking_no: Option<String>,
+    pub exchange_carrier: Option<String>,
+    pub exchange_tracking_no: Option<String>,
+    pub refund_id: Option<u64>,
+    pub admin_id: Option<u32>,
+    pub reject_reason: Option<String>,
+    pub created_at: Option<DateTime<Local>>,
+    pub updated_at: Option<DateTime<Local>>,
+}
+
+impl AfterSale {
+    pub fn status(&self) -> Result<AfterSaleStatus, String> {
+        self.status.parse()
+    }
+
+    pub fn kind(&self) -> Result<AfterSaleKind, String> {
+        self.kind.parse()
+    }
+}
+
+/// 客服备注
+#[derive(FromRow, Debug, Clone, Serialize)]
+pub struct AfterSaleNote {
+    pub id: u64,
+    pub after_sale_id: u64,
+    pub admin_id: u32,
+    pub note: String,
+    pub created_at: Option<DateTime<Local>>,
+}
+
+/// 后台查看的售后单详情，带客服备注
+#[derive(Debug, Clone, Serialize)]
+pub struct AfterSaleDetail {
+    #[serde(flatten)]
+    pub after_sale: AfterSale,
+    pub notes: Vec<AfterSaleNote>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewAfterSale {
+    pub after_sale_no: String,
+    pub order_id: u64,
+    pub order_item_id: u64,
+    pub user_id: u32,
+    pub kind: AfterSaleKind,
+    pub reason: AfterSaleReason,
+    pub description: String,
+    pub photos: Vec<String>,
+    pub quantity: u32,
+    pub refund_amount: i64,
+    pub exchange_sku_id: Option<u64>,
+}
+
+/// 用户提交的售后申请
+#[derive(Debug, Clone, Deserialize)]
+pub struct AfterSaleApply {
+    pub order_item_id: u64,
+    pub kind: AfterSaleKind,
+    pub reason: AfterSaleReason,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub photos: Vec<String>,
+    pub quantity: u32,
+    /// 换货时换成的 SKU，须为同一商品；为空表示换同款
+    #[serde(default)]
+    pub exchange_sku_id: Option<u64>,
+}
+
+impl AfterSaleApply {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.quantity == 0 {
+            return Err("quantity must be positive".to_string());
+        }
+        if self.description.chars().count() > 500 {
+            return Err("description is too long".to_string());
+        }
+        if self.photos.len() > MAX_PHOTOS {
+            return Err(format!("at most {} photos are allowed", MAX_PHOTOS));
+        }
+        if self.photos.iter().any(|url| !url.starts_with("https://") || url.len() > 512) {
+            return Err("photos must be https URLs".to_string());
+        }
+        if self.kind == AfterSaleKind::Return && self.exchange_sku_id.is_some() {
+            return Err("exchange_sku_id is only for exchanges".to_string());
+        }
+        Ok(())
+    }
+}
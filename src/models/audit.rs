@@ -0,0 +1,55 @@
+use sqlx::FromRow;
+use sqlx::types::Json;
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Local, NaiveDate};
+
+/// 一个实体的变更，只包含前后不同的字段；新建时 `before` 为 null
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditChange {
+    pub entity: String,
+    pub entity_id: String,
+    pub before: serde_json::Value,
+    pub after: serde_json::Value,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewAuditLog {
+    pub actor_id: u32,
+    pub ip: Option<String>,
+    pub forwarded_for: Option<String>,
+    pub session_id: Option<String>,
+    pub action: String,
+    pub target_type: String,
+    pub target_id: Option<String>,
+    pub status_code: Option<u16>,
+    pub request: Option<serde_json::Value>,
+    pub changes: Vec<AuditChange>,
+}
+
+#[derive(FromRow, Debug, Clone, Serialize)]
+pub struct AuditLog {
+    pub id: u64,
+    pub actor_id: u32,
+    pub ip: Option<String>,
+    pub forwarded_for: Option<String>,
+    pub session_id: Option<String>,
+    pub action: String,
+    pub target_type: String,
+    pub target_id: Option<String>,
+    pub status_code: Option<u16>,
+    pub request: Option<Json<serde_json::Value>>,
+    pub changes: Json<Vec<AuditChange>>,
+    pub created_at: Option<DateTime<Local>>,
+}
+
+/// 审计日志查询条件，均为可选；日期范围含两端
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuditQuery {
+    pub actor_id: Option<u32>,
+    /// 按前缀匹配，如 `POST /admin/refunds`
+    pub action: Option<String>,
+    pub target_type: Option<String>,
+    pub target_id: Option<String>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
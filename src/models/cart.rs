@@ -0,0 +1,41 @@
+use sqlx::FromRow;
+use serde::{Serialize, Deserialize};
+
+/// 单个 SKU 在购物车中的最大数量
+pub const MAX_LINE_QUANTITY: u32 = 99;
+/// 购物车最多容纳的 SKU 行数，游客和登录用户一致
+pub const MAX_CART_LINES: usize = 100;
+
+/// 购物车行：登录用户存 `cart_items`，游客存 Session
+#[derive(FromRow, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CartLine {
+    pub sku_id: u64,
+    pub quantity: u32,
+    pub selected: bool,
+}
+
+/// 读取购物车时按当前价格和库存重新计算的行
+#[derive(Debug, Clone, Serialize)]
+pub struct CartLineView {
+    pub sku_id: u64,
+    pub product_id: u64,
+    pub product_name: String,
+    pub sku_title: String,
+    /// 当前单价，单位：分
+    pub unit_price: i64,
+    pub quantity: u32,
+    pub selected: bool,
+    pub stock: i64,
+    /// 在售且库存充足才可结算
+    pub purchasable: bool,
+    pub line_total: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CartView {
+    pub lines: Vec<CartLineView>,
+    /// 已勾选且可结算的件数
+    pub selected_quantity: u32,
+    /// 已勾选且可结算的金额，单位：分
+    pub selected_total: i64,
+}
@@ -0,0 +1,48 @@
+use sqlx::FromRow;
+use serde::{Serialize, Deserialize};
+
+/// 上架状态，商品和 SKU 共用
+pub const STATUS_ON_SALE: &str = "on_sale";
+
+/// SKU 连同所属商品的信息，用于购物车和下单时重新取价
+#[derive(FromRow, Debug, Clone, Serialize, Deserialize)]
+pub struct SkuDetail {
+    pub sku_id: u64,
+    pub product_id: u64,
+    pub product_name: String,
+    pub category_id: u64,
+    pub title: String,
+    /// 单价，单位：分
+    pub price: i64,
+    pub sku_status: String,
+    pub product_status: String,
+    /// 单件重量，单位：克
+    pub weight_grams: u32,
+    /// 商品使用的运费模板，为空时按统一运费计算
+    pub freight_template_id: Option<u64>,
+}
+
+impl SkuDetail {
+    pub fn is_on_sale(&self) -> bool {
+        self.sku_status == STATUS_ON_SALE && self.product_status == STATUS_ON_SALE
+    }
+}
+
+/// 商品概要，用于收藏、浏览记录等列表展示当前价格和是否可买
+#[derive(FromRow, Debug, Clone, Serialize, Deserialize)]
+pub struct ProductSummary {
+    pub product_id: u64,
+    pub name: String,
+    pub category_id: u64,
+    pub status: String,
+    /// 在售 SKU 的最低价，没有在售 SKU 时为空
+    pub min_price: Option<i64>,
+    pub on_sale_skus: i64,
+    pub favorite_count: u32,
+}
+
+impl ProductSummary {
+    pub fn is_available(&self) -> bool {
+        self.status == STATUS_ON_SALE && self.on_sale_skus > 0
+    }
+}
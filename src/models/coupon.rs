@@ -0,0 +1,227 @@
+use sqlx::FromRow;
+use sqlx::types::Json;
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Local};
+use std::str::FromStr;
+
+/// 模板可领取
+pub const TEMPLATE_ACTIVE: &str = "active";
+/// 模板已停发，已领取的券不受影响
+pub const TEMPLATE_DISABLED: &str = "disabled";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CouponKind {
+    /// 立减
+    Fixed,
+    /// 折扣，可设封顶
+    Percent,
+    /// 满减
+    Threshold,
+    /// 免运费
+    FreeShipping,
+}
+
+impl CouponKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CouponKind::Fixed => "fixed",
+            CouponKind::Percent => "percent",
+            CouponKind::Threshold => "threshold",
+            CouponKind::FreeShipping => "free_shipping",
+        }
+    }
+}
+
+impl FromStr for CouponKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use CouponKind::*;
+        [Fixed, Percent, Threshold, FreeShipping]
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| format!("unknown coupon kind: {}", s))
+    }
+}
+
+/// 适用范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CouponScope {
+    All,
+    Category,
+    Product,
+}
+
+impl CouponScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CouponScope::All => "all",
+            CouponScope::Category => "category",
+            CouponScope::Product => "product",
+        }
+    }
+}
+
+impl FromStr for CouponScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use CouponScope::*;
+        [All, Category, Product]
+            .into_iter()
+            .find(|scope| scope.as_str() == s)
+            .ok_or_else(|| format!("unknown coupon scope: {}", s))
+    }
+}
+
+/// 用户券状态：未使用 -> 下单锁定 -> 支付核销；订单取消时从锁定回到未使用
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserCouponStatus {
+    Unused,
+    Locked,
+    Used,
+}
+
+impl UserCouponStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UserCouponStatus::Unused => "unused",
+            UserCouponStatus::Locked => "locked",
+            UserCouponStatus::Used => "used",
+        }
+    }
+}
+
+/// 优惠券模板；金额单位：分
+#[derive(FromRow, Debug, Clone, Serialize)]
+pub struct CouponTemplate {
+    pub id: u64,
+    pub name: String,
+    pub kind: String,
+    pub threshold: i64,
+    pub amount: i64,
+    pub percent_off: u32,
+    pub max_discount: i64,
+    pub scope: String,
+    pub scope_ids: Json<Vec<u64>>,
+    pub total_quota: u32,
+    pub issued: u32,
+    pub per_user_limit: u32,
+    pub valid_from: DateTime<Local>,
+    pub valid_to: DateTime<Local>,
+    pub valid_days: u32,
+    pub status: String,
+    pub created_at: Option<DateTime<Local>>,
+    pub updated_at: Option<DateTime<Local>>,
+}
+
+impl CouponTemplate {
+    /// 领取后的过期时间
+    pub fn expires_at(&self, claimed_at: DateTime<Local>) -> DateTime<Local> {
+        if self.valid_days > 0 {
+            claimed_at + chrono::Duration::days(i64::from(self.valid_days))
+        } else {
+            self.valid_to
+        }
+    }
+}
+
+/// 后台创建的模板
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewCouponTemplate {
+    pub name: String,
+    pub kind: CouponKind,
+    #[serde(default)]
+    pub threshold: i64,
+    #[serde(default)]
+    pub amount: i64,
+    #[serde(default)]
+    pub percent_off: u32,
+    #[serde(default)]
+    pub max_discount: i64,
+    pub scope: CouponScope,
+    #[serde(default)]
+    pub scope_ids: Vec<u64>,
+    pub total_quota: u32,
+    #[serde(default = "default_per_user_limit")]
+    pub per_user_limit: u32,
+    pub valid_from: DateTime<Local>,
+    pub valid_to: DateTime<Local>,
+    #[serde(default)]
+    pub valid_days: u32,
+}
+
+fn default_per_user_limit() -> u32 {
+    1
+}
+
+impl NewCouponTemplate {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("name is required".to_string());
+        }
+        match self.kind {
+            CouponKind::Fixed if self.amount <= 0 => return Err("amount must be positive".to_string()),
+            CouponKind::Threshold if self.amount <= 0 || self.threshold <= self.amount => {
+                return Err("threshold coupon needs 0 < amount < threshold".to_string());
+            }
+            CouponKind::Percent if !(1..=99).contains(&self.percent_off) => {
+                return Err("percent_off must be between 1 and 99".to_string());
+            }
+            _ => {}
+        }
+        if self.threshold < 0 || self.max_discount < 0 {
+            return Err("threshold and max_discount must not be negative".to_string());
+        }
+        if self.scope != CouponScope::All && self.scope_ids.is_empty() {
+            return Err("scope_ids is required for a limited scope".to_string());
+        }
+        if self.total_quota == 0 || self.per_user_limit == 0 {
+            return Err("total_quota and per_user_limit must be positive".to_string());
+        }
+        if self.valid_to <= self.valid_from {
+            return Err("valid_to must be after valid_from".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(FromRow, Debug, Clone, Serialize)]
+pub struct UserCoupon {
+    pub id: u64,
+    pub template_id: u64,
+    pub user_id: u32,
+    pub status: String,
+    pub order_no: Option<String>,
+    pub expires_at: DateTime<Local>,
+    pub used_at: Option<DateTime<Local>>,
+    pub created_at: Option<DateTime<Local>>,
+    pub updated_at: Option<DateTime<Local>>,
+}
+
+impl UserCoupon {
+    pub fn is_usable(&self, now: DateTime<Local>) -> bool {
+        self.status == UserCouponStatus::Unused.as_str() && self.expires_at > now
+    }
+}
+
+/// 「我的优惠券」列表项
+#[derive(Debug, Clone, Serialize)]
+pub struct MyCoupon {
+    #[serde(flatten)]
+    pub coupon: UserCoupon,
+    pub usable: bool,
+    pub template: CouponTemplate,
+}
+
+/// 领券结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClaimOutcome {
+    Claimed(u64),
+    /// 模板不存在、已停发或不在领取时间内
+    Unavailable,
+    SoldOut,
+    LimitReached,
+}
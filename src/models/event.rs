@@ -0,0 +1,88 @@
+use sqlx::FromRow;
+use serde::{Serialize, Deserialize};
+use serde::de::DeserializeOwned;
+use chrono::{DateTime, Local};
+use crate::models::order::{Actor, Order, OrderStatus};
+
+/// 订单状态变更事件的类型前缀，完整类型为 `order.<状态>`，例如 `order.paid`
+pub const ORDER_EVENT_PREFIX: &str = "order.";
+
+/// 领域事件；`payload` 为具体事件结构序列化后的 JSON
+#[derive(Debug, Clone, PartialEq)]
+pub struct DomainEvent {
+    pub event_type: String,
+    /// 事件所属对象的 ID，例如订单 ID
+    pub aggregate_id: String,
+    pub payload: serde_json::Value,
+}
+
+impl DomainEvent {
+    pub fn order_status_changed(event: &OrderStatusChanged) -> Self {
+        DomainEvent {
+            event_type: format!("{}{}", ORDER_EVENT_PREFIX, event.to),
+            aggregate_id: event.order_id.to_string(),
+            payload: serde_json::to_value(event).unwrap_or_default(),
+        }
+    }
+
+    pub fn decode<T: DeserializeOwned>(&self) -> Result<T, String> {
+        serde_json::from_value(self.payload.clone()).map_err(|e| format!("invalid {} payload: {}", self.event_type, e))
+    }
+}
+
+/// 订单状态流转成功
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderStatusChanged {
+    pub order_id: u64,
+    pub order_no: String,
+    pub user_id: u32,
+    pub from: String,
+    pub to: String,
+    pub payable_amount: i64,
+    pub actor_type: String,
+    pub actor_id: u32,
+    pub reason: String,
+    pub occurred_at: DateTime<Local>,
+}
+
+impl OrderStatusChanged {
+    pub fn new(order: &Order, from: OrderStatus, to: OrderStatus, actor: Actor, reason: &str) -> Self {
+        OrderStatusChanged {
+            order_id: order.id,
+            order_no: order.order_no.clone(),
+            user_id: order.user_id,
+            from: from.as_str().to_string(),
+            to: to.as_str().to_string(),
+            payable_amount: order.payable_amount,
+            actor_type: actor.actor_type.as_str().to_string(),
+            actor_id: actor.id,
+            reason: reason.to_string(),
+            occurred_at: Local::now(),
+        }
+    }
+}
+
+/// 发件箱中的一条事件
+#[derive(FromRow, Debug, Clone, Serialize)]
+pub struct OutboxEvent {
+    pub id: u64,
+    pub event_type: String,
+    pub aggregate_id: String,
+    pub payload: String,
+    pub attempts: u32,
+    pub next_attempt_at: DateTime<Local>,
+    pub published_at: Option<DateTime<Local>>,
+    pub last_error: Option<String>,
+    pub created_at: Option<DateTime<Local>>,
+}
+
+impl OutboxEvent {
+    pub fn to_event(&self) -> Result<DomainEvent, String> {
+        let payload = serde_json::from_str(&self.payload).map_err(|e| format!("invalid outbox payload {}: {}", self.id, e))?;
+        Ok(DomainEvent {
+            event_type: self.event_type.clone(),
+            aggregate_id: self.aggregate_id.clone(),
+            payload,
+        })
+    }
+}
@@ -0,0 +1,203 @@
+use sqlx::FromRow;
+use sqlx::types::Json;
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Local, NaiveDate};
+use std::str::FromStr;
+use crate::domain::blob::BlobFile;
+
+/// 可导出的数据
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportKind {
+    Orders,
+    Users,
+    Inventory,
+}
+
+impl ExportKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportKind::Orders => "orders",
+            ExportKind::Users => "users",
+            ExportKind::Inventory => "inventory",
+        }
+    }
+
+    /// 表头，与 `ExportRepo::fetch_batch` 返回的列一一对应
+    pub fn headers(&self) -> &'static [&'static str] {
+        match self {
+            ExportKind::Orders => &[
+                "订单号", "用户ID", "状态", "商品金额(元)", "优惠金额(元)", "运费(元)", "应付金额(元)",
+                "收货人", "手机号", "收货地址", "下单时间",
+            ],
+            ExportKind::Users => &["用户ID", "用户名", "角色", "注册时间"],
+            ExportKind::Inventory => &["SKU ID", "商品ID", "商品名称", "规格", "价格(元)", "状态", "库存", "库存模式"],
+        }
+    }
+}
+
+impl FromStr for ExportKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use ExportKind::*;
+        [Orders, Users, Inventory]
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| format!("unknown export kind: {}", s))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Csv,
+    Xlsx,
+}
+
+impl ExportFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Xlsx => "xlsx",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Xlsx => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [ExportFormat::Csv, ExportFormat::Xlsx]
+            .into_iter()
+            .find(|format| format.as_str() == s)
+            .ok_or_else(|| format!("unknown export format: {}", s))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportStatus {
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+    /// 下载链接已过期，文件已删除
+    Expired,
+}
+
+impl ExportStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportStatus::Pending => "pending",
+            ExportStatus::Running => "running",
+            ExportStatus::Succeeded => "succeeded",
+            ExportStatus::Failed => "failed",
+            ExportStatus::Expired => "expired",
+        }
+    }
+}
+
+/// 导出的筛选条件，不适用于当前数据的条件被忽略
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportFilter {
+    /// 订单状态，仅订单
+    pub status: Option<String>,
+    /// 创建日期范围（含两端），适用于订单和用户
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    /// 只导出库存低于该值的 SKU，仅库存
+    pub stock_below: Option<i64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExportRequest {
+    pub kind: ExportKind,
+    pub format: ExportFormat,
+    #[serde(default)]
+    pub filter: ExportFilter,
+}
+
+#[derive(FromRow, Debug, Clone, Serialize)]
+pub struct Export {
+    pub id: u64,
+    pub kind: String,
+    pub format: String,
+    pub filter: Json<ExportFilter>,
+    pub status: String,
+    pub total_rows: u64,
+    pub processed_rows: u64,
+    #[serde(skip)]
+    pub blob_key: Option<String>,
+    pub file_size: Option<u64>,
+    pub error: Option<String>,
+    pub admin_id: u32,
+    /// 下载链接的过期时间
+    pub expires_at: Option<DateTime<Local>>,
+    pub started_at: Option<DateTime<Local>>,
+    pub finished_at: Option<DateTime<Local>>,
+    pub created_at: Option<DateTime<Local>>,
+    pub updated_at: Option<DateTime<Local>>,
+}
+
+impl Export {
+    pub fn is(&self, status: ExportStatus) -> bool {
+        self.status == status.as_str()
+    }
+
+    pub fn file_name(&self) -> String {
+        format!("{}-{}.{}", self.kind, self.id, self.format)
+    }
+}
+
+/// `GET /admin/exports/{id}` 的返回
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportProgress {
+    #[serde(flatten)]
+    pub export: Export,
+    /// 已写入行数占总行数的百分比
+    pub percent: u32,
+    /// 导出成功且未过期时的下载地址
+    pub download_url: Option<String>,
+}
+
+/// 下载的导出文件，内容从文件存储流式读取
+pub struct ExportFile {
+    pub file_name: String,
+    pub content_type: &'static str,
+    pub content: BlobFile,
+}
+
+/// 一行导出数据：主键与各列
+pub type ExportRow = (u64, Vec<Cell>);
+
+/// 导出文件中的一个单元格
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Text(String),
+    Int(i64),
+    /// 金额（分），输出为元
+    Amount(i64),
+}
+
+impl Cell {
+    /// CSV 中的文本形式；以公式字符开头的文本前加单引号，避免被表格软件当作公式执行
+    pub fn to_text(&self) -> String {
+        match self {
+            Cell::Text(text) if text.starts_with(['=', '+', '-', '@']) => format!("'{}", text),
+            Cell::Text(text) => text.clone(),
+            Cell::Int(value) => value.to_string(),
+            Cell::Amount(cents) => {
+                let sign = if *cents < 0 { "-" } else { "" };
+                format!("{}{}.{:02}", sign, cents.abs() / 100, cents.abs() % 100)
+            }
+        }
+    }
+}
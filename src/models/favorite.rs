@@ -0,0 +1,31 @@
+use sqlx::FromRow;
+use serde::Serialize;
+use chrono::{DateTime, Local};
+
+#[derive(FromRow, Debug, Clone, Serialize)]
+pub struct Favorite {
+    pub id: u64,
+    pub user_id: u32,
+    pub product_id: u64,
+    pub created_at: Option<DateTime<Local>>,
+}
+
+/// 浏览记录中的一条，`viewed_at` 为毫秒时间戳
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ViewedProduct {
+    pub product_id: u64,
+    pub viewed_at: i64,
+}
+
+/// 收藏或浏览记录列表中的商品，价格和可售状态取自当前商品数据
+#[derive(Debug, Clone, Serialize)]
+pub struct ProductCard {
+    pub product_id: u64,
+    pub name: String,
+    /// 在售 SKU 的最低价（分），已下架时为空
+    pub min_price: Option<i64>,
+    pub available: bool,
+    pub favorite_count: u32,
+    /// 收藏或浏览的时间
+    pub saved_at: DateTime<Local>,
+}
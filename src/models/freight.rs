@@ -0,0 +1,112 @@
+use sqlx::FromRow;
+use sqlx::types::Json;
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Local};
+use std::str::FromStr;
+
+/// 计费方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChargeBy {
+    /// 按件数
+    Piece,
+    /// 按重量（克）
+    Weight,
+}
+
+impl ChargeBy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChargeBy::Piece => "piece",
+            ChargeBy::Weight => "weight",
+        }
+    }
+}
+
+impl FromStr for ChargeBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use ChargeBy::*;
+        [Piece, Weight]
+            .into_iter()
+            .find(|charge_by| charge_by.as_str() == s)
+            .ok_or_else(|| format!("unknown charge_by: {}", s))
+    }
+}
+
+/// 一条计费规则：`first` 件(克)内收 `first_fee`，之后每 `additional` 件(克)加收 `additional_fee`，不足一份按一份算
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FreightRule {
+    /// 适用的省份，为空表示默认规则
+    #[serde(default)]
+    pub provinces: Vec<String>,
+    pub first: u32,
+    pub first_fee: i64,
+    pub additional: u32,
+    pub additional_fee: i64,
+}
+
+impl FreightRule {
+    pub fn fee(&self, units: u64) -> i64 {
+        if units == 0 {
+            return 0;
+        }
+        let extra = units.saturating_sub(u64::from(self.first));
+        if extra == 0 || self.additional == 0 {
+            return self.first_fee;
+        }
+        let steps = extra.div_ceil(u64::from(self.additional));
+        self.first_fee + steps as i64 * self.additional_fee
+    }
+}
+
+#[derive(FromRow, Debug, Clone, Serialize)]
+pub struct FreightTemplate {
+    pub id: u64,
+    pub name: String,
+    pub charge_by: String,
+    pub rules: Json<Vec<FreightRule>>,
+    pub free_threshold: i64,
+    pub created_at: Option<DateTime<Local>>,
+    pub updated_at: Option<DateTime<Local>>,
+}
+
+impl FreightTemplate {
+    /// 收货省份适用的规则：优先匹配省份，否则用默认规则
+    pub fn rule_for(&self, province: &str) -> Option<&FreightRule> {
+        let rules = &self.rules.0;
+        rules
+            .iter()
+            .find(|rule| rule.provinces.iter().any(|p| p == province))
+            .or_else(|| rules.iter().find(|rule| rule.provinces.is_empty()))
+    }
+}
+
+/// 后台创建或修改的模板
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewFreightTemplate {
+    pub name: String,
+    pub charge_by: ChargeBy,
+    pub rules: Vec<FreightRule>,
+    #[serde(default)]
+    pub free_threshold: i64,
+}
+
+impl NewFreightTemplate {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("name is required".to_string());
+        }
+        if self.free_threshold < 0 {
+            return Err("free_threshold must not be negative".to_string());
+        }
+        if self.rules.iter().filter(|rule| rule.provinces.is_empty()).count() != 1 {
+            return Err("exactly one default rule without provinces is required".to_string());
+        }
+        if self.rules.iter().any(|rule| rule.first == 0 || rule.first_fee < 0 || rule.additional_fee < 0) {
+            return Err("rules need a positive first unit and non-negative fees".to_string());
+        }
+        Ok(())
+    }
+}
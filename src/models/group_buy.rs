@@ -0,0 +1,157 @@
+use sqlx::FromRow;
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Duration, Local};
+use std::str::FromStr;
+use crate::models::order::OrderDetail;
+
+/// 活动进行中
+pub const CAMPAIGN_ACTIVE: &str = "active";
+/// 活动已下线，已开的团照常进行
+pub const CAMPAIGN_DISABLED: &str = "disabled";
+
+/// 团状态：拼团中 -> 成团 / 失败
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupStatus {
+    Forming,
+    Succeeded,
+    Failed,
+}
+
+impl GroupStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GroupStatus::Forming => "forming",
+            GroupStatus::Succeeded => "succeeded",
+            GroupStatus::Failed => "failed",
+        }
+    }
+}
+
+impl FromStr for GroupStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use GroupStatus::*;
+        [Forming, Succeeded, Failed]
+            .into_iter()
+            .find(|status| status.as_str() == s)
+            .ok_or_else(|| format!("unknown group status: {}", s))
+    }
+}
+
+/// 拼团活动；金额单位：分
+#[derive(FromRow, Debug, Clone, Serialize)]
+pub struct GroupCampaign {
+    pub id: u64,
+    pub name: String,
+    pub sku_id: u64,
+    pub group_price: i64,
+    pub group_size: u32,
+    pub expire_minutes: u32,
+    pub starts_at: DateTime<Local>,
+    pub ends_at: DateTime<Local>,
+    pub status: String,
+    pub created_at: Option<DateTime<Local>>,
+    pub updated_at: Option<DateTime<Local>>,
+}
+
+impl GroupCampaign {
+    /// 可以开团：活动启用且在活动时间内
+    pub fn is_open(&self, now: DateTime<Local>) -> bool {
+        self.status == CAMPAIGN_ACTIVE && self.starts_at <= now && now < self.ends_at
+    }
+
+    /// 在 `now` 开的团的截止时间，不晚于活动结束
+    pub fn group_expires_at(&self, now: DateTime<Local>) -> DateTime<Local> {
+        (now + Duration::minutes(i64::from(self.expire_minutes))).min(self.ends_at)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewGroupCampaign {
+    pub name: String,
+    pub sku_id: u64,
+    pub group_price: i64,
+    pub group_size: u32,
+    pub expire_minutes: u32,
+    pub starts_at: DateTime<Local>,
+    pub ends_at: DateTime<Local>,
+}
+
+impl NewGroupCampaign {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("name is required".to_string());
+        }
+        if self.group_price <= 0 {
+            return Err("group_price must be positive".to_string());
+        }
+        if !(2..=100).contains(&self.group_size) {
+            return Err("group_size must be between 2 and 100".to_string());
+        }
+        if self.expire_minutes == 0 {
+            return Err("expire_minutes must be positive".to_string());
+        }
+        if self.ends_at <= self.starts_at {
+            return Err("ends_at must be after starts_at".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(FromRow, Debug, Clone, Serialize)]
+pub struct GroupBuy {
+    pub id: u64,
+    pub campaign_id: u64,
+    pub leader_id: u32,
+    pub group_size: u32,
+    pub paid_count: u32,
+    pub status: String,
+    pub expires_at: DateTime<Local>,
+    pub succeeded_at: Option<DateTime<Local>>,
+    pub created_at: Option<DateTime<Local>>,
+    pub updated_at: Option<DateTime<Local>>,
+}
+
+impl GroupBuy {
+    pub fn is(&self, status: GroupStatus) -> bool {
+        self.status == status.as_str()
+    }
+}
+
+#[derive(FromRow, Debug, Clone, Serialize)]
+pub struct GroupMember {
+    pub id: u64,
+    pub group_id: u64,
+    pub user_id: u32,
+    pub is_leader: bool,
+    pub order_id: Option<u64>,
+    pub paid_at: Option<DateTime<Local>>,
+    pub created_at: Option<DateTime<Local>>,
+}
+
+/// 分享页展示的团详情
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupDetail {
+    #[serde(flatten)]
+    pub group: GroupBuy,
+    pub campaign: GroupCampaign,
+    pub members: Vec<GroupMember>,
+}
+
+/// 开团或参团的结果：团详情和需要支付的订单
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupOrder {
+    pub group: GroupDetail,
+    pub order: OrderDetail,
+}
+
+/// 参团占位的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoinOutcome {
+    Joined(u64),
+    /// 团已结束或已过截止时间
+    Closed,
+    Full,
+    AlreadyMember,
+}
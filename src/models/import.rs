@@ -0,0 +1,125 @@
+use sqlx::FromRow;
+use serde::Serialize;
+use chrono::{DateTime, Local};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportStatus {
+    Pending,
+    Running,
+    Succeeded,
+    /// 有行未通过校验（不写入任何数据）或执行出错
+    Failed,
+}
+
+impl ImportStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImportStatus::Pending => "pending",
+            ImportStatus::Running => "running",
+            ImportStatus::Succeeded => "succeeded",
+            ImportStatus::Failed => "failed",
+        }
+    }
+}
+
+#[derive(FromRow, Debug, Clone, Serialize)]
+pub struct Import {
+    pub id: u64,
+    pub format: String,
+    pub dry_run: bool,
+    pub status: String,
+    #[serde(skip)]
+    pub report_key: Option<String>,
+    pub total_rows: u64,
+    pub processed_rows: u64,
+    pub error_rows: u64,
+    pub created_products: u64,
+    pub updated_products: u64,
+    pub created_skus: u64,
+    pub updated_skus: u64,
+    pub error: Option<String>,
+    pub admin_id: u32,
+    pub started_at: Option<DateTime<Local>>,
+    pub finished_at: Option<DateTime<Local>>,
+    pub created_at: Option<DateTime<Local>>,
+    pub updated_at: Option<DateTime<Local>>,
+}
+
+impl Import {
+    pub fn is(&self, status: ImportStatus) -> bool {
+        self.status == status.as_str()
+    }
+
+    /// 上传的原始文件
+    pub fn source_key(&self) -> String {
+        source_key(self.id, &self.format)
+    }
+}
+
+pub fn source_key(id: u64, format: &str) -> String {
+    format!("imports/{}/source.{}", id, format)
+}
+
+/// `GET /admin/imports/{id}` 的返回
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportProgress {
+    #[serde(flatten)]
+    pub import: Import,
+    /// 已处理行数占总行数的百分比
+    pub percent: u32,
+    /// 逐行结果文件的下载地址
+    pub report_url: Option<String>,
+}
+
+/// 导入结束时的统计
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportCounts {
+    pub total_rows: u64,
+    pub error_rows: u64,
+    pub created_products: u64,
+    pub updated_products: u64,
+    pub created_skus: u64,
+    pub updated_skus: u64,
+}
+
+/// 校验通过的一行，对应一个 SKU
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkuImport {
+    /// 在文件中的行号，用于结果文件
+    pub line: u64,
+    pub code: String,
+    pub title: String,
+    /// 单价，单位：分
+    pub price: i64,
+    pub weight_grams: u32,
+    pub status: String,
+    /// 新建 SKU 的初始库存，已有 SKU 不修改库存
+    pub stock: i64,
+}
+
+/// 按商品编码归并后的商品及其 SKU
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProductImport {
+    pub code: String,
+    pub name: String,
+    pub category_id: u64,
+    pub status: String,
+    pub skus: Vec<SkuImport>,
+}
+
+/// 写入一个商品的结果，`previous_prices` 与 `ProductImport::skus` 一一对应，
+/// 为写入前的价格，None 表示该 SKU 为新增
+#[derive(Debug, Clone)]
+pub struct ProductUpsert {
+    pub created: bool,
+    pub previous_prices: Vec<Option<i64>>,
+}
+
+/// 未通过校验的一行
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineError {
+    pub line: u64,
+    pub product_code: String,
+    pub sku_code: String,
+    pub message: String,
+}
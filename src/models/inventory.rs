@@ -0,0 +1,55 @@
+use sqlx::FromRow;
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Local};
+
+/// 秒杀模式：Redis 计数器扣减，后台异步回写 MySQL；默认的 `mysql` 模式走行锁扣减
+pub const STOCK_MODE_REDIS: &str = "redis";
+
+#[derive(FromRow, Debug, Clone, Serialize, Deserialize)]
+pub struct Inventory {
+    pub sku_id: u64,
+    pub stock: i64,
+    pub stock_mode: String,
+    pub per_user_limit: Option<u32>,
+    pub created_at: Option<DateTime<Local>>,
+    pub updated_at: Option<DateTime<Local>>,
+}
+
+impl Inventory {
+    pub fn is_redis_mode(&self) -> bool {
+        self.stock_mode == STOCK_MODE_REDIS
+    }
+}
+
+/// 库存流水类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MovementKind {
+    /// 下单预占（扣减可售库存）
+    Reserve,
+    /// 取消 / 超时释放预占
+    Release,
+    /// 售后退货入库
+    Restock,
+}
+
+impl MovementKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MovementKind::Reserve => "reserve",
+            MovementKind::Release => "release",
+            MovementKind::Restock => "restock",
+        }
+    }
+}
+
+/// 一次库存变动；Redis 模式下由 Lua 脚本写入队列，再由对账任务落库
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StockMovement {
+    pub sku_id: u64,
+    pub order_ref: String,
+    pub user_id: u32,
+    pub kind: MovementKind,
+    /// 对可售库存的增量：预占为负，释放和入库为正
+    pub delta: i64,
+}
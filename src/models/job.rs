@@ -0,0 +1,28 @@
+use serde::{Serialize, Deserialize};
+
+/// 默认最多投递次数，超过后进入死信队列
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 8;
+
+/// 延迟队列中的任务；`id` 相同的任务只会保留一份，用于去重
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: String,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    /// 已投递次数，由队列在每次领取时加一
+    #[serde(default)]
+    pub attempts: u32,
+    pub max_attempts: u32,
+}
+
+impl Job {
+    pub fn new(kind: &str, key: &str, payload: serde_json::Value) -> Self {
+        Job {
+            id: format!("{}:{}", kind, key),
+            kind: kind.to_string(),
+            payload,
+            attempts: 0,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+        }
+    }
+}
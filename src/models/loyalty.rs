@@ -0,0 +1,115 @@
+use sqlx::FromRow;
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Local};
+use std::fmt;
+use std::str::FromStr;
+
+/// 积分流水类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PointsKind {
+    /// 订单完成发放
+    Earn,
+    /// 下单抵扣
+    Redeem,
+    /// 订单取消或退款，退回抵扣的积分
+    Release,
+    /// 订单退款，收回发放的积分
+    Reverse,
+    /// 到期清零
+    Expire,
+}
+
+impl PointsKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PointsKind::Earn => "earn",
+            PointsKind::Redeem => "redeem",
+            PointsKind::Release => "release",
+            PointsKind::Reverse => "reverse",
+            PointsKind::Expire => "expire",
+        }
+    }
+}
+
+impl fmt::Display for PointsKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for PointsKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [PointsKind::Earn, PointsKind::Redeem, PointsKind::Release, PointsKind::Reverse, PointsKind::Expire]
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| format!("unknown points kind: {}", s))
+    }
+}
+
+/// 会员积分余额与等级
+#[derive(FromRow, Debug, Clone, Default, Serialize)]
+pub struct Member {
+    pub user_id: u32,
+    pub level: u32,
+    pub points_balance: i64,
+    pub rolling_spend: i64,
+    pub created_at: Option<DateTime<Local>>,
+    pub updated_at: Option<DateTime<Local>>,
+}
+
+#[derive(FromRow, Debug, Clone, Serialize)]
+pub struct PointsEntry {
+    pub id: u64,
+    pub user_id: u32,
+    pub kind: String,
+    pub delta: i64,
+    pub balance_after: i64,
+    pub ref_no: String,
+    pub expires_at: Option<DateTime<Local>>,
+    pub note: String,
+    pub created_at: Option<DateTime<Local>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewPointsEntry {
+    pub user_id: u32,
+    pub kind: PointsKind,
+    pub delta: i64,
+    pub ref_no: String,
+    pub expires_at: Option<DateTime<Local>>,
+    pub note: String,
+}
+
+/// 写入一条积分流水的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointsOutcome {
+    /// 已记账，附带记账后的余额
+    Applied(i64),
+    /// 同一业务单号已经记过，什么都没有做
+    Duplicate,
+    /// 扣减后余额为负，没有记账
+    Insufficient,
+}
+
+/// 下一个会员等级及还差的消费金额
+#[derive(Debug, Clone, Serialize)]
+pub struct NextTier {
+    pub level: u32,
+    pub name: String,
+    pub min_spend: i64,
+    pub spend_needed: i64,
+}
+
+/// `GET /me/points` 的返回
+#[derive(Debug, Clone, Serialize)]
+pub struct MyPoints {
+    pub balance: i64,
+    pub level: u32,
+    pub tier_name: String,
+    pub rolling_spend: i64,
+    pub next_tier: Option<NextTier>,
+    pub history: Vec<PointsEntry>,
+}
@@ -1,7 +1,36 @@
+pub mod inventory;
+pub mod catalog;
+pub mod cart;
+pub mod address;
+pub mod order;
+pub mod job;
+pub mod payment;
+pub mod refund;
+pub mod reconciliation;
+pub mod coupon;
+pub mod promotion;
+pub mod group_buy;
+pub mod freight;
+pub mod shipment;
+pub mod after_sale;
+pub mod review;
+pub mod notification;
+pub mod loyalty;
+pub mod favorite;
+pub mod referral;
+pub mod stats;
+pub mod export;
+pub mod import;
+pub mod audit;
+pub mod event;
+
 use sqlx::FromRow;
 use serde::{Serialize, Deserialize};
 use chrono::{DateTime, Local};
 
+/// 后台管理员角色
+pub const ROLE_ADMIN: &str = "admin";
+
 #[derive(FromRow, Debug, Clone, Serialize, Deserialize)]
 pub struct User {
     pub id: u32,
@@ -10,6 +39,14 @@ pub struct User {
     pub passwd: String,
     #[serde(skip)]
     pub salt: String,
+    #[serde(default)]
+    pub role: String,
     pub created_at: Option<DateTime<Local>>,
     pub updated_at: Option<DateTime<Local>>,
-}
\ No newline at end of file
+}
+
+impl User {
+    pub fn is_admin(&self) -> bool {
+        self.role == ROLE_ADMIN
+    }
+}
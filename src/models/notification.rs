@@ -0,0 +1,83 @@
+use sqlx::FromRow;
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Local};
+use std::collections::BTreeMap;
+
+/// 用户对某个模板的订阅授权
+#[derive(FromRow, Debug, Clone, Serialize)]
+pub struct Subscription {
+    pub id: u64,
+    pub user_id: u32,
+    pub template_id: String,
+    pub openid: String,
+    pub remaining: u32,
+    pub created_at: Option<DateTime<Local>>,
+    pub updated_at: Option<DateTime<Local>>,
+}
+
+/// 小程序端 `requestSubscribeMessage` 返回后上报的授权结果
+#[derive(Debug, Clone, Deserialize)]
+pub struct SubscribeApply {
+    pub openid: String,
+    /// 用户点了「允许」的模板
+    pub template_ids: Vec<String>,
+}
+
+/// 一次发送尝试的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryStatus {
+    Sent,
+    /// 调用失败，任务会重试
+    Failed,
+    /// 没有可用授权或用户已拒收，不再重试
+    Skipped,
+}
+
+impl DeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryStatus::Sent => "sent",
+            DeliveryStatus::Failed => "failed",
+            DeliveryStatus::Skipped => "skipped",
+        }
+    }
+}
+
+#[derive(FromRow, Debug, Clone, Serialize)]
+pub struct Delivery {
+    pub id: u64,
+    pub user_id: u32,
+    pub order_id: u64,
+    pub event: String,
+    pub template_id: String,
+    pub status: String,
+    pub error: Option<String>,
+    pub created_at: Option<DateTime<Local>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewDelivery {
+    pub user_id: u32,
+    pub order_id: u64,
+    pub event: String,
+    pub template_id: String,
+    pub status: DeliveryStatus,
+    pub error: Option<String>,
+}
+
+/// 待发送的订阅消息，`data` 为模板字段名到取值
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubscribeMessage {
+    pub openid: String,
+    pub template_id: String,
+    pub page: String,
+    pub data: BTreeMap<String, String>,
+}
+
+/// 发送结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendOutcome {
+    Sent,
+    /// 用户未授权或已拒收（微信返回 43101）
+    Refused,
+}
@@ -0,0 +1,216 @@
+use sqlx::FromRow;
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Local};
+use std::fmt;
+use std::str::FromStr;
+use crate::models::inventory::StockMovement;
+
+/// 订单状态，数据库中以 snake_case 字符串保存
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderStatus {
+    PendingPayment,
+    Paid,
+    Shipped,
+    Delivered,
+    Completed,
+    Cancelled,
+    Refunding,
+    Refunded,
+    Closed,
+}
+
+impl OrderStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderStatus::PendingPayment => "pending_payment",
+            OrderStatus::Paid => "paid",
+            OrderStatus::Shipped => "shipped",
+            OrderStatus::Delivered => "delivered",
+            OrderStatus::Completed => "completed",
+            OrderStatus::Cancelled => "cancelled",
+            OrderStatus::Refunding => "refunding",
+            OrderStatus::Refunded => "refunded",
+            OrderStatus::Closed => "closed",
+        }
+    }
+
+    /// 状态机允许的流转。`Refunding` 回到付款后的各状态表示退款被拒绝或撤回，
+    /// 具体回到哪一个由服务层根据流转记录决定。
+    pub fn can_transition_to(&self, to: OrderStatus) -> bool {
+        use OrderStatus::*;
+        matches!(
+            (self, to),
+            (PendingPayment, Paid)
+                | (PendingPayment, Cancelled)
+                | (Paid, Shipped)
+                | (Paid, Refunding)
+                | (Shipped, Delivered)
+                | (Shipped, Refunding)
+                | (Delivered, Completed)
+                | (Delivered, Refunding)
+                | (Completed, Refunding)
+                | (Refunding, Refunded)
+                | (Refunding, Paid)
+                | (Refunding, Shipped)
+                | (Refunding, Delivered)
+                | (Refunding, Completed)
+                | (Refunded, Closed)
+        )
+    }
+}
+
+impl fmt::Display for OrderStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for OrderStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use OrderStatus::*;
+        [PendingPayment, Paid, Shipped, Delivered, Completed, Cancelled, Refunding, Refunded, Closed]
+            .into_iter()
+            .find(|status| status.as_str() == s)
+            .ok_or_else(|| format!("unknown order status: {}", s))
+    }
+}
+
+/// 触发状态流转的一方
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActorType {
+    User,
+    Admin,
+    System,
+}
+
+impl ActorType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActorType::User => "user",
+            ActorType::Admin => "admin",
+            ActorType::System => "system",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Actor {
+    pub actor_type: ActorType,
+    /// 用户或管理员 ID，系统任务为 0
+    pub id: u32,
+}
+
+impl Actor {
+    pub fn user(id: u32) -> Self {
+        Actor { actor_type: ActorType::User, id }
+    }
+
+    pub fn admin(id: u32) -> Self {
+        Actor { actor_type: ActorType::Admin, id }
+    }
+
+    pub fn system() -> Self {
+        Actor { actor_type: ActorType::System, id: 0 }
+    }
+}
+
+/// 一次状态流转的审计记录
+#[derive(FromRow, Debug, Clone, Serialize, Deserialize)]
+pub struct OrderEvent {
+    pub id: u64,
+    pub order_id: u64,
+    pub from_status: Option<String>,
+    pub to_status: String,
+    pub actor_type: String,
+    pub actor_id: u32,
+    pub reason: String,
+    pub created_at: Option<DateTime<Local>>,
+}
+
+/// 订单；金额单位：分
+#[derive(FromRow, Debug, Clone, Serialize, Deserialize)]
+pub struct Order {
+    pub id: u64,
+    pub order_no: String,
+    pub user_id: u32,
+    pub status: String,
+    pub goods_amount: i64,
+    pub discount_amount: i64,
+    pub shipping_fee: i64,
+    pub payable_amount: i64,
+    pub receiver_name: String,
+    pub receiver_phone: String,
+    pub province: String,
+    pub city: String,
+    pub district: String,
+    pub address_detail: String,
+    pub created_at: Option<DateTime<Local>>,
+    pub updated_at: Option<DateTime<Local>>,
+}
+
+impl Order {
+    pub fn status(&self) -> Result<OrderStatus, String> {
+        self.status.parse()
+    }
+}
+
+/// 订单行，商品信息与价格为下单时快照
+#[derive(FromRow, Debug, Clone, Serialize, Deserialize)]
+pub struct OrderItem {
+    pub id: u64,
+    pub order_id: u64,
+    pub sku_id: u64,
+    pub product_id: u64,
+    pub product_name: String,
+    pub sku_title: String,
+    pub unit_price: i64,
+    pub quantity: u32,
+    pub line_total: i64,
+    pub discount_amount: i64,
+    pub payable_amount: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OrderDetail {
+    #[serde(flatten)]
+    pub order: Order,
+    pub items: Vec<OrderItem>,
+}
+
+/// 待写入的订单，`id` 由数据库生成
+#[derive(Debug, Clone)]
+pub struct NewOrder {
+    pub order_no: String,
+    pub user_id: u32,
+    pub status: OrderStatus,
+    pub goods_amount: i64,
+    pub discount_amount: i64,
+    pub shipping_fee: i64,
+    pub payable_amount: i64,
+    pub receiver_name: String,
+    pub receiver_phone: String,
+    pub province: String,
+    pub city: String,
+    pub district: String,
+    pub address_detail: String,
+    pub items: Vec<NewOrderItem>,
+    /// MySQL 模式 SKU 的库存预占，与订单在同一事务中写入
+    pub reservations: Vec<StockMovement>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewOrderItem {
+    pub sku_id: u64,
+    pub product_id: u64,
+    pub product_name: String,
+    pub sku_title: String,
+    pub unit_price: i64,
+    pub quantity: u32,
+    pub line_total: i64,
+    pub discount_amount: i64,
+    pub payable_amount: i64,
+}
@@ -0,0 +1,51 @@
+use sqlx::FromRow;
+use serde::Serialize;
+use chrono::{DateTime, Local};
+
+/// 微信支付 JSAPI / 小程序支付
+pub const CHANNEL_WECHAT_JSAPI: &str = "wechat_jsapi";
+
+/// 已创建预支付单、等待用户付款
+pub const PAYMENT_PENDING: &str = "pending";
+/// 渠道通知支付成功
+pub const PAYMENT_SUCCEEDED: &str = "succeeded";
+/// 渠道侧交易已关闭（超时未付或被撤销）
+pub const PAYMENT_CLOSED: &str = "closed";
+
+#[derive(FromRow, Debug, Clone, Serialize)]
+pub struct Payment {
+    pub id: u64,
+    pub order_id: u64,
+    pub user_id: u32,
+    pub channel: String,
+    pub out_trade_no: String,
+    pub amount: i64,
+    pub status: String,
+    pub prepay_id: Option<String>,
+    pub transaction_id: Option<String>,
+    pub paid_at: Option<DateTime<Local>>,
+    pub created_at: Option<DateTime<Local>>,
+    pub updated_at: Option<DateTime<Local>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewPayment {
+    pub order_id: u64,
+    pub user_id: u32,
+    pub channel: &'static str,
+    pub out_trade_no: String,
+    pub amount: i64,
+    pub prepay_id: String,
+}
+
+/// 小程序 `wx.requestPayment` 的参数，字段名与微信文档保持一致
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsapiPayParams {
+    pub app_id: String,
+    pub time_stamp: String,
+    pub nonce_str: String,
+    pub package: String,
+    pub sign_type: String,
+    pub pay_sign: String,
+}
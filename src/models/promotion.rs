@@ -0,0 +1,166 @@
+use sqlx::FromRow;
+use sqlx::types::Json;
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Local};
+
+/// 活动启用
+pub const PROMOTION_ACTIVE: &str = "active";
+/// 活动已下线
+pub const PROMOTION_DISABLED: &str = "disabled";
+
+/// 活动适用的商品范围；三个列表都为空表示全场，否则命中任意一个即可
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PromotionScope {
+    #[serde(default)]
+    pub category_ids: Vec<u64>,
+    #[serde(default)]
+    pub product_ids: Vec<u64>,
+    #[serde(default)]
+    pub sku_ids: Vec<u64>,
+}
+
+impl PromotionScope {
+    pub fn is_all(&self) -> bool {
+        self.category_ids.is_empty() && self.product_ids.is_empty() && self.sku_ids.is_empty()
+    }
+
+    pub fn matches(&self, category_id: u64, product_id: u64, sku_id: u64) -> bool {
+        self.is_all()
+            || self.category_ids.contains(&category_id)
+            || self.product_ids.contains(&product_id)
+            || self.sku_ids.contains(&sku_id)
+    }
+}
+
+/// 满额档位：适用商品应付满 `threshold` 减 `off`，金额单位：分
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpendTier {
+    pub threshold: i64,
+    pub off: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemberPrice {
+    pub sku_id: u64,
+    pub price: i64,
+}
+
+/// 活动规则，以 JSON 存在 `promotions.rule` 中，`type` 区分规则类型
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PromotionRule {
+    /// 阶梯满减，取满足的最高一档
+    TieredSpend {
+        #[serde(default)]
+        scope: PromotionScope,
+        tiers: Vec<SpendTier>,
+    },
+    /// 买 N 送 M：适用商品每 N+M 件中最便宜的 M 件免单
+    BuyNGetM {
+        #[serde(default)]
+        scope: PromotionScope,
+        buy: u32,
+        free: u32,
+    },
+    /// 组合价：每 SKU 各一件为一组，按 `price` 计价，可成多组
+    Bundle { sku_ids: Vec<u64>, price: i64 },
+    /// 会员价：会员等级不低于 `min_level` 时按指定单价计价
+    MemberPrice {
+        #[serde(default = "default_min_level")]
+        min_level: u32,
+        prices: Vec<MemberPrice>,
+    },
+    /// 限时折扣：活动时间内按比例减价，15 表示减 15%
+    LimitedTime {
+        #[serde(default)]
+        scope: PromotionScope,
+        percent_off: u32,
+    },
+}
+
+fn default_min_level() -> u32 {
+    1
+}
+
+impl PromotionRule {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            PromotionRule::TieredSpend { tiers, .. } => {
+                if tiers.is_empty() || tiers.iter().any(|tier| tier.off <= 0 || tier.threshold < tier.off) {
+                    return Err("tiers need 0 < off <= threshold".to_string());
+                }
+            }
+            PromotionRule::BuyNGetM { buy, free, .. } => {
+                if *buy == 0 || *free == 0 {
+                    return Err("buy and free must be positive".to_string());
+                }
+            }
+            PromotionRule::Bundle { sku_ids, price } => {
+                let mut distinct = sku_ids.clone();
+                distinct.sort_unstable();
+                distinct.dedup();
+                if distinct.len() < 2 || distinct.len() != sku_ids.len() || *price <= 0 {
+                    return Err("bundle needs at least 2 distinct SKUs and a positive price".to_string());
+                }
+            }
+            PromotionRule::MemberPrice { prices, .. } => {
+                if prices.is_empty() || prices.iter().any(|price| price.price <= 0) {
+                    return Err("member prices must be positive".to_string());
+                }
+            }
+            PromotionRule::LimitedTime { percent_off, .. } => {
+                if !(1..=99).contains(percent_off) {
+                    return Err("percent_off must be between 1 and 99".to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(FromRow, Debug, Clone, Serialize)]
+pub struct Promotion {
+    pub id: u64,
+    pub name: String,
+    /// 数值越大越先计算
+    pub priority: i32,
+    /// 独占：不与其他活动叠加在同一商品行上
+    pub exclusive: bool,
+    pub rule: Json<PromotionRule>,
+    pub starts_at: DateTime<Local>,
+    pub ends_at: DateTime<Local>,
+    pub status: String,
+    pub created_at: Option<DateTime<Local>>,
+    pub updated_at: Option<DateTime<Local>>,
+}
+
+impl Promotion {
+    pub fn is_active(&self, now: DateTime<Local>) -> bool {
+        self.status == PROMOTION_ACTIVE && self.starts_at <= now && now < self.ends_at
+    }
+}
+
+/// 后台创建的活动
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewPromotion {
+    pub name: String,
+    #[serde(default)]
+    pub priority: i32,
+    #[serde(default)]
+    pub exclusive: bool,
+    pub rule: PromotionRule,
+    pub starts_at: DateTime<Local>,
+    pub ends_at: DateTime<Local>,
+}
+
+impl NewPromotion {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("name is required".to_string());
+        }
+        if self.ends_at <= self.starts_at {
+            return Err("ends_at must be after starts_at".to_string());
+        }
+        self.rule.validate()
+    }
+}
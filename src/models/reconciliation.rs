@@ -0,0 +1,67 @@
+use sqlx::FromRow;
+use serde::Serialize;
+use chrono::{DateTime, Local, NaiveDate};
+
+/// 差异类别：支付单 / 退款单
+pub const CATEGORY_PAYMENT: &str = "payment";
+pub const CATEGORY_REFUND: &str = "refund";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiscrepancyKind {
+    /// 账单中有，本地没有成功记录
+    MissingLocal,
+    /// 本地成功，账单中没有
+    MissingRemote,
+    /// 两边都有但金额不一致
+    AmountMismatch,
+}
+
+impl DiscrepancyKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiscrepancyKind::MissingLocal => "missing_local",
+            DiscrepancyKind::MissingRemote => "missing_remote",
+            DiscrepancyKind::AmountMismatch => "amount_mismatch",
+        }
+    }
+}
+
+#[derive(FromRow, Debug, Clone, Serialize)]
+pub struct ReconciliationRun {
+    pub id: u64,
+    pub bill_date: NaiveDate,
+    pub remote_count: u32,
+    pub local_count: u32,
+    pub discrepancy_count: u32,
+    pub created_at: Option<DateTime<Local>>,
+    pub updated_at: Option<DateTime<Local>>,
+}
+
+/// 对账差异；金额单位：分
+#[derive(FromRow, Debug, Clone, Serialize)]
+pub struct Discrepancy {
+    pub id: u64,
+    pub run_id: u64,
+    pub category: String,
+    pub kind: String,
+    pub reference_no: String,
+    pub local_amount: Option<i64>,
+    pub remote_amount: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewDiscrepancy {
+    pub category: &'static str,
+    pub kind: DiscrepancyKind,
+    pub reference_no: String,
+    pub local_amount: Option<i64>,
+    pub remote_amount: Option<i64>,
+}
+
+/// 某个账单日的对账报告
+#[derive(Debug, Clone, Serialize)]
+pub struct ReconciliationReport {
+    #[serde(flatten)]
+    pub run: ReconciliationRun,
+    pub discrepancies: Vec<Discrepancy>,
+}
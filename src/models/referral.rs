@@ -0,0 +1,113 @@
+use sqlx::FromRow;
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Local};
+use std::str::FromStr;
+
+/// 佣金状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommissionStatus {
+    /// 订单已支付，售后期未结束
+    Pending,
+    /// 售后期结束，可以提现
+    Withdrawable,
+    /// 订单退款，佣金冲回
+    Reversed,
+}
+
+impl CommissionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CommissionStatus::Pending => "pending",
+            CommissionStatus::Withdrawable => "withdrawable",
+            CommissionStatus::Reversed => "reversed",
+        }
+    }
+}
+
+impl FromStr for CommissionStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use CommissionStatus::*;
+        [Pending, Withdrawable, Reversed]
+            .into_iter()
+            .find(|status| status.as_str() == s)
+            .ok_or_else(|| format!("unknown commission status: {}", s))
+    }
+}
+
+#[derive(FromRow, Debug, Clone, Serialize)]
+pub struct ReferralCode {
+    pub user_id: u32,
+    pub code: String,
+    pub created_at: Option<DateTime<Local>>,
+}
+
+/// 客户归属的推广人
+#[derive(FromRow, Debug, Clone, Serialize)]
+pub struct ReferralBinding {
+    pub user_id: u32,
+    pub referrer_id: u32,
+    pub code: String,
+    pub bound_at: DateTime<Local>,
+    pub expires_at: DateTime<Local>,
+}
+
+impl ReferralBinding {
+    pub fn is_active(&self, now: DateTime<Local>) -> bool {
+        self.expires_at > now
+    }
+}
+
+#[derive(FromRow, Debug, Clone, Serialize)]
+pub struct Commission {
+    pub id: u64,
+    pub order_id: u64,
+    pub order_no: String,
+    pub buyer_id: u32,
+    pub beneficiary_id: u32,
+    pub level: u8,
+    pub base_amount: i64,
+    pub rate_bp: u32,
+    pub amount: i64,
+    pub status: String,
+    pub settle_at: Option<DateTime<Local>>,
+    pub settled_at: Option<DateTime<Local>>,
+    pub created_at: Option<DateTime<Local>>,
+    pub updated_at: Option<DateTime<Local>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewCommission {
+    pub order_id: u64,
+    pub order_no: String,
+    pub buyer_id: u32,
+    pub beneficiary_id: u32,
+    pub level: u8,
+    pub base_amount: i64,
+    pub rate_bp: u32,
+    pub amount: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BindReferral {
+    pub code: String,
+}
+
+/// 推广码和分享路径
+#[derive(Debug, Clone, Serialize)]
+pub struct ShareInfo {
+    pub code: String,
+    pub share_path: String,
+}
+
+/// `GET /referral/commissions` 的返回，金额单位为分
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MyCommissions {
+    pub pending: i64,
+    pub withdrawable: i64,
+    pub reversed: i64,
+    /// 当前归属自己的客户数
+    pub invited_count: i64,
+    pub items: Vec<Commission>,
+}
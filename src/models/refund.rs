@@ -0,0 +1,79 @@
+use sqlx::FromRow;
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Local};
+
+/// 退款单状态：申请 -> 审核拒绝 / 已提交渠道 -> 成功 / 失败
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefundStatus {
+    Pending,
+    Rejected,
+    Processing,
+    Succeeded,
+    Failed,
+}
+
+impl RefundStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RefundStatus::Pending => "pending",
+            RefundStatus::Rejected => "rejected",
+            RefundStatus::Processing => "processing",
+            RefundStatus::Succeeded => "succeeded",
+            RefundStatus::Failed => "failed",
+        }
+    }
+}
+
+/// 退款单；金额单位：分
+#[derive(FromRow, Debug, Clone, Serialize, Deserialize)]
+pub struct Refund {
+    pub id: u64,
+    pub refund_no: String,
+    pub order_id: u64,
+    pub order_item_id: Option<u64>,
+    pub user_id: u32,
+    pub amount: i64,
+    pub reason: String,
+    pub status: String,
+    pub order_status: String,
+    pub restock: bool,
+    pub admin_id: Option<u32>,
+    pub reject_reason: Option<String>,
+    pub channel_refund_id: Option<String>,
+    pub refunded_at: Option<DateTime<Local>>,
+    pub created_at: Option<DateTime<Local>>,
+    pub updated_at: Option<DateTime<Local>>,
+}
+
+impl Refund {
+    pub fn is(&self, status: RefundStatus) -> bool {
+        self.status == status.as_str()
+    }
+
+    /// 占用可退额度的退款：审核中、处理中和已成功的
+    pub fn holds_amount(&self) -> bool {
+        self.is(RefundStatus::Pending) || self.is(RefundStatus::Processing) || self.is(RefundStatus::Succeeded)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NewRefund {
+    pub refund_no: String,
+    pub order_id: u64,
+    pub order_item_id: Option<u64>,
+    pub user_id: u32,
+    pub amount: i64,
+    pub reason: String,
+    pub order_status: String,
+}
+
+/// 用户提交的退款申请
+#[derive(Debug, Clone, Deserialize)]
+pub struct RefundApply {
+    /// 为空表示按整单退款
+    #[serde(default)]
+    pub order_item_id: Option<u64>,
+    pub amount: i64,
+    #[serde(default)]
+    pub reason: String,
+}
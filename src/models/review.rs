@@ -0,0 +1,178 @@
+use sqlx::FromRow;
+use sqlx::types::Json;
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Local};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// 每条评价（含追评）最多图片数
+pub const MAX_REVIEW_PHOTOS: usize = 9;
+
+/// 评价审核状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewStatus {
+    Pending,
+    Approved,
+    Hidden,
+}
+
+impl ReviewStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReviewStatus::Pending => "pending",
+            ReviewStatus::Approved => "approved",
+            ReviewStatus::Hidden => "hidden",
+        }
+    }
+}
+
+impl FromStr for ReviewStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use ReviewStatus::*;
+        [Pending, Approved, Hidden]
+            .into_iter()
+            .find(|status| status.as_str() == s)
+            .ok_or_else(|| format!("unknown review status: {}", s))
+    }
+}
+
+#[derive(FromRow, Debug, Clone, Serialize)]
+pub struct Review {
+    pub id: u64,
+    pub order_id: u64,
+    pub order_item_id: u64,
+    pub user_id: u32,
+    pub product_id: u64,
+    pub sku_id: u64,
+    pub sku_title: String,
+    pub rating: u8,
+    pub content: String,
+    pub photos: Json<Vec<String>>,
+    pub has_photos: bool,
+    pub status: String,
+    pub follow_up: Option<String>,
+    pub follow_up_photos: Option<Json<Vec<String>>>,
+    pub followed_up_at: Option<DateTime<Local>>,
+    pub reply: Option<String>,
+    pub replied_at: Option<DateTime<Local>>,
+    pub created_at: Option<DateTime<Local>>,
+    pub updated_at: Option<DateTime<Local>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewReview {
+    pub order_id: u64,
+    pub order_item_id: u64,
+    pub user_id: u32,
+    pub product_id: u64,
+    pub sku_id: u64,
+    pub sku_title: String,
+    pub rating: u8,
+    pub content: String,
+    pub photos: Vec<String>,
+}
+
+/// 用户提交的评价
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReviewApply {
+    pub order_item_id: u64,
+    pub rating: u8,
+    #[serde(default)]
+    pub content: String,
+    #[serde(default)]
+    pub photos: Vec<String>,
+}
+
+impl ReviewApply {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=5).contains(&self.rating) {
+            return Err("rating must be between 1 and 5".to_string());
+        }
+        validate_text(&self.content, &self.photos)
+    }
+}
+
+/// 追评
+#[derive(Debug, Clone, Deserialize)]
+pub struct FollowUpApply {
+    pub content: String,
+    #[serde(default)]
+    pub photos: Vec<String>,
+}
+
+impl FollowUpApply {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.content.trim().is_empty() {
+            return Err("content is required".to_string());
+        }
+        validate_text(&self.content, &self.photos)
+    }
+}
+
+fn validate_text(content: &str, photos: &[String]) -> Result<(), String> {
+    if content.chars().count() > 500 {
+        return Err("content is too long".to_string());
+    }
+    if photos.len() > MAX_REVIEW_PHOTOS {
+        return Err(format!("at most {} photos are allowed", MAX_REVIEW_PHOTOS));
+    }
+    if photos.iter().any(|url| !url.starts_with("https://") || url.len() > 512) {
+        return Err("photos must be https URLs".to_string());
+    }
+    Ok(())
+}
+
+/// 商品评价列表的筛选条件
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ReviewFilter {
+    /// 只看某个星级
+    #[serde(default)]
+    pub rating: Option<u8>,
+    /// 只看有图评价
+    #[serde(default)]
+    pub has_photos: bool,
+}
+
+/// 商品上缓存的评分汇总
+#[derive(FromRow, Debug, Clone, Default)]
+pub struct ReviewStats {
+    pub review_count: u32,
+    pub rating_sum: u32,
+    pub photo_review_count: u32,
+}
+
+/// 商品评价页头部的汇总
+#[derive(Debug, Clone, Serialize)]
+pub struct ReviewSummary {
+    pub review_count: u32,
+    /// 平均分，保留一位小数；没有评价时为 0
+    pub average_rating: f64,
+    pub photo_review_count: u32,
+    /// 各星级的评价数
+    pub rating_counts: BTreeMap<u8, u32>,
+}
+
+impl ReviewSummary {
+    pub fn new(stats: &ReviewStats, rating_counts: BTreeMap<u8, u32>) -> Self {
+        let average_rating = if stats.review_count == 0 {
+            0.0
+        } else {
+            (f64::from(stats.rating_sum) * 10.0 / f64::from(stats.review_count)).round() / 10.0
+        };
+        Self {
+            review_count: stats.review_count,
+            average_rating,
+            photo_review_count: stats.photo_review_count,
+            rating_counts,
+        }
+    }
+}
+
+/// 商品评价页：汇总加一页评价
+#[derive(Debug, Clone, Serialize)]
+pub struct ProductReviews {
+    pub summary: ReviewSummary,
+    pub reviews: Vec<Review>,
+}
@@ -0,0 +1,48 @@
+use sqlx::FromRow;
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Local};
+
+/// 发货记录
+#[derive(FromRow, Debug, Clone, Serialize, Deserialize)]
+pub struct Shipment {
+    pub id: u64,
+    pub order_id: u64,
+    pub carrier: String,
+    pub tracking_no: String,
+    pub admin_id: u32,
+    pub shipped_at: DateTime<Local>,
+    pub created_at: Option<DateTime<Local>>,
+    pub updated_at: Option<DateTime<Local>>,
+}
+
+/// 物流状态，由各服务商的状态码归一而来
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackingState {
+    /// 服务商暂无轨迹
+    NoRecord,
+    Collected,
+    InTransit,
+    Delivering,
+    Delivered,
+    /// 疑难、拒签等需要人工处理的情况
+    Exception,
+    Returned,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrackingEvent {
+    /// 服务商给出的时间文本，按原样展示
+    pub time: String,
+    pub description: String,
+}
+
+/// 物流轨迹，`events` 按时间倒序
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrackingInfo {
+    pub carrier: String,
+    pub tracking_no: String,
+    pub state: TrackingState,
+    pub events: Vec<TrackingEvent>,
+    pub queried_at: DateTime<Local>,
+}
@@ -0,0 +1,107 @@
+use sqlx::FromRow;
+use serde::{Serialize, Deserialize};
+use chrono::{Datelike, Days, NaiveDate};
+
+/// 统计数据的分组粒度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Granularity {
+    #[default]
+    Day,
+    /// 自然周，周一为第一天
+    Week,
+    Month,
+}
+
+impl Granularity {
+    /// 日期所在周期的第一天
+    pub fn period_start(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Granularity::Day => date,
+            Granularity::Week => date - Days::new(u64::from(date.weekday().num_days_from_monday())),
+            Granularity::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+}
+
+/// 一天的汇总数据，金额单位：分
+#[derive(FromRow, Debug, Clone, Default, Serialize)]
+pub struct DailyStats {
+    pub stat_date: NaiveDate,
+    pub order_count: u32,
+    pub paid_order_count: u32,
+    pub gmv: i64,
+    pub paying_users: u32,
+    pub refund_count: u32,
+    pub refund_amount: i64,
+}
+
+/// 一个周期的指标
+#[derive(Debug, Clone, Serialize)]
+pub struct StatsPoint {
+    /// 周期第一天；汇总行为查询起始日
+    pub period: NaiveDate,
+    pub order_count: u64,
+    pub paid_order_count: u64,
+    pub gmv: i64,
+    /// 周期内去重的支付用户数
+    pub paying_users: u64,
+    /// 客单价：支付金额 / 支付订单数
+    pub avg_order_value: i64,
+    pub refund_count: u64,
+    pub refund_amount: i64,
+    /// 退款率：退款金额 / 支付金额，保留 4 位小数
+    pub refund_rate: f64,
+}
+
+impl StatsPoint {
+    pub fn new(period: NaiveDate) -> Self {
+        Self {
+            period,
+            order_count: 0,
+            paid_order_count: 0,
+            gmv: 0,
+            paying_users: 0,
+            avg_order_value: 0,
+            refund_count: 0,
+            refund_amount: 0,
+            refund_rate: 0.0,
+        }
+    }
+
+    pub fn add(&mut self, day: &DailyStats) {
+        self.order_count += u64::from(day.order_count);
+        self.paid_order_count += u64::from(day.paid_order_count);
+        self.gmv += day.gmv;
+        self.refund_count += u64::from(day.refund_count);
+        self.refund_amount += day.refund_amount;
+    }
+
+    /// 累加完成后计算比率类指标
+    pub fn finish(&mut self) {
+        if self.paid_order_count > 0 {
+            self.avg_order_value = self.gmv / self.paid_order_count as i64;
+        }
+        if self.gmv > 0 {
+            self.refund_rate = (self.refund_amount as f64 / self.gmv as f64 * 10_000.0).round() / 10_000.0;
+        }
+    }
+}
+
+/// `GET /admin/stats/overview` 的返回
+#[derive(Debug, Clone, Serialize)]
+pub struct StatsOverview {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub group_by: Granularity,
+    pub totals: StatsPoint,
+    pub series: Vec<StatsPoint>,
+}
+
+#[derive(FromRow, Debug, Clone, Serialize)]
+pub struct ProductStats {
+    pub product_id: u64,
+    pub product_name: String,
+    pub quantity: i64,
+    pub gmv: i64,
+}
@@ -0,0 +1,55 @@
+use sqlx::{MySql, Pool};
+use std::sync::Arc;
+use crate::domain::BoxFuture;
+use crate::domain::address::AddressRepo;
+use crate::models::address::{Address, NewAddress};
+
+pub struct AddressRepository {
+    pool: Pool<MySql>,
+}
+
+impl AddressRepository {
+    pub fn new(pool: Pool<MySql>) -> Arc<Self> {
+        Arc::new(Self { pool })
+    }
+}
+
+impl AddressRepo for AddressRepository {
+    fn find(&self, user_id: u32, id: u64) -> BoxFuture<'_, Result<Option<Address>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Address>("SELECT * FROM user_addresses WHERE id = ? AND user_id = ?")
+                .bind(id)
+                .bind(user_id)
+                .fetch_optional(&self.pool)
+                .await
+        })
+    }
+
+    fn list(&self, user_id: u32) -> BoxFuture<'_, Result<Vec<Address>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Address>("SELECT * FROM user_addresses WHERE user_id = ? ORDER BY id DESC")
+                .bind(user_id)
+                .fetch_all(&self.pool)
+                .await
+        })
+    }
+
+    fn create<'a>(&'a self, user_id: u32, address: &'a NewAddress) -> BoxFuture<'a, Result<u64, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query(
+                "INSERT INTO user_addresses (user_id, receiver_name, phone, province, city, district, detail) \
+                 VALUES (?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(user_id)
+            .bind(&address.receiver_name)
+            .bind(&address.phone)
+            .bind(&address.province)
+            .bind(&address.city)
+            .bind(&address.district)
+            .bind(&address.detail)
+            .execute(&self.pool)
+            .await?;
+            Ok(result.last_insert_id())
+        })
+    }
+}
@@ -0,0 +1,222 @@
+use sqlx::{MySql, Pool};
+use sqlx::types::Json;
+use std::sync::Arc;
+use crate::domain::BoxFuture;
+use crate::domain::after_sale::AfterSaleRepo;
+use crate::models::after_sale::{AfterSale, AfterSaleNote, AfterSaleStatus, NewAfterSale};
+
+pub struct AfterSaleRepository {
+    pool: Pool<MySql>,
+}
+
+impl AfterSaleRepository {
+    pub fn new(pool: Pool<MySql>) -> Arc<Self> {
+        Arc::new(Self { pool })
+    }
+}
+
+/// 进行中的状态，用于查询订单行上未结束的售后单
+const OPEN_STATUSES: [AfterSaleStatus; 5] = [
+    AfterSaleStatus::Requested,
+    AfterSaleStatus::Approved,
+    AfterSaleStatus::BuyerShipped,
+    AfterSaleStatus::Received,
+    AfterSaleStatus::Refunding,
+];
+
+impl AfterSaleRepo for AfterSaleRepository {
+    fn create<'a>(&'a self, after_sale: &'a NewAfterSale) -> BoxFuture<'a, Result<u64, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query(
+                "INSERT INTO after_sales (after_sale_no, order_id, order_item_id, user_id, kind, reason, description, photos, \
+                 quantity, refund_amount, exchange_sku_id, status) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(&after_sale.after_sale_no)
+            .bind(after_sale.order_id)
+            .bind(after_sale.order_item_id)
+            .bind(after_sale.user_id)
+            .bind(after_sale.kind.as_str())
+            .bind(after_sale.reason.as_str())
+            .bind(&after_sale.description)
+            .bind(Json(&after_sale.photos))
+            .bind(after_sale.quantity)
+            .bind(after_sale.refund_amount)
+            .bind(after_sale.exchange_sku_id)
+            .bind(AfterSaleStatus::Requested.as_str())
+            .execute(&self.pool)
+            .await?;
+            Ok(result.last_insert_id())
+        })
+    }
+
+    fn find(&self, id: u64) -> BoxFuture<'_, Result<Option<AfterSale>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, AfterSale>("SELECT * FROM after_sales WHERE id = ?")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await
+        })
+    }
+
+    fn list_by_user(&self, user_id: u32, limit: u32, offset: u32) -> BoxFuture<'_, Result<Vec<AfterSale>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, AfterSale>("SELECT * FROM after_sales WHERE user_id = ? ORDER BY id DESC LIMIT ? OFFSET ?")
+                .bind(user_id)
+                .bind(limit)
+                .bind(offset)
+                .fetch_all(&self.pool)
+                .await
+        })
+    }
+
+    fn list_by_status<'a>(&'a self, status: &'a str, limit: u32, offset: u32) -> BoxFuture<'a, Result<Vec<AfterSale>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, AfterSale>("SELECT * FROM after_sales WHERE status = ? ORDER BY id LIMIT ? OFFSET ?")
+                .bind(status)
+                .bind(limit)
+                .bind(offset)
+                .fetch_all(&self.pool)
+                .await
+        })
+    }
+
+    fn find_open_by_item(&self, order_item_id: u64) -> BoxFuture<'_, Result<Option<AfterSale>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, AfterSale>(
+                "SELECT * FROM after_sales WHERE order_item_id = ? AND status IN (?, ?, ?, ?, ?) ORDER BY id DESC LIMIT 1",
+            )
+            .bind(order_item_id)
+            .bind(OPEN_STATUSES[0].as_str())
+            .bind(OPEN_STATUSES[1].as_str())
+            .bind(OPEN_STATUSES[2].as_str())
+            .bind(OPEN_STATUSES[3].as_str())
+            .bind(OPEN_STATUSES[4].as_str())
+            .fetch_optional(&self.pool)
+            .await
+        })
+    }
+
+    fn list_by_item(&self, order_item_id: u64) -> BoxFuture<'_, Result<Vec<AfterSale>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, AfterSale>("SELECT * FROM after_sales WHERE order_item_id = ? ORDER BY id")
+                .bind(order_item_id)
+                .fetch_all(&self.pool)
+                .await
+        })
+    }
+
+    fn list_refunding(&self, order_id: u64) -> BoxFuture<'_, Result<Vec<AfterSale>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, AfterSale>("SELECT * FROM after_sales WHERE order_id = ? AND status = ? ORDER BY id")
+                .bind(order_id)
+                .bind(AfterSaleStatus::Refunding.as_str())
+                .fetch_all(&self.pool)
+                .await
+        })
+    }
+
+    fn set_status(&self, id: u64, from: AfterSaleStatus, to: AfterSaleStatus, admin_id: Option<u32>) -> BoxFuture<'_, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query("UPDATE after_sales SET status = ?, admin_id = COALESCE(?, admin_id) WHERE id = ? AND status = ?")
+                .bind(to.as_str())
+                .bind(admin_id)
+                .bind(id)
+                .bind(from.as_str())
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected() == 1)
+        })
+    }
+
+    fn ship_back<'a>(&'a self, id: u64, carrier: &'a str, tracking_no: &'a str) -> BoxFuture<'a, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query(
+                "UPDATE after_sales SET status = ?, return_carrier = ?, return_tracking_no = ? WHERE id = ? AND status = ?",
+            )
+            .bind(AfterSaleStatus::BuyerShipped.as_str())
+            .bind(carrier)
+            .bind(tracking_no)
+            .bind(id)
+            .bind(AfterSaleStatus::Approved.as_str())
+            .execute(&self.pool)
+            .await?;
+            Ok(result.rows_affected() == 1)
+        })
+    }
+
+    fn reject<'a>(&'a self, id: u64, from: AfterSaleStatus, admin_id: u32, reason: &'a str) -> BoxFuture<'a, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query("UPDATE after_sales SET status = ?, admin_id = ?, reject_reason = ? WHERE id = ? AND status = ?")
+                .bind(AfterSaleStatus::Rejected.as_str())
+                .bind(admin_id)
+                .bind(reason)
+                .bind(id)
+                .bind(from.as_str())
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected() == 1)
+        })
+    }
+
+    fn set_refund(&self, id: u64, refund_id: u64) -> BoxFuture<'_, Result<(), sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query("UPDATE after_sales SET refund_id = ? WHERE id = ?")
+                .bind(refund_id)
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn reset_refund(&self, id: u64) -> BoxFuture<'_, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query("UPDATE after_sales SET status = ?, refund_id = NULL WHERE id = ? AND status = ?")
+                .bind(AfterSaleStatus::Received.as_str())
+                .bind(id)
+                .bind(AfterSaleStatus::Refunding.as_str())
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected() == 1)
+        })
+    }
+
+    fn exchange<'a>(&'a self, id: u64, admin_id: u32, carrier: &'a str, tracking_no: &'a str) -> BoxFuture<'a, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query(
+                "UPDATE after_sales SET status = ?, admin_id = ?, exchange_carrier = ?, exchange_tracking_no = ? \
+                 WHERE id = ? AND status = ?",
+            )
+            .bind(AfterSaleStatus::Exchanged.as_str())
+            .bind(admin_id)
+            .bind(carrier)
+            .bind(tracking_no)
+            .bind(id)
+            .bind(AfterSaleStatus::Received.as_str())
+            .execute(&self.pool)
+            .await?;
+            Ok(result.rows_affected() == 1)
+        })
+    }
+
+    fn add_note<'a>(&'a self, id: u64, admin_id: u32, note: &'a str) -> BoxFuture<'a, Result<(), sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query("INSERT INTO after_sale_notes (after_sale_id, admin_id, note) VALUES (?, ?, ?)")
+                .bind(id)
+                .bind(admin_id)
+                .bind(note)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn list_notes(&self, id: u64) -> BoxFuture<'_, Result<Vec<AfterSaleNote>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, AfterSaleNote>("SELECT * FROM after_sale_notes WHERE after_sale_id = ? ORDER BY id")
+                .bind(id)
+                .fetch_all(&self.pool)
+                .await
+        })
+    }
+}
@@ -0,0 +1,70 @@
+use chrono::Days;
+use sqlx::types::Json;
+use sqlx::{MySql, Pool, QueryBuilder};
+use std::sync::Arc;
+use crate::domain::BoxFuture;
+use crate::domain::audit::AuditRepo;
+use crate::models::audit::{AuditLog, AuditQuery, NewAuditLog};
+
+pub struct AuditRepository {
+    pool: Pool<MySql>,
+}
+
+impl AuditRepository {
+    pub fn new(pool: Pool<MySql>) -> Arc<Self> {
+        Arc::new(Self { pool })
+    }
+}
+
+impl AuditRepo for AuditRepository {
+    fn insert<'a>(&'a self, log: &'a NewAuditLog) -> BoxFuture<'a, Result<u64, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query(
+                "INSERT INTO audit_logs \
+                 (actor_id, ip, forwarded_for, session_id, action, target_type, target_id, status_code, request, changes) \
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(log.actor_id)
+            .bind(&log.ip)
+            .bind(&log.forwarded_for)
+            .bind(&log.session_id)
+            .bind(&log.action)
+            .bind(&log.target_type)
+            .bind(&log.target_id)
+            .bind(log.status_code)
+            .bind(log.request.as_ref().map(Json))
+            .bind(Json(&log.changes))
+            .execute(&self.pool)
+            .await?;
+            Ok(result.last_insert_id())
+        })
+    }
+
+    fn search<'a>(&'a self, query: &'a AuditQuery, limit: u32, offset: u32) -> BoxFuture<'a, Result<Vec<AuditLog>, sqlx::Error>> {
+        Box::pin(async move {
+            let mut builder = QueryBuilder::<MySql>::new("SELECT * FROM audit_logs WHERE 1 = 1");
+            if let Some(actor_id) = query.actor_id {
+                builder.push(" AND actor_id = ").push_bind(actor_id);
+            }
+            if let Some(action) = &query.action {
+                let escaped = action.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+                builder.push(" AND action LIKE ").push_bind(format!("{}%", escaped));
+            }
+            if let Some(target_type) = &query.target_type {
+                builder.push(" AND target_type = ").push_bind(target_type);
+            }
+            if let Some(target_id) = &query.target_id {
+                builder.push(" AND target_id = ").push_bind(target_id);
+            }
+            if let Some(from) = query.from {
+                builder.push(" AND created_at >= ").push_bind(from);
+            }
+            if let Some(to) = query.to.and_then(|to| to.checked_add_days(Days::new(1))) {
+                builder.push(" AND created_at < ").push_bind(to);
+            }
+            builder.push(" ORDER BY id DESC LIMIT ").push_bind(limit);
+            builder.push(" OFFSET ").push_bind(offset);
+            builder.build_query_as::<AuditLog>().fetch_all(&self.pool).await
+        })
+    }
+}
@@ -0,0 +1,68 @@
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use crate::domain::BoxFuture;
+use crate::domain::blob::{BlobFile, BlobStore};
+
+/// 本地目录实现的文件存储
+pub struct LocalBlobStore {
+    root: PathBuf,
+}
+
+impl LocalBlobStore {
+    pub fn new(root: impl Into<PathBuf>) -> Arc<Self> {
+        Arc::new(Self { root: root.into() })
+    }
+
+    /// key 只能是根目录下的相对路径
+    fn path(&self, key: &str) -> Result<PathBuf, io::Error> {
+        let relative = Path::new(key);
+        if key.is_empty() || !relative.components().all(|part| matches!(part, Component::Normal(_))) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("invalid blob key: {}", key)));
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+impl BlobStore for LocalBlobStore {
+    fn put<'a>(&'a self, key: &'a str, bytes: &'a [u8]) -> BoxFuture<'a, Result<(), io::Error>> {
+        Box::pin(async move {
+            let target = self.path(key)?;
+            if let Some(parent) = target.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::write(target, bytes).await
+        })
+    }
+
+    fn put_file<'a>(&'a self, key: &'a str, path: &'a Path) -> BoxFuture<'a, Result<u64, io::Error>> {
+        Box::pin(async move {
+            let target = self.path(key)?;
+            if let Some(parent) = target.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::copy(path, &target).await
+        })
+    }
+
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Vec<u8>, io::Error>> {
+        Box::pin(async move { tokio::fs::read(self.path(key)?).await })
+    }
+
+    fn open<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<BlobFile, io::Error>> {
+        Box::pin(async move {
+            let file = tokio::fs::File::open(self.path(key)?).await?;
+            let size = file.metadata().await?.len();
+            Ok(BlobFile { reader: Box::pin(file), size })
+        })
+    }
+
+    fn delete<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<(), io::Error>> {
+        Box::pin(async move {
+            match tokio::fs::remove_file(self.path(key)?).await {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            }
+        })
+    }
+}
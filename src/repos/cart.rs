@@ -0,0 +1,119 @@
+use sqlx::{MySql, Pool, QueryBuilder};
+use std::sync::Arc;
+use crate::domain::BoxFuture;
+use crate::domain::cart::CartRepo;
+use crate::models::cart::CartLine;
+
+const UPSERT_SQL: &str = "INSERT INTO cart_items (user_id, sku_id, quantity, selected) VALUES (?, ?, LEAST(?, ?), ?) \
+     ON DUPLICATE KEY UPDATE quantity = LEAST(quantity + VALUES(quantity), ?)";
+
+pub struct CartRepository {
+    pool: Pool<MySql>,
+}
+
+impl CartRepository {
+    pub fn new(pool: Pool<MySql>) -> Arc<Self> {
+        Arc::new(Self { pool })
+    }
+}
+
+impl CartRepo for CartRepository {
+    fn list(&self, user_id: u32) -> BoxFuture<'_, Result<Vec<CartLine>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, CartLine>(
+                "SELECT sku_id, quantity, selected FROM cart_items WHERE user_id = ? ORDER BY updated_at DESC, id DESC",
+            )
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await
+        })
+    }
+
+    fn add(&self, user_id: u32, sku_id: u64, quantity: u32, max_quantity: u32) -> BoxFuture<'_, Result<(), sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query(UPSERT_SQL)
+                .bind(user_id)
+                .bind(sku_id)
+                .bind(quantity)
+                .bind(max_quantity)
+                .bind(true)
+                .bind(max_quantity)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn set_quantity(&self, user_id: u32, sku_id: u64, quantity: u32) -> BoxFuture<'_, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            // 数量不变时 rows_affected 为 0，所以用存在性判断是否找到
+            let found: Option<(u64,)> = sqlx::query_as("SELECT id FROM cart_items WHERE user_id = ? AND sku_id = ?")
+                .bind(user_id)
+                .bind(sku_id)
+                .fetch_optional(&self.pool)
+                .await?;
+            if found.is_none() {
+                return Ok(false);
+            }
+            sqlx::query("UPDATE cart_items SET quantity = ? WHERE user_id = ? AND sku_id = ?")
+                .bind(quantity)
+                .bind(user_id)
+                .bind(sku_id)
+                .execute(&self.pool)
+                .await?;
+            Ok(true)
+        })
+    }
+
+    fn remove<'a>(&'a self, user_id: u32, sku_ids: &'a [u64]) -> BoxFuture<'a, Result<(), sqlx::Error>> {
+        Box::pin(async move {
+            if sku_ids.is_empty() {
+                return Ok(());
+            }
+            let mut query = QueryBuilder::<MySql>::new("DELETE FROM cart_items WHERE user_id = ");
+            query.push_bind(user_id).push(" AND sku_id IN (");
+            let mut ids = query.separated(", ");
+            for id in sku_ids {
+                ids.push_bind(*id);
+            }
+            query.push(")");
+            query.build().execute(&self.pool).await?;
+            Ok(())
+        })
+    }
+
+    fn set_selected<'a>(&'a self, user_id: u32, sku_ids: &'a [u64], selected: bool) -> BoxFuture<'a, Result<(), sqlx::Error>> {
+        Box::pin(async move {
+            if sku_ids.is_empty() {
+                return Ok(());
+            }
+            let mut query = QueryBuilder::<MySql>::new("UPDATE cart_items SET selected = ");
+            query.push_bind(selected).push(" WHERE user_id = ").push_bind(user_id).push(" AND sku_id IN (");
+            let mut ids = query.separated(", ");
+            for id in sku_ids {
+                ids.push_bind(*id);
+            }
+            query.push(")");
+            query.build().execute(&self.pool).await?;
+            Ok(())
+        })
+    }
+
+    fn merge<'a>(&'a self, user_id: u32, lines: &'a [CartLine], max_quantity: u32) -> BoxFuture<'a, Result<(), sqlx::Error>> {
+        Box::pin(async move {
+            let mut tx = self.pool.begin().await?;
+            for line in lines {
+                sqlx::query(UPSERT_SQL)
+                    .bind(user_id)
+                    .bind(line.sku_id)
+                    .bind(line.quantity)
+                    .bind(max_quantity)
+                    .bind(line.selected)
+                    .bind(max_quantity)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            tx.commit().await
+        })
+    }
+}
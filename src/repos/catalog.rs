@@ -0,0 +1,58 @@
+use sqlx::{MySql, Pool, QueryBuilder};
+use std::sync::Arc;
+use crate::domain::BoxFuture;
+use crate::domain::catalog::CatalogRepo;
+use crate::models::catalog::{ProductSummary, STATUS_ON_SALE, SkuDetail};
+
+pub struct CatalogRepository {
+    pool: Pool<MySql>,
+}
+
+impl CatalogRepository {
+    pub fn new(pool: Pool<MySql>) -> Arc<Self> {
+        Arc::new(Self { pool })
+    }
+}
+
+impl CatalogRepo for CatalogRepository {
+    fn find_skus<'a>(&'a self, sku_ids: &'a [u64]) -> BoxFuture<'a, Result<Vec<SkuDetail>, sqlx::Error>> {
+        Box::pin(async move {
+            if sku_ids.is_empty() {
+                return Ok(Vec::new());
+            }
+            let mut query = QueryBuilder::<MySql>::new(
+                "SELECT s.id AS sku_id, s.product_id, p.name AS product_name, p.category_id, s.title, s.price, \
+                 s.status AS sku_status, p.status AS product_status, s.weight_grams, p.freight_template_id \
+                 FROM skus s JOIN products p ON p.id = s.product_id WHERE s.id IN (",
+            );
+            let mut ids = query.separated(", ");
+            for id in sku_ids {
+                ids.push_bind(*id);
+            }
+            query.push(")");
+            query.build_query_as::<SkuDetail>().fetch_all(&self.pool).await
+        })
+    }
+
+    fn find_products<'a>(&'a self, product_ids: &'a [u64]) -> BoxFuture<'a, Result<Vec<ProductSummary>, sqlx::Error>> {
+        Box::pin(async move {
+            if product_ids.is_empty() {
+                return Ok(Vec::new());
+            }
+            let mut query = QueryBuilder::<MySql>::new(
+                "SELECT p.id AS product_id, p.name, p.category_id, p.status, p.favorite_count, \
+                 MIN(CASE WHEN s.status = ",
+            );
+            query.push_bind(STATUS_ON_SALE);
+            query.push(" THEN s.price END) AS min_price, CAST(COALESCE(SUM(s.status = ");
+            query.push_bind(STATUS_ON_SALE);
+            query.push("), 0) AS SIGNED) AS on_sale_skus FROM products p LEFT JOIN skus s ON s.product_id = p.id WHERE p.id IN (");
+            let mut ids = query.separated(", ");
+            for id in product_ids {
+                ids.push_bind(*id);
+            }
+            query.push(") GROUP BY p.id");
+            query.build_query_as::<ProductSummary>().fetch_all(&self.pool).await
+        })
+    }
+}
@@ -0,0 +1,218 @@
+use sqlx::{MySql, Pool, QueryBuilder};
+use sqlx::types::Json;
+use std::sync::Arc;
+use chrono::{DateTime, Local};
+use crate::domain::BoxFuture;
+use crate::domain::coupon::CouponRepo;
+use crate::models::coupon::{ClaimOutcome, CouponTemplate, NewCouponTemplate, TEMPLATE_ACTIVE, UserCoupon, UserCouponStatus};
+
+pub struct CouponRepository {
+    pool: Pool<MySql>,
+}
+
+impl CouponRepository {
+    pub fn new(pool: Pool<MySql>) -> Arc<Self> {
+        Arc::new(Self { pool })
+    }
+}
+
+impl CouponRepo for CouponRepository {
+    fn create_template<'a>(&'a self, template: &'a NewCouponTemplate) -> BoxFuture<'a, Result<u64, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query(
+                "INSERT INTO coupon_templates (name, kind, threshold, amount, percent_off, max_discount, scope, scope_ids, \
+                 total_quota, per_user_limit, valid_from, valid_to, valid_days, status) \
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(template.name.trim())
+            .bind(template.kind.as_str())
+            .bind(template.threshold)
+            .bind(template.amount)
+            .bind(template.percent_off)
+            .bind(template.max_discount)
+            .bind(template.scope.as_str())
+            .bind(Json(&template.scope_ids))
+            .bind(template.total_quota)
+            .bind(template.per_user_limit)
+            .bind(template.valid_from)
+            .bind(template.valid_to)
+            .bind(template.valid_days)
+            .bind(TEMPLATE_ACTIVE)
+            .execute(&self.pool)
+            .await?;
+            Ok(result.last_insert_id())
+        })
+    }
+
+    fn find_template(&self, id: u64) -> BoxFuture<'_, Result<Option<CouponTemplate>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, CouponTemplate>("SELECT * FROM coupon_templates WHERE id = ?")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await
+        })
+    }
+
+    fn find_templates<'a>(&'a self, ids: &'a [u64]) -> BoxFuture<'a, Result<Vec<CouponTemplate>, sqlx::Error>> {
+        Box::pin(async move {
+            if ids.is_empty() {
+                return Ok(Vec::new());
+            }
+            let mut query = QueryBuilder::<MySql>::new("SELECT * FROM coupon_templates WHERE id IN (");
+            let mut separated = query.separated(", ");
+            for id in ids {
+                separated.push_bind(*id);
+            }
+            query.push(")");
+            query.build_query_as::<CouponTemplate>().fetch_all(&self.pool).await
+        })
+    }
+
+    fn list_templates(&self, limit: u32, offset: u32) -> BoxFuture<'_, Result<Vec<CouponTemplate>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, CouponTemplate>("SELECT * FROM coupon_templates ORDER BY id DESC LIMIT ? OFFSET ?")
+                .bind(limit)
+                .bind(offset)
+                .fetch_all(&self.pool)
+                .await
+        })
+    }
+
+    fn list_claimable(&self, now: DateTime<Local>) -> BoxFuture<'_, Result<Vec<CouponTemplate>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, CouponTemplate>(
+                "SELECT * FROM coupon_templates \
+                 WHERE status = ? AND valid_from <= ? AND valid_to > ? AND issued < total_quota ORDER BY id",
+            )
+            .bind(TEMPLATE_ACTIVE)
+            .bind(now)
+            .bind(now)
+            .fetch_all(&self.pool)
+            .await
+        })
+    }
+
+    fn set_template_status<'a>(&'a self, id: u64, status: &'a str) -> BoxFuture<'a, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query("UPDATE coupon_templates SET status = ? WHERE id = ?")
+                .bind(status)
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected() == 1)
+        })
+    }
+
+    fn claim(&self, template_id: u64, user_id: u32, now: DateTime<Local>) -> BoxFuture<'_, Result<ClaimOutcome, sqlx::Error>> {
+        Box::pin(async move {
+            let mut tx = self.pool.begin().await?;
+            // 条件更新同时完成余量判断和扣减，并持有模板行锁直到提交，
+            // 同一模板的领取在这里串行，后面的限领计数不会被并发请求绕过
+            let taken = sqlx::query(
+                "UPDATE coupon_templates SET issued = issued + 1 \
+                 WHERE id = ? AND status = ? AND valid_from <= ? AND valid_to > ? AND issued < total_quota",
+            )
+            .bind(template_id)
+            .bind(TEMPLATE_ACTIVE)
+            .bind(now)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+            if taken == 0 {
+                tx.rollback().await?;
+                return Ok(match self.find_template(template_id).await? {
+                    Some(t) if t.status == TEMPLATE_ACTIVE && t.valid_from <= now && t.valid_to > now => ClaimOutcome::SoldOut,
+                    _ => ClaimOutcome::Unavailable,
+                });
+            }
+
+            let template = sqlx::query_as::<_, CouponTemplate>("SELECT * FROM coupon_templates WHERE id = ?")
+                .bind(template_id)
+                .fetch_one(&mut *tx)
+                .await?;
+            let (claimed,): (i64,) =
+                sqlx::query_as("SELECT COUNT(*) FROM user_coupons WHERE template_id = ? AND user_id = ? FOR UPDATE")
+                    .bind(template_id)
+                    .bind(user_id)
+                    .fetch_one(&mut *tx)
+                    .await?;
+            if claimed >= i64::from(template.per_user_limit) {
+                tx.rollback().await?;
+                return Ok(ClaimOutcome::LimitReached);
+            }
+
+            let id = sqlx::query("INSERT INTO user_coupons (template_id, user_id, status, expires_at) VALUES (?, ?, ?, ?)")
+                .bind(template_id)
+                .bind(user_id)
+                .bind(UserCouponStatus::Unused.as_str())
+                .bind(template.expires_at(now))
+                .execute(&mut *tx)
+                .await?
+                .last_insert_id();
+            tx.commit().await?;
+            Ok(ClaimOutcome::Claimed(id))
+        })
+    }
+
+    fn find_user_coupon(&self, id: u64) -> BoxFuture<'_, Result<Option<UserCoupon>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, UserCoupon>("SELECT * FROM user_coupons WHERE id = ?")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await
+        })
+    }
+
+    fn list_user_coupons(&self, user_id: u32) -> BoxFuture<'_, Result<Vec<UserCoupon>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, UserCoupon>("SELECT * FROM user_coupons WHERE user_id = ? ORDER BY id DESC")
+                .bind(user_id)
+                .fetch_all(&self.pool)
+                .await
+        })
+    }
+
+    fn lock<'a>(&'a self, id: u64, user_id: u32, order_no: &'a str, now: DateTime<Local>) -> BoxFuture<'a, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query(
+                "UPDATE user_coupons SET status = ?, order_no = ? \
+                 WHERE id = ? AND user_id = ? AND status = ? AND expires_at > ?",
+            )
+            .bind(UserCouponStatus::Locked.as_str())
+            .bind(order_no)
+            .bind(id)
+            .bind(user_id)
+            .bind(UserCouponStatus::Unused.as_str())
+            .bind(now)
+            .execute(&self.pool)
+            .await?;
+            Ok(result.rows_affected() == 1)
+        })
+    }
+
+    fn release<'a>(&'a self, order_no: &'a str) -> BoxFuture<'a, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query("UPDATE user_coupons SET status = ?, order_no = NULL WHERE order_no = ? AND status = ?")
+                .bind(UserCouponStatus::Unused.as_str())
+                .bind(order_no)
+                .bind(UserCouponStatus::Locked.as_str())
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected() > 0)
+        })
+    }
+
+    fn consume<'a>(&'a self, order_no: &'a str, used_at: DateTime<Local>) -> BoxFuture<'a, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query("UPDATE user_coupons SET status = ?, used_at = ? WHERE order_no = ? AND status = ?")
+                .bind(UserCouponStatus::Used.as_str())
+                .bind(used_at)
+                .bind(order_no)
+                .bind(UserCouponStatus::Locked.as_str())
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected() > 0)
+        })
+    }
+}
@@ -0,0 +1,286 @@
+use chrono::{DateTime, Days, Local};
+use sqlx::types::Json;
+use sqlx::{FromRow, MySql, Pool, QueryBuilder};
+use std::sync::Arc;
+use crate::domain::BoxFuture;
+use crate::domain::export::ExportRepo;
+use crate::models::export::{Cell, Export, ExportFilter, ExportKind, ExportRequest, ExportRow, ExportStatus};
+use crate::models::order::Order;
+
+pub struct ExportRepository {
+    pool: Pool<MySql>,
+}
+
+impl ExportRepository {
+    pub fn new(pool: Pool<MySql>) -> Arc<Self> {
+        Arc::new(Self { pool })
+    }
+}
+
+#[derive(FromRow)]
+struct UserRow {
+    id: u32,
+    username: String,
+    role: String,
+    created_at: Option<DateTime<Local>>,
+}
+
+#[derive(FromRow)]
+struct InventoryRow {
+    sku_id: u64,
+    product_id: u64,
+    name: String,
+    title: String,
+    price: i64,
+    status: String,
+    stock: i64,
+    stock_mode: String,
+}
+
+fn time_text(time: Option<DateTime<Local>>) -> Cell {
+    Cell::Text(time.map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string()).unwrap_or_default())
+}
+
+/// FROM 子句及筛选条件；`count_rows` 与 `fetch_batch` 共用
+fn push_source(query: &mut QueryBuilder<'_, MySql>, kind: ExportKind, filter: &ExportFilter) {
+    let created_at = match kind {
+        ExportKind::Orders => {
+            query.push(" FROM orders o WHERE 1 = 1");
+            if let Some(status) = &filter.status {
+                query.push(" AND o.status = ").push_bind(status.clone());
+            }
+            Some("o.created_at")
+        }
+        ExportKind::Users => {
+            query.push(" FROM t_user u WHERE 1 = 1");
+            Some("u.created_at")
+        }
+        ExportKind::Inventory => {
+            query.push(" FROM inventory i JOIN skus s ON s.id = i.sku_id JOIN products p ON p.id = s.product_id WHERE 1 = 1");
+            if let Some(stock_below) = filter.stock_below {
+                query.push(" AND i.stock < ").push_bind(stock_below);
+            }
+            None
+        }
+    };
+    if let Some(column) = created_at {
+        if let Some(from) = filter.from {
+            query.push(format!(" AND {} >= ", column)).push_bind(from);
+        }
+        if let Some(to) = filter.to.and_then(|to| to.checked_add_days(Days::new(1))) {
+            query.push(format!(" AND {} < ", column)).push_bind(to);
+        }
+    }
+}
+
+fn id_column(kind: ExportKind) -> &'static str {
+    match kind {
+        ExportKind::Orders => "o.id",
+        ExportKind::Users => "u.id",
+        ExportKind::Inventory => "i.sku_id",
+    }
+}
+
+impl ExportRepo for ExportRepository {
+    fn create<'a>(&'a self, request: &'a ExportRequest, admin_id: u32) -> BoxFuture<'a, Result<u64, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query("INSERT INTO export_jobs (kind, format, filter, status, admin_id) VALUES (?, ?, ?, ?, ?)")
+                .bind(request.kind.as_str())
+                .bind(request.format.as_str())
+                .bind(Json(&request.filter))
+                .bind(ExportStatus::Pending.as_str())
+                .bind(admin_id)
+                .execute(&self.pool)
+                .await?;
+            Ok(result.last_insert_id())
+        })
+    }
+
+    fn find(&self, id: u64) -> BoxFuture<'_, Result<Option<Export>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Export>("SELECT * FROM export_jobs WHERE id = ?")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await
+        })
+    }
+
+    fn list(&self, limit: u32, offset: u32) -> BoxFuture<'_, Result<Vec<Export>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Export>("SELECT * FROM export_jobs ORDER BY id DESC LIMIT ? OFFSET ?")
+                .bind(limit)
+                .bind(offset)
+                .fetch_all(&self.pool)
+                .await
+        })
+    }
+
+    fn start(&self, id: u64, total_rows: u64, now: DateTime<Local>) -> BoxFuture<'_, Result<(), sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query(
+                "UPDATE export_jobs SET status = ?, total_rows = ?, processed_rows = 0, error = NULL, started_at = ? WHERE id = ?",
+            )
+            .bind(ExportStatus::Running.as_str())
+            .bind(total_rows)
+            .bind(now)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn progress(&self, id: u64, processed_rows: u64) -> BoxFuture<'_, Result<(), sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query("UPDATE export_jobs SET processed_rows = ? WHERE id = ?")
+                .bind(processed_rows)
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn complete<'a>(
+        &'a self,
+        id: u64,
+        blob_key: &'a str,
+        file_size: u64,
+        processed_rows: u64,
+        expires_at: DateTime<Local>,
+    ) -> BoxFuture<'a, Result<(), sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query(
+                "UPDATE export_jobs SET status = ?, blob_key = ?, file_size = ?, processed_rows = ?, expires_at = ?, \
+                 finished_at = NOW() WHERE id = ?",
+            )
+            .bind(ExportStatus::Succeeded.as_str())
+            .bind(blob_key)
+            .bind(file_size)
+            .bind(processed_rows)
+            .bind(expires_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn fail<'a>(&'a self, id: u64, error: &'a str) -> BoxFuture<'a, Result<(), sqlx::Error>> {
+        Box::pin(async move {
+            let error: String = error.chars().take(512).collect();
+            sqlx::query("UPDATE export_jobs SET status = ?, error = ?, finished_at = NOW() WHERE id = ?")
+                .bind(ExportStatus::Failed.as_str())
+                .bind(error)
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn expire(&self, id: u64) -> BoxFuture<'_, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query("UPDATE export_jobs SET status = ? WHERE id = ? AND status = ?")
+                .bind(ExportStatus::Expired.as_str())
+                .bind(id)
+                .bind(ExportStatus::Succeeded.as_str())
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected() == 1)
+        })
+    }
+
+    fn count_rows<'a>(&'a self, kind: ExportKind, filter: &'a ExportFilter) -> BoxFuture<'a, Result<u64, sqlx::Error>> {
+        Box::pin(async move {
+            let mut query = QueryBuilder::<MySql>::new("SELECT COUNT(*)");
+            push_source(&mut query, kind, filter);
+            let (count,): (i64,) = query.build_query_as().fetch_one(&self.pool).await?;
+            Ok(count.max(0) as u64)
+        })
+    }
+
+    fn fetch_batch<'a>(
+        &'a self,
+        kind: ExportKind,
+        filter: &'a ExportFilter,
+        after_id: u64,
+        limit: u32,
+    ) -> BoxFuture<'a, Result<Vec<ExportRow>, sqlx::Error>> {
+        Box::pin(async move {
+            let columns = match kind {
+                ExportKind::Orders => "SELECT o.*",
+                ExportKind::Users => "SELECT u.id, u.username, u.role, u.created_at",
+                ExportKind::Inventory => {
+                    "SELECT i.sku_id, s.product_id, p.name, s.title, s.price, s.status, i.stock, i.stock_mode"
+                }
+            };
+            let mut query = QueryBuilder::<MySql>::new(columns);
+            push_source(&mut query, kind, filter);
+            let id = id_column(kind);
+            query.push(format!(" AND {} > ", id)).push_bind(after_id);
+            query.push(format!(" ORDER BY {} LIMIT ", id)).push_bind(limit);
+
+            let rows = match kind {
+                ExportKind::Orders => query
+                    .build_query_as::<Order>()
+                    .fetch_all(&self.pool)
+                    .await?
+                    .into_iter()
+                    .map(|order| {
+                        let address = format!("{}{}{}{}", order.province, order.city, order.district, order.address_detail);
+                        let cells = vec![
+                            Cell::Text(order.order_no),
+                            Cell::Int(i64::from(order.user_id)),
+                            Cell::Text(order.status),
+                            Cell::Amount(order.goods_amount),
+                            Cell::Amount(order.discount_amount),
+                            Cell::Amount(order.shipping_fee),
+                            Cell::Amount(order.payable_amount),
+                            Cell::Text(order.receiver_name),
+                            Cell::Text(order.receiver_phone),
+                            Cell::Text(address),
+                            time_text(order.created_at),
+                        ];
+                        (order.id, cells)
+                    })
+                    .collect(),
+                ExportKind::Users => query
+                    .build_query_as::<UserRow>()
+                    .fetch_all(&self.pool)
+                    .await?
+                    .into_iter()
+                    .map(|user| {
+                        let cells = vec![
+                            Cell::Int(i64::from(user.id)),
+                            Cell::Text(user.username),
+                            Cell::Text(user.role),
+                            time_text(user.created_at),
+                        ];
+                        (u64::from(user.id), cells)
+                    })
+                    .collect(),
+                ExportKind::Inventory => query
+                    .build_query_as::<InventoryRow>()
+                    .fetch_all(&self.pool)
+                    .await?
+                    .into_iter()
+                    .map(|row| {
+                        let cells = vec![
+                            Cell::Int(row.sku_id as i64),
+                            Cell::Int(row.product_id as i64),
+                            Cell::Text(row.name),
+                            Cell::Text(row.title),
+                            Cell::Amount(row.price),
+                            Cell::Text(row.status),
+                            Cell::Int(row.stock),
+                            Cell::Text(row.stock_mode),
+                        ];
+                        (row.sku_id, cells)
+                    })
+                    .collect(),
+            };
+            Ok(rows)
+        })
+    }
+}
@@ -0,0 +1,72 @@
+use sqlx::{MySql, Pool};
+use std::sync::Arc;
+use crate::domain::BoxFuture;
+use crate::domain::favorite::FavoriteRepo;
+use crate::models::favorite::Favorite;
+
+pub struct FavoriteRepository {
+    pool: Pool<MySql>,
+}
+
+impl FavoriteRepository {
+    pub fn new(pool: Pool<MySql>) -> Arc<Self> {
+        Arc::new(Self { pool })
+    }
+}
+
+impl FavoriteRepo for FavoriteRepository {
+    fn add(&self, user_id: u32, product_id: u64) -> BoxFuture<'_, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let mut tx = self.pool.begin().await?;
+            let inserted = sqlx::query("INSERT IGNORE INTO favorites (user_id, product_id) VALUES (?, ?)")
+                .bind(user_id)
+                .bind(product_id)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+            if inserted == 0 {
+                tx.rollback().await?;
+                return Ok(false);
+            }
+            sqlx::query("UPDATE products SET favorite_count = favorite_count + 1 WHERE id = ?")
+                .bind(product_id)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            Ok(true)
+        })
+    }
+
+    fn remove(&self, user_id: u32, product_id: u64) -> BoxFuture<'_, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let mut tx = self.pool.begin().await?;
+            let deleted = sqlx::query("DELETE FROM favorites WHERE user_id = ? AND product_id = ?")
+                .bind(user_id)
+                .bind(product_id)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+            if deleted == 0 {
+                tx.rollback().await?;
+                return Ok(false);
+            }
+            sqlx::query("UPDATE products SET favorite_count = favorite_count - 1 WHERE id = ? AND favorite_count > 0")
+                .bind(product_id)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            Ok(true)
+        })
+    }
+
+    fn list(&self, user_id: u32, limit: u32, offset: u32) -> BoxFuture<'_, Result<Vec<Favorite>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Favorite>("SELECT * FROM favorites WHERE user_id = ? ORDER BY id DESC LIMIT ? OFFSET ?")
+                .bind(user_id)
+                .bind(limit)
+                .bind(offset)
+                .fetch_all(&self.pool)
+                .await
+        })
+    }
+}
@@ -0,0 +1,95 @@
+use sqlx::{MySql, Pool, QueryBuilder};
+use sqlx::types::Json;
+use std::sync::Arc;
+use crate::domain::BoxFuture;
+use crate::domain::freight::FreightRepo;
+use crate::models::freight::{FreightTemplate, NewFreightTemplate};
+
+pub struct FreightRepository {
+    pool: Pool<MySql>,
+}
+
+impl FreightRepository {
+    pub fn new(pool: Pool<MySql>) -> Arc<Self> {
+        Arc::new(Self { pool })
+    }
+}
+
+impl FreightRepo for FreightRepository {
+    fn create<'a>(&'a self, template: &'a NewFreightTemplate) -> BoxFuture<'a, Result<u64, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query("INSERT INTO freight_templates (name, charge_by, rules, free_threshold) VALUES (?, ?, ?, ?)")
+                .bind(template.name.trim())
+                .bind(template.charge_by.as_str())
+                .bind(Json(&template.rules))
+                .bind(template.free_threshold)
+                .execute(&self.pool)
+                .await?;
+            Ok(result.last_insert_id())
+        })
+    }
+
+    fn update<'a>(&'a self, id: u64, template: &'a NewFreightTemplate) -> BoxFuture<'a, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query("UPDATE freight_templates SET name = ?, charge_by = ?, rules = ?, free_threshold = ? WHERE id = ?")
+                .bind(template.name.trim())
+                .bind(template.charge_by.as_str())
+                .bind(Json(&template.rules))
+                .bind(template.free_threshold)
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected() == 1)
+        })
+    }
+
+    fn find(&self, id: u64) -> BoxFuture<'_, Result<Option<FreightTemplate>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, FreightTemplate>("SELECT * FROM freight_templates WHERE id = ?")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await
+        })
+    }
+
+    fn find_many<'a>(&'a self, ids: &'a [u64]) -> BoxFuture<'a, Result<Vec<FreightTemplate>, sqlx::Error>> {
+        Box::pin(async move {
+            if ids.is_empty() {
+                return Ok(Vec::new());
+            }
+            let mut query = QueryBuilder::<MySql>::new("SELECT * FROM freight_templates WHERE id IN (");
+            let mut separated = query.separated(", ");
+            for id in ids {
+                separated.push_bind(*id);
+            }
+            query.push(")");
+            query.build_query_as::<FreightTemplate>().fetch_all(&self.pool).await
+        })
+    }
+
+    fn list(&self) -> BoxFuture<'_, Result<Vec<FreightTemplate>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, FreightTemplate>("SELECT * FROM freight_templates ORDER BY id")
+                .fetch_all(&self.pool)
+                .await
+        })
+    }
+
+    fn assign(&self, product_id: u64, template_id: Option<u64>) -> BoxFuture<'_, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let (found,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM products WHERE id = ?")
+                .bind(product_id)
+                .fetch_one(&self.pool)
+                .await?;
+            if found == 0 {
+                return Ok(false);
+            }
+            sqlx::query("UPDATE products SET freight_template_id = ? WHERE id = ?")
+                .bind(template_id)
+                .bind(product_id)
+                .execute(&self.pool)
+                .await?;
+            Ok(true)
+        })
+    }
+}
@@ -0,0 +1,260 @@
+use sqlx::{MySql, Pool};
+use std::sync::Arc;
+use chrono::{DateTime, Local};
+use crate::domain::BoxFuture;
+use crate::domain::group_buy::GroupBuyRepo;
+use crate::models::group_buy::{CAMPAIGN_ACTIVE, GroupBuy, GroupCampaign, GroupMember, GroupStatus, JoinOutcome, NewGroupCampaign};
+use crate::models::order::OrderStatus;
+
+/// 占名额的成员：尚在下单中，或订单未取消
+const ACTIVE_MEMBER: &str = "(m.order_id IS NULL OR o.status <> ?)";
+
+pub struct GroupBuyRepository {
+    pool: Pool<MySql>,
+}
+
+impl GroupBuyRepository {
+    pub fn new(pool: Pool<MySql>) -> Arc<Self> {
+        Arc::new(Self { pool })
+    }
+}
+
+impl GroupBuyRepo for GroupBuyRepository {
+    fn create_campaign<'a>(&'a self, campaign: &'a NewGroupCampaign) -> BoxFuture<'a, Result<u64, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query(
+                "INSERT INTO group_campaigns (name, sku_id, group_price, group_size, expire_minutes, starts_at, ends_at, status) \
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(campaign.name.trim())
+            .bind(campaign.sku_id)
+            .bind(campaign.group_price)
+            .bind(campaign.group_size)
+            .bind(campaign.expire_minutes)
+            .bind(campaign.starts_at)
+            .bind(campaign.ends_at)
+            .bind(CAMPAIGN_ACTIVE)
+            .execute(&self.pool)
+            .await?;
+            Ok(result.last_insert_id())
+        })
+    }
+
+    fn find_campaign(&self, id: u64) -> BoxFuture<'_, Result<Option<GroupCampaign>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, GroupCampaign>("SELECT * FROM group_campaigns WHERE id = ?")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await
+        })
+    }
+
+    fn list_campaigns(&self, limit: u32, offset: u32) -> BoxFuture<'_, Result<Vec<GroupCampaign>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, GroupCampaign>("SELECT * FROM group_campaigns ORDER BY id DESC LIMIT ? OFFSET ?")
+                .bind(limit)
+                .bind(offset)
+                .fetch_all(&self.pool)
+                .await
+        })
+    }
+
+    fn list_open_campaigns(&self, now: DateTime<Local>) -> BoxFuture<'_, Result<Vec<GroupCampaign>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, GroupCampaign>(
+                "SELECT * FROM group_campaigns WHERE status = ? AND starts_at <= ? AND ends_at > ? ORDER BY id",
+            )
+            .bind(CAMPAIGN_ACTIVE)
+            .bind(now)
+            .bind(now)
+            .fetch_all(&self.pool)
+            .await
+        })
+    }
+
+    fn set_campaign_status<'a>(&'a self, id: u64, status: &'a str) -> BoxFuture<'a, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query("UPDATE group_campaigns SET status = ? WHERE id = ?")
+                .bind(status)
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected() == 1)
+        })
+    }
+
+    fn open(&self, campaign: &GroupCampaign, leader_id: u32, expires_at: DateTime<Local>) -> BoxFuture<'_, Result<(u64, u64), sqlx::Error>> {
+        let (campaign_id, group_size) = (campaign.id, campaign.group_size);
+        Box::pin(async move {
+            let mut tx = self.pool.begin().await?;
+            let group_id = sqlx::query(
+                "INSERT INTO group_buys (campaign_id, leader_id, group_size, status, expires_at) VALUES (?, ?, ?, ?, ?)",
+            )
+            .bind(campaign_id)
+            .bind(leader_id)
+            .bind(group_size)
+            .bind(GroupStatus::Forming.as_str())
+            .bind(expires_at)
+            .execute(&mut *tx)
+            .await?
+            .last_insert_id();
+            let member_id = sqlx::query("INSERT INTO group_members (group_id, user_id, is_leader) VALUES (?, ?, 1)")
+                .bind(group_id)
+                .bind(leader_id)
+                .execute(&mut *tx)
+                .await?
+                .last_insert_id();
+            tx.commit().await?;
+            Ok((group_id, member_id))
+        })
+    }
+
+    fn join(&self, group_id: u64, user_id: u32, now: DateTime<Local>) -> BoxFuture<'_, Result<JoinOutcome, sqlx::Error>> {
+        Box::pin(async move {
+            let mut tx = self.pool.begin().await?;
+            // 行锁让同一个团的参团串行，名额计数不会被并发请求绕过
+            let group = sqlx::query_as::<_, GroupBuy>("SELECT * FROM group_buys WHERE id = ? FOR UPDATE")
+                .bind(group_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+            let Some(group) = group.filter(|group| group.is(GroupStatus::Forming) && group.expires_at > now) else {
+                tx.rollback().await?;
+                return Ok(JoinOutcome::Closed);
+            };
+
+            let (members, mine): (i64, i64) = sqlx::query_as(&format!(
+                "SELECT COUNT(*), COALESCE(SUM(m.user_id = ?), 0) FROM group_members m \
+                 LEFT JOIN orders o ON o.id = m.order_id WHERE m.group_id = ? AND {}",
+                ACTIVE_MEMBER
+            ))
+            .bind(user_id)
+            .bind(group_id)
+            .bind(OrderStatus::Cancelled.as_str())
+            .fetch_one(&mut *tx)
+            .await?;
+            if mine > 0 {
+                tx.rollback().await?;
+                return Ok(JoinOutcome::AlreadyMember);
+            }
+            if members >= i64::from(group.group_size) {
+                tx.rollback().await?;
+                return Ok(JoinOutcome::Full);
+            }
+
+            let member_id = sqlx::query("INSERT INTO group_members (group_id, user_id) VALUES (?, ?)")
+                .bind(group_id)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?
+                .last_insert_id();
+            tx.commit().await?;
+            Ok(JoinOutcome::Joined(member_id))
+        })
+    }
+
+    fn attach_order(&self, member_id: u64, order_id: u64) -> BoxFuture<'_, Result<(), sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query("UPDATE group_members SET order_id = ? WHERE id = ?")
+                .bind(order_id)
+                .bind(member_id)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn remove_member(&self, member_id: u64) -> BoxFuture<'_, Result<(), sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query("DELETE FROM group_members WHERE id = ? AND order_id IS NULL")
+                .bind(member_id)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn find_group(&self, id: u64) -> BoxFuture<'_, Result<Option<GroupBuy>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, GroupBuy>("SELECT * FROM group_buys WHERE id = ?")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await
+        })
+    }
+
+    fn list_members(&self, group_id: u64) -> BoxFuture<'_, Result<Vec<GroupMember>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, GroupMember>("SELECT * FROM group_members WHERE group_id = ? ORDER BY id")
+                .bind(group_id)
+                .fetch_all(&self.pool)
+                .await
+        })
+    }
+
+    fn mark_paid(&self, order_id: u64, paid_at: DateTime<Local>) -> BoxFuture<'_, Result<Option<GroupBuy>, sqlx::Error>> {
+        Box::pin(async move {
+            let mut tx = self.pool.begin().await?;
+            let member = sqlx::query_as::<_, GroupMember>("SELECT * FROM group_members WHERE order_id = ? FOR UPDATE")
+                .bind(order_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+            let Some(member) = member else {
+                tx.rollback().await?;
+                return Ok(None);
+            };
+
+            if member.paid_at.is_none() {
+                sqlx::query("UPDATE group_members SET paid_at = ? WHERE id = ?")
+                    .bind(paid_at)
+                    .bind(member.id)
+                    .execute(&mut *tx)
+                    .await?;
+                sqlx::query("UPDATE group_buys SET paid_count = paid_count + 1 WHERE id = ?")
+                    .bind(member.group_id)
+                    .execute(&mut *tx)
+                    .await?;
+                sqlx::query(
+                    "UPDATE group_buys SET status = ?, succeeded_at = ? \
+                     WHERE id = ? AND status = ? AND paid_count >= group_size",
+                )
+                .bind(GroupStatus::Succeeded.as_str())
+                .bind(paid_at)
+                .bind(member.group_id)
+                .bind(GroupStatus::Forming.as_str())
+                .execute(&mut *tx)
+                .await?;
+            }
+            let group = sqlx::query_as::<_, GroupBuy>("SELECT * FROM group_buys WHERE id = ?")
+                .bind(member.group_id)
+                .fetch_one(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            Ok(Some(group))
+        })
+    }
+
+    fn fail(&self, group_id: u64, now: DateTime<Local>) -> BoxFuture<'_, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query("UPDATE group_buys SET status = ? WHERE id = ? AND status = ? AND expires_at <= ?")
+                .bind(GroupStatus::Failed.as_str())
+                .bind(group_id)
+                .bind(GroupStatus::Forming.as_str())
+                .bind(now)
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected() == 1)
+        })
+    }
+
+    fn abort(&self, group_id: u64) -> BoxFuture<'_, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query("UPDATE group_buys SET status = ? WHERE id = ? AND status = ?")
+                .bind(GroupStatus::Failed.as_str())
+                .bind(group_id)
+                .bind(GroupStatus::Forming.as_str())
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected() == 1)
+        })
+    }
+}
@@ -0,0 +1,67 @@
+use std::sync::Arc;
+use fred::clients::Pool as RedisPool;
+use fred::interfaces::{KeysInterface, LuaInterface, SortedSetsInterface};
+use crate::domain::BoxFuture;
+use crate::domain::favorite::BrowsingHistory;
+use crate::models::favorite::ViewedProduct;
+
+/// KEYS: 浏览记录 ARGV: 浏览时间, 商品 ID, 保留条数, 过期秒数
+/// 同一商品只更新时间，按时间淘汰最旧的记录
+const RECORD_SCRIPT: &str = r#"
+redis.call('ZADD', KEYS[1], ARGV[1], ARGV[2])
+redis.call('ZREMRANGEBYRANK', KEYS[1], 0, -tonumber(ARGV[3]) - 1)
+redis.call('EXPIRE', KEYS[1], ARGV[4])
+return 1
+"#;
+
+pub struct RedisBrowsingHistory {
+    pool: RedisPool,
+}
+
+impl RedisBrowsingHistory {
+    pub fn new(pool: RedisPool) -> Arc<Self> {
+        Arc::new(Self { pool })
+    }
+}
+
+fn key(user_id: u32) -> String {
+    format!("history:{}", user_id)
+}
+
+impl BrowsingHistory for RedisBrowsingHistory {
+    fn record(&self, user_id: u32, product_id: u64, viewed_at: i64, limit: u32, ttl_secs: u64) -> BoxFuture<'_, Result<(), fred::error::Error>> {
+        Box::pin(async move {
+            let args = vec![viewed_at.to_string(), product_id.to_string(), limit.max(1).to_string(), ttl_secs.max(1).to_string()];
+            let _: i64 = self.pool.eval(RECORD_SCRIPT, vec![key(user_id)], args).await?;
+            Ok(())
+        })
+    }
+
+    fn list(&self, user_id: u32, limit: u32) -> BoxFuture<'_, Result<Vec<ViewedProduct>, fred::error::Error>> {
+        Box::pin(async move {
+            let stop = i64::from(limit.max(1)) - 1;
+            let entries: Vec<(String, f64)> = self.pool.zrange(key(user_id), 0, stop, None, true, None, true).await?;
+            // 非法成员（手工写入等）直接跳过
+            Ok(entries
+                .into_iter()
+                .filter_map(|(member, score)| {
+                    member.parse().ok().map(|product_id| ViewedProduct { product_id, viewed_at: score as i64 })
+                })
+                .collect())
+        })
+    }
+
+    fn remove(&self, user_id: u32, product_id: u64) -> BoxFuture<'_, Result<(), fred::error::Error>> {
+        Box::pin(async move {
+            let _: i64 = self.pool.zrem(key(user_id), product_id.to_string()).await?;
+            Ok(())
+        })
+    }
+
+    fn clear(&self, user_id: u32) -> BoxFuture<'_, Result<(), fred::error::Error>> {
+        Box::pin(async move {
+            let _: i64 = self.pool.del(key(user_id)).await?;
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,212 @@
+use chrono::{DateTime, Local};
+use sqlx::{MySql, Pool, QueryBuilder};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use crate::domain::BoxFuture;
+use crate::domain::import::ImportRepo;
+use crate::models::import::{Import, ImportCounts, ImportStatus, ProductImport, ProductUpsert};
+
+pub struct ImportRepository {
+    pool: Pool<MySql>,
+}
+
+impl ImportRepository {
+    pub fn new(pool: Pool<MySql>) -> Arc<Self> {
+        Arc::new(Self { pool })
+    }
+
+    async fn existing_codes(&self, table: &str, codes: &[&str]) -> Result<Vec<String>, sqlx::Error> {
+        if codes.is_empty() {
+            return Ok(Vec::new());
+        }
+        let rows: Vec<(String,)> = codes_query(table, "code", codes).build_query_as().fetch_all(&self.pool).await?;
+        Ok(rows.into_iter().map(|(code,)| code).collect())
+    }
+}
+
+/// `SELECT code FROM <table> WHERE code IN (...)`，`codes` 不能为空
+fn codes_query<'a>(table: &str, columns: &str, codes: &'a [&'a str]) -> QueryBuilder<'a, MySql> {
+    let mut query = QueryBuilder::<MySql>::new(format!("SELECT {} FROM {} WHERE code IN (", columns, table));
+    let mut separated = query.separated(", ");
+    for code in codes {
+        separated.push_bind(*code);
+    }
+    query.push(")");
+    query
+}
+
+impl ImportRepo for ImportRepository {
+    fn create<'a>(&'a self, format: &'a str, dry_run: bool, admin_id: u32) -> BoxFuture<'a, Result<u64, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query("INSERT INTO import_jobs (format, dry_run, status, admin_id) VALUES (?, ?, ?, ?)")
+                .bind(format)
+                .bind(dry_run)
+                .bind(ImportStatus::Pending.as_str())
+                .bind(admin_id)
+                .execute(&self.pool)
+                .await?;
+            Ok(result.last_insert_id())
+        })
+    }
+
+    fn find(&self, id: u64) -> BoxFuture<'_, Result<Option<Import>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Import>("SELECT * FROM import_jobs WHERE id = ?")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await
+        })
+    }
+
+    fn list(&self, limit: u32, offset: u32) -> BoxFuture<'_, Result<Vec<Import>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Import>("SELECT * FROM import_jobs ORDER BY id DESC LIMIT ? OFFSET ?")
+                .bind(limit)
+                .bind(offset)
+                .fetch_all(&self.pool)
+                .await
+        })
+    }
+
+    fn start(&self, id: u64, total_rows: u64, now: DateTime<Local>) -> BoxFuture<'_, Result<(), sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query(
+                "UPDATE import_jobs SET status = ?, total_rows = ?, processed_rows = 0, error = NULL, started_at = ? WHERE id = ?",
+            )
+            .bind(ImportStatus::Running.as_str())
+            .bind(total_rows)
+            .bind(now)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn progress(&self, id: u64, processed_rows: u64) -> BoxFuture<'_, Result<(), sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query("UPDATE import_jobs SET processed_rows = ? WHERE id = ?")
+                .bind(processed_rows)
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn finish<'a>(
+        &'a self,
+        id: u64,
+        status: ImportStatus,
+        counts: &'a ImportCounts,
+        report_key: &'a str,
+        error: Option<&'a str>,
+    ) -> BoxFuture<'a, Result<(), sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query(
+                "UPDATE import_jobs SET status = ?, report_key = ?, total_rows = ?, processed_rows = ?, error_rows = ?, \
+                 created_products = ?, updated_products = ?, created_skus = ?, updated_skus = ?, error = ?, \
+                 finished_at = NOW() WHERE id = ?",
+            )
+            .bind(status.as_str())
+            .bind(report_key)
+            .bind(counts.total_rows)
+            .bind(counts.total_rows)
+            .bind(counts.error_rows)
+            .bind(counts.created_products)
+            .bind(counts.updated_products)
+            .bind(counts.created_skus)
+            .bind(counts.updated_skus)
+            .bind(error)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn fail<'a>(&'a self, id: u64, error: &'a str) -> BoxFuture<'a, Result<(), sqlx::Error>> {
+        Box::pin(async move {
+            let error: String = error.chars().take(512).collect();
+            sqlx::query("UPDATE import_jobs SET status = ?, error = ?, finished_at = NOW() WHERE id = ?")
+                .bind(ImportStatus::Failed.as_str())
+                .bind(error)
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn existing_product_codes<'a>(&'a self, codes: &'a [&'a str]) -> BoxFuture<'a, Result<Vec<String>, sqlx::Error>> {
+        Box::pin(self.existing_codes("products", codes))
+    }
+
+    fn existing_sku_codes<'a>(&'a self, codes: &'a [&'a str]) -> BoxFuture<'a, Result<Vec<String>, sqlx::Error>> {
+        Box::pin(self.existing_codes("skus", codes))
+    }
+
+    fn upsert_products<'a>(&'a self, products: &'a [ProductImport]) -> BoxFuture<'a, Result<Vec<ProductUpsert>, sqlx::Error>> {
+        Box::pin(async move {
+            if products.is_empty() {
+                return Ok(Vec::new());
+            }
+            let mut tx = self.pool.begin().await?;
+            // 先查出已存在的编码，区分新增与更新；已有 SKU 同时取回原价格用于审计
+            let product_codes: Vec<&str> = products.iter().map(|product| product.code.as_str()).collect();
+            let sku_codes: Vec<&str> = products.iter().flat_map(|product| product.skus.iter().map(|sku| sku.code.as_str())).collect();
+            let rows: Vec<(String,)> = codes_query("products", "code", &product_codes).build_query_as().fetch_all(&mut *tx).await?;
+            let existing_products: HashSet<String> = rows.into_iter().map(|(code,)| code).collect();
+            let rows: Vec<(String, i64)> = codes_query("skus", "code, price", &sku_codes).build_query_as().fetch_all(&mut *tx).await?;
+            let existing_skus: HashMap<String, i64> = rows.into_iter().collect();
+
+            let mut results = Vec::with_capacity(products.len());
+            for product in products {
+                // LAST_INSERT_ID(id) 让更新已有商品时也能取回其 id
+                let product_id = sqlx::query(
+                    "INSERT INTO products (code, name, category_id, status) VALUES (?, ?, ?, ?) \
+                     ON DUPLICATE KEY UPDATE id = LAST_INSERT_ID(id), name = VALUES(name), \
+                     category_id = VALUES(category_id), status = VALUES(status)",
+                )
+                .bind(&product.code)
+                .bind(&product.name)
+                .bind(product.category_id)
+                .bind(&product.status)
+                .execute(&mut *tx)
+                .await?
+                .last_insert_id();
+
+                let mut previous_prices = Vec::with_capacity(product.skus.len());
+                for sku in &product.skus {
+                    let sku_id = sqlx::query(
+                        "INSERT INTO skus (product_id, code, title, price, weight_grams, status) VALUES (?, ?, ?, ?, ?, ?) \
+                         ON DUPLICATE KEY UPDATE id = LAST_INSERT_ID(id), product_id = VALUES(product_id), \
+                         title = VALUES(title), price = VALUES(price), weight_grams = VALUES(weight_grams), status = VALUES(status)",
+                    )
+                    .bind(product_id)
+                    .bind(&sku.code)
+                    .bind(&sku.title)
+                    .bind(sku.price)
+                    .bind(sku.weight_grams)
+                    .bind(&sku.status)
+                    .execute(&mut *tx)
+                    .await?
+                    .last_insert_id();
+
+                    let previous_price = existing_skus.get(&sku.code).copied();
+                    if previous_price.is_none() {
+                        sqlx::query("INSERT IGNORE INTO inventory (sku_id, stock) VALUES (?, ?)")
+                            .bind(sku_id)
+                            .bind(sku.stock)
+                            .execute(&mut *tx)
+                            .await?;
+                    }
+                    previous_prices.push(previous_price);
+                }
+                results.push(ProductUpsert { created: !existing_products.contains(&product.code), previous_prices });
+            }
+            tx.commit().await?;
+            Ok(results)
+        })
+    }
+}
@@ -0,0 +1,181 @@
+use sqlx::{MySql, MySqlConnection, Pool};
+use std::sync::Arc;
+use crate::domain::BoxFuture;
+use crate::domain::inventory::InventoryRepo;
+use crate::models::inventory::{Inventory, MovementKind, StockMovement, STOCK_MODE_REDIS};
+
+pub struct InventoryRepository {
+    pool: Pool<MySql>,
+}
+
+impl InventoryRepository {
+    pub fn new(pool: Pool<MySql>) -> Arc<Self> {
+        Arc::new(Self { pool })
+    }
+}
+
+/// 在调用方的事务中预占 MySQL 模式库存：写流水并条件扣减。
+/// 库存不足返回 `false`，由调用方回滚；同一订单重复预占视为成功
+pub(crate) async fn reserve_stock(conn: &mut MySqlConnection, movement: &StockMovement) -> Result<bool, sqlx::Error> {
+    let inserted = sqlx::query(
+        "INSERT IGNORE INTO inventory_ledger (sku_id, order_ref, user_id, kind, delta, source) \
+         VALUES (?, ?, ?, ?, ?, 'mysql')",
+    )
+    .bind(movement.sku_id)
+    .bind(&movement.order_ref)
+    .bind(movement.user_id)
+    .bind(movement.kind.as_str())
+    .bind(movement.delta)
+    .execute(&mut *conn)
+    .await?
+    .rows_affected();
+    if inserted == 0 {
+        return Ok(true);
+    }
+
+    let updated = sqlx::query("UPDATE inventory SET stock = stock + ? WHERE sku_id = ? AND stock + ? >= 0")
+        .bind(movement.delta)
+        .bind(movement.sku_id)
+        .bind(movement.delta)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+    Ok(updated > 0)
+}
+
+impl InventoryRepo for InventoryRepository {
+    fn find_by_sku(&self, sku_id: u64) -> BoxFuture<'_, Result<Option<Inventory>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Inventory>("SELECT * FROM inventory WHERE sku_id = ?")
+                .bind(sku_id)
+                .fetch_optional(&self.pool)
+                .await
+        })
+    }
+
+    fn list_redis_mode(&self) -> BoxFuture<'_, Result<Vec<Inventory>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Inventory>("SELECT * FROM inventory WHERE stock_mode = ?")
+                .bind(STOCK_MODE_REDIS)
+                .fetch_all(&self.pool)
+                .await
+        })
+    }
+
+    fn reserve<'a>(&'a self, movement: &'a StockMovement) -> BoxFuture<'a, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let mut tx = self.pool.begin().await?;
+            if !reserve_stock(&mut tx, movement).await? {
+                tx.rollback().await?;
+                return Ok(false);
+            }
+            tx.commit().await?;
+            Ok(true)
+        })
+    }
+
+    fn release<'a>(&'a self, sku_id: u64, order_ref: &'a str) -> BoxFuture<'a, Result<Option<i64>, sqlx::Error>> {
+        Box::pin(async move {
+            let mut tx = self.pool.begin().await?;
+            let reserved: Option<(u32, i64)> = sqlx::query_as(
+                "SELECT user_id, delta FROM inventory_ledger WHERE sku_id = ? AND order_ref = ? AND kind = ? FOR UPDATE",
+            )
+            .bind(sku_id)
+            .bind(order_ref)
+            .bind(MovementKind::Reserve.as_str())
+            .fetch_optional(&mut *tx)
+            .await?;
+            let Some((user_id, delta)) = reserved else {
+                tx.rollback().await?;
+                return Ok(None);
+            };
+
+            let qty = -delta;
+            let inserted = sqlx::query(
+                "INSERT IGNORE INTO inventory_ledger (sku_id, order_ref, user_id, kind, delta, source) \
+                 VALUES (?, ?, ?, ?, ?, 'mysql')",
+            )
+            .bind(sku_id)
+            .bind(order_ref)
+            .bind(user_id)
+            .bind(MovementKind::Release.as_str())
+            .bind(qty)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+            if inserted == 0 {
+                tx.rollback().await?;
+                return Ok(None);
+            }
+
+            sqlx::query("UPDATE inventory SET stock = stock + ? WHERE sku_id = ?")
+                .bind(qty)
+                .bind(sku_id)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            Ok(Some(qty))
+        })
+    }
+
+    fn restock<'a>(&'a self, movement: &'a StockMovement) -> BoxFuture<'a, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let mut tx = self.pool.begin().await?;
+            let inserted = sqlx::query(
+                "INSERT IGNORE INTO inventory_ledger (sku_id, order_ref, user_id, kind, delta, source) \
+                 VALUES (?, ?, ?, ?, ?, 'mysql')",
+            )
+            .bind(movement.sku_id)
+            .bind(&movement.order_ref)
+            .bind(movement.user_id)
+            .bind(movement.kind.as_str())
+            .bind(movement.delta)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+            if inserted == 0 {
+                tx.rollback().await?;
+                return Ok(false);
+            }
+
+            sqlx::query("UPDATE inventory SET stock = stock + ? WHERE sku_id = ?")
+                .bind(movement.delta)
+                .bind(movement.sku_id)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            Ok(true)
+        })
+    }
+
+    fn apply_movement<'a>(&'a self, movement: &'a StockMovement) -> BoxFuture<'a, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let mut tx = self.pool.begin().await?;
+            let inserted = sqlx::query(
+                "INSERT IGNORE INTO inventory_ledger (sku_id, order_ref, user_id, kind, delta, source) \
+                 VALUES (?, ?, ?, ?, ?, 'redis')",
+            )
+            .bind(movement.sku_id)
+            .bind(&movement.order_ref)
+            .bind(movement.user_id)
+            .bind(movement.kind.as_str())
+            .bind(movement.delta)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+            if inserted == 0 {
+                tx.rollback().await?;
+                return Ok(false);
+            }
+
+            // Redis 端已经校验过库存，这里不再做非负判断，出现偏差由对账发现
+            sqlx::query("UPDATE inventory SET stock = stock + ? WHERE sku_id = ?")
+                .bind(movement.delta)
+                .bind(movement.sku_id)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            Ok(true)
+        })
+    }
+}
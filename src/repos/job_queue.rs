@@ -0,0 +1,103 @@
+use std::sync::Arc;
+use fred::clients::Pool as RedisPool;
+use fred::interfaces::{HashesInterface, ListInterface, LuaInterface, SortedSetsInterface};
+use crate::domain::BoxFuture;
+use crate::domain::jobs::JobQueue;
+use crate::models::job::Job;
+
+const READY_KEY: &str = "jobs:ready";
+const INFLIGHT_KEY: &str = "jobs:inflight";
+const DATA_KEY: &str = "jobs:data";
+const DEAD_KEY: &str = "jobs:dead";
+
+/// KEYS: 待执行, 任务数据 ARGV: 任务 ID, 任务报文, 执行时间
+const ENQUEUE_SCRIPT: &str = r#"
+redis.call('HSET', KEYS[2], ARGV[1], ARGV[2])
+redis.call('ZADD', KEYS[1], ARGV[3], ARGV[1])
+return 1
+"#;
+
+/// KEYS: 待执行, 执行中, 任务数据 ARGV: 当前时间, 可见性超时时间
+/// 先把超时未确认的任务放回待执行，再领取一个到期任务并累加投递次数
+const CLAIM_SCRIPT: &str = r#"
+local expired = redis.call('ZRANGEBYSCORE', KEYS[2], '-inf', ARGV[1])
+for _, id in ipairs(expired) do
+  redis.call('ZREM', KEYS[2], id)
+  redis.call('ZADD', KEYS[1], ARGV[1], id)
+end
+local ids = redis.call('ZRANGEBYSCORE', KEYS[1], '-inf', ARGV[1], 'LIMIT', 0, 1)
+if #ids == 0 then return false end
+local id = ids[1]
+redis.call('ZREM', KEYS[1], id)
+local raw = redis.call('HGET', KEYS[3], id)
+if not raw then return false end
+local job = cjson.decode(raw)
+job.attempts = (job.attempts or 0) + 1
+raw = cjson.encode(job)
+redis.call('HSET', KEYS[3], id, raw)
+redis.call('ZADD', KEYS[2], ARGV[2], id)
+return raw
+"#;
+
+pub struct RedisJobQueue {
+    pool: RedisPool,
+}
+
+impl RedisJobQueue {
+    pub fn new(pool: RedisPool) -> Arc<Self> {
+        Arc::new(Self { pool })
+    }
+}
+
+fn encode(job: &Job) -> Result<String, fred::error::Error> {
+    serde_json::to_string(job).map_err(|e| fred::error::Error::new(fred::error::ErrorKind::Parse, e.to_string()))
+}
+
+impl JobQueue for RedisJobQueue {
+    fn enqueue<'a>(&'a self, job: &'a Job, run_at: i64) -> BoxFuture<'a, Result<(), fred::error::Error>> {
+        Box::pin(async move {
+            let args = vec![job.id.clone(), encode(job)?, run_at.to_string()];
+            let _: i64 = self.pool.eval(ENQUEUE_SCRIPT, vec![READY_KEY, DATA_KEY], args).await?;
+            Ok(())
+        })
+    }
+
+    fn claim(&self, now: i64, visible_at: i64) -> BoxFuture<'_, Result<Option<Job>, fred::error::Error>> {
+        Box::pin(async move {
+            let raw: Option<String> = self
+                .pool
+                .eval(CLAIM_SCRIPT, vec![READY_KEY, INFLIGHT_KEY, DATA_KEY], vec![now, visible_at])
+                .await?;
+            match raw {
+                Some(raw) => serde_json::from_str(&raw)
+                    .map(Some)
+                    .map_err(|e| fred::error::Error::new(fred::error::ErrorKind::Parse, e.to_string())),
+                None => Ok(None),
+            }
+        })
+    }
+
+    fn ack<'a>(&'a self, job_id: &'a str) -> BoxFuture<'a, Result<(), fred::error::Error>> {
+        Box::pin(async move {
+            let _: i64 = self.pool.zrem(INFLIGHT_KEY, job_id).await?;
+            let _: i64 = self.pool.hdel(DATA_KEY, job_id).await?;
+            Ok(())
+        })
+    }
+
+    fn retry<'a>(&'a self, job_id: &'a str, run_at: i64) -> BoxFuture<'a, Result<(), fred::error::Error>> {
+        Box::pin(async move {
+            let _: i64 = self.pool.zrem(INFLIGHT_KEY, job_id).await?;
+            let _: i64 = self.pool.zadd(READY_KEY, None, None, false, false, (run_at as f64, job_id)).await?;
+            Ok(())
+        })
+    }
+
+    fn bury<'a>(&'a self, job: &'a Job, error: &'a str) -> BoxFuture<'a, Result<(), fred::error::Error>> {
+        Box::pin(async move {
+            let record = serde_json::json!({ "job": job, "error": error }).to_string();
+            let _: i64 = self.pool.rpush(DEAD_KEY, record).await?;
+            self.ack(&job.id).await
+        })
+    }
+}
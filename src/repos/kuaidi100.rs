@@ -0,0 +1,185 @@
+//! 快递100 实时查询接口。请求参数 `param` 为 JSON，签名为 MD5(param + key + customer) 的大写十六进制。
+
+use chrono::Local;
+use md5::{Digest, Md5};
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use crate::domain::BoxFuture;
+use crate::domain::payment::GatewayError;
+use crate::domain::shipment::TrackingProvider;
+use crate::models::shipment::{TrackingEvent, TrackingInfo, TrackingState};
+use wx_shop::LogisticsSettings;
+
+const QUERY_PATH: &str = "/poll/query.do";
+/// 单号暂无轨迹时的返回码
+const NO_RECORD_CODE: &str = "500";
+
+pub struct Kuaidi100Client {
+    http: reqwest::Client,
+    settings: LogisticsSettings,
+}
+
+#[derive(Deserialize)]
+struct QueryResponse {
+    /// 只在出错时返回 false
+    #[serde(default)]
+    result: Option<bool>,
+    #[serde(default, rename = "returnCode")]
+    return_code: Option<String>,
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    state: String,
+    #[serde(default)]
+    data: Vec<QueryEvent>,
+}
+
+#[derive(Deserialize)]
+struct QueryEvent {
+    time: String,
+    context: String,
+}
+
+impl Kuaidi100Client {
+    pub fn new(settings: &LogisticsSettings) -> Result<Arc<Self>, String> {
+        let http = reqwest::Client::builder()
+            .user_agent(concat!("wx-shop/", env!("CARGO_PKG_VERSION")))
+            .timeout(std::time::Duration::from_secs(10))
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Arc::new(Self { http, settings: settings.clone() }))
+    }
+
+    fn sign(&self, param: &str) -> String {
+        let mut hasher = Md5::new();
+        hasher.update(param.as_bytes());
+        hasher.update(self.settings.kuaidi100_key.as_bytes());
+        hasher.update(self.settings.kuaidi100_customer.as_bytes());
+        hasher.finalize().iter().map(|byte| format!("{:02X}", byte)).collect()
+    }
+}
+
+/// 快递100 的 state：0 在途，1 揽收，2 疑难，3 签收，4 退签，5 派件，6 退回，14 拒签，其余按在途处理
+fn tracking_state(state: &str) -> TrackingState {
+    match state {
+        "1" => TrackingState::Collected,
+        "2" | "14" => TrackingState::Exception,
+        "3" => TrackingState::Delivered,
+        "4" | "6" => TrackingState::Returned,
+        "5" => TrackingState::Delivering,
+        _ => TrackingState::InTransit,
+    }
+}
+
+impl TrackingProvider for Kuaidi100Client {
+    fn query<'a>(&'a self, carrier: &'a str, tracking_no: &'a str, phone: &'a str) -> BoxFuture<'a, Result<TrackingInfo, GatewayError>> {
+        Box::pin(async move {
+            let param = json!({ "com": carrier, "num": tracking_no, "phone": phone }).to_string();
+            let sign = self.sign(&param);
+            let response = self
+                .http
+                .post(format!("{}{}", self.settings.kuaidi100_api_base, QUERY_PATH))
+                .form(&[("customer", self.settings.kuaidi100_customer.as_str()), ("sign", sign.as_str()), ("param", param.as_str())])
+                .send()
+                .await?;
+            let status = response.status();
+            if !status.is_success() {
+                let body = response.text().await.unwrap_or_default();
+                return Err(GatewayError::Api { status: status.as_u16(), body });
+            }
+
+            let body: QueryResponse = response.json().await?;
+            let mut info = TrackingInfo {
+                carrier: carrier.to_string(),
+                tracking_no: tracking_no.to_string(),
+                state: TrackingState::NoRecord,
+                events: Vec::new(),
+                queried_at: Local::now(),
+            };
+            if body.result == Some(false) {
+                if body.return_code.as_deref() == Some(NO_RECORD_CODE) {
+                    return Ok(info);
+                }
+                return Err(GatewayError::Api { status: status.as_u16(), body: body.message });
+            }
+            info.state = tracking_state(&body.state);
+            info.events = body
+                .data
+                .into_iter()
+                .map(|event| TrackingEvent { time: event.time, description: event.context })
+                .collect();
+            Ok(info)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_string_contains, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn settings(api_base: String) -> LogisticsSettings {
+        LogisticsSettings {
+            kuaidi100_customer: "CUSTOMER".into(),
+            kuaidi100_key: "KEY".into(),
+            kuaidi100_api_base: api_base,
+            ..LogisticsSettings::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_query_signs_and_parses_events() {
+        let server = MockServer::start().await;
+        let client = Kuaidi100Client::new(&settings(server.uri())).unwrap();
+        let param = json!({ "com": "yuantong", "num": "YT001", "phone": "13800000000" }).to_string();
+        Mock::given(method("POST"))
+            .and(path(QUERY_PATH))
+            .and(body_string_contains(format!("sign={}", client.sign(&param))))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "message": "ok",
+                "state": "3",
+                "status": "200",
+                "data": [
+                    { "time": "2024-01-03 10:00:00", "ftime": "2024-01-03 10:00:00", "context": "已签收" },
+                    { "time": "2024-01-02 08:00:00", "ftime": "2024-01-02 08:00:00", "context": "运输中" }
+                ]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let info = client.query("yuantong", "YT001", "13800000000").await.unwrap();
+        assert_eq!(info.state, TrackingState::Delivered);
+        assert_eq!(info.events.len(), 2);
+        assert_eq!(info.events[0].description, "已签收");
+    }
+
+    #[tokio::test]
+    async fn test_query_without_record_and_errors() {
+        let server = MockServer::start().await;
+        let client = Kuaidi100Client::new(&settings(server.uri())).unwrap();
+        Mock::given(method("POST"))
+            .and(path(QUERY_PATH))
+            .and(body_string_contains("NONE"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "result": false, "returnCode": "500", "message": "查询无结果，请隔段时间再查"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(QUERY_PATH))
+            .and(body_string_contains("BAD"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "result": false, "returnCode": "601", "message": "POLL:KEY已过期"
+            })))
+            .mount(&server)
+            .await;
+
+        let info = client.query("yuantong", "NONE", "").await.unwrap();
+        assert_eq!(info.state, TrackingState::NoRecord);
+        assert!(info.events.is_empty());
+        assert!(matches!(client.query("yuantong", "BAD", "").await, Err(GatewayError::Api { .. })));
+    }
+}
@@ -0,0 +1,169 @@
+use chrono::{DateTime, Local};
+use sqlx::{MySql, Pool};
+use std::sync::Arc;
+use crate::domain::BoxFuture;
+use crate::domain::loyalty::LoyaltyRepo;
+use crate::models::loyalty::{Member, NewPointsEntry, PointsEntry, PointsKind, PointsOutcome};
+use crate::models::order::OrderStatus;
+
+pub struct LoyaltyRepository {
+    pool: Pool<MySql>,
+}
+
+impl LoyaltyRepository {
+    pub fn new(pool: Pool<MySql>) -> Arc<Self> {
+        Arc::new(Self { pool })
+    }
+}
+
+impl LoyaltyRepo for LoyaltyRepository {
+    fn find_member(&self, user_id: u32) -> BoxFuture<'_, Result<Option<Member>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Member>("SELECT * FROM members WHERE user_id = ?")
+                .bind(user_id)
+                .fetch_optional(&self.pool)
+                .await
+        })
+    }
+
+    fn append<'a>(&'a self, entry: &'a NewPointsEntry) -> BoxFuture<'a, Result<PointsOutcome, sqlx::Error>> {
+        Box::pin(async move {
+            let mut tx = self.pool.begin().await?;
+            sqlx::query("INSERT IGNORE INTO members (user_id) VALUES (?)")
+                .bind(entry.user_id)
+                .execute(&mut *tx)
+                .await?;
+            // 行锁让同一用户的积分变动串行，余额不会被并发扣成负数
+            let (balance,): (i64,) = sqlx::query_as("SELECT points_balance FROM members WHERE user_id = ? FOR UPDATE")
+                .bind(entry.user_id)
+                .fetch_one(&mut *tx)
+                .await?;
+            let balance_after = balance + entry.delta;
+            if balance_after < 0 {
+                tx.rollback().await?;
+                return Ok(PointsOutcome::Insufficient);
+            }
+
+            let inserted = sqlx::query(
+                "INSERT IGNORE INTO points_ledger (user_id, kind, delta, balance_after, ref_no, expires_at, note) \
+                 VALUES (?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(entry.user_id)
+            .bind(entry.kind.as_str())
+            .bind(entry.delta)
+            .bind(balance_after)
+            .bind(&entry.ref_no)
+            .bind(entry.expires_at)
+            .bind(&entry.note)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+            if inserted == 0 {
+                tx.rollback().await?;
+                return Ok(PointsOutcome::Duplicate);
+            }
+
+            sqlx::query("UPDATE members SET points_balance = ? WHERE user_id = ?")
+                .bind(balance_after)
+                .bind(entry.user_id)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            Ok(PointsOutcome::Applied(balance_after))
+        })
+    }
+
+    fn find_entry<'a>(&'a self, user_id: u32, kind: PointsKind, ref_no: &'a str) -> BoxFuture<'a, Result<Option<PointsEntry>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, PointsEntry>("SELECT * FROM points_ledger WHERE user_id = ? AND kind = ? AND ref_no = ?")
+                .bind(user_id)
+                .bind(kind.as_str())
+                .bind(ref_no)
+                .fetch_optional(&self.pool)
+                .await
+        })
+    }
+
+    fn list_entries(&self, user_id: u32, limit: u32, offset: u32) -> BoxFuture<'_, Result<Vec<PointsEntry>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, PointsEntry>("SELECT * FROM points_ledger WHERE user_id = ? ORDER BY id DESC LIMIT ? OFFSET ?")
+                .bind(user_id)
+                .bind(limit)
+                .bind(offset)
+                .fetch_all(&self.pool)
+                .await
+        })
+    }
+
+    fn expiry_totals(&self, user_id: u32, now: DateTime<Local>) -> BoxFuture<'_, Result<(i64, i64), sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as(
+                "SELECT CAST(COALESCE(SUM(CASE WHEN delta > 0 AND expires_at <= ? THEN delta ELSE 0 END), 0) AS SIGNED), \
+                        CAST(COALESCE(SUM(CASE WHEN delta < 0 THEN -delta ELSE 0 END), 0) AS SIGNED) \
+                 FROM points_ledger WHERE user_id = ?",
+            )
+            .bind(now)
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await
+        })
+    }
+
+    fn users_with_expired(&self, now: DateTime<Local>, after_user_id: u32, limit: u32) -> BoxFuture<'_, Result<Vec<u32>, sqlx::Error>> {
+        Box::pin(async move {
+            let rows: Vec<(u32,)> = sqlx::query_as(
+                "SELECT DISTINCT l.user_id FROM points_ledger l JOIN members m ON m.user_id = l.user_id \
+                 WHERE l.delta > 0 AND l.expires_at <= ? AND m.points_balance > 0 AND l.user_id > ? \
+                 ORDER BY l.user_id LIMIT ?",
+            )
+            .bind(now)
+            .bind(after_user_id)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+            Ok(rows.into_iter().map(|(user_id,)| user_id).collect())
+        })
+    }
+
+    fn rolling_spend(&self, user_id: u32, since: DateTime<Local>) -> BoxFuture<'_, Result<i64, sqlx::Error>> {
+        Box::pin(async move {
+            // 已完成的订单不会再变更，updated_at 即完成时间
+            let (spend,): (i64,) = sqlx::query_as(
+                "SELECT CAST(COALESCE(SUM(payable_amount), 0) AS SIGNED) FROM orders \
+                 WHERE user_id = ? AND status = ? AND updated_at >= ?",
+            )
+            .bind(user_id)
+            .bind(OrderStatus::Completed.as_str())
+            .bind(since)
+            .fetch_one(&self.pool)
+            .await?;
+            Ok(spend)
+        })
+    }
+
+    fn save_tier(&self, user_id: u32, level: u32, rolling_spend: i64) -> BoxFuture<'_, Result<(), sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query(
+                "INSERT INTO members (user_id, level, rolling_spend) VALUES (?, ?, ?) \
+                 ON DUPLICATE KEY UPDATE level = VALUES(level), rolling_spend = VALUES(rolling_spend)",
+            )
+            .bind(user_id)
+            .bind(level)
+            .bind(rolling_spend)
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn tiered_users(&self, after_user_id: u32, limit: u32) -> BoxFuture<'_, Result<Vec<u32>, sqlx::Error>> {
+        Box::pin(async move {
+            let rows: Vec<(u32,)> = sqlx::query_as("SELECT user_id FROM members WHERE level > 0 AND user_id > ? ORDER BY user_id LIMIT ?")
+                .bind(after_user_id)
+                .bind(limit)
+                .fetch_all(&self.pool)
+                .await?;
+            Ok(rows.into_iter().map(|(user_id,)| user_id).collect())
+        })
+    }
+}
@@ -1 +1,34 @@
 pub mod users;
+pub mod inventory;
+pub mod stock_counter;
+pub mod catalog;
+pub mod cart;
+pub mod address;
+pub mod order;
+pub mod job_queue;
+pub mod payment;
+pub mod refund;
+pub mod reconciliation;
+pub mod coupon;
+pub mod promotion;
+pub mod group_buy;
+pub mod freight;
+pub mod shipment;
+pub mod after_sale;
+pub mod review;
+pub mod notification;
+pub mod loyalty;
+pub mod favorite;
+pub mod referral;
+pub mod stats;
+pub mod blob;
+pub mod export;
+pub mod import;
+pub mod audit;
+pub mod history;
+pub mod outbox;
+pub mod tracking;
+pub mod kuaidi100;
+pub mod wechat_subscribe;
+pub mod wechat_auth;
+pub mod wechat_pay;
@@ -0,0 +1,119 @@
+use sqlx::{MySql, Pool};
+use std::sync::Arc;
+use crate::domain::BoxFuture;
+use crate::domain::notification::NotificationRepo;
+use crate::models::notification::{Delivery, DeliveryStatus, NewDelivery, Subscription};
+
+pub struct NotificationRepository {
+    pool: Pool<MySql>,
+}
+
+impl NotificationRepository {
+    pub fn new(pool: Pool<MySql>) -> Arc<Self> {
+        Arc::new(Self { pool })
+    }
+}
+
+impl NotificationRepo for NotificationRepository {
+    fn subscribe<'a>(&'a self, user_id: u32, openid: &'a str, template_id: &'a str) -> BoxFuture<'a, Result<(), sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query(
+                "INSERT INTO notification_subscriptions (user_id, template_id, openid, remaining) VALUES (?, ?, ?, 1) \
+                 ON DUPLICATE KEY UPDATE openid = VALUES(openid), remaining = remaining + 1",
+            )
+            .bind(user_id)
+            .bind(template_id)
+            .bind(openid)
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn list_subscriptions(&self, user_id: u32) -> BoxFuture<'_, Result<Vec<Subscription>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Subscription>("SELECT * FROM notification_subscriptions WHERE user_id = ? ORDER BY id")
+                .bind(user_id)
+                .fetch_all(&self.pool)
+                .await
+        })
+    }
+
+    fn find_usable<'a>(&'a self, user_id: u32, template_id: &'a str) -> BoxFuture<'a, Result<Option<Subscription>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Subscription>(
+                "SELECT * FROM notification_subscriptions WHERE user_id = ? AND template_id = ? AND remaining > 0",
+            )
+            .bind(user_id)
+            .bind(template_id)
+            .fetch_optional(&self.pool)
+            .await
+        })
+    }
+
+    fn consume<'a>(&'a self, user_id: u32, template_id: &'a str) -> BoxFuture<'a, Result<(), sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query(
+                "UPDATE notification_subscriptions SET remaining = remaining - 1 \
+                 WHERE user_id = ? AND template_id = ? AND remaining > 0",
+            )
+            .bind(user_id)
+            .bind(template_id)
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn revoke<'a>(&'a self, user_id: u32, template_id: &'a str) -> BoxFuture<'a, Result<(), sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query("UPDATE notification_subscriptions SET remaining = 0 WHERE user_id = ? AND template_id = ?")
+                .bind(user_id)
+                .bind(template_id)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn has_sent<'a>(&'a self, order_id: u64, event: &'a str) -> BoxFuture<'a, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let (count,): (i64,) =
+                sqlx::query_as("SELECT COUNT(*) FROM notification_deliveries WHERE order_id = ? AND event = ? AND status = ?")
+                    .bind(order_id)
+                    .bind(event)
+                    .bind(DeliveryStatus::Sent.as_str())
+                    .fetch_one(&self.pool)
+                    .await?;
+            Ok(count > 0)
+        })
+    }
+
+    fn log_delivery<'a>(&'a self, delivery: &'a NewDelivery) -> BoxFuture<'a, Result<(), sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query(
+                "INSERT INTO notification_deliveries (user_id, order_id, event, template_id, status, error) VALUES (?, ?, ?, ?, ?, ?)",
+            )
+            .bind(delivery.user_id)
+            .bind(delivery.order_id)
+            .bind(&delivery.event)
+            .bind(&delivery.template_id)
+            .bind(delivery.status.as_str())
+            .bind(delivery.error.as_deref())
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn list_deliveries<'a>(&'a self, status: &'a str, limit: u32, offset: u32) -> BoxFuture<'a, Result<Vec<Delivery>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Delivery>("SELECT * FROM notification_deliveries WHERE status = ? ORDER BY id DESC LIMIT ? OFFSET ?")
+                .bind(status)
+                .bind(limit)
+                .bind(offset)
+                .fetch_all(&self.pool)
+                .await
+        })
+    }
+}
@@ -0,0 +1,165 @@
+use sqlx::{MySql, Pool};
+use std::sync::Arc;
+use crate::domain::BoxFuture;
+use crate::domain::order::{OrderRepo, OrderStateRepo, OutOfStock};
+use crate::models::event::DomainEvent;
+use crate::models::order::{Actor, ActorType, NewOrder, Order, OrderEvent, OrderItem, OrderStatus};
+use crate::repos::inventory::reserve_stock;
+
+pub struct OrderRepository {
+    pool: Pool<MySql>,
+}
+
+impl OrderRepository {
+    pub fn new(pool: Pool<MySql>) -> Arc<Self> {
+        Arc::new(Self { pool })
+    }
+}
+
+impl OrderRepo for OrderRepository {
+    fn create<'a>(&'a self, order: &'a NewOrder) -> BoxFuture<'a, Result<Result<u64, OutOfStock>, sqlx::Error>> {
+        Box::pin(async move {
+            let mut tx = self.pool.begin().await?;
+            for movement in &order.reservations {
+                if !reserve_stock(&mut tx, movement).await? {
+                    tx.rollback().await?;
+                    return Ok(Err(OutOfStock { sku_id: movement.sku_id }));
+                }
+            }
+            let order_id = sqlx::query(
+                "INSERT INTO orders (order_no, user_id, status, goods_amount, discount_amount, shipping_fee, payable_amount, \
+                 receiver_name, receiver_phone, province, city, district, address_detail) \
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(&order.order_no)
+            .bind(order.user_id)
+            .bind(order.status.as_str())
+            .bind(order.goods_amount)
+            .bind(order.discount_amount)
+            .bind(order.shipping_fee)
+            .bind(order.payable_amount)
+            .bind(&order.receiver_name)
+            .bind(&order.receiver_phone)
+            .bind(&order.province)
+            .bind(&order.city)
+            .bind(&order.district)
+            .bind(&order.address_detail)
+            .execute(&mut *tx)
+            .await?
+            .last_insert_id();
+
+            for item in &order.items {
+                sqlx::query(
+                    "INSERT INTO order_items (order_id, sku_id, product_id, product_name, sku_title, unit_price, quantity, \
+                     line_total, discount_amount, payable_amount) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                )
+                .bind(order_id)
+                .bind(item.sku_id)
+                .bind(item.product_id)
+                .bind(&item.product_name)
+                .bind(&item.sku_title)
+                .bind(item.unit_price)
+                .bind(item.quantity)
+                .bind(item.line_total)
+                .bind(item.discount_amount)
+                .bind(item.payable_amount)
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            sqlx::query("INSERT INTO order_events (order_id, from_status, to_status, actor_type, actor_id, reason) VALUES (?, NULL, ?, ?, ?, ?)")
+                .bind(order_id)
+                .bind(order.status.as_str())
+                .bind(ActorType::User.as_str())
+                .bind(order.user_id)
+                .bind("order placed")
+                .execute(&mut *tx)
+                .await?;
+
+            tx.commit().await?;
+            Ok(Ok(order_id))
+        })
+    }
+
+    fn find_by_id(&self, id: u64) -> BoxFuture<'_, Result<Option<Order>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Order>("SELECT * FROM orders WHERE id = ?")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await
+        })
+    }
+
+    fn find_items(&self, order_id: u64) -> BoxFuture<'_, Result<Vec<OrderItem>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, OrderItem>("SELECT * FROM order_items WHERE order_id = ? ORDER BY id")
+                .bind(order_id)
+                .fetch_all(&self.pool)
+                .await
+        })
+    }
+
+    fn list_by_user(&self, user_id: u32, limit: u32, offset: u32) -> BoxFuture<'_, Result<Vec<Order>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Order>("SELECT * FROM orders WHERE user_id = ? ORDER BY id DESC LIMIT ? OFFSET ?")
+                .bind(user_id)
+                .bind(limit)
+                .bind(offset)
+                .fetch_all(&self.pool)
+                .await
+        })
+    }
+}
+
+impl OrderStateRepo for OrderRepository {
+    fn find_order(&self, id: u64) -> BoxFuture<'_, Result<Option<Order>, sqlx::Error>> {
+        self.find_by_id(id)
+    }
+
+    fn transition<'a>(
+        &'a self,
+        order_id: u64,
+        from: OrderStatus,
+        to: OrderStatus,
+        actor: Actor,
+        reason: &'a str,
+        event: &'a DomainEvent,
+    ) -> BoxFuture<'a, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let mut tx = self.pool.begin().await?;
+            let updated = sqlx::query("UPDATE orders SET status = ? WHERE id = ? AND status = ?")
+                .bind(to.as_str())
+                .bind(order_id)
+                .bind(from.as_str())
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+            if updated == 0 {
+                tx.rollback().await?;
+                return Ok(false);
+            }
+
+            sqlx::query("INSERT INTO order_events (order_id, from_status, to_status, actor_type, actor_id, reason) VALUES (?, ?, ?, ?, ?, ?)")
+                .bind(order_id)
+                .bind(from.as_str())
+                .bind(to.as_str())
+                .bind(actor.actor_type.as_str())
+                .bind(actor.id)
+                .bind(reason)
+                .execute(&mut *tx)
+                .await?;
+            crate::repos::outbox::append(&mut tx, event).await?;
+            tx.commit().await?;
+            Ok(true)
+        })
+    }
+
+    fn list_events(&self, order_id: u64) -> BoxFuture<'_, Result<Vec<OrderEvent>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, OrderEvent>("SELECT * FROM order_events WHERE order_id = ? ORDER BY id")
+                .bind(order_id)
+                .fetch_all(&self.pool)
+                .await
+        })
+    }
+}
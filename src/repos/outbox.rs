@@ -0,0 +1,81 @@
+use chrono::{DateTime, Local};
+use sqlx::{MySql, MySqlConnection, Pool};
+use std::sync::Arc;
+use crate::domain::BoxFuture;
+use crate::domain::events::OutboxRepo;
+use crate::models::event::{DomainEvent, OutboxEvent};
+
+/// 在调用方的事务中写入一条发件箱事件，随业务数据一起提交或回滚
+pub async fn append(conn: &mut MySqlConnection, event: &DomainEvent) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO outbox_events (event_type, aggregate_id, payload) VALUES (?, ?, ?)")
+        .bind(&event.event_type)
+        .bind(&event.aggregate_id)
+        .bind(event.payload.to_string())
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+pub struct OutboxRepository {
+    pool: Pool<MySql>,
+}
+
+impl OutboxRepository {
+    pub fn new(pool: Pool<MySql>) -> Arc<Self> {
+        Arc::new(Self { pool })
+    }
+}
+
+impl OutboxRepo for OutboxRepository {
+    fn fetch_due(&self, now: DateTime<Local>, max_attempts: u32, limit: u32) -> BoxFuture<'_, Result<Vec<OutboxEvent>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, OutboxEvent>(
+                "SELECT * FROM outbox_events WHERE published_at IS NULL AND next_attempt_at <= ? AND attempts < ? ORDER BY id LIMIT ?",
+            )
+            .bind(now)
+            .bind(max_attempts)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+        })
+    }
+
+    fn claim(&self, id: u64, attempts: u32, lease_until: DateTime<Local>) -> BoxFuture<'_, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let updated = sqlx::query(
+                "UPDATE outbox_events SET attempts = attempts + 1, next_attempt_at = ? \
+                 WHERE id = ? AND attempts = ? AND published_at IS NULL",
+            )
+            .bind(lease_until)
+            .bind(id)
+            .bind(attempts)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+            Ok(updated == 1)
+        })
+    }
+
+    fn mark_published(&self, id: u64, at: DateTime<Local>) -> BoxFuture<'_, Result<(), sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query("UPDATE outbox_events SET published_at = ?, last_error = NULL WHERE id = ?")
+                .bind(at)
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn mark_failed<'a>(&'a self, id: u64, error: &'a str, next_attempt_at: DateTime<Local>) -> BoxFuture<'a, Result<(), sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query("UPDATE outbox_events SET last_error = ?, next_attempt_at = ? WHERE id = ?")
+                .bind(error)
+                .bind(next_attempt_at)
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,125 @@
+use sqlx::{MySql, Pool};
+use std::sync::Arc;
+use crate::domain::BoxFuture;
+use crate::domain::payment::PaymentRepo;
+use chrono::{DateTime, Local};
+use crate::models::payment::{NewPayment, PAYMENT_CLOSED, PAYMENT_PENDING, PAYMENT_SUCCEEDED, Payment};
+
+pub struct PaymentRepository {
+    pool: Pool<MySql>,
+}
+
+impl PaymentRepository {
+    pub fn new(pool: Pool<MySql>) -> Arc<Self> {
+        Arc::new(Self { pool })
+    }
+}
+
+impl PaymentRepo for PaymentRepository {
+    fn save_prepay<'a>(&'a self, payment: &'a NewPayment) -> BoxFuture<'a, Result<(), sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query(
+                "INSERT INTO payments (order_id, user_id, channel, out_trade_no, amount, status, prepay_id) \
+                 VALUES (?, ?, ?, ?, ?, ?, ?) \
+                 ON DUPLICATE KEY UPDATE amount = VALUES(amount), prepay_id = VALUES(prepay_id)",
+            )
+            .bind(payment.order_id)
+            .bind(payment.user_id)
+            .bind(payment.channel)
+            .bind(&payment.out_trade_no)
+            .bind(payment.amount)
+            .bind(PAYMENT_PENDING)
+            .bind(&payment.prepay_id)
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn find_by_out_trade_no<'a>(&'a self, out_trade_no: &'a str) -> BoxFuture<'a, Result<Option<Payment>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Payment>("SELECT * FROM payments WHERE out_trade_no = ?")
+                .bind(out_trade_no)
+                .fetch_optional(&self.pool)
+                .await
+        })
+    }
+
+    fn find_by_order(&self, order_id: u64) -> BoxFuture<'_, Result<Option<Payment>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Payment>("SELECT * FROM payments WHERE order_id = ?")
+                .bind(order_id)
+                .fetch_optional(&self.pool)
+                .await
+        })
+    }
+
+    fn mark_succeeded<'a>(
+        &'a self,
+        id: u64,
+        transaction_id: &'a str,
+        paid_at: DateTime<Local>,
+    ) -> BoxFuture<'a, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query(
+                "UPDATE payments SET status = ?, transaction_id = ?, paid_at = ? WHERE id = ? AND status = ?",
+            )
+            .bind(PAYMENT_SUCCEEDED)
+            .bind(transaction_id)
+            .bind(paid_at)
+            .bind(id)
+            .bind(PAYMENT_PENDING)
+            .execute(&self.pool)
+            .await?;
+            Ok(result.rows_affected() == 1)
+        })
+    }
+
+    fn list_succeeded_between(
+        &self,
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+    ) -> BoxFuture<'_, Result<Vec<Payment>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Payment>(
+                "SELECT * FROM payments WHERE status = ? AND paid_at >= ? AND paid_at < ? ORDER BY id",
+            )
+            .bind(PAYMENT_SUCCEEDED)
+            .bind(start)
+            .bind(end)
+            .fetch_all(&self.pool)
+            .await
+        })
+    }
+
+    fn list_pending(
+        &self,
+        before: DateTime<Local>,
+        after: DateTime<Local>,
+        limit: u32,
+    ) -> BoxFuture<'_, Result<Vec<Payment>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Payment>(
+                "SELECT * FROM payments WHERE status = ? AND updated_at < ? AND updated_at > ? ORDER BY updated_at LIMIT ?",
+            )
+            .bind(PAYMENT_PENDING)
+            .bind(before)
+            .bind(after)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+        })
+    }
+
+    fn mark_closed(&self, id: u64) -> BoxFuture<'_, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query("UPDATE payments SET status = ? WHERE id = ? AND status = ?")
+                .bind(PAYMENT_CLOSED)
+                .bind(id)
+                .bind(PAYMENT_PENDING)
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected() == 1)
+        })
+    }
+}
@@ -0,0 +1,82 @@
+use sqlx::{MySql, Pool};
+use sqlx::types::Json;
+use std::sync::Arc;
+use chrono::{DateTime, Local};
+use crate::domain::BoxFuture;
+use crate::domain::promotion::PromotionRepo;
+use crate::models::promotion::{NewPromotion, PROMOTION_ACTIVE, Promotion};
+
+pub struct PromotionRepository {
+    pool: Pool<MySql>,
+}
+
+impl PromotionRepository {
+    pub fn new(pool: Pool<MySql>) -> Arc<Self> {
+        Arc::new(Self { pool })
+    }
+}
+
+impl PromotionRepo for PromotionRepository {
+    fn create<'a>(&'a self, promotion: &'a NewPromotion) -> BoxFuture<'a, Result<u64, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query(
+                "INSERT INTO promotions (name, priority, exclusive, rule, starts_at, ends_at, status) \
+                 VALUES (?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(promotion.name.trim())
+            .bind(promotion.priority)
+            .bind(promotion.exclusive)
+            .bind(Json(&promotion.rule))
+            .bind(promotion.starts_at)
+            .bind(promotion.ends_at)
+            .bind(PROMOTION_ACTIVE)
+            .execute(&self.pool)
+            .await?;
+            Ok(result.last_insert_id())
+        })
+    }
+
+    fn find(&self, id: u64) -> BoxFuture<'_, Result<Option<Promotion>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Promotion>("SELECT * FROM promotions WHERE id = ?")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await
+        })
+    }
+
+    fn list(&self, limit: u32, offset: u32) -> BoxFuture<'_, Result<Vec<Promotion>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Promotion>("SELECT * FROM promotions ORDER BY id DESC LIMIT ? OFFSET ?")
+                .bind(limit)
+                .bind(offset)
+                .fetch_all(&self.pool)
+                .await
+        })
+    }
+
+    fn list_active(&self, now: DateTime<Local>) -> BoxFuture<'_, Result<Vec<Promotion>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Promotion>(
+                "SELECT * FROM promotions WHERE status = ? AND starts_at <= ? AND ends_at > ? \
+                 ORDER BY priority DESC, id",
+            )
+            .bind(PROMOTION_ACTIVE)
+            .bind(now)
+            .bind(now)
+            .fetch_all(&self.pool)
+            .await
+        })
+    }
+
+    fn set_status<'a>(&'a self, id: u64, status: &'a str) -> BoxFuture<'a, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query("UPDATE promotions SET status = ? WHERE id = ?")
+                .bind(status)
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected() == 1)
+        })
+    }
+}
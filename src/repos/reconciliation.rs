@@ -0,0 +1,98 @@
+use sqlx::{MySql, Pool};
+use std::sync::Arc;
+use chrono::NaiveDate;
+use crate::domain::BoxFuture;
+use crate::domain::reconciliation::ReconciliationRepo;
+use crate::models::reconciliation::{Discrepancy, NewDiscrepancy, ReconciliationRun};
+
+pub struct ReconciliationRepository {
+    pool: Pool<MySql>,
+}
+
+impl ReconciliationRepository {
+    pub fn new(pool: Pool<MySql>) -> Arc<Self> {
+        Arc::new(Self { pool })
+    }
+}
+
+impl ReconciliationRepo for ReconciliationRepository {
+    fn save<'a>(
+        &'a self,
+        bill_date: NaiveDate,
+        remote_count: u32,
+        local_count: u32,
+        discrepancies: &'a [NewDiscrepancy],
+    ) -> BoxFuture<'a, Result<u64, sqlx::Error>> {
+        Box::pin(async move {
+            let mut tx = self.pool.begin().await?;
+            // LAST_INSERT_ID(id) 让覆盖已有记录时也能取回其 id
+            let run_id = sqlx::query(
+                "INSERT INTO reconciliation_runs (bill_date, remote_count, local_count, discrepancy_count) \
+                 VALUES (?, ?, ?, ?) \
+                 ON DUPLICATE KEY UPDATE id = LAST_INSERT_ID(id), remote_count = VALUES(remote_count), \
+                 local_count = VALUES(local_count), discrepancy_count = VALUES(discrepancy_count)",
+            )
+            .bind(bill_date)
+            .bind(remote_count)
+            .bind(local_count)
+            .bind(discrepancies.len() as u32)
+            .execute(&mut *tx)
+            .await?
+            .last_insert_id();
+
+            sqlx::query("DELETE FROM reconciliation_discrepancies WHERE run_id = ?")
+                .bind(run_id)
+                .execute(&mut *tx)
+                .await?;
+            for discrepancy in discrepancies {
+                sqlx::query(
+                    "INSERT INTO reconciliation_discrepancies \
+                     (run_id, category, kind, reference_no, local_amount, remote_amount) VALUES (?, ?, ?, ?, ?, ?)",
+                )
+                .bind(run_id)
+                .bind(discrepancy.category)
+                .bind(discrepancy.kind.as_str())
+                .bind(&discrepancy.reference_no)
+                .bind(discrepancy.local_amount)
+                .bind(discrepancy.remote_amount)
+                .execute(&mut *tx)
+                .await?;
+            }
+            tx.commit().await?;
+            Ok(run_id)
+        })
+    }
+
+    fn find_by_date(&self, bill_date: NaiveDate) -> BoxFuture<'_, Result<Option<ReconciliationRun>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, ReconciliationRun>("SELECT * FROM reconciliation_runs WHERE bill_date = ?")
+                .bind(bill_date)
+                .fetch_optional(&self.pool)
+                .await
+        })
+    }
+
+    fn list_runs(&self, limit: u32, offset: u32) -> BoxFuture<'_, Result<Vec<ReconciliationRun>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, ReconciliationRun>(
+                "SELECT * FROM reconciliation_runs ORDER BY bill_date DESC LIMIT ? OFFSET ?",
+            )
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await
+        })
+    }
+
+    fn list_discrepancies(&self, run_id: u64) -> BoxFuture<'_, Result<Vec<Discrepancy>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Discrepancy>(
+                "SELECT id, run_id, category, kind, reference_no, local_amount, remote_amount \
+                 FROM reconciliation_discrepancies WHERE run_id = ? ORDER BY id",
+            )
+            .bind(run_id)
+            .fetch_all(&self.pool)
+            .await
+        })
+    }
+}
@@ -0,0 +1,239 @@
+use chrono::{DateTime, Local};
+use sqlx::{MySql, Pool};
+use std::sync::Arc;
+use crate::domain::BoxFuture;
+use crate::domain::referral::ReferralRepo;
+use crate::models::order::OrderStatus;
+use crate::models::referral::{Commission, CommissionStatus, NewCommission, ReferralBinding, ReferralCode};
+
+pub struct ReferralRepository {
+    pool: Pool<MySql>,
+}
+
+impl ReferralRepository {
+    pub fn new(pool: Pool<MySql>) -> Arc<Self> {
+        Arc::new(Self { pool })
+    }
+}
+
+impl ReferralRepo for ReferralRepository {
+    fn find_code_by_user(&self, user_id: u32) -> BoxFuture<'_, Result<Option<ReferralCode>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, ReferralCode>("SELECT * FROM referral_codes WHERE user_id = ?")
+                .bind(user_id)
+                .fetch_optional(&self.pool)
+                .await
+        })
+    }
+
+    fn find_code<'a>(&'a self, code: &'a str) -> BoxFuture<'a, Result<Option<ReferralCode>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, ReferralCode>("SELECT * FROM referral_codes WHERE code = ?")
+                .bind(code)
+                .fetch_optional(&self.pool)
+                .await
+        })
+    }
+
+    fn create_code<'a>(&'a self, user_id: u32, code: &'a str) -> BoxFuture<'a, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query("INSERT IGNORE INTO referral_codes (user_id, code) VALUES (?, ?)")
+                .bind(user_id)
+                .bind(code)
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected() == 1)
+        })
+    }
+
+    fn find_binding(&self, user_id: u32) -> BoxFuture<'_, Result<Option<ReferralBinding>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, ReferralBinding>("SELECT * FROM referral_bindings WHERE user_id = ?")
+                .bind(user_id)
+                .fetch_optional(&self.pool)
+                .await
+        })
+    }
+
+    fn bind<'a>(
+        &'a self,
+        user_id: u32,
+        referrer_id: u32,
+        code: &'a str,
+        now: DateTime<Local>,
+        expires_at: DateTime<Local>,
+    ) -> BoxFuture<'a, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let inserted = sqlx::query(
+                "INSERT IGNORE INTO referral_bindings (user_id, referrer_id, code, bound_at, expires_at) VALUES (?, ?, ?, ?, ?)",
+            )
+            .bind(user_id)
+            .bind(referrer_id)
+            .bind(code)
+            .bind(now)
+            .bind(expires_at)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+            if inserted == 1 {
+                return Ok(true);
+            }
+            // 已有归属时只有过期后才能被新的推广人覆盖
+            let updated = sqlx::query(
+                "UPDATE referral_bindings SET referrer_id = ?, code = ?, bound_at = ?, expires_at = ? \
+                 WHERE user_id = ? AND expires_at <= ?",
+            )
+            .bind(referrer_id)
+            .bind(code)
+            .bind(now)
+            .bind(expires_at)
+            .bind(user_id)
+            .bind(now)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+            Ok(updated == 1)
+        })
+    }
+
+    fn has_paid_orders(&self, user_id: u32) -> BoxFuture<'_, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let (paid,): (i64,) = sqlx::query_as(
+                "SELECT EXISTS(SELECT 1 FROM orders WHERE user_id = ? AND status NOT IN (?, ?))",
+            )
+            .bind(user_id)
+            .bind(OrderStatus::PendingPayment.as_str())
+            .bind(OrderStatus::Cancelled.as_str())
+            .fetch_one(&self.pool)
+            .await?;
+            Ok(paid == 1)
+        })
+    }
+
+    fn count_invited(&self, referrer_id: u32, now: DateTime<Local>) -> BoxFuture<'_, Result<i64, sqlx::Error>> {
+        Box::pin(async move {
+            let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM referral_bindings WHERE referrer_id = ? AND expires_at > ?")
+                .bind(referrer_id)
+                .bind(now)
+                .fetch_one(&self.pool)
+                .await?;
+            Ok(count)
+        })
+    }
+
+    fn add_commissions<'a>(&'a self, commissions: &'a [NewCommission]) -> BoxFuture<'a, Result<(), sqlx::Error>> {
+        Box::pin(async move {
+            let mut tx = self.pool.begin().await?;
+            for commission in commissions {
+                sqlx::query(
+                    "INSERT IGNORE INTO commissions \
+                     (order_id, order_no, buyer_id, beneficiary_id, level, base_amount, rate_bp, amount, status) \
+                     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                )
+                .bind(commission.order_id)
+                .bind(&commission.order_no)
+                .bind(commission.buyer_id)
+                .bind(commission.beneficiary_id)
+                .bind(commission.level)
+                .bind(commission.base_amount)
+                .bind(commission.rate_bp)
+                .bind(commission.amount)
+                .bind(CommissionStatus::Pending.as_str())
+                .execute(&mut *tx)
+                .await?;
+            }
+            tx.commit().await
+        })
+    }
+
+    fn schedule_settle(&self, order_id: u64, settle_at: DateTime<Local>) -> BoxFuture<'_, Result<u64, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query("UPDATE commissions SET settle_at = ? WHERE order_id = ? AND status = ?")
+                .bind(settle_at)
+                .bind(order_id)
+                .bind(CommissionStatus::Pending.as_str())
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected())
+        })
+    }
+
+    fn due_orders(&self, now: DateTime<Local>, after_order_id: u64, limit: u32) -> BoxFuture<'_, Result<Vec<u64>, sqlx::Error>> {
+        Box::pin(async move {
+            let rows: Vec<(u64,)> = sqlx::query_as(
+                "SELECT DISTINCT order_id FROM commissions WHERE status = ? AND settle_at <= ? AND order_id > ? \
+                 ORDER BY order_id LIMIT ?",
+            )
+            .bind(CommissionStatus::Pending.as_str())
+            .bind(now)
+            .bind(after_order_id)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+            Ok(rows.into_iter().map(|(order_id,)| order_id).collect())
+        })
+    }
+
+    fn settle(&self, order_id: u64, base_amount: i64, now: DateTime<Local>) -> BoxFuture<'_, Result<u64, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query(
+                "UPDATE commissions SET status = ?, base_amount = ?, amount = ? * rate_bp DIV 10000, settled_at = ? \
+                 WHERE order_id = ? AND status = ?",
+            )
+            .bind(CommissionStatus::Withdrawable.as_str())
+            .bind(base_amount)
+            .bind(base_amount)
+            .bind(now)
+            .bind(order_id)
+            .bind(CommissionStatus::Pending.as_str())
+            .execute(&self.pool)
+            .await?;
+            Ok(result.rows_affected())
+        })
+    }
+
+    fn reverse(&self, order_id: u64) -> BoxFuture<'_, Result<u64, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query("UPDATE commissions SET status = ? WHERE order_id = ? AND status <> ?")
+                .bind(CommissionStatus::Reversed.as_str())
+                .bind(order_id)
+                .bind(CommissionStatus::Reversed.as_str())
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected())
+        })
+    }
+
+    fn list_by_beneficiary(&self, beneficiary_id: u32, limit: u32, offset: u32) -> BoxFuture<'_, Result<Vec<Commission>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Commission>("SELECT * FROM commissions WHERE beneficiary_id = ? ORDER BY id DESC LIMIT ? OFFSET ?")
+                .bind(beneficiary_id)
+                .bind(limit)
+                .bind(offset)
+                .fetch_all(&self.pool)
+                .await
+        })
+    }
+
+    fn list_by_status(&self, status: CommissionStatus, limit: u32, offset: u32) -> BoxFuture<'_, Result<Vec<Commission>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Commission>("SELECT * FROM commissions WHERE status = ? ORDER BY id DESC LIMIT ? OFFSET ?")
+                .bind(status.as_str())
+                .bind(limit)
+                .bind(offset)
+                .fetch_all(&self.pool)
+                .await
+        })
+    }
+
+    fn totals(&self, beneficiary_id: u32) -> BoxFuture<'_, Result<Vec<(String, i64)>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as(
+                "SELECT status, CAST(COALESCE(SUM(amount), 0) AS SIGNED) FROM commissions WHERE beneficiary_id = ? GROUP BY status",
+            )
+            .bind(beneficiary_id)
+            .fetch_all(&self.pool)
+            .await
+        })
+    }
+}
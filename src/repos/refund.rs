@@ -0,0 +1,154 @@
+use sqlx::{MySql, Pool};
+use std::sync::Arc;
+use chrono::{DateTime, Local};
+use crate::domain::BoxFuture;
+use crate::domain::refund::RefundRepo;
+use crate::models::refund::{NewRefund, Refund, RefundStatus};
+
+pub struct RefundRepository {
+    pool: Pool<MySql>,
+}
+
+impl RefundRepository {
+    pub fn new(pool: Pool<MySql>) -> Arc<Self> {
+        Arc::new(Self { pool })
+    }
+}
+
+impl RefundRepo for RefundRepository {
+    fn create<'a>(&'a self, refund: &'a NewRefund) -> BoxFuture<'a, Result<u64, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query(
+                "INSERT INTO refunds (refund_no, order_id, order_item_id, user_id, amount, reason, status, order_status) \
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(&refund.refund_no)
+            .bind(refund.order_id)
+            .bind(refund.order_item_id)
+            .bind(refund.user_id)
+            .bind(refund.amount)
+            .bind(&refund.reason)
+            .bind(RefundStatus::Pending.as_str())
+            .bind(&refund.order_status)
+            .execute(&self.pool)
+            .await?;
+            Ok(result.last_insert_id())
+        })
+    }
+
+    fn find(&self, id: u64) -> BoxFuture<'_, Result<Option<Refund>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Refund>("SELECT * FROM refunds WHERE id = ?")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await
+        })
+    }
+
+    fn find_by_refund_no<'a>(&'a self, refund_no: &'a str) -> BoxFuture<'a, Result<Option<Refund>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Refund>("SELECT * FROM refunds WHERE refund_no = ?")
+                .bind(refund_no)
+                .fetch_optional(&self.pool)
+                .await
+        })
+    }
+
+    fn list_by_order(&self, order_id: u64) -> BoxFuture<'_, Result<Vec<Refund>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Refund>("SELECT * FROM refunds WHERE order_id = ? ORDER BY id")
+                .bind(order_id)
+                .fetch_all(&self.pool)
+                .await
+        })
+    }
+
+    fn list_by_status<'a>(&'a self, status: &'a str, limit: u32, offset: u32) -> BoxFuture<'a, Result<Vec<Refund>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Refund>("SELECT * FROM refunds WHERE status = ? ORDER BY id LIMIT ? OFFSET ?")
+                .bind(status)
+                .bind(limit)
+                .bind(offset)
+                .fetch_all(&self.pool)
+                .await
+        })
+    }
+
+    fn approve(&self, id: u64, admin_id: u32, restock: bool) -> BoxFuture<'_, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query("UPDATE refunds SET status = ?, admin_id = ?, restock = ? WHERE id = ? AND status = ?")
+                .bind(RefundStatus::Processing.as_str())
+                .bind(admin_id)
+                .bind(restock)
+                .bind(id)
+                .bind(RefundStatus::Pending.as_str())
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected() == 1)
+        })
+    }
+
+    fn reject<'a>(&'a self, id: u64, admin_id: u32, reason: &'a str) -> BoxFuture<'a, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query("UPDATE refunds SET status = ?, admin_id = ?, reject_reason = ? WHERE id = ? AND status = ?")
+                .bind(RefundStatus::Rejected.as_str())
+                .bind(admin_id)
+                .bind(reason)
+                .bind(id)
+                .bind(RefundStatus::Pending.as_str())
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected() == 1)
+        })
+    }
+
+    fn mark_succeeded<'a>(
+        &'a self,
+        id: u64,
+        channel_refund_id: &'a str,
+        refunded_at: DateTime<Local>,
+    ) -> BoxFuture<'a, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query(
+                "UPDATE refunds SET status = ?, channel_refund_id = ?, refunded_at = ? WHERE id = ? AND status = ?",
+            )
+            .bind(RefundStatus::Succeeded.as_str())
+            .bind(channel_refund_id)
+            .bind(refunded_at)
+            .bind(id)
+            .bind(RefundStatus::Processing.as_str())
+            .execute(&self.pool)
+            .await?;
+            Ok(result.rows_affected() == 1)
+        })
+    }
+
+    fn mark_failed(&self, id: u64) -> BoxFuture<'_, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query("UPDATE refunds SET status = ? WHERE id = ? AND status = ?")
+                .bind(RefundStatus::Failed.as_str())
+                .bind(id)
+                .bind(RefundStatus::Processing.as_str())
+                .execute(&self.pool)
+                .await?;
+            Ok(result.rows_affected() == 1)
+        })
+    }
+
+    fn list_succeeded_between(
+        &self,
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+    ) -> BoxFuture<'_, Result<Vec<Refund>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Refund>(
+                "SELECT * FROM refunds WHERE status = ? AND refunded_at >= ? AND refunded_at < ? ORDER BY id",
+            )
+            .bind(RefundStatus::Succeeded.as_str())
+            .bind(start)
+            .bind(end)
+            .fetch_all(&self.pool)
+            .await
+        })
+    }
+}
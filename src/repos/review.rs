@@ -0,0 +1,185 @@
+use sqlx::{MySql, Pool, QueryBuilder};
+use sqlx::types::Json;
+use std::sync::Arc;
+use crate::domain::BoxFuture;
+use crate::domain::review::ReviewRepo;
+use crate::models::review::{NewReview, Review, ReviewFilter, ReviewStats, ReviewStatus};
+
+pub struct ReviewRepository {
+    pool: Pool<MySql>,
+}
+
+impl ReviewRepository {
+    pub fn new(pool: Pool<MySql>) -> Arc<Self> {
+        Arc::new(Self { pool })
+    }
+}
+
+impl ReviewRepo for ReviewRepository {
+    fn create<'a>(&'a self, review: &'a NewReview) -> BoxFuture<'a, Result<u64, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query(
+                "INSERT INTO reviews (order_id, order_item_id, user_id, product_id, sku_id, sku_title, rating, content, \
+                 photos, has_photos, status) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(review.order_id)
+            .bind(review.order_item_id)
+            .bind(review.user_id)
+            .bind(review.product_id)
+            .bind(review.sku_id)
+            .bind(&review.sku_title)
+            .bind(review.rating)
+            .bind(&review.content)
+            .bind(Json(&review.photos))
+            .bind(!review.photos.is_empty())
+            .bind(ReviewStatus::Pending.as_str())
+            .execute(&self.pool)
+            .await?;
+            Ok(result.last_insert_id())
+        })
+    }
+
+    fn find(&self, id: u64) -> BoxFuture<'_, Result<Option<Review>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Review>("SELECT * FROM reviews WHERE id = ?")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await
+        })
+    }
+
+    fn find_by_item(&self, order_item_id: u64) -> BoxFuture<'_, Result<Option<Review>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Review>("SELECT * FROM reviews WHERE order_item_id = ?")
+                .bind(order_item_id)
+                .fetch_optional(&self.pool)
+                .await
+        })
+    }
+
+    fn list_by_user(&self, user_id: u32, limit: u32, offset: u32) -> BoxFuture<'_, Result<Vec<Review>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Review>("SELECT * FROM reviews WHERE user_id = ? ORDER BY id DESC LIMIT ? OFFSET ?")
+                .bind(user_id)
+                .bind(limit)
+                .bind(offset)
+                .fetch_all(&self.pool)
+                .await
+        })
+    }
+
+    fn list_by_status<'a>(&'a self, status: &'a str, limit: u32, offset: u32) -> BoxFuture<'a, Result<Vec<Review>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Review>("SELECT * FROM reviews WHERE status = ? ORDER BY updated_at, id LIMIT ? OFFSET ?")
+                .bind(status)
+                .bind(limit)
+                .bind(offset)
+                .fetch_all(&self.pool)
+                .await
+        })
+    }
+
+    fn list_for_product<'a>(
+        &'a self,
+        product_id: u64,
+        filter: &'a ReviewFilter,
+        limit: u32,
+        offset: u32,
+    ) -> BoxFuture<'a, Result<Vec<Review>, sqlx::Error>> {
+        Box::pin(async move {
+            let mut query = QueryBuilder::<MySql>::new("SELECT * FROM reviews WHERE product_id = ");
+            query.push_bind(product_id);
+            query.push(" AND status = ").push_bind(ReviewStatus::Approved.as_str());
+            if let Some(rating) = filter.rating {
+                query.push(" AND rating = ").push_bind(rating);
+            }
+            if filter.has_photos {
+                query.push(" AND has_photos = 1");
+            }
+            query.push(" ORDER BY id DESC LIMIT ").push_bind(limit);
+            query.push(" OFFSET ").push_bind(offset);
+            query.build_query_as::<Review>().fetch_all(&self.pool).await
+        })
+    }
+
+    fn rating_counts(&self, product_id: u64) -> BoxFuture<'_, Result<Vec<(u8, u32)>, sqlx::Error>> {
+        Box::pin(async move {
+            let rows: Vec<(u8, i64)> = sqlx::query_as(
+                "SELECT rating, COUNT(*) FROM reviews WHERE product_id = ? AND status = ? GROUP BY rating",
+            )
+            .bind(product_id)
+            .bind(ReviewStatus::Approved.as_str())
+            .fetch_all(&self.pool)
+            .await?;
+            Ok(rows.into_iter().map(|(rating, count)| (rating, count as u32)).collect())
+        })
+    }
+
+    fn follow_up<'a>(&'a self, id: u64, content: &'a str, photos: &'a [String]) -> BoxFuture<'a, Result<bool, sqlx::Error>> {
+        Box::pin(async move {
+            let result = sqlx::query(
+                "UPDATE reviews SET follow_up = ?, follow_up_photos = ?, followed_up_at = NOW(), status = ?, \
+                 has_photos = has_photos OR ? WHERE id = ? AND follow_up IS NULL",
+            )
+            .bind(content)
+            .bind(Json(photos))
+            .bind(ReviewStatus::Pending.as_str())
+            .bind(!photos.is_empty())
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+            Ok(result.rows_affected() == 1)
+        })
+    }
+
+    fn set_status(&self, id: u64, status: ReviewStatus) -> BoxFuture<'_, Result<(), sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query("UPDATE reviews SET status = ? WHERE id = ?")
+                .bind(status.as_str())
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn reply<'a>(&'a self, id: u64, reply: &'a str) -> BoxFuture<'a, Result<(), sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query("UPDATE reviews SET reply = ?, replied_at = NOW() WHERE id = ?")
+                .bind(reply)
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn refresh_stats(&self, product_id: u64) -> BoxFuture<'_, Result<(), sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query(
+                "UPDATE products p JOIN ( \
+                     SELECT COUNT(*) AS review_count, COALESCE(SUM(rating), 0) AS rating_sum, \
+                            COALESCE(SUM(has_photos), 0) AS photo_review_count \
+                     FROM reviews WHERE product_id = ? AND status = ? \
+                 ) r SET p.review_count = r.review_count, p.rating_sum = r.rating_sum, \
+                         p.photo_review_count = r.photo_review_count \
+                 WHERE p.id = ?",
+            )
+            .bind(product_id)
+            .bind(ReviewStatus::Approved.as_str())
+            .bind(product_id)
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn stats(&self, product_id: u64) -> BoxFuture<'_, Result<Option<ReviewStats>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, ReviewStats>("SELECT review_count, rating_sum, photo_review_count FROM products WHERE id = ?")
+                .bind(product_id)
+                .fetch_optional(&self.pool)
+                .await
+        })
+    }
+}
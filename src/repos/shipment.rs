@@ -0,0 +1,51 @@
+use sqlx::{MySql, Pool};
+use std::sync::Arc;
+use chrono::{DateTime, Local};
+use crate::domain::BoxFuture;
+use crate::domain::shipment::ShipmentRepo;
+use crate::models::shipment::Shipment;
+
+pub struct ShipmentRepository {
+    pool: Pool<MySql>,
+}
+
+impl ShipmentRepository {
+    pub fn new(pool: Pool<MySql>) -> Arc<Self> {
+        Arc::new(Self { pool })
+    }
+}
+
+impl ShipmentRepo for ShipmentRepository {
+    fn upsert<'a>(
+        &'a self,
+        order_id: u64,
+        carrier: &'a str,
+        tracking_no: &'a str,
+        admin_id: u32,
+        shipped_at: DateTime<Local>,
+    ) -> BoxFuture<'a, Result<(), sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query(
+                "INSERT INTO shipments (order_id, carrier, tracking_no, admin_id, shipped_at) VALUES (?, ?, ?, ?, ?) \
+                 ON DUPLICATE KEY UPDATE carrier = VALUES(carrier), tracking_no = VALUES(tracking_no), admin_id = VALUES(admin_id)",
+            )
+            .bind(order_id)
+            .bind(carrier)
+            .bind(tracking_no)
+            .bind(admin_id)
+            .bind(shipped_at)
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn find_by_order(&self, order_id: u64) -> BoxFuture<'_, Result<Option<Shipment>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, Shipment>("SELECT * FROM shipments WHERE order_id = ?")
+                .bind(order_id)
+                .fetch_optional(&self.pool)
+                .await
+        })
+    }
+}
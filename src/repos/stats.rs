@@ -0,0 +1,151 @@
+use chrono::{DateTime, Local, NaiveDate};
+use sqlx::{MySql, Pool};
+use std::sync::Arc;
+use crate::domain::BoxFuture;
+use crate::domain::stats::StatsRepo;
+use crate::models::payment::PAYMENT_SUCCEEDED;
+use crate::models::refund::RefundStatus;
+use crate::models::stats::{DailyStats, Granularity, ProductStats};
+
+pub struct StatsRepository {
+    pool: Pool<MySql>,
+}
+
+impl StatsRepository {
+    pub fn new(pool: Pool<MySql>) -> Arc<Self> {
+        Arc::new(Self { pool })
+    }
+}
+
+/// 日期所在周期第一天的 SQL 表达式，与 `Granularity::period_start` 一致
+fn period_expr(group_by: Granularity) -> &'static str {
+    match group_by {
+        Granularity::Day => "stat_date",
+        Granularity::Week => "DATE_SUB(stat_date, INTERVAL WEEKDAY(stat_date) DAY)",
+        Granularity::Month => "DATE_SUB(stat_date, INTERVAL DAYOFMONTH(stat_date) - 1 DAY)",
+    }
+}
+
+impl StatsRepo for StatsRepository {
+    fn rollup(&self, stat_date: NaiveDate, start: DateTime<Local>, end: DateTime<Local>) -> BoxFuture<'_, Result<DailyStats, sqlx::Error>> {
+        Box::pin(async move {
+            let mut tx = self.pool.begin().await?;
+            for table in ["stats_daily", "stats_daily_payers", "stats_daily_products"] {
+                sqlx::query(&format!("DELETE FROM {} WHERE stat_date = ?", table))
+                    .bind(stat_date)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+
+            sqlx::query(
+                "INSERT INTO stats_daily \
+                 (stat_date, order_count, paid_order_count, gmv, paying_users, refund_count, refund_amount) \
+                 SELECT ?, (SELECT COUNT(*) FROM orders WHERE created_at >= ? AND created_at < ?), \
+                 p.paid_count, p.gmv, p.payers, r.refund_count, r.refund_amount \
+                 FROM (SELECT COUNT(*) AS paid_count, COALESCE(SUM(amount), 0) AS gmv, COUNT(DISTINCT user_id) AS payers \
+                       FROM payments WHERE status = ? AND paid_at >= ? AND paid_at < ?) p, \
+                      (SELECT COUNT(*) AS refund_count, COALESCE(SUM(amount), 0) AS refund_amount \
+                       FROM refunds WHERE status = ? AND refunded_at >= ? AND refunded_at < ?) r",
+            )
+            .bind(stat_date)
+            .bind(start)
+            .bind(end)
+            .bind(PAYMENT_SUCCEEDED)
+            .bind(start)
+            .bind(end)
+            .bind(RefundStatus::Succeeded.as_str())
+            .bind(start)
+            .bind(end)
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                "INSERT INTO stats_daily_payers (stat_date, user_id) \
+                 SELECT DISTINCT ?, user_id FROM payments WHERE status = ? AND paid_at >= ? AND paid_at < ?",
+            )
+            .bind(stat_date)
+            .bind(PAYMENT_SUCCEEDED)
+            .bind(start)
+            .bind(end)
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                "INSERT INTO stats_daily_products (stat_date, product_id, product_name, quantity, gmv) \
+                 SELECT ?, i.product_id, MAX(i.product_name), SUM(i.quantity), SUM(i.payable_amount) \
+                 FROM payments p JOIN order_items i ON i.order_id = p.order_id \
+                 WHERE p.status = ? AND p.paid_at >= ? AND p.paid_at < ? GROUP BY i.product_id",
+            )
+            .bind(stat_date)
+            .bind(PAYMENT_SUCCEEDED)
+            .bind(start)
+            .bind(end)
+            .execute(&mut *tx)
+            .await?;
+
+            let stats = sqlx::query_as::<_, DailyStats>(
+                "SELECT stat_date, order_count, paid_order_count, gmv, paying_users, refund_count, refund_amount \
+                 FROM stats_daily WHERE stat_date = ?",
+            )
+            .bind(stat_date)
+            .fetch_one(&mut *tx)
+            .await?;
+            tx.commit().await?;
+            Ok(stats)
+        })
+    }
+
+    fn list_daily(&self, from: NaiveDate, to: NaiveDate) -> BoxFuture<'_, Result<Vec<DailyStats>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, DailyStats>(
+                "SELECT stat_date, order_count, paid_order_count, gmv, paying_users, refund_count, refund_amount \
+                 FROM stats_daily WHERE stat_date BETWEEN ? AND ? ORDER BY stat_date",
+            )
+            .bind(from)
+            .bind(to)
+            .fetch_all(&self.pool)
+            .await
+        })
+    }
+
+    fn payers_by_period(&self, from: NaiveDate, to: NaiveDate, group_by: Granularity) -> BoxFuture<'_, Result<Vec<(NaiveDate, i64)>, sqlx::Error>> {
+        Box::pin(async move {
+            let period = period_expr(group_by);
+            sqlx::query_as(&format!(
+                "SELECT {period} AS period, COUNT(DISTINCT user_id) FROM stats_daily_payers \
+                 WHERE stat_date BETWEEN ? AND ? GROUP BY period",
+            ))
+            .bind(from)
+            .bind(to)
+            .fetch_all(&self.pool)
+            .await
+        })
+    }
+
+    fn count_payers(&self, from: NaiveDate, to: NaiveDate) -> BoxFuture<'_, Result<i64, sqlx::Error>> {
+        Box::pin(async move {
+            let (count,): (i64,) = sqlx::query_as("SELECT COUNT(DISTINCT user_id) FROM stats_daily_payers WHERE stat_date BETWEEN ? AND ?")
+                .bind(from)
+                .bind(to)
+                .fetch_one(&self.pool)
+                .await?;
+            Ok(count)
+        })
+    }
+
+    fn top_products(&self, from: NaiveDate, to: NaiveDate, limit: u32) -> BoxFuture<'_, Result<Vec<ProductStats>, sqlx::Error>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, ProductStats>(
+                "SELECT product_id, MAX(product_name) AS product_name, \
+                 CAST(SUM(quantity) AS SIGNED) AS quantity, CAST(SUM(gmv) AS SIGNED) AS gmv \
+                 FROM stats_daily_products WHERE stat_date BETWEEN ? AND ? \
+                 GROUP BY product_id ORDER BY SUM(gmv) DESC, product_id LIMIT ?",
+            )
+            .bind(from)
+            .bind(to)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+        })
+    }
+}
@@ -0,0 +1,202 @@
+use std::sync::Arc;
+use fred::clients::Pool as RedisPool;
+use fred::interfaces::{KeysInterface, ListInterface, LuaInterface};
+use fred::types::SetOptions;
+use fred::types::lists::LMoveDirection;
+use crate::domain::BoxFuture;
+use crate::domain::inventory::{CounterRejection, StockCounter};
+use crate::models::inventory::StockMovement;
+
+const MOVEMENTS_KEY: &str = "stock:movements";
+const PROCESSING_KEY: &str = "stock:movements:processing";
+
+/// KEYS: 计数器, 预占表, 用户已购表, 流水队列
+/// ARGV: 数量, 订单号, 用户 ID, 限购(0 表示不限), 流水报文
+/// 返回剩余库存；-1 未初始化，-2 超出限购，-3 库存不足
+const RESERVE_SCRIPT: &str = r#"
+local stock = redis.call('GET', KEYS[1])
+if not stock then return -1 end
+if redis.call('HEXISTS', KEYS[2], ARGV[2]) == 1 then return tonumber(stock) end
+local qty = tonumber(ARGV[1])
+local limit = tonumber(ARGV[4])
+if limit > 0 then
+  local bought = tonumber(redis.call('HGET', KEYS[3], ARGV[3]) or '0')
+  if bought + qty > limit then return -2 end
+end
+if tonumber(stock) < qty then return -3 end
+redis.call('HSET', KEYS[2], ARGV[2], ARGV[5])
+redis.call('HINCRBY', KEYS[3], ARGV[3], qty)
+redis.call('RPUSH', KEYS[4], ARGV[5])
+return redis.call('DECRBY', KEYS[1], qty)
+"#;
+
+/// KEYS: 计数器, 预占表, 用户已购表, 流水队列
+/// ARGV: 订单号
+/// 返回回补数量；-1 表示没有对应预占
+const RELEASE_SCRIPT: &str = r#"
+local hold = redis.call('HGET', KEYS[2], ARGV[1])
+if not hold then return -1 end
+local m = cjson.decode(hold)
+local qty = -m.delta
+redis.call('HDEL', KEYS[2], ARGV[1])
+redis.call('HINCRBY', KEYS[3], tostring(m.user_id), -qty)
+redis.call('INCRBY', KEYS[1], qty)
+m.kind = 'release'
+m.delta = qty
+redis.call('RPUSH', KEYS[4], cjson.encode(m))
+return qty
+"#;
+
+/// KEYS: 计数器, 入库表, 流水队列
+/// ARGV: 售后单号, 数量, 流水报文
+/// 返回回补后的库存；-1 未初始化，-2 已入库过
+const RESTOCK_SCRIPT: &str = r#"
+if not redis.call('GET', KEYS[1]) then return -1 end
+if redis.call('HSETNX', KEYS[2], ARGV[1], ARGV[3]) == 0 then return -2 end
+redis.call('RPUSH', KEYS[3], ARGV[3])
+return redis.call('INCRBY', KEYS[1], ARGV[2])
+"#;
+
+/// KEYS: 处理中队列, 流水队列
+/// ARGV: 流水报文
+/// 原子地把流水移回待处理队列队首，返回移回的条数
+const NACK_SCRIPT: &str = r#"
+local removed = redis.call('LREM', KEYS[1], 1, ARGV[1])
+if removed > 0 then redis.call('LPUSH', KEYS[2], ARGV[1]) end
+return removed
+"#;
+
+pub struct RedisStockCounter {
+    pool: RedisPool,
+}
+
+impl RedisStockCounter {
+    pub fn new(pool: RedisPool) -> Arc<Self> {
+        Arc::new(Self { pool })
+    }
+
+    fn keys(sku_id: u64) -> Vec<String> {
+        vec![
+            format!("stock:{}", sku_id),
+            format!("stock:{}:holds", sku_id),
+            format!("stock:{}:users", sku_id),
+            MOVEMENTS_KEY.to_string(),
+        ]
+    }
+}
+
+impl StockCounter for RedisStockCounter {
+    fn seed(&self, sku_id: u64, stock: i64) -> BoxFuture<'_, Result<bool, fred::error::Error>> {
+        Box::pin(async move {
+            let reply: Option<String> = self
+                .pool
+                .set(format!("stock:{}", sku_id), stock, None, Some(SetOptions::NX), false)
+                .await?;
+            Ok(reply.is_some())
+        })
+    }
+
+    fn get(&self, sku_id: u64) -> BoxFuture<'_, Result<Option<i64>, fred::error::Error>> {
+        Box::pin(async move { self.pool.get(format!("stock:{}", sku_id)).await })
+    }
+
+    fn reserve<'a>(
+        &'a self,
+        movement: &'a StockMovement,
+        per_user_limit: Option<u32>,
+    ) -> BoxFuture<'a, Result<Result<i64, CounterRejection>, fred::error::Error>> {
+        Box::pin(async move {
+            let payload = serde_json::to_string(movement)
+                .map_err(|e| fred::error::Error::new(fred::error::ErrorKind::Parse, e.to_string()))?;
+            let args = vec![
+                (-movement.delta).to_string(),
+                movement.order_ref.clone(),
+                movement.user_id.to_string(),
+                per_user_limit.unwrap_or(0).to_string(),
+                payload,
+            ];
+            let code: i64 = self.pool.eval(RESERVE_SCRIPT, Self::keys(movement.sku_id), args).await?;
+            Ok(match code {
+                -1 => Err(CounterRejection::NotInitialized),
+                -2 => Err(CounterRejection::LimitExceeded),
+                -3 => Err(CounterRejection::OutOfStock),
+                remaining => Ok(remaining),
+            })
+        })
+    }
+
+    fn release<'a>(&'a self, sku_id: u64, order_ref: &'a str) -> BoxFuture<'a, Result<Option<i64>, fred::error::Error>> {
+        Box::pin(async move {
+            let qty: i64 = self.pool.eval(RELEASE_SCRIPT, Self::keys(sku_id), vec![order_ref.to_string()]).await?;
+            Ok((qty >= 0).then_some(qty))
+        })
+    }
+
+    fn restock<'a>(&'a self, movement: &'a StockMovement) -> BoxFuture<'a, Result<Result<bool, CounterRejection>, fred::error::Error>> {
+        Box::pin(async move {
+            let payload = serde_json::to_string(movement)
+                .map_err(|e| fred::error::Error::new(fred::error::ErrorKind::Parse, e.to_string()))?;
+            let keys = vec![
+                format!("stock:{}", movement.sku_id),
+                format!("stock:{}:restocks", movement.sku_id),
+                MOVEMENTS_KEY.to_string(),
+            ];
+            let args = vec![movement.order_ref.clone(), movement.delta.to_string(), payload];
+            let code: i64 = self.pool.eval(RESTOCK_SCRIPT, keys, args).await?;
+            Ok(match code {
+                -1 => Err(CounterRejection::NotInitialized),
+                -2 => Ok(false),
+                _ => Ok(true),
+            })
+        })
+    }
+
+    fn claim_movement(&self) -> BoxFuture<'_, Result<Option<String>, fred::error::Error>> {
+        Box::pin(async move {
+            self.pool
+                .lmove(MOVEMENTS_KEY, PROCESSING_KEY, LMoveDirection::Left, LMoveDirection::Right)
+                .await
+        })
+    }
+
+    fn ack_movement<'a>(&'a self, raw: &'a str) -> BoxFuture<'a, Result<(), fred::error::Error>> {
+        Box::pin(async move {
+            let _: i64 = self.pool.lrem(PROCESSING_KEY, 1, raw).await?;
+            Ok(())
+        })
+    }
+
+    fn nack_movement<'a>(&'a self, raw: &'a str) -> BoxFuture<'a, Result<(), fred::error::Error>> {
+        Box::pin(async move {
+            let _: i64 = self
+                .pool
+                .eval(NACK_SCRIPT, vec![PROCESSING_KEY, MOVEMENTS_KEY], vec![raw])
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn requeue_unacked(&self) -> BoxFuture<'_, Result<u64, fred::error::Error>> {
+        Box::pin(async move {
+            let mut moved = 0;
+            loop {
+                let item: Option<String> = self
+                    .pool
+                    .lmove(PROCESSING_KEY, MOVEMENTS_KEY, LMoveDirection::Right, LMoveDirection::Left)
+                    .await?;
+                if item.is_none() {
+                    return Ok(moved);
+                }
+                moved += 1;
+            }
+        })
+    }
+
+    fn pending(&self) -> BoxFuture<'_, Result<u64, fred::error::Error>> {
+        Box::pin(async move {
+            let queued: u64 = self.pool.llen(MOVEMENTS_KEY).await?;
+            let processing: u64 = self.pool.llen(PROCESSING_KEY).await?;
+            Ok(queued + processing)
+        })
+    }
+}
@@ -0,0 +1,68 @@
+use std::sync::Arc;
+use chrono::Local;
+use fred::clients::Pool as RedisPool;
+use fred::interfaces::KeysInterface;
+use fred::types::Expiration;
+use crate::domain::BoxFuture;
+use crate::domain::payment::GatewayError;
+use crate::domain::shipment::{TrackingCache, TrackingProvider};
+use crate::models::shipment::{TrackingEvent, TrackingInfo, TrackingState};
+
+pub struct RedisTrackingCache {
+    pool: RedisPool,
+}
+
+impl RedisTrackingCache {
+    pub fn new(pool: RedisPool) -> Arc<Self> {
+        Arc::new(Self { pool })
+    }
+}
+
+impl TrackingCache for RedisTrackingCache {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<TrackingInfo>, fred::error::Error>> {
+        Box::pin(async move {
+            let cached: Option<String> = self.pool.get(key).await?;
+            // 结构变化后旧缓存解析失败，当作未命中
+            Ok(cached.and_then(|json| serde_json::from_str(&json).ok()))
+        })
+    }
+
+    fn set<'a>(&'a self, key: &'a str, info: &'a TrackingInfo, ttl_secs: u64) -> BoxFuture<'a, Result<(), fred::error::Error>> {
+        Box::pin(async move {
+            let json = serde_json::to_string(info)
+                .map_err(|e| fred::error::Error::new(fred::error::ErrorKind::Parse, e.to_string()))?;
+            let _: Option<String> = self
+                .pool
+                .set(key, json, Some(Expiration::EX(ttl_secs.max(1) as i64)), None, false)
+                .await?;
+            Ok(())
+        })
+    }
+}
+
+/// 不调用外部接口，按运单号返回固定的两条轨迹；用于测试和本地开发
+#[derive(Default)]
+pub struct FakeTrackingProvider;
+
+impl FakeTrackingProvider {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self)
+    }
+}
+
+impl TrackingProvider for FakeTrackingProvider {
+    fn query<'a>(&'a self, carrier: &'a str, tracking_no: &'a str, _phone: &'a str) -> BoxFuture<'a, Result<TrackingInfo, GatewayError>> {
+        Box::pin(async move {
+            Ok(TrackingInfo {
+                carrier: carrier.to_string(),
+                tracking_no: tracking_no.to_string(),
+                state: TrackingState::InTransit,
+                events: vec![
+                    TrackingEvent { time: "2024-01-02 08:00:00".to_string(), description: "快件已到达转运中心".to_string() },
+                    TrackingEvent { time: "2024-01-01 18:00:00".to_string(), description: "快递员已揽收".to_string() },
+                ],
+                queried_at: Local::now(),
+            })
+        })
+    }
+}
@@ -0,0 +1,105 @@
+//! 小程序登录凭证校验接口（jscode2session），只取 openid，不保存 session_key。
+
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+use crate::domain::BoxFuture;
+use crate::domain::payment::GatewayError;
+use crate::domain::users::WechatAuth;
+use wx_shop::NotificationSettings;
+
+const CODE2SESSION_PATH: &str = "/sns/jscode2session";
+
+pub struct WechatAuthClient {
+    http: reqwest::Client,
+    app_id: String,
+    app_secret: String,
+    api_base: String,
+}
+
+#[derive(Deserialize)]
+struct SessionResponse {
+    #[serde(default)]
+    openid: String,
+    #[serde(default)]
+    errcode: i64,
+    #[serde(default)]
+    errmsg: String,
+}
+
+impl WechatAuthClient {
+    /// AppSecret 和接口域名与订阅消息共用同一个小程序的配置
+    pub fn new(app_id: &str, settings: &NotificationSettings) -> Result<Arc<Self>, String> {
+        let http = reqwest::Client::builder()
+            .user_agent(concat!("wx-shop/", env!("CARGO_PKG_VERSION")))
+            .timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Arc::new(Self {
+            http,
+            app_id: app_id.to_string(),
+            app_secret: settings.app_secret.clone(),
+            api_base: settings.api_base.clone(),
+        }))
+    }
+}
+
+impl WechatAuth for WechatAuthClient {
+    fn code_to_openid<'a>(&'a self, code: &'a str) -> BoxFuture<'a, Result<String, GatewayError>> {
+        Box::pin(async move {
+            let response = self
+                .http
+                .get(format!("{}{}", self.api_base, CODE2SESSION_PATH))
+                .query(&[
+                    ("appid", self.app_id.as_str()),
+                    ("secret", self.app_secret.as_str()),
+                    ("js_code", code),
+                    ("grant_type", "authorization_code"),
+                ])
+                .send()
+                .await?;
+            let status = response.status();
+            if !status.is_success() {
+                let body = response.text().await.unwrap_or_default();
+                return Err(GatewayError::Api { status: status.as_u16(), body });
+            }
+            let body: SessionResponse = response.json().await?;
+            if body.errcode != 0 || body.openid.is_empty() {
+                return Err(GatewayError::Api { status: status.as_u16(), body: format!("{} {}", body.errcode, body.errmsg) });
+            }
+            Ok(body.openid)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_code_to_openid() {
+        let server = MockServer::start().await;
+        let settings = NotificationSettings { app_secret: "SECRET".into(), api_base: server.uri(), ..NotificationSettings::default() };
+        let client = WechatAuthClient::new("wxapp", &settings).unwrap();
+        Mock::given(method("GET"))
+            .and(path(CODE2SESSION_PATH))
+            .and(query_param("appid", "wxapp"))
+            .and(query_param("secret", "SECRET"))
+            .and(query_param("js_code", "good"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "openid": "OPENID", "session_key": "KEY" })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(CODE2SESSION_PATH))
+            .and(query_param("js_code", "used"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "errcode": 40163, "errmsg": "code been used" })))
+            .mount(&server)
+            .await;
+
+        assert_eq!(client.code_to_openid("good").await.unwrap(), "OPENID");
+        assert!(matches!(client.code_to_openid("used").await, Err(GatewayError::Api { .. })));
+    }
+}
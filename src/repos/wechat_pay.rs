@@ -0,0 +1,648 @@
+//! 微信支付 APIv3 客户端。请求使用商户私钥 SHA256-RSA2048 签名，
+//! 回调通知用平台证书验签、APIv3 密钥解密；接口地址来自配置，测试时指向本地 mock 服务。
+
+use aes_gcm::aead::{Aead, Payload};
+use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chrono::NaiveDate;
+use rand::Rng;
+use rand::distr::Alphanumeric;
+use rsa::{RsaPrivateKey, RsaPublicKey};
+use rsa::pkcs1v15::{Signature, SigningKey, VerifyingKey};
+use rsa::pkcs8::{DecodePrivateKey, DecodePublicKey};
+use rsa::signature::{SignatureEncoding, Signer, Verifier};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::json;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use x509_cert::Certificate;
+use x509_cert::der::{DecodePem, Encode};
+use crate::domain::BoxFuture;
+use crate::domain::payment::{
+    GatewayError, NotifyHeaders, PayGateway, PrepayRequest, RefundRequest, RefundResult, TradeBill, Transaction,
+};
+use crate::models::payment::JsapiPayParams;
+use wx_shop::WechatPaySettings;
+
+const JSAPI_PREPAY_PATH: &str = "/v3/pay/transactions/jsapi";
+const QUERY_BY_OUT_TRADE_NO_PATH: &str = "/v3/pay/transactions/out-trade-no";
+const CERTIFICATES_PATH: &str = "/v3/certificates";
+const REFUNDS_PATH: &str = "/v3/refund/domestic/refunds";
+const TRADE_BILL_PATH: &str = "/v3/bill/tradebill";
+/// 通知时间戳与本机时间允许的偏差（秒），超出视为重放
+const NOTIFY_MAX_SKEW_SECS: i64 = 300;
+/// 遇到未知证书序列号时，两次下载平台证书的最小间隔
+const CERT_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+pub struct WechatPayClient {
+    http: reqwest::Client,
+    settings: WechatPaySettings,
+    signing_key: SigningKey<Sha256>,
+    platform_certs: RwLock<PlatformCerts>,
+}
+
+/// 平台证书缓存，按序列号索引公钥
+#[derive(Default)]
+struct PlatformCerts {
+    keys: HashMap<String, VerifyingKey<Sha256>>,
+    refreshed_at: Option<Instant>,
+}
+
+#[derive(Deserialize)]
+struct PrepayResponse {
+    prepay_id: String,
+}
+
+/// AEAD_AES_256_GCM 加密的报文
+#[derive(Deserialize)]
+struct EncryptedResource {
+    ciphertext: String,
+    nonce: String,
+    associated_data: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Notification {
+    resource: EncryptedResource,
+}
+
+#[derive(Deserialize)]
+struct CertificatesResponse {
+    data: Vec<PlatformCertificate>,
+}
+
+#[derive(Deserialize)]
+struct BillResponse {
+    hash_value: String,
+    download_url: String,
+}
+
+#[derive(Deserialize)]
+struct PlatformCertificate {
+    serial_no: String,
+    encrypt_certificate: EncryptedResource,
+}
+
+impl WechatPayClient {
+    /// 从 `private_key_path` 读取商户私钥（PKCS#8 PEM）
+    pub fn new(settings: &WechatPaySettings) -> Result<Arc<Self>, String> {
+        let pem = std::fs::read_to_string(&settings.private_key_path)
+            .map_err(|e| format!("read {}: {}", settings.private_key_path, e))?;
+        Self::with_private_key_pem(settings, &pem)
+    }
+
+    pub fn with_private_key_pem(settings: &WechatPaySettings, pem: &str) -> Result<Arc<Self>, String> {
+        let key = RsaPrivateKey::from_pkcs8_pem(pem).map_err(|e| format!("invalid merchant private key: {}", e))?;
+        let http = reqwest::Client::builder()
+            .user_agent(concat!("wx-shop/", env!("CARGO_PKG_VERSION")))
+            .timeout(std::time::Duration::from_secs(10))
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Arc::new(Self {
+            http,
+            settings: settings.clone(),
+            signing_key: SigningKey::<Sha256>::new(key),
+            platform_certs: RwLock::new(PlatformCerts::default()),
+        }))
+    }
+
+    fn sign(&self, message: &str) -> Result<String, GatewayError> {
+        let signature = self
+            .signing_key
+            .try_sign(message.as_bytes())
+            .map_err(|e| GatewayError::Sign(e.to_string()))?;
+        Ok(BASE64.encode(signature.to_bytes()))
+    }
+
+    /// 构造 `Authorization` 头，签名串为「方法\nURL\n时间戳\n随机串\n报文主体\n」
+    fn authorization(&self, method: &str, url_path: &str, body: &str) -> Result<String, GatewayError> {
+        let timestamp = chrono::Utc::now().timestamp();
+        let nonce = nonce_str();
+        let signature = self.sign(&format!("{}\n{}\n{}\n{}\n{}\n", method, url_path, timestamp, nonce, body))?;
+        Ok(format!(
+            "WECHATPAY2-SHA256-RSA2048 mchid=\"{}\",nonce_str=\"{}\",signature=\"{}\",timestamp=\"{}\",serial_no=\"{}\"",
+            self.settings.mch_id, nonce, signature, timestamp, self.settings.serial_no
+        ))
+    }
+
+    async fn post_json<T: DeserializeOwned>(&self, url_path: &str, body: String) -> Result<T, GatewayError> {
+        let response = self
+            .http
+            .post(format!("{}{}", self.settings.api_base, url_path))
+            .header(reqwest::header::AUTHORIZATION, self.authorization("POST", url_path, &body)?)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(GatewayError::Api { status: status.as_u16(), body: response.text().await.unwrap_or_default() });
+        }
+        Ok(response.json::<T>().await?)
+    }
+
+    /// 校验通知的时间戳和平台签名，返回解密后的 resource 明文
+    async fn verified_resource(&self, headers: &NotifyHeaders, body: &str) -> Result<Vec<u8>, GatewayError> {
+        let timestamp: i64 = headers
+            .timestamp
+            .parse()
+            .map_err(|_| GatewayError::Verify("invalid timestamp".to_string()))?;
+        if (chrono::Utc::now().timestamp() - timestamp).abs() > NOTIFY_MAX_SKEW_SECS {
+            return Err(GatewayError::Verify("notification expired".to_string()));
+        }
+
+        // 验签串为「时间戳\n随机串\n报文主体\n」
+        let key = self.platform_key(&headers.serial).await?;
+        let signature = BASE64
+            .decode(&headers.signature)
+            .ok()
+            .and_then(|bytes| Signature::try_from(bytes.as_slice()).ok())
+            .ok_or_else(|| GatewayError::Verify("malformed signature".to_string()))?;
+        key.verify(format!("{}\n{}\n{}\n", headers.timestamp, headers.nonce, body).as_bytes(), &signature)
+            .map_err(|_| GatewayError::Verify("signature mismatch".to_string()))?;
+
+        let notification: Notification = serde_json::from_str(body)
+            .map_err(|e| GatewayError::Verify(format!("invalid notification: {}", e)))?;
+        self.decrypt(&notification.resource)
+    }
+
+    /// 用 APIv3 密钥解密回调报文或平台证书
+    fn decrypt(&self, resource: &EncryptedResource) -> Result<Vec<u8>, GatewayError> {
+        let cipher = Aes256Gcm::new_from_slice(self.settings.api_v3_key.as_bytes())
+            .map_err(|_| GatewayError::Verify("api_v3_key must be 32 bytes".to_string()))?;
+        if resource.nonce.len() != 12 {
+            return Err(GatewayError::Verify("invalid nonce".to_string()));
+        }
+        let ciphertext = BASE64
+            .decode(&resource.ciphertext)
+            .map_err(|e| GatewayError::Verify(format!("invalid ciphertext: {}", e)))?;
+        let payload = Payload {
+            msg: &ciphertext,
+            aad: resource.associated_data.as_deref().unwrap_or_default().as_bytes(),
+        };
+        cipher
+            .decrypt(Nonce::from_slice(resource.nonce.as_bytes()), payload)
+            .map_err(|_| GatewayError::Verify("decrypt failed".to_string()))
+    }
+
+    /// 签名的 GET 请求；`url_path` 是参与签名的路径和查询串
+    async fn get(&self, url: &str, url_path: &str) -> Result<reqwest::Response, GatewayError> {
+        let response = self
+            .http
+            .get(url)
+            .header(reqwest::header::AUTHORIZATION, self.authorization("GET", url_path, "")?)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(GatewayError::Api { status: status.as_u16(), body: response.text().await.unwrap_or_default() });
+        }
+        Ok(response)
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, url_path: &str) -> Result<T, GatewayError> {
+        let url = format!("{}{}", self.settings.api_base, url_path);
+        Ok(self.get(&url, url_path).await?.json::<T>().await?)
+    }
+
+    /// 下载并解密当前有效的平台证书
+    async fn download_certificates(&self) -> Result<HashMap<String, VerifyingKey<Sha256>>, GatewayError> {
+        let response: CertificatesResponse = self.get_json(CERTIFICATES_PATH).await?;
+        let mut keys = HashMap::new();
+        for cert in response.data {
+            let pem = self.decrypt(&cert.encrypt_certificate)?;
+            keys.insert(cert.serial_no, verifying_key_from_pem(&pem)?);
+        }
+        Ok(keys)
+    }
+
+    /// 按序列号取平台公钥；缓存未命中时重新下载（平台证书轮换），但限制下载频率
+    async fn platform_key(&self, serial: &str) -> Result<VerifyingKey<Sha256>, GatewayError> {
+        {
+            let certs = self.platform_certs.read().unwrap();
+            if let Some(key) = certs.keys.get(serial) {
+                return Ok(key.clone());
+            }
+            if certs.refreshed_at.is_some_and(|at| at.elapsed() < CERT_REFRESH_INTERVAL) {
+                return Err(GatewayError::Verify(format!("unknown platform certificate {}", serial)));
+            }
+        }
+
+        let keys = self.download_certificates().await?;
+        let mut certs = self.platform_certs.write().unwrap();
+        certs.keys.extend(keys);
+        certs.refreshed_at = Some(Instant::now());
+        certs
+            .keys
+            .get(serial)
+            .cloned()
+            .ok_or_else(|| GatewayError::Verify(format!("unknown platform certificate {}", serial)))
+    }
+}
+
+impl PayGateway for WechatPayClient {
+    fn jsapi_prepay<'a>(&'a self, request: &'a PrepayRequest) -> BoxFuture<'a, Result<String, GatewayError>> {
+        Box::pin(async move {
+            let mut body = json!({
+                "appid": self.settings.app_id,
+                "mchid": self.settings.mch_id,
+                "description": request.description,
+                "out_trade_no": request.out_trade_no,
+                "notify_url": self.settings.notify_url,
+                "amount": { "total": request.amount, "currency": "CNY" },
+                "payer": { "openid": request.payer_openid },
+            });
+            if let Some(time_expire) = &request.time_expire {
+                body["time_expire"] = json!(time_expire);
+            }
+            let response: PrepayResponse = self.post_json(JSAPI_PREPAY_PATH, body.to_string()).await?;
+            Ok(response.prepay_id)
+        })
+    }
+
+    fn jsapi_pay_params(&self, prepay_id: &str) -> Result<JsapiPayParams, GatewayError> {
+        let time_stamp = chrono::Utc::now().timestamp().to_string();
+        let nonce_str = nonce_str();
+        let package = format!("prepay_id={}", prepay_id);
+        let pay_sign = self.sign(&format!("{}\n{}\n{}\n{}\n", self.settings.app_id, time_stamp, nonce_str, package))?;
+        Ok(JsapiPayParams {
+            app_id: self.settings.app_id.clone(),
+            time_stamp,
+            nonce_str,
+            package,
+            sign_type: "RSA".to_string(),
+            pay_sign,
+        })
+    }
+
+    fn parse_notify<'a>(&'a self, headers: &'a NotifyHeaders, body: &'a str) -> BoxFuture<'a, Result<Transaction, GatewayError>> {
+        Box::pin(async move {
+            let plaintext = self.verified_resource(headers, body).await?;
+            serde_json::from_slice(&plaintext).map_err(|e| GatewayError::Verify(format!("invalid transaction: {}", e)))
+        })
+    }
+
+    fn query_order<'a>(&'a self, out_trade_no: &'a str) -> BoxFuture<'a, Result<Option<Transaction>, GatewayError>> {
+        Box::pin(async move {
+            let url_path = format!("{}/{}?mchid={}", QUERY_BY_OUT_TRADE_NO_PATH, out_trade_no, self.settings.mch_id);
+            match self.get_json(&url_path).await {
+                Ok(transaction) => Ok(Some(transaction)),
+                Err(GatewayError::Api { status: 404, body }) if body.contains("ORDER_NOT_EXIST") => Ok(None),
+                Err(e) => Err(e),
+            }
+        })
+    }
+
+    fn refund<'a>(&'a self, request: &'a RefundRequest) -> BoxFuture<'a, Result<RefundResult, GatewayError>> {
+        Box::pin(async move {
+            let mut body = json!({
+                "out_trade_no": request.out_trade_no,
+                "out_refund_no": request.out_refund_no,
+                "reason": request.reason,
+                "amount": { "refund": request.refund, "total": request.total, "currency": "CNY" },
+            });
+            if !self.settings.refund_notify_url.is_empty() {
+                body["notify_url"] = json!(self.settings.refund_notify_url);
+            }
+            self.post_json(REFUNDS_PATH, body.to_string()).await
+        })
+    }
+
+    fn parse_refund_notify<'a>(&'a self, headers: &'a NotifyHeaders, body: &'a str) -> BoxFuture<'a, Result<RefundResult, GatewayError>> {
+        Box::pin(async move {
+            let plaintext = self.verified_resource(headers, body).await?;
+            serde_json::from_slice(&plaintext).map_err(|e| GatewayError::Verify(format!("invalid refund: {}", e)))
+        })
+    }
+
+    fn download_trade_bill(&self, bill_date: NaiveDate) -> BoxFuture<'_, Result<Option<TradeBill>, GatewayError>> {
+        Box::pin(async move {
+            let url_path = format!("{}?bill_date={}&bill_type=ALL&tar_type=GZIP", TRADE_BILL_PATH, bill_date.format("%Y-%m-%d"));
+            let bill: BillResponse = match self.get_json(&url_path).await {
+                Ok(bill) => bill,
+                // 当天没有交易时微信不生成账单
+                Err(GatewayError::Api { body, .. }) if body.contains("NO_STATEMENT_EXIST") => return Ok(None),
+                Err(e) => return Err(e),
+            };
+
+            // 下载地址同样需要签名，签名串使用其路径和查询串
+            let download_url = reqwest::Url::parse(&bill.download_url)
+                .map_err(|e| GatewayError::Verify(format!("invalid download_url: {}", e)))?;
+            let download_path = match download_url.query() {
+                Some(query) => format!("{}?{}", download_url.path(), query),
+                None => download_url.path().to_string(),
+            };
+            let data = self.get(download_url.as_str(), &download_path).await?.bytes().await?;
+            Ok(Some(TradeBill { data: data.to_vec(), sha1: bill.hash_value }))
+        })
+    }
+}
+
+/// 未配置商户号时使用：所有调用都返回 `GatewayError::Unconfigured`
+pub struct UnconfiguredPayGateway;
+
+impl UnconfiguredPayGateway {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self)
+    }
+}
+
+impl PayGateway for UnconfiguredPayGateway {
+    fn jsapi_prepay<'a>(&'a self, _request: &'a PrepayRequest) -> BoxFuture<'a, Result<String, GatewayError>> {
+        Box::pin(async { Err(GatewayError::Unconfigured) })
+    }
+
+    fn jsapi_pay_params(&self, _prepay_id: &str) -> Result<JsapiPayParams, GatewayError> {
+        Err(GatewayError::Unconfigured)
+    }
+
+    fn parse_notify<'a>(&'a self, _headers: &'a NotifyHeaders, _body: &'a str) -> BoxFuture<'a, Result<Transaction, GatewayError>> {
+        Box::pin(async { Err(GatewayError::Unconfigured) })
+    }
+
+    fn query_order<'a>(&'a self, _out_trade_no: &'a str) -> BoxFuture<'a, Result<Option<Transaction>, GatewayError>> {
+        Box::pin(async { Err(GatewayError::Unconfigured) })
+    }
+
+    fn refund<'a>(&'a self, _request: &'a RefundRequest) -> BoxFuture<'a, Result<RefundResult, GatewayError>> {
+        Box::pin(async { Err(GatewayError::Unconfigured) })
+    }
+
+    fn parse_refund_notify<'a>(&'a self, _headers: &'a NotifyHeaders, _body: &'a str) -> BoxFuture<'a, Result<RefundResult, GatewayError>> {
+        Box::pin(async { Err(GatewayError::Unconfigured) })
+    }
+
+    fn download_trade_bill(&self, _bill_date: NaiveDate) -> BoxFuture<'_, Result<Option<TradeBill>, GatewayError>> {
+        Box::pin(async { Err(GatewayError::Unconfigured) })
+    }
+}
+
+fn verifying_key_from_pem(pem: &[u8]) -> Result<VerifyingKey<Sha256>, GatewayError> {
+    let invalid = |e: String| GatewayError::Verify(format!("invalid platform certificate: {}", e));
+    let cert = Certificate::from_pem(pem).map_err(|e| invalid(e.to_string()))?;
+    let spki = cert.tbs_certificate.subject_public_key_info.to_der().map_err(|e| invalid(e.to_string()))?;
+    let key = RsaPublicKey::from_public_key_der(&spki).map_err(|e| invalid(e.to_string()))?;
+    Ok(VerifyingKey::new(key))
+}
+
+fn nonce_str() -> String {
+    rand::rng().sample_iter(&Alphanumeric).take(32).map(char::from).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, header_exists, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const TEST_KEY: &str = include_str!("../../testdata/wechat_pay_merchant_key.pem");
+    /// 测试中平台证书与商户使用同一把密钥
+    const TEST_PLATFORM_CERT: &str = include_str!("../../testdata/wechat_pay_platform_cert.pem");
+    const TEST_API_V3_KEY: &str = "0123456789abcdef0123456789abcdef";
+
+    fn settings(api_base: String) -> WechatPaySettings {
+        WechatPaySettings {
+            app_id: "wx_app".into(),
+            mch_id: "1900000001".into(),
+            serial_no: "SERIAL".into(),
+            private_key_path: String::new(),
+            api_v3_key: TEST_API_V3_KEY.into(),
+            notify_url: "https://shop.example.com/pay/wechat/notify".into(),
+            refund_notify_url: String::new(),
+            api_base,
+        }
+    }
+
+    fn verify(message: &str, signature: &str) -> bool {
+        let key = RsaPrivateKey::from_pkcs8_pem(TEST_KEY).unwrap();
+        let verifying_key = VerifyingKey::<Sha256>::new(key.to_public_key());
+        let signature = Signature::try_from(BASE64.decode(signature).unwrap().as_slice()).unwrap();
+        verifying_key.verify(message.as_bytes(), &signature).is_ok()
+    }
+
+    fn encrypt(plaintext: &str, nonce: &str, associated_data: &str) -> serde_json::Value {
+        let cipher = Aes256Gcm::new_from_slice(TEST_API_V3_KEY.as_bytes()).unwrap();
+        let payload = Payload { msg: plaintext.as_bytes(), aad: associated_data.as_bytes() };
+        let ciphertext = cipher.encrypt(Nonce::from_slice(nonce.as_bytes()), payload).unwrap();
+        json!({
+            "algorithm": "AEAD_AES_256_GCM",
+            "ciphertext": BASE64.encode(ciphertext),
+            "nonce": nonce,
+            "associated_data": associated_data,
+        })
+    }
+
+    async fn mock_certificates(server: &MockServer) {
+        Mock::given(method("GET"))
+            .and(path(CERTIFICATES_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "data": [{ "serial_no": "PLATFORM1", "encrypt_certificate": encrypt(TEST_PLATFORM_CERT, "certnonce123", "certificate") }]
+            })))
+            .expect(1)
+            .mount(server)
+            .await;
+    }
+
+    fn signed_headers(client: &WechatPayClient, body: &str, serial: &str) -> NotifyHeaders {
+        let timestamp = chrono::Utc::now().timestamp().to_string();
+        let nonce = nonce_str();
+        let signature = client.sign(&format!("{}\n{}\n{}\n", timestamp, nonce, body)).unwrap();
+        NotifyHeaders { timestamp, nonce, signature, serial: serial.to_string() }
+    }
+
+    fn notify_body() -> String {
+        let transaction = json!({
+            "appid": "wx_app",
+            "mchid": "1900000001",
+            "out_trade_no": "NO1",
+            "transaction_id": "4200000000000000001",
+            "trade_state": "SUCCESS",
+            "success_time": "2024-01-01T10:00:00+08:00",
+            "amount": { "total": 990, "payer_total": 990, "currency": "CNY" },
+        });
+        json!({
+            "id": "EV-1",
+            "event_type": "TRANSACTION.SUCCESS",
+            "resource": encrypt(&transaction.to_string(), "notifynonce1", "transaction"),
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_parse_notify_verifies_and_decrypts() {
+        let server = MockServer::start().await;
+        mock_certificates(&server).await;
+        let client = WechatPayClient::with_private_key_pem(&settings(server.uri()), TEST_KEY).unwrap();
+
+        let body = notify_body();
+        let headers = signed_headers(&client, &body, "PLATFORM1");
+        let transaction = client.parse_notify(&headers, &body).await.unwrap();
+        assert!(transaction.is_success());
+        assert_eq!(transaction.out_trade_no, "NO1");
+        assert_eq!(transaction.amount.total, 990);
+
+        // 证书已缓存，篡改报文后验签失败，未知序列号也不会立即重新下载
+        let tampered = body.replace("EV-1", "EV-2");
+        assert!(matches!(client.parse_notify(&headers, &tampered).await, Err(GatewayError::Verify(_))));
+        let unknown = signed_headers(&client, &body, "PLATFORM2");
+        assert!(matches!(client.parse_notify(&unknown, &body).await, Err(GatewayError::Verify(_))));
+    }
+
+    #[tokio::test]
+    async fn test_jsapi_prepay_against_mock() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(JSAPI_PREPAY_PATH))
+            .and(header_exists("authorization"))
+            .and(body_partial_json(json!({ "out_trade_no": "NO1", "amount": { "total": 990 }, "payer": { "openid": "o1" } })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "prepay_id": "wx201410272009395522657a690389285100" })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = WechatPayClient::with_private_key_pem(&settings(server.uri()), TEST_KEY).unwrap();
+        let request = PrepayRequest {
+            description: "测试商品".into(),
+            out_trade_no: "NO1".into(),
+            amount: 990,
+            payer_openid: "o1".into(),
+            time_expire: None,
+        };
+        let prepay_id = client.jsapi_prepay(&request).await.unwrap();
+        assert_eq!(prepay_id, "wx201410272009395522657a690389285100");
+
+        let received = &server.received_requests().await.unwrap()[0];
+        let authorization = received.headers.get("authorization").unwrap().to_str().unwrap();
+        assert!(authorization.starts_with("WECHATPAY2-SHA256-RSA2048 mchid=\"1900000001\""));
+    }
+
+    #[tokio::test]
+    async fn test_api_error_is_reported() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({ "code": "PARAM_ERROR", "message": "bad" })))
+            .mount(&server)
+            .await;
+
+        let client = WechatPayClient::with_private_key_pem(&settings(server.uri()), TEST_KEY).unwrap();
+        let request = PrepayRequest {
+            description: "测试商品".into(),
+            out_trade_no: "NO1".into(),
+            amount: 1,
+            payer_openid: "o1".into(),
+            time_expire: None,
+        };
+        let result = client.jsapi_prepay(&request).await;
+        assert!(matches!(result, Err(GatewayError::Api { status: 400, .. })));
+    }
+
+    #[tokio::test]
+    async fn test_refund_against_mock() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(REFUNDS_PATH))
+            .and(body_partial_json(json!({ "out_refund_no": "R1", "amount": { "refund": 300, "total": 990 } })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "refund_id": "50000000382019052709732678859",
+                "out_refund_no": "R1",
+                "status": "PROCESSING",
+                "amount": { "refund": 300, "total": 990, "currency": "CNY" },
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = WechatPayClient::with_private_key_pem(&settings(server.uri()), TEST_KEY).unwrap();
+        let request = RefundRequest {
+            out_trade_no: "NO1".into(),
+            out_refund_no: "R1".into(),
+            reason: "不想要了".into(),
+            refund: 300,
+            total: 990,
+        };
+        let result = client.refund(&request).await.unwrap();
+        assert!(!result.is_success() && !result.is_failed());
+        assert_eq!(result.amount.refund, 300);
+    }
+
+    #[tokio::test]
+    async fn test_query_order_against_mock() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(format!("{}/NO1", QUERY_BY_OUT_TRADE_NO_PATH)))
+            .and(query_param("mchid", "1900000001"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "appid": "wx_app",
+                "mchid": "1900000001",
+                "out_trade_no": "NO1",
+                "trade_state": "NOTPAY",
+                "trade_state_desc": "订单未支付",
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("{}/NO2", QUERY_BY_OUT_TRADE_NO_PATH)))
+            .respond_with(ResponseTemplate::new(404).set_body_json(json!({ "code": "ORDER_NOT_EXIST", "message": "订单不存在" })))
+            .mount(&server)
+            .await;
+
+        let client = WechatPayClient::with_private_key_pem(&settings(server.uri()), TEST_KEY).unwrap();
+        let transaction = client.query_order("NO1").await.unwrap().unwrap();
+        assert!(!transaction.is_success() && !transaction.is_closed());
+        assert!(transaction.transaction_id.is_empty());
+        assert!(client.query_order("NO2").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_download_trade_bill_against_mock() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(TRADE_BILL_PATH))
+            .and(query_param("bill_date", "2024-01-01"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "hash_type": "SHA1",
+                "hash_value": "79bb0bf3e5d1cb26ac8ab7ba6f0c4f2b7b3b7c29",
+                "download_url": format!("{}/v3/billdownload/file?token=T1", server.uri()),
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v3/billdownload/file"))
+            .and(query_param("token", "T1"))
+            .and(header_exists("authorization"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"gzip bytes".to_vec()))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(TRADE_BILL_PATH))
+            .and(query_param("bill_date", "2024-01-02"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({ "code": "NO_STATEMENT_EXIST", "message": "no bill" })))
+            .mount(&server)
+            .await;
+
+        let client = WechatPayClient::with_private_key_pem(&settings(server.uri()), TEST_KEY).unwrap();
+        let bill = client.download_trade_bill(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()).await.unwrap().unwrap();
+        assert_eq!(bill.data, b"gzip bytes");
+        assert_eq!(bill.sha1, "79bb0bf3e5d1cb26ac8ab7ba6f0c4f2b7b3b7c29");
+        assert!(client.download_trade_bill(NaiveDate::from_ymd_opt(2024, 1, 2).unwrap()).await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_pay_sign_verifies() {
+        let client = WechatPayClient::with_private_key_pem(&settings(String::new()), TEST_KEY).unwrap();
+        let params = client.jsapi_pay_params("wx123").unwrap();
+        assert_eq!(params.package, "prepay_id=wx123");
+        let message = format!("{}\n{}\n{}\n{}\n", params.app_id, params.time_stamp, params.nonce_str, params.package);
+        assert!(verify(&message, &params.pay_sign));
+    }
+}
@@ -0,0 +1,233 @@
+//! 小程序订阅消息发送接口。access_token 通过 AppSecret 换取并缓存到过期前，
+//! 微信返回 token 失效时清空缓存，下次发送重新获取。
+
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex as AsyncMutex;
+use crate::domain::BoxFuture;
+use crate::domain::notification::SubscribeTransport;
+use crate::domain::payment::GatewayError;
+use crate::models::notification::{SendOutcome, SubscribeMessage};
+use wx_shop::NotificationSettings;
+
+const TOKEN_PATH: &str = "/cgi-bin/token";
+const SEND_PATH: &str = "/cgi-bin/message/subscribe/send";
+/// access_token 无效或已过期
+const TOKEN_INVALID_CODES: [i64; 3] = [40001, 40014, 42001];
+/// 用户拒绝接受消息或授权次数已用完
+const REFUSED_CODE: i64 = 43101;
+/// 提前刷新 token 的余量
+const TOKEN_REFRESH_MARGIN: Duration = Duration::from_secs(300);
+
+pub struct WechatSubscribeClient {
+    http: reqwest::Client,
+    app_id: String,
+    settings: NotificationSettings,
+    token: AsyncMutex<Option<(String, Instant)>>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    #[serde(default)]
+    access_token: String,
+    #[serde(default)]
+    expires_in: u64,
+    #[serde(default)]
+    errcode: i64,
+    #[serde(default)]
+    errmsg: String,
+}
+
+#[derive(Deserialize)]
+struct SendResponse {
+    #[serde(default)]
+    errcode: i64,
+    #[serde(default)]
+    errmsg: String,
+}
+
+impl WechatSubscribeClient {
+    pub fn new(app_id: &str, settings: &NotificationSettings) -> Result<Arc<Self>, String> {
+        let http = reqwest::Client::builder()
+            .user_agent(concat!("wx-shop/", env!("CARGO_PKG_VERSION")))
+            .timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Arc::new(Self {
+            http,
+            app_id: app_id.to_string(),
+            settings: settings.clone(),
+            token: AsyncMutex::new(None),
+        }))
+    }
+
+    /// 持锁获取 token，避免并发发送时重复换取导致旧 token 失效
+    async fn access_token(&self) -> Result<String, GatewayError> {
+        let mut cached = self.token.lock().await;
+        if let Some((token, expires_at)) = cached.as_ref()
+            && Instant::now() < *expires_at
+        {
+            return Ok(token.clone());
+        }
+
+        let response = self
+            .http
+            .get(format!("{}{}", self.settings.api_base, TOKEN_PATH))
+            .query(&[
+                ("grant_type", "client_credential"),
+                ("appid", self.app_id.as_str()),
+                ("secret", self.settings.app_secret.as_str()),
+            ])
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(GatewayError::Api { status: status.as_u16(), body });
+        }
+        let body: TokenResponse = response.json().await?;
+        if body.errcode != 0 || body.access_token.is_empty() {
+            return Err(GatewayError::Api { status: status.as_u16(), body: format!("{} {}", body.errcode, body.errmsg) });
+        }
+        let ttl = Duration::from_secs(body.expires_in).saturating_sub(TOKEN_REFRESH_MARGIN);
+        *cached = Some((body.access_token.clone(), Instant::now() + ttl));
+        Ok(body.access_token)
+    }
+
+    async fn invalidate_token(&self) {
+        *self.token.lock().await = None;
+    }
+}
+
+impl SubscribeTransport for WechatSubscribeClient {
+    fn send<'a>(&'a self, message: &'a SubscribeMessage) -> BoxFuture<'a, Result<SendOutcome, GatewayError>> {
+        Box::pin(async move {
+            let token = self.access_token().await?;
+            let data: Map<String, Value> = message
+                .data
+                .iter()
+                .map(|(key, value)| (key.clone(), json!({ "value": value })))
+                .collect();
+            let response = self
+                .http
+                .post(format!("{}{}", self.settings.api_base, SEND_PATH))
+                .query(&[("access_token", token.as_str())])
+                .json(&json!({
+                    "touser": message.openid,
+                    "template_id": message.template_id,
+                    "page": message.page,
+                    "miniprogram_state": self.settings.miniprogram_state,
+                    "lang": "zh_CN",
+                    "data": data,
+                }))
+                .send()
+                .await?;
+            let status = response.status();
+            if !status.is_success() {
+                let body = response.text().await.unwrap_or_default();
+                return Err(GatewayError::Api { status: status.as_u16(), body });
+            }
+
+            let body: SendResponse = response.json().await?;
+            match body.errcode {
+                0 => Ok(SendOutcome::Sent),
+                REFUSED_CODE => Ok(SendOutcome::Refused),
+                code => {
+                    if TOKEN_INVALID_CODES.contains(&code) {
+                        self.invalidate_token().await;
+                    }
+                    Err(GatewayError::Api { status: status.as_u16(), body: format!("{} {}", code, body.errmsg) })
+                }
+            }
+        })
+    }
+}
+
+/// 不调用外部接口，只记录待发送的消息；用于测试和本地开发
+#[derive(Default)]
+pub struct RecordingTransport {
+    sent: Mutex<Vec<SubscribeMessage>>,
+}
+
+impl RecordingTransport {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+}
+
+impl SubscribeTransport for RecordingTransport {
+    fn send<'a>(&'a self, message: &'a SubscribeMessage) -> BoxFuture<'a, Result<SendOutcome, GatewayError>> {
+        Box::pin(async move {
+            tracing::info!("Subscribe message to {} with template {}: {:?}", message.openid, message.template_id, message.data);
+            self.sent.lock().unwrap().push(message.clone());
+            Ok(SendOutcome::Sent)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use wiremock::matchers::{body_partial_json, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn message(openid: &str) -> SubscribeMessage {
+        SubscribeMessage {
+            openid: openid.to_string(),
+            template_id: "TPL".to_string(),
+            page: "pages/order/detail?id=1".to_string(),
+            data: BTreeMap::from([("character_string1".to_string(), "NO001".to_string())]),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_caches_token_and_maps_errors() {
+        let server = MockServer::start().await;
+        let settings = NotificationSettings { app_secret: "SECRET".into(), api_base: server.uri(), ..NotificationSettings::default() };
+        let client = WechatSubscribeClient::new("wxapp", &settings).unwrap();
+        Mock::given(method("GET"))
+            .and(path(TOKEN_PATH))
+            .and(query_param("appid", "wxapp"))
+            .and(query_param("secret", "SECRET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "access_token": "TOKEN", "expires_in": 7200 })))
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(SEND_PATH))
+            .and(query_param("access_token", "TOKEN"))
+            .and(body_partial_json(json!({ "touser": "ok", "data": { "character_string1": { "value": "NO001" } } })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "errcode": 0, "errmsg": "ok" })))
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(SEND_PATH))
+            .and(body_partial_json(json!({ "touser": "refused" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "errcode": 43101, "errmsg": "user refuse to accept the msg" })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(SEND_PATH))
+            .and(body_partial_json(json!({ "touser": "expired" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "errcode": 42001, "errmsg": "access_token expired" })))
+            .mount(&server)
+            .await;
+
+        assert_eq!(client.send(&message("ok")).await.unwrap(), SendOutcome::Sent);
+        assert_eq!(client.send(&message("refused")).await.unwrap(), SendOutcome::Refused);
+        // token 失效后清空缓存，下一次发送重新获取
+        assert!(matches!(client.send(&message("expired")).await, Err(GatewayError::Api { .. })));
+        assert_eq!(client.send(&message("ok")).await.unwrap(), SendOutcome::Sent);
+    }
+
+    #[tokio::test]
+    async fn test_recording_transport_keeps_messages() {
+        let transport = RecordingTransport::new();
+        assert_eq!(transport.send(&message("fake")).await.unwrap(), SendOutcome::Sent);
+        assert_eq!(*transport.sent.lock().unwrap(), vec![message("fake")]);
+    }
+}
@@ -0,0 +1,69 @@
+use axum::extract::DefaultBodyLimit;
+use axum::routing::{get, post, put};
+use axum::Router;
+use crate::AppState;
+use crate::handler::{after_sale, audit, coupon, export, freight, group_buy, import, notification, promotion, reconciliation, referral, refund, review, shipment, stats};
+use crate::router::middleware;
+
+/// 后台管理接口，仅管理员可访问
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/admin/refunds", get(refund::admin_list_refunds_handler))
+        .route("/admin/refunds/{id}/approve", post(refund::admin_approve_refund_handler))
+        .route("/admin/refunds/{id}/reject", post(refund::admin_reject_refund_handler))
+        .route(
+            "/admin/coupon-templates",
+            get(coupon::admin_list_coupon_templates_handler).post(coupon::admin_create_coupon_template_handler),
+        )
+        .route("/admin/coupon-templates/{id}/disable", post(coupon::admin_disable_coupon_template_handler))
+        .route(
+            "/admin/promotions",
+            get(promotion::admin_list_promotions_handler).post(promotion::admin_create_promotion_handler),
+        )
+        .route("/admin/promotions/{id}/disable", post(promotion::admin_disable_promotion_handler))
+        .route(
+            "/admin/group-campaigns",
+            get(group_buy::admin_list_group_campaigns_handler).post(group_buy::admin_create_group_campaign_handler),
+        )
+        .route("/admin/group-campaigns/{id}/disable", post(group_buy::admin_disable_group_campaign_handler))
+        .route(
+            "/admin/freight-templates",
+            get(freight::admin_list_freight_templates_handler).post(freight::admin_create_freight_template_handler),
+        )
+        .route("/admin/freight-templates/{id}", put(freight::admin_update_freight_template_handler))
+        .route("/admin/products/{id}/freight-template", put(freight::admin_assign_freight_template_handler))
+        .route("/admin/orders/{id}/ship", post(shipment::admin_ship_order_handler))
+        .route("/admin/after-sales", get(after_sale::admin_list_after_sales_handler))
+        .route("/admin/after-sales/{id}", get(after_sale::admin_get_after_sale_handler))
+        .route("/admin/after-sales/{id}/approve", post(after_sale::admin_approve_after_sale_handler))
+        .route("/admin/after-sales/{id}/reject", post(after_sale::admin_reject_after_sale_handler))
+        .route("/admin/after-sales/{id}/receive", post(after_sale::admin_receive_after_sale_handler))
+        .route("/admin/after-sales/{id}/refund", post(after_sale::admin_refund_after_sale_handler))
+        .route("/admin/after-sales/{id}/exchange", post(after_sale::admin_exchange_after_sale_handler))
+        .route("/admin/after-sales/{id}/notes", post(after_sale::admin_add_after_sale_note_handler))
+        .route("/admin/reviews", get(review::admin_list_reviews_handler))
+        .route("/admin/reviews/{id}/approve", post(review::admin_approve_review_handler))
+        .route("/admin/reviews/{id}/hide", post(review::admin_hide_review_handler))
+        .route("/admin/reviews/{id}/reply", post(review::admin_reply_review_handler))
+        .route("/admin/commissions", get(referral::admin_list_commissions_handler))
+        .route("/admin/notification-deliveries", get(notification::admin_list_deliveries_handler))
+        .route("/admin/stats/overview", get(stats::admin_stats_overview_handler))
+        .route("/admin/stats/top-products", get(stats::admin_top_products_handler))
+        .route("/admin/stats/rollup", post(stats::admin_rollup_stats_handler))
+        .route("/admin/exports", get(export::admin_list_exports_handler).post(export::admin_submit_export_handler))
+        .route("/admin/exports/{id}", get(export::admin_get_export_handler))
+        .route("/admin/exports/{id}/download", get(export::admin_download_export_handler))
+        .route(
+            "/admin/imports",
+            get(import::admin_list_imports_handler)
+                .post(import::admin_submit_import_handler)
+                .layer(DefaultBodyLimit::max(import::MAX_IMPORT_BYTES)),
+        )
+        .route("/admin/imports/{id}", get(import::admin_get_import_handler))
+        .route("/admin/imports/{id}/report", get(import::admin_download_import_report_handler))
+        .route("/admin/audit-logs", get(audit::admin_list_audit_logs_handler))
+        .route("/admin/reconciliations", get(reconciliation::admin_list_reconciliations_handler))
+        .route("/admin/reconciliations/{bill_date}", get(reconciliation::admin_get_reconciliation_handler))
+        .route_layer(axum::middleware::from_fn(middleware::audit))
+        .route_layer(axum::middleware::from_fn(middleware::require_admin))
+}
@@ -3,18 +3,22 @@ use axum::{middleware::Next, extract::Request};
 use serde_json::json;
 use tower_sessions::Session;
 use crate::models;
+use crate::handler::SESSION_USER_KEY;
 use axum::body::{Body, Bytes};
+use axum::extract::{ConnectInfo, Extension, MatchedPath};
+use axum::http::{Method, header};
 use http_body_util::BodyExt;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use crate::models::audit::NewAuditLog;
+use crate::service::audit::{self, AuditService};
 
 pub async fn require_login(
     request: Request,
     next: Next,
 ) -> Result<axum::response::Response, StatusCode> {
     let logged_in = if let Some(session) = request.extensions().get::<Session>() {
-        match session.get::<models::User>("user").await {
-            Ok(Some(_)) => true,
-            _ => false,
-        }
+        matches!(session.get::<models::User>(SESSION_USER_KEY).await, Ok(Some(_)))
     } else {
         false
     };
@@ -26,10 +30,107 @@ pub async fn require_login(
     Ok(next.run(request).await)
 }
 
+/// 报文含支付等敏感信息、不打印请求体的路径
+const UNLOGGED_BODY_PATHS: &[&str] = &["/pay/wechat/notify", "/pay/wechat/refund-notify"];
+
+pub async fn require_admin(
+    request: Request,
+    next: Next,
+) -> Result<axum::response::Response, StatusCode> {
+    let user = match request.extensions().get::<Session>() {
+        Some(session) => session.get::<models::User>(SESSION_USER_KEY).await.ok().flatten(),
+        None => None,
+    };
+
+    match user {
+        None => Ok(Json(json!({"code": 4010, "msg": "not logged in"})).into_response()),
+        Some(user) if !user.is_admin() => Ok(Json(json!({"code": 4030, "msg": "forbidden"})).into_response()),
+        Some(_) => Ok(next.run(request).await),
+    }
+}
+
+/// 审计时读取 JSON 请求体的上限，与 axum 默认的请求体上限一致
+const MAX_AUDIT_BODY_BYTES: usize = 2 * 1024 * 1024;
+/// 超过该大小的请求体不写入审计日志，只记录大小
+const MAX_LOGGED_BODY_BYTES: usize = 16 * 1024;
+
+/// 记录管理员的写操作：操作人、来源、接口、操作对象、结果，以及服务记录的敏感数据变更。
+/// 需放在 `require_admin` 之内，只处理非 GET 请求
+pub async fn audit(
+    Extension(audit_service): Extension<Arc<dyn AuditService>>,
+    request: Request,
+    next: Next,
+) -> Result<axum::response::Response, StatusCode> {
+    if matches!(*request.method(), Method::GET | Method::HEAD | Method::OPTIONS) {
+        return Ok(next.run(request).await);
+    }
+    let session = request.extensions().get::<Session>().cloned();
+    let actor_id = match &session {
+        Some(session) => session.get::<models::User>(SESSION_USER_KEY).await.ok().flatten().map_or(0, |user| user.id),
+        None => 0,
+    };
+    let route = request.extensions().get::<MatchedPath>().map_or_else(|| request.uri().path().to_string(), |path| path.as_str().to_string());
+    let (target_type, target_id) = audit::target(&route, request.uri().path());
+    let action = format!("{} {}", request.method(), route);
+    let ip = request.extensions().get::<ConnectInfo<SocketAddr>>().map(|info| info.0.ip().to_string());
+    let forwarded_for = request
+        .headers()
+        .get("x-forwarded-for")
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.chars().take(255).collect());
+
+    let (request, body) = if is_json(&request) {
+        let (parts, body) = request.into_parts();
+        let bytes = axum::body::to_bytes(body, MAX_AUDIT_BODY_BYTES).await.map_err(|_| StatusCode::PAYLOAD_TOO_LARGE)?;
+        let logged = if bytes.len() <= MAX_LOGGED_BODY_BYTES {
+            serde_json::from_slice::<serde_json::Value>(&bytes).ok().map(|mut value| {
+                audit::redact(&mut value);
+                value
+            })
+        } else {
+            Some(json!({"truncated": true, "bytes": bytes.len()}))
+        };
+        (Request::from_parts(parts, Body::from(bytes)), logged)
+    } else {
+        (request, None)
+    };
+
+    let (response, changes) = audit::collect_changes(next.run(request)).await;
+    let log = NewAuditLog {
+        actor_id,
+        ip,
+        forwarded_for,
+        session_id: session.and_then(|session| session.id()).map(|id| id.to_string()),
+        action,
+        target_type,
+        target_id,
+        status_code: Some(response.status().as_u16()),
+        request: body,
+        changes,
+    };
+    // 操作已经完成，日志写入失败不影响响应
+    if let Err(e) = audit_service.record(log).await {
+        tracing::error!("Failed to write audit log: {:?}", e);
+    }
+    Ok(response)
+}
+
+fn is_json(request: &Request) -> bool {
+    request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"))
+}
+
 pub async fn print_request_body(
     request: Request,
     next: Next,
 ) -> Result<axum::response::Response, axum::http::StatusCode> {
+    // 只打印 JSON 请求体，上传文件等其它类型原样透传，不缓冲进内存
+    if !is_json(&request) || UNLOGGED_BODY_PATHS.contains(&request.uri().path()) {
+        return Ok(next.run(request).await);
+    }
     let (parts, body) = request.into_parts();
     let bytes = buffer_and_print("request", body).await?;
     let req = Request::from_parts(parts, Body::from(bytes));
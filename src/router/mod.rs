@@ -3,6 +3,7 @@ use crate::AppState;
 
 pub mod public;
 pub mod protected;
+pub mod admin;
 pub mod middleware;
 pub mod error;
 
@@ -10,5 +11,6 @@ pub fn routes() -> Router<AppState> {
     Router::new()
         .merge(public::routes())
         .merge(protected::routes())
+        .merge(admin::routes())
         .fallback(error::handler_404)
 }
@@ -1,12 +1,43 @@
-use axum::routing::get;
+use axum::routing::{get, post};
 use axum::Router;
 use crate::AppState;
-use crate::handler::users;
+use crate::handler::{address, after_sale, coupon, favorite, group_buy, loyalty, notification, order, payment, referral, refund, review, shipment, users};
 use crate::router::middleware;
 
 pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/user/{id}", get(users::get_user_by_id_handler))
+        .route("/addresses", get(address::list_addresses_handler).post(address::create_address_handler))
+        .route("/coupons/{template_id}/claim", post(coupon::claim_coupon_handler))
+        .route("/coupons/mine", get(coupon::list_my_coupons_handler))
+        .route("/group-buys/campaigns/{id}/open", post(group_buy::open_group_handler))
+        .route("/group-buys/{id}/join", post(group_buy::join_group_handler))
+        .route("/orders/preview", post(order::preview_order_handler))
+        .route("/orders", get(order::list_orders_handler).post(order::create_order_handler))
+        .route("/orders/{id}", get(order::get_order_handler))
+        .route("/orders/{id}/cancel", post(order::cancel_order_handler))
+        .route("/orders/{id}/events", get(order::list_order_events_handler))
+        .route("/orders/{id}/pay", post(payment::jsapi_pay_handler))
+        .route("/orders/{id}/sync-payment", post(payment::sync_payment_handler))
+        .route("/orders/{id}/tracking", get(shipment::order_tracking_handler))
+        .route("/orders/{id}/confirm-receipt", post(shipment::confirm_receipt_handler))
+        .route("/orders/{id}/after-sales", post(after_sale::request_after_sale_handler))
+        .route("/orders/{id}/reviews", post(review::create_review_handler))
+        .route("/reviews/mine", get(review::list_my_reviews_handler))
+        .route("/reviews/{id}/follow-up", post(review::follow_up_review_handler))
+        .route("/me/points", get(loyalty::my_points_handler))
+        .route("/referral/share", get(referral::share_info_handler))
+        .route("/referral/bind", post(referral::bind_referral_handler))
+        .route("/referral/commissions", get(referral::my_commissions_handler))
+        .route("/favorites", get(favorite::list_favorites_handler))
+        .route("/favorites/{product_id}", post(favorite::add_favorite_handler).delete(favorite::remove_favorite_handler))
+        .route("/history", get(favorite::list_history_handler).delete(favorite::clear_history_handler))
+        .route("/history/{product_id}", post(favorite::record_view_handler).delete(favorite::remove_view_handler))
+        .route("/notifications/subscriptions", post(notification::subscribe_handler))
+        .route("/after-sales", get(after_sale::list_my_after_sales_handler))
+        .route("/after-sales/{id}", get(after_sale::get_after_sale_handler))
+        .route("/after-sales/{id}/ship-back", post(after_sale::ship_back_handler))
+        .route("/orders/{id}/refunds", get(refund::list_order_refunds_handler).post(refund::apply_refund_handler))
         .route_layer(axum::middleware::from_fn(middleware::require_login))
 }
 
@@ -1,12 +1,28 @@
-use axum::routing::{get, post};
+use axum::routing::{get, post, put};
 use axum::Router;
 use crate::AppState;
-use crate::handler::{index, users};
+use crate::handler::{cart, coupon, group_buy, index, inventory, notification, payment, promotion, refund, review, users};
 
 pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/login", post(users::login_handler))
         .route("/debug/hash", post(users::hash_handler))
+        .route("/inventory/{sku_id}", get(inventory::get_stock_handler))
+        .route("/coupons", get(coupon::list_claimable_coupons_handler))
+        .route("/promotions", get(promotion::list_active_promotions_handler))
+        .route("/products/{id}/reviews", get(review::list_product_reviews_handler))
+        .route("/notifications/templates", get(notification::list_templates_handler))
+        .route("/group-buys/campaigns", get(group_buy::list_group_campaigns_handler))
+        // 分享出去的团详情，未登录也可查看
+        .route("/group-buys/{id}", get(group_buy::get_group_handler))
+        // 购物车对游客开放，登录后自动合并
+        .route("/cart", get(cart::get_cart_handler))
+        .route("/cart/items", post(cart::add_item_handler))
+        .route("/cart/items/{sku_id}", put(cart::update_quantity_handler).delete(cart::remove_item_handler))
+        .route("/cart/selection", put(cart::select_items_handler))
+        // 支付渠道回调，靠报文签名鉴权
+        .route("/pay/wechat/notify", post(payment::wechat_notify_handler))
+        .route("/pay/wechat/refund-notify", post(refund::wechat_refund_notify_handler))
         .route("/", get(index::index))
 }
 
@@ -0,0 +1,51 @@
+use crate::domain::BoxFuture;
+use crate::domain::address::AddressRepo;
+use crate::models::address::{Address, NewAddress};
+use crate::service::ServiceError;
+use std::sync::Arc;
+
+pub trait AddressService: Send + Sync {
+    fn list(&self, user_id: u32) -> BoxFuture<'_, Result<Vec<Address>, ServiceError>>;
+    fn create(&self, user_id: u32, address: NewAddress) -> BoxFuture<'_, Result<Address, ServiceError>>;
+}
+
+pub struct AddressServiceImpl<R: AddressRepo + 'static> {
+    repo: Arc<R>,
+}
+
+impl<R: AddressRepo + 'static> AddressServiceImpl<R> {
+    pub fn new(repo: Arc<R>) -> Self {
+        Self { repo }
+    }
+}
+
+impl<R: AddressRepo + 'static> AddressService for AddressServiceImpl<R> {
+    fn list(&self, user_id: u32) -> BoxFuture<'_, Result<Vec<Address>, ServiceError>> {
+        Box::pin(async move { Ok(self.repo.list(user_id).await?) })
+    }
+
+    fn create(&self, user_id: u32, address: NewAddress) -> BoxFuture<'_, Result<Address, ServiceError>> {
+        Box::pin(async move {
+            let required = [
+                &address.receiver_name,
+                &address.phone,
+                &address.province,
+                &address.city,
+                &address.district,
+                &address.detail,
+            ];
+            if required.iter().any(|field| field.trim().is_empty()) {
+                return Err(ServiceError::BadRequest("address fields must not be empty".to_string()));
+            }
+            let id = self.repo.create(user_id, &address).await?;
+            self.repo
+                .find(user_id, id)
+                .await?
+                .ok_or_else(|| ServiceError::NotFound(format!("Address with ID {} not found", id)))
+        })
+    }
+}
+
+pub fn new_address_service<R: AddressRepo + 'static>(repo: Arc<R>) -> Arc<dyn AddressService> {
+    Arc::new(AddressServiceImpl::new(repo)) as Arc<dyn AddressService>
+}
@@ -0,0 +1,496 @@
+use crate::domain::BoxFuture;
+use crate::domain::after_sale::AfterSaleRepo;
+use crate::domain::catalog::CatalogRepo;
+use crate::domain::order::OrderRepo;
+use crate::models::after_sale::{
+    AfterSale, AfterSaleApply, AfterSaleDetail, AfterSaleKind, AfterSaleStatus, NewAfterSale,
+};
+use crate::models::event::{DomainEvent, ORDER_EVENT_PREFIX, OrderStatusChanged};
+use crate::models::order::{OrderItem, OrderStatus};
+use crate::models::refund::RefundStatus;
+use crate::service::ServiceError;
+use crate::service::events::EventSubscriber;
+use crate::service::inventory::InventoryService;
+use crate::service::refund::RefundService;
+use chrono::Local;
+use rand::Rng;
+use std::sync::Arc;
+
+pub trait AfterSaleService: Send + Sync {
+    /// 用户对已签收订单的一行申请退货退款或换货
+    fn request<'a>(&'a self, user_id: u32, order_id: u64, apply: &'a AfterSaleApply) -> BoxFuture<'a, Result<AfterSale, ServiceError>>;
+    fn list_mine(&self, user_id: u32, page: u32, page_size: u32) -> BoxFuture<'_, Result<Vec<AfterSale>, ServiceError>>;
+    fn get(&self, user_id: u32, id: u64) -> BoxFuture<'_, Result<AfterSale, ServiceError>>;
+    /// 审核通过后用户填写寄回的运单
+    fn ship_back<'a>(&'a self, user_id: u32, id: u64, carrier: &'a str, tracking_no: &'a str) -> BoxFuture<'a, Result<AfterSale, ServiceError>>;
+    fn list_by_status<'a>(&'a self, status: &'a str, page: u32, page_size: u32) -> BoxFuture<'a, Result<Vec<AfterSale>, ServiceError>>;
+    /// 后台详情，带客服备注
+    fn detail(&self, id: u64) -> BoxFuture<'_, Result<AfterSaleDetail, ServiceError>>;
+    fn approve(&self, admin_id: u32, id: u64) -> BoxFuture<'_, Result<AfterSale, ServiceError>>;
+    /// 申请阶段拒绝，或收货验货不通过时拒绝
+    fn reject<'a>(&'a self, admin_id: u32, id: u64, reason: &'a str) -> BoxFuture<'a, Result<AfterSale, ServiceError>>;
+    /// 确认收到寄回的商品
+    fn receive(&self, admin_id: u32, id: u64) -> BoxFuture<'_, Result<AfterSale, ServiceError>>;
+    /// 退货单收货后按行退款，售后单进入退款中；渠道提交失败时可以重复调用。
+    /// 渠道确认成功后退回的件数入库、售后单置为已退款，失败时回到已收货
+    fn refund(&self, admin_id: u32, id: u64) -> BoxFuture<'_, Result<AfterSale, ServiceError>>;
+    /// 换货单收货后预占新商品库存并记录发出的运单
+    fn exchange<'a>(&'a self, admin_id: u32, id: u64, carrier: &'a str, tracking_no: &'a str) -> BoxFuture<'a, Result<AfterSale, ServiceError>>;
+    fn add_note<'a>(&'a self, admin_id: u32, id: u64, note: &'a str) -> BoxFuture<'a, Result<AfterSaleDetail, ServiceError>>;
+    /// 订单的退款有了结果后调用，把结果落到退款中的售后单上；可以重复调用
+    fn on_refund_settled(&self, order_id: u64) -> BoxFuture<'_, Result<(), ServiceError>>;
+}
+
+/// 售后依赖的其他服务
+pub struct AfterSaleDeps {
+    pub refunds: Arc<dyn RefundService>,
+    pub inventory: Arc<dyn InventoryService>,
+}
+
+pub struct AfterSaleServiceImpl<R: AfterSaleRepo + 'static, O: OrderRepo + 'static, C: CatalogRepo + 'static> {
+    repo: Arc<R>,
+    orders: Arc<O>,
+    catalog: Arc<C>,
+    refunds: Arc<dyn RefundService>,
+    inventory: Arc<dyn InventoryService>,
+}
+
+impl<R: AfterSaleRepo + 'static, O: OrderRepo + 'static, C: CatalogRepo + 'static> AfterSaleServiceImpl<R, O, C> {
+    pub fn new(repo: Arc<R>, orders: Arc<O>, catalog: Arc<C>, deps: AfterSaleDeps) -> Self {
+        let AfterSaleDeps { refunds, inventory } = deps;
+        Self { repo, orders, catalog, refunds, inventory }
+    }
+
+    async fn load(&self, id: u64) -> Result<AfterSale, ServiceError> {
+        self.repo
+            .find(id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("After-sale with ID {} not found", id)))
+    }
+
+    async fn load_user(&self, user_id: u32, id: u64) -> Result<AfterSale, ServiceError> {
+        match self.repo.find(id).await? {
+            Some(after_sale) if after_sale.user_id == user_id => Ok(after_sale),
+            _ => Err(ServiceError::NotFound(format!("After-sale with ID {} not found", id))),
+        }
+    }
+
+    /// 按状态机推进一步，`from` 为当前状态
+    async fn advance(&self, after_sale: &AfterSale, to: AfterSaleStatus, admin_id: Option<u32>) -> Result<AfterSale, ServiceError> {
+        let from = check_transition(after_sale, to)?;
+        if !self.repo.set_status(after_sale.id, from, to, admin_id).await? {
+            return Err(ServiceError::Conflict(format!("after-sale {} changed concurrently", after_sale.id)));
+        }
+        self.load(after_sale.id).await
+    }
+
+    /// 按渠道退款结果推进退款中的售后单：成功时退回的件数入库并置为已退款，
+    /// 失败时回到已收货并清空退款单；还在处理中时不变
+    async fn settle_refund(&self, after_sale: &AfterSale) -> Result<AfterSale, ServiceError> {
+        let Some(refund_id) = after_sale.refund_id.filter(|_| after_sale.status() == Ok(AfterSaleStatus::Refunding)) else {
+            return Ok(after_sale.clone());
+        };
+        let refund = self
+            .refunds
+            .list_for_order(after_sale.user_id, after_sale.order_id)
+            .await?
+            .into_iter()
+            .find(|refund| refund.id == refund_id)
+            .ok_or_else(|| ServiceError::NotFound(format!("Refund with ID {} not found", refund_id)))?;
+        if refund.is(RefundStatus::Succeeded) {
+            let item = self
+                .orders
+                .find_items(after_sale.order_id)
+                .await?
+                .into_iter()
+                .find(|item| item.id == after_sale.order_item_id)
+                .ok_or_else(|| ServiceError::NotFound(format!("Order item {} not found", after_sale.order_item_id)))?;
+            // 按售后单号入库，重复执行只生效一次
+            self.inventory
+                .restock(item.sku_id, after_sale.user_id, after_sale.quantity, &after_sale.after_sale_no)
+                .await?;
+            self.repo.set_status(after_sale.id, AfterSaleStatus::Refunding, AfterSaleStatus::Refunded, None).await?;
+        } else if refund.is(RefundStatus::Failed) {
+            tracing::error!("Refund {} of after-sale {} failed", refund.refund_no, after_sale.after_sale_no);
+            self.repo.reset_refund(after_sale.id).await?;
+        }
+        self.load(after_sale.id).await
+    }
+
+    /// 换货发出的 SKU：未指定时换同款，指定时须为同一商品且在售
+    async fn exchange_sku(&self, item: &OrderItem, sku_id: Option<u64>) -> Result<u64, ServiceError> {
+        let Some(sku_id) = sku_id.filter(|&id| id != item.sku_id) else {
+            return Ok(item.sku_id);
+        };
+        self.catalog
+            .find_skus(&[sku_id])
+            .await?
+            .into_iter()
+            .find(|sku| sku.product_id == item.product_id && sku.is_on_sale())
+            .map(|sku| sku.sku_id)
+            .ok_or_else(|| ServiceError::BadRequest(format!("SKU {} cannot be exchanged for item {}", sku_id, item.id)))
+    }
+}
+
+impl<R: AfterSaleRepo + 'static, O: OrderRepo + 'static, C: CatalogRepo + 'static> AfterSaleService for AfterSaleServiceImpl<R, O, C> {
+    fn request<'a>(&'a self, user_id: u32, order_id: u64, apply: &'a AfterSaleApply) -> BoxFuture<'a, Result<AfterSale, ServiceError>> {
+        Box::pin(async move {
+            apply.validate().map_err(ServiceError::BadRequest)?;
+            let order = match self.orders.find_by_id(order_id).await? {
+                Some(order) if order.user_id == user_id => order,
+                _ => return Err(ServiceError::NotFound(format!("Order with ID {} not found", order_id))),
+            };
+            let status = order.status().map_err(ServiceError::Conflict)?;
+            if !matches!(status, OrderStatus::Delivered | OrderStatus::Completed) {
+                return Err(ServiceError::Conflict(format!("order {} is {}, after-sales need a delivered order", order_id, status)));
+            }
+            let item = self
+                .orders
+                .find_items(order_id)
+                .await?
+                .into_iter()
+                .find(|item| item.id == apply.order_item_id)
+                .ok_or_else(|| ServiceError::NotFound(format!("Order item {} not found", apply.order_item_id)))?;
+            // 已退货或换货的件数不能再次申请，被拒绝的申请不占用件数
+            let claimed = claimed_quantity(&self.repo.list_by_item(item.id).await?);
+            let remaining = item.quantity.saturating_sub(claimed);
+            if apply.quantity > remaining {
+                return Err(ServiceError::BadRequest(format!("item {} only has {} pieces left for after-sales", item.id, remaining)));
+            }
+            if let Some(open) = self.repo.find_open_by_item(item.id).await? {
+                return Err(ServiceError::Conflict(format!("item {} already has after-sale {} in progress", item.id, open.after_sale_no)));
+            }
+
+            let (refund_amount, exchange_sku_id) = match apply.kind {
+                AfterSaleKind::Return => (return_amount(&item, apply.quantity), None),
+                AfterSaleKind::Exchange => (0, Some(self.exchange_sku(&item, apply.exchange_sku_id).await?)),
+            };
+            let new_after_sale = NewAfterSale {
+                after_sale_no: generate_after_sale_no(),
+                order_id,
+                order_item_id: item.id,
+                user_id,
+                kind: apply.kind,
+                reason: apply.reason,
+                description: apply.description.trim().to_string(),
+                photos: apply.photos.clone(),
+                quantity: apply.quantity,
+                refund_amount,
+                exchange_sku_id,
+            };
+            let id = self.repo.create(&new_after_sale).await?;
+            self.load(id).await
+        })
+    }
+
+    fn list_mine(&self, user_id: u32, page: u32, page_size: u32) -> BoxFuture<'_, Result<Vec<AfterSale>, ServiceError>> {
+        Box::pin(async move {
+            let page_size = page_size.clamp(1, 50);
+            let offset = page.saturating_sub(1) * page_size;
+            Ok(self.repo.list_by_user(user_id, page_size, offset).await?)
+        })
+    }
+
+    fn get(&self, user_id: u32, id: u64) -> BoxFuture<'_, Result<AfterSale, ServiceError>> {
+        Box::pin(async move { self.load_user(user_id, id).await })
+    }
+
+    fn ship_back<'a>(&'a self, user_id: u32, id: u64, carrier: &'a str, tracking_no: &'a str) -> BoxFuture<'a, Result<AfterSale, ServiceError>> {
+        Box::pin(async move {
+            let (carrier, tracking_no) = (carrier.trim(), tracking_no.trim());
+            if carrier.is_empty() || tracking_no.is_empty() {
+                return Err(ServiceError::BadRequest("carrier and tracking_no are required".to_string()));
+            }
+            let after_sale = self.load_user(user_id, id).await?;
+            check_transition(&after_sale, AfterSaleStatus::BuyerShipped)?;
+            if !self.repo.ship_back(id, carrier, tracking_no).await? {
+                return Err(ServiceError::Conflict(format!("after-sale {} changed concurrently", id)));
+            }
+            self.load(id).await
+        })
+    }
+
+    fn list_by_status<'a>(&'a self, status: &'a str, page: u32, page_size: u32) -> BoxFuture<'a, Result<Vec<AfterSale>, ServiceError>> {
+        Box::pin(async move {
+            let page_size = page_size.clamp(1, 100);
+            let offset = page.saturating_sub(1) * page_size;
+            Ok(self.repo.list_by_status(status, page_size, offset).await?)
+        })
+    }
+
+    fn detail(&self, id: u64) -> BoxFuture<'_, Result<AfterSaleDetail, ServiceError>> {
+        Box::pin(async move {
+            let after_sale = self.load(id).await?;
+            let notes = self.repo.list_notes(id).await?;
+            Ok(AfterSaleDetail { after_sale, notes })
+        })
+    }
+
+    fn approve(&self, admin_id: u32, id: u64) -> BoxFuture<'_, Result<AfterSale, ServiceError>> {
+        Box::pin(async move {
+            let after_sale = self.load(id).await?;
+            self.advance(&after_sale, AfterSaleStatus::Approved, Some(admin_id)).await
+        })
+    }
+
+    fn reject<'a>(&'a self, admin_id: u32, id: u64, reason: &'a str) -> BoxFuture<'a, Result<AfterSale, ServiceError>> {
+        Box::pin(async move {
+            let reason = reason.trim();
+            if reason.is_empty() {
+                return Err(ServiceError::BadRequest("reject reason is required".to_string()));
+            }
+            let after_sale = self.load(id).await?;
+            let from = check_transition(&after_sale, AfterSaleStatus::Rejected)?;
+            if !self.repo.reject(id, from, admin_id, reason).await? {
+                return Err(ServiceError::Conflict(format!("after-sale {} changed concurrently", id)));
+            }
+            self.load(id).await
+        })
+    }
+
+    fn receive(&self, admin_id: u32, id: u64) -> BoxFuture<'_, Result<AfterSale, ServiceError>> {
+        Box::pin(async move {
+            let after_sale = self.load(id).await?;
+            self.advance(&after_sale, AfterSaleStatus::Received, Some(admin_id)).await
+        })
+    }
+
+    fn refund(&self, admin_id: u32, id: u64) -> BoxFuture<'_, Result<AfterSale, ServiceError>> {
+        Box::pin(async move {
+            let mut after_sale = self.load(id).await?;
+            // 先占住售后单状态，避免重复发起退款；创建退款失败时回到已收货
+            if after_sale.refund_id.is_none() {
+                after_sale = self.advance(&after_sale, AfterSaleStatus::Refunding, Some(admin_id)).await?;
+                let reason = format!("after-sale {}", after_sale.after_sale_no);
+                let created = self
+                    .refunds
+                    .create_approved(admin_id, after_sale.order_id, after_sale.order_item_id, after_sale.refund_amount, &reason)
+                    .await;
+                let refund = match created {
+                    Ok(refund) => refund,
+                    Err(e) => {
+                        if let Err(rollback) = self.repo.set_status(id, AfterSaleStatus::Refunding, AfterSaleStatus::Received, None).await {
+                            tracing::error!("Failed to restore after-sale {} after refund error: {:?}", id, rollback);
+                        }
+                        return Err(e);
+                    }
+                };
+                self.repo.set_refund(id, refund.id).await?;
+                after_sale.refund_id = Some(refund.id);
+            }
+            let Some(refund_id) = after_sale.refund_id else {
+                return Err(ServiceError::Conflict(format!("after-sale {} has no refund", id)));
+            };
+            if after_sale.status() == Ok(AfterSaleStatus::Refunding) {
+                self.refunds.approve(admin_id, refund_id, false).await?;
+            }
+            self.settle_refund(&self.load(id).await?).await
+        })
+    }
+
+    fn exchange<'a>(&'a self, admin_id: u32, id: u64, carrier: &'a str, tracking_no: &'a str) -> BoxFuture<'a, Result<AfterSale, ServiceError>> {
+        Box::pin(async move {
+            let (carrier, tracking_no) = (carrier.trim(), tracking_no.trim());
+            if carrier.is_empty() || tracking_no.is_empty() {
+                return Err(ServiceError::BadRequest("carrier and tracking_no are required".to_string()));
+            }
+            let after_sale = self.load(id).await?;
+            check_transition(&after_sale, AfterSaleStatus::Exchanged)?;
+            let sku_id = after_sale
+                .exchange_sku_id
+                .ok_or_else(|| ServiceError::Conflict(format!("after-sale {} has no exchange SKU", id)))?;
+            // 换出的商品按售后单号预占库存；寄回的商品需验货，不自动入库
+            self.inventory
+                .reserve(sku_id, after_sale.user_id, after_sale.quantity, &after_sale.after_sale_no)
+                .await?;
+            if !self.repo.exchange(id, admin_id, carrier, tracking_no).await? {
+                if let Err(e) = self.inventory.release(sku_id, &after_sale.after_sale_no).await {
+                    tracing::error!("Failed to release exchange stock of after-sale {}: {:?}", after_sale.after_sale_no, e);
+                }
+                return Err(ServiceError::Conflict(format!("after-sale {} changed concurrently", id)));
+            }
+            self.load(id).await
+        })
+    }
+
+    fn add_note<'a>(&'a self, admin_id: u32, id: u64, note: &'a str) -> BoxFuture<'a, Result<AfterSaleDetail, ServiceError>> {
+        Box::pin(async move {
+            let note = note.trim();
+            if note.is_empty() || note.chars().count() > 1000 {
+                return Err(ServiceError::BadRequest("note must be 1 to 1000 characters".to_string()));
+            }
+            self.load(id).await?;
+            self.repo.add_note(id, admin_id, note).await?;
+            self.detail(id).await
+        })
+    }
+
+    fn on_refund_settled(&self, order_id: u64) -> BoxFuture<'_, Result<(), ServiceError>> {
+        Box::pin(async move {
+            for after_sale in self.repo.list_refunding(order_id).await? {
+                self.settle_refund(&after_sale).await?;
+            }
+            Ok(())
+        })
+    }
+}
+
+pub fn new_after_sale_service<R, O, C>(repo: Arc<R>, orders: Arc<O>, catalog: Arc<C>, deps: AfterSaleDeps) -> Arc<dyn AfterSaleService>
+where
+    R: AfterSaleRepo + 'static,
+    O: OrderRepo + 'static,
+    C: CatalogRepo + 'static,
+{
+    Arc::new(AfterSaleServiceImpl::new(repo, orders, catalog, deps)) as Arc<dyn AfterSaleService>
+}
+
+/// 订单离开退款中时把退款结果落到售后单上；重复投递时入库按售后单号去重
+pub struct AfterSaleRefundSubscriber {
+    service: Arc<dyn AfterSaleService>,
+}
+
+impl AfterSaleRefundSubscriber {
+    pub fn new(service: Arc<dyn AfterSaleService>) -> Arc<Self> {
+        Arc::new(Self { service })
+    }
+}
+
+impl EventSubscriber for AfterSaleRefundSubscriber {
+    fn name(&self) -> &'static str {
+        "after_sale_refund"
+    }
+
+    fn handles(&self, event_type: &str) -> bool {
+        // 售后订单都已签收，退款结束后回到已签收 / 已完成，或全额退完置为已退款
+        [OrderStatus::Delivered, OrderStatus::Completed, OrderStatus::Refunded]
+            .iter()
+            .any(|status| event_type.strip_prefix(ORDER_EVENT_PREFIX) == Some(status.as_str()))
+    }
+
+    fn handle<'a>(&'a self, event: &'a DomainEvent) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let changed: OrderStatusChanged = event.decode().map_err(ServiceError::BadRequest)?;
+            if changed.from != OrderStatus::Refunding.as_str() {
+                return Ok(());
+            }
+            self.service.on_refund_settled(changed.order_id).await
+        })
+    }
+}
+
+/// 订单行上已被售后单占用的件数，被拒绝的不计
+fn claimed_quantity(after_sales: &[AfterSale]) -> u32 {
+    after_sales
+        .iter()
+        .filter(|after_sale| after_sale.status() != Ok(AfterSaleStatus::Rejected))
+        .map(|after_sale| after_sale.quantity)
+        .sum()
+}
+
+/// 售后单号：S + 申请时间（秒）+ 8 位随机数
+fn generate_after_sale_no() -> String {
+    let suffix: u32 = rand::rng().random_range(0..100_000_000);
+    format!("S{}{:08}", Local::now().format("%Y%m%d%H%M%S"), suffix)
+}
+
+/// 校验状态流转，返回当前状态
+fn check_transition(after_sale: &AfterSale, to: AfterSaleStatus) -> Result<AfterSaleStatus, ServiceError> {
+    let from = after_sale.status().map_err(ServiceError::Conflict)?;
+    let kind = after_sale.kind().map_err(ServiceError::Conflict)?;
+    if !from.can_transition_to(to, kind) {
+        return Err(ServiceError::Conflict(format!("after-sale {} cannot move from {} to {}", after_sale.id, from, to)));
+    }
+    Ok(from)
+}
+
+/// 退货退款金额：按件数占比分摊行实付，整行退货时等于行实付
+fn return_amount(item: &OrderItem, quantity: u32) -> i64 {
+    if quantity >= item.quantity {
+        return item.payable_amount;
+    }
+    item.payable_amount * i64::from(quantity) / i64::from(item.quantity.max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(quantity: u32, payable_amount: i64) -> OrderItem {
+        OrderItem {
+            id: 1,
+            order_id: 1,
+            sku_id: 10,
+            product_id: 1,
+            product_name: String::new(),
+            sku_title: String::new(),
+            unit_price: 1000,
+            quantity,
+            line_total: 1000 * i64::from(quantity),
+            discount_amount: 1000 * i64::from(quantity) - payable_amount,
+            payable_amount,
+        }
+    }
+
+    #[test]
+    fn test_return_amount_is_proportional() {
+        assert_eq!(return_amount(&item(3, 2500), 1), 833);
+        assert_eq!(return_amount(&item(3, 2500), 2), 1666);
+        assert_eq!(return_amount(&item(3, 2500), 3), 2500);
+    }
+
+    fn after_sale(quantity: u32, status: AfterSaleStatus) -> AfterSale {
+        AfterSale {
+            id: 1,
+            after_sale_no: "S1".to_string(),
+            order_id: 1,
+            order_item_id: 1,
+            user_id: 1,
+            kind: AfterSaleKind::Exchange.as_str().to_string(),
+            reason: String::new(),
+            description: String::new(),
+            photos: sqlx::types::Json(Vec::new()),
+            quantity,
+            refund_amount: 0,
+            exchange_sku_id: Some(10),
+            status: status.as_str().to_string(),
+            return_carrier: None,
+            return_tracking_no: None,
+            exchange_carrier: None,
+            exchange_tracking_no: None,
+            refund_id: None,
+            admin_id: None,
+            reject_reason: None,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn test_claimed_quantity_skips_rejected() {
+        let previous = [
+            after_sale(1, AfterSaleStatus::Exchanged),
+            after_sale(2, AfterSaleStatus::Rejected),
+            after_sale(1, AfterSaleStatus::Refunded),
+        ];
+        assert_eq!(claimed_quantity(&previous), 2);
+        assert_eq!(claimed_quantity(&[]), 0);
+    }
+
+    #[test]
+    fn test_state_machine_follows_kind() {
+        use AfterSaleStatus::*;
+        let path = [Requested, Approved, BuyerShipped, Received];
+        for pair in path.windows(2) {
+            assert!(pair[0].can_transition_to(pair[1], AfterSaleKind::Return));
+        }
+        assert!(Received.can_transition_to(Refunding, AfterSaleKind::Return));
+        assert!(!Received.can_transition_to(Refunded, AfterSaleKind::Return));
+        assert!(Refunding.can_transition_to(Refunded, AfterSaleKind::Return));
+        assert!(Refunding.can_transition_to(Received, AfterSaleKind::Return));
+        assert!(!Received.can_transition_to(Exchanged, AfterSaleKind::Return));
+        assert!(Received.can_transition_to(Exchanged, AfterSaleKind::Exchange));
+        assert!(!Received.can_transition_to(Refunding, AfterSaleKind::Exchange));
+        assert!(Requested.can_transition_to(Rejected, AfterSaleKind::Exchange));
+        assert!(!Approved.can_transition_to(Received, AfterSaleKind::Return));
+    }
+}
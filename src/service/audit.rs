@@ -0,0 +1,179 @@
+use crate::domain::BoxFuture;
+use crate::domain::audit::AuditRepo;
+use crate::models::audit::{AuditChange, AuditLog, AuditQuery, NewAuditLog};
+use crate::service::ServiceError;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::fmt::Display;
+use std::sync::Arc;
+
+tokio::task_local! {
+    /// 当前请求中服务记录的变更，由审计中间件收集后写入日志
+    static CHANGES: RefCell<Vec<AuditChange>>;
+}
+
+/// 对比时忽略的字段
+const IGNORED_FIELDS: [&str; 1] = ["updated_at"];
+/// 请求体中需要脱敏的字段名（包含即脱敏，不区分大小写）
+const SECRET_KEYS: [&str; 4] = ["password", "passwd", "secret", "token"];
+
+pub trait AuditService: Send + Sync {
+    fn record(&self, log: NewAuditLog) -> BoxFuture<'_, Result<(), ServiceError>>;
+    fn search(&self, query: AuditQuery, page: u32, page_size: u32) -> BoxFuture<'_, Result<Vec<AuditLog>, ServiceError>>;
+}
+
+pub struct AuditServiceImpl<R: AuditRepo + 'static> {
+    repo: Arc<R>,
+}
+
+impl<R: AuditRepo + 'static> AuditServiceImpl<R> {
+    pub fn new(repo: Arc<R>) -> Self {
+        Self { repo }
+    }
+}
+
+impl<R: AuditRepo + 'static> AuditService for AuditServiceImpl<R> {
+    fn record(&self, log: NewAuditLog) -> BoxFuture<'_, Result<(), ServiceError>> {
+        Box::pin(async move {
+            self.repo.insert(&log).await?;
+            Ok(())
+        })
+    }
+
+    fn search(&self, query: AuditQuery, page: u32, page_size: u32) -> BoxFuture<'_, Result<Vec<AuditLog>, ServiceError>> {
+        Box::pin(async move {
+            if let (Some(from), Some(to)) = (query.from, query.to)
+                && from > to
+            {
+                return Err(ServiceError::BadRequest(format!("from {} is after to {}", from, to)));
+            }
+            let page_size = page_size.clamp(1, 100);
+            let offset = page.saturating_sub(1) * page_size;
+            Ok(self.repo.search(&query, page_size, offset).await?)
+        })
+    }
+}
+
+/// 执行 `future` 并收集期间通过 [`record_change`] 记录的变更
+pub async fn collect_changes<F: Future>(future: F) -> (F::Output, Vec<AuditChange>) {
+    CHANGES
+        .scope(RefCell::new(Vec::new()), async move {
+            let output = future.await;
+            (output, CHANGES.with(|changes| changes.take()))
+        })
+        .await
+}
+
+/// 服务在修改价格、退款、角色等敏感数据后调用；不在请求的审计范围内（如后台任务）时忽略
+pub fn record_change<T: Serialize>(entity: &str, entity_id: impl Display, before: Option<&T>, after: &T) {
+    if CHANGES.try_with(|_| ()).is_err() {
+        return;
+    }
+    if let Some(change) = diff(entity, &entity_id.to_string(), before, after) {
+        let _ = CHANGES.try_with(|changes| changes.borrow_mut().push(change));
+    }
+}
+
+/// 只保留前后不同的顶层字段；没有变化时返回 None
+pub fn diff<T: Serialize>(entity: &str, entity_id: &str, before: Option<&T>, after: &T) -> Option<AuditChange> {
+    let after = serde_json::to_value(after).ok()?;
+    let before = match before {
+        Some(before) => serde_json::to_value(before).ok()?,
+        None => Value::Null,
+    };
+    let (before, after) = match (before, after) {
+        (Value::Object(before), Value::Object(after)) => {
+            let keys: BTreeSet<&String> = before
+                .keys()
+                .chain(after.keys())
+                .filter(|key| !IGNORED_FIELDS.contains(&key.as_str()))
+                .collect();
+            let (mut old, mut new) = (Map::new(), Map::new());
+            for key in keys {
+                let (from, to) = (before.get(key).unwrap_or(&Value::Null), after.get(key).unwrap_or(&Value::Null));
+                if from != to {
+                    old.insert(key.clone(), from.clone());
+                    new.insert(key.clone(), to.clone());
+                }
+            }
+            if new.is_empty() {
+                return None;
+            }
+            (Value::Object(old), Value::Object(new))
+        }
+        (Value::Null, after) => (Value::Null, after),
+        (before, after) if before != after => (before, after),
+        _ => return None,
+    };
+    Some(AuditChange { entity: entity.to_string(), entity_id: entity_id.to_string(), before, after })
+}
+
+/// 从路由模板和实际路径得到操作对象：`/admin/` 后的第一段为类型，第一个路径参数为 ID
+pub fn target(route: &str, path: &str) -> (String, Option<String>) {
+    let segments: Vec<(&str, &str)> = route
+        .split('/')
+        .zip(path.split('/'))
+        .filter(|(template, _)| !template.is_empty() && *template != "admin")
+        .collect();
+    let target_type = segments.first().map_or("", |(template, _)| *template).to_string();
+    let target_id = segments
+        .iter()
+        .find(|(template, _)| template.starts_with('{'))
+        .map(|(_, value)| value.to_string());
+    (target_type, target_id)
+}
+
+/// 把请求体中的密码、密钥等字段替换为 "***"
+pub fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                let key = key.to_ascii_lowercase();
+                if SECRET_KEYS.iter().any(|secret| key.contains(secret)) {
+                    *value = Value::String("***".to_string());
+                } else {
+                    redact(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+pub fn new_audit_service<R: AuditRepo + 'static>(repo: Arc<R>) -> Arc<dyn AuditService> {
+    Arc::new(AuditServiceImpl::new(repo)) as Arc<dyn AuditService>
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_changes_are_collected_within_scope_only() {
+        let before = json!({"id": 1, "status": "pending", "amount": 500, "updated_at": "a"});
+        let after = json!({"id": 1, "status": "processing", "amount": 500, "updated_at": "b"});
+        record_change("refund", 1, Some(&before), &after);
+
+        let ((), changes) = collect_changes(async {
+            record_change("refund", 1, Some(&before), &after);
+            record_change("refund", 2, Some(&after), &after);
+        })
+        .await;
+        assert_eq!(changes, vec![AuditChange {
+            entity: "refund".into(),
+            entity_id: "1".into(),
+            before: json!({"status": "pending"}),
+            after: json!({"status": "processing"}),
+        }]);
+
+        assert_eq!(target("/admin/refunds/{id}/approve", "/admin/refunds/42/approve"), ("refunds".into(), Some("42".into())));
+        assert_eq!(target("/admin/stats/rollup", "/admin/stats/rollup"), ("stats".into(), None));
+        let mut body = json!({"user": {"Password": "x"}, "reason": "ok"});
+        redact(&mut body);
+        assert_eq!(body, json!({"user": {"Password": "***"}, "reason": "ok"}));
+    }
+}
@@ -0,0 +1,241 @@
+use crate::domain::BoxFuture;
+use crate::domain::cart::CartRepo;
+use crate::domain::catalog::CatalogRepo;
+use crate::models::cart::{CartLine, CartLineView, CartView, MAX_CART_LINES, MAX_LINE_QUANTITY};
+use crate::service::ServiceError;
+use crate::service::inventory::InventoryService;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub trait CartService: Send + Sync {
+    /// 校验 SKU 存在且在售、数量在允许范围内；游客和登录用户共用
+    fn check_line(&self, sku_id: u64, quantity: u32) -> BoxFuture<'_, Result<(), ServiceError>>;
+    /// 按当前价格和库存重新计算购物车，已下架或库存不足的行标记为不可结算
+    fn view(&self, lines: Vec<CartLine>) -> BoxFuture<'_, Result<CartView, ServiceError>>;
+    fn list(&self, user_id: u32) -> BoxFuture<'_, Result<Vec<CartLine>, ServiceError>>;
+    fn add(&self, user_id: u32, sku_id: u64, quantity: u32) -> BoxFuture<'_, Result<(), ServiceError>>;
+    fn set_quantity(&self, user_id: u32, sku_id: u64, quantity: u32) -> BoxFuture<'_, Result<(), ServiceError>>;
+    fn remove<'a>(&'a self, user_id: u32, sku_ids: &'a [u64]) -> BoxFuture<'a, Result<(), ServiceError>>;
+    fn select<'a>(&'a self, user_id: u32, sku_ids: &'a [u64], selected: bool) -> BoxFuture<'a, Result<(), ServiceError>>;
+    /// 登录成功后把游客购物车并入用户购物车，同一 SKU 数量累加
+    fn merge(&self, user_id: u32, guest_lines: Vec<CartLine>) -> BoxFuture<'_, Result<(), ServiceError>>;
+}
+
+pub struct CartServiceImpl<R: CartRepo + 'static, C: CatalogRepo + 'static> {
+    repo: Arc<R>,
+    catalog: Arc<C>,
+    inventory: Arc<dyn InventoryService>,
+}
+
+impl<R: CartRepo + 'static, C: CatalogRepo + 'static> CartServiceImpl<R, C> {
+    pub fn new(repo: Arc<R>, catalog: Arc<C>, inventory: Arc<dyn InventoryService>) -> Self {
+        Self { repo, catalog, inventory }
+    }
+}
+
+impl<R: CartRepo + 'static, C: CatalogRepo + 'static> CartService for CartServiceImpl<R, C> {
+    fn check_line(&self, sku_id: u64, quantity: u32) -> BoxFuture<'_, Result<(), ServiceError>> {
+        Box::pin(async move {
+            check_quantity(quantity)?;
+            let skus = self.catalog.find_skus(&[sku_id]).await?;
+            match skus.first() {
+                Some(sku) if sku.is_on_sale() => Ok(()),
+                Some(_) => Err(ServiceError::Conflict(format!("SKU {} is off sale", sku_id))),
+                None => Err(ServiceError::NotFound(format!("SKU {} not found", sku_id))),
+            }
+        })
+    }
+
+    fn view(&self, lines: Vec<CartLine>) -> BoxFuture<'_, Result<CartView, ServiceError>> {
+        Box::pin(async move {
+            let sku_ids: Vec<u64> = lines.iter().map(|line| line.sku_id).collect();
+            let skus: HashMap<u64, _> = self
+                .catalog
+                .find_skus(&sku_ids)
+                .await?
+                .into_iter()
+                .map(|sku| (sku.sku_id, sku))
+                .collect();
+
+            let mut view = CartView { lines: Vec::with_capacity(lines.len()), selected_quantity: 0, selected_total: 0 };
+            for line in lines {
+                // SKU 已被删除的行不再展示
+                let Some(sku) = skus.get(&line.sku_id) else {
+                    continue;
+                };
+                let stock = match self.inventory.available(line.sku_id).await {
+                    Ok(stock) => stock,
+                    Err(ServiceError::NotFound(_)) => 0,
+                    Err(e) => return Err(e),
+                };
+                let purchasable = sku.is_on_sale() && stock >= i64::from(line.quantity);
+                let line_total = sku.price * i64::from(line.quantity);
+                if line.selected && purchasable {
+                    view.selected_quantity += line.quantity;
+                    view.selected_total += line_total;
+                }
+                view.lines.push(CartLineView {
+                    sku_id: line.sku_id,
+                    product_id: sku.product_id,
+                    product_name: sku.product_name.clone(),
+                    sku_title: sku.title.clone(),
+                    unit_price: sku.price,
+                    quantity: line.quantity,
+                    selected: line.selected,
+                    stock,
+                    purchasable,
+                    line_total,
+                });
+            }
+            Ok(view)
+        })
+    }
+
+    fn list(&self, user_id: u32) -> BoxFuture<'_, Result<Vec<CartLine>, ServiceError>> {
+        Box::pin(async move { Ok(self.repo.list(user_id).await?) })
+    }
+
+    fn add(&self, user_id: u32, sku_id: u64, quantity: u32) -> BoxFuture<'_, Result<(), ServiceError>> {
+        Box::pin(async move {
+            check_quantity(quantity)?;
+            let lines = self.repo.list(user_id).await?;
+            check_line_count(&lines, sku_id)?;
+            Ok(self.repo.add(user_id, sku_id, quantity, MAX_LINE_QUANTITY).await?)
+        })
+    }
+
+    fn set_quantity(&self, user_id: u32, sku_id: u64, quantity: u32) -> BoxFuture<'_, Result<(), ServiceError>> {
+        Box::pin(async move {
+            check_quantity(quantity)?;
+            if self.repo.set_quantity(user_id, sku_id, quantity).await? {
+                Ok(())
+            } else {
+                Err(ServiceError::NotFound(format!("SKU {} is not in cart", sku_id)))
+            }
+        })
+    }
+
+    fn remove<'a>(&'a self, user_id: u32, sku_ids: &'a [u64]) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move { Ok(self.repo.remove(user_id, sku_ids).await?) })
+    }
+
+    fn select<'a>(&'a self, user_id: u32, sku_ids: &'a [u64], selected: bool) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move { Ok(self.repo.set_selected(user_id, sku_ids, selected).await?) })
+    }
+
+    fn merge(&self, user_id: u32, guest_lines: Vec<CartLine>) -> BoxFuture<'_, Result<(), ServiceError>> {
+        Box::pin(async move {
+            if guest_lines.is_empty() {
+                return Ok(());
+            }
+            // 合并后超出行数上限的游客行直接丢弃，已在用户购物车中的 SKU 只累加数量
+            let mut lines = self.repo.list(user_id).await?;
+            let mut merged = Vec::with_capacity(guest_lines.len());
+            for line in guest_lines {
+                if lines.iter().any(|existing| existing.sku_id == line.sku_id) {
+                    merged.push(line);
+                } else if lines.len() < MAX_CART_LINES {
+                    lines.push(line.clone());
+                    merged.push(line);
+                }
+            }
+            Ok(self.repo.merge(user_id, &merged, MAX_LINE_QUANTITY).await?)
+        })
+    }
+}
+
+pub fn new_cart_service<R, C>(repo: Arc<R>, catalog: Arc<C>, inventory: Arc<dyn InventoryService>) -> Arc<dyn CartService>
+where
+    R: CartRepo + 'static,
+    C: CatalogRepo + 'static,
+{
+    Arc::new(CartServiceImpl::new(repo, catalog, inventory)) as Arc<dyn CartService>
+}
+
+fn check_quantity(quantity: u32) -> Result<(), ServiceError> {
+    if quantity == 0 || quantity > MAX_LINE_QUANTITY {
+        return Err(ServiceError::BadRequest(format!("quantity must be between 1 and {}", MAX_LINE_QUANTITY)));
+    }
+    Ok(())
+}
+
+fn check_line_count(lines: &[CartLine], sku_id: u64) -> Result<(), ServiceError> {
+    if lines.len() >= MAX_CART_LINES && !lines.iter().any(|line| line.sku_id == sku_id) {
+        return Err(ServiceError::Conflict(format!("cart cannot hold more than {} items", MAX_CART_LINES)));
+    }
+    Ok(())
+}
+
+// 以下是游客购物车（Session）上的同等操作，语义与 `cart_items` 保持一致
+
+pub fn add_line(lines: &mut Vec<CartLine>, sku_id: u64, quantity: u32) -> Result<(), ServiceError> {
+    check_quantity(quantity)?;
+    check_line_count(lines, sku_id)?;
+    match lines.iter_mut().find(|line| line.sku_id == sku_id) {
+        Some(line) => line.quantity = (line.quantity + quantity).min(MAX_LINE_QUANTITY),
+        None => lines.insert(0, CartLine { sku_id, quantity, selected: true }),
+    }
+    Ok(())
+}
+
+pub fn set_line_quantity(lines: &mut [CartLine], sku_id: u64, quantity: u32) -> Result<(), ServiceError> {
+    check_quantity(quantity)?;
+    let line = lines
+        .iter_mut()
+        .find(|line| line.sku_id == sku_id)
+        .ok_or_else(|| ServiceError::NotFound(format!("SKU {} is not in cart", sku_id)))?;
+    line.quantity = quantity;
+    Ok(())
+}
+
+pub fn remove_lines(lines: &mut Vec<CartLine>, sku_ids: &[u64]) {
+    lines.retain(|line| !sku_ids.contains(&line.sku_id));
+}
+
+pub fn select_lines(lines: &mut [CartLine], sku_ids: &[u64], selected: bool) {
+    for line in lines.iter_mut().filter(|line| sku_ids.contains(&line.sku_id)) {
+        line.selected = selected;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_line_accumulates_and_caps() {
+        let mut lines = Vec::new();
+        add_line(&mut lines, 1, 2).unwrap();
+        add_line(&mut lines, 2, 1).unwrap();
+        add_line(&mut lines, 1, 98).unwrap();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].sku_id, 2);
+        assert_eq!(lines[1].quantity, MAX_LINE_QUANTITY);
+        assert!(add_line(&mut lines, 3, MAX_LINE_QUANTITY + 1).is_err());
+    }
+
+    #[test]
+    fn test_add_line_caps_line_count() {
+        let mut lines = Vec::new();
+        for sku_id in 0..MAX_CART_LINES as u64 {
+            add_line(&mut lines, sku_id, 1).unwrap();
+        }
+        assert!(matches!(add_line(&mut lines, 1000, 1), Err(ServiceError::Conflict(_))));
+        // 已在购物车中的 SKU 仍可累加
+        add_line(&mut lines, 0, 1).unwrap();
+        assert_eq!(lines.len(), MAX_CART_LINES);
+    }
+
+    #[test]
+    fn test_guest_line_updates() {
+        let mut lines = Vec::new();
+        add_line(&mut lines, 1, 1).unwrap();
+        add_line(&mut lines, 2, 1).unwrap();
+        select_lines(&mut lines, &[1], false);
+        set_line_quantity(&mut lines, 2, 5).unwrap();
+        assert!(set_line_quantity(&mut lines, 3, 1).is_err());
+        assert!(set_line_quantity(&mut lines, 2, MAX_LINE_QUANTITY + 1).is_err());
+        remove_lines(&mut lines, &[1]);
+        assert_eq!(lines, vec![CartLine { sku_id: 2, quantity: 5, selected: true }]);
+    }
+}
@@ -0,0 +1,464 @@
+use crate::domain::BoxFuture;
+use crate::domain::coupon::CouponRepo;
+use crate::models::coupon::{
+    ClaimOutcome, CouponKind, CouponScope, CouponTemplate, MyCoupon, NewCouponTemplate, TEMPLATE_DISABLED, UserCoupon,
+};
+use crate::models::event::{DomainEvent, ORDER_EVENT_PREFIX, OrderStatusChanged};
+use crate::models::order::OrderStatus;
+use crate::service::ServiceError;
+use crate::service::events::EventSubscriber;
+use crate::service::pricing::{Adjustment, PricingInput, PricingStep, Quote};
+use chrono::Local;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// 计价明细中优惠券的来源标识
+const COUPON_SOURCE: &str = "coupon";
+
+pub trait CouponService: Send + Sync {
+    /// 当前可领取的券
+    fn list_claimable(&self) -> BoxFuture<'_, Result<Vec<CouponTemplate>, ServiceError>>;
+    fn claim(&self, user_id: u32, template_id: u64) -> BoxFuture<'_, Result<UserCoupon, ServiceError>>;
+    fn list_mine(&self, user_id: u32) -> BoxFuture<'_, Result<Vec<MyCoupon>, ServiceError>>;
+    /// 校验用户的券当前可用，返回其模板用于结算计价
+    fn checkout_template(&self, user_id: u32, coupon_id: u64) -> BoxFuture<'_, Result<CouponTemplate, ServiceError>>;
+    /// 下单时把券锁定到订单，已被其他订单锁定或已过期时返回冲突
+    fn lock<'a>(&'a self, user_id: u32, coupon_id: u64, order_no: &'a str) -> BoxFuture<'a, Result<(), ServiceError>>;
+    /// 订单取消后退回锁定的券，订单没有用券时什么都不做
+    fn release<'a>(&'a self, order_no: &'a str) -> BoxFuture<'a, Result<(), ServiceError>>;
+    /// 订单支付成功后核销锁定的券；失败时返回错误，由 `order.paid` 事件重新投递
+    fn consume<'a>(&'a self, order_no: &'a str) -> BoxFuture<'a, Result<(), ServiceError>>;
+    fn create_template<'a>(&'a self, template: &'a NewCouponTemplate) -> BoxFuture<'a, Result<CouponTemplate, ServiceError>>;
+    fn list_templates(&self, page: u32, page_size: u32) -> BoxFuture<'_, Result<Vec<CouponTemplate>, ServiceError>>;
+    /// 停发模板，已领取的券仍可使用
+    fn disable_template(&self, id: u64) -> BoxFuture<'_, Result<CouponTemplate, ServiceError>>;
+}
+
+pub struct CouponServiceImpl<R: CouponRepo + 'static> {
+    repo: Arc<R>,
+}
+
+impl<R: CouponRepo + 'static> CouponServiceImpl<R> {
+    pub fn new(repo: Arc<R>) -> Self {
+        Self { repo }
+    }
+
+    async fn load_template(&self, id: u64) -> Result<CouponTemplate, ServiceError> {
+        self.repo
+            .find_template(id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Coupon template with ID {} not found", id)))
+    }
+
+    async fn load_user_coupon(&self, user_id: u32, coupon_id: u64) -> Result<UserCoupon, ServiceError> {
+        match self.repo.find_user_coupon(coupon_id).await? {
+            Some(coupon) if coupon.user_id == user_id => Ok(coupon),
+            _ => Err(ServiceError::NotFound(format!("Coupon with ID {} not found", coupon_id))),
+        }
+    }
+}
+
+impl<R: CouponRepo + 'static> CouponService for CouponServiceImpl<R> {
+    fn list_claimable(&self) -> BoxFuture<'_, Result<Vec<CouponTemplate>, ServiceError>> {
+        Box::pin(async move { Ok(self.repo.list_claimable(Local::now()).await?) })
+    }
+
+    fn claim(&self, user_id: u32, template_id: u64) -> BoxFuture<'_, Result<UserCoupon, ServiceError>> {
+        Box::pin(async move {
+            let coupon_id = match self.repo.claim(template_id, user_id, Local::now()).await? {
+                ClaimOutcome::Claimed(id) => id,
+                ClaimOutcome::Unavailable => {
+                    return Err(ServiceError::NotFound(format!("Coupon template with ID {} is not claimable", template_id)));
+                }
+                ClaimOutcome::SoldOut => return Err(ServiceError::Conflict("coupon is sold out".to_string())),
+                ClaimOutcome::LimitReached => {
+                    return Err(ServiceError::Conflict("coupon claim limit reached".to_string()));
+                }
+            };
+            self.load_user_coupon(user_id, coupon_id).await
+        })
+    }
+
+    fn list_mine(&self, user_id: u32) -> BoxFuture<'_, Result<Vec<MyCoupon>, ServiceError>> {
+        Box::pin(async move {
+            let coupons = self.repo.list_user_coupons(user_id).await?;
+            let mut template_ids: Vec<u64> = coupons.iter().map(|coupon| coupon.template_id).collect();
+            template_ids.sort_unstable();
+            template_ids.dedup();
+            let templates: HashMap<u64, CouponTemplate> = self
+                .repo
+                .find_templates(&template_ids)
+                .await?
+                .into_iter()
+                .map(|template| (template.id, template))
+                .collect();
+
+            let now = Local::now();
+            Ok(coupons
+                .into_iter()
+                .filter_map(|coupon| {
+                    let template = templates.get(&coupon.template_id)?.clone();
+                    Some(MyCoupon { usable: coupon.is_usable(now), coupon, template })
+                })
+                .collect())
+        })
+    }
+
+    fn checkout_template(&self, user_id: u32, coupon_id: u64) -> BoxFuture<'_, Result<CouponTemplate, ServiceError>> {
+        Box::pin(async move {
+            let coupon = self.load_user_coupon(user_id, coupon_id).await?;
+            if !coupon.is_usable(Local::now()) {
+                return Err(ServiceError::Conflict(format!("coupon {} is used or expired", coupon_id)));
+            }
+            self.load_template(coupon.template_id).await
+        })
+    }
+
+    fn lock<'a>(&'a self, user_id: u32, coupon_id: u64, order_no: &'a str) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move {
+            if !self.repo.lock(coupon_id, user_id, order_no, Local::now()).await? {
+                return Err(ServiceError::Conflict(format!("coupon {} is used or expired", coupon_id)));
+            }
+            Ok(())
+        })
+    }
+
+    fn release<'a>(&'a self, order_no: &'a str) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move {
+            if self.repo.release(order_no).await? {
+                tracing::info!("Released coupon of order {}", order_no);
+            }
+            Ok(())
+        })
+    }
+
+    fn consume<'a>(&'a self, order_no: &'a str) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move {
+            if self.repo.consume(order_no, Local::now()).await? {
+                tracing::info!("Consumed coupon of order {}", order_no);
+            }
+            Ok(())
+        })
+    }
+
+    fn create_template<'a>(&'a self, template: &'a NewCouponTemplate) -> BoxFuture<'a, Result<CouponTemplate, ServiceError>> {
+        Box::pin(async move {
+            template.validate().map_err(ServiceError::BadRequest)?;
+            let id = self.repo.create_template(template).await?;
+            self.load_template(id).await
+        })
+    }
+
+    fn list_templates(&self, page: u32, page_size: u32) -> BoxFuture<'_, Result<Vec<CouponTemplate>, ServiceError>> {
+        Box::pin(async move {
+            let page_size = page_size.clamp(1, 100);
+            let offset = page.saturating_sub(1) * page_size;
+            Ok(self.repo.list_templates(page_size, offset).await?)
+        })
+    }
+
+    fn disable_template(&self, id: u64) -> BoxFuture<'_, Result<CouponTemplate, ServiceError>> {
+        Box::pin(async move {
+            self.load_template(id).await?;
+            self.repo.set_template_status(id, TEMPLATE_DISABLED).await?;
+            self.load_template(id).await
+        })
+    }
+}
+
+pub fn new_coupon_service<R: CouponRepo + 'static>(repo: Arc<R>) -> Arc<dyn CouponService> {
+    Arc::new(CouponServiceImpl::new(repo)) as Arc<dyn CouponService>
+}
+
+/// 订单支付成功后核销锁定的优惠券；只更新锁定中的券，重复投递不会重复核销
+pub struct CouponConsumeSubscriber {
+    service: Arc<dyn CouponService>,
+}
+
+impl CouponConsumeSubscriber {
+    pub fn new(service: Arc<dyn CouponService>) -> Arc<Self> {
+        Arc::new(Self { service })
+    }
+}
+
+impl EventSubscriber for CouponConsumeSubscriber {
+    fn name(&self) -> &'static str {
+        "coupon_consume"
+    }
+
+    fn handles(&self, event_type: &str) -> bool {
+        event_type.strip_prefix(ORDER_EVENT_PREFIX) == Some(OrderStatus::Paid.as_str())
+    }
+
+    fn handle<'a>(&'a self, event: &'a DomainEvent) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let changed: OrderStatusChanged = event.decode().map_err(ServiceError::BadRequest)?;
+            self.service.consume(&changed.order_no).await
+        })
+    }
+}
+
+/// 结算时使用的优惠券。免运费券需要放在运费步骤之后，其余放在运费之前，
+/// 这样包邮门槛按券后金额判断。
+pub struct CouponStep {
+    template: CouponTemplate,
+    kind: CouponKind,
+    scope: CouponScope,
+}
+
+impl CouponStep {
+    pub fn new(template: CouponTemplate) -> Result<Self, ServiceError> {
+        let kind = template.kind.parse().map_err(ServiceError::Conflict)?;
+        let scope = template.scope.parse().map_err(ServiceError::Conflict)?;
+        Ok(Self { template, kind, scope })
+    }
+
+    pub fn is_shipping(&self) -> bool {
+        self.kind == CouponKind::FreeShipping
+    }
+
+    /// 适用范围内的商品行
+    fn eligible_lines(&self, input: &PricingInput) -> Vec<usize> {
+        let ids = &self.template.scope_ids.0;
+        input
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| match self.scope {
+                CouponScope::All => true,
+                CouponScope::Category => ids.contains(&item.category_id),
+                CouponScope::Product => ids.contains(&item.product_id),
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// 按适用商品的应付小计计算减免金额
+    fn discount(&self, subtotal: i64) -> i64 {
+        match self.kind {
+            CouponKind::Fixed | CouponKind::Threshold => self.template.amount,
+            CouponKind::Percent => {
+                let discount = subtotal * i64::from(self.template.percent_off) / 100;
+                if self.template.max_discount > 0 {
+                    discount.min(self.template.max_discount)
+                } else {
+                    discount
+                }
+            }
+            CouponKind::FreeShipping => 0,
+        }
+    }
+}
+
+impl PricingStep for CouponStep {
+    fn apply(&self, input: &PricingInput, quote: &mut Quote) -> Result<(), ServiceError> {
+        let lines = self.eligible_lines(input);
+        if lines.is_empty() {
+            return Err(ServiceError::Conflict(format!("coupon {} does not apply to any item", self.template.name)));
+        }
+        let subtotal: i64 = lines.iter().map(|&i| quote.lines[i].payable_amount).sum();
+        if subtotal < self.template.threshold {
+            return Err(ServiceError::Conflict(format!(
+                "coupon {} requires {} of eligible items, got {}",
+                self.template.name, self.template.threshold, subtotal
+            )));
+        }
+
+        if self.is_shipping() {
+            if quote.shipping_fee > 0 {
+                quote.adjustments.push(Adjustment {
+                    source: COUPON_SOURCE.to_string(),
+                    description: self.template.name.clone(),
+                    amount: quote.shipping_fee,
+                });
+                quote.shipping_fee = 0;
+            }
+        } else {
+            quote.apply_discount(COUPON_SOURCE, &self.template.name, self.discount(subtotal), &lines);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::order::{Actor, Order};
+    use crate::service::pricing::{FlatShipping, PricingItem, PricingPipeline};
+    use sqlx::types::Json;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn template(kind: CouponKind, scope: CouponScope, scope_ids: Vec<u64>) -> CouponTemplate {
+        let now = Local::now();
+        CouponTemplate {
+            id: 1,
+            name: "券".to_string(),
+            kind: kind.as_str().to_string(),
+            threshold: 0,
+            amount: 0,
+            percent_off: 0,
+            max_discount: 0,
+            scope: scope.as_str().to_string(),
+            scope_ids: Json(scope_ids),
+            total_quota: 100,
+            issued: 0,
+            per_user_limit: 1,
+            valid_from: now,
+            valid_to: now,
+            valid_days: 0,
+            status: "active".to_string(),
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    fn input() -> PricingInput {
+        let item = |sku_id: u64, category_id: u64, unit_price: i64| PricingItem {
+            sku_id,
+            product_id: sku_id,
+            category_id,
+            product_name: format!("p{}", sku_id),
+            sku_title: format!("s{}", sku_id),
+            unit_price,
+            quantity: 1,
+            weight_grams: 0,
+            freight_template_id: None,
+        };
+        PricingInput { items: vec![item(1, 10, 6000), item(2, 20, 4000)] }
+    }
+
+    fn quote(template: CouponTemplate) -> Result<Quote, ServiceError> {
+        let step = CouponStep::new(template)?;
+        let shipping = Box::new(FlatShipping { fee: 800, free_threshold: 0 });
+        let steps: Vec<Box<dyn PricingStep>> = if step.is_shipping() {
+            vec![shipping, Box::new(step)]
+        } else {
+            vec![Box::new(step), shipping]
+        };
+        PricingPipeline::new(steps).quote(&input())
+    }
+
+    #[test]
+    fn test_threshold_coupon_limited_to_category() {
+        let mut t = template(CouponKind::Threshold, CouponScope::Category, vec![10]);
+        t.threshold = 5000;
+        t.amount = 1000;
+        let discounted = quote(t.clone()).unwrap();
+        assert_eq!(discounted.lines[0].discount_amount, 1000);
+        assert_eq!(discounted.lines[1].discount_amount, 0);
+        assert_eq!(discounted.payable_amount, 9800);
+
+        t.threshold = 6001;
+        assert!(matches!(quote(t), Err(ServiceError::Conflict(_))));
+    }
+
+    #[test]
+    fn test_percent_coupon_is_capped() {
+        let mut t = template(CouponKind::Percent, CouponScope::All, Vec::new());
+        t.percent_off = 15;
+        assert_eq!(quote(t.clone()).unwrap().discount_amount, 1500);
+        t.max_discount = 1000;
+        assert_eq!(quote(t).unwrap().discount_amount, 1000);
+    }
+
+    #[test]
+    fn test_free_shipping_and_scope_mismatch() {
+        let mut t = template(CouponKind::FreeShipping, CouponScope::All, Vec::new());
+        t.threshold = 9000;
+        let free = quote(t).unwrap();
+        assert_eq!(free.shipping_fee, 0);
+        assert_eq!(free.payable_amount, 10000);
+        assert_eq!(free.adjustments[0].amount, 800);
+
+        let t = template(CouponKind::Fixed, CouponScope::Product, vec![99]);
+        assert!(matches!(quote(t), Err(ServiceError::Conflict(_))));
+    }
+
+    /// 只实现核销的券服务，前 `failures` 次核销失败
+    #[derive(Default)]
+    struct FlakyCoupons {
+        consumed: Mutex<Vec<String>>,
+        failures: AtomicU32,
+    }
+
+    impl CouponService for FlakyCoupons {
+        fn list_claimable(&self) -> BoxFuture<'_, Result<Vec<CouponTemplate>, ServiceError>> {
+            unreachable!()
+        }
+        fn claim(&self, _user_id: u32, _template_id: u64) -> BoxFuture<'_, Result<UserCoupon, ServiceError>> {
+            unreachable!()
+        }
+        fn list_mine(&self, _user_id: u32) -> BoxFuture<'_, Result<Vec<MyCoupon>, ServiceError>> {
+            unreachable!()
+        }
+        fn checkout_template(&self, _user_id: u32, _coupon_id: u64) -> BoxFuture<'_, Result<CouponTemplate, ServiceError>> {
+            unreachable!()
+        }
+        fn lock<'a>(&'a self, _user_id: u32, _coupon_id: u64, _order_no: &'a str) -> BoxFuture<'a, Result<(), ServiceError>> {
+            unreachable!()
+        }
+        fn release<'a>(&'a self, _order_no: &'a str) -> BoxFuture<'a, Result<(), ServiceError>> {
+            unreachable!()
+        }
+        fn consume<'a>(&'a self, order_no: &'a str) -> BoxFuture<'a, Result<(), ServiceError>> {
+            let failed = self.failures.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1)).is_ok();
+            if !failed {
+                self.consumed.lock().unwrap().push(order_no.to_string());
+            }
+            Box::pin(async move {
+                if failed {
+                    Err(ServiceError::Database(sqlx::Error::PoolTimedOut))
+                } else {
+                    Ok(())
+                }
+            })
+        }
+        fn create_template<'a>(&'a self, _template: &'a NewCouponTemplate) -> BoxFuture<'a, Result<CouponTemplate, ServiceError>> {
+            unreachable!()
+        }
+        fn list_templates(&self, _page: u32, _page_size: u32) -> BoxFuture<'_, Result<Vec<CouponTemplate>, ServiceError>> {
+            unreachable!()
+        }
+        fn disable_template(&self, _id: u64) -> BoxFuture<'_, Result<CouponTemplate, ServiceError>> {
+            unreachable!()
+        }
+    }
+
+    fn paid_event() -> DomainEvent {
+        let order = Order {
+            id: 1,
+            order_no: "NO1".into(),
+            user_id: 1,
+            status: OrderStatus::Paid.as_str().to_string(),
+            goods_amount: 100,
+            discount_amount: 0,
+            shipping_fee: 0,
+            payable_amount: 100,
+            receiver_name: "r".into(),
+            receiver_phone: "p".into(),
+            province: "p".into(),
+            city: "c".into(),
+            district: "d".into(),
+            address_detail: "a".into(),
+            created_at: None,
+            updated_at: None,
+        };
+        let changed = OrderStatusChanged::new(&order, OrderStatus::PendingPayment, OrderStatus::Paid, Actor::system(), "paid");
+        DomainEvent::order_status_changed(&changed)
+    }
+
+    #[tokio::test]
+    async fn test_consume_subscriber_fails_so_the_event_is_redelivered() {
+        let coupons = Arc::new(FlakyCoupons { failures: AtomicU32::new(1), ..Default::default() });
+        let subscriber = CouponConsumeSubscriber::new(coupons.clone());
+        assert!(subscriber.handles("order.paid"));
+        assert!(!subscriber.handles("order.cancelled"));
+
+        let event = paid_event();
+        assert!(subscriber.handle(&event).await.is_err());
+        assert!(coupons.consumed.lock().unwrap().is_empty());
+        subscriber.handle(&event).await.unwrap();
+        assert_eq!(*coupons.consumed.lock().unwrap(), ["NO1"]);
+    }
+}
@@ -0,0 +1,265 @@
+use crate::domain::BoxFuture;
+use crate::domain::events::OutboxRepo;
+use crate::models::event::{DomainEvent, OutboxEvent};
+use crate::service::ServiceError;
+use crate::service::jobs::backoff_millis;
+use chrono::Local;
+use std::sync::Arc;
+use std::time::Duration;
+use wx_shop::OutboxSettings;
+
+/// 领域事件的订阅者。事件至少投递一次，任一订阅者失败时整条事件会重新投递给所有订阅者，实现必须是幂等的。
+pub trait EventSubscriber: Send + Sync {
+    fn name(&self) -> &'static str;
+    /// 是否关心该类型的事件
+    fn handles(&self, event_type: &str) -> bool;
+    fn handle<'a>(&'a self, event: &'a DomainEvent) -> BoxFuture<'a, Result<(), ServiceError>>;
+}
+
+/// 进程内事件总线，由发件箱转发协程调用
+#[derive(Default)]
+pub struct EventBus {
+    subscribers: Vec<Arc<dyn EventSubscriber>>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subscribe(mut self, subscriber: Arc<dyn EventSubscriber>) -> Self {
+        self.subscribers.push(subscriber);
+        self
+    }
+
+    /// 依次交给所有关心该事件的订阅者，全部执行后返回第一个错误
+    pub async fn publish(&self, event: &DomainEvent) -> Result<(), ServiceError> {
+        let mut first_error = None;
+        for subscriber in self.subscribers.iter().filter(|s| s.handles(&event.event_type)) {
+            if let Err(e) = subscriber.handle(event).await {
+                tracing::warn!("Subscriber {} failed on {} {}: {:?}", subscriber.name(), event.event_type, event.aggregate_id, e);
+                first_error.get_or_insert(e);
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+}
+
+/// 把发件箱中到期的事件转发到事件总线。多实例部署时以乐观锁领取，同一条事件同一时刻只有一个实例在投递；
+/// 失败的事件按退避时间重试，不保证同一订单的事件按顺序送达。
+pub struct OutboxRelay {
+    repo: Arc<dyn OutboxRepo>,
+    bus: Arc<EventBus>,
+    settings: OutboxSettings,
+}
+
+impl OutboxRelay {
+    pub fn new(repo: Arc<dyn OutboxRepo>, bus: Arc<EventBus>, settings: OutboxSettings) -> Self {
+        Self { repo, bus, settings }
+    }
+
+    pub fn spawn(self) {
+        tokio::spawn(async move {
+            let poll_interval = Duration::from_millis(self.settings.poll_interval_ms.max(1));
+            loop {
+                match self.relay_once().await {
+                    Ok(count) if count > 0 => {}
+                    Ok(_) => tokio::time::sleep(poll_interval).await,
+                    Err(e) => {
+                        tracing::error!("Outbox relay failed: {:?}", e);
+                        tokio::time::sleep(poll_interval).await;
+                    }
+                }
+            }
+        });
+    }
+
+    /// 处理一批到期事件，返回领取到的条数
+    pub async fn relay_once(&self) -> Result<usize, ServiceError> {
+        let now = Local::now();
+        let due = self.repo.fetch_due(now, self.settings.max_attempts, self.settings.batch_size).await?;
+        let lease_until = now + chrono::Duration::seconds(self.settings.lease_secs as i64);
+        let mut claimed = 0;
+        for row in due {
+            if !self.repo.claim(row.id, row.attempts, lease_until).await? {
+                continue;
+            }
+            claimed += 1;
+            self.deliver(&row).await?;
+        }
+        Ok(claimed)
+    }
+
+    async fn deliver(&self, row: &OutboxEvent) -> Result<(), ServiceError> {
+        let result = match row.to_event() {
+            Ok(event) => self.bus.publish(&event).await,
+            Err(e) => Err(ServiceError::BadRequest(e)),
+        };
+        match result {
+            Ok(()) => self.repo.mark_published(row.id, Local::now()).await?,
+            Err(e) => {
+                let attempts = row.attempts + 1;
+                if attempts >= self.settings.max_attempts {
+                    tracing::error!("Outbox event {} gave up after {} attempts: {:?}", row.id, attempts, e);
+                }
+                let error: String = format!("{:?}", e).chars().take(500).collect();
+                let next_attempt_at = Local::now() + chrono::Duration::milliseconds(backoff_millis(attempts));
+                self.repo.mark_failed(row.id, &error, next_attempt_at).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[derive(Default)]
+    struct MemoryOutbox {
+        rows: Mutex<Vec<OutboxEvent>>,
+    }
+
+    impl MemoryOutbox {
+        fn with_event(event_type: &str) -> Arc<Self> {
+            let repo = MemoryOutbox::default();
+            repo.rows.lock().unwrap().push(OutboxEvent {
+                id: 1,
+                event_type: event_type.to_string(),
+                aggregate_id: "1".to_string(),
+                payload: r#"{"order_id":1}"#.to_string(),
+                attempts: 0,
+                next_attempt_at: Local::now() - chrono::Duration::seconds(1),
+                published_at: None,
+                last_error: None,
+                created_at: None,
+            });
+            Arc::new(repo)
+        }
+
+        fn row(&self) -> OutboxEvent {
+            self.rows.lock().unwrap()[0].clone()
+        }
+
+        /// 模拟时间流逝，让退避中的事件立即到期
+        fn make_due(&self) {
+            self.rows.lock().unwrap()[0].next_attempt_at = Local::now() - chrono::Duration::seconds(1);
+        }
+    }
+
+    impl OutboxRepo for MemoryOutbox {
+        fn fetch_due(&self, now: DateTime<Local>, max_attempts: u32, limit: u32) -> BoxFuture<'_, Result<Vec<OutboxEvent>, sqlx::Error>> {
+            let rows = self
+                .rows
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|r| r.published_at.is_none() && r.next_attempt_at <= now && r.attempts < max_attempts)
+                .take(limit as usize)
+                .cloned()
+                .collect();
+            Box::pin(async move { Ok(rows) })
+        }
+
+        fn claim(&self, id: u64, attempts: u32, lease_until: DateTime<Local>) -> BoxFuture<'_, Result<bool, sqlx::Error>> {
+            let mut rows = self.rows.lock().unwrap();
+            let claimed = match rows.iter_mut().find(|r| r.id == id && r.attempts == attempts && r.published_at.is_none()) {
+                Some(row) => {
+                    row.attempts += 1;
+                    row.next_attempt_at = lease_until;
+                    true
+                }
+                None => false,
+            };
+            Box::pin(async move { Ok(claimed) })
+        }
+
+        fn mark_published(&self, id: u64, at: DateTime<Local>) -> BoxFuture<'_, Result<(), sqlx::Error>> {
+            if let Some(row) = self.rows.lock().unwrap().iter_mut().find(|r| r.id == id) {
+                row.published_at = Some(at);
+            }
+            Box::pin(async { Ok(()) })
+        }
+
+        fn mark_failed<'a>(&'a self, id: u64, error: &'a str, next_attempt_at: DateTime<Local>) -> BoxFuture<'a, Result<(), sqlx::Error>> {
+            if let Some(row) = self.rows.lock().unwrap().iter_mut().find(|r| r.id == id) {
+                row.last_error = Some(error.to_string());
+                row.next_attempt_at = next_attempt_at;
+            }
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    /// 前 `failures` 次调用失败
+    struct FlakySubscriber {
+        failures: u32,
+        calls: AtomicU32,
+    }
+
+    impl EventSubscriber for FlakySubscriber {
+        fn name(&self) -> &'static str {
+            "flaky"
+        }
+
+        fn handles(&self, event_type: &str) -> bool {
+            event_type == "order.paid"
+        }
+
+        fn handle<'a>(&'a self, _event: &'a DomainEvent) -> BoxFuture<'a, Result<(), ServiceError>> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            let failures = self.failures;
+            Box::pin(async move {
+                if call < failures {
+                    Err(ServiceError::BadRequest("temporarily unavailable".to_string()))
+                } else {
+                    Ok(())
+                }
+            })
+        }
+    }
+
+    fn relay(repo: Arc<MemoryOutbox>, subscriber: Arc<FlakySubscriber>) -> OutboxRelay {
+        let bus = Arc::new(EventBus::new().subscribe(subscriber));
+        OutboxRelay::new(repo, bus, OutboxSettings { max_attempts: 2, ..OutboxSettings::default() })
+    }
+
+    #[tokio::test]
+    async fn test_failed_event_is_retried_until_published() {
+        let repo = MemoryOutbox::with_event("order.paid");
+        let subscriber = Arc::new(FlakySubscriber { failures: 1, calls: AtomicU32::new(0) });
+        let relay = relay(repo.clone(), subscriber.clone());
+
+        assert_eq!(relay.relay_once().await.unwrap(), 1);
+        assert!(repo.row().published_at.is_none());
+        assert!(repo.row().last_error.is_some());
+        // 退避期内不会再次投递
+        assert_eq!(relay.relay_once().await.unwrap(), 0);
+
+        repo.make_due();
+        assert_eq!(relay.relay_once().await.unwrap(), 1);
+        assert!(repo.row().published_at.is_some());
+        assert_eq!(subscriber.calls.load(Ordering::SeqCst), 2);
+        assert_eq!(relay.relay_once().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_unhandled_event_is_published_and_exhausted_event_is_parked() {
+        let repo = MemoryOutbox::with_event("order.shipped");
+        let subscriber = Arc::new(FlakySubscriber { failures: 0, calls: AtomicU32::new(0) });
+        relay(repo.clone(), subscriber.clone()).relay_once().await.unwrap();
+        assert!(repo.row().published_at.is_some());
+        assert_eq!(subscriber.calls.load(Ordering::SeqCst), 0);
+
+        let repo = MemoryOutbox::with_event("order.paid");
+        let relay = relay(repo.clone(), Arc::new(FlakySubscriber { failures: 10, calls: AtomicU32::new(0) }));
+        for _ in 0..3 {
+            relay.relay_once().await.unwrap();
+            repo.make_due();
+        }
+        assert_eq!(repo.row().attempts, 2);
+        assert!(repo.row().published_at.is_none());
+    }
+}
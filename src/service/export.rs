@@ -0,0 +1,381 @@
+use crate::domain::BoxFuture;
+use crate::domain::blob::BlobStore;
+use crate::domain::export::ExportRepo;
+use crate::domain::jobs::JobQueue;
+use crate::models::export::{Cell, Export, ExportFile, ExportFilter, ExportFormat, ExportKind, ExportProgress, ExportRequest, ExportStatus};
+use crate::models::job::Job;
+use crate::models::order::OrderStatus;
+use crate::service::ServiceError;
+use crate::service::jobs::{JobHandler, now_millis};
+use chrono::{DateTime, Duration, Local};
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use wx_shop::ExportSettings;
+
+/// 生成导出文件的任务
+pub const EXPORT_RUN_JOB: &str = "export_run";
+/// 下载链接过期后删除文件的任务
+pub const EXPORT_PURGE_JOB: &str = "export_purge";
+
+/// xlsx 单个工作表除表头外最多容纳的行数
+const XLSX_MAX_ROWS: u64 = 1_048_575;
+
+pub trait ExportService: Send + Sync {
+    /// 创建导出并立即排期执行
+    fn submit(&self, request: ExportRequest, admin_id: u32) -> BoxFuture<'_, Result<ExportProgress, ServiceError>>;
+    fn get(&self, id: u64) -> BoxFuture<'_, Result<ExportProgress, ServiceError>>;
+    fn list(&self, page: u32, page_size: u32) -> BoxFuture<'_, Result<Vec<ExportProgress>, ServiceError>>;
+    /// 分批读取数据写入文件并上传；已结束的导出直接跳过
+    fn run(&self, id: u64, attempt: u32) -> BoxFuture<'_, Result<(), ServiceError>>;
+    fn fail<'a>(&'a self, id: u64, error: &'a str) -> BoxFuture<'a, Result<(), ServiceError>>;
+    /// 删除过期的文件
+    fn purge(&self, id: u64) -> BoxFuture<'_, Result<(), ServiceError>>;
+    fn download(&self, id: u64) -> BoxFuture<'_, Result<ExportFile, ServiceError>>;
+}
+
+pub struct ExportServiceImpl<R: ExportRepo + 'static> {
+    repo: Arc<R>,
+    blob: Arc<dyn BlobStore>,
+    jobs: Arc<dyn JobQueue>,
+    settings: ExportSettings,
+}
+
+impl<R: ExportRepo + 'static> ExportServiceImpl<R> {
+    pub fn new(repo: Arc<R>, blob: Arc<dyn BlobStore>, jobs: Arc<dyn JobQueue>, settings: ExportSettings) -> Self {
+        Self { repo, blob, jobs, settings }
+    }
+
+    async fn find(&self, id: u64) -> Result<Export, ServiceError> {
+        self.repo
+            .find(id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("export {} not found", id)))
+    }
+
+    /// 写出全部数据，返回写入的行数
+    async fn write_file(&self, id: u64, kind: ExportKind, format: ExportFormat, filter: &ExportFilter, path: &Path) -> Result<u64, ServiceError> {
+        let batch_size = self.settings.batch_size.max(1);
+        let file_path = path.to_path_buf();
+        let mut writer = blocking(move || ExportWriter::create(format, kind.headers(), &file_path)).await?;
+        let mut after_id = 0;
+        let mut processed = 0;
+        loop {
+            let batch = self.repo.fetch_batch(kind, filter, after_id, batch_size).await?;
+            let Some((last_id, _)) = batch.last() else { break };
+            after_id = *last_id;
+            let full = batch.len() == batch_size as usize;
+            processed += batch.len() as u64;
+            writer = blocking(move || {
+                for (_, cells) in &batch {
+                    writer.write_row(cells)?;
+                }
+                Ok(writer)
+            })
+            .await?;
+            self.repo.progress(id, processed).await?;
+            if !full {
+                break;
+            }
+        }
+        let file_path = path.to_path_buf();
+        blocking(move || writer.finish(&file_path)).await?;
+        Ok(processed)
+    }
+}
+
+impl<R: ExportRepo + 'static> ExportService for ExportServiceImpl<R> {
+    fn submit(&self, request: ExportRequest, admin_id: u32) -> BoxFuture<'_, Result<ExportProgress, ServiceError>> {
+        Box::pin(async move {
+            let filter = &request.filter;
+            if let (Some(from), Some(to)) = (filter.from, filter.to)
+                && from > to
+            {
+                return Err(ServiceError::BadRequest(format!("from {} is after to {}", from, to)));
+            }
+            if let Some(status) = &filter.status {
+                status.parse::<OrderStatus>().map_err(ServiceError::BadRequest)?;
+            }
+            let id = self.repo.create(&request, admin_id).await?;
+            let job = Job::new(EXPORT_RUN_JOB, &id.to_string(), serde_json::json!({ "export_id": id }));
+            self.jobs.enqueue(&job, now_millis()).await?;
+            tracing::info!("Admin {} submitted {} export {}", admin_id, request.kind.as_str(), id);
+            Ok(to_progress(self.find(id).await?, Local::now()))
+        })
+    }
+
+    fn get(&self, id: u64) -> BoxFuture<'_, Result<ExportProgress, ServiceError>> {
+        Box::pin(async move { Ok(to_progress(self.find(id).await?, Local::now())) })
+    }
+
+    fn list(&self, page: u32, page_size: u32) -> BoxFuture<'_, Result<Vec<ExportProgress>, ServiceError>> {
+        Box::pin(async move {
+            let page_size = page_size.clamp(1, 100);
+            let offset = page.saturating_sub(1) * page_size;
+            let now = Local::now();
+            let exports = self.repo.list(page_size, offset).await?;
+            Ok(exports.into_iter().map(|export| to_progress(export, now)).collect())
+        })
+    }
+
+    fn run(&self, id: u64, attempt: u32) -> BoxFuture<'_, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let export = self.find(id).await?;
+            if !export.is(ExportStatus::Pending) && !export.is(ExportStatus::Running) {
+                return Ok(());
+            }
+            let kind: ExportKind = export.kind.parse().map_err(ServiceError::BadRequest)?;
+            let format: ExportFormat = export.format.parse().map_err(ServiceError::BadRequest)?;
+            let filter = &export.filter.0;
+
+            let total = self.repo.count_rows(kind, filter).await?;
+            let max_rows = match format {
+                ExportFormat::Csv => self.settings.max_rows,
+                ExportFormat::Xlsx => self.settings.max_rows.min(XLSX_MAX_ROWS),
+            };
+            if total > max_rows {
+                // 重试也不会成功，直接标记失败
+                let error = format!("{} rows exceed the limit of {}, narrow down the filter", total, max_rows);
+                self.repo.fail(id, &error).await?;
+                return Ok(());
+            }
+            self.repo.start(id, total, Local::now()).await?;
+
+            // 每次投递写不同的临时文件，可见性超时后重复投递的执行互不干扰
+            let path: PathBuf = std::env::temp_dir().join(format!("export-{}-{}.{}", id, attempt, format.as_str()));
+            let key = format!("exports/{}", export.file_name());
+            let uploaded = match self.write_file(id, kind, format, filter, &path).await {
+                Ok(rows) => self.blob.put_file(&key, &path).await.map(|size| (rows, size)).map_err(ServiceError::from),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = tokio::fs::remove_file(&path).await {
+                tracing::warn!("Failed to remove temp file {}: {:?}", path.display(), e);
+            }
+            let (rows, size) = uploaded?;
+
+            let expires_at = Local::now() + Duration::hours(i64::from(self.settings.link_ttl_hours));
+            self.repo.complete(id, &key, size, rows, expires_at).await?;
+            let purge = Job::new(EXPORT_PURGE_JOB, &id.to_string(), serde_json::json!({ "export_id": id }));
+            self.jobs.enqueue(&purge, expires_at.timestamp_millis()).await?;
+            tracing::info!("Export {} wrote {} rows ({} bytes)", id, rows, size);
+            Ok(())
+        })
+    }
+
+    fn fail<'a>(&'a self, id: u64, error: &'a str) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move { Ok(self.repo.fail(id, error).await?) })
+    }
+
+    fn purge(&self, id: u64) -> BoxFuture<'_, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let export = self.find(id).await?;
+            if let Some(key) = &export.blob_key {
+                self.blob.delete(key).await?;
+            }
+            if self.repo.expire(id).await? {
+                tracing::info!("Export {} expired, file removed", id);
+            }
+            Ok(())
+        })
+    }
+
+    fn download(&self, id: u64) -> BoxFuture<'_, Result<ExportFile, ServiceError>> {
+        Box::pin(async move {
+            let export = self.find(id).await?;
+            if export.is(ExportStatus::Expired) || export.expires_at.is_some_and(|at| at <= Local::now()) {
+                return Err(ServiceError::Conflict(format!("download link of export {} has expired", id)));
+            }
+            let (true, Some(key)) = (export.is(ExportStatus::Succeeded), &export.blob_key) else {
+                return Err(ServiceError::Conflict(format!("export {} is {}", id, export.status)));
+            };
+            let format: ExportFormat = export.format.parse().map_err(ServiceError::BadRequest)?;
+            let content = self.blob.open(key).await?;
+            Ok(ExportFile { file_name: export.file_name(), content_type: format.content_type(), content })
+        })
+    }
+}
+
+/// 计算进度百分比；成功且未过期时给出下载地址
+fn to_progress(export: Export, now: DateTime<Local>) -> ExportProgress {
+    let succeeded = export.is(ExportStatus::Succeeded);
+    let percent = (export.processed_rows.min(export.total_rows) * 100)
+        .checked_div(export.total_rows)
+        .map_or(if succeeded { 100 } else { 0 }, |percent| percent as u32);
+    let download_url = (succeeded && export.expires_at.is_some_and(|at| at > now))
+        .then(|| format!("/admin/exports/{}/download", export.id));
+    ExportProgress { export, percent, download_url }
+}
+
+/// 写文件是阻塞 IO，放到阻塞线程池执行，不占用异步工作线程
+async fn blocking<T, F>(f: F) -> Result<T, ServiceError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, ServiceError> + Send + 'static,
+{
+    tokio::task::spawn_blocking(f).await.map_err(|e| ServiceError::Storage(io::Error::other(e)))?
+}
+
+/// 按格式逐行写文件
+enum ExportWriter {
+    Csv(csv::Writer<File>),
+    /// xlsx 使用常量内存模式：写完的行随即落到临时文件，结束时再打包
+    Xlsx { workbook: Box<Workbook>, row: u32, amount: Format },
+}
+
+fn xlsx_error(e: XlsxError) -> ServiceError {
+    ServiceError::Storage(io::Error::other(e))
+}
+
+impl ExportWriter {
+    fn create(format: ExportFormat, headers: &[&str], path: &Path) -> Result<Self, ServiceError> {
+        match format {
+            ExportFormat::Csv => {
+                let mut file = File::create(path)?;
+                // 写入 BOM，Excel 才能识别 UTF-8 编码的中文
+                file.write_all(b"\xEF\xBB\xBF")?;
+                let mut writer = csv::Writer::from_writer(file);
+                writer.write_record(headers).map_err(io::Error::from)?;
+                Ok(ExportWriter::Csv(writer))
+            }
+            ExportFormat::Xlsx => {
+                let mut workbook = Workbook::new();
+                // 常量内存模式要求按行顺序写入，导出本来就是逐行追加
+                let sheet = workbook.add_worksheet_with_constant_memory();
+                for (col, header) in headers.iter().enumerate() {
+                    sheet.write_string(0, col as u16, *header).map_err(xlsx_error)?;
+                }
+                Ok(ExportWriter::Xlsx { workbook: Box::new(workbook), row: 1, amount: Format::new().set_num_format("0.00") })
+            }
+        }
+    }
+
+    fn write_row(&mut self, cells: &[Cell]) -> Result<(), ServiceError> {
+        match self {
+            ExportWriter::Csv(writer) => {
+                writer.write_record(cells.iter().map(Cell::to_text)).map_err(io::Error::from)?;
+            }
+            ExportWriter::Xlsx { workbook, row, amount } => {
+                let sheet = workbook.worksheet_from_index(0).map_err(xlsx_error)?;
+                for (col, cell) in cells.iter().enumerate() {
+                    let col = col as u16;
+                    match cell {
+                        Cell::Text(text) => sheet.write_string(*row, col, text),
+                        Cell::Int(value) => sheet.write_number(*row, col, *value as f64),
+                        Cell::Amount(cents) => sheet.write_number_with_format(*row, col, *cents as f64 / 100.0, amount),
+                    }
+                    .map_err(xlsx_error)?;
+                }
+                *row += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self, path: &Path) -> Result<(), ServiceError> {
+        match self {
+            ExportWriter::Csv(mut writer) => writer.flush()?,
+            ExportWriter::Xlsx { mut workbook, .. } => workbook.save(path).map_err(xlsx_error)?,
+        }
+        Ok(())
+    }
+}
+
+fn export_id(job: &Job) -> Result<u64, ServiceError> {
+    job.payload["export_id"]
+        .as_u64()
+        .ok_or_else(|| ServiceError::BadRequest(format!("invalid payload of job {}", job.id)))
+}
+
+/// 执行导出；最后一次重试仍失败时把导出标记为失败
+pub struct ExportRunJob {
+    service: Arc<dyn ExportService>,
+}
+
+impl ExportRunJob {
+    pub fn new(service: Arc<dyn ExportService>) -> Arc<Self> {
+        Arc::new(Self { service })
+    }
+}
+
+impl JobHandler for ExportRunJob {
+    fn kind(&self) -> &'static str {
+        EXPORT_RUN_JOB
+    }
+
+    fn handle<'a>(&'a self, job: &'a Job) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let id = export_id(job)?;
+            let result = self.service.run(id, job.attempts).await;
+            if let Err(e) = &result
+                && job.attempts >= job.max_attempts
+            {
+                self.service.fail(id, &format!("{:?}", e)).await?;
+            }
+            result
+        })
+    }
+}
+
+pub struct ExportPurgeJob {
+    service: Arc<dyn ExportService>,
+}
+
+impl ExportPurgeJob {
+    pub fn new(service: Arc<dyn ExportService>) -> Arc<Self> {
+        Arc::new(Self { service })
+    }
+}
+
+impl JobHandler for ExportPurgeJob {
+    fn kind(&self) -> &'static str {
+        EXPORT_PURGE_JOB
+    }
+
+    fn handle<'a>(&'a self, job: &'a Job) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move { self.service.purge(export_id(job)?).await })
+    }
+}
+
+pub fn new_export_service<R: ExportRepo + 'static>(
+    repo: Arc<R>,
+    blob: Arc<dyn BlobStore>,
+    jobs: Arc<dyn JobQueue>,
+    settings: ExportSettings,
+) -> Arc<dyn ExportService> {
+    Arc::new(ExportServiceImpl::new(repo, blob, jobs, settings)) as Arc<dyn ExportService>
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_writer_formats_cells() {
+        let path = std::env::temp_dir().join(format!("export-test-{}.csv", std::process::id()));
+        let mut writer = ExportWriter::create(ExportFormat::Csv, &["订单号", "金额(元)"], &path).unwrap();
+        writer.write_row(&[Cell::Text("=SUM(A1)".into()), Cell::Amount(-1205)]).unwrap();
+        writer.write_row(&[Cell::Text("a,b".into()), Cell::Int(7)]).unwrap();
+        writer.finish(&path).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(content, "\u{feff}订单号,金额(元)\n'=SUM(A1),-12.05\n\"a,b\",7\n");
+    }
+
+    #[test]
+    fn test_xlsx_writer_streams_rows() {
+        let path = std::env::temp_dir().join(format!("export-test-{}.xlsx", std::process::id()));
+        let mut writer = ExportWriter::create(ExportFormat::Xlsx, &["订单号", "金额(元)"], &path).unwrap();
+        writer.write_row(&[Cell::Text("N1".into()), Cell::Amount(-1205)]).unwrap();
+        writer.write_row(&[Cell::Text("N2".into()), Cell::Int(7)]).unwrap();
+        writer.finish(&path).unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let rows = crate::service::sheet::read_rows(ExportFormat::Xlsx, &bytes).unwrap();
+        let cells: Vec<Vec<String>> = rows.into_iter().map(|(_, cells)| cells).collect();
+        assert_eq!(cells, [["订单号", "金额(元)"], ["N1", "-12.05"], ["N2", "7"]]);
+    }
+}
@@ -0,0 +1,177 @@
+use crate::domain::BoxFuture;
+use crate::domain::catalog::CatalogRepo;
+use crate::domain::favorite::{BrowsingHistory, FavoriteRepo};
+use crate::models::catalog::ProductSummary;
+use crate::models::favorite::ProductCard;
+use crate::service::ServiceError;
+use chrono::{DateTime, Local, TimeZone};
+use std::collections::HashMap;
+use std::sync::Arc;
+use wx_shop::HistorySettings;
+
+pub trait FavoriteService: Send + Sync {
+    /// 收藏商品，重复收藏不报错
+    fn add_favorite(&self, user_id: u32, product_id: u64) -> BoxFuture<'_, Result<(), ServiceError>>;
+    /// 取消收藏，未收藏时不报错
+    fn remove_favorite(&self, user_id: u32, product_id: u64) -> BoxFuture<'_, Result<(), ServiceError>>;
+    /// 按收藏时间倒序，价格和可售状态取当前数据；已删除的商品不展示
+    fn list_favorites(&self, user_id: u32, page: u32, page_size: u32) -> BoxFuture<'_, Result<Vec<ProductCard>, ServiceError>>;
+    fn record_view(&self, user_id: u32, product_id: u64) -> BoxFuture<'_, Result<(), ServiceError>>;
+    fn remove_view(&self, user_id: u32, product_id: u64) -> BoxFuture<'_, Result<(), ServiceError>>;
+    fn clear_history(&self, user_id: u32) -> BoxFuture<'_, Result<(), ServiceError>>;
+    /// 按浏览时间倒序
+    fn list_history(&self, user_id: u32) -> BoxFuture<'_, Result<Vec<ProductCard>, ServiceError>>;
+}
+
+pub struct FavoriteServiceImpl<F: FavoriteRepo + 'static, H: BrowsingHistory + 'static, C: CatalogRepo + 'static> {
+    favorites: Arc<F>,
+    history: Arc<H>,
+    catalog: Arc<C>,
+    settings: HistorySettings,
+}
+
+impl<F: FavoriteRepo + 'static, H: BrowsingHistory + 'static, C: CatalogRepo + 'static> FavoriteServiceImpl<F, H, C> {
+    pub fn new(favorites: Arc<F>, history: Arc<H>, catalog: Arc<C>, settings: HistorySettings) -> Self {
+        Self { favorites, history, catalog, settings }
+    }
+
+    async fn ensure_product(&self, product_id: u64) -> Result<(), ServiceError> {
+        if self.catalog.find_products(&[product_id]).await?.is_empty() {
+            return Err(ServiceError::NotFound(format!("Product with ID {} not found", product_id)));
+        }
+        Ok(())
+    }
+
+    async fn cards(&self, entries: Vec<(u64, DateTime<Local>)>) -> Result<Vec<ProductCard>, ServiceError> {
+        if entries.is_empty() {
+            return Ok(Vec::new());
+        }
+        let product_ids: Vec<u64> = entries.iter().map(|(product_id, _)| *product_id).collect();
+        let products = self.catalog.find_products(&product_ids).await?;
+        Ok(hydrate(&entries, products))
+    }
+}
+
+/// 按记录的顺序拼装商品卡片，查不到的商品跳过
+fn hydrate(entries: &[(u64, DateTime<Local>)], products: Vec<ProductSummary>) -> Vec<ProductCard> {
+    let mut products: HashMap<u64, ProductSummary> = products.into_iter().map(|product| (product.product_id, product)).collect();
+    entries
+        .iter()
+        .filter_map(|(product_id, saved_at)| {
+            let product = products.remove(product_id)?;
+            let available = product.is_available();
+            Some(ProductCard {
+                product_id: product.product_id,
+                name: product.name,
+                min_price: product.min_price.filter(|_| available),
+                available,
+                favorite_count: product.favorite_count,
+                saved_at: *saved_at,
+            })
+        })
+        .collect()
+}
+
+impl<F: FavoriteRepo + 'static, H: BrowsingHistory + 'static, C: CatalogRepo + 'static> FavoriteService for FavoriteServiceImpl<F, H, C> {
+    fn add_favorite(&self, user_id: u32, product_id: u64) -> BoxFuture<'_, Result<(), ServiceError>> {
+        Box::pin(async move {
+            self.ensure_product(product_id).await?;
+            self.favorites.add(user_id, product_id).await?;
+            Ok(())
+        })
+    }
+
+    fn remove_favorite(&self, user_id: u32, product_id: u64) -> BoxFuture<'_, Result<(), ServiceError>> {
+        Box::pin(async move {
+            self.favorites.remove(user_id, product_id).await?;
+            Ok(())
+        })
+    }
+
+    fn list_favorites(&self, user_id: u32, page: u32, page_size: u32) -> BoxFuture<'_, Result<Vec<ProductCard>, ServiceError>> {
+        Box::pin(async move {
+            let page_size = page_size.clamp(1, 50);
+            let offset = page.saturating_sub(1) * page_size;
+            let favorites = self.favorites.list(user_id, page_size, offset).await?;
+            let entries = favorites
+                .into_iter()
+                .map(|favorite| (favorite.product_id, favorite.created_at.unwrap_or_else(Local::now)))
+                .collect();
+            self.cards(entries).await
+        })
+    }
+
+    fn record_view(&self, user_id: u32, product_id: u64) -> BoxFuture<'_, Result<(), ServiceError>> {
+        Box::pin(async move {
+            self.ensure_product(product_id).await?;
+            let ttl_secs = u64::from(self.settings.ttl_days.max(1)) * 86_400;
+            self.history
+                .record(user_id, product_id, Local::now().timestamp_millis(), self.settings.limit, ttl_secs)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn remove_view(&self, user_id: u32, product_id: u64) -> BoxFuture<'_, Result<(), ServiceError>> {
+        Box::pin(async move {
+            self.history.remove(user_id, product_id).await?;
+            Ok(())
+        })
+    }
+
+    fn clear_history(&self, user_id: u32) -> BoxFuture<'_, Result<(), ServiceError>> {
+        Box::pin(async move {
+            self.history.clear(user_id).await?;
+            Ok(())
+        })
+    }
+
+    fn list_history(&self, user_id: u32) -> BoxFuture<'_, Result<Vec<ProductCard>, ServiceError>> {
+        Box::pin(async move {
+            let viewed = self.history.list(user_id, self.settings.limit).await?;
+            let entries = viewed
+                .into_iter()
+                .filter_map(|view| Local.timestamp_millis_opt(view.viewed_at).single().map(|at| (view.product_id, at)))
+                .collect();
+            self.cards(entries).await
+        })
+    }
+}
+
+pub fn new_favorite_service<F: FavoriteRepo + 'static, H: BrowsingHistory + 'static, C: CatalogRepo + 'static>(
+    favorites: Arc<F>,
+    history: Arc<H>,
+    catalog: Arc<C>,
+    settings: HistorySettings,
+) -> Arc<dyn FavoriteService> {
+    Arc::new(FavoriteServiceImpl::new(favorites, history, catalog, settings)) as Arc<dyn FavoriteService>
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn product(product_id: u64, status: &str, on_sale_skus: i64) -> ProductSummary {
+        ProductSummary {
+            product_id,
+            name: format!("商品{}", product_id),
+            category_id: 1,
+            status: status.to_string(),
+            min_price: Some(990),
+            on_sale_skus,
+            favorite_count: 3,
+        }
+    }
+
+    #[test]
+    fn test_hydrate_keeps_saved_order_and_skips_missing_products() {
+        let now = Local::now();
+        let entries = vec![(3, now), (1, now), (2, now)];
+        let cards = hydrate(&entries, vec![product(1, "on_sale", 2), product(3, "off_shelf", 1)]);
+        assert_eq!(cards.iter().map(|card| card.product_id).collect::<Vec<_>>(), vec![3, 1]);
+        assert!(!cards[0].available);
+        assert_eq!(cards[0].min_price, None);
+        assert!(cards[1].available);
+        assert_eq!(cards[1].min_price, Some(990));
+    }
+}
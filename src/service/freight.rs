@@ -0,0 +1,223 @@
+use crate::domain::BoxFuture;
+use crate::domain::freight::FreightRepo;
+use crate::models::freight::{ChargeBy, FreightTemplate, NewFreightTemplate};
+use crate::service::ServiceError;
+use crate::service::pricing::{FlatShipping, PricingInput, PricingStep, Quote};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+pub trait FreightService: Send + Sync {
+    fn list(&self) -> BoxFuture<'_, Result<Vec<FreightTemplate>, ServiceError>>;
+    fn create<'a>(&'a self, template: &'a NewFreightTemplate) -> BoxFuture<'a, Result<FreightTemplate, ServiceError>>;
+    fn update<'a>(&'a self, id: u64, template: &'a NewFreightTemplate) -> BoxFuture<'a, Result<FreightTemplate, ServiceError>>;
+    /// 设置商品的运费模板，`None` 表示改回统一运费
+    fn assign(&self, product_id: u64, template_id: Option<u64>) -> BoxFuture<'_, Result<(), ServiceError>>;
+    /// 结算时按 ID 批量加载模板
+    fn templates<'a>(&'a self, ids: &'a [u64]) -> BoxFuture<'a, Result<HashMap<u64, FreightTemplate>, ServiceError>>;
+}
+
+pub struct FreightServiceImpl<R: FreightRepo + 'static> {
+    repo: Arc<R>,
+}
+
+impl<R: FreightRepo + 'static> FreightServiceImpl<R> {
+    pub fn new(repo: Arc<R>) -> Self {
+        Self { repo }
+    }
+
+    async fn load(&self, id: u64) -> Result<FreightTemplate, ServiceError> {
+        self.repo
+            .find(id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Freight template with ID {} not found", id)))
+    }
+}
+
+impl<R: FreightRepo + 'static> FreightService for FreightServiceImpl<R> {
+    fn list(&self) -> BoxFuture<'_, Result<Vec<FreightTemplate>, ServiceError>> {
+        Box::pin(async move { Ok(self.repo.list().await?) })
+    }
+
+    fn create<'a>(&'a self, template: &'a NewFreightTemplate) -> BoxFuture<'a, Result<FreightTemplate, ServiceError>> {
+        Box::pin(async move {
+            template.validate().map_err(ServiceError::BadRequest)?;
+            let id = self.repo.create(template).await?;
+            self.load(id).await
+        })
+    }
+
+    fn update<'a>(&'a self, id: u64, template: &'a NewFreightTemplate) -> BoxFuture<'a, Result<FreightTemplate, ServiceError>> {
+        Box::pin(async move {
+            template.validate().map_err(ServiceError::BadRequest)?;
+            self.load(id).await?;
+            self.repo.update(id, template).await?;
+            self.load(id).await
+        })
+    }
+
+    fn assign(&self, product_id: u64, template_id: Option<u64>) -> BoxFuture<'_, Result<(), ServiceError>> {
+        Box::pin(async move {
+            if let Some(template_id) = template_id {
+                self.load(template_id).await?;
+            }
+            if !self.repo.assign(product_id, template_id).await? {
+                return Err(ServiceError::NotFound(format!("Product with ID {} not found", product_id)));
+            }
+            Ok(())
+        })
+    }
+
+    fn templates<'a>(&'a self, ids: &'a [u64]) -> BoxFuture<'a, Result<HashMap<u64, FreightTemplate>, ServiceError>> {
+        Box::pin(async move {
+            let templates = self.repo.find_many(ids).await?;
+            Ok(templates.into_iter().map(|template| (template.id, template)).collect())
+        })
+    }
+}
+
+pub fn new_freight_service<R: FreightRepo + 'static>(repo: Arc<R>) -> Arc<dyn FreightService> {
+    Arc::new(FreightServiceImpl::new(repo)) as Arc<dyn FreightService>
+}
+
+/// 按运费模板计算运费：同一模板的商品合并计件(重)，各模板单独判断包邮后相加；
+/// 未设置模板（或模板已删除）的商品合成一组，按统一运费计算
+pub struct FreightStep {
+    templates: HashMap<u64, FreightTemplate>,
+    province: String,
+    fallback: FlatShipping,
+}
+
+impl FreightStep {
+    pub fn new(templates: HashMap<u64, FreightTemplate>, province: &str, fallback: FlatShipping) -> Self {
+        Self { templates, province: province.to_string(), fallback }
+    }
+}
+
+impl PricingStep for FreightStep {
+    fn apply(&self, input: &PricingInput, quote: &mut Quote) -> Result<(), ServiceError> {
+        // BTreeMap 保证计算顺序稳定，None 组排在最前
+        let mut groups: BTreeMap<Option<u64>, Vec<usize>> = BTreeMap::new();
+        for (i, item) in input.items.iter().enumerate() {
+            let key = item.freight_template_id.filter(|id| self.templates.contains_key(id));
+            groups.entry(key).or_default().push(i);
+        }
+
+        let mut fee = 0;
+        for (template_id, lines) in groups {
+            let payable: i64 = lines.iter().map(|&i| quote.lines[i].payable_amount).sum();
+            let Some(template) = template_id.and_then(|id| self.templates.get(&id)) else {
+                let free = self.fallback.free_threshold > 0 && payable >= self.fallback.free_threshold;
+                fee += if free { 0 } else { self.fallback.fee };
+                continue;
+            };
+            if template.free_threshold > 0 && payable >= template.free_threshold {
+                continue;
+            }
+            let rule = template.rule_for(&self.province).ok_or_else(|| {
+                ServiceError::BadRequest(format!("{} is not covered by freight template {}", self.province, template.id))
+            })?;
+            let units: u64 = lines
+                .iter()
+                .map(|&i| {
+                    let item = &input.items[i];
+                    match template.charge_by.parse() {
+                        Ok(ChargeBy::Weight) => u64::from(item.weight_grams) * u64::from(item.quantity),
+                        _ => u64::from(item.quantity),
+                    }
+                })
+                .sum();
+            fee += rule.fee(units);
+        }
+        quote.shipping_fee = fee;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::freight::FreightRule;
+    use crate::service::pricing::{PricingItem, PricingPipeline};
+    use sqlx::types::Json;
+
+    fn item(sku_id: u64, unit_price: i64, quantity: u32, weight_grams: u32, freight_template_id: Option<u64>) -> PricingItem {
+        PricingItem {
+            sku_id,
+            product_id: sku_id,
+            category_id: 0,
+            product_name: format!("p{}", sku_id),
+            sku_title: format!("s{}", sku_id),
+            unit_price,
+            quantity,
+            weight_grams,
+            freight_template_id,
+        }
+    }
+
+    fn rule(provinces: &[&str], first: u32, first_fee: i64, additional: u32, additional_fee: i64) -> FreightRule {
+        FreightRule {
+            provinces: provinces.iter().map(|p| p.to_string()).collect(),
+            first,
+            first_fee,
+            additional,
+            additional_fee,
+        }
+    }
+
+    fn template(id: u64, charge_by: ChargeBy, rules: Vec<FreightRule>, free_threshold: i64) -> FreightTemplate {
+        FreightTemplate {
+            id,
+            name: format!("t{}", id),
+            charge_by: charge_by.as_str().to_string(),
+            rules: Json(rules),
+            free_threshold,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    fn shipping_fee(templates: Vec<FreightTemplate>, province: &str, items: Vec<PricingItem>) -> i64 {
+        let templates = templates.into_iter().map(|t| (t.id, t)).collect();
+        let step = FreightStep::new(templates, province, FlatShipping { fee: 800, free_threshold: 9900 });
+        let quote = PricingPipeline::new(vec![Box::new(step)]).quote(&PricingInput { items }).unwrap();
+        quote.shipping_fee
+    }
+
+    #[test]
+    fn test_rule_fee_rounds_up_additional_units() {
+        let rule = rule(&[], 1000, 1000, 500, 300);
+        assert_eq!(rule.fee(0), 0);
+        assert_eq!(rule.fee(1000), 1000);
+        assert_eq!(rule.fee(1001), 1300);
+        assert_eq!(rule.fee(2000), 1600);
+    }
+
+    #[test]
+    fn test_weight_template_with_region_rule() {
+        let weight = template(1, ChargeBy::Weight, vec![rule(&[], 1000, 1000, 1000, 500), rule(&["新疆"], 1000, 2000, 1000, 1500)], 0);
+        let items = vec![item(1, 3000, 3, 600, Some(1))];
+        // 1800 克：首重 1000 克 + 续重 1 份
+        assert_eq!(shipping_fee(vec![weight.clone()], "广东", items.clone()), 1500);
+        assert_eq!(shipping_fee(vec![weight], "新疆", items), 3500);
+    }
+
+    #[test]
+    fn test_templates_are_summed_and_free_separately() {
+        let piece = template(1, ChargeBy::Piece, vec![rule(&[], 1, 600, 1, 200)], 5000);
+        let weight = template(2, ChargeBy::Weight, vec![rule(&[], 1000, 1000, 1000, 500)], 0);
+        let items = vec![item(1, 2000, 3, 0, Some(1)), item(2, 1000, 1, 500, Some(2))];
+        // 模板 1 满 5000 包邮，只收模板 2 的首重
+        assert_eq!(shipping_fee(vec![piece.clone(), weight.clone()], "广东", items), 1000);
+
+        let items = vec![item(1, 1000, 2, 0, Some(1)), item(2, 1000, 1, 500, Some(2)), item(3, 500, 1, 0, None)];
+        assert_eq!(shipping_fee(vec![piece, weight], "广东", items), 800 + 1000 + 800);
+    }
+
+    #[test]
+    fn test_items_without_template_use_flat_shipping() {
+        assert_eq!(shipping_fee(Vec::new(), "广东", vec![item(1, 5000, 1, 0, None)]), 800);
+        assert_eq!(shipping_fee(Vec::new(), "广东", vec![item(1, 5000, 2, 0, None)]), 0);
+        // 模板已被删除时按统一运费计算
+        assert_eq!(shipping_fee(Vec::new(), "广东", vec![item(1, 5000, 1, 0, Some(9))]), 800);
+    }
+}
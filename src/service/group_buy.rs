@@ -0,0 +1,750 @@
+use crate::domain::BoxFuture;
+use crate::domain::group_buy::GroupBuyRepo;
+use crate::domain::jobs::JobQueue;
+use crate::models::group_buy::{
+    CAMPAIGN_DISABLED, GroupBuy, GroupCampaign, GroupDetail, GroupOrder, GroupStatus, JoinOutcome, NewGroupCampaign,
+};
+use crate::models::event::{DomainEvent, ORDER_EVENT_PREFIX, OrderStatusChanged};
+use crate::models::job::Job;
+use crate::models::order::OrderStatus;
+use crate::service::ServiceError;
+use crate::service::events::EventSubscriber;
+use crate::service::jobs::JobHandler;
+use crate::service::order::OrderService;
+use crate::service::refund::RefundService;
+use chrono::Local;
+use std::sync::Arc;
+
+/// 拼团截止任务
+pub const GROUP_EXPIRE_JOB: &str = "group_buy_expire";
+
+pub trait GroupBuyService: Send + Sync {
+    /// 当前可以开团的活动
+    fn list_campaigns(&self) -> BoxFuture<'_, Result<Vec<GroupCampaign>, ServiceError>>;
+    /// 开团：创建团并为团长按拼团价下单；团长下单失败时团直接失败
+    fn open(&self, user_id: u32, campaign_id: u64, address_id: u64) -> BoxFuture<'_, Result<GroupOrder, ServiceError>>;
+    /// 参团：占一个名额并下单，订单取消后名额释放
+    fn join(&self, user_id: u32, group_id: u64, address_id: u64) -> BoxFuture<'_, Result<GroupOrder, ServiceError>>;
+    /// 团详情，用于分享页
+    fn get(&self, group_id: u64) -> BoxFuture<'_, Result<GroupDetail, ServiceError>>;
+    /// 订单支付成功后调用：计入已支付人数，满员即成团；团已失败时直接退款。
+    /// 不是拼团订单时什么都不做
+    fn on_order_paid(&self, order_id: u64) -> BoxFuture<'_, Result<(), ServiceError>>;
+    /// 截止时仍未成团：置为失败，已支付的订单整单退款，未支付的订单取消
+    fn expire(&self, group_id: u64) -> BoxFuture<'_, Result<(), ServiceError>>;
+    fn create_campaign<'a>(&'a self, campaign: &'a NewGroupCampaign) -> BoxFuture<'a, Result<GroupCampaign, ServiceError>>;
+    fn list_all_campaigns(&self, page: u32, page_size: u32) -> BoxFuture<'_, Result<Vec<GroupCampaign>, ServiceError>>;
+    /// 下线活动，不能再开新团，已开的团照常进行
+    fn disable_campaign(&self, id: u64) -> BoxFuture<'_, Result<GroupCampaign, ServiceError>>;
+}
+
+pub struct GroupBuyServiceImpl<R: GroupBuyRepo + 'static> {
+    repo: Arc<R>,
+    orders: Arc<dyn OrderService>,
+    refunds: Arc<dyn RefundService>,
+    jobs: Arc<dyn JobQueue>,
+}
+
+impl<R: GroupBuyRepo + 'static> GroupBuyServiceImpl<R> {
+    pub fn new(repo: Arc<R>, orders: Arc<dyn OrderService>, refunds: Arc<dyn RefundService>, jobs: Arc<dyn JobQueue>) -> Self {
+        Self { repo, orders, refunds, jobs }
+    }
+
+    async fn load_campaign(&self, id: u64) -> Result<GroupCampaign, ServiceError> {
+        self.repo
+            .find_campaign(id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Group campaign with ID {} not found", id)))
+    }
+
+    /// 按订单当前状态结算失败团的成员：`paid_at` 由 order.paid 订阅者异步写入，不能用来判断是否已付款
+    async fn settle_failed_member(&self, user_id: u32, order_id: u64) -> Result<(), ServiceError> {
+        let detail = self.orders.get(user_id, order_id).await?;
+        match detail.order.status().map_err(ServiceError::Conflict)? {
+            OrderStatus::PendingPayment => self.orders.cancel_unpaid(order_id).await,
+            OrderStatus::Cancelled | OrderStatus::Refunded | OrderStatus::Closed => Ok(()),
+            _ => self.refunds.refund_order(order_id, "group buy failed").await.map(|_| ()),
+        }
+    }
+
+    async fn load_group(&self, id: u64) -> Result<GroupBuy, ServiceError> {
+        self.repo
+            .find_group(id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Group with ID {} not found", id)))
+    }
+
+    /// 为已占位的成员下单；下单失败时撤销占位
+    async fn checkout(
+        &self,
+        user_id: u32,
+        group_id: u64,
+        member_id: u64,
+        address_id: u64,
+        campaign: &GroupCampaign,
+    ) -> Result<GroupOrder, ServiceError> {
+        let order = match self.orders.place_group(user_id, address_id, campaign.sku_id, campaign.group_price).await {
+            Ok(order) => order,
+            Err(e) => {
+                if let Err(rollback) = self.repo.remove_member(member_id).await {
+                    tracing::error!("Failed to remove member {} of group {}: {:?}", member_id, group_id, rollback);
+                }
+                return Err(e);
+            }
+        };
+        self.repo.attach_order(member_id, order.order.id).await?;
+        Ok(GroupOrder { group: self.get(group_id).await?, order })
+    }
+
+    /// 开团没有完成时撤销这个团：置为失败，期间已参团的订单按拼团失败处理
+    async fn abort(&self, group_id: u64) {
+        let result = match self.repo.abort(group_id).await {
+            Ok(_) => self.expire(group_id).await,
+            Err(e) => Err(e.into()),
+        };
+        if let Err(e) = result {
+            tracing::error!("Failed to abort group {}: {:?}", group_id, e);
+        }
+    }
+}
+
+impl<R: GroupBuyRepo + 'static> GroupBuyService for GroupBuyServiceImpl<R> {
+    fn list_campaigns(&self) -> BoxFuture<'_, Result<Vec<GroupCampaign>, ServiceError>> {
+        Box::pin(async move { Ok(self.repo.list_open_campaigns(Local::now()).await?) })
+    }
+
+    fn open(&self, user_id: u32, campaign_id: u64, address_id: u64) -> BoxFuture<'_, Result<GroupOrder, ServiceError>> {
+        Box::pin(async move {
+            let campaign = self.load_campaign(campaign_id).await?;
+            let now = Local::now();
+            if !campaign.is_open(now) {
+                return Err(ServiceError::Conflict(format!("group campaign {} is not open", campaign_id)));
+            }
+            let expires_at = campaign.group_expires_at(now);
+            let (group_id, member_id) = self.repo.open(&campaign, user_id, expires_at).await?;
+
+            let job = Job::new(GROUP_EXPIRE_JOB, &group_id.to_string(), serde_json::json!({ "group_id": group_id }));
+            // 没有截止任务的团不会被关闭，参团订单也不会退款，因此本次开团失败
+            if let Err(e) = self.jobs.enqueue(&job, expires_at.timestamp_millis()).await {
+                tracing::error!("Failed to schedule expiry of group {}: {:?}", group_id, e);
+                self.abort(group_id).await;
+                return Err(e.into());
+            }
+            let result = self.checkout(user_id, group_id, member_id, address_id, &campaign).await;
+            if result.is_err() {
+                self.abort(group_id).await;
+            }
+            result
+        })
+    }
+
+    fn join(&self, user_id: u32, group_id: u64, address_id: u64) -> BoxFuture<'_, Result<GroupOrder, ServiceError>> {
+        Box::pin(async move {
+            let group = self.load_group(group_id).await?;
+            let campaign = self.load_campaign(group.campaign_id).await?;
+            let member_id = match self.repo.join(group_id, user_id, Local::now()).await? {
+                JoinOutcome::Joined(id) => id,
+                JoinOutcome::Closed => return Err(ServiceError::Conflict(format!("group {} is closed", group_id))),
+                JoinOutcome::Full => return Err(ServiceError::Conflict(format!("group {} is full", group_id))),
+                JoinOutcome::AlreadyMember => {
+                    return Err(ServiceError::Conflict(format!("already joined group {}", group_id)));
+                }
+            };
+            self.checkout(user_id, group_id, member_id, address_id, &campaign).await
+        })
+    }
+
+    fn get(&self, group_id: u64) -> BoxFuture<'_, Result<GroupDetail, ServiceError>> {
+        Box::pin(async move {
+            let group = self.load_group(group_id).await?;
+            let campaign = self.load_campaign(group.campaign_id).await?;
+            let members = self.repo.list_members(group_id).await?;
+            Ok(GroupDetail { group, campaign, members })
+        })
+    }
+
+    fn on_order_paid(&self, order_id: u64) -> BoxFuture<'_, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let Some(group) = self.repo.mark_paid(order_id, Local::now()).await? else {
+                return Ok(());
+            };
+            if group.is(GroupStatus::Succeeded) && group.paid_count == group.group_size {
+                tracing::info!("Group {} succeeded with {} members", group.id, group.paid_count);
+            }
+            // 截止后才到账的支付
+            if group.is(GroupStatus::Failed) {
+                self.refunds.refund_order(order_id, "group buy failed").await?;
+            }
+            Ok(())
+        })
+    }
+
+    fn expire(&self, group_id: u64) -> BoxFuture<'_, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let group = self.load_group(group_id).await?;
+            // 已成团，或者任务在截止前被投递时什么都不做
+            let failed = group.is(GroupStatus::Failed)
+                || (group.is(GroupStatus::Forming) && self.repo.fail(group_id, Local::now()).await?);
+            if !failed {
+                return Ok(());
+            }
+
+            // 成员在置为失败之后读取，之后才支付的由 on_order_paid 退款。
+            // 退款和取消都可以重复执行，出错的交给任务重试
+            let mut retry = None;
+            for member in self.repo.list_members(group_id).await? {
+                let Some(order_id) = member.order_id else { continue };
+                if let Err(e) = self.settle_failed_member(member.user_id, order_id).await {
+                    tracing::error!("Failed to settle order {} of failed group {}: {:?}", order_id, group_id, e);
+                    retry = Some(e);
+                }
+            }
+            retry.map_or(Ok(()), Err)
+        })
+    }
+
+    fn create_campaign<'a>(&'a self, campaign: &'a NewGroupCampaign) -> BoxFuture<'a, Result<GroupCampaign, ServiceError>> {
+        Box::pin(async move {
+            campaign.validate().map_err(ServiceError::BadRequest)?;
+            let id = self.repo.create_campaign(campaign).await?;
+            self.load_campaign(id).await
+        })
+    }
+
+    fn list_all_campaigns(&self, page: u32, page_size: u32) -> BoxFuture<'_, Result<Vec<GroupCampaign>, ServiceError>> {
+        Box::pin(async move {
+            let page_size = page_size.clamp(1, 100);
+            let offset = page.saturating_sub(1) * page_size;
+            Ok(self.repo.list_campaigns(page_size, offset).await?)
+        })
+    }
+
+    fn disable_campaign(&self, id: u64) -> BoxFuture<'_, Result<GroupCampaign, ServiceError>> {
+        Box::pin(async move {
+            self.load_campaign(id).await?;
+            self.repo.set_campaign_status(id, CAMPAIGN_DISABLED).await?;
+            self.load_campaign(id).await
+        })
+    }
+}
+
+pub fn new_group_buy_service<R: GroupBuyRepo + 'static>(
+    repo: Arc<R>,
+    orders: Arc<dyn OrderService>,
+    refunds: Arc<dyn RefundService>,
+    jobs: Arc<dyn JobQueue>,
+) -> Arc<dyn GroupBuyService> {
+    Arc::new(GroupBuyServiceImpl::new(repo, orders, refunds, jobs)) as Arc<dyn GroupBuyService>
+}
+
+/// 拼团截止任务：未成团的团置为失败并退款，重复投递时只生效一次
+pub struct GroupExpireJob {
+    service: Arc<dyn GroupBuyService>,
+}
+
+impl GroupExpireJob {
+    pub fn new(service: Arc<dyn GroupBuyService>) -> Arc<Self> {
+        Arc::new(Self { service })
+    }
+}
+
+impl JobHandler for GroupExpireJob {
+    fn kind(&self) -> &'static str {
+        GROUP_EXPIRE_JOB
+    }
+
+    fn handle<'a>(&'a self, job: &'a Job) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let group_id = job.payload["group_id"]
+                .as_u64()
+                .ok_or_else(|| ServiceError::BadRequest(format!("invalid payload of job {}", job.id)))?;
+            self.service.expire(group_id).await
+        })
+    }
+}
+
+/// 订单支付成功后计入拼团人数，截止后才到账的整单退款；重复投递时只计数一次
+pub struct GroupBuyPaidSubscriber {
+    service: Arc<dyn GroupBuyService>,
+}
+
+impl GroupBuyPaidSubscriber {
+    pub fn new(service: Arc<dyn GroupBuyService>) -> Arc<Self> {
+        Arc::new(Self { service })
+    }
+}
+
+impl EventSubscriber for GroupBuyPaidSubscriber {
+    fn name(&self) -> &'static str {
+        "group_buy_paid"
+    }
+
+    fn handles(&self, event_type: &str) -> bool {
+        event_type.strip_prefix(ORDER_EVENT_PREFIX) == Some(OrderStatus::Paid.as_str())
+    }
+
+    fn handle<'a>(&'a self, event: &'a DomainEvent) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let changed: OrderStatusChanged = event.decode().map_err(ServiceError::BadRequest)?;
+            self.service.on_order_paid(changed.order_id).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::payment::NotifyHeaders;
+    use crate::models::group_buy::{CAMPAIGN_ACTIVE, GroupMember};
+    use crate::models::order::{Actor, Order, OrderDetail, OrderEvent};
+    use crate::models::refund::{Refund, RefundApply, RefundStatus};
+    use crate::service::pricing::Quote;
+    use chrono::{DateTime, Duration};
+    use std::sync::Mutex;
+
+    fn campaign(ends_in_minutes: i64) -> GroupCampaign {
+        let now = Local::now();
+        GroupCampaign {
+            id: 1,
+            name: "两人团".to_string(),
+            sku_id: 1,
+            group_price: 990,
+            group_size: 2,
+            expire_minutes: 60,
+            starts_at: now - Duration::hours(1),
+            ends_at: now + Duration::minutes(ends_in_minutes),
+            status: CAMPAIGN_ACTIVE.to_string(),
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn test_group_expiry_capped_by_campaign_end() {
+        let now = Local::now();
+        let long = campaign(24 * 60);
+        assert_eq!(long.group_expires_at(now), now + Duration::minutes(60));
+        let ending = campaign(10);
+        assert_eq!(ending.group_expires_at(now), ending.ends_at);
+        assert!(ending.is_open(now));
+
+        let mut disabled = campaign(10);
+        disabled.status = CAMPAIGN_DISABLED.to_string();
+        assert!(!disabled.is_open(now));
+    }
+
+    #[test]
+    fn test_new_campaign_validation() {
+        let now = Local::now();
+        let mut new = NewGroupCampaign {
+            name: "三人团".to_string(),
+            sku_id: 1,
+            group_price: 1990,
+            group_size: 3,
+            expire_minutes: 1440,
+            starts_at: now,
+            ends_at: now + Duration::days(7),
+        };
+        assert!(new.validate().is_ok());
+        new.group_size = 1;
+        assert!(new.validate().is_err());
+        new.group_size = 3;
+        new.ends_at = now;
+        assert!(new.validate().is_err());
+    }
+
+    fn group(id: u64, status: GroupStatus, expires_at: DateTime<Local>) -> GroupBuy {
+        GroupBuy {
+            id,
+            campaign_id: 1,
+            leader_id: 1,
+            group_size: 2,
+            paid_count: 0,
+            status: status.as_str().to_string(),
+            expires_at,
+            succeeded_at: None,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    fn member(id: u64, group_id: u64, order_id: Option<u64>, paid: bool) -> GroupMember {
+        GroupMember {
+            id,
+            group_id,
+            user_id: id as u32,
+            is_leader: id == 1,
+            order_id,
+            paid_at: paid.then(Local::now),
+            created_at: None,
+        }
+    }
+
+    fn refund(order_id: u64) -> Refund {
+        Refund {
+            id: order_id,
+            refund_no: format!("R{}", order_id),
+            order_id,
+            order_item_id: None,
+            user_id: 1,
+            amount: 990,
+            reason: "group buy failed".to_string(),
+            status: RefundStatus::Processing.as_str().to_string(),
+            order_status: "paid".to_string(),
+            restock: true,
+            admin_id: Some(0),
+            reject_reason: None,
+            channel_refund_id: None,
+            refunded_at: None,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    #[derive(Default)]
+    struct MemoryGroups {
+        groups: Mutex<Vec<GroupBuy>>,
+        members: Mutex<Vec<GroupMember>>,
+    }
+
+    impl MemoryGroups {
+        fn status(&self, group_id: u64) -> String {
+            self.groups.lock().unwrap().iter().find(|g| g.id == group_id).unwrap().status.clone()
+        }
+
+        fn set_status(&self, group_id: u64, from: GroupStatus, to: GroupStatus, now: Option<DateTime<Local>>) -> bool {
+            let mut groups = self.groups.lock().unwrap();
+            match groups.iter_mut().find(|g| g.id == group_id) {
+                Some(g) if g.is(from) && now.is_none_or(|now| g.expires_at <= now) => {
+                    g.status = to.as_str().to_string();
+                    true
+                }
+                _ => false,
+            }
+        }
+    }
+
+    impl GroupBuyRepo for MemoryGroups {
+        fn create_campaign<'a>(&'a self, _campaign: &'a NewGroupCampaign) -> BoxFuture<'a, Result<u64, sqlx::Error>> {
+            unreachable!()
+        }
+        fn find_campaign(&self, _id: u64) -> BoxFuture<'_, Result<Option<GroupCampaign>, sqlx::Error>> {
+            Box::pin(async { Ok(Some(campaign(24 * 60))) })
+        }
+        fn list_campaigns(&self, _limit: u32, _offset: u32) -> BoxFuture<'_, Result<Vec<GroupCampaign>, sqlx::Error>> {
+            unreachable!()
+        }
+        fn list_open_campaigns(&self, _now: DateTime<Local>) -> BoxFuture<'_, Result<Vec<GroupCampaign>, sqlx::Error>> {
+            unreachable!()
+        }
+        fn set_campaign_status<'a>(&'a self, _id: u64, _status: &'a str) -> BoxFuture<'a, Result<bool, sqlx::Error>> {
+            unreachable!()
+        }
+        fn open(&self, campaign: &GroupCampaign, leader_id: u32, expires_at: DateTime<Local>) -> BoxFuture<'_, Result<(u64, u64), sqlx::Error>> {
+            let mut groups = self.groups.lock().unwrap();
+            let group_id = groups.len() as u64 + 1;
+            let mut opened = group(group_id, GroupStatus::Forming, expires_at);
+            opened.leader_id = leader_id;
+            opened.group_size = campaign.group_size;
+            groups.push(opened);
+            let mut members = self.members.lock().unwrap();
+            let member_id = members.len() as u64 + 1;
+            members.push(member(member_id, group_id, None, false));
+            Box::pin(async move { Ok((group_id, member_id)) })
+        }
+        fn join(&self, _group_id: u64, _user_id: u32, _now: DateTime<Local>) -> BoxFuture<'_, Result<JoinOutcome, sqlx::Error>> {
+            unreachable!()
+        }
+        fn attach_order(&self, _member_id: u64, _order_id: u64) -> BoxFuture<'_, Result<(), sqlx::Error>> {
+            unreachable!()
+        }
+        fn remove_member(&self, member_id: u64) -> BoxFuture<'_, Result<(), sqlx::Error>> {
+            self.members.lock().unwrap().retain(|m| m.id != member_id || m.order_id.is_some());
+            Box::pin(async { Ok(()) })
+        }
+        fn find_group(&self, id: u64) -> BoxFuture<'_, Result<Option<GroupBuy>, sqlx::Error>> {
+            let found = self.groups.lock().unwrap().iter().find(|g| g.id == id).cloned();
+            Box::pin(async move { Ok(found) })
+        }
+        fn list_members(&self, group_id: u64) -> BoxFuture<'_, Result<Vec<GroupMember>, sqlx::Error>> {
+            let members = self.members.lock().unwrap().iter().filter(|m| m.group_id == group_id).cloned().collect();
+            Box::pin(async move { Ok(members) })
+        }
+        fn mark_paid(&self, order_id: u64, paid_at: DateTime<Local>) -> BoxFuture<'_, Result<Option<GroupBuy>, sqlx::Error>> {
+            let mut members = self.members.lock().unwrap();
+            let mut groups = self.groups.lock().unwrap();
+            let found = members.iter_mut().find(|m| m.order_id == Some(order_id)).map(|m| {
+                let group = groups.iter_mut().find(|g| g.id == m.group_id).unwrap();
+                if m.paid_at.is_none() {
+                    m.paid_at = Some(paid_at);
+                    group.paid_count += 1;
+                    if group.is(GroupStatus::Forming) && group.paid_count >= group.group_size {
+                        group.status = GroupStatus::Succeeded.as_str().to_string();
+                    }
+                }
+                group.clone()
+            });
+            Box::pin(async move { Ok(found) })
+        }
+        fn fail(&self, group_id: u64, now: DateTime<Local>) -> BoxFuture<'_, Result<bool, sqlx::Error>> {
+            let failed = self.set_status(group_id, GroupStatus::Forming, GroupStatus::Failed, Some(now));
+            Box::pin(async move { Ok(failed) })
+        }
+        fn abort(&self, group_id: u64) -> BoxFuture<'_, Result<bool, sqlx::Error>> {
+            let failed = self.set_status(group_id, GroupStatus::Forming, GroupStatus::Failed, None);
+            Box::pin(async move { Ok(failed) })
+        }
+    }
+
+    /// 订单、退款和任务队列的桩实现：记录退款和取消的订单，下单总是失败
+    #[derive(Default)]
+    struct Settlement {
+        /// 订单当前状态，未列出的订单视为待支付
+        statuses: Mutex<Vec<(u64, OrderStatus)>>,
+        refunded: Mutex<Vec<u64>>,
+        cancelled: Mutex<Vec<u64>>,
+        enqueue_down: bool,
+    }
+
+    impl Settlement {
+        fn with_statuses(statuses: &[(u64, OrderStatus)]) -> Arc<Self> {
+            Arc::new(Settlement { statuses: Mutex::new(statuses.to_vec()), ..Default::default() })
+        }
+    }
+
+    fn order(id: u64, user_id: u32, status: OrderStatus) -> Order {
+        Order {
+            id,
+            order_no: format!("NO{}", id),
+            user_id,
+            status: status.as_str().to_string(),
+            goods_amount: 990,
+            discount_amount: 0,
+            shipping_fee: 0,
+            payable_amount: 990,
+            receiver_name: "r".into(),
+            receiver_phone: "p".into(),
+            province: "p".into(),
+            city: "c".into(),
+            district: "d".into(),
+            address_detail: "a".into(),
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    impl OrderService for Settlement {
+        fn preview(&self, _user_id: u32, _address_id: u64, _coupon_id: Option<u64>, _points: i64) -> BoxFuture<'_, Result<Quote, ServiceError>> {
+            unreachable!()
+        }
+        fn place(
+            &self,
+            _user_id: u32,
+            _address_id: u64,
+            _coupon_id: Option<u64>,
+            _points: i64,
+            _expected_payable: i64,
+        ) -> BoxFuture<'_, Result<OrderDetail, ServiceError>> {
+            unreachable!()
+        }
+        fn place_group(&self, _user_id: u32, _address_id: u64, sku_id: u64, _group_price: i64) -> BoxFuture<'_, Result<OrderDetail, ServiceError>> {
+            Box::pin(async move { Err(ServiceError::Conflict(format!("SKU {} is out of stock", sku_id))) })
+        }
+        fn get(&self, user_id: u32, order_id: u64) -> BoxFuture<'_, Result<OrderDetail, ServiceError>> {
+            let status = self
+                .statuses
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|(id, _)| *id == order_id)
+                .map_or(OrderStatus::PendingPayment, |(_, status)| *status);
+            let detail = OrderDetail { order: order(order_id, user_id, status), items: Vec::new() };
+            Box::pin(async move { Ok(detail) })
+        }
+        fn list(&self, _user_id: u32, _page: u32, _page_size: u32) -> BoxFuture<'_, Result<Vec<Order>, ServiceError>> {
+            unreachable!()
+        }
+        fn cancel<'a>(&'a self, _user_id: u32, _order_id: u64, _reason: &'a str) -> BoxFuture<'a, Result<OrderDetail, ServiceError>> {
+            unreachable!()
+        }
+        fn events(&self, _user_id: u32, _order_id: u64) -> BoxFuture<'_, Result<Vec<OrderEvent>, ServiceError>> {
+            unreachable!()
+        }
+        fn cancel_unpaid(&self, order_id: u64) -> BoxFuture<'_, Result<(), ServiceError>> {
+            self.cancelled.lock().unwrap().push(order_id);
+            Box::pin(async { Ok(()) })
+        }
+        fn complete_delivered(&self, _order_id: u64) -> BoxFuture<'_, Result<(), ServiceError>> {
+            unreachable!()
+        }
+    }
+
+    impl RefundService for Settlement {
+        fn apply<'a>(&'a self, _user_id: u32, _order_id: u64, _apply: &'a RefundApply) -> BoxFuture<'a, Result<Refund, ServiceError>> {
+            unreachable!()
+        }
+        fn list_for_order(&self, _user_id: u32, _order_id: u64) -> BoxFuture<'_, Result<Vec<Refund>, ServiceError>> {
+            unreachable!()
+        }
+        fn list_by_status<'a>(&'a self, _status: &'a str, _page: u32, _page_size: u32) -> BoxFuture<'a, Result<Vec<Refund>, ServiceError>> {
+            unreachable!()
+        }
+        fn approve(&self, _admin_id: u32, _refund_id: u64, _restock: bool) -> BoxFuture<'_, Result<Refund, ServiceError>> {
+            unreachable!()
+        }
+        fn refund_order<'a>(&'a self, order_id: u64, _reason: &'a str) -> BoxFuture<'a, Result<Refund, ServiceError>> {
+            self.refunded.lock().unwrap().push(order_id);
+            Box::pin(async move { Ok(refund(order_id)) })
+        }
+        fn refund_cancelled<'a>(&'a self, _order_id: u64, _reason: &'a str) -> BoxFuture<'a, Result<Refund, ServiceError>> {
+            unreachable!()
+        }
+        fn create_approved<'a>(
+            &'a self,
+            _admin_id: u32,
+            _order_id: u64,
+            _order_item_id: u64,
+            _amount: i64,
+            _reason: &'a str,
+        ) -> BoxFuture<'a, Result<Refund, ServiceError>> {
+            unreachable!()
+        }
+        fn reject<'a>(&'a self, _admin_id: u32, _refund_id: u64, _reason: &'a str) -> BoxFuture<'a, Result<Refund, ServiceError>> {
+            unreachable!()
+        }
+        fn handle_wechat_notify<'a>(&'a self, _headers: &'a NotifyHeaders, _body: &'a str) -> BoxFuture<'a, Result<(), ServiceError>> {
+            unreachable!()
+        }
+    }
+
+    impl JobQueue for Settlement {
+        fn enqueue<'a>(&'a self, _job: &'a Job, _run_at: i64) -> BoxFuture<'a, Result<(), fred::error::Error>> {
+            let result = if self.enqueue_down {
+                Err(fred::error::Error::new(fred::error::ErrorKind::IO, "connection lost"))
+            } else {
+                Ok(())
+            };
+            Box::pin(async move { result })
+        }
+        fn claim(&self, _now: i64, _visible_at: i64) -> BoxFuture<'_, Result<Option<Job>, fred::error::Error>> {
+            unreachable!()
+        }
+        fn ack<'a>(&'a self, _job_id: &'a str) -> BoxFuture<'a, Result<(), fred::error::Error>> {
+            unreachable!()
+        }
+        fn retry<'a>(&'a self, _job_id: &'a str, _run_at: i64) -> BoxFuture<'a, Result<(), fred::error::Error>> {
+            unreachable!()
+        }
+        fn bury<'a>(&'a self, _job: &'a Job, _error: &'a str) -> BoxFuture<'a, Result<(), fred::error::Error>> {
+            unreachable!()
+        }
+    }
+
+    fn service(repo: Arc<MemoryGroups>, settlement: Arc<Settlement>) -> GroupBuyServiceImpl<MemoryGroups> {
+        GroupBuyServiceImpl::new(repo, settlement.clone(), settlement.clone(), settlement)
+    }
+
+    #[tokio::test]
+    async fn test_expire_refunds_paid_and_cancels_unpaid() {
+        let repo = Arc::new(MemoryGroups::default());
+        repo.groups.lock().unwrap().push(group(1, GroupStatus::Forming, Local::now() - Duration::minutes(1)));
+        repo.members.lock().unwrap().extend([
+            member(1, 1, Some(11), true),
+            member(2, 1, Some(12), false),
+            member(3, 1, None, false),
+            // 已付款但 order.paid 还没投递到拼团，paid_at 为空
+            member(4, 1, Some(14), false),
+            member(5, 1, Some(15), false),
+        ]);
+        let settlement =
+            Settlement::with_statuses(&[(11, OrderStatus::Paid), (14, OrderStatus::Paid), (15, OrderStatus::Cancelled)]);
+        let service = service(repo.clone(), settlement.clone());
+
+        service.expire(1).await.unwrap();
+        assert_eq!(repo.status(1), GroupStatus::Failed.as_str());
+        assert_eq!(*settlement.refunded.lock().unwrap(), vec![11, 14]);
+        assert_eq!(*settlement.cancelled.lock().unwrap(), vec![12]);
+
+        // 重复投递时再结算一次，由退款和取消各自保证幂等
+        service.expire(1).await.unwrap();
+        assert_eq!(*settlement.refunded.lock().unwrap(), vec![11, 14, 11, 14]);
+    }
+
+    #[tokio::test]
+    async fn test_expire_before_deadline_or_after_success_does_nothing() {
+        let repo = Arc::new(MemoryGroups::default());
+        repo.groups.lock().unwrap().extend([
+            group(1, GroupStatus::Forming, Local::now() + Duration::minutes(10)),
+            group(2, GroupStatus::Succeeded, Local::now() - Duration::minutes(1)),
+        ]);
+        repo.members.lock().unwrap().extend([member(1, 1, Some(11), true), member(2, 2, Some(21), true)]);
+        let settlement = Arc::new(Settlement::default());
+        let service = service(repo.clone(), settlement.clone());
+
+        service.expire(1).await.unwrap();
+        service.expire(2).await.unwrap();
+        assert_eq!(repo.status(1), GroupStatus::Forming.as_str());
+        assert_eq!(repo.status(2), GroupStatus::Succeeded.as_str());
+        assert!(settlement.refunded.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_on_order_paid_forms_group_and_refunds_late_payment() {
+        let repo = Arc::new(MemoryGroups::default());
+        repo.groups.lock().unwrap().extend([
+            group(1, GroupStatus::Forming, Local::now() + Duration::minutes(10)),
+            group(2, GroupStatus::Failed, Local::now() - Duration::minutes(1)),
+        ]);
+        repo.members.lock().unwrap().extend([
+            member(1, 1, Some(11), false),
+            member(2, 1, Some(12), false),
+            member(3, 2, Some(21), false),
+        ]);
+        let settlement = Arc::new(Settlement::default());
+        let service = service(repo.clone(), settlement.clone());
+
+        service.on_order_paid(11).await.unwrap();
+        service.on_order_paid(11).await.unwrap();
+        assert_eq!(repo.status(1), GroupStatus::Forming.as_str());
+        service.on_order_paid(12).await.unwrap();
+        assert_eq!(repo.status(1), GroupStatus::Succeeded.as_str());
+        // 不是拼团订单
+        service.on_order_paid(99).await.unwrap();
+        assert!(settlement.refunded.lock().unwrap().is_empty());
+
+        service.on_order_paid(21).await.unwrap();
+        assert_eq!(*settlement.refunded.lock().unwrap(), vec![21]);
+    }
+
+    #[tokio::test]
+    async fn test_paid_subscriber_counts_payment() {
+        let repo = Arc::new(MemoryGroups::default());
+        let mut forming = group(1, GroupStatus::Forming, Local::now() + Duration::minutes(10));
+        forming.paid_count = 1;
+        repo.groups.lock().unwrap().push(forming);
+        repo.members.lock().unwrap().extend([member(1, 1, Some(11), false), member(2, 1, Some(12), true)]);
+        let subscriber = GroupBuyPaidSubscriber::new(Arc::new(service(repo.clone(), Arc::new(Settlement::default()))));
+        assert!(subscriber.handles("order.paid"));
+        assert!(!subscriber.handles("order.shipped"));
+
+        let paid = order(11, 1, OrderStatus::Paid);
+        let changed = OrderStatusChanged::new(&paid, OrderStatus::PendingPayment, OrderStatus::Paid, Actor::system(), "paid");
+        subscriber.handle(&DomainEvent::order_status_changed(&changed)).await.unwrap();
+        assert_eq!(repo.status(1), GroupStatus::Succeeded.as_str());
+    }
+
+    #[tokio::test]
+    async fn test_open_fails_group_when_leader_cannot_order_or_expiry_not_scheduled() {
+        let repo = Arc::new(MemoryGroups::default());
+        let settlement = Arc::new(Settlement::default());
+        let service = service(repo.clone(), settlement);
+        assert!(matches!(service.open(1, 1, 1).await, Err(ServiceError::Conflict(_))));
+        assert_eq!(repo.status(1), GroupStatus::Failed.as_str());
+        assert!(repo.members.lock().unwrap().is_empty());
+
+        let settlement = Arc::new(Settlement { enqueue_down: true, ..Default::default() });
+        let service = GroupBuyServiceImpl::new(repo.clone(), settlement.clone(), settlement.clone(), settlement);
+        assert!(matches!(service.open(1, 1, 1).await, Err(ServiceError::Redis(_))));
+        assert_eq!(repo.status(2), GroupStatus::Failed.as_str());
+    }
+}
@@ -0,0 +1,510 @@
+use crate::domain::BoxFuture;
+use crate::domain::blob::BlobStore;
+use crate::domain::import::ImportRepo;
+use crate::domain::jobs::JobQueue;
+use crate::models::audit::{AuditChange, NewAuditLog};
+use crate::models::catalog::STATUS_ON_SALE;
+use crate::models::export::{ExportFile, ExportFormat};
+use crate::models::import::{
+    Import, ImportCounts, ImportProgress, ImportStatus, LineError, ProductImport, SkuImport, source_key,
+};
+use crate::models::job::Job;
+use crate::service::ServiceError;
+use crate::service::audit::AuditService;
+use crate::service::jobs::{JobHandler, now_millis};
+use crate::service::reconciliation::parse_yuan;
+use crate::service::sheet::{SheetRow, read_rows};
+use chrono::Local;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use wx_shop::ImportSettings;
+
+/// 执行商品导入的任务
+pub const IMPORT_RUN_JOB: &str = "import_run";
+
+/// 商品下架状态
+const STATUS_OFF_SHELF: &str = "off_shelf";
+/// 编码的最大长度
+const MAX_CODE_LEN: usize = 64;
+/// 商品名称、规格的最大长度
+const MAX_NAME_LEN: usize = 128;
+
+/// 表头：字段名与中文列名，两种写法都可以
+const COLUMNS: [(&str, &str); 10] = [
+    ("product_code", "商品编码"),
+    ("product_name", "商品名称"),
+    ("category_id", "分类ID"),
+    ("product_status", "商品状态"),
+    ("sku_code", "SKU编码"),
+    ("sku_title", "规格"),
+    ("price", "价格(元)"),
+    ("weight_grams", "重量(克)"),
+    ("sku_status", "SKU状态"),
+    ("stock", "库存"),
+];
+const REQUIRED_COLUMNS: [&str; 5] = ["product_code", "product_name", "sku_code", "sku_title", "price"];
+
+pub trait ImportService: Send + Sync {
+    /// 保存上传的文件并立即排期执行
+    fn submit(&self, format: ExportFormat, dry_run: bool, bytes: Vec<u8>, admin_id: u32) -> BoxFuture<'_, Result<ImportProgress, ServiceError>>;
+    fn get(&self, id: u64) -> BoxFuture<'_, Result<ImportProgress, ServiceError>>;
+    fn list(&self, page: u32, page_size: u32) -> BoxFuture<'_, Result<Vec<ImportProgress>, ServiceError>>;
+    /// 校验全部行，全部通过后分批写入；已结束的导入直接跳过
+    fn run(&self, id: u64) -> BoxFuture<'_, Result<(), ServiceError>>;
+    fn fail<'a>(&'a self, id: u64, error: &'a str) -> BoxFuture<'a, Result<(), ServiceError>>;
+    /// 逐行结果文件
+    fn report(&self, id: u64) -> BoxFuture<'_, Result<ExportFile, ServiceError>>;
+}
+
+pub struct ImportServiceImpl<R: ImportRepo + 'static> {
+    repo: Arc<R>,
+    blob: Arc<dyn BlobStore>,
+    jobs: Arc<dyn JobQueue>,
+    audit: Arc<dyn AuditService>,
+    settings: ImportSettings,
+}
+
+/// 结果文件中的一行
+struct ReportLine {
+    line: u64,
+    product_code: String,
+    sku_code: String,
+    result: &'static str,
+    message: String,
+}
+
+impl<R: ImportRepo + 'static> ImportServiceImpl<R> {
+    pub fn new(
+        repo: Arc<R>,
+        blob: Arc<dyn BlobStore>,
+        jobs: Arc<dyn JobQueue>,
+        audit: Arc<dyn AuditService>,
+        settings: ImportSettings,
+    ) -> Self {
+        Self { repo, blob, jobs, audit, settings }
+    }
+
+    async fn find(&self, id: u64) -> Result<Import, ServiceError> {
+        self.repo
+            .find(id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("import {} not found", id)))
+    }
+
+    /// 预览：按编码是否已存在判断新增还是更新，不写入
+    async fn preview(&self, products: &[ProductImport], counts: &mut ImportCounts) -> Result<Vec<ReportLine>, ServiceError> {
+        let mut existing_products = HashSet::new();
+        let mut existing_skus = HashSet::new();
+        for chunk in products.chunks(self.settings.chunk_size.max(1)) {
+            let codes: Vec<&str> = chunk.iter().map(|product| product.code.as_str()).collect();
+            existing_products.extend(self.repo.existing_product_codes(&codes).await?);
+            let codes: Vec<&str> = chunk.iter().flat_map(|product| product.skus.iter().map(|sku| sku.code.as_str())).collect();
+            existing_skus.extend(self.repo.existing_sku_codes(&codes).await?);
+        }
+        let mut lines = Vec::new();
+        for product in products {
+            let created = !existing_products.contains(&product.code);
+            let skus_created = product.skus.iter().map(|sku| !existing_skus.contains(&sku.code)).collect::<Vec<_>>();
+            count(counts, created, &skus_created);
+            lines.extend(report_lines(product, created, &skus_created, &[]));
+        }
+        Ok(lines)
+    }
+
+    /// 每批商品一个事务写入，每批后更新进度；批内有改价时写一条审计日志
+    async fn upsert(&self, import: &Import, products: &[ProductImport], counts: &mut ImportCounts) -> Result<Vec<ReportLine>, ServiceError> {
+        let mut lines = Vec::new();
+        let mut processed = 0;
+        for chunk in products.chunks(self.settings.chunk_size.max(1)) {
+            let results = self.repo.upsert_products(chunk).await?;
+            let mut changes = Vec::new();
+            for (product, result) in chunk.iter().zip(&results) {
+                let skus_created: Vec<bool> = result.previous_prices.iter().map(Option::is_none).collect();
+                count(counts, result.created, &skus_created);
+                lines.extend(report_lines(product, result.created, &skus_created, &result.previous_prices));
+                changes.extend(price_changes(product, &result.previous_prices));
+                processed += product.skus.len() as u64;
+            }
+            self.repo.progress(import.id, processed).await?;
+            if !changes.is_empty() {
+                let log = NewAuditLog {
+                    actor_id: import.admin_id,
+                    ip: None,
+                    forwarded_for: None,
+                    session_id: None,
+                    action: IMPORT_RUN_JOB.to_string(),
+                    target_type: "imports".to_string(),
+                    target_id: Some(import.id.to_string()),
+                    status_code: None,
+                    request: None,
+                    changes,
+                };
+                // 数据已提交，审计写入失败只记录日志，避免重试重复导入
+                if let Err(e) = self.audit.record(log).await {
+                    tracing::error!("Failed to record price changes of import {}: {:?}", import.id, e);
+                }
+            }
+        }
+        Ok(lines)
+    }
+
+    async fn write_report(&self, id: u64, mut lines: Vec<ReportLine>) -> Result<String, ServiceError> {
+        lines.sort_by_key(|line| line.line);
+        let mut writer = csv::Writer::from_writer(b"\xEF\xBB\xBF".to_vec());
+        writer.write_record(["行号", "商品编码", "SKU编码", "结果", "说明"]).map_err(std::io::Error::from)?;
+        for line in &lines {
+            let line_no = line.line.to_string();
+            writer
+                .write_record([line_no.as_str(), &line.product_code, &line.sku_code, line.result, &line.message])
+                .map_err(std::io::Error::from)?;
+        }
+        let bytes = writer.into_inner().map_err(|e| std::io::Error::other(e.to_string()))?;
+        let key = format!("imports/{}/report.csv", id);
+        self.blob.put(&key, &bytes).await?;
+        Ok(key)
+    }
+}
+
+impl<R: ImportRepo + 'static> ImportService for ImportServiceImpl<R> {
+    fn submit(&self, format: ExportFormat, dry_run: bool, bytes: Vec<u8>, admin_id: u32) -> BoxFuture<'_, Result<ImportProgress, ServiceError>> {
+        Box::pin(async move {
+            if bytes.is_empty() {
+                return Err(ServiceError::BadRequest("import file is empty".to_string()));
+            }
+            let id = self.repo.create(format.as_str(), dry_run, admin_id).await?;
+            self.blob.put(&source_key(id, format.as_str()), &bytes).await?;
+            let job = Job::new(IMPORT_RUN_JOB, &id.to_string(), serde_json::json!({ "import_id": id }));
+            self.jobs.enqueue(&job, now_millis()).await?;
+            tracing::info!("Admin {} submitted import {} ({} bytes, dry run: {})", admin_id, id, bytes.len(), dry_run);
+            Ok(to_progress(self.find(id).await?))
+        })
+    }
+
+    fn get(&self, id: u64) -> BoxFuture<'_, Result<ImportProgress, ServiceError>> {
+        Box::pin(async move { Ok(to_progress(self.find(id).await?)) })
+    }
+
+    fn list(&self, page: u32, page_size: u32) -> BoxFuture<'_, Result<Vec<ImportProgress>, ServiceError>> {
+        Box::pin(async move {
+            let page_size = page_size.clamp(1, 100);
+            let offset = page.saturating_sub(1) * page_size;
+            let imports = self.repo.list(page_size, offset).await?;
+            Ok(imports.into_iter().map(to_progress).collect())
+        })
+    }
+
+    fn run(&self, id: u64) -> BoxFuture<'_, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let import = self.find(id).await?;
+            if !import.is(ImportStatus::Pending) && !import.is(ImportStatus::Running) {
+                return Ok(());
+            }
+            let format: ExportFormat = import.format.parse().map_err(ServiceError::BadRequest)?;
+            let bytes = self.blob.get(&import.source_key()).await?;
+            // 文件本身有问题时重试也不会成功，直接标记失败
+            let parsed = read_rows(format, &bytes).and_then(|rows| validate(&rows));
+            let (products, errors, total_rows) = match parsed {
+                Ok(parsed) => parsed,
+                Err(e) => return Ok(self.repo.fail(id, &e).await?),
+            };
+            if total_rows > self.settings.max_rows {
+                let error = format!("{} rows exceed the limit of {}, split the file", total_rows, self.settings.max_rows);
+                return Ok(self.repo.fail(id, &error).await?);
+            }
+            self.repo.start(id, total_rows, Local::now()).await?;
+
+            let mut counts = ImportCounts { total_rows, error_rows: errors.len() as u64, ..Default::default() };
+            if !errors.is_empty() {
+                let lines = errors
+                    .into_iter()
+                    .map(|error| ReportLine {
+                        line: error.line,
+                        product_code: error.product_code,
+                        sku_code: error.sku_code,
+                        result: "错误",
+                        message: error.message,
+                    })
+                    .collect();
+                let report_key = self.write_report(id, lines).await?;
+                let error = format!("{} rows failed validation, nothing was imported", counts.error_rows);
+                self.repo.finish(id, ImportStatus::Failed, &counts, &report_key, Some(&error)).await?;
+                return Ok(());
+            }
+
+            let lines = if import.dry_run {
+                self.preview(&products, &mut counts).await?
+            } else {
+                self.upsert(&import, &products, &mut counts).await?
+            };
+            let report_key = self.write_report(id, lines).await?;
+            self.repo.finish(id, ImportStatus::Succeeded, &counts, &report_key, None).await?;
+            tracing::info!("Import {} finished: {:?}, dry run: {}", id, counts, import.dry_run);
+            Ok(())
+        })
+    }
+
+    fn fail<'a>(&'a self, id: u64, error: &'a str) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move { Ok(self.repo.fail(id, error).await?) })
+    }
+
+    fn report(&self, id: u64) -> BoxFuture<'_, Result<ExportFile, ServiceError>> {
+        Box::pin(async move {
+            let import = self.find(id).await?;
+            let key = import
+                .report_key
+                .ok_or_else(|| ServiceError::Conflict(format!("import {} has no report yet", id)))?;
+            let content = self.blob.open(&key).await?;
+            Ok(ExportFile { file_name: format!("import-{}-report.csv", id), content_type: ExportFormat::Csv.content_type(), content })
+        })
+    }
+}
+
+fn to_progress(import: Import) -> ImportProgress {
+    let finished = import.is(ImportStatus::Succeeded) || import.is(ImportStatus::Failed);
+    let percent = (import.processed_rows.min(import.total_rows) * 100)
+        .checked_div(import.total_rows)
+        .map_or(if finished { 100 } else { 0 }, |percent| percent as u32);
+    let report_url = import.report_key.as_ref().map(|_| format!("/admin/imports/{}/report", import.id));
+    ImportProgress { import, percent, report_url }
+}
+
+fn count(counts: &mut ImportCounts, product_created: bool, skus_created: &[bool]) {
+    if product_created {
+        counts.created_products += 1;
+    } else {
+        counts.updated_products += 1;
+    }
+    let created_skus = skus_created.iter().filter(|created| **created).count() as u64;
+    counts.created_skus += created_skus;
+    counts.updated_skus += skus_created.len() as u64 - created_skus;
+}
+
+/// `previous_prices` 为空时（预览）不标注改价
+fn report_lines(product: &ProductImport, product_created: bool, skus_created: &[bool], previous_prices: &[Option<i64>]) -> Vec<ReportLine> {
+    product
+        .skus
+        .iter()
+        .zip(skus_created)
+        .enumerate()
+        .map(|(index, (sku, created))| {
+            let mut notes = Vec::new();
+            if product_created {
+                notes.push("new product".to_string());
+            }
+            if let Some(Some(previous)) = previous_prices.get(index)
+                && *previous != sku.price
+            {
+                notes.push(format!("price {} -> {}", previous, sku.price));
+            }
+            ReportLine {
+                line: sku.line,
+                product_code: product.code.clone(),
+                sku_code: sku.code.clone(),
+                result: if *created { "新增" } else { "更新" },
+                message: notes.join("; "),
+            }
+        })
+        .collect()
+}
+
+/// 已有 SKU 的改价记录，价格单位为分
+fn price_changes(product: &ProductImport, previous_prices: &[Option<i64>]) -> Vec<AuditChange> {
+    product
+        .skus
+        .iter()
+        .zip(previous_prices)
+        .filter_map(|(sku, previous)| match previous {
+            Some(previous) if *previous != sku.price => Some(AuditChange {
+                entity: "sku".to_string(),
+                entity_id: sku.code.clone(),
+                before: serde_json::json!({ "price": previous }),
+                after: serde_json::json!({ "price": sku.price }),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// 校验表格：第一行为表头，其余每行一个 SKU，按商品编码归并。
+/// 返回归并后的商品、未通过校验的行与数据行数；表头不合法时返回错误
+fn validate(rows: &[SheetRow]) -> Result<(Vec<ProductImport>, Vec<LineError>, u64), String> {
+    let Some(((_, header), rows)) = rows.split_first() else {
+        return Err("import file has no header".to_string());
+    };
+    let mut columns: HashMap<&str, usize> = HashMap::new();
+    for (index, name) in header.iter().enumerate() {
+        if let Some((field, _)) = COLUMNS.iter().find(|(field, label)| name == field || name == label) {
+            columns.insert(field, index);
+        }
+    }
+    let missing: Vec<&str> = REQUIRED_COLUMNS.into_iter().filter(|field| !columns.contains_key(field)).collect();
+    if !missing.is_empty() {
+        return Err(format!("missing columns: {}", missing.join(", ")));
+    }
+
+    let mut products: Vec<ProductImport> = Vec::new();
+    // 商品编码 -> (products 中的下标, 首次出现的行号)
+    let mut product_index: HashMap<String, (usize, u64)> = HashMap::new();
+    let mut sku_lines: HashMap<String, u64> = HashMap::new();
+    let mut errors = Vec::new();
+    for (line, cells) in rows {
+        let get = |field: &str| columns.get(field).and_then(|index| cells.get(*index)).map_or("", String::as_str);
+        let mut messages = Vec::new();
+        let mut text = |field: &str, max_len: usize| {
+            let value = get(field);
+            if value.is_empty() {
+                messages.push(format!("{} is required", field));
+            } else if value.chars().count() > max_len {
+                messages.push(format!("{} is longer than {} characters", field, max_len));
+            }
+            value.to_string()
+        };
+        let product_code = text("product_code", MAX_CODE_LEN);
+        let product_name = text("product_name", MAX_NAME_LEN);
+        let sku_code = text("sku_code", MAX_CODE_LEN);
+        let sku_title = text("sku_title", MAX_NAME_LEN);
+
+        let mut number = |field: &str, max: u64| -> u64 {
+            let value = get(field);
+            if value.is_empty() {
+                return 0;
+            }
+            // xlsx 中的整数可能带 ".0"
+            match value.strip_suffix(".0").unwrap_or(value).parse::<u64>() {
+                Ok(number) if number <= max => number,
+                _ => {
+                    messages.push(format!("{} {:?} is not a valid non-negative integer", field, value));
+                    0
+                }
+            }
+        };
+        let category_id = number("category_id", u64::MAX);
+        let weight_grams = number("weight_grams", u64::from(u32::MAX)) as u32;
+        let stock = number("stock", i64::MAX as u64) as i64;
+
+        let price = match parse_yuan(get("price")) {
+            Some(price) if price > 0 => price,
+            _ => {
+                messages.push(format!("price {:?} must be a positive amount with at most 2 decimals", get("price")));
+                0
+            }
+        };
+        let mut status = |field: &str| {
+            let value = get(field);
+            match value {
+                "" => STATUS_ON_SALE.to_string(),
+                STATUS_ON_SALE | STATUS_OFF_SHELF => value.to_string(),
+                _ => {
+                    messages.push(format!("{} must be {} or {}", field, STATUS_ON_SALE, STATUS_OFF_SHELF));
+                    value.to_string()
+                }
+            }
+        };
+        let product_status = status("product_status");
+        let sku_status = status("sku_status");
+
+        if let Some(first) = sku_lines.get(&sku_code) {
+            messages.push(format!("sku_code duplicates line {}", first));
+        }
+        if let Some((index, first)) = product_index.get(&product_code) {
+            let product = &products[*index];
+            if product.name != product_name || product.category_id != category_id || product.status != product_status {
+                messages.push(format!("product fields differ from line {} of the same product_code", first));
+            }
+        }
+
+        if !messages.is_empty() {
+            errors.push(LineError { line: *line, product_code, sku_code, message: messages.join("; ") });
+            continue;
+        }
+        sku_lines.insert(sku_code.clone(), *line);
+        let sku = SkuImport { line: *line, code: sku_code, title: sku_title, price, weight_grams, status: sku_status, stock };
+        match product_index.get(&product_code) {
+            Some((index, _)) => products[*index].skus.push(sku),
+            None => {
+                product_index.insert(product_code.clone(), (products.len(), *line));
+                products.push(ProductImport {
+                    code: product_code,
+                    name: product_name,
+                    category_id,
+                    status: product_status,
+                    skus: vec![sku],
+                });
+            }
+        }
+    }
+    Ok((products, errors, rows.len() as u64))
+}
+
+/// 执行导入；最后一次重试仍失败时把导入标记为失败
+pub struct ImportRunJob {
+    service: Arc<dyn ImportService>,
+}
+
+impl ImportRunJob {
+    pub fn new(service: Arc<dyn ImportService>) -> Arc<Self> {
+        Arc::new(Self { service })
+    }
+}
+
+impl JobHandler for ImportRunJob {
+    fn kind(&self) -> &'static str {
+        IMPORT_RUN_JOB
+    }
+
+    fn handle<'a>(&'a self, job: &'a Job) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let id = job.payload["import_id"]
+                .as_u64()
+                .ok_or_else(|| ServiceError::BadRequest(format!("invalid payload of job {}", job.id)))?;
+            let result = self.service.run(id).await;
+            if let Err(e) = &result
+                && job.attempts >= job.max_attempts
+            {
+                self.service.fail(id, &format!("{:?}", e)).await?;
+            }
+            result
+        })
+    }
+}
+
+pub fn new_import_service<R: ImportRepo + 'static>(
+    repo: Arc<R>,
+    blob: Arc<dyn BlobStore>,
+    jobs: Arc<dyn JobQueue>,
+    audit: Arc<dyn AuditService>,
+    settings: ImportSettings,
+) -> Arc<dyn ImportService> {
+    Arc::new(ImportServiceImpl::new(repo, blob, jobs, audit, settings)) as Arc<dyn ImportService>
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(line: u64, cells: &[&str]) -> SheetRow {
+        (line, cells.iter().map(|cell| cell.to_string()).collect())
+    }
+
+    #[test]
+    fn test_validate_groups_skus_and_reports_line_errors() {
+        let rows = vec![
+            row(1, &["商品编码", "商品名称", "sku_code", "规格", "价格(元)", "库存"]),
+            row(2, &["P1", "T恤", "P1-S", "S", "59.9", "10"]),
+            row(3, &["P1", "T恤", "P1-M", "M", "59.90", ""]),
+            row(4, &["P1", "衬衫", "P1-L", "L", "59.9", "1"]),
+            row(5, &["P2", "帽子", "P1-S", "均码", "0", "-1"]),
+        ];
+        let (products, errors, total) = validate(&rows).unwrap();
+
+        assert_eq!(total, 4);
+        assert_eq!(products.len(), 1);
+        assert_eq!(products[0].skus.iter().map(|sku| (sku.line, sku.price, sku.stock)).collect::<Vec<_>>(), vec![(2, 5990, 10), (3, 5990, 0)]);
+        assert_eq!(errors.iter().map(|error| error.line).collect::<Vec<_>>(), vec![4, 5]);
+        assert!(errors[0].message.contains("differ from line 2"));
+        assert_eq!(errors[1].message.split("; ").count(), 3);
+
+        let missing = validate(&[row(1, &["商品编码", "规格"])]).unwrap_err();
+        assert_eq!(missing, "missing columns: product_name, sku_code, price");
+    }
+}
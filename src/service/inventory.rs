@@ -0,0 +1,548 @@
+use crate::domain::BoxFuture;
+use crate::domain::inventory::{CounterRejection, InventoryRepo, StockCounter};
+use crate::models::inventory::{Inventory, MovementKind, StockMovement};
+use crate::service::ServiceError;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 一轮对账的结果
+#[derive(Debug, Default, Serialize)]
+pub struct ReconcileReport {
+    /// 成功回写 MySQL 的流水数
+    pub applied: usize,
+    /// 已回写过、被幂等跳过的流水数
+    pub duplicates: usize,
+    /// 队列清空后 Redis 与 MySQL 不一致的 SKU
+    pub drifts: Vec<StockDrift>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StockDrift {
+    pub sku_id: u64,
+    pub redis_stock: Option<i64>,
+    pub mysql_stock: i64,
+}
+
+pub trait InventoryService: Send + Sync {
+    /// 为订单预占库存，按 SKU 的 stock_mode 选择 MySQL 行锁或 Redis 计数器
+    fn reserve<'a>(&'a self, sku_id: u64, user_id: u32, quantity: u32, order_ref: &'a str) -> BoxFuture<'a, Result<(), ServiceError>>;
+    /// 释放订单预占的库存，重复释放是无害的
+    fn release<'a>(&'a self, sku_id: u64, order_ref: &'a str) -> BoxFuture<'a, Result<Option<i64>, ServiceError>>;
+    /// 售后退货入库：按 `order_ref`（售后单号）回补指定数量，重复入库只生效一次
+    fn restock<'a>(&'a self, sku_id: u64, user_id: u32, quantity: u32, order_ref: &'a str) -> BoxFuture<'a, Result<(), ServiceError>>;
+    /// SKU 是否为秒杀（Redis 计数器）模式；MySQL 模式的预占可以并入调用方的事务
+    fn is_flash_sale(&self, sku_id: u64) -> BoxFuture<'_, Result<bool, ServiceError>>;
+    /// 当前可售库存
+    fn available(&self, sku_id: u64) -> BoxFuture<'_, Result<i64, ServiceError>>;
+    /// 为所有秒杀 SKU 初始化 Redis 计数器（已存在的不覆盖）
+    fn warm_up(&self) -> BoxFuture<'_, Result<usize, ServiceError>>;
+    /// 把 Redis 流水回写 MySQL，并在队列清空时检查两边是否一致
+    fn reconcile(&self, batch_size: usize) -> BoxFuture<'_, Result<ReconcileReport, ServiceError>>;
+}
+
+pub struct InventoryServiceImpl<R: InventoryRepo + 'static, C: StockCounter + 'static> {
+    repo: Arc<R>,
+    counter: Arc<C>,
+}
+
+impl<R: InventoryRepo + 'static, C: StockCounter + 'static> InventoryServiceImpl<R, C> {
+    pub fn new(repo: Arc<R>, counter: Arc<C>) -> Self {
+        Self { repo, counter }
+    }
+
+    async fn load(&self, sku_id: u64) -> Result<Inventory, ServiceError> {
+        self.repo
+            .find_by_sku(sku_id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Inventory for SKU {} not found", sku_id)))
+    }
+
+    async fn check_drift(&self) -> Result<Vec<StockDrift>, ServiceError> {
+        let mut drifts = Vec::new();
+        for inventory in self.repo.list_redis_mode().await? {
+            let redis_stock = self.counter.get(inventory.sku_id).await?;
+            // 读取期间又有新流水进入时本轮不下结论
+            if self.counter.pending().await? > 0 {
+                return Ok(drifts);
+            }
+            if redis_stock != Some(inventory.stock) {
+                tracing::warn!(
+                    "Stock drift detected for SKU {}: redis={:?}, mysql={}",
+                    inventory.sku_id, redis_stock, inventory.stock
+                );
+                drifts.push(StockDrift { sku_id: inventory.sku_id, redis_stock, mysql_stock: inventory.stock });
+            }
+        }
+        Ok(drifts)
+    }
+}
+
+impl<R: InventoryRepo + 'static, C: StockCounter + 'static> InventoryService for InventoryServiceImpl<R, C> {
+    fn reserve<'a>(&'a self, sku_id: u64, user_id: u32, quantity: u32, order_ref: &'a str) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let inventory = self.load(sku_id).await?;
+            let movement = StockMovement {
+                sku_id,
+                order_ref: order_ref.to_string(),
+                user_id,
+                kind: MovementKind::Reserve,
+                delta: -i64::from(quantity),
+            };
+
+            if !inventory.is_redis_mode() {
+                return if self.repo.reserve(&movement).await? {
+                    Ok(())
+                } else {
+                    Err(ServiceError::Conflict(format!("SKU {} is out of stock", sku_id)))
+                };
+            }
+
+            match self.counter.reserve(&movement, inventory.per_user_limit).await? {
+                Ok(_) => Ok(()),
+                Err(CounterRejection::OutOfStock) => Err(ServiceError::Conflict(format!("SKU {} is out of stock", sku_id))),
+                Err(CounterRejection::LimitExceeded) => Err(ServiceError::Conflict(format!(
+                    "Purchase limit of SKU {} exceeded",
+                    sku_id
+                ))),
+                Err(CounterRejection::NotInitialized) => {
+                    tracing::error!("Stock counter for SKU {} is not initialized", sku_id);
+                    Err(ServiceError::Conflict(format!("SKU {} is not on sale yet", sku_id)))
+                }
+            }
+        })
+    }
+
+    fn release<'a>(&'a self, sku_id: u64, order_ref: &'a str) -> BoxFuture<'a, Result<Option<i64>, ServiceError>> {
+        Box::pin(async move {
+            let inventory = self.load(sku_id).await?;
+            if inventory.is_redis_mode() {
+                Ok(self.counter.release(sku_id, order_ref).await?)
+            } else {
+                Ok(self.repo.release(sku_id, order_ref).await?)
+            }
+        })
+    }
+
+    fn restock<'a>(&'a self, sku_id: u64, user_id: u32, quantity: u32, order_ref: &'a str) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let inventory = self.load(sku_id).await?;
+            let movement = StockMovement {
+                sku_id,
+                order_ref: order_ref.to_string(),
+                user_id,
+                kind: MovementKind::Restock,
+                delta: i64::from(quantity),
+            };
+            // 计数器还没预热时直接入 MySQL，预热时会以 MySQL 库存为准
+            if inventory.is_redis_mode() && self.counter.restock(&movement).await?.is_ok() {
+                return Ok(());
+            }
+            self.repo.restock(&movement).await?;
+            Ok(())
+        })
+    }
+
+    fn is_flash_sale(&self, sku_id: u64) -> BoxFuture<'_, Result<bool, ServiceError>> {
+        Box::pin(async move { Ok(self.load(sku_id).await?.is_redis_mode()) })
+    }
+
+    fn available(&self, sku_id: u64) -> BoxFuture<'_, Result<i64, ServiceError>> {
+        Box::pin(async move {
+            let inventory = self.load(sku_id).await?;
+            if inventory.is_redis_mode()
+                && let Some(stock) = self.counter.get(sku_id).await?
+            {
+                return Ok(stock);
+            }
+            Ok(inventory.stock)
+        })
+    }
+
+    fn warm_up(&self) -> BoxFuture<'_, Result<usize, ServiceError>> {
+        Box::pin(async move {
+            let requeued = self.counter.requeue_unacked().await?;
+            if requeued > 0 {
+                tracing::warn!("Requeued {} unacknowledged stock movements", requeued);
+            }
+
+            let mut seeded = 0;
+            for inventory in self.repo.list_redis_mode().await? {
+                if self.counter.seed(inventory.sku_id, inventory.stock).await? {
+                    seeded += 1;
+                }
+            }
+            Ok(seeded)
+        })
+    }
+
+    fn reconcile(&self, batch_size: usize) -> BoxFuture<'_, Result<ReconcileReport, ServiceError>> {
+        Box::pin(async move {
+            let mut report = ReconcileReport::default();
+            for _ in 0..batch_size {
+                let Some(raw) = self.counter.claim_movement().await? else {
+                    break;
+                };
+                match serde_json::from_str::<StockMovement>(&raw) {
+                    Ok(movement) => match self.repo.apply_movement(&movement).await {
+                        Ok(true) => report.applied += 1,
+                        Ok(false) => report.duplicates += 1,
+                        Err(e) => {
+                            // 写库失败时放回待处理队列，否则 pending 一直大于 0，漂移检测也随之停止
+                            self.counter.nack_movement(&raw).await?;
+                            return Err(e.into());
+                        }
+                    },
+                    Err(e) => tracing::error!("Dropping malformed stock movement {:?}: {}", raw, e),
+                }
+                self.counter.ack_movement(&raw).await?;
+            }
+
+            if self.counter.pending().await? == 0 {
+                report.drifts = self.check_drift().await?;
+            }
+            Ok(report)
+        })
+    }
+}
+
+pub fn new_inventory_service<R, C>(repo: Arc<R>, counter: Arc<C>) -> Arc<dyn InventoryService>
+where
+    R: InventoryRepo + 'static,
+    C: StockCounter + 'static,
+{
+    Arc::new(InventoryServiceImpl::new(repo, counter)) as Arc<dyn InventoryService>
+}
+
+/// 启动秒杀库存对账任务：预热计数器后按固定间隔回写流水
+pub fn spawn_reconciler(service: Arc<dyn InventoryService>, interval: Duration, batch_size: usize) {
+    tokio::spawn(async move {
+        match service.warm_up().await {
+            Ok(seeded) => tracing::info!("Seeded {} flash-sale stock counters", seeded),
+            Err(e) => tracing::error!("Failed to warm up stock counters: {:?}", e),
+        }
+
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match service.reconcile(batch_size).await {
+                Ok(report) if report.applied > 0 || !report.drifts.is_empty() => {
+                    tracing::info!(
+                        "Stock reconcile: applied={}, duplicates={}, drifts={}",
+                        report.applied, report.duplicates, report.drifts.len()
+                    );
+                }
+                Ok(_) => {}
+                Err(e) => tracing::error!("Stock reconcile failed: {:?}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::inventory::STOCK_MODE_REDIS;
+    use std::collections::{HashMap, VecDeque};
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    fn inventory(sku_id: u64, stock: i64, stock_mode: &str, per_user_limit: Option<u32>) -> Inventory {
+        Inventory { sku_id, stock, stock_mode: stock_mode.to_string(), per_user_limit, created_at: None, updated_at: None }
+    }
+
+    /// 内存版库存表与库存流水
+    #[derive(Default)]
+    struct MemoryInventory {
+        rows: Mutex<Vec<Inventory>>,
+        ledger: Mutex<Vec<StockMovement>>,
+        fail_writes: AtomicBool,
+    }
+
+    impl MemoryInventory {
+        fn with(rows: Vec<Inventory>) -> Arc<Self> {
+            Arc::new(MemoryInventory { rows: Mutex::new(rows), ..Default::default() })
+        }
+
+        fn stock(&self, sku_id: u64) -> i64 {
+            self.rows.lock().unwrap().iter().find(|row| row.sku_id == sku_id).unwrap().stock
+        }
+
+        fn recorded(&self, movement: &StockMovement) -> bool {
+            let ledger = self.ledger.lock().unwrap();
+            ledger.iter().any(|m| m.sku_id == movement.sku_id && m.order_ref == movement.order_ref && m.kind == movement.kind)
+        }
+
+        /// 流水不存在时记账并调整库存，返回是否真正写入
+        fn record(&self, movement: &StockMovement) -> bool {
+            if self.recorded(movement) {
+                return false;
+            }
+            self.ledger.lock().unwrap().push(movement.clone());
+            let mut rows = self.rows.lock().unwrap();
+            rows.iter_mut().find(|row| row.sku_id == movement.sku_id).unwrap().stock += movement.delta;
+            true
+        }
+    }
+
+    impl InventoryRepo for MemoryInventory {
+        fn find_by_sku(&self, sku_id: u64) -> BoxFuture<'_, Result<Option<Inventory>, sqlx::Error>> {
+            let row = self.rows.lock().unwrap().iter().find(|row| row.sku_id == sku_id).cloned();
+            Box::pin(async move { Ok(row) })
+        }
+
+        fn list_redis_mode(&self) -> BoxFuture<'_, Result<Vec<Inventory>, sqlx::Error>> {
+            let rows = self.rows.lock().unwrap().iter().filter(|row| row.is_redis_mode()).cloned().collect();
+            Box::pin(async move { Ok(rows) })
+        }
+
+        fn reserve<'a>(&'a self, movement: &'a StockMovement) -> BoxFuture<'a, Result<bool, sqlx::Error>> {
+            let reserved = self.recorded(movement) || (self.stock(movement.sku_id) + movement.delta >= 0 && self.record(movement));
+            Box::pin(async move { Ok(reserved) })
+        }
+
+        fn release<'a>(&'a self, sku_id: u64, order_ref: &'a str) -> BoxFuture<'a, Result<Option<i64>, sqlx::Error>> {
+            let hold = self
+                .ledger
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|m| m.sku_id == sku_id && m.order_ref == order_ref && m.kind == MovementKind::Reserve)
+                .cloned();
+            let released = hold.and_then(|hold| {
+                let movement = StockMovement { kind: MovementKind::Release, delta: -hold.delta, ..hold };
+                self.record(&movement).then_some(movement.delta)
+            });
+            Box::pin(async move { Ok(released) })
+        }
+
+        fn restock<'a>(&'a self, movement: &'a StockMovement) -> BoxFuture<'a, Result<bool, sqlx::Error>> {
+            let restocked = self.record(movement);
+            Box::pin(async move { Ok(restocked) })
+        }
+
+        fn apply_movement<'a>(&'a self, movement: &'a StockMovement) -> BoxFuture<'a, Result<bool, sqlx::Error>> {
+            let result = if self.fail_writes.load(Ordering::SeqCst) {
+                Err(sqlx::Error::PoolTimedOut)
+            } else {
+                Ok(self.record(movement))
+            };
+            Box::pin(async move { result })
+        }
+    }
+
+    /// 内存版 Redis 计数器，语义与 Lua 脚本一致
+    #[derive(Default)]
+    struct MemoryCounter {
+        stock: Mutex<HashMap<u64, i64>>,
+        holds: Mutex<HashMap<(u64, String), StockMovement>>,
+        restocks: Mutex<HashMap<(u64, String), StockMovement>>,
+        bought: Mutex<HashMap<(u64, u32), i64>>,
+        queue: Mutex<VecDeque<String>>,
+        processing: Mutex<Vec<String>>,
+    }
+
+    impl StockCounter for MemoryCounter {
+        fn seed(&self, sku_id: u64, stock: i64) -> BoxFuture<'_, Result<bool, fred::error::Error>> {
+            let mut counters = self.stock.lock().unwrap();
+            let seeded = !counters.contains_key(&sku_id);
+            counters.entry(sku_id).or_insert(stock);
+            Box::pin(async move { Ok(seeded) })
+        }
+
+        fn get(&self, sku_id: u64) -> BoxFuture<'_, Result<Option<i64>, fred::error::Error>> {
+            let stock = self.stock.lock().unwrap().get(&sku_id).copied();
+            Box::pin(async move { Ok(stock) })
+        }
+
+        fn reserve<'a>(
+            &'a self,
+            movement: &'a StockMovement,
+            per_user_limit: Option<u32>,
+        ) -> BoxFuture<'a, Result<Result<i64, CounterRejection>, fred::error::Error>> {
+            let mut counters = self.stock.lock().unwrap();
+            let mut holds = self.holds.lock().unwrap();
+            let mut bought = self.bought.lock().unwrap();
+            let quantity = -movement.delta;
+            let result = match counters.get_mut(&movement.sku_id) {
+                None => Err(CounterRejection::NotInitialized),
+                Some(stock) if holds.contains_key(&(movement.sku_id, movement.order_ref.clone())) => Ok(*stock),
+                Some(stock) => {
+                    let user = bought.entry((movement.sku_id, movement.user_id)).or_default();
+                    if per_user_limit.is_some_and(|limit| *user + quantity > i64::from(limit)) {
+                        Err(CounterRejection::LimitExceeded)
+                    } else if *stock < quantity {
+                        Err(CounterRejection::OutOfStock)
+                    } else {
+                        *stock -= quantity;
+                        *user += quantity;
+                        holds.insert((movement.sku_id, movement.order_ref.clone()), movement.clone());
+                        self.queue.lock().unwrap().push_back(serde_json::to_string(movement).unwrap());
+                        Ok(*stock)
+                    }
+                }
+            };
+            Box::pin(async move { Ok(result) })
+        }
+
+        fn release<'a>(&'a self, sku_id: u64, order_ref: &'a str) -> BoxFuture<'a, Result<Option<i64>, fred::error::Error>> {
+            let hold = self.holds.lock().unwrap().remove(&(sku_id, order_ref.to_string()));
+            let released = hold.map(|hold| {
+                let quantity = -hold.delta;
+                *self.stock.lock().unwrap().get_mut(&sku_id).unwrap() += quantity;
+                *self.bought.lock().unwrap().get_mut(&(sku_id, hold.user_id)).unwrap() -= quantity;
+                let movement = StockMovement { kind: MovementKind::Release, delta: quantity, ..hold };
+                self.queue.lock().unwrap().push_back(serde_json::to_string(&movement).unwrap());
+                quantity
+            });
+            Box::pin(async move { Ok(released) })
+        }
+
+        fn restock<'a>(&'a self, movement: &'a StockMovement) -> BoxFuture<'a, Result<Result<bool, CounterRejection>, fred::error::Error>> {
+            let mut counters = self.stock.lock().unwrap();
+            let mut restocks = self.restocks.lock().unwrap();
+            let result = match counters.get_mut(&movement.sku_id) {
+                None => Err(CounterRejection::NotInitialized),
+                Some(_) if restocks.contains_key(&(movement.sku_id, movement.order_ref.clone())) => Ok(false),
+                Some(stock) => {
+                    *stock += movement.delta;
+                    restocks.insert((movement.sku_id, movement.order_ref.clone()), movement.clone());
+                    self.queue.lock().unwrap().push_back(serde_json::to_string(movement).unwrap());
+                    Ok(true)
+                }
+            };
+            Box::pin(async move { Ok(result) })
+        }
+
+        fn claim_movement(&self) -> BoxFuture<'_, Result<Option<String>, fred::error::Error>> {
+            let raw = self.queue.lock().unwrap().pop_front();
+            if let Some(raw) = &raw {
+                self.processing.lock().unwrap().push(raw.clone());
+            }
+            Box::pin(async move { Ok(raw) })
+        }
+
+        fn ack_movement<'a>(&'a self, raw: &'a str) -> BoxFuture<'a, Result<(), fred::error::Error>> {
+            self.processing.lock().unwrap().retain(|item| item != raw);
+            Box::pin(async { Ok(()) })
+        }
+
+        fn nack_movement<'a>(&'a self, raw: &'a str) -> BoxFuture<'a, Result<(), fred::error::Error>> {
+            let mut processing = self.processing.lock().unwrap();
+            if let Some(index) = processing.iter().position(|item| item == raw) {
+                processing.remove(index);
+                self.queue.lock().unwrap().push_front(raw.to_string());
+            }
+            Box::pin(async { Ok(()) })
+        }
+
+        fn requeue_unacked(&self) -> BoxFuture<'_, Result<u64, fred::error::Error>> {
+            let items: Vec<String> = self.processing.lock().unwrap().drain(..).collect();
+            let moved = items.len() as u64;
+            let mut queue = self.queue.lock().unwrap();
+            for item in items.into_iter().rev() {
+                queue.push_front(item);
+            }
+            Box::pin(async move { Ok(moved) })
+        }
+
+        fn pending(&self) -> BoxFuture<'_, Result<u64, fred::error::Error>> {
+            let pending = self.queue.lock().unwrap().len() + self.processing.lock().unwrap().len();
+            Box::pin(async move { Ok(pending as u64) })
+        }
+    }
+
+    fn flash_sale(stock: i64, per_user_limit: Option<u32>) -> (Arc<MemoryInventory>, Arc<MemoryCounter>, InventoryServiceImpl<MemoryInventory, MemoryCounter>) {
+        let repo = MemoryInventory::with(vec![inventory(1, stock, STOCK_MODE_REDIS, per_user_limit)]);
+        let counter = Arc::new(MemoryCounter::default());
+        let service = InventoryServiceImpl::new(repo.clone(), counter.clone());
+        (repo, counter, service)
+    }
+
+    #[tokio::test]
+    async fn test_flash_sale_reserve_enforces_stock_and_user_limit() {
+        let (_, _, service) = flash_sale(3, Some(2));
+        assert!(matches!(service.reserve(1, 7, 1, "A").await, Err(ServiceError::Conflict(_))), "counter not warmed up");
+        assert_eq!(service.warm_up().await.unwrap(), 1);
+
+        service.reserve(1, 7, 2, "A").await.unwrap();
+        service.reserve(1, 7, 2, "A").await.unwrap();
+        let limited = service.reserve(1, 7, 1, "B").await;
+        assert!(matches!(limited, Err(ServiceError::Conflict(msg)) if msg.contains("limit")));
+        let sold_out = service.reserve(1, 8, 2, "C").await;
+        assert!(matches!(sold_out, Err(ServiceError::Conflict(msg)) if msg.contains("out of stock")));
+        assert_eq!(service.available(1).await.unwrap(), 1);
+
+        assert_eq!(service.release(1, "A").await.unwrap(), Some(2));
+        assert_eq!(service.release(1, "A").await.unwrap(), None);
+        service.reserve(1, 7, 2, "B").await.unwrap();
+        assert_eq!(service.available(1).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_mysql_mode_reserve_and_release() {
+        let repo = MemoryInventory::with(vec![inventory(2, 2, "mysql", None)]);
+        let service = InventoryServiceImpl::new(repo.clone(), Arc::new(MemoryCounter::default()));
+        service.reserve(2, 7, 2, "A").await.unwrap();
+        assert!(matches!(service.reserve(2, 8, 1, "B").await, Err(ServiceError::Conflict(_))));
+        assert_eq!(service.release(2, "A").await.unwrap(), Some(2));
+        assert_eq!(service.release(2, "A").await.unwrap(), None);
+        assert_eq!(service.available(2).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_writes_back_movements_and_reports_drift() {
+        let (repo, counter, service) = flash_sale(5, None);
+        service.warm_up().await.unwrap();
+        service.reserve(1, 7, 2, "A").await.unwrap();
+        service.reserve(1, 8, 1, "B").await.unwrap();
+        service.release(1, "B").await.unwrap();
+
+        let report = service.reconcile(2).await.unwrap();
+        assert_eq!((report.applied, report.drifts.len()), (2, 0), "drift is not checked while movements are pending");
+        let report = service.reconcile(10).await.unwrap();
+        assert_eq!((report.applied, report.duplicates), (1, 0));
+        assert!(report.drifts.is_empty());
+        assert_eq!(repo.stock(1), 3);
+
+        counter.stock.lock().unwrap().insert(1, 4);
+        let report = service.reconcile(10).await.unwrap();
+        assert_eq!(report.drifts.len(), 1);
+        assert_eq!((report.drifts[0].redis_stock, report.drifts[0].mysql_stock), (Some(4), 3));
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_requeues_movement_when_write_back_fails() {
+        let (repo, counter, service) = flash_sale(5, None);
+        service.warm_up().await.unwrap();
+        service.reserve(1, 7, 2, "A").await.unwrap();
+
+        repo.fail_writes.store(true, Ordering::SeqCst);
+        assert!(service.reconcile(10).await.is_err());
+        assert_eq!(counter.queue.lock().unwrap().len(), 1);
+        assert!(counter.processing.lock().unwrap().is_empty());
+
+        repo.fail_writes.store(false, Ordering::SeqCst);
+        let report = service.reconcile(10).await.unwrap();
+        assert_eq!(report.applied, 1);
+        assert!(report.drifts.is_empty());
+        assert_eq!(repo.stock(1), 3);
+    }
+
+    #[tokio::test]
+    async fn test_restock_applies_once_per_after_sale() {
+        let repo = MemoryInventory::with(vec![inventory(2, 2, "mysql", None)]);
+        let service = InventoryServiceImpl::new(repo.clone(), Arc::new(MemoryCounter::default()));
+        service.restock(2, 7, 1, "S1").await.unwrap();
+        service.restock(2, 7, 1, "S1").await.unwrap();
+        assert_eq!(service.available(2).await.unwrap(), 3);
+
+        let (repo, _, service) = flash_sale(5, None);
+        service.warm_up().await.unwrap();
+        service.restock(1, 7, 2, "S2").await.unwrap();
+        service.restock(1, 7, 2, "S2").await.unwrap();
+        assert_eq!(service.available(1).await.unwrap(), 7);
+        service.reconcile(10).await.unwrap();
+        assert_eq!(repo.stock(1), 7);
+    }
+}
@@ -0,0 +1,243 @@
+use crate::domain::BoxFuture;
+use crate::domain::jobs::JobQueue;
+use crate::models::job::Job;
+use crate::service::ServiceError;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use wx_shop::JobSettings;
+
+/// 某一类任务的处理器。任务可能被重复投递，实现必须是幂等的。
+pub trait JobHandler: Send + Sync {
+    fn kind(&self) -> &'static str;
+    fn handle<'a>(&'a self, job: &'a Job) -> BoxFuture<'a, Result<(), ServiceError>>;
+}
+
+/// 当前毫秒时间戳
+pub fn now_millis() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+/// 失败重试的退避时间：5 秒起指数增长，最长 10 分钟
+pub fn backoff_millis(attempts: u32) -> i64 {
+    let exp = attempts.saturating_sub(1).min(10);
+    (5_000i64 << exp).min(600_000)
+}
+
+pub struct JobWorker {
+    queue: Arc<dyn JobQueue>,
+    handlers: HashMap<&'static str, Arc<dyn JobHandler>>,
+    settings: JobSettings,
+}
+
+impl JobWorker {
+    pub fn new(queue: Arc<dyn JobQueue>, settings: JobSettings) -> Self {
+        Self { queue, handlers: HashMap::new(), settings }
+    }
+
+    pub fn register(mut self, handler: Arc<dyn JobHandler>) -> Self {
+        self.handlers.insert(handler.kind(), handler);
+        self
+    }
+
+    /// 启动 `concurrency` 个轮询协程
+    pub fn spawn(self) {
+        let worker = Arc::new(self);
+        for _ in 0..worker.settings.concurrency.max(1) {
+            let worker = worker.clone();
+            tokio::spawn(async move { worker.run().await });
+        }
+    }
+
+    async fn run(&self) {
+        let poll_interval = Duration::from_millis(self.settings.poll_interval_ms);
+        let visibility = self.settings.visibility_timeout_secs as i64 * 1000;
+        loop {
+            let now = now_millis();
+            match self.queue.claim(now, now + visibility).await {
+                Ok(Some(job)) => self.execute(job).await,
+                Ok(None) => tokio::time::sleep(poll_interval).await,
+                Err(e) => {
+                    tracing::error!("Failed to claim job: {:?}", e);
+                    tokio::time::sleep(poll_interval).await;
+                }
+            }
+        }
+    }
+
+    async fn execute(&self, job: Job) {
+        let result = match self.handlers.get(job.kind.as_str()) {
+            Some(handler) => handler.handle(&job).await,
+            None => Err(ServiceError::NotFound(format!("no handler for job kind {}", job.kind))),
+        };
+
+        let outcome = match result {
+            Ok(()) => self.queue.ack(&job.id).await,
+            Err(e) if job.attempts >= job.max_attempts => {
+                tracing::error!("Job {} failed after {} attempts, burying: {:?}", job.id, job.attempts, e);
+                self.queue.bury(&job, &format!("{:?}", e)).await
+            }
+            Err(e) => {
+                tracing::warn!("Job {} failed on attempt {}, will retry: {:?}", job.id, job.attempts, e);
+                self.queue.retry(&job.id, now_millis() + backoff_millis(job.attempts)).await
+            }
+        };
+        // 确认失败时任务会在可见性超时后重新投递
+        if let Err(e) = outcome {
+            tracing::error!("Failed to settle job {}: {:?}", job.id, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    struct Entry {
+        job: Job,
+        run_at: i64,
+        /// 执行中时为重新可见的时间
+        inflight: Option<i64>,
+    }
+
+    /// 内存版延迟队列，语义与 Redis 脚本一致
+    #[derive(Default)]
+    struct MemoryQueue {
+        jobs: Mutex<HashMap<String, Entry>>,
+        dead: Mutex<Vec<(Job, String)>>,
+    }
+
+    impl JobQueue for MemoryQueue {
+        fn enqueue<'a>(&'a self, job: &'a Job, run_at: i64) -> BoxFuture<'a, Result<(), fred::error::Error>> {
+            self.jobs.lock().unwrap().insert(job.id.clone(), Entry { job: job.clone(), run_at, inflight: None });
+            Box::pin(async { Ok(()) })
+        }
+
+        fn claim(&self, now: i64, visible_at: i64) -> BoxFuture<'_, Result<Option<Job>, fred::error::Error>> {
+            let mut jobs = self.jobs.lock().unwrap();
+            for entry in jobs.values_mut() {
+                if entry.inflight.is_some_and(|until| until <= now) {
+                    entry.inflight = None;
+                    entry.run_at = now;
+                }
+            }
+            let claimed = jobs
+                .values_mut()
+                .filter(|entry| entry.inflight.is_none() && entry.run_at <= now)
+                .min_by_key(|entry| entry.run_at)
+                .map(|entry| {
+                    entry.job.attempts += 1;
+                    entry.inflight = Some(visible_at);
+                    entry.job.clone()
+                });
+            Box::pin(async move { Ok(claimed) })
+        }
+
+        fn ack<'a>(&'a self, job_id: &'a str) -> BoxFuture<'a, Result<(), fred::error::Error>> {
+            self.jobs.lock().unwrap().remove(job_id);
+            Box::pin(async { Ok(()) })
+        }
+
+        fn retry<'a>(&'a self, job_id: &'a str, run_at: i64) -> BoxFuture<'a, Result<(), fred::error::Error>> {
+            if let Some(entry) = self.jobs.lock().unwrap().get_mut(job_id) {
+                entry.run_at = run_at;
+                entry.inflight = None;
+            }
+            Box::pin(async { Ok(()) })
+        }
+
+        fn bury<'a>(&'a self, job: &'a Job, error: &'a str) -> BoxFuture<'a, Result<(), fred::error::Error>> {
+            self.dead.lock().unwrap().push((job.clone(), error.to_string()));
+            self.jobs.lock().unwrap().remove(&job.id);
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    /// 前 `failures` 次执行失败
+    struct Flaky {
+        failures: u32,
+        calls: AtomicU32,
+    }
+
+    impl JobHandler for Flaky {
+        fn kind(&self) -> &'static str {
+            "flaky"
+        }
+
+        fn handle<'a>(&'a self, _job: &'a Job) -> BoxFuture<'a, Result<(), ServiceError>> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            let failures = self.failures;
+            Box::pin(async move {
+                if call <= failures {
+                    Err(ServiceError::Conflict(format!("failure {}", call)))
+                } else {
+                    Ok(())
+                }
+            })
+        }
+    }
+
+    fn worker(queue: Arc<MemoryQueue>, failures: u32) -> JobWorker {
+        let settings = JobSettings { concurrency: 1, poll_interval_ms: 10, visibility_timeout_secs: 60 };
+        JobWorker::new(queue, settings).register(Arc::new(Flaky { failures, calls: AtomicU32::new(0) }))
+    }
+
+    #[test]
+    fn test_backoff_grows_and_caps() {
+        assert_eq!(backoff_millis(1), 5_000);
+        assert_eq!(backoff_millis(2), 10_000);
+        assert_eq!(backoff_millis(30), 600_000);
+    }
+
+    #[tokio::test]
+    async fn test_failed_job_is_retried_with_backoff_then_acked() {
+        let queue = Arc::new(MemoryQueue::default());
+        let worker = worker(queue.clone(), 1);
+        queue.enqueue(&Job::new("flaky", "1", serde_json::json!({})), 0).await.unwrap();
+
+        let now = now_millis();
+        let job = queue.claim(now, now + 60_000).await.unwrap().unwrap();
+        worker.execute(job).await;
+        assert!(queue.claim(now, now + 60_000).await.unwrap().is_none(), "retry waits for the backoff");
+
+        let later = now_millis() + backoff_millis(1);
+        let job = queue.claim(later, later + 60_000).await.unwrap().unwrap();
+        assert_eq!(job.attempts, 2);
+        worker.execute(job).await;
+        assert!(queue.jobs.lock().unwrap().is_empty());
+        assert!(queue.dead.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_job_is_buried_after_max_attempts() {
+        let queue = Arc::new(MemoryQueue::default());
+        let worker = worker(queue.clone(), u32::MAX);
+        let mut job = Job::new("flaky", "1", serde_json::json!({}));
+        job.max_attempts = 2;
+        queue.enqueue(&job, 0).await.unwrap();
+
+        for _ in 0..2 {
+            let now = now_millis() + 3_600_000;
+            let job = queue.claim(now, now + 60_000).await.unwrap().unwrap();
+            worker.execute(job).await;
+        }
+        assert!(queue.jobs.lock().unwrap().is_empty());
+        let dead = queue.dead.lock().unwrap();
+        assert_eq!((dead.len(), dead[0].0.attempts), (1, 2));
+    }
+
+    #[tokio::test]
+    async fn test_job_without_handler_is_kept_for_retry() {
+        let queue = Arc::new(MemoryQueue::default());
+        let worker = worker(queue.clone(), 0);
+        queue.enqueue(&Job::new("unknown", "1", serde_json::json!({})), 0).await.unwrap();
+        let now = now_millis();
+        let job = queue.claim(now, now + 60_000).await.unwrap().unwrap();
+        worker.execute(job).await;
+        let jobs = queue.jobs.lock().unwrap();
+        let entry = &jobs["unknown:1"];
+        assert!(entry.inflight.is_none() && entry.run_at > now);
+    }
+}
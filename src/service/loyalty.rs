@@ -0,0 +1,501 @@
+use crate::domain::BoxFuture;
+use crate::domain::catalog::CatalogRepo;
+use crate::domain::jobs::JobQueue;
+use crate::domain::loyalty::LoyaltyRepo;
+use crate::domain::order::OrderRepo;
+use crate::models::event::{DomainEvent, ORDER_EVENT_PREFIX, OrderStatusChanged};
+use crate::models::job::Job;
+use crate::models::loyalty::{Member, MyPoints, NewPointsEntry, NextTier, PointsKind, PointsOutcome};
+use crate::models::order::{Order, OrderStatus};
+use crate::service::ServiceError;
+use crate::service::events::EventSubscriber;
+use crate::service::jobs::JobHandler;
+use crate::service::pricing::{PricingInput, PricingStep, Quote};
+use chrono::{DateTime, Days, Local, NaiveDate, TimeZone, Timelike};
+use std::collections::HashMap;
+use std::sync::Arc;
+use wx_shop::{LoyaltySettings, MemberTier};
+
+/// 每日积分过期与等级重算任务
+pub const POINTS_EXPIRE_JOB: &str = "points_expire";
+/// 计价明细中积分抵扣的来源标识
+const POINTS_SOURCE: &str = "points";
+/// 批量处理用户时每批的数量
+const BATCH_SIZE: u32 = 200;
+
+pub trait LoyaltyService: Send + Sync {
+    /// 会员信息，从未产生积分的用户返回 0 级、0 积分
+    fn member(&self, user_id: u32) -> BoxFuture<'_, Result<Member, ServiceError>>;
+    /// 结算时的积分抵扣步骤，按会员当前余额限制
+    fn points_step(&self, member: &Member, requested: i64) -> PointsStep;
+    /// 下单时扣减抵扣的积分，余额不足时返回冲突；同一订单重复调用只扣一次
+    fn redeem<'a>(&'a self, user_id: u32, points: i64, order_no: &'a str) -> BoxFuture<'a, Result<(), ServiceError>>;
+    /// 订单取消或退款后退回抵扣的积分，订单没有用积分时什么都不做
+    fn release<'a>(&'a self, user_id: u32, order_no: &'a str) -> BoxFuture<'a, Result<(), ServiceError>>;
+    /// 订单完成后按类目倍率发放积分，并重新计算会员等级
+    fn earn_for_order(&self, order_id: u64) -> BoxFuture<'_, Result<(), ServiceError>>;
+    /// 订单退款后退回抵扣的积分并收回发放的积分，收回数量不超过当前余额
+    fn reverse_for_order(&self, order_id: u64) -> BoxFuture<'_, Result<(), ServiceError>>;
+    /// 清理到期积分并重新计算已有等级用户的等级，返回处理的用户数
+    fn expire_and_retier(&self, now: DateTime<Local>) -> BoxFuture<'_, Result<usize, ServiceError>>;
+    fn my_points(&self, user_id: u32, page: u32, page_size: u32) -> BoxFuture<'_, Result<MyPoints, ServiceError>>;
+}
+
+pub struct LoyaltyServiceImpl<R: LoyaltyRepo + 'static, O: OrderRepo + 'static, C: CatalogRepo + 'static> {
+    repo: Arc<R>,
+    orders: Arc<O>,
+    catalog: Arc<C>,
+    category_rates: HashMap<u64, u32>,
+    settings: LoyaltySettings,
+}
+
+impl<R: LoyaltyRepo + 'static, O: OrderRepo + 'static, C: CatalogRepo + 'static> LoyaltyServiceImpl<R, O, C> {
+    pub fn new(repo: Arc<R>, orders: Arc<O>, catalog: Arc<C>, settings: LoyaltySettings) -> Self {
+        let category_rates = settings
+            .category_points_per_yuan
+            .iter()
+            .filter_map(|(category_id, rate)| match category_id.parse() {
+                Ok(id) => Some((id, *rate)),
+                Err(_) => {
+                    tracing::warn!("Ignoring points rate of invalid category ID {}", category_id);
+                    None
+                }
+            })
+            .collect();
+        Self { repo, orders, catalog, category_rates, settings }
+    }
+
+    async fn load_order(&self, order_id: u64) -> Result<Order, ServiceError> {
+        self.orders
+            .find_by_id(order_id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Order with ID {} not found", order_id)))
+    }
+
+    fn expires_at(&self, now: DateTime<Local>) -> Option<DateTime<Local>> {
+        now.checked_add_days(Days::new(u64::from(self.settings.expire_after_days)))
+    }
+
+    async fn refresh_tier(&self, user_id: u32, now: DateTime<Local>) -> Result<(), ServiceError> {
+        let since = now
+            .checked_sub_days(Days::new(u64::from(self.settings.tier_window_days)))
+            .unwrap_or(now);
+        let spend = self.repo.rolling_spend(user_id, since).await?;
+        let level = tier_for(&self.settings.tiers, spend).map_or(0, |tier| tier.level);
+        self.repo.save_tier(user_id, level, spend).await?;
+        Ok(())
+    }
+
+    /// 按先进先出清理到期积分，同一天重复执行只记一次
+    async fn expire_user(&self, user_id: u32, now: DateTime<Local>) -> Result<(), ServiceError> {
+        let (expired, debited) = self.repo.expiry_totals(user_id, now).await?;
+        let balance = self.repo.find_member(user_id).await?.map_or(0, |member| member.points_balance);
+        let due = expired_points(expired, debited).min(balance);
+        if due <= 0 {
+            return Ok(());
+        }
+        let entry = NewPointsEntry {
+            user_id,
+            kind: PointsKind::Expire,
+            delta: -due,
+            ref_no: now.date_naive().to_string(),
+            expires_at: None,
+            note: "积分过期".to_string(),
+        };
+        self.repo.append(&entry).await?;
+        Ok(())
+    }
+}
+
+impl<R: LoyaltyRepo + 'static, O: OrderRepo + 'static, C: CatalogRepo + 'static> LoyaltyService for LoyaltyServiceImpl<R, O, C> {
+    fn member(&self, user_id: u32) -> BoxFuture<'_, Result<Member, ServiceError>> {
+        Box::pin(async move {
+            Ok(self
+                .repo
+                .find_member(user_id)
+                .await?
+                .unwrap_or_else(|| Member { user_id, ..Member::default() }))
+        })
+    }
+
+    fn points_step(&self, member: &Member, requested: i64) -> PointsStep {
+        PointsStep::new(requested, member.points_balance, &self.settings)
+    }
+
+    fn redeem<'a>(&'a self, user_id: u32, points: i64, order_no: &'a str) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move {
+            if points <= 0 {
+                return Ok(());
+            }
+            let entry = NewPointsEntry {
+                user_id,
+                kind: PointsKind::Redeem,
+                delta: -points,
+                ref_no: order_no.to_string(),
+                expires_at: None,
+                note: "下单抵扣".to_string(),
+            };
+            match self.repo.append(&entry).await? {
+                PointsOutcome::Applied(_) | PointsOutcome::Duplicate => Ok(()),
+                PointsOutcome::Insufficient => Err(ServiceError::Conflict("insufficient points".to_string())),
+            }
+        })
+    }
+
+    fn release<'a>(&'a self, user_id: u32, order_no: &'a str) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let Some(redeemed) = self.repo.find_entry(user_id, PointsKind::Redeem, order_no).await? else {
+                return Ok(());
+            };
+            let entry = NewPointsEntry {
+                user_id,
+                kind: PointsKind::Release,
+                delta: -redeemed.delta,
+                ref_no: order_no.to_string(),
+                expires_at: self.expires_at(Local::now()),
+                note: "订单取消退回".to_string(),
+            };
+            self.repo.append(&entry).await?;
+            Ok(())
+        })
+    }
+
+    fn earn_for_order(&self, order_id: u64) -> BoxFuture<'_, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let order = self.load_order(order_id).await?;
+            let items = self.orders.find_items(order_id).await?;
+            let sku_ids: Vec<u64> = items.iter().map(|item| item.sku_id).collect();
+            let categories: HashMap<u64, u64> = self
+                .catalog
+                .find_skus(&sku_ids)
+                .await?
+                .into_iter()
+                .map(|sku| (sku.sku_id, sku.category_id))
+                .collect();
+            // 商品已删除时按默认倍率计算
+            let lines: Vec<(Option<u64>, i64)> = items
+                .iter()
+                .map(|item| (categories.get(&item.sku_id).copied(), item.payable_amount))
+                .collect();
+            let points = earn_points(&lines, self.settings.earn_points_per_yuan, &self.category_rates);
+
+            let now = Local::now();
+            if points > 0 {
+                let entry = NewPointsEntry {
+                    user_id: order.user_id,
+                    kind: PointsKind::Earn,
+                    delta: points,
+                    ref_no: order.order_no.clone(),
+                    expires_at: self.expires_at(now),
+                    note: "订单完成".to_string(),
+                };
+                self.repo.append(&entry).await?;
+            }
+            self.refresh_tier(order.user_id, now).await
+        })
+    }
+
+    fn reverse_for_order(&self, order_id: u64) -> BoxFuture<'_, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let order = self.load_order(order_id).await?;
+            self.release(order.user_id, &order.order_no).await?;
+
+            if let Some(earned) = self.repo.find_entry(order.user_id, PointsKind::Earn, &order.order_no).await? {
+                let balance = self.member(order.user_id).await?.points_balance;
+                let amount = earned.delta.min(balance);
+                if amount > 0 {
+                    let entry = NewPointsEntry {
+                        user_id: order.user_id,
+                        kind: PointsKind::Reverse,
+                        delta: -amount,
+                        ref_no: order.order_no.clone(),
+                        expires_at: None,
+                        note: "订单退款收回".to_string(),
+                    };
+                    self.repo.append(&entry).await?;
+                }
+            }
+            self.refresh_tier(order.user_id, Local::now()).await
+        })
+    }
+
+    fn expire_and_retier(&self, now: DateTime<Local>) -> BoxFuture<'_, Result<usize, ServiceError>> {
+        Box::pin(async move {
+            let mut processed = 0;
+            let mut after = 0;
+            loop {
+                let user_ids = self.repo.users_with_expired(now, after, BATCH_SIZE).await?;
+                let Some(&last) = user_ids.last() else { break };
+                for user_id in user_ids {
+                    self.expire_user(user_id, now).await?;
+                    processed += 1;
+                }
+                after = last;
+            }
+            // 滚动窗口外的消费不再计入，已有等级的用户需要定期降级
+            let mut after = 0;
+            loop {
+                let user_ids = self.repo.tiered_users(after, BATCH_SIZE).await?;
+                let Some(&last) = user_ids.last() else { break };
+                for user_id in user_ids {
+                    self.refresh_tier(user_id, now).await?;
+                    processed += 1;
+                }
+                after = last;
+            }
+            Ok(processed)
+        })
+    }
+
+    fn my_points(&self, user_id: u32, page: u32, page_size: u32) -> BoxFuture<'_, Result<MyPoints, ServiceError>> {
+        Box::pin(async move {
+            let member = self.member(user_id).await?;
+            let page_size = page_size.clamp(1, 50);
+            let offset = page.saturating_sub(1) * page_size;
+            let history = self.repo.list_entries(user_id, page_size, offset).await?;
+            let tier_name = self
+                .settings
+                .tiers
+                .iter()
+                .find(|tier| tier.level == member.level)
+                .map_or_else(|| "普通会员".to_string(), |tier| tier.name.clone());
+            let next_tier = self
+                .settings
+                .tiers
+                .iter()
+                .filter(|tier| tier.level > member.level)
+                .min_by_key(|tier| tier.level)
+                .map(|tier| NextTier {
+                    level: tier.level,
+                    name: tier.name.clone(),
+                    min_spend: tier.min_spend,
+                    spend_needed: (tier.min_spend - member.rolling_spend).max(0),
+                });
+            Ok(MyPoints {
+                balance: member.points_balance,
+                level: member.level,
+                tier_name,
+                rolling_spend: member.rolling_spend,
+                next_tier,
+                history,
+            })
+        })
+    }
+}
+
+/// 消费达到门槛的最高等级
+fn tier_for(tiers: &[MemberTier], spend: i64) -> Option<&MemberTier> {
+    tiers.iter().filter(|tier| spend >= tier.min_spend).max_by_key(|tier| tier.level)
+}
+
+/// 每行按所属类目的倍率计算，实付金额不足 1 元的部分不计积分
+fn earn_points(lines: &[(Option<u64>, i64)], default_rate: u32, category_rates: &HashMap<u64, u32>) -> i64 {
+    lines
+        .iter()
+        .map(|&(category_id, payable)| {
+            let rate = category_id
+                .and_then(|id| category_rates.get(&id).copied())
+                .unwrap_or(default_rate);
+            payable.max(0) / 100 * i64::from(rate)
+        })
+        .sum()
+}
+
+/// 积分按获得先后消耗：到期的增加积分中还没有被扣减抵消的部分即为应过期的积分
+fn expired_points(expired_credits: i64, debits: i64) -> i64 {
+    (expired_credits - debits).max(0)
+}
+
+/// 结算时使用积分抵扣，放在优惠券之后、运费之前；抵扣不超过余额和商品应付的一定比例，
+/// 实际使用的积分数写入 `Quote::points_used`
+pub struct PointsStep {
+    requested: i64,
+    balance: i64,
+    cents_per_point: i64,
+    max_redeem_percent: u32,
+}
+
+impl PointsStep {
+    pub fn new(requested: i64, balance: i64, settings: &LoyaltySettings) -> Self {
+        Self {
+            requested,
+            balance,
+            cents_per_point: settings.cents_per_point.max(1),
+            max_redeem_percent: settings.max_redeem_percent.min(100),
+        }
+    }
+}
+
+impl PricingStep for PointsStep {
+    fn apply(&self, _input: &PricingInput, quote: &mut Quote) -> Result<(), ServiceError> {
+        let points = self.requested.min(self.balance);
+        if points <= 0 {
+            return Ok(());
+        }
+        let cap = quote.goods_payable() * i64::from(self.max_redeem_percent) / 100;
+        let amount = (points * self.cents_per_point).min(cap);
+        // 只按整积分抵扣
+        let points = amount / self.cents_per_point;
+        if points == 0 {
+            return Ok(());
+        }
+        let lines: Vec<usize> = (0..quote.lines.len()).collect();
+        let applied = quote.apply_discount(POINTS_SOURCE, &format!("{}积分抵扣", points), points * self.cents_per_point, &lines);
+        quote.points_used = applied / self.cents_per_point;
+        Ok(())
+    }
+}
+
+/// 订单完成发放积分，退款收回；重复投递时由流水的唯一约束保证只记一次
+pub struct LoyaltySubscriber {
+    service: Arc<dyn LoyaltyService>,
+}
+
+impl LoyaltySubscriber {
+    pub fn new(service: Arc<dyn LoyaltyService>) -> Arc<Self> {
+        Arc::new(Self { service })
+    }
+}
+
+impl EventSubscriber for LoyaltySubscriber {
+    fn name(&self) -> &'static str {
+        "loyalty"
+    }
+
+    fn handles(&self, event_type: &str) -> bool {
+        [OrderStatus::Completed, OrderStatus::Refunded]
+            .iter()
+            .any(|status| event_type.strip_prefix(ORDER_EVENT_PREFIX) == Some(status.as_str()))
+    }
+
+    fn handle<'a>(&'a self, event: &'a DomainEvent) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let changed: OrderStatusChanged = event.decode().map_err(ServiceError::BadRequest)?;
+            if changed.to == OrderStatus::Completed.as_str() {
+                self.service.earn_for_order(changed.order_id).await
+            } else {
+                self.service.reverse_for_order(changed.order_id).await
+            }
+        })
+    }
+}
+
+/// 每日积分过期任务：执行前先排期下一天，重复投递时流水按日期去重
+pub struct PointsExpireJob {
+    service: Arc<dyn LoyaltyService>,
+    jobs: Arc<dyn JobQueue>,
+    run_at_hour: u32,
+}
+
+impl PointsExpireJob {
+    pub fn new(service: Arc<dyn LoyaltyService>, jobs: Arc<dyn JobQueue>, run_at_hour: u32) -> Arc<Self> {
+        Arc::new(Self { service, jobs, run_at_hour })
+    }
+}
+
+impl JobHandler for PointsExpireJob {
+    fn kind(&self) -> &'static str {
+        POINTS_EXPIRE_JOB
+    }
+
+    fn handle<'a>(&'a self, job: &'a Job) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let run_date = job.payload["run_date"]
+                .as_str()
+                .and_then(|date| date.parse::<NaiveDate>().ok())
+                .ok_or_else(|| ServiceError::BadRequest(format!("invalid payload of job {}", job.id)))?;
+            if let Some(next) = run_date.checked_add_days(Days::new(1)) {
+                schedule_points_expire(self.jobs.as_ref(), next, self.run_at_hour).await?;
+            }
+            let processed = self.service.expire_and_retier(Local::now()).await?;
+            tracing::info!("Points expiry of {} processed {} members", run_date, processed);
+            Ok(())
+        })
+    }
+}
+
+/// 在 `run_date` 的 `run_at_hour` 点执行；同一天重复排期只保留一个任务
+pub async fn schedule_points_expire(jobs: &dyn JobQueue, run_date: NaiveDate, run_at_hour: u32) -> Result<(), ServiceError> {
+    let run_at = run_date
+        .and_hms_opt(run_at_hour.min(23), 0, 0)
+        .and_then(|time| Local.from_local_datetime(&time).earliest())
+        .ok_or_else(|| ServiceError::BadRequest(format!("invalid run date {}", run_date)))?;
+    let job = Job::new(POINTS_EXPIRE_JOB, &run_date.to_string(), serde_json::json!({ "run_date": run_date }));
+    Ok(jobs.enqueue(&job, run_at.timestamp_millis()).await?)
+}
+
+/// 启动时应排期的执行日：今天还没到执行时间则是今天，否则是明天
+pub fn next_points_expire_date(now: DateTime<Local>, run_at_hour: u32) -> NaiveDate {
+    let today = now.date_naive();
+    if now.hour() < run_at_hour {
+        today
+    } else {
+        today.succ_opt().unwrap_or(today)
+    }
+}
+
+pub fn new_loyalty_service<R, O, C>(repo: Arc<R>, orders: Arc<O>, catalog: Arc<C>, settings: LoyaltySettings) -> Arc<dyn LoyaltyService>
+where
+    R: LoyaltyRepo + 'static,
+    O: OrderRepo + 'static,
+    C: CatalogRepo + 'static,
+{
+    Arc::new(LoyaltyServiceImpl::new(repo, orders, catalog, settings)) as Arc<dyn LoyaltyService>
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::pricing::{PricingItem, PricingPipeline};
+
+    fn tier(level: u32, min_spend: i64) -> MemberTier {
+        MemberTier { level, name: format!("L{}", level), min_spend }
+    }
+
+    fn item(sku_id: u64, unit_price: i64) -> PricingItem {
+        PricingItem {
+            sku_id,
+            product_id: sku_id,
+            category_id: 1,
+            product_name: format!("商品{}", sku_id),
+            sku_title: "默认".to_string(),
+            unit_price,
+            quantity: 1,
+            weight_grams: 0,
+            freight_template_id: None,
+        }
+    }
+
+    #[test]
+    fn test_tier_and_earn_rules() {
+        let tiers = [tier(1, 100_000), tier(2, 500_000)];
+        assert!(tier_for(&tiers, 99_999).is_none());
+        assert_eq!(tier_for(&tiers, 100_000).map(|t| t.level), Some(1));
+        assert_eq!(tier_for(&tiers, 900_000).map(|t| t.level), Some(2));
+
+        let rates = HashMap::from([(12, 3)]);
+        // 99.99 元按默认倍率记 99 分，类目 12 的 10 元按 3 倍记 30 分
+        assert_eq!(earn_points(&[(Some(1), 9_999), (Some(12), 1_000), (None, 50)], 1, &rates), 129);
+
+        assert_eq!(expired_points(500, 200), 300);
+        assert_eq!(expired_points(500, 800), 0);
+    }
+
+    #[test]
+    fn test_points_step_caps_by_balance_and_ratio() {
+        let settings = LoyaltySettings { cents_per_point: 2, max_redeem_percent: 50, ..LoyaltySettings::default() };
+        let input = PricingInput { items: vec![item(1, 3_000), item(2, 1_003)] };
+
+        // 余额不足时按余额抵扣
+        let quote = PricingPipeline::new(vec![Box::new(PointsStep::new(5_000, 300, &settings))]).quote(&input).unwrap();
+        assert_eq!((quote.points_used, quote.discount_amount), (300, 600));
+
+        // 最多抵扣 50%：4003 分的一半为 2001 分，按整积分向下取整
+        let quote = PricingPipeline::new(vec![Box::new(PointsStep::new(5_000, 5_000, &settings))]).quote(&input).unwrap();
+        assert_eq!((quote.points_used, quote.discount_amount), (1_000, 2_000));
+        assert_eq!(quote.lines.iter().map(|line| line.discount_amount).sum::<i64>(), 2_000);
+
+        let quote = PricingPipeline::new(vec![Box::new(PointsStep::new(0, 5_000, &settings))]).quote(&input).unwrap();
+        assert_eq!(quote.points_used, 0);
+    }
+}
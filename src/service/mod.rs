@@ -1,12 +1,47 @@
 pub mod users;
+pub mod inventory;
+pub mod cart;
+pub mod pricing;
+pub mod address;
+pub mod order;
+pub mod order_state;
+pub mod jobs;
+pub mod payment;
+pub mod refund;
+pub mod reconciliation;
+pub mod coupon;
+pub mod promotion;
+pub mod group_buy;
+pub mod freight;
+pub mod shipment;
+pub mod after_sale;
+pub mod review;
+pub mod notification;
+pub mod loyalty;
+pub mod favorite;
+pub mod referral;
+pub mod stats;
+pub mod export;
+pub mod import;
+pub mod sheet;
+pub mod audit;
+pub mod events;
 
 use axum::response::{Response, IntoResponse};
 use axum::{http::StatusCode, Json};
 use serde_json::json;
+use crate::domain::payment::GatewayError;
 #[derive(Debug)]
 pub enum ServiceError {
     NotFound(String),        // 业务错误：找不到资源
+    BadRequest(String),      // 业务错误：请求参数不合法
+    Unauthorized,            // 业务错误：未登录
+    Conflict(String),        // 业务错误：与当前状态冲突（库存不足、限购等）
     Database(sqlx::Error),   // 基础设施错误：数据库操作失败
+    Redis(fred::error::Error), // 基础设施错误：Redis 操作失败
+    Session(tower_sessions::session::Error), // 基础设施错误：Session 读写失败
+    Gateway(GatewayError),   // 基础设施错误：支付渠道调用失败
+    Storage(std::io::Error), // 基础设施错误：文件读写失败
 }
 
 // 实现 From trait，让 ? 操作符可以自动转换
@@ -16,6 +51,29 @@ impl From<sqlx::Error> for ServiceError {
     }
 }
 
+impl From<fred::error::Error> for ServiceError {
+    fn from(e: fred::error::Error) -> Self {
+        ServiceError::Redis(e)
+    }
+}
+
+impl From<tower_sessions::session::Error> for ServiceError {
+    fn from(e: tower_sessions::session::Error) -> Self {
+        ServiceError::Session(e)
+    }
+}
+
+impl From<GatewayError> for ServiceError {
+    fn from(e: GatewayError) -> Self {
+        ServiceError::Gateway(e)
+    }
+}
+
+impl From<std::io::Error> for ServiceError {
+    fn from(e: std::io::Error) -> Self {
+        ServiceError::Storage(e)
+    }
+}
 
 impl IntoResponse for ServiceError {
     fn into_response(self) -> Response {
@@ -24,6 +82,18 @@ impl IntoResponse for ServiceError {
                 // 业务错误：404 Not Found
                 (StatusCode::NOT_FOUND, Json(json!({"code": 4040, "msg": msg}))).into_response()
             }
+            ServiceError::BadRequest(msg) => {
+                // 业务错误：400 Bad Request
+                (StatusCode::BAD_REQUEST, Json(json!({"code": 4000, "msg": msg}))).into_response()
+            }
+            ServiceError::Unauthorized => {
+                // 业务错误：401 Unauthorized
+                (StatusCode::UNAUTHORIZED, Json(json!({"code": 4010, "msg": "not logged in"}))).into_response()
+            }
+            ServiceError::Conflict(msg) => {
+                // 业务错误：409 Conflict
+                (StatusCode::CONFLICT, Json(json!({"code": 4090, "msg": msg}))).into_response()
+            }
             // 假设您处理了 Database 错误
             _ => {
                 // 内部错误：500 Internal Server Error
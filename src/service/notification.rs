@@ -0,0 +1,360 @@
+use crate::domain::BoxFuture;
+use crate::domain::jobs::JobQueue;
+use crate::domain::notification::{NotificationRepo, SubscribeTransport};
+use crate::domain::order::OrderRepo;
+use crate::models::job::Job;
+use crate::models::notification::{Delivery, DeliveryStatus, NewDelivery, SendOutcome, SubscribeApply, SubscribeMessage, Subscription};
+use crate::models::event::{DomainEvent, ORDER_EVENT_PREFIX, OrderStatusChanged};
+use crate::models::order::{Order, OrderItem};
+use crate::service::ServiceError;
+use crate::service::jobs::{JobHandler, now_millis};
+use crate::service::events::EventSubscriber;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use wx_shop::{NotificationSettings, SubscribeTemplate};
+
+pub const ORDER_NOTIFICATION_JOB: &str = "order_notification";
+
+pub trait NotificationService: Send + Sync {
+    /// 已配置的订单状态到模板 ID，小程序端据此调起订阅授权
+    fn templates(&self) -> BTreeMap<String, String>;
+    /// 记录用户同意的模板，每同意一次可发送一条
+    fn subscribe<'a>(&'a self, user_id: u32, apply: &'a SubscribeApply) -> BoxFuture<'a, Result<Vec<Subscription>, ServiceError>>;
+    /// 发送订单状态通知；通道失败时返回错误由任务重试，没有授权时记为 skipped
+    fn deliver<'a>(&'a self, order_id: u64, event: &'a str, reason: &'a str, occurred_at: &'a str) -> BoxFuture<'a, Result<(), ServiceError>>;
+    fn list_deliveries<'a>(&'a self, status: &'a str, page: u32, page_size: u32) -> BoxFuture<'a, Result<Vec<Delivery>, ServiceError>>;
+}
+
+pub struct NotificationServiceImpl<R: NotificationRepo + 'static, O: OrderRepo + 'static> {
+    repo: Arc<R>,
+    orders: Arc<O>,
+    transport: Arc<dyn SubscribeTransport>,
+    settings: NotificationSettings,
+}
+
+impl<R: NotificationRepo + 'static, O: OrderRepo + 'static> NotificationServiceImpl<R, O> {
+    pub fn new(repo: Arc<R>, orders: Arc<O>, transport: Arc<dyn SubscribeTransport>, settings: NotificationSettings) -> Self {
+        Self { repo, orders, transport, settings }
+    }
+
+    fn template(&self, event: &str) -> Option<&SubscribeTemplate> {
+        self.settings.templates.get(event).filter(|template| !template.template_id.is_empty())
+    }
+
+    async fn log(&self, order: &Order, event: &str, template_id: &str, status: DeliveryStatus, error: Option<String>) -> Result<(), ServiceError> {
+        let delivery = NewDelivery {
+            user_id: order.user_id,
+            order_id: order.id,
+            event: event.to_string(),
+            template_id: template_id.to_string(),
+            status,
+            error,
+        };
+        self.repo.log_delivery(&delivery).await?;
+        Ok(())
+    }
+}
+
+impl<R: NotificationRepo + 'static, O: OrderRepo + 'static> NotificationService for NotificationServiceImpl<R, O> {
+    fn templates(&self) -> BTreeMap<String, String> {
+        self.settings
+            .templates
+            .iter()
+            .filter(|(_, template)| !template.template_id.is_empty())
+            .map(|(event, template)| (event.clone(), template.template_id.clone()))
+            .collect()
+    }
+
+    fn subscribe<'a>(&'a self, user_id: u32, apply: &'a SubscribeApply) -> BoxFuture<'a, Result<Vec<Subscription>, ServiceError>> {
+        Box::pin(async move {
+            let openid = apply.openid.trim();
+            if openid.is_empty() {
+                return Err(ServiceError::BadRequest("openid is required".to_string()));
+            }
+            let known = self.templates();
+            for template_id in &apply.template_ids {
+                if !known.values().any(|id| id == template_id) {
+                    return Err(ServiceError::BadRequest(format!("unknown template {}", template_id)));
+                }
+            }
+            for template_id in &apply.template_ids {
+                self.repo.subscribe(user_id, openid, template_id).await?;
+            }
+            Ok(self.repo.list_subscriptions(user_id).await?)
+        })
+    }
+
+    fn deliver<'a>(&'a self, order_id: u64, event: &'a str, reason: &'a str, occurred_at: &'a str) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let Some(template) = self.template(event) else {
+                return Ok(());
+            };
+            // 任务至少投递一次，已发送过的事件不再重复打扰用户
+            if self.repo.has_sent(order_id, event).await? {
+                return Ok(());
+            }
+            let Some(order) = self.orders.find_by_id(order_id).await? else {
+                tracing::warn!("Order {} not found for {} notification", order_id, event);
+                return Ok(());
+            };
+            let Some(subscription) = self.repo.find_usable(order.user_id, &template.template_id).await? else {
+                return self.log(&order, event, &template.template_id, DeliveryStatus::Skipped, Some("no subscription".to_string())).await;
+            };
+
+            let items = self.orders.find_items(order_id).await?;
+            let message = SubscribeMessage {
+                openid: subscription.openid,
+                template_id: template.template_id.clone(),
+                page: self.settings.page.replace("{order_id}", &order_id.to_string()),
+                data: render_fields(template, &order, &items, event, reason, occurred_at),
+            };
+            match self.transport.send(&message).await {
+                Ok(SendOutcome::Sent) => {
+                    self.repo.consume(order.user_id, &template.template_id).await?;
+                    self.log(&order, event, &template.template_id, DeliveryStatus::Sent, None).await
+                }
+                Ok(SendOutcome::Refused) => {
+                    self.repo.revoke(order.user_id, &template.template_id).await?;
+                    self.log(&order, event, &template.template_id, DeliveryStatus::Skipped, Some("refused by user".to_string())).await
+                }
+                Err(e) => {
+                    let error: String = e.to_string().chars().take(500).collect();
+                    if let Err(log_error) = self.log(&order, event, &template.template_id, DeliveryStatus::Failed, Some(error)).await {
+                        tracing::error!("Failed to log notification delivery for order {}: {:?}", order_id, log_error);
+                    }
+                    Err(ServiceError::Gateway(e))
+                }
+            }
+        })
+    }
+
+    fn list_deliveries<'a>(&'a self, status: &'a str, page: u32, page_size: u32) -> BoxFuture<'a, Result<Vec<Delivery>, ServiceError>> {
+        Box::pin(async move {
+            let page = page.max(1);
+            let page_size = page_size.clamp(1, 100);
+            Ok(self.repo.list_deliveries(status, page_size, (page - 1) * page_size).await?)
+        })
+    }
+}
+
+/// 订阅消息中展示的状态文字
+fn status_label(event: &str) -> &str {
+    match event {
+        "pending_payment" => "待支付",
+        "paid" => "已支付",
+        "shipped" => "已发货",
+        "delivered" => "已签收",
+        "completed" => "已完成",
+        "cancelled" => "已取消",
+        "refunding" => "退款中",
+        "refunded" => "已退款",
+        "closed" => "已关闭",
+        other => other,
+    }
+}
+
+/// 按模板字段的类型截断：thing 最多 20 个字符，character_string 最多 32 个，phrase 最多 5 个
+fn truncate_for(field: &str, value: String) -> String {
+    let limit = if field.starts_with("thing") {
+        20
+    } else if field.starts_with("character_string") {
+        32
+    } else if field.starts_with("phrase") {
+        5
+    } else {
+        return value;
+    };
+    if value.chars().count() <= limit {
+        value
+    } else {
+        value.chars().take(limit).collect()
+    }
+}
+
+/// 按模板配置的取值来源填充字段；状态文字取触发通知的事件，而不是订单当前状态
+fn render_fields(
+    template: &SubscribeTemplate,
+    order: &Order,
+    items: &[OrderItem],
+    event: &str,
+    reason: &str,
+    occurred_at: &str,
+) -> BTreeMap<String, String> {
+    template
+        .fields
+        .iter()
+        .map(|(field, source)| {
+            let value = match source.as_str() {
+                "order_no" => order.order_no.clone(),
+                "payable_amount" => format!("{}.{:02}元", order.payable_amount / 100, order.payable_amount % 100),
+                "product_name" => match items {
+                    [] => String::new(),
+                    [item] => item.product_name.clone(),
+                    [item, ..] => format!("{} 等{}件", item.product_name, items.iter().map(|i| i.quantity).sum::<u32>()),
+                },
+                "event_time" => occurred_at.to_string(),
+                "status" => status_label(event).to_string(),
+                "reason" => reason.to_string(),
+                literal => literal.to_string(),
+            };
+            (field.clone(), truncate_for(field, value))
+        })
+        .collect()
+}
+
+/// 订阅订单状态变更事件：目标状态配置了模板时投递发送任务；投递失败返回错误，由发件箱重试
+pub struct OrderNotificationSubscriber {
+    jobs: Arc<dyn JobQueue>,
+    event_types: Vec<String>,
+}
+
+impl OrderNotificationSubscriber {
+    pub fn new(jobs: Arc<dyn JobQueue>, settings: &NotificationSettings) -> Arc<Self> {
+        let event_types = settings
+            .templates
+            .iter()
+            .filter(|(_, template)| !template.template_id.is_empty())
+            .map(|(status, _)| format!("{}{}", ORDER_EVENT_PREFIX, status))
+            .collect();
+        Arc::new(Self { jobs, event_types })
+    }
+}
+
+impl EventSubscriber for OrderNotificationSubscriber {
+    fn name(&self) -> &'static str {
+        "order_notification"
+    }
+
+    fn handles(&self, event_type: &str) -> bool {
+        self.event_types.iter().any(|t| t == event_type)
+    }
+
+    fn handle<'a>(&'a self, event: &'a DomainEvent) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let changed: OrderStatusChanged = event.decode().map_err(ServiceError::BadRequest)?;
+            // 同一订单同一状态的任务在队列中只保留一份，发送前再按发送记录去重
+            let job = Job::new(
+                ORDER_NOTIFICATION_JOB,
+                &format!("{}:{}", changed.order_id, changed.to),
+                serde_json::json!({
+                    "order_id": changed.order_id,
+                    "event": changed.to,
+                    "reason": changed.reason,
+                    "occurred_at": changed.occurred_at.format("%Y-%m-%d %H:%M").to_string(),
+                }),
+            );
+            self.jobs.enqueue(&job, now_millis()).await?;
+            Ok(())
+        })
+    }
+}
+
+/// 发送订单状态通知；失败按任务队列的退避策略重试
+pub struct OrderNotificationJob {
+    service: Arc<dyn NotificationService>,
+}
+
+impl OrderNotificationJob {
+    pub fn new(service: Arc<dyn NotificationService>) -> Arc<Self> {
+        Arc::new(Self { service })
+    }
+}
+
+impl JobHandler for OrderNotificationJob {
+    fn kind(&self) -> &'static str {
+        ORDER_NOTIFICATION_JOB
+    }
+
+    fn handle<'a>(&'a self, job: &'a Job) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let invalid = || ServiceError::BadRequest(format!("invalid payload of job {}", job.id));
+            let order_id = job.payload["order_id"].as_u64().ok_or_else(invalid)?;
+            let event = job.payload["event"].as_str().ok_or_else(invalid)?;
+            let reason = job.payload["reason"].as_str().unwrap_or_default();
+            let occurred_at = job.payload["occurred_at"].as_str().unwrap_or_default();
+            self.service.deliver(order_id, event, reason, occurred_at).await
+        })
+    }
+}
+
+pub fn new_notification_service<R, O>(
+    repo: Arc<R>,
+    orders: Arc<O>,
+    transport: Arc<dyn SubscribeTransport>,
+    settings: NotificationSettings,
+) -> Arc<dyn NotificationService>
+where
+    R: NotificationRepo + 'static,
+    O: OrderRepo + 'static,
+{
+    Arc::new(NotificationServiceImpl::new(repo, orders, transport, settings)) as Arc<dyn NotificationService>
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order() -> Order {
+        Order {
+            id: 7,
+            order_no: "SO20240101000000000001".to_string(),
+            user_id: 1,
+            status: "shipped".to_string(),
+            goods_amount: 12_900,
+            discount_amount: 0,
+            shipping_fee: 0,
+            payable_amount: 12_900,
+            receiver_name: "张三".to_string(),
+            receiver_phone: "13800000000".to_string(),
+            province: "浙江省".to_string(),
+            city: "杭州市".to_string(),
+            district: "西湖区".to_string(),
+            address_detail: "文三路 1 号".to_string(),
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    fn item(name: &str, quantity: u32) -> OrderItem {
+        OrderItem {
+            id: 1,
+            order_id: 7,
+            sku_id: 1,
+            product_id: 1,
+            product_name: name.to_string(),
+            sku_title: "默认".to_string(),
+            unit_price: 4_300,
+            quantity,
+            line_total: 4_300 * quantity as i64,
+            discount_amount: 0,
+            payable_amount: 4_300 * quantity as i64,
+        }
+    }
+
+    #[test]
+    fn test_render_fields_from_sources() {
+        let template = SubscribeTemplate {
+            template_id: "TPL".to_string(),
+            fields: BTreeMap::from([
+                ("character_string1".to_string(), "order_no".to_string()),
+                ("amount2".to_string(), "payable_amount".to_string()),
+                ("thing3".to_string(), "product_name".to_string()),
+                ("phrase4".to_string(), "status".to_string()),
+                ("thing5".to_string(), "reason".to_string()),
+                ("thing6".to_string(), "感谢您的支持".to_string()),
+            ]),
+        };
+        let items = [item("一款名字非常非常非常非常长的保温杯", 2), item("杯套", 1)];
+        let data = render_fields(&template, &order(), &items, "shipped", "yuantong YT001", "2024-01-01 10:00");
+        assert_eq!(data["character_string1"], "SO20240101000000000001");
+        assert_eq!(data["amount2"], "129.00元");
+        assert_eq!(data["thing3"].chars().count(), 20);
+        assert!(data["thing3"].starts_with("一款名字"));
+        assert_eq!(data["phrase4"], "已发货");
+        assert_eq!(data["thing5"], "yuantong YT001");
+        assert_eq!(data["thing6"], "感谢您的支持");
+
+        let data = render_fields(&template, &order(), &[item("杯套", 1)], "paid", "", "");
+        assert_eq!(data["thing3"], "杯套");
+    }
+}
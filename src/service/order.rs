@@ -0,0 +1,994 @@
+use crate::domain::BoxFuture;
+use crate::domain::address::AddressRepo;
+use crate::domain::catalog::CatalogRepo;
+use crate::domain::jobs::JobQueue;
+use crate::domain::order::{OrderRepo, OutOfStock};
+use crate::models::address::Address;
+use crate::models::inventory::{MovementKind, StockMovement};
+use crate::models::event::{DomainEvent, ORDER_EVENT_PREFIX, OrderStatusChanged};
+use crate::models::job::Job;
+use crate::models::order::{Actor, NewOrder, NewOrderItem, Order, OrderDetail, OrderEvent, OrderStatus};
+use crate::service::ServiceError;
+use crate::service::cart::CartService;
+use crate::service::coupon::{CouponService, CouponStep};
+use crate::service::freight::{FreightService, FreightStep};
+use crate::service::inventory::InventoryService;
+use crate::service::jobs::{now_millis, JobHandler};
+use crate::service::order_state::OrderStateService;
+use crate::service::events::EventSubscriber;
+use crate::service::loyalty::LoyaltyService;
+use crate::service::promotion::{EvalContext, PromotionService, PromotionStep};
+use crate::service::pricing::{FlatShipping, PricingInput, PricingItem, PricingPipeline, PricingStep, Quote};
+use rand::Rng;
+use std::collections::HashMap;
+use std::sync::Arc;
+use wx_shop::OrderSettings;
+
+/// 待支付超时取消任务
+pub const ORDER_TIMEOUT_JOB: &str = "order_timeout";
+/// 签收后自动完成任务
+pub const ORDER_COMPLETE_JOB: &str = "order_complete";
+
+pub trait OrderService: Send + Sync {
+    /// 按购物车中勾选的商品试算金额，不占库存也不锁券；`points` 为希望抵扣的积分，超出可用部分按可用计算
+    fn preview(&self, user_id: u32, address_id: u64, coupon_id: Option<u64>, points: i64) -> BoxFuture<'_, Result<Quote, ServiceError>>;
+    /// 下单：重新计价，应付金额与预览不一致时拒绝；预占库存、锁定优惠券、扣减积分后写入订单
+    fn place(
+        &self,
+        user_id: u32,
+        address_id: u64,
+        coupon_id: Option<u64>,
+        points: i64,
+        expected_payable: i64,
+    ) -> BoxFuture<'_, Result<OrderDetail, ServiceError>>;
+    /// 拼团下单：单件 SKU 按拼团价计价，只计运费，不参与活动和优惠券，也不改动购物车
+    fn place_group(&self, user_id: u32, address_id: u64, sku_id: u64, group_price: i64) -> BoxFuture<'_, Result<OrderDetail, ServiceError>>;
+    fn get(&self, user_id: u32, order_id: u64) -> BoxFuture<'_, Result<OrderDetail, ServiceError>>;
+    fn list(&self, user_id: u32, page: u32, page_size: u32) -> BoxFuture<'_, Result<Vec<Order>, ServiceError>>;
+    /// 用户取消待支付订单并回补库存
+    fn cancel<'a>(&'a self, user_id: u32, order_id: u64, reason: &'a str) -> BoxFuture<'a, Result<OrderDetail, ServiceError>>;
+    fn events(&self, user_id: u32, order_id: u64) -> BoxFuture<'_, Result<Vec<OrderEvent>, ServiceError>>;
+    /// 系统关闭超时未支付的订单并回补库存；订单已支付时什么都不做，已取消时补做一次回补
+    fn cancel_unpaid(&self, order_id: u64) -> BoxFuture<'_, Result<(), ServiceError>>;
+    /// 签收满 `auto_complete_days` 天后系统确认完成；订单不再是已签收状态（如申请了退款）时什么都不做
+    fn complete_delivered(&self, order_id: u64) -> BoxFuture<'_, Result<(), ServiceError>>;
+}
+
+/// 下单流程依赖的其他服务
+pub struct OrderDeps {
+    pub cart: Arc<dyn CartService>,
+    pub inventory: Arc<dyn InventoryService>,
+    pub state: Arc<dyn OrderStateService>,
+    pub jobs: Arc<dyn JobQueue>,
+    pub coupons: Arc<dyn CouponService>,
+    pub promotions: Arc<dyn PromotionService>,
+    pub freight: Arc<dyn FreightService>,
+    pub loyalty: Arc<dyn LoyaltyService>,
+}
+
+pub struct OrderServiceImpl<R: OrderRepo + 'static, A: AddressRepo + 'static, C: CatalogRepo + 'static> {
+    repo: Arc<R>,
+    addresses: Arc<A>,
+    catalog: Arc<C>,
+    cart: Arc<dyn CartService>,
+    inventory: Arc<dyn InventoryService>,
+    state: Arc<dyn OrderStateService>,
+    jobs: Arc<dyn JobQueue>,
+    coupons: Arc<dyn CouponService>,
+    promotions: Arc<dyn PromotionService>,
+    freight: Arc<dyn FreightService>,
+    loyalty: Arc<dyn LoyaltyService>,
+    settings: OrderSettings,
+}
+
+impl<R: OrderRepo + 'static, A: AddressRepo + 'static, C: CatalogRepo + 'static> OrderServiceImpl<R, A, C> {
+    pub fn new(repo: Arc<R>, addresses: Arc<A>, catalog: Arc<C>, deps: OrderDeps, settings: OrderSettings) -> Self {
+        let OrderDeps { cart, inventory, state, jobs, coupons, promotions, freight, loyalty } = deps;
+        Self { repo, addresses, catalog, cart, inventory, state, jobs, coupons, promotions, freight, loyalty, settings }
+    }
+
+    /// 读取地址和勾选的购物车行，并按当前价格组装计价输入
+    async fn load_address(&self, user_id: u32, address_id: u64) -> Result<Address, ServiceError> {
+        self.addresses
+            .find(user_id, address_id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Address with ID {} not found", address_id)))
+    }
+
+    async fn checkout_input(&self, user_id: u32, address_id: u64) -> Result<(Address, PricingInput), ServiceError> {
+        let address = self.load_address(user_id, address_id).await?;
+
+        let lines: Vec<_> = self.cart.list(user_id).await?.into_iter().filter(|line| line.selected).collect();
+        if lines.is_empty() {
+            return Err(ServiceError::BadRequest("no cart items selected for checkout".to_string()));
+        }
+
+        let sku_ids: Vec<u64> = lines.iter().map(|line| line.sku_id).collect();
+        let skus: HashMap<u64, _> = self
+            .catalog
+            .find_skus(&sku_ids)
+            .await?
+            .into_iter()
+            .map(|sku| (sku.sku_id, sku))
+            .collect();
+
+        let mut items = Vec::with_capacity(lines.len());
+        for line in lines {
+            let sku = skus
+                .get(&line.sku_id)
+                .filter(|sku| sku.is_on_sale())
+                .ok_or_else(|| ServiceError::Conflict(format!("SKU {} is no longer available", line.sku_id)))?;
+            items.push(PricingItem {
+                sku_id: sku.sku_id,
+                product_id: sku.product_id,
+                category_id: sku.category_id,
+                product_name: sku.product_name.clone(),
+                sku_title: sku.title.clone(),
+                unit_price: sku.price,
+                quantity: line.quantity,
+                weight_grams: sku.weight_grams,
+                freight_template_id: sku.freight_template_id,
+            });
+        }
+
+        let input = PricingInput { items };
+        Ok((address, input))
+    }
+
+    /// 计价流水线：活动（含会员价） -> 优惠券减免 -> 积分抵扣 -> 运费 -> 免运费券
+    async fn pipeline(
+        &self,
+        user_id: u32,
+        coupon_id: Option<u64>,
+        points: i64,
+        input: &PricingInput,
+        address: &Address,
+    ) -> Result<PricingPipeline, ServiceError> {
+        let member = self.loyalty.member(user_id).await?;
+        let ctx = EvalContext { now: chrono::Local::now(), member_level: member.level };
+        let promotions = PromotionStep::new(self.promotions.list_active().await?, ctx);
+        let coupon = match coupon_id {
+            Some(coupon_id) => Some(CouponStep::new(self.coupons.checkout_template(user_id, coupon_id).await?)?),
+            None => None,
+        };
+        let mut steps: Vec<Box<dyn PricingStep>> = vec![Box::new(promotions)];
+        let (discount, shipping) = match coupon {
+            Some(step) if step.is_shipping() => (None, Some(step)),
+            other => (other, None),
+        };
+        if let Some(step) = discount {
+            steps.push(Box::new(step));
+        }
+        if points > 0 {
+            steps.push(Box::new(self.loyalty.points_step(&member, points)));
+        }
+        steps.push(Box::new(self.freight_step(input, address).await?));
+        if let Some(step) = shipping {
+            steps.push(Box::new(step));
+        }
+        Ok(PricingPipeline::new(steps))
+    }
+
+    /// 按收货省份和商品的运费模板计算运费，未设置模板的商品按统一运费
+    async fn freight_step(&self, input: &PricingInput, address: &Address) -> Result<FreightStep, ServiceError> {
+        let mut template_ids: Vec<u64> = input.items.iter().filter_map(|item| item.freight_template_id).collect();
+        template_ids.sort_unstable();
+        template_ids.dedup();
+        let templates = self.freight.templates(&template_ids).await?;
+        let fallback = FlatShipping {
+            fee: self.settings.shipping_fee,
+            free_threshold: self.settings.free_shipping_threshold,
+        };
+        Ok(FreightStep::new(templates, &address.province, fallback))
+    }
+
+    /// 预占库存、锁券并写入订单，返回订单 ID
+    async fn submit(&self, user_id: u32, address: Address, quote: &Quote, coupon_id: Option<u64>) -> Result<u64, ServiceError> {
+        let order_no = generate_order_no();
+        let mut flash_sale = Vec::new();
+        let mut reservations = Vec::new();
+        for line in &quote.lines {
+            if self.inventory.is_flash_sale(line.sku_id).await? {
+                flash_sale.push(line);
+            } else {
+                reservations.push(StockMovement {
+                    sku_id: line.sku_id,
+                    order_ref: order_no.clone(),
+                    user_id,
+                    kind: MovementKind::Reserve,
+                    delta: -i64::from(line.quantity),
+                });
+            }
+        }
+        // 普通 SKU 随订单在同一事务中预占；秒杀 SKU 的库存在 Redis 中，无法共用事务，
+        // 因此先逐行预占，之后任何一步失败都回补已预占的部分
+        let mut reserved = Vec::with_capacity(flash_sale.len());
+        for line in flash_sale {
+            if let Err(e) = self.inventory.reserve(line.sku_id, user_id, line.quantity, &order_no).await {
+                self.rollback(user_id, &reserved, &order_no).await;
+                return Err(e);
+            }
+            reserved.push(line.sku_id);
+        }
+        // 锁券是条件更新，同一张券并发下单只有一单能锁定成功
+        if let Some(coupon_id) = coupon_id
+            && let Err(e) = self.coupons.lock(user_id, coupon_id, &order_no).await
+        {
+            self.rollback(user_id, &reserved, &order_no).await;
+            return Err(e);
+        }
+        // 积分按订单号扣减，余额不足时连同券一起退回
+        if let Err(e) = self.loyalty.redeem(user_id, quote.points_used, &order_no).await {
+            self.rollback(user_id, &reserved, &order_no).await;
+            return Err(e);
+        }
+
+        let new_order = NewOrder {
+            order_no: order_no.clone(),
+            user_id,
+            status: OrderStatus::PendingPayment,
+            goods_amount: quote.goods_amount,
+            discount_amount: quote.discount_amount,
+            shipping_fee: quote.shipping_fee,
+            payable_amount: quote.payable_amount,
+            receiver_name: address.receiver_name,
+            receiver_phone: address.phone,
+            province: address.province,
+            city: address.city,
+            district: address.district,
+            address_detail: address.detail,
+            items: quote
+                .lines
+                .iter()
+                .map(|line| NewOrderItem {
+                    sku_id: line.sku_id,
+                    product_id: line.product_id,
+                    product_name: line.product_name.clone(),
+                    sku_title: line.sku_title.clone(),
+                    unit_price: line.unit_price,
+                    quantity: line.quantity,
+                    line_total: line.line_total,
+                    discount_amount: line.discount_amount,
+                    payable_amount: line.payable_amount,
+                })
+                .collect(),
+            reservations,
+        };
+        let order_id = match self.repo.create(&new_order).await {
+            Ok(Ok(id)) => id,
+            Ok(Err(OutOfStock { sku_id })) => {
+                self.rollback(user_id, &reserved, &order_no).await;
+                return Err(ServiceError::Conflict(format!("SKU {} is out of stock", sku_id)));
+            }
+            Err(e) => {
+                self.rollback(user_id, &reserved, &order_no).await;
+                return Err(e.into());
+            }
+        };
+
+        let timeout_job = Job::new(
+            ORDER_TIMEOUT_JOB,
+            &order_id.to_string(),
+            serde_json::json!({ "order_id": order_id }),
+        );
+        let run_at = now_millis() + self.settings.payment_timeout_minutes as i64 * 60_000;
+        // 没有超时任务的订单不会被自动关闭，库存会一直被占用，因此本次下单失败
+        if let Err(e) = self.jobs.enqueue(&timeout_job, run_at).await {
+            tracing::error!("Failed to schedule payment timeout for order {}: {:?}", order_id, e);
+            // 关单失败也要回补预占，否则库存和优惠券会一直被这张无法自动关闭的订单占着
+            if let Err(cancel_err) = self
+                .state
+                .transition(order_id, OrderStatus::Cancelled, Actor::system(), "payment timeout not scheduled")
+                .await
+            {
+                tracing::error!("Failed to cancel unscheduled order {}: {:?}", order_id, cancel_err);
+            }
+            let sku_ids: Vec<u64> = quote.lines.iter().map(|line| line.sku_id).collect();
+            self.rollback(user_id, &sku_ids, &order_no).await;
+            return Err(e.into());
+        }
+        Ok(order_id)
+    }
+
+    /// 下单失败时的补偿：回补已预占的部分，错误只记日志，调用方返回原始错误
+    async fn rollback(&self, user_id: u32, sku_ids: &[u64], order_no: &str) {
+        if let Err(e) = self.release_order(user_id, sku_ids, order_no).await {
+            tracing::error!("Failed to roll back order {}: {:?}", order_no, e);
+        }
+    }
+
+    /// 订单取消后回补库存，退回优惠券和抵扣的积分；各步骤按订单号幂等，失败后可以整体重试
+    async fn release_order(&self, user_id: u32, sku_ids: &[u64], order_no: &str) -> Result<(), ServiceError> {
+        let stock = self.release_all(sku_ids, order_no).await;
+        let coupon = self.coupons.release(order_no).await;
+        let points = self.loyalty.release(user_id, order_no).await;
+        stock.and(coupon).and(points)
+    }
+
+    /// 逐个回补，某个 SKU 失败不影响其余 SKU，返回第一个错误
+    async fn release_all(&self, sku_ids: &[u64], order_no: &str) -> Result<(), ServiceError> {
+        let mut result = Ok(());
+        for &sku_id in sku_ids {
+            if let Err(e) = self.inventory.release(sku_id, order_no).await {
+                tracing::error!("Failed to release stock of SKU {} for order {}: {:?}", sku_id, order_no, e);
+                result = result.and(Err(e));
+            }
+        }
+        result
+    }
+}
+
+impl<R: OrderRepo + 'static, A: AddressRepo + 'static, C: CatalogRepo + 'static> OrderService for OrderServiceImpl<R, A, C> {
+    fn preview(&self, user_id: u32, address_id: u64, coupon_id: Option<u64>, points: i64) -> BoxFuture<'_, Result<Quote, ServiceError>> {
+        Box::pin(async move {
+            let (address, input) = self.checkout_input(user_id, address_id).await?;
+            self.pipeline(user_id, coupon_id, points, &input, &address).await?.quote(&input)
+        })
+    }
+
+    fn place(
+        &self,
+        user_id: u32,
+        address_id: u64,
+        coupon_id: Option<u64>,
+        points: i64,
+        expected_payable: i64,
+    ) -> BoxFuture<'_, Result<OrderDetail, ServiceError>> {
+        Box::pin(async move {
+            let (address, input) = self.checkout_input(user_id, address_id).await?;
+            let quote = self.pipeline(user_id, coupon_id, points, &input, &address).await?.quote(&input)?;
+            if quote.payable_amount != expected_payable {
+                return Err(ServiceError::Conflict(format!(
+                    "order total changed from {} to {}, please preview again",
+                    expected_payable, quote.payable_amount
+                )));
+            }
+
+            let order_id = self.submit(user_id, address, &quote, coupon_id).await?;
+
+            // 订单已生成，清理购物车失败不影响下单结果
+            let sku_ids: Vec<u64> = quote.lines.iter().map(|line| line.sku_id).collect();
+            if let Err(e) = self.cart.remove(user_id, &sku_ids).await {
+                tracing::warn!("Failed to remove ordered items from cart of user {}: {:?}", user_id, e);
+            }
+            self.get(user_id, order_id).await
+        })
+    }
+
+    fn place_group(&self, user_id: u32, address_id: u64, sku_id: u64, group_price: i64) -> BoxFuture<'_, Result<OrderDetail, ServiceError>> {
+        Box::pin(async move {
+            let address = self.load_address(user_id, address_id).await?;
+            let sku = self
+                .catalog
+                .find_skus(&[sku_id])
+                .await?
+                .into_iter()
+                .find(|sku| sku.is_on_sale())
+                .ok_or_else(|| ServiceError::Conflict(format!("SKU {} is no longer available", sku_id)))?;
+            let input = PricingInput {
+                items: vec![PricingItem {
+                    sku_id: sku.sku_id,
+                    product_id: sku.product_id,
+                    category_id: sku.category_id,
+                    product_name: sku.product_name.clone(),
+                    sku_title: sku.title.clone(),
+                    unit_price: group_price,
+                    quantity: 1,
+                    weight_grams: sku.weight_grams,
+                    freight_template_id: sku.freight_template_id,
+                }],
+            };
+            let freight = self.freight_step(&input, &address).await?;
+            let quote = PricingPipeline::new(vec![Box::new(freight)]).quote(&input)?;
+            let order_id = self.submit(user_id, address, &quote, None).await?;
+            self.get(user_id, order_id).await
+        })
+    }
+
+    fn get(&self, user_id: u32, order_id: u64) -> BoxFuture<'_, Result<OrderDetail, ServiceError>> {
+        Box::pin(async move {
+            let order = self
+                .repo
+                .find_by_id(order_id)
+                .await?
+                .filter(|order| order.user_id == user_id)
+                .ok_or_else(|| ServiceError::NotFound(format!("Order with ID {} not found", order_id)))?;
+            let items = self.repo.find_items(order_id).await?;
+            Ok(OrderDetail { order, items })
+        })
+    }
+
+    fn list(&self, user_id: u32, page: u32, page_size: u32) -> BoxFuture<'_, Result<Vec<Order>, ServiceError>> {
+        Box::pin(async move {
+            let page_size = page_size.clamp(1, 50);
+            let offset = page.saturating_sub(1) * page_size;
+            Ok(self.repo.list_by_user(user_id, page_size, offset).await?)
+        })
+    }
+
+    fn cancel<'a>(&'a self, user_id: u32, order_id: u64, reason: &'a str) -> BoxFuture<'a, Result<OrderDetail, ServiceError>> {
+        Box::pin(async move {
+            let detail = self.get(user_id, order_id).await?;
+            let order = self
+                .state
+                .transition(order_id, OrderStatus::Cancelled, Actor::user(user_id), reason)
+                .await?
+                .into_order();
+            // 重复取消也会回补一次：上次回补失败时由此补做，回补按订单号幂等
+            let sku_ids: Vec<u64> = detail.items.iter().map(|item| item.sku_id).collect();
+            self.release_order(user_id, &sku_ids, &order.order_no).await?;
+            Ok(OrderDetail { order, items: detail.items })
+        })
+    }
+
+    fn events(&self, user_id: u32, order_id: u64) -> BoxFuture<'_, Result<Vec<OrderEvent>, ServiceError>> {
+        Box::pin(async move {
+            self.get(user_id, order_id).await?;
+            self.state.events(order_id).await
+        })
+    }
+
+    fn cancel_unpaid(&self, order_id: u64) -> BoxFuture<'_, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let Some(order) = self.repo.find_by_id(order_id).await? else {
+                return Ok(());
+            };
+            match order.status() {
+                Ok(OrderStatus::PendingPayment) => {
+                    match self
+                        .state
+                        .transition(order_id, OrderStatus::Cancelled, Actor::system(), "payment timeout")
+                        .await
+                    {
+                        Ok(_) => {}
+                        // 与支付回调并发，订单已被支付
+                        Err(ServiceError::Conflict(msg)) => {
+                            tracing::info!("Skip cancelling order {}: {}", order_id, msg);
+                            return Ok(());
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+                // 已取消的订单再回补一次：上次回补失败时任务会重新投递，回补按订单号幂等
+                Ok(OrderStatus::Cancelled) => {}
+                _ => return Ok(()),
+            }
+            let sku_ids: Vec<u64> = self.repo.find_items(order_id).await?.iter().map(|item| item.sku_id).collect();
+            // 回补失败时返回错误，由任务队列重试
+            self.release_order(order.user_id, &sku_ids, &order.order_no).await
+        })
+    }
+
+    fn complete_delivered(&self, order_id: u64) -> BoxFuture<'_, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let Some(order) = self.repo.find_by_id(order_id).await? else {
+                return Ok(());
+            };
+            if order.status() != Ok(OrderStatus::Delivered) {
+                return Ok(());
+            }
+            match self
+                .state
+                .transition(order_id, OrderStatus::Completed, Actor::system(), "auto complete")
+                .await
+            {
+                Ok(_) => Ok(()),
+                // 与退款申请并发，订单已离开已签收状态
+                Err(ServiceError::Conflict(msg)) => {
+                    tracing::info!("Skip completing order {}: {}", order_id, msg);
+                    Ok(())
+                }
+                Err(e) => Err(e),
+            }
+        })
+    }
+}
+
+/// 待支付超时任务：取消订单并回补库存，重复投递时由状态机保证只生效一次
+pub struct OrderTimeoutJob {
+    orders: Arc<dyn OrderService>,
+}
+
+impl OrderTimeoutJob {
+    pub fn new(orders: Arc<dyn OrderService>) -> Arc<Self> {
+        Arc::new(Self { orders })
+    }
+}
+
+impl JobHandler for OrderTimeoutJob {
+    fn kind(&self) -> &'static str {
+        ORDER_TIMEOUT_JOB
+    }
+
+    fn handle<'a>(&'a self, job: &'a Job) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let order_id = job.payload["order_id"]
+                .as_u64()
+                .ok_or_else(|| ServiceError::BadRequest(format!("invalid payload of job {}", job.id)))?;
+            self.orders.cancel_unpaid(order_id).await
+        })
+    }
+}
+
+/// 签收后自动完成任务，重复投递时由状态机保证只生效一次
+pub struct OrderCompleteJob {
+    orders: Arc<dyn OrderService>,
+}
+
+impl OrderCompleteJob {
+    pub fn new(orders: Arc<dyn OrderService>) -> Arc<Self> {
+        Arc::new(Self { orders })
+    }
+}
+
+impl JobHandler for OrderCompleteJob {
+    fn kind(&self) -> &'static str {
+        ORDER_COMPLETE_JOB
+    }
+
+    fn handle<'a>(&'a self, job: &'a Job) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let order_id = job.payload["order_id"]
+                .as_u64()
+                .ok_or_else(|| ServiceError::BadRequest(format!("invalid payload of job {}", job.id)))?;
+            self.orders.complete_delivered(order_id).await
+        })
+    }
+}
+
+/// 订单签收后排期自动完成任务
+pub struct OrderCompleteScheduler {
+    jobs: Arc<dyn JobQueue>,
+    auto_complete_days: u32,
+}
+
+impl OrderCompleteScheduler {
+    pub fn new(jobs: Arc<dyn JobQueue>, settings: &OrderSettings) -> Arc<Self> {
+        Arc::new(Self { jobs, auto_complete_days: settings.auto_complete_days })
+    }
+}
+
+impl EventSubscriber for OrderCompleteScheduler {
+    fn name(&self) -> &'static str {
+        "order_complete"
+    }
+
+    fn handles(&self, event_type: &str) -> bool {
+        event_type.strip_prefix(ORDER_EVENT_PREFIX) == Some(OrderStatus::Delivered.as_str())
+    }
+
+    fn handle<'a>(&'a self, event: &'a DomainEvent) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let changed: OrderStatusChanged = event.decode().map_err(ServiceError::BadRequest)?;
+            let job = Job::new(
+                ORDER_COMPLETE_JOB,
+                &changed.order_id.to_string(),
+                serde_json::json!({ "order_id": changed.order_id }),
+            );
+            let run_at = changed.occurred_at.timestamp_millis() + i64::from(self.auto_complete_days) * 86_400_000;
+            self.jobs.enqueue(&job, run_at).await?;
+            Ok(())
+        })
+    }
+}
+
+pub fn new_order_service<R, A, C>(
+    repo: Arc<R>,
+    addresses: Arc<A>,
+    catalog: Arc<C>,
+    deps: OrderDeps,
+    settings: OrderSettings,
+) -> Arc<dyn OrderService>
+where
+    R: OrderRepo + 'static,
+    A: AddressRepo + 'static,
+    C: CatalogRepo + 'static,
+{
+    Arc::new(OrderServiceImpl::new(repo, addresses, catalog, deps, settings)) as Arc<dyn OrderService>
+}
+
+/// 订单号：下单时间（秒）+ 8 位随机数
+fn generate_order_no() -> String {
+    let suffix: u32 = rand::rng().random_range(0..100_000_000);
+    format!("{}{:08}", chrono::Local::now().format("%Y%m%d%H%M%S"), suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::order::OrderStateRepo;
+    use crate::models::address::NewAddress;
+    use crate::models::cart::{CartLine, CartView};
+    use crate::models::catalog::{ProductSummary, SkuDetail};
+    use crate::models::coupon::{CouponTemplate, MyCoupon, NewCouponTemplate, UserCoupon};
+    use crate::models::freight::{FreightTemplate, NewFreightTemplate};
+    use crate::models::loyalty::{Member, MyPoints};
+    use crate::models::order::OrderItem;
+    use crate::models::promotion::{NewPromotion, Promotion};
+    use crate::service::inventory::ReconcileReport;
+    use crate::service::loyalty::PointsStep;
+    use crate::service::order_state::OrderStateServiceImpl;
+    use chrono::{DateTime, Local};
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// 内存版订单仓储：一个订单，两行商品
+    struct MemoryOrders {
+        orders: Mutex<Vec<Order>>,
+        items: Vec<OrderItem>,
+        events: Mutex<Vec<OrderEvent>>,
+    }
+
+    impl MemoryOrders {
+        fn with_order(status: OrderStatus) -> Arc<Self> {
+            let order = Order {
+                id: 1,
+                order_no: "NO1".into(),
+                user_id: 1,
+                status: status.as_str().to_string(),
+                goods_amount: 200,
+                discount_amount: 0,
+                shipping_fee: 0,
+                payable_amount: 200,
+                receiver_name: "r".into(),
+                receiver_phone: "p".into(),
+                province: "p".into(),
+                city: "c".into(),
+                district: "d".into(),
+                address_detail: "a".into(),
+                created_at: None,
+                updated_at: None,
+            };
+            let items = [10, 11]
+                .into_iter()
+                .map(|sku_id| OrderItem {
+                    id: sku_id,
+                    order_id: 1,
+                    sku_id,
+                    product_id: 1,
+                    product_name: "p".into(),
+                    sku_title: "s".into(),
+                    unit_price: 100,
+                    quantity: 1,
+                    line_total: 100,
+                    discount_amount: 0,
+                    payable_amount: 100,
+                })
+                .collect();
+            Arc::new(MemoryOrders { orders: Mutex::new(vec![order]), items, events: Mutex::new(Vec::new()) })
+        }
+
+        fn status(&self) -> String {
+            self.orders.lock().unwrap()[0].status.clone()
+        }
+    }
+
+    impl OrderRepo for MemoryOrders {
+        fn create<'a>(&'a self, _order: &'a NewOrder) -> BoxFuture<'a, Result<Result<u64, OutOfStock>, sqlx::Error>> {
+            unreachable!()
+        }
+
+        fn find_by_id(&self, id: u64) -> BoxFuture<'_, Result<Option<Order>, sqlx::Error>> {
+            let order = self.orders.lock().unwrap().iter().find(|o| o.id == id).cloned();
+            Box::pin(async move { Ok(order) })
+        }
+
+        fn find_items(&self, order_id: u64) -> BoxFuture<'_, Result<Vec<OrderItem>, sqlx::Error>> {
+            let items = self.items.iter().filter(|item| item.order_id == order_id).cloned().collect();
+            Box::pin(async move { Ok(items) })
+        }
+
+        fn list_by_user(&self, _user_id: u32, _limit: u32, _offset: u32) -> BoxFuture<'_, Result<Vec<Order>, sqlx::Error>> {
+            unreachable!()
+        }
+    }
+
+    impl OrderStateRepo for MemoryOrders {
+        fn find_order(&self, id: u64) -> BoxFuture<'_, Result<Option<Order>, sqlx::Error>> {
+            self.find_by_id(id)
+        }
+
+        fn transition<'a>(
+            &'a self,
+            order_id: u64,
+            from: OrderStatus,
+            to: OrderStatus,
+            actor: Actor,
+            reason: &'a str,
+            _event: &'a DomainEvent,
+        ) -> BoxFuture<'a, Result<bool, sqlx::Error>> {
+            let mut orders = self.orders.lock().unwrap();
+            let order = orders.iter_mut().find(|o| o.id == order_id && o.status == from.as_str());
+            let applied = order.is_some();
+            if let Some(order) = order {
+                order.status = to.as_str().to_string();
+                let mut events = self.events.lock().unwrap();
+                let id = events.len() as u64 + 1;
+                events.push(OrderEvent {
+                    id,
+                    order_id,
+                    from_status: Some(from.as_str().to_string()),
+                    to_status: to.as_str().to_string(),
+                    actor_type: actor.actor_type.as_str().to_string(),
+                    actor_id: actor.id,
+                    reason: reason.to_string(),
+                    created_at: None,
+                });
+            }
+            Box::pin(async move { Ok(applied) })
+        }
+
+        fn list_events(&self, order_id: u64) -> BoxFuture<'_, Result<Vec<OrderEvent>, sqlx::Error>> {
+            let events = self.events.lock().unwrap().iter().filter(|e| e.order_id == order_id).cloned().collect();
+            Box::pin(async move { Ok(events) })
+        }
+    }
+
+    /// 只记录回补的库存服务，前 `failures` 次回补失败
+    #[derive(Default)]
+    struct RecordingInventory {
+        released: Mutex<Vec<u64>>,
+        failures: AtomicU32,
+    }
+
+    impl InventoryService for RecordingInventory {
+        fn reserve<'a>(&'a self, _sku_id: u64, _user_id: u32, _quantity: u32, _order_ref: &'a str) -> BoxFuture<'a, Result<(), ServiceError>> {
+            unreachable!()
+        }
+
+        fn release<'a>(&'a self, sku_id: u64, _order_ref: &'a str) -> BoxFuture<'a, Result<Option<i64>, ServiceError>> {
+            let failed = self.failures.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1)).is_ok();
+            if !failed {
+                self.released.lock().unwrap().push(sku_id);
+            }
+            Box::pin(async move {
+                if failed {
+                    Err(ServiceError::Redis(fred::error::Error::new(fred::error::ErrorKind::IO, "connection lost")))
+                } else {
+                    Ok(Some(1))
+                }
+            })
+        }
+
+        fn restock<'a>(&'a self, _sku_id: u64, _user_id: u32, _quantity: u32, _order_ref: &'a str) -> BoxFuture<'a, Result<(), ServiceError>> {
+            unreachable!()
+        }
+
+        fn is_flash_sale(&self, _sku_id: u64) -> BoxFuture<'_, Result<bool, ServiceError>> {
+            unreachable!()
+        }
+
+        fn available(&self, _sku_id: u64) -> BoxFuture<'_, Result<i64, ServiceError>> {
+            unreachable!()
+        }
+
+        fn warm_up(&self) -> BoxFuture<'_, Result<usize, ServiceError>> {
+            unreachable!()
+        }
+
+        fn reconcile(&self, _batch_size: usize) -> BoxFuture<'_, Result<ReconcileReport, ServiceError>> {
+            unreachable!()
+        }
+    }
+
+    /// 其余依赖的桩实现：只记录退券和退积分，其他方法不应被调用
+    #[derive(Default)]
+    struct Stubs {
+        released: Mutex<Vec<&'static str>>,
+    }
+
+    impl AddressRepo for Stubs {
+        fn find(&self, _user_id: u32, _id: u64) -> BoxFuture<'_, Result<Option<Address>, sqlx::Error>> {
+            unreachable!()
+        }
+        fn list(&self, _user_id: u32) -> BoxFuture<'_, Result<Vec<Address>, sqlx::Error>> {
+            unreachable!()
+        }
+        fn create<'a>(&'a self, _user_id: u32, _address: &'a NewAddress) -> BoxFuture<'a, Result<u64, sqlx::Error>> {
+            unreachable!()
+        }
+    }
+
+    impl CatalogRepo for Stubs {
+        fn find_skus<'a>(&'a self, _sku_ids: &'a [u64]) -> BoxFuture<'a, Result<Vec<SkuDetail>, sqlx::Error>> {
+            unreachable!()
+        }
+        fn find_products<'a>(&'a self, _product_ids: &'a [u64]) -> BoxFuture<'a, Result<Vec<ProductSummary>, sqlx::Error>> {
+            unreachable!()
+        }
+    }
+
+    impl CartService for Stubs {
+        fn check_line(&self, _sku_id: u64, _quantity: u32) -> BoxFuture<'_, Result<(), ServiceError>> {
+            unreachable!()
+        }
+        fn view(&self, _lines: Vec<CartLine>) -> BoxFuture<'_, Result<CartView, ServiceError>> {
+            unreachable!()
+        }
+        fn list(&self, _user_id: u32) -> BoxFuture<'_, Result<Vec<CartLine>, ServiceError>> {
+            unreachable!()
+        }
+        fn add(&self, _user_id: u32, _sku_id: u64, _quantity: u32) -> BoxFuture<'_, Result<(), ServiceError>> {
+            unreachable!()
+        }
+        fn set_quantity(&self, _user_id: u32, _sku_id: u64, _quantity: u32) -> BoxFuture<'_, Result<(), ServiceError>> {
+            unreachable!()
+        }
+        fn remove<'a>(&'a self, _user_id: u32, _sku_ids: &'a [u64]) -> BoxFuture<'a, Result<(), ServiceError>> {
+            unreachable!()
+        }
+        fn select<'a>(&'a self, _user_id: u32, _sku_ids: &'a [u64], _selected: bool) -> BoxFuture<'a, Result<(), ServiceError>> {
+            unreachable!()
+        }
+        fn merge(&self, _user_id: u32, _guest_lines: Vec<CartLine>) -> BoxFuture<'_, Result<(), ServiceError>> {
+            unreachable!()
+        }
+    }
+
+    impl CouponService for Stubs {
+        fn list_claimable(&self) -> BoxFuture<'_, Result<Vec<CouponTemplate>, ServiceError>> {
+            unreachable!()
+        }
+        fn claim(&self, _user_id: u32, _template_id: u64) -> BoxFuture<'_, Result<UserCoupon, ServiceError>> {
+            unreachable!()
+        }
+        fn list_mine(&self, _user_id: u32) -> BoxFuture<'_, Result<Vec<MyCoupon>, ServiceError>> {
+            unreachable!()
+        }
+        fn checkout_template(&self, _user_id: u32, _coupon_id: u64) -> BoxFuture<'_, Result<CouponTemplate, ServiceError>> {
+            unreachable!()
+        }
+        fn lock<'a>(&'a self, _user_id: u32, _coupon_id: u64, _order_no: &'a str) -> BoxFuture<'a, Result<(), ServiceError>> {
+            unreachable!()
+        }
+        fn release<'a>(&'a self, _order_no: &'a str) -> BoxFuture<'a, Result<(), ServiceError>> {
+            self.released.lock().unwrap().push("coupon");
+            Box::pin(async { Ok(()) })
+        }
+        fn consume<'a>(&'a self, _order_no: &'a str) -> BoxFuture<'a, Result<(), ServiceError>> {
+            unreachable!()
+        }
+        fn create_template<'a>(&'a self, _template: &'a NewCouponTemplate) -> BoxFuture<'a, Result<CouponTemplate, ServiceError>> {
+            unreachable!()
+        }
+        fn list_templates(&self, _page: u32, _page_size: u32) -> BoxFuture<'_, Result<Vec<CouponTemplate>, ServiceError>> {
+            unreachable!()
+        }
+        fn disable_template(&self, _id: u64) -> BoxFuture<'_, Result<CouponTemplate, ServiceError>> {
+            unreachable!()
+        }
+    }
+
+    impl PromotionService for Stubs {
+        fn list_active(&self) -> BoxFuture<'_, Result<Vec<Promotion>, ServiceError>> {
+            unreachable!()
+        }
+        fn create<'a>(&'a self, _promotion: &'a NewPromotion) -> BoxFuture<'a, Result<Promotion, ServiceError>> {
+            unreachable!()
+        }
+        fn list(&self, _page: u32, _page_size: u32) -> BoxFuture<'_, Result<Vec<Promotion>, ServiceError>> {
+            unreachable!()
+        }
+        fn disable(&self, _id: u64) -> BoxFuture<'_, Result<Promotion, ServiceError>> {
+            unreachable!()
+        }
+    }
+
+    impl FreightService for Stubs {
+        fn list(&self) -> BoxFuture<'_, Result<Vec<FreightTemplate>, ServiceError>> {
+            unreachable!()
+        }
+        fn create<'a>(&'a self, _template: &'a NewFreightTemplate) -> BoxFuture<'a, Result<FreightTemplate, ServiceError>> {
+            unreachable!()
+        }
+        fn update<'a>(&'a self, _id: u64, _template: &'a NewFreightTemplate) -> BoxFuture<'a, Result<FreightTemplate, ServiceError>> {
+            unreachable!()
+        }
+        fn assign(&self, _product_id: u64, _template_id: Option<u64>) -> BoxFuture<'_, Result<(), ServiceError>> {
+            unreachable!()
+        }
+        fn templates<'a>(&'a self, _ids: &'a [u64]) -> BoxFuture<'a, Result<HashMap<u64, FreightTemplate>, ServiceError>> {
+            unreachable!()
+        }
+    }
+
+    impl LoyaltyService for Stubs {
+        fn member(&self, _user_id: u32) -> BoxFuture<'_, Result<Member, ServiceError>> {
+            unreachable!()
+        }
+        fn points_step(&self, _member: &Member, _requested: i64) -> PointsStep {
+            unreachable!()
+        }
+        fn redeem<'a>(&'a self, _user_id: u32, _points: i64, _order_no: &'a str) -> BoxFuture<'a, Result<(), ServiceError>> {
+            unreachable!()
+        }
+        fn release<'a>(&'a self, _user_id: u32, _order_no: &'a str) -> BoxFuture<'a, Result<(), ServiceError>> {
+            self.released.lock().unwrap().push("points");
+            Box::pin(async { Ok(()) })
+        }
+        fn earn_for_order(&self, _order_id: u64) -> BoxFuture<'_, Result<(), ServiceError>> {
+            unreachable!()
+        }
+        fn reverse_for_order(&self, _order_id: u64) -> BoxFuture<'_, Result<(), ServiceError>> {
+            unreachable!()
+        }
+        fn expire_and_retier(&self, _now: DateTime<Local>) -> BoxFuture<'_, Result<usize, ServiceError>> {
+            unreachable!()
+        }
+        fn my_points(&self, _user_id: u32, _page: u32, _page_size: u32) -> BoxFuture<'_, Result<MyPoints, ServiceError>> {
+            unreachable!()
+        }
+    }
+
+    impl JobQueue for Stubs {
+        fn enqueue<'a>(&'a self, _job: &'a Job, _run_at: i64) -> BoxFuture<'a, Result<(), fred::error::Error>> {
+            unreachable!()
+        }
+        fn claim(&self, _now: i64, _visible_at: i64) -> BoxFuture<'_, Result<Option<Job>, fred::error::Error>> {
+            unreachable!()
+        }
+        fn ack<'a>(&'a self, _job_id: &'a str) -> BoxFuture<'a, Result<(), fred::error::Error>> {
+            unreachable!()
+        }
+        fn retry<'a>(&'a self, _job_id: &'a str, _run_at: i64) -> BoxFuture<'a, Result<(), fred::error::Error>> {
+            unreachable!()
+        }
+        fn bury<'a>(&'a self, _job: &'a Job, _error: &'a str) -> BoxFuture<'a, Result<(), fred::error::Error>> {
+            unreachable!()
+        }
+    }
+
+    fn timeout_job(orders: Arc<MemoryOrders>, inventory: Arc<RecordingInventory>, stubs: Arc<Stubs>) -> Arc<OrderTimeoutJob> {
+        let deps = OrderDeps {
+            cart: stubs.clone(),
+            inventory,
+            state: Arc::new(OrderStateServiceImpl::new(orders.clone())),
+            jobs: stubs.clone(),
+            coupons: stubs.clone(),
+            promotions: stubs.clone(),
+            freight: stubs.clone(),
+            loyalty: stubs.clone(),
+        };
+        let service = OrderServiceImpl::new(orders, stubs.clone(), stubs, deps, OrderSettings::default());
+        OrderTimeoutJob::new(Arc::new(service))
+    }
+
+    fn job() -> Job {
+        Job::new(ORDER_TIMEOUT_JOB, "1", serde_json::json!({ "order_id": 1 }))
+    }
+
+    #[tokio::test]
+    async fn test_timeout_cancels_unpaid_order_and_releases_everything() {
+        let orders = MemoryOrders::with_order(OrderStatus::PendingPayment);
+        let inventory = Arc::new(RecordingInventory::default());
+        let stubs = Arc::new(Stubs::default());
+        let handler = timeout_job(orders.clone(), inventory.clone(), stubs.clone());
+
+        handler.handle(&job()).await.unwrap();
+        assert_eq!(orders.status(), "cancelled");
+        assert_eq!(*inventory.released.lock().unwrap(), [10, 11]);
+        assert_eq!(*stubs.released.lock().unwrap(), ["coupon", "points"]);
+
+        let paid = MemoryOrders::with_order(OrderStatus::Paid);
+        let inventory = Arc::new(RecordingInventory::default());
+        timeout_job(paid.clone(), inventory.clone(), stubs).handle(&job()).await.unwrap();
+        assert_eq!(paid.status(), "paid");
+        assert!(inventory.released.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_release_is_retried_on_redelivery() {
+        let orders = MemoryOrders::with_order(OrderStatus::PendingPayment);
+        let inventory = Arc::new(RecordingInventory { failures: AtomicU32::new(1), ..Default::default() });
+        let stubs = Arc::new(Stubs::default());
+        let handler = timeout_job(orders.clone(), inventory.clone(), stubs.clone());
+
+        assert!(handler.handle(&job()).await.is_err(), "the job must fail so the queue retries it");
+        assert_eq!(orders.status(), "cancelled");
+        assert_eq!(*inventory.released.lock().unwrap(), [11]);
+
+        handler.handle(&job()).await.unwrap();
+        assert_eq!(*inventory.released.lock().unwrap(), [11, 10, 11]);
+        assert_eq!(orders.events.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,261 @@
+use crate::domain::BoxFuture;
+use crate::domain::order::OrderStateRepo;
+use crate::models::event::{DomainEvent, OrderStatusChanged};
+use crate::models::order::{Actor, Order, OrderEvent, OrderStatus};
+use crate::service::ServiceError;
+use std::sync::Arc;
+
+/// 一次流转请求的结果
+#[derive(Debug)]
+pub enum TransitionOutcome {
+    /// 本次请求完成了流转，调用方可以执行后续动作（回补库存、发通知等）
+    Applied(Order),
+    /// 订单已经处于目标状态（重复投递、并发请求），什么都没有做
+    AlreadyInState(Order),
+}
+
+impl TransitionOutcome {
+    pub fn is_applied(&self) -> bool {
+        matches!(self, TransitionOutcome::Applied(_))
+    }
+
+    pub fn into_order(self) -> Order {
+        match self {
+            TransitionOutcome::Applied(order) | TransitionOutcome::AlreadyInState(order) => order,
+        }
+    }
+}
+
+pub trait OrderStateService: Send + Sync {
+    /// 按状态机把订单流转到 `to`，非法流转返回 `ServiceError::Conflict`
+    fn transition<'a>(
+        &'a self,
+        order_id: u64,
+        to: OrderStatus,
+        actor: Actor,
+        reason: &'a str,
+    ) -> BoxFuture<'a, Result<TransitionOutcome, ServiceError>>;
+    fn events(&self, order_id: u64) -> BoxFuture<'_, Result<Vec<OrderEvent>, ServiceError>>;
+}
+
+pub struct OrderStateServiceImpl<R: OrderStateRepo + 'static> {
+    repo: Arc<R>,
+}
+
+impl<R: OrderStateRepo + 'static> OrderStateServiceImpl<R> {
+    pub fn new(repo: Arc<R>) -> Self {
+        Self { repo }
+    }
+
+    async fn load(&self, order_id: u64) -> Result<(Order, OrderStatus), ServiceError> {
+        let order = self
+            .repo
+            .find_order(order_id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Order with ID {} not found", order_id)))?;
+        let status = order.status().map_err(ServiceError::Conflict)?;
+        Ok((order, status))
+    }
+
+    /// 退款被拒绝或撤回时只能回到进入退款前的状态
+    async fn check_refund_rollback(&self, order_id: u64, to: OrderStatus) -> Result<(), ServiceError> {
+        let events = self.repo.list_events(order_id).await?;
+        let before_refund = events
+            .iter()
+            .rev()
+            .find(|event| event.to_status == OrderStatus::Refunding.as_str())
+            .and_then(|event| event.from_status.as_deref());
+        if before_refund == Some(to.as_str()) {
+            Ok(())
+        } else {
+            Err(ServiceError::Conflict(format!(
+                "order {} can only return to {:?} after refund",
+                order_id, before_refund
+            )))
+        }
+    }
+}
+
+impl<R: OrderStateRepo + 'static> OrderStateService for OrderStateServiceImpl<R> {
+    fn transition<'a>(
+        &'a self,
+        order_id: u64,
+        to: OrderStatus,
+        actor: Actor,
+        reason: &'a str,
+    ) -> BoxFuture<'a, Result<TransitionOutcome, ServiceError>> {
+        Box::pin(async move {
+            let (order, from) = self.load(order_id).await?;
+            if from == to {
+                return Ok(TransitionOutcome::AlreadyInState(order));
+            }
+            if !from.can_transition_to(to) {
+                return Err(ServiceError::Conflict(format!(
+                    "order {} cannot transition from {} to {}",
+                    order_id, from, to
+                )));
+            }
+            if from == OrderStatus::Refunding && to != OrderStatus::Refunded {
+                self.check_refund_rollback(order_id, to).await?;
+            }
+
+            let event = DomainEvent::order_status_changed(&OrderStatusChanged::new(&order, from, to, actor, reason));
+            if self.repo.transition(order_id, from, to, actor, reason, &event).await? {
+                let (order, _) = self.load(order_id).await?;
+                return Ok(TransitionOutcome::Applied(order));
+            }
+
+            // 并发修改：如果别人已经把订单推进到目标状态，视为重复请求
+            let (order, current) = self.load(order_id).await?;
+            if current == to {
+                Ok(TransitionOutcome::AlreadyInState(order))
+            } else {
+                Err(ServiceError::Conflict(format!(
+                    "order {} changed concurrently from {} to {}",
+                    order_id, from, current
+                )))
+            }
+        })
+    }
+
+    fn events(&self, order_id: u64) -> BoxFuture<'_, Result<Vec<OrderEvent>, ServiceError>> {
+        Box::pin(async move { Ok(self.repo.list_events(order_id).await?) })
+    }
+}
+
+pub fn new_order_state_service<R: OrderStateRepo + 'static>(repo: Arc<R>) -> Arc<dyn OrderStateService> {
+    Arc::new(OrderStateServiceImpl::new(repo)) as Arc<dyn OrderStateService>
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// 内存版订单仓储，只保存状态和流转记录
+    #[derive(Default)]
+    struct MemoryOrders {
+        orders: Mutex<Vec<Order>>,
+        events: Mutex<Vec<OrderEvent>>,
+        outbox: Mutex<Vec<DomainEvent>>,
+    }
+
+    impl MemoryOrders {
+        fn with_order(id: u64, status: OrderStatus) -> Arc<Self> {
+            let repo = MemoryOrders::default();
+            repo.orders.lock().unwrap().push(Order {
+                id,
+                order_no: format!("NO{}", id),
+                user_id: 1,
+                status: status.as_str().to_string(),
+                goods_amount: 100,
+                discount_amount: 0,
+                shipping_fee: 0,
+                payable_amount: 100,
+                receiver_name: "r".into(),
+                receiver_phone: "p".into(),
+                province: "p".into(),
+                city: "c".into(),
+                district: "d".into(),
+                address_detail: "a".into(),
+                created_at: None,
+                updated_at: None,
+            });
+            Arc::new(repo)
+        }
+    }
+
+    impl OrderStateRepo for MemoryOrders {
+        fn find_order(&self, id: u64) -> BoxFuture<'_, Result<Option<Order>, sqlx::Error>> {
+            let order = self.orders.lock().unwrap().iter().find(|o| o.id == id).cloned();
+            Box::pin(async move { Ok(order) })
+        }
+
+        fn transition<'a>(
+            &'a self,
+            order_id: u64,
+            from: OrderStatus,
+            to: OrderStatus,
+            actor: Actor,
+            reason: &'a str,
+            event: &'a DomainEvent,
+        ) -> BoxFuture<'a, Result<bool, sqlx::Error>> {
+            let mut orders = self.orders.lock().unwrap();
+            let order = orders.iter_mut().find(|o| o.id == order_id && o.status == from.as_str());
+            let applied = match order {
+                Some(order) => {
+                    order.status = to.as_str().to_string();
+                    let mut events = self.events.lock().unwrap();
+                    let id = events.len() as u64 + 1;
+                    events.push(OrderEvent {
+                        id,
+                        order_id,
+                        from_status: Some(from.as_str().to_string()),
+                        to_status: to.as_str().to_string(),
+                        actor_type: actor.actor_type.as_str().to_string(),
+                        actor_id: actor.id,
+                        reason: reason.to_string(),
+                        created_at: None,
+                    });
+                    self.outbox.lock().unwrap().push(event.clone());
+                    true
+                }
+                None => false,
+            };
+            Box::pin(async move { Ok(applied) })
+        }
+
+        fn list_events(&self, order_id: u64) -> BoxFuture<'_, Result<Vec<OrderEvent>, sqlx::Error>> {
+            let events = self.events.lock().unwrap().iter().filter(|e| e.order_id == order_id).cloned().collect();
+            Box::pin(async move { Ok(events) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_happy_path_records_every_transition() {
+        let repo = MemoryOrders::with_order(1, OrderStatus::PendingPayment);
+        let service = OrderStateServiceImpl::new(repo.clone());
+        for to in [OrderStatus::Paid, OrderStatus::Shipped, OrderStatus::Delivered, OrderStatus::Completed] {
+            let outcome = service.transition(1, to, Actor::user(1), "test").await.unwrap();
+            assert!(outcome.is_applied());
+        }
+        let events = service.events(1).await.unwrap();
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[3].to_status, "completed");
+
+        let outbox = repo.outbox.lock().unwrap();
+        let types: Vec<&str> = outbox.iter().map(|event| event.event_type.as_str()).collect();
+        assert_eq!(types, ["order.paid", "order.shipped", "order.delivered", "order.completed"]);
+        let paid: OrderStatusChanged = outbox[0].decode().unwrap();
+        assert_eq!((paid.order_id, paid.from.as_str(), paid.user_id), (1, "pending_payment", 1));
+    }
+
+    #[tokio::test]
+    async fn test_illegal_transition_is_conflict() {
+        let repo = MemoryOrders::with_order(1, OrderStatus::PendingPayment);
+        let service = OrderStateServiceImpl::new(repo);
+        let result = service.transition(1, OrderStatus::Shipped, Actor::user(1), "skip payment").await;
+        assert!(matches!(result, Err(ServiceError::Conflict(_))));
+    }
+
+    #[tokio::test]
+    async fn test_repeated_transition_is_noop() {
+        let repo = MemoryOrders::with_order(1, OrderStatus::PendingPayment);
+        let service = OrderStateServiceImpl::new(repo.clone());
+        assert!(service.transition(1, OrderStatus::Paid, Actor::user(1), "notify").await.unwrap().is_applied());
+        assert!(!service.transition(1, OrderStatus::Paid, Actor::user(1), "notify").await.unwrap().is_applied());
+        assert_eq!(repo.events.lock().unwrap().len(), 1);
+        assert_eq!(repo.outbox.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_refund_rejection_returns_to_previous_status() {
+        let repo = MemoryOrders::with_order(1, OrderStatus::Shipped);
+        let service = OrderStateServiceImpl::new(repo);
+        service.transition(1, OrderStatus::Refunding, Actor::user(1), "refund").await.unwrap();
+        let wrong = service.transition(1, OrderStatus::Paid, Actor::user(1), "rejected").await;
+        assert!(matches!(wrong, Err(ServiceError::Conflict(_))));
+        let back = service.transition(1, OrderStatus::Shipped, Actor::user(1), "rejected").await.unwrap();
+        assert_eq!(back.into_order().status, "shipped");
+    }
+}
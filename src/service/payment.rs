@@ -0,0 +1,274 @@
+use crate::domain::BoxFuture;
+use crate::domain::payment::{NotifyHeaders, PayGateway, PaymentRepo, PrepayRequest, Transaction};
+use crate::models::order::{Actor, OrderDetail, OrderItem, OrderStatus};
+use crate::models::payment::{CHANNEL_WECHAT_JSAPI, JsapiPayParams, NewPayment, PAYMENT_PENDING, Payment};
+use crate::service::ServiceError;
+use crate::service::order::OrderService;
+use crate::service::order_state::OrderStateService;
+use crate::service::refund::RefundService;
+use chrono::{DateTime, Local, SecondsFormat};
+use std::sync::Arc;
+use std::time::Duration;
+use wx_shop::{OrderSettings, PaymentSyncSettings, WechatPaySettings};
+
+/// 微信支付商品描述最长 127 字节，这里按字符保守截断
+const MAX_DESCRIPTION_CHARS: usize = 40;
+
+pub trait PaymentService: Send + Sync {
+    /// 为用户自己的待支付订单创建 JSAPI 预支付单，返回小程序调起支付的参数
+    fn jsapi_pay<'a>(&'a self, user_id: u32, order_id: u64, openid: &'a str) -> BoxFuture<'a, Result<JsapiPayParams, ServiceError>>;
+    /// 处理微信支付结果通知：验签解密、核对金额和商户号后把订单置为已支付。
+    /// 重复通知只会生效一次；返回错误时微信会按策略重发。
+    fn handle_wechat_notify<'a>(&'a self, headers: &'a NotifyHeaders, body: &'a str) -> BoxFuture<'a, Result<(), ServiceError>>;
+    /// 小程序 `requestPayment` 返回后主动查单，按结果推进订单并返回最新订单详情
+    fn sync(&self, user_id: u32, order_id: u64) -> BoxFuture<'_, Result<OrderDetail, ServiceError>>;
+    /// 查询一批长时间未收到通知的待支付单，返回状态发生变化的数量
+    fn sync_pending(&self, before: DateTime<Local>, after: DateTime<Local>, limit: u32) -> BoxFuture<'_, Result<usize, ServiceError>>;
+}
+
+/// 支付流程依赖的其他服务
+pub struct PaymentDeps {
+    pub orders: Arc<dyn OrderService>,
+    pub state: Arc<dyn OrderStateService>,
+    pub gateway: Arc<dyn PayGateway>,
+    pub refunds: Arc<dyn RefundService>,
+}
+
+pub struct PaymentServiceImpl<R: PaymentRepo + 'static> {
+    repo: Arc<R>,
+    orders: Arc<dyn OrderService>,
+    state: Arc<dyn OrderStateService>,
+    gateway: Arc<dyn PayGateway>,
+    refunds: Arc<dyn RefundService>,
+    settings: OrderSettings,
+    wechat_pay: WechatPaySettings,
+}
+
+impl<R: PaymentRepo + 'static> PaymentServiceImpl<R> {
+    pub fn new(repo: Arc<R>, deps: PaymentDeps, settings: OrderSettings, wechat_pay: WechatPaySettings) -> Self {
+        let PaymentDeps { orders, state, gateway, refunds } = deps;
+        Self { repo, orders, state, gateway, refunds, settings, wechat_pay }
+    }
+
+    /// 向微信查询支付单并应用与通知相同的处理：成功则置为已支付，交易关闭则取消订单。
+    /// 返回支付单状态是否发生变化
+    async fn sync_payment(&self, payment: &Payment) -> Result<bool, ServiceError> {
+        if payment.status != PAYMENT_PENDING {
+            return Ok(false);
+        }
+        let Some(transaction) = self.gateway.query_order(&payment.out_trade_no).await? else {
+            return Ok(false);
+        };
+        if transaction.is_success() {
+            self.settle(&transaction).await?;
+            return Ok(true);
+        }
+        if transaction.is_closed() {
+            if self.repo.mark_closed(payment.id).await? {
+                tracing::info!("Payment {} closed by channel: {}", payment.out_trade_no, transaction.trade_state);
+                self.orders.cancel_unpaid(payment.order_id).await?;
+            }
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// 记录支付成功并推进订单；支付单和订单状态都是条件更新，重复执行是安全的
+    async fn settle(&self, transaction: &Transaction) -> Result<(), ServiceError> {
+        if transaction.mchid != self.wechat_pay.mch_id || transaction.appid != self.wechat_pay.app_id {
+            return Err(ServiceError::Conflict(format!(
+                "notification for {} belongs to merchant {} / app {}",
+                transaction.out_trade_no, transaction.mchid, transaction.appid
+            )));
+        }
+        let payment = self
+            .repo
+            .find_by_out_trade_no(&transaction.out_trade_no)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Payment {} not found", transaction.out_trade_no)))?;
+        if transaction.amount.total != payment.amount {
+            return Err(ServiceError::Conflict(format!(
+                "payment {} amount mismatch: expected {}, notified {}",
+                payment.out_trade_no, payment.amount, transaction.amount.total
+            )));
+        }
+
+        let paid_at = transaction
+            .success_time
+            .as_deref()
+            .and_then(|time| DateTime::parse_from_rfc3339(time).ok())
+            .map(|time| time.with_timezone(&Local))
+            .unwrap_or_else(Local::now);
+        self.repo.mark_succeeded(payment.id, &transaction.transaction_id, paid_at).await?;
+
+        let reason = format!("wechat pay {}", transaction.transaction_id);
+        match self.state.transition(payment.order_id, OrderStatus::Paid, Actor::system(), &reason).await {
+            Ok(outcome) => {
+                // 核销优惠券、拼团计数由 order.paid 事件的订阅者完成，失败时由发件箱重新投递
+                if outcome.is_applied() {
+                    tracing::info!("Order {} paid by {}", payment.order_id, transaction.transaction_id);
+                }
+                Ok(())
+            }
+            // 订单已超时关闭：库存和优惠券都已退回，这笔款原路退回；退款出错时让微信重发通知再试
+            Err(ServiceError::Conflict(msg)) => {
+                let detail = self.orders.get(payment.user_id, payment.order_id).await?;
+                if detail.order.status() != Ok(OrderStatus::Cancelled) {
+                    tracing::error!("Payment {} succeeded but order cannot be paid: {}", payment.out_trade_no, msg);
+                    return Ok(());
+                }
+                tracing::warn!("Payment {} arrived after order {} was cancelled, refunding", payment.out_trade_no, payment.order_id);
+                let refund = self.refunds.refund_cancelled(payment.order_id, "order cancelled before payment").await?;
+                tracing::info!("Refund {} of cancelled order {} is {}", refund.refund_no, payment.order_id, refund.status);
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl<R: PaymentRepo + 'static> PaymentService for PaymentServiceImpl<R> {
+    fn jsapi_pay<'a>(&'a self, user_id: u32, order_id: u64, openid: &'a str) -> BoxFuture<'a, Result<JsapiPayParams, ServiceError>> {
+        Box::pin(async move {
+            let detail = self.orders.get(user_id, order_id).await?;
+            let order = &detail.order;
+            if order.status() != Ok(OrderStatus::PendingPayment) {
+                return Err(ServiceError::Conflict(format!("order {} is not pending payment", order_id)));
+            }
+            if order.payable_amount <= 0 {
+                return Err(ServiceError::Conflict(format!("order {} has nothing to pay", order_id)));
+            }
+
+            // 与超时关单对齐，过了截止时间微信侧也不再允许付款
+            let timeout = chrono::Duration::minutes(self.settings.payment_timeout_minutes as i64);
+            let request = PrepayRequest {
+                description: payment_description(&detail.items),
+                out_trade_no: order.order_no.clone(),
+                amount: order.payable_amount,
+                payer_openid: openid.to_string(),
+                time_expire: order
+                    .created_at
+                    .map(|created_at| (created_at + timeout).to_rfc3339_opts(SecondsFormat::Secs, false)),
+            };
+            let prepay_id = self.gateway.jsapi_prepay(&request).await?;
+
+            self.repo
+                .save_prepay(&NewPayment {
+                    order_id,
+                    user_id,
+                    channel: CHANNEL_WECHAT_JSAPI,
+                    out_trade_no: order.order_no.clone(),
+                    amount: order.payable_amount,
+                    prepay_id: prepay_id.clone(),
+                })
+                .await?;
+
+            Ok(self.gateway.jsapi_pay_params(&prepay_id)?)
+        })
+    }
+
+    fn handle_wechat_notify<'a>(&'a self, headers: &'a NotifyHeaders, body: &'a str) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let transaction = self.gateway.parse_notify(headers, body).await?;
+            if !transaction.is_success() {
+                tracing::info!("Ignore {} notification for {}", transaction.trade_state, transaction.out_trade_no);
+                return Ok(());
+            }
+            self.settle(&transaction).await
+        })
+    }
+
+    fn sync(&self, user_id: u32, order_id: u64) -> BoxFuture<'_, Result<OrderDetail, ServiceError>> {
+        Box::pin(async move {
+            // 先校验订单归属
+            self.orders.get(user_id, order_id).await?;
+            if let Some(payment) = self.repo.find_by_order(order_id).await? {
+                self.sync_payment(&payment).await?;
+            }
+            self.orders.get(user_id, order_id).await
+        })
+    }
+
+    fn sync_pending(&self, before: DateTime<Local>, after: DateTime<Local>, limit: u32) -> BoxFuture<'_, Result<usize, ServiceError>> {
+        Box::pin(async move {
+            let mut changed = 0;
+            for payment in self.repo.list_pending(before, after, limit).await? {
+                // 单笔失败不影响其他支付单，下一轮会再次查询
+                match self.sync_payment(&payment).await {
+                    Ok(true) => changed += 1,
+                    Ok(false) => {}
+                    Err(e) => tracing::warn!("Failed to sync payment {}: {:?}", payment.out_trade_no, e),
+                }
+            }
+            Ok(changed)
+        })
+    }
+}
+
+pub fn new_payment_service<R: PaymentRepo + 'static>(
+    repo: Arc<R>,
+    deps: PaymentDeps,
+    settings: OrderSettings,
+    wechat_pay: WechatPaySettings,
+) -> Arc<dyn PaymentService> {
+    Arc::new(PaymentServiceImpl::new(repo, deps, settings, wechat_pay)) as Arc<dyn PaymentService>
+}
+
+/// 后台定时查单，补偿丢失的支付结果通知
+pub fn spawn_payment_sync(service: Arc<dyn PaymentService>, settings: PaymentSyncSettings) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(settings.interval_secs.max(1)));
+        loop {
+            ticker.tick().await;
+            let now = Local::now();
+            let before = now - chrono::Duration::seconds(settings.pending_after_secs as i64);
+            let after = now - chrono::Duration::minutes(settings.give_up_after_minutes as i64);
+            match service.sync_pending(before, after, settings.batch_size).await {
+                Ok(changed) if changed > 0 => tracing::info!("Payment sync: {} payments updated", changed),
+                Ok(_) => {}
+                Err(e) => tracing::error!("Payment sync failed: {:?}", e),
+            }
+        }
+    });
+}
+
+/// 商品描述：首个商品名，多件商品时追加件数
+fn payment_description(items: &[OrderItem]) -> String {
+    let Some(first) = items.first() else {
+        return String::from("订单支付");
+    };
+    let name: String = first.product_name.chars().take(MAX_DESCRIPTION_CHARS).collect();
+    if items.len() > 1 {
+        format!("{} 等{}件商品", name, items.len())
+    } else {
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(product_name: &str) -> OrderItem {
+        OrderItem {
+            id: 1,
+            order_id: 1,
+            sku_id: 1,
+            product_id: 1,
+            product_name: product_name.to_string(),
+            sku_title: String::new(),
+            unit_price: 100,
+            quantity: 1,
+            line_total: 100,
+            discount_amount: 0,
+            payable_amount: 100,
+        }
+    }
+
+    #[test]
+    fn test_payment_description() {
+        assert_eq!(payment_description(&[item("苹果")]), "苹果");
+        assert_eq!(payment_description(&[item("苹果"), item("香蕉")]), "苹果 等2件商品");
+        assert_eq!(payment_description(&[item(&"长".repeat(100))]).chars().count(), MAX_DESCRIPTION_CHARS);
+    }
+}
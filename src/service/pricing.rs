@@ -0,0 +1,258 @@
+//! 结算计价流水线：金额均为整数「分」，各 [`PricingStep`] 按顺序作用在同一份 [`Quote`] 上。
+//! 步骤只使用预先加载的数据、不做 I/O，所以预览和下单的结果可以直接比较。
+
+use crate::service::ServiceError;
+use serde::Serialize;
+
+/// 参与计价的商品行
+#[derive(Debug, Clone)]
+pub struct PricingItem {
+    pub sku_id: u64,
+    pub product_id: u64,
+    /// 用于优惠券、活动的适用范围判断
+    pub category_id: u64,
+    pub product_name: String,
+    pub sku_title: String,
+    pub unit_price: i64,
+    pub quantity: u32,
+    /// 单件重量（克），按重量计费的运费模板使用
+    pub weight_grams: u32,
+    pub freight_template_id: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PricingInput {
+    pub items: Vec<PricingItem>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QuoteLine {
+    pub sku_id: u64,
+    pub product_id: u64,
+    pub product_name: String,
+    pub sku_title: String,
+    pub unit_price: i64,
+    pub quantity: u32,
+    pub line_total: i64,
+    /// 分摊到本行的优惠
+    pub discount_amount: i64,
+    pub payable_amount: i64,
+}
+
+/// 一条优惠明细，`amount` 为实际扣减的金额（正数）
+#[derive(Debug, Clone, Serialize)]
+pub struct Adjustment {
+    pub source: String,
+    pub description: String,
+    pub amount: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Quote {
+    pub lines: Vec<QuoteLine>,
+    pub goods_amount: i64,
+    pub discount_amount: i64,
+    pub shipping_fee: i64,
+    pub payable_amount: i64,
+    pub adjustments: Vec<Adjustment>,
+    /// 本单抵扣使用的积分
+    pub points_used: i64,
+}
+
+impl Quote {
+    fn from_input(input: &PricingInput) -> Self {
+        let lines: Vec<QuoteLine> = input
+            .items
+            .iter()
+            .map(|item| {
+                let line_total = item.unit_price * i64::from(item.quantity);
+                QuoteLine {
+                    sku_id: item.sku_id,
+                    product_id: item.product_id,
+                    product_name: item.product_name.clone(),
+                    sku_title: item.sku_title.clone(),
+                    unit_price: item.unit_price,
+                    quantity: item.quantity,
+                    line_total,
+                    discount_amount: 0,
+                    payable_amount: line_total,
+                }
+            })
+            .collect();
+        let goods_amount = lines.iter().map(|line| line.line_total).sum();
+        Quote {
+            lines,
+            goods_amount,
+            discount_amount: 0,
+            shipping_fee: 0,
+            payable_amount: goods_amount,
+            adjustments: Vec::new(),
+            points_used: 0,
+        }
+    }
+
+    /// 商品部分扣除优惠后的金额（不含运费）
+    pub fn goods_payable(&self) -> i64 {
+        self.lines.iter().map(|line| line.payable_amount).sum()
+    }
+
+    /// 把一笔优惠按行应付金额比例分摊到 `line_indexes` 指定的行上，
+    /// 超出这些行剩余应付的部分不生效。返回实际扣减的金额。
+    pub fn apply_discount(&mut self, source: &str, description: &str, amount: i64, line_indexes: &[usize]) -> i64 {
+        let weights: Vec<i64> = line_indexes.iter().map(|&i| self.lines[i].payable_amount).collect();
+        let amount = amount.min(weights.iter().sum()).max(0);
+        if amount == 0 {
+            return 0;
+        }
+        for (&i, share) in line_indexes.iter().zip(allocate(amount, &weights)) {
+            let line = &mut self.lines[i];
+            line.discount_amount += share;
+            line.payable_amount -= share;
+        }
+        self.adjustments.push(Adjustment {
+            source: source.to_string(),
+            description: description.to_string(),
+            amount,
+        });
+        amount
+    }
+
+    /// 按已算好的逐行金额扣减，每行不超过其剩余应付。返回实际扣减的金额。
+    pub fn apply_line_discounts(&mut self, source: &str, description: &str, shares: &[(usize, i64)]) -> i64 {
+        let mut amount = 0;
+        for &(i, share) in shares {
+            let line = &mut self.lines[i];
+            let share = share.min(line.payable_amount).max(0);
+            line.discount_amount += share;
+            line.payable_amount -= share;
+            amount += share;
+        }
+        if amount > 0 {
+            self.adjustments.push(Adjustment {
+                source: source.to_string(),
+                description: description.to_string(),
+                amount,
+            });
+        }
+        amount
+    }
+
+    fn finalize(&mut self) {
+        self.discount_amount = self.lines.iter().map(|line| line.discount_amount).sum();
+        self.payable_amount = self.goods_amount - self.discount_amount + self.shipping_fee;
+    }
+}
+
+/// 按权重把 `amount` 分到每一份上，结果之和严格等于 `amount`。
+/// 先按比例向下取整，余下的分按小数部分从大到小依次补一分，小数相同时靠前的优先。
+pub fn allocate(amount: i64, weights: &[i64]) -> Vec<i64> {
+    let total: i64 = weights.iter().sum();
+    if total <= 0 {
+        return vec![0; weights.len()];
+    }
+    let mut shares = Vec::with_capacity(weights.len());
+    let mut remainders = Vec::with_capacity(weights.len());
+    for (i, &weight) in weights.iter().enumerate() {
+        let exact = i128::from(amount) * i128::from(weight);
+        shares.push((exact / i128::from(total)) as i64);
+        remainders.push((exact % i128::from(total), i));
+    }
+    let mut left = amount - shares.iter().sum::<i64>();
+    remainders.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    for (_, i) in remainders {
+        if left == 0 {
+            break;
+        }
+        shares[i] += 1;
+        left -= 1;
+    }
+    shares
+}
+
+pub trait PricingStep: Send + Sync {
+    fn apply(&self, input: &PricingInput, quote: &mut Quote) -> Result<(), ServiceError>;
+}
+
+/// 固定运费，商品应付达到门槛包邮；门槛为 0 表示不设包邮
+pub struct FlatShipping {
+    pub fee: i64,
+    pub free_threshold: i64,
+}
+
+impl PricingStep for FlatShipping {
+    fn apply(&self, _input: &PricingInput, quote: &mut Quote) -> Result<(), ServiceError> {
+        let free = self.free_threshold > 0 && quote.goods_payable() >= self.free_threshold;
+        quote.shipping_fee = if free { 0 } else { self.fee };
+        Ok(())
+    }
+}
+
+pub struct PricingPipeline {
+    steps: Vec<Box<dyn PricingStep>>,
+}
+
+impl PricingPipeline {
+    pub fn new(steps: Vec<Box<dyn PricingStep>>) -> Self {
+        Self { steps }
+    }
+
+    pub fn quote(&self, input: &PricingInput) -> Result<Quote, ServiceError> {
+        let mut quote = Quote::from_input(input);
+        for step in &self.steps {
+            step.apply(input, &mut quote)?;
+        }
+        quote.finalize();
+        Ok(quote)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(sku_id: u64, unit_price: i64, quantity: u32) -> PricingItem {
+        PricingItem {
+            sku_id,
+            product_id: sku_id,
+            category_id: 0,
+            product_name: format!("p{}", sku_id),
+            sku_title: format!("s{}", sku_id),
+            unit_price,
+            quantity,
+            weight_grams: 0,
+            freight_template_id: None,
+        }
+    }
+
+    #[test]
+    fn test_allocate_is_exact() {
+        assert_eq!(allocate(100, &[1, 1, 1]), vec![34, 33, 33]);
+        assert_eq!(allocate(10, &[0, 5]), vec![0, 10]);
+        assert_eq!(allocate(7, &[0, 0]), vec![0, 0]);
+    }
+
+    #[test]
+    fn test_quote_with_discount_and_shipping() {
+        let input = PricingInput { items: vec![item(1, 1000, 2), item(2, 500, 1)] };
+
+        struct TenOff;
+        impl PricingStep for TenOff {
+            fn apply(&self, _input: &PricingInput, quote: &mut Quote) -> Result<(), ServiceError> {
+                quote.apply_discount("test", "立减 10 元", 1000, &[0, 1]);
+                Ok(())
+            }
+        }
+
+        let pipeline = PricingPipeline::new(vec![
+            Box::new(TenOff),
+            Box::new(FlatShipping { fee: 800, free_threshold: 5000 }),
+        ]);
+        let quote = pipeline.quote(&input).unwrap();
+        assert_eq!(quote.goods_amount, 2500);
+        assert_eq!(quote.discount_amount, 1000);
+        assert_eq!(quote.lines[0].discount_amount, 800);
+        assert_eq!(quote.lines[1].discount_amount, 200);
+        assert_eq!(quote.shipping_fee, 800);
+        assert_eq!(quote.payable_amount, 2300);
+    }
+}
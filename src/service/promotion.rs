@@ -0,0 +1,593 @@
+//! 营销活动。规则以 JSON 声明存库，[`evaluate`] 是不做 I/O 的纯函数：
+//! 给定活动和商品行，按优先级逐个计算，返回逐行分摊的优惠和每个活动的说明。
+
+use crate::domain::BoxFuture;
+use crate::domain::promotion::PromotionRepo;
+use crate::models::promotion::{NewPromotion, PROMOTION_DISABLED, Promotion, PromotionRule};
+use crate::service::ServiceError;
+use crate::service::pricing::{PricingInput, PricingStep, Quote, allocate};
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::sync::Arc;
+
+/// 计价明细中活动优惠的来源标识
+const PROMOTION_SOURCE: &str = "promotion";
+
+/// 参与活动计算的商品行，`payable` 为进入活动计算前的应付金额
+#[derive(Debug, Clone)]
+pub struct PromotionLine {
+    pub sku_id: u64,
+    pub product_id: u64,
+    pub category_id: u64,
+    pub unit_price: i64,
+    pub quantity: u32,
+    pub payable: i64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EvalContext {
+    pub now: DateTime<Local>,
+    pub member_level: u32,
+}
+
+/// 某个活动分摊到某一行的优惠
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LineAllocation {
+    pub line: usize,
+    pub sku_id: u64,
+    pub promotion_id: u64,
+    pub amount: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AppliedPromotion {
+    pub promotion_id: u64,
+    pub name: String,
+    pub amount: i64,
+    pub explanation: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Evaluation {
+    pub allocations: Vec<LineAllocation>,
+    pub applied: Vec<AppliedPromotion>,
+    pub total_discount: i64,
+}
+
+/// 按优先级从高到低（同优先级按 ID）依次计算活动，每行累计优惠不超过其应付金额。
+/// 独占活动只作用在尚未享受优惠的行上，并且作用过的行不再参与后续活动；
+/// 非独占活动可以在同一行上叠加。
+pub fn evaluate(promotions: &[Promotion], lines: &[PromotionLine], ctx: &EvalContext) -> Evaluation {
+    let mut ordered: Vec<&Promotion> = promotions.iter().filter(|p| p.is_active(ctx.now)).collect();
+    ordered.sort_by(|a, b| b.priority.cmp(&a.priority).then(a.id.cmp(&b.id)));
+
+    let mut remaining: Vec<i64> = lines.iter().map(|line| line.payable.max(0)).collect();
+    let mut discounted = vec![false; lines.len()];
+    let mut locked = vec![false; lines.len()];
+    let mut evaluation = Evaluation::default();
+    for promotion in ordered {
+        let open: Vec<usize> = (0..lines.len())
+            .filter(|&i| remaining[i] > 0 && !locked[i] && !(promotion.exclusive && discounted[i]))
+            .collect();
+        let Some((shares, explanation)) = apply_rule(&promotion.rule, lines, &remaining, &open, ctx) else {
+            continue;
+        };
+
+        let mut amount = 0;
+        for (i, share) in shares {
+            let share = share.min(remaining[i]);
+            if share <= 0 {
+                continue;
+            }
+            remaining[i] -= share;
+            discounted[i] = true;
+            locked[i] |= promotion.exclusive;
+            amount += share;
+            evaluation.allocations.push(LineAllocation {
+                line: i,
+                sku_id: lines[i].sku_id,
+                promotion_id: promotion.id,
+                amount: share,
+            });
+        }
+        if amount > 0 {
+            evaluation.total_discount += amount;
+            evaluation.applied.push(AppliedPromotion {
+                promotion_id: promotion.id,
+                name: promotion.name.clone(),
+                amount,
+                explanation,
+            });
+        }
+    }
+    evaluation
+}
+
+/// 计算单条规则在 `open` 行上的逐行优惠，规则不成立时返回 `None`。
+/// 返回的金额可能超过行剩余应付，由调用方截断。
+fn apply_rule(
+    rule: &PromotionRule,
+    lines: &[PromotionLine],
+    remaining: &[i64],
+    open: &[usize],
+    ctx: &EvalContext,
+) -> Option<(Vec<(usize, i64)>, String)> {
+    match rule {
+        PromotionRule::TieredSpend { scope, tiers } => {
+            let eligible: Vec<usize> = open
+                .iter()
+                .copied()
+                .filter(|&i| scope.matches(lines[i].category_id, lines[i].product_id, lines[i].sku_id))
+                .collect();
+            let weights: Vec<i64> = eligible.iter().map(|&i| remaining[i]).collect();
+            let subtotal: i64 = weights.iter().sum();
+            let tier = tiers
+                .iter()
+                .filter(|tier| tier.threshold <= subtotal && tier.off > 0)
+                .max_by_key(|tier| tier.threshold)?;
+            let off = tier.off.min(subtotal);
+            let shares = eligible.into_iter().zip(allocate(off, &weights)).collect();
+            Some((shares, format!("满 {} 减 {}", yuan(tier.threshold), yuan(tier.off))))
+        }
+        PromotionRule::BuyNGetM { scope, buy, free } => {
+            let group = u64::from(*buy) + u64::from(*free);
+            if *buy == 0 || *free == 0 {
+                return None;
+            }
+            let mut eligible: Vec<usize> = open
+                .iter()
+                .copied()
+                .filter(|&i| scope.matches(lines[i].category_id, lines[i].product_id, lines[i].sku_id))
+                .collect();
+            let units: u64 = eligible.iter().map(|&i| u64::from(lines[i].quantity)).sum();
+            let free_units = units / group * u64::from(*free);
+            if free_units == 0 {
+                return None;
+            }
+            // 免单的是最便宜的那几件
+            eligible.sort_by_key(|&i| (lines[i].unit_price, i));
+            let mut left = free_units;
+            let mut shares = Vec::new();
+            for i in eligible {
+                if left == 0 {
+                    break;
+                }
+                let taken = left.min(u64::from(lines[i].quantity));
+                left -= taken;
+                shares.push((i, lines[i].unit_price * taken as i64));
+            }
+            Some((shares, format!("买 {} 送 {}，{} 件免单", buy, free, free_units)))
+        }
+        PromotionRule::Bundle { sku_ids, price } => {
+            let picked: Vec<usize> = sku_ids
+                .iter()
+                .map(|sku_id| open.iter().copied().find(|&i| lines[i].sku_id == *sku_id))
+                .collect::<Option<_>>()?;
+            let sets = picked.iter().map(|&i| lines[i].quantity).min().filter(|&sets| sets > 0)?;
+            let sets = i64::from(sets);
+            let set_price: i64 = picked.iter().map(|&i| lines[i].unit_price).sum();
+            if set_price <= *price {
+                return None;
+            }
+            // 只有成组的件数参与分摊
+            let weights: Vec<i64> = picked.iter().map(|&i| (lines[i].unit_price * sets).min(remaining[i])).collect();
+            let off = ((set_price - price) * sets).min(weights.iter().sum());
+            let shares = picked.into_iter().zip(allocate(off, &weights)).collect();
+            Some((shares, format!("组合价 {}，共 {} 组", yuan(*price), sets)))
+        }
+        PromotionRule::MemberPrice { min_level, prices } => {
+            if ctx.member_level < *min_level {
+                return None;
+            }
+            let shares: Vec<(usize, i64)> = open
+                .iter()
+                .filter_map(|&i| {
+                    let price = prices.iter().find(|price| price.sku_id == lines[i].sku_id)?;
+                    let off = (lines[i].unit_price - price.price).max(0) * i64::from(lines[i].quantity);
+                    Some((i, off))
+                })
+                .collect();
+            Some((shares, format!("{} 级及以上会员价", min_level)))
+        }
+        PromotionRule::LimitedTime { scope, percent_off } => {
+            let percent = i64::from(*percent_off).min(100);
+            let shares: Vec<(usize, i64)> = open
+                .iter()
+                .copied()
+                .filter(|&i| scope.matches(lines[i].category_id, lines[i].product_id, lines[i].sku_id))
+                .map(|i| (i, remaining[i] * percent / 100))
+                .collect();
+            Some((shares, format!("限时减 {}%", percent_off)))
+        }
+    }
+}
+
+/// 分转为「元」的展示文本
+fn yuan(cents: i64) -> String {
+    format!("{}.{:02}", cents / 100, (cents % 100).abs())
+}
+
+pub trait PromotionService: Send + Sync {
+    /// 当前进行中的活动
+    fn list_active(&self) -> BoxFuture<'_, Result<Vec<Promotion>, ServiceError>>;
+    fn create<'a>(&'a self, promotion: &'a NewPromotion) -> BoxFuture<'a, Result<Promotion, ServiceError>>;
+    fn list(&self, page: u32, page_size: u32) -> BoxFuture<'_, Result<Vec<Promotion>, ServiceError>>;
+    /// 下线活动，之后的结算不再计算该活动
+    fn disable(&self, id: u64) -> BoxFuture<'_, Result<Promotion, ServiceError>>;
+}
+
+pub struct PromotionServiceImpl<R: PromotionRepo + 'static> {
+    repo: Arc<R>,
+}
+
+impl<R: PromotionRepo + 'static> PromotionServiceImpl<R> {
+    pub fn new(repo: Arc<R>) -> Self {
+        Self { repo }
+    }
+
+    async fn load(&self, id: u64) -> Result<Promotion, ServiceError> {
+        self.repo
+            .find(id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Promotion with ID {} not found", id)))
+    }
+}
+
+impl<R: PromotionRepo + 'static> PromotionService for PromotionServiceImpl<R> {
+    fn list_active(&self) -> BoxFuture<'_, Result<Vec<Promotion>, ServiceError>> {
+        Box::pin(async move { Ok(self.repo.list_active(Local::now()).await?) })
+    }
+
+    fn create<'a>(&'a self, promotion: &'a NewPromotion) -> BoxFuture<'a, Result<Promotion, ServiceError>> {
+        Box::pin(async move {
+            promotion.validate().map_err(ServiceError::BadRequest)?;
+            let id = self.repo.create(promotion).await?;
+            self.load(id).await
+        })
+    }
+
+    fn list(&self, page: u32, page_size: u32) -> BoxFuture<'_, Result<Vec<Promotion>, ServiceError>> {
+        Box::pin(async move {
+            let page_size = page_size.clamp(1, 100);
+            let offset = page.saturating_sub(1) * page_size;
+            Ok(self.repo.list(page_size, offset).await?)
+        })
+    }
+
+    fn disable(&self, id: u64) -> BoxFuture<'_, Result<Promotion, ServiceError>> {
+        Box::pin(async move {
+            self.load(id).await?;
+            self.repo.set_status(id, PROMOTION_DISABLED).await?;
+            self.load(id).await
+        })
+    }
+}
+
+pub fn new_promotion_service<R: PromotionRepo + 'static>(repo: Arc<R>) -> Arc<dyn PromotionService> {
+    Arc::new(PromotionServiceImpl::new(repo)) as Arc<dyn PromotionService>
+}
+
+/// 结算时计算进行中的活动，放在优惠券之前，券的门槛按活动后的金额判断
+pub struct PromotionStep {
+    promotions: Vec<Promotion>,
+    ctx: EvalContext,
+}
+
+impl PromotionStep {
+    pub fn new(promotions: Vec<Promotion>, ctx: EvalContext) -> Self {
+        Self { promotions, ctx }
+    }
+}
+
+impl PricingStep for PromotionStep {
+    fn apply(&self, input: &PricingInput, quote: &mut Quote) -> Result<(), ServiceError> {
+        let lines: Vec<PromotionLine> = input
+            .items
+            .iter()
+            .zip(&quote.lines)
+            .map(|(item, line)| PromotionLine {
+                sku_id: item.sku_id,
+                product_id: item.product_id,
+                category_id: item.category_id,
+                unit_price: item.unit_price,
+                quantity: item.quantity,
+                payable: line.payable_amount,
+            })
+            .collect();
+        let evaluation = evaluate(&self.promotions, &lines, &self.ctx);
+        for applied in &evaluation.applied {
+            let shares: Vec<(usize, i64)> = evaluation
+                .allocations
+                .iter()
+                .filter(|allocation| allocation.promotion_id == applied.promotion_id)
+                .map(|allocation| (allocation.line, allocation.amount))
+                .collect();
+            let description = format!("{}：{}", applied.name, applied.explanation);
+            quote.apply_line_discounts(PROMOTION_SOURCE, &description, &shares);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::promotion::{MemberPrice, PROMOTION_ACTIVE, PromotionScope, SpendTier};
+    use crate::service::pricing::{PricingItem, PricingPipeline};
+    use chrono::Duration;
+    use proptest::prelude::*;
+    use sqlx::types::Json;
+
+    fn promotion(id: u64, priority: i32, exclusive: bool, rule: PromotionRule) -> Promotion {
+        let now = Local::now();
+        Promotion {
+            id,
+            name: format!("活动{}", id),
+            priority,
+            exclusive,
+            rule: Json(rule),
+            starts_at: now - Duration::hours(1),
+            ends_at: now + Duration::hours(1),
+            status: PROMOTION_ACTIVE.to_string(),
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    fn line(sku_id: u64, category_id: u64, unit_price: i64, quantity: u32) -> PromotionLine {
+        PromotionLine {
+            sku_id,
+            product_id: sku_id,
+            category_id,
+            unit_price,
+            quantity,
+            payable: unit_price * i64::from(quantity),
+        }
+    }
+
+    fn ctx(member_level: u32) -> EvalContext {
+        EvalContext { now: Local::now(), member_level }
+    }
+
+    fn line_discounts(evaluation: &Evaluation, lines: usize) -> Vec<i64> {
+        let mut discounts = vec![0; lines];
+        for allocation in &evaluation.allocations {
+            discounts[allocation.line] += allocation.amount;
+        }
+        discounts
+    }
+
+    #[test]
+    fn test_tiered_spend_picks_highest_tier() {
+        let rule = PromotionRule::TieredSpend {
+            scope: PromotionScope { category_ids: vec![10], ..Default::default() },
+            tiers: vec![SpendTier { threshold: 10000, off: 1000 }, SpendTier { threshold: 20000, off: 3000 }],
+        };
+        let lines = vec![line(1, 10, 15000, 1), line(2, 10, 5000, 1), line(3, 20, 99900, 1)];
+        let evaluation = evaluate(&[promotion(1, 0, false, rule)], &lines, &ctx(0));
+        assert_eq!(evaluation.total_discount, 3000);
+        assert_eq!(line_discounts(&evaluation, 3), vec![2250, 750, 0]);
+        assert_eq!(evaluation.applied[0].explanation, "满 200.00 减 30.00");
+    }
+
+    #[test]
+    fn test_buy_n_get_m_frees_cheapest_units() {
+        let rule = PromotionRule::BuyNGetM { scope: PromotionScope::default(), buy: 2, free: 1 };
+        let lines = vec![line(1, 0, 3000, 2), line(2, 0, 1000, 2), line(3, 0, 2000, 3)];
+        let evaluation = evaluate(&[promotion(1, 0, false, rule)], &lines, &ctx(0));
+        // 7 件成 2 组，免最便宜的 2 件
+        assert_eq!(line_discounts(&evaluation, 3), vec![0, 2000, 0]);
+        assert_eq!(evaluation.applied[0].explanation, "买 2 送 1，2 件免单");
+    }
+
+    #[test]
+    fn test_bundle_counts_complete_sets() {
+        let rule = PromotionRule::Bundle { sku_ids: vec![1, 2], price: 5000 };
+        let lines = vec![line(1, 0, 4000, 3), line(2, 0, 2000, 2)];
+        let evaluation = evaluate(&[promotion(1, 0, false, rule.clone())], &lines, &ctx(0));
+        // 两组，每组原价 60 元，共减 20 元，按成组部分的金额 8000:4000 分摊
+        assert_eq!(evaluation.total_discount, 2000);
+        assert_eq!(line_discounts(&evaluation, 2), vec![1333, 667]);
+
+        let evaluation = evaluate(&[promotion(1, 0, false, rule)], &lines[..1], &ctx(0));
+        assert!(evaluation.applied.is_empty());
+    }
+
+    #[test]
+    fn test_member_price_requires_level() {
+        let rule = PromotionRule::MemberPrice { min_level: 2, prices: vec![MemberPrice { sku_id: 1, price: 800 }] };
+        let lines = vec![line(1, 0, 1000, 3), line(2, 0, 1000, 1)];
+        let promotions = [promotion(1, 0, false, rule)];
+        assert_eq!(evaluate(&promotions, &lines, &ctx(1)).total_discount, 0);
+        let evaluation = evaluate(&promotions, &lines, &ctx(2));
+        assert_eq!(line_discounts(&evaluation, 2), vec![600, 0]);
+    }
+
+    #[test]
+    fn test_exclusive_and_stacking_follow_priority() {
+        let limited = PromotionRule::LimitedTime {
+            scope: PromotionScope { sku_ids: vec![1], ..Default::default() },
+            percent_off: 10,
+        };
+        let tiered = PromotionRule::TieredSpend {
+            scope: PromotionScope::default(),
+            tiers: vec![SpendTier { threshold: 1000, off: 500 }],
+        };
+        let lines = vec![line(1, 0, 10000, 1), line(2, 0, 5000, 1)];
+
+        // 限时折扣独占且优先级高：满减只作用在第二行
+        let promotions = [promotion(1, 10, true, limited.clone()), promotion(2, 0, false, tiered.clone())];
+        let evaluation = evaluate(&promotions, &lines, &ctx(0));
+        assert_eq!(line_discounts(&evaluation, 2), vec![1000, 500]);
+
+        // 都不独占时叠加，满减按折后金额分摊
+        let promotions = [promotion(1, 10, false, limited.clone()), promotion(2, 0, false, tiered.clone())];
+        let evaluation = evaluate(&promotions, &lines, &ctx(0));
+        assert_eq!(line_discounts(&evaluation, 2), vec![1000 + 321, 179]);
+
+        // 独占的满减优先级低时，跳过已经打折的行
+        let promotions = [promotion(1, 10, false, limited), promotion(2, 0, true, tiered)];
+        let evaluation = evaluate(&promotions, &lines, &ctx(0));
+        assert_eq!(line_discounts(&evaluation, 2), vec![1000, 500]);
+    }
+
+    #[test]
+    fn test_inactive_promotions_are_ignored() {
+        let rule = PromotionRule::LimitedTime { scope: PromotionScope::default(), percent_off: 50 };
+        let mut expired = promotion(1, 0, false, rule.clone());
+        expired.ends_at = Local::now() - Duration::minutes(1);
+        let mut disabled = promotion(2, 0, false, rule);
+        disabled.status = PROMOTION_DISABLED.to_string();
+        assert_eq!(evaluate(&[expired, disabled], &[line(1, 0, 1000, 1)], &ctx(0)).total_discount, 0);
+    }
+
+    #[test]
+    fn test_promotion_step_writes_quote() {
+        let rule = PromotionRule::LimitedTime { scope: PromotionScope::default(), percent_off: 20 };
+        let step = PromotionStep::new(vec![promotion(1, 0, false, rule)], ctx(0));
+        let input = PricingInput {
+            items: vec![PricingItem {
+                sku_id: 1,
+                product_id: 1,
+                category_id: 0,
+                product_name: "p1".to_string(),
+                sku_title: "s1".to_string(),
+                unit_price: 2500,
+                quantity: 2,
+                weight_grams: 0,
+                freight_template_id: None,
+            }],
+        };
+        let quote = PricingPipeline::new(vec![Box::new(step)]).quote(&input).unwrap();
+        assert_eq!(quote.discount_amount, 1000);
+        assert_eq!(quote.payable_amount, 4000);
+        assert_eq!(quote.adjustments[0].description, "活动1：限时减 20%");
+    }
+
+    fn scope_strategy() -> impl Strategy<Value = PromotionScope> {
+        (prop::collection::vec(1..4u64, 0..2), prop::collection::vec(1..6u64, 0..2))
+            .prop_map(|(category_ids, sku_ids)| PromotionScope { category_ids, product_ids: Vec::new(), sku_ids })
+    }
+
+    fn rule_strategy() -> impl Strategy<Value = PromotionRule> {
+        prop_oneof![
+            (scope_strategy(), prop::collection::vec((0..50_000i64, 1..20_000i64), 1..4)).prop_map(|(scope, tiers)| {
+                let tiers = tiers.into_iter().map(|(threshold, off)| SpendTier { threshold, off }).collect();
+                PromotionRule::TieredSpend { scope, tiers }
+            }),
+            (scope_strategy(), 1..4u32, 1..3u32).prop_map(|(scope, buy, free)| PromotionRule::BuyNGetM { scope, buy, free }),
+            (prop::collection::vec(1..6u64, 2..4), 1..30_000i64)
+                .prop_map(|(sku_ids, price)| PromotionRule::Bundle { sku_ids, price }),
+            (0..3u32, prop::collection::vec((1..6u64, 1..20_000i64), 1..4)).prop_map(|(min_level, prices)| {
+                let prices = prices.into_iter().map(|(sku_id, price)| MemberPrice { sku_id, price }).collect();
+                PromotionRule::MemberPrice { min_level, prices }
+            }),
+            (scope_strategy(), 1..100u32).prop_map(|(scope, percent_off)| PromotionRule::LimitedTime { scope, percent_off }),
+        ]
+    }
+
+    fn promotions_strategy() -> impl Strategy<Value = Vec<Promotion>> {
+        prop::collection::vec((-2..3i32, any::<bool>(), rule_strategy()), 0..6).prop_map(|specs| {
+            specs
+                .into_iter()
+                .enumerate()
+                .map(|(i, (priority, exclusive, rule))| promotion(i as u64 + 1, priority, exclusive, rule))
+                .collect()
+        })
+    }
+
+    fn lines_strategy() -> impl Strategy<Value = Vec<PromotionLine>> {
+        prop::collection::vec((1..6u64, 1..4u64, 1..20_000i64, 1..6u32), 0..6).prop_map(|specs| {
+            specs
+                .into_iter()
+                .map(|(sku_id, category_id, unit_price, quantity)| line(sku_id, category_id, unit_price, quantity))
+                .collect()
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_discounts_stay_within_line_payable(
+            promotions in promotions_strategy(),
+            lines in lines_strategy(),
+            level in 0..3u32,
+        ) {
+            let evaluation = evaluate(&promotions, &lines, &ctx(level));
+            for (line, discount) in lines.iter().zip(line_discounts(&evaluation, lines.len())) {
+                prop_assert!(discount >= 0);
+                prop_assert!(discount <= line.payable);
+            }
+            prop_assert!(evaluation.allocations.iter().all(|allocation| allocation.amount > 0));
+        }
+
+        #[test]
+        fn prop_allocations_add_up_to_the_cent(
+            promotions in promotions_strategy(),
+            lines in lines_strategy(),
+            level in 0..3u32,
+        ) {
+            let evaluation = evaluate(&promotions, &lines, &ctx(level));
+            for applied in &evaluation.applied {
+                let allocated: i64 = evaluation
+                    .allocations
+                    .iter()
+                    .filter(|allocation| allocation.promotion_id == applied.promotion_id)
+                    .map(|allocation| allocation.amount)
+                    .sum();
+                prop_assert_eq!(allocated, applied.amount);
+            }
+            let allocated: i64 = evaluation.allocations.iter().map(|allocation| allocation.amount).sum();
+            prop_assert_eq!(allocated, evaluation.total_discount);
+        }
+
+        #[test]
+        fn prop_exclusive_lines_are_not_stacked(
+            promotions in promotions_strategy(),
+            lines in lines_strategy(),
+            level in 0..3u32,
+        ) {
+            let evaluation = evaluate(&promotions, &lines, &ctx(level));
+            for allocation in &evaluation.allocations {
+                let promotion = promotions.iter().find(|p| p.id == allocation.promotion_id).unwrap();
+                if promotion.exclusive {
+                    prop_assert!(evaluation
+                        .allocations
+                        .iter()
+                        .filter(|other| other.line == allocation.line)
+                        .all(|other| other.promotion_id == allocation.promotion_id));
+                }
+            }
+        }
+
+        #[test]
+        fn prop_quote_total_never_negative(
+            promotions in promotions_strategy(),
+            lines in lines_strategy(),
+            level in 0..3u32,
+        ) {
+            let input = PricingInput {
+                items: lines
+                    .iter()
+                    .map(|line| PricingItem {
+                        sku_id: line.sku_id,
+                        product_id: line.product_id,
+                        category_id: line.category_id,
+                        product_name: String::new(),
+                        sku_title: String::new(),
+                        unit_price: line.unit_price,
+                        quantity: line.quantity,
+                        weight_grams: 0,
+                        freight_template_id: None,
+                    })
+                    .collect(),
+            };
+            let expected = evaluate(&promotions, &lines, &ctx(level)).total_discount;
+            let step = PromotionStep::new(promotions, ctx(level));
+            let quote = PricingPipeline::new(vec![Box::new(step)]).quote(&input).unwrap();
+            prop_assert!(quote.payable_amount >= 0);
+            prop_assert!(quote.lines.iter().all(|line| line.payable_amount >= 0));
+            prop_assert_eq!(quote.discount_amount, expected);
+            prop_assert_eq!(quote.adjustments.iter().map(|a| a.amount).sum::<i64>(), expected);
+        }
+    }
+}
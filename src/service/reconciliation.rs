@@ -0,0 +1,413 @@
+//! 微信支付交易账单对账。每天下载前一天的账单，与本地成功的支付单、退款单逐笔核对，
+//! 差异写入 MySQL 供财务在后台查看。账单按北京时间切日，服务器时区需与之一致。
+
+use crate::domain::BoxFuture;
+use crate::domain::jobs::JobQueue;
+use crate::domain::payment::{GatewayError, PayGateway, PaymentRepo};
+use crate::domain::reconciliation::ReconciliationRepo;
+use crate::domain::refund::RefundRepo;
+use crate::models::job::Job;
+use crate::models::payment::Payment;
+use crate::models::reconciliation::{
+    CATEGORY_PAYMENT, CATEGORY_REFUND, DiscrepancyKind, NewDiscrepancy, ReconciliationReport, ReconciliationRun,
+};
+use crate::models::refund::Refund;
+use crate::service::ServiceError;
+use crate::service::jobs::JobHandler;
+use chrono::{DateTime, Days, Local, NaiveDate, TimeZone, Timelike};
+use sha1::{Digest, Sha1};
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::sync::Arc;
+
+pub const BILL_RECONCILE_JOB: &str = "bill_reconcile";
+
+/// 账单明细之后的汇总区以此开头
+const SUMMARY_HEADER: &str = "总交易单数";
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+pub trait ReconciliationService: Send + Sync {
+    /// 下载并核对某个账单日，结果覆盖该日之前的对账记录
+    fn run(&self, bill_date: NaiveDate) -> BoxFuture<'_, Result<ReconciliationReport, ServiceError>>;
+    fn get(&self, bill_date: NaiveDate) -> BoxFuture<'_, Result<ReconciliationReport, ServiceError>>;
+    fn list(&self, page: u32, page_size: u32) -> BoxFuture<'_, Result<Vec<ReconciliationRun>, ServiceError>>;
+}
+
+pub struct ReconciliationServiceImpl<R: ReconciliationRepo + 'static, P: PaymentRepo + 'static, F: RefundRepo + 'static> {
+    repo: Arc<R>,
+    payments: Arc<P>,
+    refunds: Arc<F>,
+    gateway: Arc<dyn PayGateway>,
+}
+
+impl<R: ReconciliationRepo + 'static, P: PaymentRepo + 'static, F: RefundRepo + 'static> ReconciliationServiceImpl<R, P, F> {
+    pub fn new(repo: Arc<R>, payments: Arc<P>, refunds: Arc<F>, gateway: Arc<dyn PayGateway>) -> Self {
+        Self { repo, payments, refunds, gateway }
+    }
+
+    async fn remote_records(&self, bill_date: NaiveDate) -> Result<Vec<BillRecord>, ServiceError> {
+        let Some(bill) = self.gateway.download_trade_bill(bill_date).await? else {
+            return Ok(Vec::new());
+        };
+        let content = decompress(&bill.data).map_err(|e| GatewayError::Verify(format!("invalid trade bill: {}", e)))?;
+        if !sha1_matches(&content, &bill.sha1) {
+            return Err(GatewayError::Verify(format!("trade bill of {} failed hash check", bill_date)).into());
+        }
+        let text = String::from_utf8(content).map_err(|e| GatewayError::Verify(format!("invalid trade bill: {}", e)))?;
+        Ok(parse_trade_bill(&text).map_err(GatewayError::Verify)?)
+    }
+}
+
+impl<R: ReconciliationRepo + 'static, P: PaymentRepo + 'static, F: RefundRepo + 'static> ReconciliationService
+    for ReconciliationServiceImpl<R, P, F>
+{
+    fn run(&self, bill_date: NaiveDate) -> BoxFuture<'_, Result<ReconciliationReport, ServiceError>> {
+        Box::pin(async move {
+            let (start, end) = day_bounds(bill_date)
+                .ok_or_else(|| ServiceError::BadRequest(format!("invalid bill date {}", bill_date)))?;
+            let remote = self.remote_records(bill_date).await?;
+            let payments = self.payments.list_succeeded_between(start, end).await?;
+            let refunds = self.refunds.list_succeeded_between(start, end).await?;
+
+            let discrepancies = compare(&remote, &payments, &refunds);
+            let local_count = (payments.len() + refunds.len()) as u32;
+            self.repo.save(bill_date, remote.len() as u32, local_count, &discrepancies).await?;
+            if discrepancies.is_empty() {
+                tracing::info!("Reconciled {}: {} records match", bill_date, remote.len());
+            } else {
+                tracing::warn!("Reconciled {}: {} discrepancies", bill_date, discrepancies.len());
+            }
+            self.get(bill_date).await
+        })
+    }
+
+    fn get(&self, bill_date: NaiveDate) -> BoxFuture<'_, Result<ReconciliationReport, ServiceError>> {
+        Box::pin(async move {
+            let run = self
+                .repo
+                .find_by_date(bill_date)
+                .await?
+                .ok_or_else(|| ServiceError::NotFound(format!("Reconciliation of {} not found", bill_date)))?;
+            let discrepancies = self.repo.list_discrepancies(run.id).await?;
+            Ok(ReconciliationReport { run, discrepancies })
+        })
+    }
+
+    fn list(&self, page: u32, page_size: u32) -> BoxFuture<'_, Result<Vec<ReconciliationRun>, ServiceError>> {
+        Box::pin(async move {
+            let page_size = page_size.clamp(1, 100);
+            let offset = page.saturating_sub(1) * page_size;
+            Ok(self.repo.list_runs(page_size, offset).await?)
+        })
+    }
+}
+
+pub fn new_reconciliation_service<R, P, F>(
+    repo: Arc<R>,
+    payments: Arc<P>,
+    refunds: Arc<F>,
+    gateway: Arc<dyn PayGateway>,
+) -> Arc<dyn ReconciliationService>
+where
+    R: ReconciliationRepo + 'static,
+    P: PaymentRepo + 'static,
+    F: RefundRepo + 'static,
+{
+    Arc::new(ReconciliationServiceImpl::new(repo, payments, refunds, gateway)) as Arc<dyn ReconciliationService>
+}
+
+/// 每日对账任务：先排好下一天的任务再执行当天对账，失败重试不会打断每日链条
+pub struct BillReconcileJob {
+    service: Arc<dyn ReconciliationService>,
+    jobs: Arc<dyn JobQueue>,
+    run_at_hour: u32,
+}
+
+impl BillReconcileJob {
+    pub fn new(service: Arc<dyn ReconciliationService>, jobs: Arc<dyn JobQueue>, run_at_hour: u32) -> Arc<Self> {
+        Arc::new(Self { service, jobs, run_at_hour })
+    }
+}
+
+impl JobHandler for BillReconcileJob {
+    fn kind(&self) -> &'static str {
+        BILL_RECONCILE_JOB
+    }
+
+    fn handle<'a>(&'a self, job: &'a Job) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let bill_date = job.payload["bill_date"]
+                .as_str()
+                .and_then(|date| date.parse::<NaiveDate>().ok())
+                .ok_or_else(|| ServiceError::BadRequest(format!("invalid payload of job {}", job.id)))?;
+            if let Some(next) = bill_date.checked_add_days(Days::new(1)) {
+                schedule_bill_reconcile(self.jobs.as_ref(), next, self.run_at_hour).await?;
+            }
+            self.service.run(bill_date).await.map(|_| ())
+        })
+    }
+}
+
+/// 在账单日次日 `run_at_hour` 点执行对账；同一账单日重复排期只保留一个任务
+pub async fn schedule_bill_reconcile(jobs: &dyn JobQueue, bill_date: NaiveDate, run_at_hour: u32) -> Result<(), ServiceError> {
+    let run_at = bill_date
+        .checked_add_days(Days::new(1))
+        .and_then(|day| day.and_hms_opt(run_at_hour.min(23), 0, 0))
+        .and_then(|time| Local.from_local_datetime(&time).earliest())
+        .ok_or_else(|| ServiceError::BadRequest(format!("invalid bill date {}", bill_date)))?;
+    let job = Job::new(BILL_RECONCILE_JOB, &bill_date.to_string(), serde_json::json!({ "bill_date": bill_date }));
+    Ok(jobs.enqueue(&job, run_at.timestamp_millis()).await?)
+}
+
+/// 启动时应排期的账单日：今天还没到执行时间则是昨天，否则是今天（明天执行）
+pub fn next_bill_date(now: DateTime<Local>, run_at_hour: u32) -> NaiveDate {
+    let today = now.date_naive();
+    if now.hour() < run_at_hour {
+        today.pred_opt().unwrap_or(today)
+    } else {
+        today
+    }
+}
+
+/// 账单日在本地时区的起止时间 [start, end)
+fn day_bounds(bill_date: NaiveDate) -> Option<(DateTime<Local>, DateTime<Local>)> {
+    let start = Local.from_local_datetime(&bill_date.and_hms_opt(0, 0, 0)?).earliest()?;
+    let end = Local.from_local_datetime(&bill_date.checked_add_days(Days::new(1))?.and_hms_opt(0, 0, 0)?).earliest()?;
+    Some((start, end))
+}
+
+/// 账单中的一笔支付或退款；金额单位：分
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BillRecord {
+    pub category: &'static str,
+    /// 支付为商户订单号，退款为商户退款单号
+    pub reference_no: String,
+    pub amount: i64,
+}
+
+/// 账单下载时选择了 GZIP，按文件头判断是否需要解压
+pub fn decompress(data: &[u8]) -> std::io::Result<Vec<u8>> {
+    if !data.starts_with(&GZIP_MAGIC) {
+        return Ok(data.to_vec());
+    }
+    let mut content = Vec::new();
+    flate2::read::GzDecoder::new(data).read_to_end(&mut content)?;
+    Ok(content)
+}
+
+/// 微信给出的摘要是解压后原始账单的 SHA1
+fn sha1_matches(content: &[u8], expected: &str) -> bool {
+    let digest: String = Sha1::digest(content).iter().map(|byte| format!("{:02x}", byte)).collect();
+    digest.eq_ignore_ascii_case(expected.trim())
+}
+
+/// 解析全部交易类型（ALL）的交易账单。每个字段前有一个反引号，明细之后是汇总区。
+/// 只保留 SUCCESS（支付）和 REFUND（退款）两类记录。
+pub fn parse_trade_bill(text: &str) -> Result<Vec<BillRecord>, String> {
+    let text = text.trim_start_matches('\u{feff}');
+    let detail = match text.find(SUMMARY_HEADER) {
+        Some(index) => &text[..index],
+        None => text,
+    };
+    let mut reader = csv::ReaderBuilder::new().from_reader(detail.as_bytes());
+    let headers = reader.headers().map_err(|e| e.to_string())?.clone();
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|header| header.trim() == name)
+            .ok_or_else(|| format!("missing column {}", name))
+    };
+    let trade_state = column("交易状态")?;
+    let out_trade_no = column("商户订单号")?;
+    let total = column("订单金额")?;
+    let out_refund_no = column("商户退款单号")?;
+    let refund = column("申请退款金额")?;
+
+    let mut records = Vec::new();
+    for (line, row) in reader.records().enumerate() {
+        let row = row.map_err(|e| e.to_string())?;
+        let field = |index: usize| row.get(index).map(|value| value.trim().trim_start_matches('`')).unwrap_or_default();
+        let (category, reference_no, amount) = match field(trade_state) {
+            "SUCCESS" => (CATEGORY_PAYMENT, field(out_trade_no), field(total)),
+            "REFUND" => (CATEGORY_REFUND, field(out_refund_no), field(refund)),
+            _ => continue,
+        };
+        let amount = parse_yuan(amount).ok_or_else(|| format!("invalid amount {:?} on row {}", amount, line + 1))?;
+        records.push(BillRecord { category, reference_no: reference_no.to_string(), amount });
+    }
+    Ok(records)
+}
+
+/// "12.30" -> 1230，不经过浮点数
+pub fn parse_yuan(value: &str) -> Option<i64> {
+    let (yuan, fen) = value.split_once('.').unwrap_or((value, ""));
+    if yuan.is_empty() || fen.len() > 2 || !yuan.bytes().chain(fen.bytes()).all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let fen = format!("{:0<2}", fen).parse::<i64>().ok()?;
+    yuan.parse::<i64>().ok()?.checked_mul(100)?.checked_add(fen)
+}
+
+/// 按 (类别, 单号) 逐笔核对，结果按类别和单号排序
+pub fn compare(remote: &[BillRecord], payments: &[Payment], refunds: &[Refund]) -> Vec<NewDiscrepancy> {
+    let remote: BTreeMap<(&'static str, &str), i64> = remote
+        .iter()
+        .map(|record| ((record.category, record.reference_no.as_str()), record.amount))
+        .collect();
+    let mut local: BTreeMap<(&'static str, &str), i64> = BTreeMap::new();
+    for payment in payments {
+        local.insert((CATEGORY_PAYMENT, payment.out_trade_no.as_str()), payment.amount);
+    }
+    for refund in refunds {
+        local.insert((CATEGORY_REFUND, refund.refund_no.as_str()), refund.amount);
+    }
+
+    let mut keys: Vec<(&'static str, &str)> = remote.keys().chain(local.keys()).copied().collect();
+    keys.sort_unstable();
+    keys.dedup();
+
+    let mut discrepancies = Vec::new();
+    for (category, reference_no) in keys {
+        let local_amount = local.get(&(category, reference_no)).copied();
+        let remote_amount = remote.get(&(category, reference_no)).copied();
+        let kind = match (local_amount, remote_amount) {
+            (None, _) => DiscrepancyKind::MissingLocal,
+            (_, None) => DiscrepancyKind::MissingRemote,
+            (Some(a), Some(b)) if a != b => DiscrepancyKind::AmountMismatch,
+            _ => continue,
+        };
+        discrepancies.push(NewDiscrepancy {
+            category,
+            kind,
+            reference_no: reference_no.to_string(),
+            local_amount,
+            remote_amount,
+        });
+    }
+    discrepancies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BILL_CSV: &[u8] = include_bytes!("../../testdata/wechat_tradebill_all.csv");
+    const BILL_GZIP: &[u8] = include_bytes!("../../testdata/wechat_tradebill_all.csv.gz");
+
+    fn payment(out_trade_no: &str, amount: i64) -> Payment {
+        Payment {
+            id: 1,
+            order_id: 1,
+            user_id: 1,
+            channel: "wechat_jsapi".to_string(),
+            out_trade_no: out_trade_no.to_string(),
+            amount,
+            status: "succeeded".to_string(),
+            prepay_id: None,
+            transaction_id: None,
+            paid_at: None,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    fn refund(refund_no: &str, amount: i64) -> Refund {
+        Refund {
+            id: 1,
+            refund_no: refund_no.to_string(),
+            order_id: 1,
+            order_item_id: None,
+            user_id: 1,
+            amount,
+            reason: String::new(),
+            status: "succeeded".to_string(),
+            order_status: "paid".to_string(),
+            restock: false,
+            admin_id: None,
+            reject_reason: None,
+            channel_refund_id: None,
+            refunded_at: None,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    fn record(category: &'static str, reference_no: &str, amount: i64) -> BillRecord {
+        BillRecord { category, reference_no: reference_no.to_string(), amount }
+    }
+
+    #[test]
+    fn test_parse_trade_bill_fixture() {
+        let records = parse_trade_bill(std::str::from_utf8(BILL_CSV).unwrap()).unwrap();
+        assert_eq!(
+            records,
+            vec![
+                record(CATEGORY_PAYMENT, "2024010109300000000001", 990),
+                record(CATEGORY_PAYMENT, "2024010110150000000002", 12000),
+                record(CATEGORY_PAYMENT, "2024010118000000000003", 500),
+                record(CATEGORY_REFUND, "R2024010120440000000001", 300),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decompress_gzip_fixture() {
+        let content = decompress(BILL_GZIP).unwrap();
+        assert_eq!(content, BILL_CSV);
+        assert_eq!(decompress(BILL_CSV).unwrap(), BILL_CSV);
+        let digest: String = Sha1::digest(BILL_CSV).iter().map(|byte| format!("{:02X}", byte)).collect();
+        assert!(sha1_matches(&content, &digest));
+        assert!(!sha1_matches(&content, "0000"));
+    }
+
+    #[test]
+    fn test_parse_rejects_missing_column() {
+        assert!(parse_trade_bill("交易时间,商户订单号\n`2024-01-01,`NO1\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_yuan() {
+        assert_eq!(parse_yuan("9.90"), Some(990));
+        assert_eq!(parse_yuan("120"), Some(12000));
+        assert_eq!(parse_yuan("0.5"), Some(50));
+        assert_eq!(parse_yuan("1.234"), None);
+        assert_eq!(parse_yuan("-1.00"), None);
+        assert_eq!(parse_yuan(""), None);
+    }
+
+    #[test]
+    fn test_compare_reports_each_kind() {
+        let remote = vec![
+            record(CATEGORY_PAYMENT, "NO1", 990),
+            record(CATEGORY_PAYMENT, "NO2", 2000),
+            record(CATEGORY_PAYMENT, "NO4", 500),
+            record(CATEGORY_REFUND, "R1", 300),
+        ];
+        let payments = vec![payment("NO1", 990), payment("NO2", 1990), payment("NO3", 100)];
+        let refunds = vec![refund("R1", 300)];
+
+        let discrepancy = |kind, reference_no: &str, local_amount, remote_amount| NewDiscrepancy {
+            category: CATEGORY_PAYMENT,
+            kind,
+            reference_no: reference_no.to_string(),
+            local_amount,
+            remote_amount,
+        };
+        assert_eq!(
+            compare(&remote, &payments, &refunds),
+            vec![
+                discrepancy(DiscrepancyKind::AmountMismatch, "NO2", Some(1990), Some(2000)),
+                discrepancy(DiscrepancyKind::MissingRemote, "NO3", Some(100), None),
+                discrepancy(DiscrepancyKind::MissingLocal, "NO4", None, Some(500)),
+            ]
+        );
+        assert!(compare(&remote[..1], &payments[..1], &[]).is_empty());
+    }
+
+    #[test]
+    fn test_next_bill_date() {
+        let at = |hour| Local.with_ymd_and_hms(2024, 1, 2, hour, 30, 0).unwrap();
+        assert_eq!(next_bill_date(at(9), 10), NaiveDate::from_ymd_opt(2024, 1, 1).unwrap());
+        assert_eq!(next_bill_date(at(10), 10), NaiveDate::from_ymd_opt(2024, 1, 2).unwrap());
+    }
+}
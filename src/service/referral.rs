@@ -0,0 +1,456 @@
+use crate::domain::BoxFuture;
+use crate::domain::after_sale::AfterSaleRepo;
+use crate::domain::jobs::JobQueue;
+use crate::domain::order::OrderRepo;
+use crate::domain::referral::ReferralRepo;
+use crate::domain::refund::RefundRepo;
+use crate::models::event::{DomainEvent, ORDER_EVENT_PREFIX, OrderStatusChanged};
+use crate::models::job::Job;
+use crate::models::order::{Order, OrderStatus};
+use crate::models::referral::{Commission, CommissionStatus, MyCommissions, NewCommission, ReferralBinding, ShareInfo};
+use crate::models::refund::RefundStatus;
+use crate::service::ServiceError;
+use crate::service::events::EventSubscriber;
+use crate::service::jobs::JobHandler;
+use chrono::{DateTime, Days, Local, NaiveDate, TimeZone, Timelike};
+use rand::Rng;
+use std::sync::Arc;
+use wx_shop::ReferralSettings;
+
+/// 每日佣金结算任务
+pub const COMMISSION_SETTLE_JOB: &str = "commission_settle";
+/// 推广码字符集，去掉了容易混淆的 0/O、1/I
+const CODE_CHARS: &[u8] = b"23456789ABCDEFGHJKLMNPQRSTUVWXYZ";
+const CODE_LEN: usize = 8;
+/// 检查绑定是否成环时最多向上追溯的层数
+const MAX_UPLINE: usize = 16;
+/// 批量结算时每批的订单数
+const BATCH_SIZE: u32 = 200;
+
+pub trait ReferralService: Send + Sync {
+    /// 自己的推广码和分享路径，首次调用时生成推广码
+    fn share_info(&self, user_id: u32) -> BoxFuture<'_, Result<ShareInfo, ServiceError>>;
+    /// 通过推广码绑定推广人；已有未过期的归属时保持不变并返回原归属
+    fn bind<'a>(&'a self, user_id: u32, code: &'a str) -> BoxFuture<'a, Result<ReferralBinding, ServiceError>>;
+    /// 订单支付后按买家当前的推广关系逐级记入待结算佣金；订单已退款或关闭时不记
+    fn record_order(&self, order_id: u64, paid_at: DateTime<Local>) -> BoxFuture<'_, Result<(), ServiceError>>;
+    /// 订单完成后，售后期满的时间点即为佣金结算时间
+    fn schedule_settle(&self, order_id: u64, completed_at: DateTime<Local>) -> BoxFuture<'_, Result<(), ServiceError>>;
+    /// 结算到期的佣金，返回转为可提现的订单数；仍有售后单未结束的订单留到下次
+    fn settle_due(&self, now: DateTime<Local>) -> BoxFuture<'_, Result<usize, ServiceError>>;
+    /// 订单退款后冲回佣金
+    fn reverse_order(&self, order_id: u64) -> BoxFuture<'_, Result<(), ServiceError>>;
+    fn my_commissions(&self, user_id: u32, page: u32, page_size: u32) -> BoxFuture<'_, Result<MyCommissions, ServiceError>>;
+    fn list_by_status(&self, status: CommissionStatus, page: u32, page_size: u32) -> BoxFuture<'_, Result<Vec<Commission>, ServiceError>>;
+}
+
+pub struct ReferralServiceImpl<R, O, F, A>
+where
+    R: ReferralRepo + 'static,
+    O: OrderRepo + 'static,
+    F: RefundRepo + 'static,
+    A: AfterSaleRepo + 'static,
+{
+    repo: Arc<R>,
+    orders: Arc<O>,
+    refunds: Arc<F>,
+    after_sales: Arc<A>,
+    settings: ReferralSettings,
+}
+
+impl<R, O, F, A> ReferralServiceImpl<R, O, F, A>
+where
+    R: ReferralRepo + 'static,
+    O: OrderRepo + 'static,
+    F: RefundRepo + 'static,
+    A: AfterSaleRepo + 'static,
+{
+    pub fn new(repo: Arc<R>, orders: Arc<O>, refunds: Arc<F>, after_sales: Arc<A>, settings: ReferralSettings) -> Self {
+        Self { repo, orders, refunds, after_sales, settings }
+    }
+
+    async fn load_order(&self, order_id: u64) -> Result<Order, ServiceError> {
+        self.orders
+            .find_by_id(order_id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Order with ID {} not found", order_id)))
+    }
+
+    /// 从 `user_id` 的推广人开始逐级向上，只沿未过期的归属追溯，遇到环时停止
+    async fn upline(&self, user_id: u32, now: DateTime<Local>, max: usize) -> Result<Vec<u32>, ServiceError> {
+        let mut chain = Vec::new();
+        let mut current = user_id;
+        while chain.len() < max {
+            let Some(binding) = self.repo.find_binding(current).await?.filter(|binding| binding.is_active(now)) else {
+                break;
+            };
+            if binding.referrer_id == user_id || chain.contains(&binding.referrer_id) {
+                break;
+            }
+            chain.push(binding.referrer_id);
+            current = binding.referrer_id;
+        }
+        Ok(chain)
+    }
+
+    async fn has_open_after_sales(&self, order_id: u64) -> Result<bool, ServiceError> {
+        for item in self.orders.find_items(order_id).await? {
+            if self.after_sales.find_open_by_item(item.id).await?.is_some() {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// 结算一个订单的佣金，返回是否已结算
+    async fn settle_order(&self, order_id: u64, now: DateTime<Local>) -> Result<bool, ServiceError> {
+        let order = self.load_order(order_id).await?;
+        let status = order.status().map_err(ServiceError::Conflict)?;
+        if matches!(status, OrderStatus::Refunded | OrderStatus::Closed) {
+            self.repo.reverse(order_id).await?;
+            return Ok(false);
+        }
+        if self.has_open_after_sales(order_id).await? {
+            return Ok(false);
+        }
+        let refunded: i64 = self
+            .refunds
+            .list_by_order(order_id)
+            .await?
+            .iter()
+            .filter(|refund| refund.is(RefundStatus::Succeeded))
+            .map(|refund| refund.amount)
+            .sum();
+        Ok(self.repo.settle(order_id, commission_base(&order, refunded), now).await? > 0)
+    }
+}
+
+impl<R, O, F, A> ReferralService for ReferralServiceImpl<R, O, F, A>
+where
+    R: ReferralRepo + 'static,
+    O: OrderRepo + 'static,
+    F: RefundRepo + 'static,
+    A: AfterSaleRepo + 'static,
+{
+    fn share_info(&self, user_id: u32) -> BoxFuture<'_, Result<ShareInfo, ServiceError>> {
+        Box::pin(async move {
+            let mut attempts = 0;
+            let code = loop {
+                if let Some(existing) = self.repo.find_code_by_user(user_id).await? {
+                    break existing.code;
+                }
+                // 生成的推广码碰巧重复时换一个重试
+                attempts += 1;
+                if attempts > 5 {
+                    return Err(ServiceError::Conflict("failed to allocate a referral code".to_string()));
+                }
+                self.repo.create_code(user_id, &generate_referral_code()).await?;
+            };
+            let share_path = self.settings.share_path.replace("{code}", &code);
+            Ok(ShareInfo { code, share_path })
+        })
+    }
+
+    fn bind<'a>(&'a self, user_id: u32, code: &'a str) -> BoxFuture<'a, Result<ReferralBinding, ServiceError>> {
+        Box::pin(async move {
+            let code = code.trim().to_ascii_uppercase();
+            let referrer = self
+                .repo
+                .find_code(&code)
+                .await?
+                .ok_or_else(|| ServiceError::NotFound(format!("Referral code {} not found", code)))?;
+            if referrer.user_id == user_id {
+                return Err(ServiceError::BadRequest("cannot bind your own referral code".to_string()));
+            }
+            let now = Local::now();
+            if let Some(binding) = self.repo.find_binding(user_id).await?.filter(|binding| binding.is_active(now)) {
+                return Ok(binding);
+            }
+            if self.settings.new_customers_only && self.repo.has_paid_orders(user_id).await? {
+                return Err(ServiceError::Conflict("only new customers can be referred".to_string()));
+            }
+            if self.upline(referrer.user_id, now, MAX_UPLINE).await?.contains(&user_id) {
+                return Err(ServiceError::Conflict(format!("user {} is already upline of the referrer", user_id)));
+            }
+
+            let expires_at = now
+                .checked_add_days(Days::new(u64::from(self.settings.attribution_days)))
+                .unwrap_or(now);
+            self.repo.bind(user_id, referrer.user_id, &code, now, expires_at).await?;
+            // 并发绑定时以先写入的为准
+            self.repo
+                .find_binding(user_id)
+                .await?
+                .ok_or_else(|| ServiceError::Conflict(format!("referral binding of user {} changed concurrently", user_id)))
+        })
+    }
+
+    fn record_order(&self, order_id: u64, paid_at: DateTime<Local>) -> BoxFuture<'_, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let rates = &self.settings.level_rates_bp;
+            if rates.is_empty() {
+                return Ok(());
+            }
+            let order = self.load_order(order_id).await?;
+            // 事件不保证顺序，退款事件可能先于支付事件处理，此时不再记佣
+            let status = order.status().map_err(ServiceError::Conflict)?;
+            if matches!(status, OrderStatus::Refunded | OrderStatus::Closed) {
+                tracing::info!("Skipping commissions of order {} in status {}", order_id, order.status);
+                return Ok(());
+            }
+            let upline = self.upline(order.user_id, paid_at, rates.len()).await?;
+            let commissions = build_commissions(&order, &upline, rates);
+            if !commissions.is_empty() {
+                self.repo.add_commissions(&commissions).await?;
+            }
+            Ok(())
+        })
+    }
+
+    fn schedule_settle(&self, order_id: u64, completed_at: DateTime<Local>) -> BoxFuture<'_, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let settle_at = completed_at
+                .checked_add_days(Days::new(u64::from(self.settings.settle_after_days)))
+                .unwrap_or(completed_at);
+            self.repo.schedule_settle(order_id, settle_at).await?;
+            Ok(())
+        })
+    }
+
+    fn settle_due(&self, now: DateTime<Local>) -> BoxFuture<'_, Result<usize, ServiceError>> {
+        Box::pin(async move {
+            let mut settled = 0;
+            let mut after = 0;
+            loop {
+                let order_ids = self.repo.due_orders(now, after, BATCH_SIZE).await?;
+                let Some(&last) = order_ids.last() else { break };
+                for order_id in order_ids {
+                    if self.settle_order(order_id, now).await? {
+                        settled += 1;
+                    }
+                }
+                after = last;
+            }
+            Ok(settled)
+        })
+    }
+
+    fn reverse_order(&self, order_id: u64) -> BoxFuture<'_, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let reversed = self.repo.reverse(order_id).await?;
+            if reversed > 0 {
+                tracing::info!("Reversed {} commissions of order {}", reversed, order_id);
+            }
+            Ok(())
+        })
+    }
+
+    fn my_commissions(&self, user_id: u32, page: u32, page_size: u32) -> BoxFuture<'_, Result<MyCommissions, ServiceError>> {
+        Box::pin(async move {
+            let page_size = page_size.clamp(1, 50);
+            let offset = page.saturating_sub(1) * page_size;
+            let mut mine = MyCommissions {
+                invited_count: self.repo.count_invited(user_id, Local::now()).await?,
+                items: self.repo.list_by_beneficiary(user_id, page_size, offset).await?,
+                ..MyCommissions::default()
+            };
+            for (status, amount) in self.repo.totals(user_id).await? {
+                match status.parse() {
+                    Ok(CommissionStatus::Pending) => mine.pending = amount,
+                    Ok(CommissionStatus::Withdrawable) => mine.withdrawable = amount,
+                    Ok(CommissionStatus::Reversed) => mine.reversed = amount,
+                    Err(e) => tracing::warn!("Ignoring commissions of user {}: {}", user_id, e),
+                }
+            }
+            Ok(mine)
+        })
+    }
+
+    fn list_by_status(&self, status: CommissionStatus, page: u32, page_size: u32) -> BoxFuture<'_, Result<Vec<Commission>, ServiceError>> {
+        Box::pin(async move {
+            let page_size = page_size.clamp(1, 100);
+            let offset = page.saturating_sub(1) * page_size;
+            Ok(self.repo.list_by_status(status, page_size, offset).await?)
+        })
+    }
+}
+
+/// 计佣金额：商品实付（不含运费）扣除已退款金额
+fn commission_base(order: &Order, refunded: i64) -> i64 {
+    (order.payable_amount - order.shipping_fee - refunded).max(0)
+}
+
+/// 第 i 个上级拿第 i 层的比例，比例为 0 的层级不记佣金
+fn build_commissions(order: &Order, upline: &[u32], rates_bp: &[u32]) -> Vec<NewCommission> {
+    let base_amount = commission_base(order, 0);
+    upline
+        .iter()
+        .zip(rates_bp)
+        .enumerate()
+        .filter(|(_, (_, rate_bp))| **rate_bp > 0)
+        .map(|(index, (&beneficiary_id, &rate_bp))| NewCommission {
+            order_id: order.id,
+            order_no: order.order_no.clone(),
+            buyer_id: order.user_id,
+            beneficiary_id,
+            level: (index + 1) as u8,
+            base_amount,
+            rate_bp,
+            amount: base_amount * i64::from(rate_bp) / 10_000,
+        })
+        .collect()
+}
+
+fn generate_referral_code() -> String {
+    let mut rng = rand::rng();
+    (0..CODE_LEN)
+        .map(|_| char::from(CODE_CHARS[rng.random_range(0..CODE_CHARS.len())]))
+        .collect()
+}
+
+/// 订单支付记佣、完成后确定结算时间、退款冲回；重复投递时由唯一约束和状态条件保证只生效一次
+pub struct ReferralSubscriber {
+    service: Arc<dyn ReferralService>,
+}
+
+impl ReferralSubscriber {
+    pub fn new(service: Arc<dyn ReferralService>) -> Arc<Self> {
+        Arc::new(Self { service })
+    }
+}
+
+impl EventSubscriber for ReferralSubscriber {
+    fn name(&self) -> &'static str {
+        "referral"
+    }
+
+    fn handles(&self, event_type: &str) -> bool {
+        [OrderStatus::Paid, OrderStatus::Completed, OrderStatus::Refunded]
+            .iter()
+            .any(|status| event_type.strip_prefix(ORDER_EVENT_PREFIX) == Some(status.as_str()))
+    }
+
+    fn handle<'a>(&'a self, event: &'a DomainEvent) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let changed: OrderStatusChanged = event.decode().map_err(ServiceError::BadRequest)?;
+            if changed.to == OrderStatus::Paid.as_str() {
+                self.service.record_order(changed.order_id, changed.occurred_at).await
+            } else if changed.to == OrderStatus::Completed.as_str() {
+                self.service.schedule_settle(changed.order_id, changed.occurred_at).await
+            } else {
+                self.service.reverse_order(changed.order_id).await
+            }
+        })
+    }
+}
+
+/// 每日佣金结算任务：执行前先排期下一天
+pub struct CommissionSettleJob {
+    service: Arc<dyn ReferralService>,
+    jobs: Arc<dyn JobQueue>,
+    run_at_hour: u32,
+}
+
+impl CommissionSettleJob {
+    pub fn new(service: Arc<dyn ReferralService>, jobs: Arc<dyn JobQueue>, run_at_hour: u32) -> Arc<Self> {
+        Arc::new(Self { service, jobs, run_at_hour })
+    }
+}
+
+impl JobHandler for CommissionSettleJob {
+    fn kind(&self) -> &'static str {
+        COMMISSION_SETTLE_JOB
+    }
+
+    fn handle<'a>(&'a self, job: &'a Job) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let run_date = job.payload["run_date"]
+                .as_str()
+                .and_then(|date| date.parse::<NaiveDate>().ok())
+                .ok_or_else(|| ServiceError::BadRequest(format!("invalid payload of job {}", job.id)))?;
+            if let Some(next) = run_date.checked_add_days(Days::new(1)) {
+                schedule_commission_settle(self.jobs.as_ref(), next, self.run_at_hour).await?;
+            }
+            let settled = self.service.settle_due(Local::now()).await?;
+            tracing::info!("Commission settlement of {} settled {} orders", run_date, settled);
+            Ok(())
+        })
+    }
+}
+
+/// 在 `run_date` 的 `run_at_hour` 点执行；同一天重复排期只保留一个任务
+pub async fn schedule_commission_settle(jobs: &dyn JobQueue, run_date: NaiveDate, run_at_hour: u32) -> Result<(), ServiceError> {
+    let run_at = run_date
+        .and_hms_opt(run_at_hour.min(23), 0, 0)
+        .and_then(|time| Local.from_local_datetime(&time).earliest())
+        .ok_or_else(|| ServiceError::BadRequest(format!("invalid run date {}", run_date)))?;
+    let job = Job::new(COMMISSION_SETTLE_JOB, &run_date.to_string(), serde_json::json!({ "run_date": run_date }));
+    Ok(jobs.enqueue(&job, run_at.timestamp_millis()).await?)
+}
+
+/// 启动时应排期的执行日：今天还没到执行时间则是今天，否则是明天
+pub fn next_commission_settle_date(now: DateTime<Local>, run_at_hour: u32) -> NaiveDate {
+    let today = now.date_naive();
+    if now.hour() < run_at_hour {
+        today
+    } else {
+        today.succ_opt().unwrap_or(today)
+    }
+}
+
+pub fn new_referral_service<R, O, F, A>(
+    repo: Arc<R>,
+    orders: Arc<O>,
+    refunds: Arc<F>,
+    after_sales: Arc<A>,
+    settings: ReferralSettings,
+) -> Arc<dyn ReferralService>
+where
+    R: ReferralRepo + 'static,
+    O: OrderRepo + 'static,
+    F: RefundRepo + 'static,
+    A: AfterSaleRepo + 'static,
+{
+    Arc::new(ReferralServiceImpl::new(repo, orders, refunds, after_sales, settings)) as Arc<dyn ReferralService>
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(payable_amount: i64, shipping_fee: i64) -> Order {
+        Order {
+            id: 7,
+            order_no: "20240101000000123".to_string(),
+            user_id: 3,
+            status: OrderStatus::Paid.as_str().to_string(),
+            goods_amount: payable_amount - shipping_fee,
+            discount_amount: 0,
+            shipping_fee,
+            payable_amount,
+            receiver_name: String::new(),
+            receiver_phone: String::new(),
+            province: String::new(),
+            city: String::new(),
+            district: String::new(),
+            address_detail: String::new(),
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn test_commissions_follow_upline_levels() {
+        let order = order(10_800, 800);
+        let commissions = build_commissions(&order, &[11, 12, 13], &[1000, 0]);
+        assert_eq!(commissions.len(), 1);
+        assert_eq!((commissions[0].beneficiary_id, commissions[0].level), (11, 1));
+        assert_eq!((commissions[0].base_amount, commissions[0].amount), (10_000, 1_000));
+
+        let commissions = build_commissions(&order, &[11, 12], &[1000, 333, 100]);
+        assert_eq!(commissions.iter().map(|c| (c.level, c.amount)).collect::<Vec<_>>(), vec![(1, 1_000), (2, 333)]);
+        // 退款超过商品实付时不计佣金
+        assert_eq!(commission_base(&order, 4_000), 6_000);
+        assert_eq!(commission_base(&order, 10_800), 0);
+    }
+}
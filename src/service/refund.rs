@@ -0,0 +1,578 @@
+use crate::domain::BoxFuture;
+use crate::domain::order::OrderRepo;
+use crate::domain::payment::{NotifyHeaders, PayGateway, PaymentRepo, RefundRequest, RefundResult};
+use crate::domain::refund::RefundRepo;
+use crate::models::order::{Actor, Order, OrderItem, OrderStatus};
+use crate::models::payment::{PAYMENT_SUCCEEDED, Payment};
+use crate::models::refund::{NewRefund, Refund, RefundApply, RefundStatus};
+use crate::service::ServiceError;
+use crate::service::audit;
+use crate::service::inventory::InventoryService;
+use crate::service::order_state::OrderStateService;
+use chrono::{DateTime, Local};
+use rand::Rng;
+use std::sync::Arc;
+
+pub trait RefundService: Send + Sync {
+    /// 用户对已支付订单（或其中一行）申请退款，订单进入退款中
+    fn apply<'a>(&'a self, user_id: u32, order_id: u64, apply: &'a RefundApply) -> BoxFuture<'a, Result<Refund, ServiceError>>;
+    fn list_for_order(&self, user_id: u32, order_id: u64) -> BoxFuture<'_, Result<Vec<Refund>, ServiceError>>;
+    fn list_by_status<'a>(&'a self, status: &'a str, page: u32, page_size: u32) -> BoxFuture<'a, Result<Vec<Refund>, ServiceError>>;
+    /// 审核通过并提交微信退款；处理中的退款可以重复提交（渠道按退款单号幂等），
+    /// 已成功的退款重复提交时补做后续处理
+    fn approve(&self, admin_id: u32, refund_id: u64, restock: bool) -> BoxFuture<'_, Result<Refund, ServiceError>>;
+    /// 系统发起整单退款（如拼团失败），不经审核直接提交渠道；重复调用会继续提交进行中的那一笔，
+    /// 已全额退款时返回最后一笔。订单上有只退一部分的退款时先把它走完，再退剩余部分
+    fn refund_order<'a>(&'a self, order_id: u64, reason: &'a str) -> BoxFuture<'a, Result<Refund, ServiceError>>;
+    /// 订单取消后才到账的支付：订单保持已取消，按支付金额原路退回，不再回补库存。
+    /// 退款单号由订单号生成，重复调用只会有一笔退款，返回它的最新状态
+    fn refund_cancelled<'a>(&'a self, order_id: u64, reason: &'a str) -> BoxFuture<'a, Result<Refund, ServiceError>>;
+    /// 售后退货入库后由客服发起的按行退款：不再单独审核，直接置为处理中，
+    /// 调用方随后用 [`RefundService::approve`] 提交渠道（提交失败时可以重复调用）。
+    /// 退回的件数由售后单在退款成功后入库，退款本身不回补库存
+    fn create_approved<'a>(
+        &'a self,
+        admin_id: u32,
+        order_id: u64,
+        order_item_id: u64,
+        amount: i64,
+        reason: &'a str,
+    ) -> BoxFuture<'a, Result<Refund, ServiceError>>;
+    /// 审核拒绝，订单回到申请前的状态
+    fn reject<'a>(&'a self, admin_id: u32, refund_id: u64, reason: &'a str) -> BoxFuture<'a, Result<Refund, ServiceError>>;
+    /// 处理微信退款结果通知，重复通知只生效一次
+    fn handle_wechat_notify<'a>(&'a self, headers: &'a NotifyHeaders, body: &'a str) -> BoxFuture<'a, Result<(), ServiceError>>;
+}
+
+pub struct RefundServiceImpl<R: RefundRepo + 'static, O: OrderRepo + 'static, P: PaymentRepo + 'static> {
+    repo: Arc<R>,
+    orders: Arc<O>,
+    payments: Arc<P>,
+    state: Arc<dyn OrderStateService>,
+    inventory: Arc<dyn InventoryService>,
+    gateway: Arc<dyn PayGateway>,
+}
+
+impl<R: RefundRepo + 'static, O: OrderRepo + 'static, P: PaymentRepo + 'static> RefundServiceImpl<R, O, P> {
+    pub fn new(
+        repo: Arc<R>,
+        orders: Arc<O>,
+        payments: Arc<P>,
+        state: Arc<dyn OrderStateService>,
+        inventory: Arc<dyn InventoryService>,
+        gateway: Arc<dyn PayGateway>,
+    ) -> Self {
+        Self { repo, orders, payments, state, inventory, gateway }
+    }
+
+    async fn load(&self, refund_id: u64) -> Result<Refund, ServiceError> {
+        self.repo
+            .find(refund_id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Refund with ID {} not found", refund_id)))
+    }
+
+    async fn load_order(&self, order_id: u64) -> Result<Order, ServiceError> {
+        self.orders
+            .find_by_id(order_id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Order with ID {} not found", order_id)))
+    }
+
+    async fn load_user_order(&self, user_id: u32, order_id: u64) -> Result<Order, ServiceError> {
+        match self.orders.find_by_id(order_id).await? {
+            Some(order) if order.user_id == user_id => Ok(order),
+            _ => Err(ServiceError::NotFound(format!("Order with ID {} not found", order_id))),
+        }
+    }
+
+    async fn paid_payment(&self, order_id: u64) -> Result<Payment, ServiceError> {
+        self.payments
+            .find_by_order(order_id)
+            .await?
+            .filter(|payment| payment.status == PAYMENT_SUCCEEDED)
+            .ok_or_else(|| ServiceError::Conflict(format!("order {} has no successful payment", order_id)))
+    }
+
+    /// 订单回到申请退款前的状态
+    async fn restore_order(&self, refund: &Refund, actor: Actor) -> Result<(), ServiceError> {
+        let before = refund.order_status.parse::<OrderStatus>().map_err(ServiceError::Conflict)?;
+        let reason = format!("refund {} {}", refund.refund_no, refund.status);
+        self.state.transition(refund.order_id, before, actor, &reason).await?;
+        Ok(())
+    }
+
+    /// 退款成功：先记账，再补做订单侧的后续处理
+    async fn settle_success(&self, refund: &Refund, result: &RefundResult) -> Result<(), ServiceError> {
+        let refunded_at = result
+            .success_time
+            .as_deref()
+            .and_then(|time| DateTime::parse_from_rfc3339(time).ok())
+            .map(|time| time.with_timezone(&Local))
+            .unwrap_or_else(Local::now);
+        // 重复通知时记账不再生效，但上次的后续处理可能没做完，照样往下走
+        self.repo.mark_succeeded(refund.id, &result.refund_id, refunded_at).await?;
+        let refund = self.load(refund.id).await?;
+        if !refund.is(RefundStatus::Succeeded) {
+            return Ok(());
+        }
+        self.complete_success(&refund).await
+    }
+
+    /// 已成功退款的后续处理：按发货情况回补库存，全额退完的订单置为已退款，否则回到原状态。
+    /// 只在订单仍因这笔退款处于退款中时执行，可以重复调用
+    async fn complete_success(&self, refund: &Refund) -> Result<(), ServiceError> {
+        let order = self.load_order(refund.order_id).await?;
+        if order.status().ok() != Some(OrderStatus::Refunding) {
+            return Ok(());
+        }
+        let refunds = self.repo.list_by_order(refund.order_id).await?;
+        if refunds.iter().map(|other| other.id).max() != Some(refund.id) {
+            return Ok(());
+        }
+        let items = self.orders.find_items(refund.order_id).await?;
+        let payment = self.paid_payment(refund.order_id).await?;
+        let fully_refunded = refunded_total(&refunds, None) >= payment.amount;
+
+        // 库存按订单号幂等释放，重复执行不会多补
+        let shipped = refund.order_status != OrderStatus::Paid.as_str();
+        if !shipped || refund.restock {
+            for sku_id in restock_skus(&items, &refunds, refund.order_item_id, fully_refunded) {
+                self.inventory.release(sku_id, &order.order_no).await?;
+            }
+        }
+
+        if fully_refunded {
+            let reason = format!("refund {} succeeded", refund.refund_no);
+            self.state.transition(refund.order_id, OrderStatus::Refunded, Actor::system(), &reason).await?;
+            Ok(())
+        } else {
+            self.restore_order(refund, Actor::system()).await
+        }
+    }
+
+    async fn settle_failure(&self, refund: &Refund) -> Result<(), ServiceError> {
+        if !self.repo.mark_failed(refund.id).await? {
+            return Ok(());
+        }
+        tracing::error!("Refund {} failed at channel, order {} restored", refund.refund_no, refund.order_id);
+        self.restore_order(&self.load(refund.id).await?, Actor::system()).await
+    }
+
+    /// 订单进入退款中并写入一笔待审核的退款；同一订单同时只能有一笔进行中的退款
+    async fn open_refund(
+        &self,
+        order: &Order,
+        order_item_id: Option<u64>,
+        amount: i64,
+        reason: &str,
+        actor: Actor,
+    ) -> Result<Refund, ServiceError> {
+        let from = order.status().map_err(ServiceError::Conflict)?;
+        let outcome = self.state.transition(order.id, OrderStatus::Refunding, actor, reason).await?;
+        if !outcome.is_applied() {
+            return Err(ServiceError::Conflict(format!("order {} already has a refund in progress", order.id)));
+        }
+        let new_refund = NewRefund {
+            refund_no: generate_refund_no(),
+            order_id: order.id,
+            order_item_id,
+            user_id: order.user_id,
+            amount,
+            reason: reason.to_string(),
+            order_status: from.as_str().to_string(),
+        };
+        let id = self.repo.create(&new_refund).await?;
+        self.load(id).await
+    }
+
+    /// 向渠道提交处理中的退款并应用返回结果
+    async fn submit(&self, refund_id: u64) -> Result<Refund, ServiceError> {
+        let refund = self.load(refund_id).await?;
+        let payment = self.paid_payment(refund.order_id).await?;
+        let request = RefundRequest {
+            out_trade_no: payment.out_trade_no.clone(),
+            out_refund_no: refund.refund_no.clone(),
+            reason: refund.reason.clone(),
+            refund: refund.amount,
+            total: payment.amount,
+        };
+        let result = self.gateway.refund(&request).await?;
+        self.apply_result(&refund, &result).await?;
+        self.load(refund_id).await
+    }
+
+    /// 系统发起或接手的退款：待审核的直接通过，再提交渠道
+    async fn submit_as_system(&self, refund: &Refund) -> Result<Refund, ServiceError> {
+        // 系统发起的退款没有审核人，与 Actor::system() 一致记为 0；商品未发出，退回库存
+        if refund.is(RefundStatus::Pending) && !self.repo.approve(refund.id, Actor::system().id, true).await? {
+            return Err(ServiceError::Conflict(format!("refund {} changed concurrently", refund.id)));
+        }
+        self.submit(refund.id).await
+    }
+
+    async fn apply_result(&self, refund: &Refund, result: &RefundResult) -> Result<(), ServiceError> {
+        if result.amount.refund != refund.amount {
+            return Err(ServiceError::Conflict(format!(
+                "refund {} amount mismatch: expected {}, channel {}",
+                refund.refund_no, refund.amount, result.amount.refund
+            )));
+        }
+        if result.is_success() {
+            self.settle_success(refund, result).await
+        } else if result.is_failed() {
+            self.settle_failure(refund).await
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<R: RefundRepo + 'static, O: OrderRepo + 'static, P: PaymentRepo + 'static> RefundService for RefundServiceImpl<R, O, P> {
+    fn apply<'a>(&'a self, user_id: u32, order_id: u64, apply: &'a RefundApply) -> BoxFuture<'a, Result<Refund, ServiceError>> {
+        Box::pin(async move {
+            let order = self.load_user_order(user_id, order_id).await?;
+            let from = order.status().map_err(ServiceError::Conflict)?;
+            if !from.can_transition_to(OrderStatus::Refunding) {
+                return Err(ServiceError::Conflict(format!("order {} cannot be refunded in status {}", order_id, from)));
+            }
+            let payment = self.paid_payment(order_id).await?;
+            let items = self.orders.find_items(order_id).await?;
+            let refunds = self.repo.list_by_order(order_id).await?;
+            let line = match apply.order_item_id {
+                Some(item_id) => Some(
+                    items
+                        .iter()
+                        .find(|item| item.id == item_id)
+                        .ok_or_else(|| ServiceError::NotFound(format!("Order item {} not found", item_id)))?,
+                ),
+                None => None,
+            };
+            check_refundable(apply.amount, payment.amount, line, &refunds)?;
+
+            // 订单进入退款中；同一订单同时只能有一笔进行中的退款
+            let reason = apply.reason.trim();
+            let outcome = self.state.transition(order_id, OrderStatus::Refunding, Actor::user(user_id), reason).await?;
+            if !outcome.is_applied() {
+                return Err(ServiceError::Conflict(format!("order {} already has a refund in progress", order_id)));
+            }
+
+            let new_refund = NewRefund {
+                refund_no: generate_refund_no(),
+                order_id,
+                order_item_id: apply.order_item_id,
+                user_id,
+                amount: apply.amount,
+                reason: reason.to_string(),
+                order_status: from.as_str().to_string(),
+            };
+            match self.repo.create(&new_refund).await {
+                Ok(id) => self.load(id).await,
+                Err(e) => {
+                    // 回滚订单状态，避免订单卡在退款中
+                    if let Err(rollback) = self.state.transition(order_id, from, Actor::system(), "refund apply failed").await {
+                        tracing::error!("Failed to restore order {} after refund apply error: {:?}", order_id, rollback);
+                    }
+                    Err(e.into())
+                }
+            }
+        })
+    }
+
+    fn list_for_order(&self, user_id: u32, order_id: u64) -> BoxFuture<'_, Result<Vec<Refund>, ServiceError>> {
+        Box::pin(async move {
+            self.load_user_order(user_id, order_id).await?;
+            Ok(self.repo.list_by_order(order_id).await?)
+        })
+    }
+
+    fn list_by_status<'a>(&'a self, status: &'a str, page: u32, page_size: u32) -> BoxFuture<'a, Result<Vec<Refund>, ServiceError>> {
+        Box::pin(async move {
+            let page_size = page_size.clamp(1, 100);
+            let offset = page.saturating_sub(1) * page_size;
+            Ok(self.repo.list_by_status(status, page_size, offset).await?)
+        })
+    }
+
+    fn approve(&self, admin_id: u32, refund_id: u64, restock: bool) -> BoxFuture<'_, Result<Refund, ServiceError>> {
+        Box::pin(async move {
+            let refund = self.load(refund_id).await?;
+            if refund.is(RefundStatus::Pending) {
+                if !self.repo.approve(refund_id, admin_id, restock).await? {
+                    return Err(ServiceError::Conflict(format!("refund {} changed concurrently", refund_id)));
+                }
+            } else if refund.is(RefundStatus::Succeeded) {
+                self.complete_success(&refund).await?;
+                return self.load(refund_id).await;
+            } else if !refund.is(RefundStatus::Processing) {
+                return Err(ServiceError::Conflict(format!("refund {} is already {}", refund_id, refund.status)));
+            }
+
+            let approved = self.submit(refund_id).await?;
+            audit::record_change("refund", refund_id, Some(&refund), &approved);
+            Ok(approved)
+        })
+    }
+
+    fn refund_order<'a>(&'a self, order_id: u64, reason: &'a str) -> BoxFuture<'a, Result<Refund, ServiceError>> {
+        Box::pin(async move {
+            let order = self.load_order(order_id).await?;
+            let payment = self.paid_payment(order_id).await?;
+            let refunds = self.repo.list_by_order(order_id).await?;
+            let covered = refunded_total(&refunds, None) >= payment.amount;
+            let in_progress = refunds
+                .iter()
+                .find(|refund| refund.is(RefundStatus::Pending) || refund.is(RefundStatus::Processing));
+            match in_progress {
+                // 进行中的退款连同已退部分覆盖了整单，继续提交它
+                Some(refund) if covered => self.submit_as_system(refund).await,
+                // 只退一部分的先走完，结束后再退剩余部分；还在渠道处理中时返回冲突，由调用方稍后重试
+                Some(refund) => {
+                    let partial = self.submit_as_system(refund).await?;
+                    if partial.is(RefundStatus::Pending) || partial.is(RefundStatus::Processing) {
+                        return Err(ServiceError::Conflict(format!(
+                            "partial refund {} of order {} is still in progress",
+                            partial.refund_no, order_id
+                        )));
+                    }
+                    self.refund_order(order_id, reason).await
+                }
+                // 已全额退款：重复调用返回最后一笔退款
+                None if covered => refunds
+                    .into_iter()
+                    .filter(|refund| refund.is(RefundStatus::Succeeded))
+                    .max_by_key(|refund| refund.id)
+                    .ok_or_else(|| ServiceError::Conflict(format!("order {} is already fully refunded", order_id))),
+                None => {
+                    let amount = payment.amount - refunded_total(&refunds, None);
+                    let refund = self.open_refund(&order, None, amount, reason, Actor::system()).await?;
+                    self.submit_as_system(&refund).await
+                }
+            }
+        })
+    }
+
+    fn refund_cancelled<'a>(&'a self, order_id: u64, reason: &'a str) -> BoxFuture<'a, Result<Refund, ServiceError>> {
+        Box::pin(async move {
+            let order = self.load_order(order_id).await?;
+            if order.status() != Ok(OrderStatus::Cancelled) {
+                return Err(ServiceError::Conflict(format!("order {} is {}, not cancelled", order_id, order.status)));
+            }
+            let payment = self.paid_payment(order_id).await?;
+            let refund_no = cancelled_refund_no(&order.order_no);
+            let existing = match self.repo.find_by_refund_no(&refund_no).await? {
+                Some(refund) => refund,
+                None => {
+                    let new_refund = NewRefund {
+                        refund_no: refund_no.clone(),
+                        order_id,
+                        order_item_id: None,
+                        user_id: order.user_id,
+                        amount: payment.amount,
+                        reason: reason.to_string(),
+                        order_status: OrderStatus::Cancelled.as_str().to_string(),
+                    };
+                    match self.repo.create(&new_refund).await {
+                        Ok(id) => self.load(id).await?,
+                        // 并发的重复通知已经写入同号退款单
+                        Err(e) => self.repo.find_by_refund_no(&refund_no).await?.ok_or(ServiceError::Database(e))?,
+                    }
+                }
+            };
+            if existing.is(RefundStatus::Pending) || existing.is(RefundStatus::Processing) {
+                self.submit_as_system(&existing).await
+            } else {
+                Ok(existing)
+            }
+        })
+    }
+
+    fn create_approved<'a>(
+        &'a self,
+        admin_id: u32,
+        order_id: u64,
+        order_item_id: u64,
+        amount: i64,
+        reason: &'a str,
+    ) -> BoxFuture<'a, Result<Refund, ServiceError>> {
+        Box::pin(async move {
+            let order = self.load_order(order_id).await?;
+            let payment = self.paid_payment(order_id).await?;
+            let items = self.orders.find_items(order_id).await?;
+            let refunds = self.repo.list_by_order(order_id).await?;
+            let line = items
+                .iter()
+                .find(|item| item.id == order_item_id)
+                .ok_or_else(|| ServiceError::NotFound(format!("Order item {} not found", order_item_id)))?;
+            check_refundable(amount, payment.amount, Some(line), &refunds)?;
+
+            let refund = self.open_refund(&order, Some(order_item_id), amount, reason, Actor::admin(admin_id)).await?;
+            // 退回的件数由售后单入库，这里不按行回补，避免部分退货时整行回补
+            if !self.repo.approve(refund.id, admin_id, false).await? {
+                return Err(ServiceError::Conflict(format!("refund {} changed concurrently", refund.id)));
+            }
+            let approved = self.load(refund.id).await?;
+            audit::record_change("refund", approved.id, None, &approved);
+            Ok(approved)
+        })
+    }
+
+    fn reject<'a>(&'a self, admin_id: u32, refund_id: u64, reason: &'a str) -> BoxFuture<'a, Result<Refund, ServiceError>> {
+        Box::pin(async move {
+            let before = self.load(refund_id).await?;
+            if !self.repo.reject(refund_id, admin_id, reason).await? {
+                let refund = self.load(refund_id).await?;
+                return Err(ServiceError::Conflict(format!("refund {} is already {}", refund_id, refund.status)));
+            }
+            let refund = self.load(refund_id).await?;
+            audit::record_change("refund", refund_id, Some(&before), &refund);
+            self.restore_order(&refund, Actor::admin(admin_id)).await?;
+            Ok(refund)
+        })
+    }
+
+    fn handle_wechat_notify<'a>(&'a self, headers: &'a NotifyHeaders, body: &'a str) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let result = self.gateway.parse_refund_notify(headers, body).await?;
+            let refund = self
+                .repo
+                .find_by_refund_no(&result.out_refund_no)
+                .await?
+                .ok_or_else(|| ServiceError::NotFound(format!("Refund {} not found", result.out_refund_no)))?;
+            self.apply_result(&refund, &result).await
+        })
+    }
+}
+
+pub fn new_refund_service<R, O, P>(
+    repo: Arc<R>,
+    orders: Arc<O>,
+    payments: Arc<P>,
+    state: Arc<dyn OrderStateService>,
+    inventory: Arc<dyn InventoryService>,
+    gateway: Arc<dyn PayGateway>,
+) -> Arc<dyn RefundService>
+where
+    R: RefundRepo + 'static,
+    O: OrderRepo + 'static,
+    P: PaymentRepo + 'static,
+{
+    Arc::new(RefundServiceImpl::new(repo, orders, payments, state, inventory, gateway)) as Arc<dyn RefundService>
+}
+
+/// 退款单号：R + 申请时间（秒）+ 8 位随机数
+/// 已取消订单的到账退款单号：C + 订单号，同一订单只会有一笔
+fn cancelled_refund_no(order_no: &str) -> String {
+    format!("C{}", order_no)
+}
+
+fn generate_refund_no() -> String {
+    let suffix: u32 = rand::rng().random_range(0..100_000_000);
+    format!("R{}{:08}", Local::now().format("%Y%m%d%H%M%S"), suffix)
+}
+
+/// 占用额度的退款合计；`order_item_id` 为 `Some` 时只统计该行
+fn refunded_total(refunds: &[Refund], order_item_id: Option<u64>) -> i64 {
+    refunds
+        .iter()
+        .filter(|refund| refund.holds_amount())
+        .filter(|refund| order_item_id.is_none() || refund.order_item_id == order_item_id)
+        .map(|refund| refund.amount)
+        .sum()
+}
+
+/// 多次部分退款累计不能超过实付金额，按行退款还不能超过该行实付
+fn check_refundable(amount: i64, paid: i64, line: Option<&OrderItem>, refunds: &[Refund]) -> Result<(), ServiceError> {
+    if amount <= 0 {
+        return Err(ServiceError::BadRequest("refund amount must be positive".to_string()));
+    }
+    let order_left = paid - refunded_total(refunds, None);
+    if amount > order_left {
+        return Err(ServiceError::BadRequest(format!("refund amount exceeds refundable {}", order_left)));
+    }
+    if let Some(item) = line {
+        let line_left = item.payable_amount - refunded_total(refunds, Some(item.id));
+        if amount > line_left {
+            return Err(ServiceError::BadRequest(format!("refund amount exceeds refundable {} of item {}", line_left, item.id)));
+        }
+    }
+    Ok(())
+}
+
+/// 需要回补库存的 SKU：整单退完时回补全部，否则只回补已全额退款的那一行
+fn restock_skus(items: &[OrderItem], refunds: &[Refund], order_item_id: Option<u64>, fully_refunded: bool) -> Vec<u64> {
+    items
+        .iter()
+        .filter(|item| {
+            fully_refunded || (order_item_id == Some(item.id) && refunded_total(refunds, Some(item.id)) >= item.payable_amount)
+        })
+        .map(|item| item.sku_id)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(id: u64, payable_amount: i64) -> OrderItem {
+        OrderItem {
+            id,
+            order_id: 1,
+            sku_id: id * 10,
+            product_id: id,
+            product_name: String::new(),
+            sku_title: String::new(),
+            unit_price: payable_amount,
+            quantity: 1,
+            line_total: payable_amount,
+            discount_amount: 0,
+            payable_amount,
+        }
+    }
+
+    fn refund(order_item_id: Option<u64>, amount: i64, status: RefundStatus) -> Refund {
+        Refund {
+            id: 1,
+            refund_no: "R1".into(),
+            order_id: 1,
+            order_item_id,
+            user_id: 1,
+            amount,
+            reason: String::new(),
+            status: status.as_str().into(),
+            order_status: "paid".into(),
+            restock: false,
+            admin_id: None,
+            reject_reason: None,
+            channel_refund_id: None,
+            refunded_at: None,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn test_partial_refunds_never_exceed_paid() {
+        let line = item(1, 600);
+        let refunds = vec![
+            refund(Some(1), 400, RefundStatus::Succeeded),
+            refund(None, 300, RefundStatus::Processing),
+            refund(None, 900, RefundStatus::Rejected),
+        ];
+        assert!(check_refundable(300, 1000, None, &refunds).is_ok());
+        assert!(check_refundable(301, 1000, None, &refunds).is_err());
+        assert!(check_refundable(200, 1000, Some(&line), &refunds).is_ok());
+        assert!(check_refundable(201, 1000, Some(&line), &refunds).is_err());
+        assert!(check_refundable(0, 1000, None, &[]).is_err());
+    }
+
+    #[test]
+    fn test_restock_only_fully_refunded_lines() {
+        let items = vec![item(1, 600), item(2, 400)];
+        let partial = vec![refund(Some(1), 300, RefundStatus::Succeeded)];
+        assert!(restock_skus(&items, &partial, Some(1), false).is_empty());
+        let full_line = vec![refund(Some(1), 600, RefundStatus::Succeeded)];
+        assert_eq!(restock_skus(&items, &full_line, Some(1), false), vec![10]);
+        assert_eq!(restock_skus(&items, &[], None, true), vec![10, 20]);
+    }
+}
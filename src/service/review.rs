@@ -0,0 +1,206 @@
+use crate::domain::BoxFuture;
+use crate::domain::order::OrderRepo;
+use crate::domain::review::ReviewRepo;
+use crate::models::order::OrderStatus;
+use crate::models::review::{
+    FollowUpApply, NewReview, ProductReviews, Review, ReviewApply, ReviewFilter, ReviewStatus, ReviewSummary,
+};
+use crate::service::ServiceError;
+use std::sync::Arc;
+
+pub trait ReviewService: Send + Sync {
+    /// 已签收或已完成订单的每个订单行可以评价一次，提交后待审核
+    fn create<'a>(&'a self, user_id: u32, order_id: u64, apply: &'a ReviewApply) -> BoxFuture<'a, Result<Review, ServiceError>>;
+    /// 追评一次，追评后整条评价重新审核
+    fn follow_up<'a>(&'a self, user_id: u32, review_id: u64, apply: &'a FollowUpApply) -> BoxFuture<'a, Result<Review, ServiceError>>;
+    fn list_mine(&self, user_id: u32, page: u32, page_size: u32) -> BoxFuture<'_, Result<Vec<Review>, ServiceError>>;
+    /// 商品评价页，只展示审核通过的评价
+    fn product_reviews<'a>(
+        &'a self,
+        product_id: u64,
+        filter: &'a ReviewFilter,
+        page: u32,
+        page_size: u32,
+    ) -> BoxFuture<'a, Result<ProductReviews, ServiceError>>;
+    fn list_by_status<'a>(&'a self, status: &'a str, page: u32, page_size: u32) -> BoxFuture<'a, Result<Vec<Review>, ServiceError>>;
+    /// 审核通过或隐藏，并重新计算商品评分
+    fn moderate(&self, review_id: u64, status: ReviewStatus) -> BoxFuture<'_, Result<Review, ServiceError>>;
+    /// 商家回复，再次回复覆盖之前的内容
+    fn reply<'a>(&'a self, review_id: u64, reply: &'a str) -> BoxFuture<'a, Result<Review, ServiceError>>;
+}
+
+pub struct ReviewServiceImpl<R: ReviewRepo + 'static, O: OrderRepo + 'static> {
+    repo: Arc<R>,
+    orders: Arc<O>,
+}
+
+impl<R: ReviewRepo + 'static, O: OrderRepo + 'static> ReviewServiceImpl<R, O> {
+    pub fn new(repo: Arc<R>, orders: Arc<O>) -> Self {
+        Self { repo, orders }
+    }
+
+    async fn load(&self, review_id: u64) -> Result<Review, ServiceError> {
+        self.repo
+            .find(review_id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Review with ID {} not found", review_id)))
+    }
+}
+
+impl<R: ReviewRepo + 'static, O: OrderRepo + 'static> ReviewService for ReviewServiceImpl<R, O> {
+    fn create<'a>(&'a self, user_id: u32, order_id: u64, apply: &'a ReviewApply) -> BoxFuture<'a, Result<Review, ServiceError>> {
+        Box::pin(async move {
+            apply.validate().map_err(ServiceError::BadRequest)?;
+            let order = match self.orders.find_by_id(order_id).await? {
+                Some(order) if order.user_id == user_id => order,
+                _ => return Err(ServiceError::NotFound(format!("Order with ID {} not found", order_id))),
+            };
+            let status = order.status().map_err(ServiceError::Conflict)?;
+            if !matches!(status, OrderStatus::Delivered | OrderStatus::Completed) {
+                return Err(ServiceError::Conflict(format!("order {} is {}, only received orders can be reviewed", order_id, status)));
+            }
+            let item = self
+                .orders
+                .find_items(order_id)
+                .await?
+                .into_iter()
+                .find(|item| item.id == apply.order_item_id)
+                .ok_or_else(|| ServiceError::NotFound(format!("Order item {} not found", apply.order_item_id)))?;
+            if self.repo.find_by_item(item.id).await?.is_some() {
+                return Err(ServiceError::Conflict(format!("item {} has already been reviewed", item.id)));
+            }
+
+            let new_review = NewReview {
+                order_id,
+                order_item_id: item.id,
+                user_id,
+                product_id: item.product_id,
+                sku_id: item.sku_id,
+                sku_title: item.sku_title,
+                rating: apply.rating,
+                content: apply.content.trim().to_string(),
+                photos: apply.photos.clone(),
+            };
+            let id = self.repo.create(&new_review).await?;
+            self.load(id).await
+        })
+    }
+
+    fn follow_up<'a>(&'a self, user_id: u32, review_id: u64, apply: &'a FollowUpApply) -> BoxFuture<'a, Result<Review, ServiceError>> {
+        Box::pin(async move {
+            apply.validate().map_err(ServiceError::BadRequest)?;
+            let review = match self.repo.find(review_id).await? {
+                Some(review) if review.user_id == user_id => review,
+                _ => return Err(ServiceError::NotFound(format!("Review with ID {} not found", review_id))),
+            };
+            if !self.repo.follow_up(review_id, apply.content.trim(), &apply.photos).await? {
+                return Err(ServiceError::Conflict(format!("review {} has already been followed up", review_id)));
+            }
+            // 原评价已计入评分时，重新审核期间先从汇总中扣除
+            if review.status == ReviewStatus::Approved.as_str() {
+                self.repo.refresh_stats(review.product_id).await?;
+            }
+            self.load(review_id).await
+        })
+    }
+
+    fn list_mine(&self, user_id: u32, page: u32, page_size: u32) -> BoxFuture<'_, Result<Vec<Review>, ServiceError>> {
+        Box::pin(async move {
+            let page_size = page_size.clamp(1, 50);
+            let offset = page.saturating_sub(1) * page_size;
+            Ok(self.repo.list_by_user(user_id, page_size, offset).await?)
+        })
+    }
+
+    fn product_reviews<'a>(
+        &'a self,
+        product_id: u64,
+        filter: &'a ReviewFilter,
+        page: u32,
+        page_size: u32,
+    ) -> BoxFuture<'a, Result<ProductReviews, ServiceError>> {
+        Box::pin(async move {
+            if filter.rating.is_some_and(|rating| !(1..=5).contains(&rating)) {
+                return Err(ServiceError::BadRequest("rating must be between 1 and 5".to_string()));
+            }
+            let stats = self
+                .repo
+                .stats(product_id)
+                .await?
+                .ok_or_else(|| ServiceError::NotFound(format!("Product with ID {} not found", product_id)))?;
+            let rating_counts = self.repo.rating_counts(product_id).await?.into_iter().collect();
+
+            let page_size = page_size.clamp(1, 50);
+            let offset = page.saturating_sub(1) * page_size;
+            let reviews = self.repo.list_for_product(product_id, filter, page_size, offset).await?;
+            Ok(ProductReviews { summary: ReviewSummary::new(&stats, rating_counts), reviews })
+        })
+    }
+
+    fn list_by_status<'a>(&'a self, status: &'a str, page: u32, page_size: u32) -> BoxFuture<'a, Result<Vec<Review>, ServiceError>> {
+        Box::pin(async move {
+            let page_size = page_size.clamp(1, 100);
+            let offset = page.saturating_sub(1) * page_size;
+            Ok(self.repo.list_by_status(status, page_size, offset).await?)
+        })
+    }
+
+    fn moderate(&self, review_id: u64, status: ReviewStatus) -> BoxFuture<'_, Result<Review, ServiceError>> {
+        Box::pin(async move {
+            let review = self.load(review_id).await?;
+            self.repo.set_status(review_id, status).await?;
+            self.repo.refresh_stats(review.product_id).await?;
+            self.load(review_id).await
+        })
+    }
+
+    fn reply<'a>(&'a self, review_id: u64, reply: &'a str) -> BoxFuture<'a, Result<Review, ServiceError>> {
+        Box::pin(async move {
+            let reply = reply.trim();
+            if reply.is_empty() || reply.chars().count() > 500 {
+                return Err(ServiceError::BadRequest("reply must be 1 to 500 characters".to_string()));
+            }
+            self.load(review_id).await?;
+            self.repo.reply(review_id, reply).await?;
+            self.load(review_id).await
+        })
+    }
+}
+
+pub fn new_review_service<R: ReviewRepo + 'static, O: OrderRepo + 'static>(repo: Arc<R>, orders: Arc<O>) -> Arc<dyn ReviewService> {
+    Arc::new(ReviewServiceImpl::new(repo, orders)) as Arc<dyn ReviewService>
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::review::ReviewStats;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_summary_average_rounds_to_one_decimal() {
+        let stats = ReviewStats { review_count: 3, rating_sum: 13, photo_review_count: 1 };
+        let summary = ReviewSummary::new(&stats, BTreeMap::from([(4, 2), (5, 1)]));
+        assert_eq!(summary.average_rating, 4.3);
+        assert_eq!(ReviewSummary::new(&ReviewStats::default(), BTreeMap::new()).average_rating, 0.0);
+    }
+
+    #[test]
+    fn test_review_validation() {
+        let mut apply = ReviewApply {
+            order_item_id: 1,
+            rating: 5,
+            content: "很好".to_string(),
+            photos: vec!["https://cdn.example.com/1.jpg".to_string()],
+        };
+        assert!(apply.validate().is_ok());
+        apply.rating = 0;
+        assert!(apply.validate().is_err());
+        apply.rating = 4;
+        apply.photos = vec!["http://cdn.example.com/1.jpg".to_string()];
+        assert!(apply.validate().is_err());
+
+        let follow_up = FollowUpApply { content: "  ".to_string(), photos: Vec::new() };
+        assert!(follow_up.validate().is_err());
+    }
+}
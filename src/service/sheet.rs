@@ -0,0 +1,201 @@
+//! 读取上传的 CSV / XLSX 表格，只取第一个工作表的文本内容
+
+use crate::models::export::ExportFormat;
+use std::io::{Cursor, Read};
+
+/// 解压后单个 XML 文件的大小上限，防止压缩炸弹
+const MAX_XML_BYTES: u64 = 256 * 1024 * 1024;
+
+/// 表格中的一行：行号（从 1 开始）与各列文本，空行不返回
+pub type SheetRow = (u64, Vec<String>);
+
+pub fn read_rows(format: ExportFormat, bytes: &[u8]) -> Result<Vec<SheetRow>, String> {
+    match format {
+        ExportFormat::Csv => read_csv(bytes),
+        ExportFormat::Xlsx => read_xlsx(bytes),
+    }
+}
+
+fn read_csv(bytes: &[u8]) -> Result<Vec<SheetRow>, String> {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    let mut reader = csv::ReaderBuilder::new().has_headers(false).flexible(true).from_reader(bytes);
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| format!("invalid csv: {}", e))?;
+        let line = record.position().map_or(0, |position| position.line());
+        let cells: Vec<String> = record.iter().map(|cell| cell.trim().to_string()).collect();
+        if cells.iter().any(|cell| !cell.is_empty()) {
+            rows.push((line, cells));
+        }
+    }
+    Ok(rows)
+}
+
+fn read_xlsx(bytes: &[u8]) -> Result<Vec<SheetRow>, String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).map_err(|e| format!("invalid xlsx: {}", e))?;
+    let mut read_entry = |name: &str| -> Result<Option<String>, String> {
+        let entry = match archive.by_name(name) {
+            Ok(entry) => entry,
+            Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+            Err(e) => return Err(format!("invalid xlsx: {}", e)),
+        };
+        if entry.size() > MAX_XML_BYTES {
+            return Err(format!("{} is too large", name));
+        }
+        let mut xml = String::new();
+        entry.take(MAX_XML_BYTES).read_to_string(&mut xml).map_err(|e| format!("invalid xlsx: {}", e))?;
+        Ok(Some(xml))
+    };
+
+    let shared: Vec<String> = read_entry("xl/sharedStrings.xml")?
+        .map(|xml| elements(&xml, "si").into_iter().map(|(_, si)| rich_text(si)).collect())
+        .unwrap_or_default();
+    let sheet = read_entry("xl/worksheets/sheet1.xml")?.ok_or("xlsx has no worksheet")?;
+
+    let mut rows = Vec::new();
+    for (index, (attrs, content)) in elements(&sheet, "row").into_iter().enumerate() {
+        let line = attr(attrs, "r").and_then(|r| r.parse().ok()).unwrap_or(index as u64 + 1);
+        let mut cells: Vec<String> = Vec::new();
+        for (attrs, content) in elements(content, "c") {
+            let col = match attr(attrs, "r") {
+                Some(r) => column_index(r).ok_or_else(|| format!("invalid cell reference {}", r))?,
+                None => cells.len(),
+            };
+            let value = elements(content, "v").first().map(|(_, v)| unescape(v));
+            let text = match attr(attrs, "t") {
+                Some("s") => {
+                    let index: usize = value.as_deref().and_then(|v| v.parse().ok()).ok_or("invalid shared string index")?;
+                    shared.get(index).cloned().ok_or("shared string index out of range")?
+                }
+                Some("inlineStr") => rich_text(content),
+                _ => value.unwrap_or_default(),
+            };
+            if cells.len() <= col {
+                cells.resize(col + 1, String::new());
+            }
+            cells[col] = text.trim().to_string();
+        }
+        if cells.iter().any(|cell| !cell.is_empty()) {
+            rows.push((line, cells));
+        }
+    }
+    Ok(rows)
+}
+
+/// 依次取出 `<name ...>content</name>` 或 `<name .../>` 的属性串与内容，不支持同名元素嵌套
+fn elements<'a>(xml: &'a str, name: &str) -> Vec<(&'a str, &'a str)> {
+    let open = format!("<{}", name);
+    let close = format!("</{}>", name);
+    let mut found = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        let after = &rest[start + open.len()..];
+        // 跳过名字只是前缀的元素，如找 <t> 时遇到 <tableParts>
+        if !after.starts_with([' ', '>', '/', '\t', '\r', '\n']) {
+            rest = after;
+            continue;
+        }
+        let Some(end) = after.find('>') else { break };
+        let attrs = &after[..end];
+        let body = &after[end + 1..];
+        if let Some(attrs) = attrs.strip_suffix('/') {
+            found.push((attrs, ""));
+            rest = body;
+        } else if let Some(stop) = body.find(&close) {
+            found.push((attrs, &body[..stop]));
+            rest = &body[stop + close.len()..];
+        } else {
+            break;
+        }
+    }
+    found
+}
+
+fn attr<'a>(attrs: &'a str, name: &str) -> Option<&'a str> {
+    attrs
+        .split_whitespace()
+        .find_map(|part| part.strip_prefix(name)?.strip_prefix("=\"")?.strip_suffix('"'))
+}
+
+/// 拼接 `<si>` / `<is>` 中所有 `<t>` 的文本，忽略注音
+fn rich_text(xml: &str) -> String {
+    let xml = match xml.find("<rPh") {
+        Some(start) => &xml[..start],
+        None => xml,
+    };
+    elements(xml, "t").into_iter().map(|(_, text)| unescape(text)).collect()
+}
+
+/// "AB12" -> 27
+fn column_index(reference: &str) -> Option<usize> {
+    let letters: Vec<u8> = reference.bytes().take_while(u8::is_ascii_uppercase).collect();
+    if letters.is_empty() {
+        return None;
+    }
+    letters
+        .iter()
+        .try_fold(0usize, |index, letter| index.checked_mul(26)?.checked_add(usize::from(letter - b'A') + 1))
+        .map(|index| index - 1)
+}
+
+fn unescape(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        let entity = &rest[start..];
+        let Some(end) = entity.find(';') else { break };
+        let decoded = match &entity[1..end] {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            code => code
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| code.strip_prefix('#').map(str::parse))
+                .and_then(Result::ok)
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(ch) => {
+                out.push(ch);
+                rest = &entity[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &entity[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_xlsxwriter::Workbook;
+
+    #[test]
+    fn test_read_xlsx_rows() {
+        let mut workbook = Workbook::new();
+        let sheet = workbook.add_worksheet();
+        sheet.write_string(0, 0, "商品编码").unwrap();
+        sheet.write_string(0, 2, "价格(元)").unwrap();
+        sheet.write_string(2, 0, "P<1>&A").unwrap();
+        sheet.write_number(2, 2, 12.5).unwrap();
+        let bytes = workbook.save_to_buffer().unwrap();
+
+        let rows = read_rows(ExportFormat::Xlsx, &bytes).unwrap();
+        assert_eq!(rows, vec![
+            (1, vec!["商品编码".to_string(), String::new(), "价格(元)".to_string()]),
+            (3, vec!["P<1>&A".to_string(), String::new(), "12.5".to_string()]),
+        ]);
+        assert_eq!(column_index("AB12"), Some(27));
+    }
+}
@@ -0,0 +1,172 @@
+use crate::domain::BoxFuture;
+use crate::domain::shipment::{ShipmentRepo, TrackingCache, TrackingProvider};
+use crate::models::order::{Actor, Order, OrderStatus};
+use crate::models::shipment::{Shipment, TrackingInfo};
+use crate::service::ServiceError;
+use crate::service::order::OrderService;
+use crate::service::order_state::OrderStateService;
+use chrono::Local;
+use std::sync::Arc;
+
+pub trait ShipmentService: Send + Sync {
+    /// 后台发货：订单置为已发货并记录快递公司和运单号；已发货的订单再次调用时更新运单号
+    fn ship<'a>(&'a self, admin_id: u32, order_id: u64, carrier: &'a str, tracking_no: &'a str) -> BoxFuture<'a, Result<Shipment, ServiceError>>;
+    /// 用户确认收货，订单由已发货置为已签收，之后可以申请售后
+    fn confirm_receipt(&self, user_id: u32, order_id: u64) -> BoxFuture<'_, Result<Order, ServiceError>>;
+    /// 用户查看自己订单的物流轨迹，结果按 `[logistics] cache_ttl_secs` 缓存
+    fn tracking(&self, user_id: u32, order_id: u64) -> BoxFuture<'_, Result<TrackingInfo, ServiceError>>;
+}
+
+/// 先查缓存，未命中再调用服务商并写回缓存；缓存不可用时直接查询
+pub struct TrackingLookup {
+    provider: Arc<dyn TrackingProvider>,
+    cache: Arc<dyn TrackingCache>,
+    ttl_secs: u64,
+}
+
+impl TrackingLookup {
+    pub fn new(provider: Arc<dyn TrackingProvider>, cache: Arc<dyn TrackingCache>, ttl_secs: u64) -> Self {
+        Self { provider, cache, ttl_secs }
+    }
+
+    pub async fn lookup(&self, carrier: &str, tracking_no: &str, phone: &str) -> Result<TrackingInfo, ServiceError> {
+        let key = format!("tracking:{}:{}", carrier, tracking_no);
+        match self.cache.get(&key).await {
+            Ok(Some(info)) => return Ok(info),
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Failed to read tracking cache {}: {:?}", key, e),
+        }
+        let info = self.provider.query(carrier, tracking_no, phone).await?;
+        if let Err(e) = self.cache.set(&key, &info, self.ttl_secs).await {
+            tracing::warn!("Failed to write tracking cache {}: {:?}", key, e);
+        }
+        Ok(info)
+    }
+}
+
+pub struct ShipmentServiceImpl<S: ShipmentRepo + 'static> {
+    repo: Arc<S>,
+    orders: Arc<dyn OrderService>,
+    state: Arc<dyn OrderStateService>,
+    lookup: TrackingLookup,
+}
+
+impl<S: ShipmentRepo + 'static> ShipmentServiceImpl<S> {
+    pub fn new(repo: Arc<S>, orders: Arc<dyn OrderService>, state: Arc<dyn OrderStateService>, lookup: TrackingLookup) -> Self {
+        Self { repo, orders, state, lookup }
+    }
+
+    async fn load(&self, order_id: u64) -> Result<Shipment, ServiceError> {
+        self.repo
+            .find_by_order(order_id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Order {} has not been shipped", order_id)))
+    }
+}
+
+impl<S: ShipmentRepo + 'static> ShipmentService for ShipmentServiceImpl<S> {
+    fn ship<'a>(&'a self, admin_id: u32, order_id: u64, carrier: &'a str, tracking_no: &'a str) -> BoxFuture<'a, Result<Shipment, ServiceError>> {
+        Box::pin(async move {
+            let carrier = carrier.trim();
+            let tracking_no = tracking_no.trim();
+            if carrier.is_empty() || tracking_no.is_empty() {
+                return Err(ServiceError::BadRequest("carrier and tracking_no are required".to_string()));
+            }
+            // 状态机校验只有已支付的订单能发货，重复发货返回 AlreadyInState
+            self.state
+                .transition(order_id, OrderStatus::Shipped, Actor::admin(admin_id), &format!("{} {}", carrier, tracking_no))
+                .await?;
+            self.repo.upsert(order_id, carrier, tracking_no, admin_id, Local::now()).await?;
+            self.load(order_id).await
+        })
+    }
+
+    fn confirm_receipt(&self, user_id: u32, order_id: u64) -> BoxFuture<'_, Result<Order, ServiceError>> {
+        Box::pin(async move {
+            self.orders.get(user_id, order_id).await?;
+            let outcome = self
+                .state
+                .transition(order_id, OrderStatus::Delivered, Actor::user(user_id), "receipt confirmed")
+                .await?;
+            Ok(outcome.into_order())
+        })
+    }
+
+    fn tracking(&self, user_id: u32, order_id: u64) -> BoxFuture<'_, Result<TrackingInfo, ServiceError>> {
+        Box::pin(async move {
+            let detail = self.orders.get(user_id, order_id).await?;
+            let shipment = self.load(order_id).await?;
+            self.lookup
+                .lookup(&shipment.carrier, &shipment.tracking_no, &detail.order.receiver_phone)
+                .await
+        })
+    }
+}
+
+pub fn new_shipment_service<S: ShipmentRepo + 'static>(
+    repo: Arc<S>,
+    orders: Arc<dyn OrderService>,
+    state: Arc<dyn OrderStateService>,
+    lookup: TrackingLookup,
+) -> Arc<dyn ShipmentService> {
+    Arc::new(ShipmentServiceImpl::new(repo, orders, state, lookup)) as Arc<dyn ShipmentService>
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::payment::GatewayError;
+    use crate::models::shipment::TrackingState;
+    use crate::repos::tracking::FakeTrackingProvider;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct CountingProvider {
+        inner: FakeTrackingProvider,
+        calls: AtomicUsize,
+    }
+
+    impl TrackingProvider for CountingProvider {
+        fn query<'a>(&'a self, carrier: &'a str, tracking_no: &'a str, phone: &'a str) -> BoxFuture<'a, Result<TrackingInfo, GatewayError>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.inner.query(carrier, tracking_no, phone)
+        }
+    }
+
+    #[derive(Default)]
+    struct MemoryCache {
+        entries: Mutex<HashMap<String, TrackingInfo>>,
+    }
+
+    impl TrackingCache for MemoryCache {
+        fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<TrackingInfo>, fred::error::Error>> {
+            Box::pin(async move { Ok(self.entries.lock().unwrap().get(key).cloned()) })
+        }
+
+        fn set<'a>(&'a self, key: &'a str, info: &'a TrackingInfo, _ttl_secs: u64) -> BoxFuture<'a, Result<(), fred::error::Error>> {
+            Box::pin(async move {
+                self.entries.lock().unwrap().insert(key.to_string(), info.clone());
+                Ok(())
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lookup_caches_provider_result() {
+        let provider = Arc::new(CountingProvider::default());
+        let cache = Arc::new(MemoryCache::default());
+        let lookup = TrackingLookup::new(provider.clone(), cache.clone(), 60);
+
+        let first = lookup.lookup("yuantong", "YT001", "13800000000").await.unwrap();
+        let second = lookup.lookup("yuantong", "YT001", "13800000000").await.unwrap();
+        assert_eq!(first.state, TrackingState::InTransit);
+        assert_eq!(second.events.len(), first.events.len());
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 1);
+        assert!(cache.entries.lock().unwrap().contains_key("tracking:yuantong:YT001"));
+
+        lookup.lookup("yuantong", "YT002", "13800000000").await.unwrap();
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 2);
+    }
+}
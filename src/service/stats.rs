@@ -0,0 +1,208 @@
+use crate::domain::BoxFuture;
+use crate::domain::jobs::JobQueue;
+use crate::domain::stats::StatsRepo;
+use crate::models::job::Job;
+use crate::models::stats::{DailyStats, Granularity, ProductStats, StatsOverview, StatsPoint};
+use crate::service::ServiceError;
+use crate::service::jobs::JobHandler;
+use chrono::{DateTime, Days, Local, NaiveDate, TimeZone, Timelike};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use wx_shop::StatsSettings;
+
+/// 每日经营数据汇总任务
+pub const STATS_ROLLUP_JOB: &str = "stats_rollup";
+
+pub trait StatsService: Send + Sync {
+    /// 重新汇总某一天，覆盖之前的结果
+    fn rollup(&self, stat_date: NaiveDate) -> BoxFuture<'_, Result<DailyStats, ServiceError>>;
+    /// 逐日重新汇总 [from, to]，返回汇总的天数
+    fn rollup_range(&self, from: NaiveDate, to: NaiveDate) -> BoxFuture<'_, Result<usize, ServiceError>>;
+    /// [from, to] 的汇总与按周期分组的趋势，只读取预聚合的日数据
+    fn overview(&self, from: NaiveDate, to: NaiveDate, group_by: Granularity) -> BoxFuture<'_, Result<StatsOverview, ServiceError>>;
+    fn top_products(&self, from: NaiveDate, to: NaiveDate, limit: u32) -> BoxFuture<'_, Result<Vec<ProductStats>, ServiceError>>;
+}
+
+pub struct StatsServiceImpl<R: StatsRepo + 'static> {
+    repo: Arc<R>,
+    settings: StatsSettings,
+}
+
+impl<R: StatsRepo + 'static> StatsServiceImpl<R> {
+    pub fn new(repo: Arc<R>, settings: StatsSettings) -> Self {
+        Self { repo, settings }
+    }
+
+    fn check_range(&self, from: NaiveDate, to: NaiveDate) -> Result<(), ServiceError> {
+        if from > to {
+            return Err(ServiceError::BadRequest(format!("from {} is after to {}", from, to)));
+        }
+        let days = (to - from).num_days() + 1;
+        if days > i64::from(self.settings.max_range_days) {
+            return Err(ServiceError::BadRequest(format!("date range is limited to {} days", self.settings.max_range_days)));
+        }
+        Ok(())
+    }
+}
+
+impl<R: StatsRepo + 'static> StatsService for StatsServiceImpl<R> {
+    fn rollup(&self, stat_date: NaiveDate) -> BoxFuture<'_, Result<DailyStats, ServiceError>> {
+        Box::pin(async move {
+            let (start, end) = day_bounds(stat_date)
+                .ok_or_else(|| ServiceError::BadRequest(format!("invalid stat date {}", stat_date)))?;
+            Ok(self.repo.rollup(stat_date, start, end).await?)
+        })
+    }
+
+    fn rollup_range(&self, from: NaiveDate, to: NaiveDate) -> BoxFuture<'_, Result<usize, ServiceError>> {
+        Box::pin(async move {
+            self.check_range(from, to)?;
+            let mut days = 0;
+            for stat_date in from.iter_days().take_while(|date| *date <= to) {
+                self.rollup(stat_date).await?;
+                days += 1;
+            }
+            Ok(days)
+        })
+    }
+
+    fn overview(&self, from: NaiveDate, to: NaiveDate, group_by: Granularity) -> BoxFuture<'_, Result<StatsOverview, ServiceError>> {
+        Box::pin(async move {
+            self.check_range(from, to)?;
+            let daily = self.repo.list_daily(from, to).await?;
+            let payers: HashMap<NaiveDate, i64> = self.repo.payers_by_period(from, to, group_by).await?.into_iter().collect();
+            let series = build_series(&daily, group_by, &payers);
+
+            let mut totals = StatsPoint::new(from);
+            daily.iter().for_each(|day| totals.add(day));
+            totals.paying_users = self.repo.count_payers(from, to).await?.max(0) as u64;
+            totals.finish();
+            Ok(StatsOverview { from, to, group_by, totals, series })
+        })
+    }
+
+    fn top_products(&self, from: NaiveDate, to: NaiveDate, limit: u32) -> BoxFuture<'_, Result<Vec<ProductStats>, ServiceError>> {
+        Box::pin(async move {
+            self.check_range(from, to)?;
+            Ok(self.repo.top_products(from, to, limit.clamp(1, 100)).await?)
+        })
+    }
+}
+
+/// 把日数据按周期累加，支付用户数取按周期去重后的值；没有数据的周期不输出
+fn build_series(daily: &[DailyStats], group_by: Granularity, payers: &HashMap<NaiveDate, i64>) -> Vec<StatsPoint> {
+    let mut periods: BTreeMap<NaiveDate, StatsPoint> = BTreeMap::new();
+    for day in daily {
+        let period = group_by.period_start(day.stat_date);
+        periods.entry(period).or_insert_with(|| StatsPoint::new(period)).add(day);
+    }
+    periods
+        .into_values()
+        .map(|mut point| {
+            point.paying_users = payers.get(&point.period).copied().unwrap_or(0).max(0) as u64;
+            point.finish();
+            point
+        })
+        .collect()
+}
+
+/// 统计日在本地时区的起止时间 [start, end)
+fn day_bounds(stat_date: NaiveDate) -> Option<(DateTime<Local>, DateTime<Local>)> {
+    let start = Local.from_local_datetime(&stat_date.and_hms_opt(0, 0, 0)?).earliest()?;
+    let end = Local.from_local_datetime(&stat_date.checked_add_days(Days::new(1))?.and_hms_opt(0, 0, 0)?).earliest()?;
+    Some((start, end))
+}
+
+/// 每日汇总任务：先排期下一天，再汇总统计日及之前 `lookback_days` 天
+pub struct StatsRollupJob {
+    service: Arc<dyn StatsService>,
+    jobs: Arc<dyn JobQueue>,
+    settings: StatsSettings,
+}
+
+impl StatsRollupJob {
+    pub fn new(service: Arc<dyn StatsService>, jobs: Arc<dyn JobQueue>, settings: StatsSettings) -> Arc<Self> {
+        Arc::new(Self { service, jobs, settings })
+    }
+}
+
+impl JobHandler for StatsRollupJob {
+    fn kind(&self) -> &'static str {
+        STATS_ROLLUP_JOB
+    }
+
+    fn handle<'a>(&'a self, job: &'a Job) -> BoxFuture<'a, Result<(), ServiceError>> {
+        Box::pin(async move {
+            let stat_date = job.payload["stat_date"]
+                .as_str()
+                .and_then(|date| date.parse::<NaiveDate>().ok())
+                .ok_or_else(|| ServiceError::BadRequest(format!("invalid payload of job {}", job.id)))?;
+            if let Some(next) = stat_date.checked_add_days(Days::new(1)) {
+                schedule_stats_rollup(self.jobs.as_ref(), next, self.settings.run_at_hour).await?;
+            }
+            let from = stat_date
+                .checked_sub_days(Days::new(u64::from(self.settings.lookback_days)))
+                .unwrap_or(stat_date);
+            let days = self.service.rollup_range(from, stat_date).await?;
+            tracing::info!("Stats rollup of {} refreshed {} days", stat_date, days);
+            Ok(())
+        })
+    }
+}
+
+/// 在统计日次日 `run_at_hour` 点执行；同一统计日重复排期只保留一个任务
+pub async fn schedule_stats_rollup(jobs: &dyn JobQueue, stat_date: NaiveDate, run_at_hour: u32) -> Result<(), ServiceError> {
+    let run_at = stat_date
+        .checked_add_days(Days::new(1))
+        .and_then(|day| day.and_hms_opt(run_at_hour.min(23), 0, 0))
+        .and_then(|time| Local.from_local_datetime(&time).earliest())
+        .ok_or_else(|| ServiceError::BadRequest(format!("invalid stat date {}", stat_date)))?;
+    let job = Job::new(STATS_ROLLUP_JOB, &stat_date.to_string(), serde_json::json!({ "stat_date": stat_date }));
+    Ok(jobs.enqueue(&job, run_at.timestamp_millis()).await?)
+}
+
+/// 启动时应排期的统计日：今天还没到执行时间则是昨天，否则是今天
+pub fn next_stats_date(now: DateTime<Local>, run_at_hour: u32) -> NaiveDate {
+    let today = now.date_naive();
+    if now.hour() < run_at_hour {
+        today.pred_opt().unwrap_or(today)
+    } else {
+        today
+    }
+}
+
+pub fn new_stats_service<R: StatsRepo + 'static>(repo: Arc<R>, settings: StatsSettings) -> Arc<dyn StatsService> {
+    Arc::new(StatsServiceImpl::new(repo, settings)) as Arc<dyn StatsService>
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(date: &str, paid_order_count: u32, gmv: i64, refund_amount: i64) -> DailyStats {
+        DailyStats {
+            stat_date: date.parse().unwrap(),
+            order_count: paid_order_count + 1,
+            paid_order_count,
+            gmv,
+            paying_users: paid_order_count,
+            refund_count: u32::from(refund_amount > 0),
+            refund_amount,
+        }
+    }
+
+    #[test]
+    fn test_series_groups_days_by_week() {
+        let monday: NaiveDate = "2024-01-08".parse().unwrap();
+        let daily = [day("2024-01-07", 1, 1000, 0), day("2024-01-08", 2, 3000, 300), day("2024-01-14", 1, 3000, 0)];
+        let payers = HashMap::from([(monday, 2)]);
+        let series = build_series(&daily, Granularity::Week, &payers);
+
+        assert_eq!(series.iter().map(|point| point.period.to_string()).collect::<Vec<_>>(), vec!["2024-01-01", "2024-01-08"]);
+        let week = &series[1];
+        assert_eq!((week.order_count, week.paid_order_count, week.gmv), (5, 3, 6000));
+        assert_eq!((week.paying_users, week.avg_order_value, week.refund_rate), (2, 2000, 0.05));
+        assert_eq!(series[0].paying_users, 0);
+        assert_eq!(Granularity::Month.period_start("2024-02-29".parse().unwrap()).to_string(), "2024-02-01");
+    }
+}